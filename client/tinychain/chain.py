@@ -2,8 +2,8 @@
 
 from tinychain import ref
 from tinychain.reflect import is_ref
-from tinychain.state import State
-from tinychain.util import uri, URI
+from tinychain.state import Map, State, Stream
+from tinychain.util import form_of, to_json, uri, URI
 
 
 class Chain(State):
@@ -26,6 +26,27 @@ class Chain(State):
         else:
             raise ValueError(f"Chain subject must be a State, not {form}")
 
+    @classmethod
+    def import_archive(cls, archive):
+        """Decode a :class:`Chain` from an `archive` previously returned by :meth:`export_archive`."""
+
+        return cls(ref.Post(uri(Chain) + "/import", Map(archive=archive)))
+
+    def export_archive(self):
+        """Return a portable archive of the history and subject of this :class:`Chain`."""
+
+        return self._get("export", rtype=Stream)
+
+    def refresh_index(self, index, since=None):
+        """
+        Bring a read-only `index` of this `Chain`'s :class:`Table`, taken as of the transaction `since`, up to date.
+
+        If the `Table` tracks its changes, only those made after `since` are applied; otherwise the index is rebuilt.
+        """
+
+        from .collection.table import Table
+        return self._post("refresh", Map(index=index, since=since), Table)
+
     def set(self, value):
         """Update the value of this `Chain`."""
 
@@ -54,10 +75,28 @@ class Chain(State):
 
 
 class Block(Chain):
-    """A :class:`Chain` which keeps track of the entire update history of its subject."""
+    """
+    A :class:`Chain` which keeps track of the entire update history of its subject.
+
+    The blocks of a new `Block` chain are linked by the given `hash_algo`, "sha256" (the default) or "sha512".
+    """
 
     __uri__ = uri(Chain) + "/block"
 
+    def __new__(cls, form, hash_algo=None):
+        return Chain.__new__(cls, form)
+
+    def __init__(self, form, hash_algo=None):
+        self.__hash_algo__ = hash_algo
+        super().__init__(form)
+
+    def __json__(self):
+        if self.__hash_algo__ is None:
+            return Chain.__json__(self)
+
+        schema = {"schema": to_json(form_of(self)), "hash_algo": self.__hash_algo__}
+        return {str(uri(self)): [schema]}
+
 
 class Sync(Chain):
     """
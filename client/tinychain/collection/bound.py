@@ -53,3 +53,32 @@ class Range(object):
 
     def __json__(self):
         return to_json((self.start, self.end))
+
+
+class Matches(object):
+    """
+    A selection bound which matches a `String` column against a regular expression.
+
+    A regular expression can't be used to seek within an index, so every row in the range selected by
+    the preceding columns of the index will be checked. Combine this with bounds on those columns when possible.
+    """
+
+    def __init__(self, pattern):
+        self.pattern = pattern
+
+    def __json__(self):
+        return to_json(["matches", self.pattern])
+
+
+class OneOf(object):
+    """
+    A selection bound which matches any one of the given values, like `IN (...)` in SQL.
+
+    Each distinct value is looked up separately in the index which supports the selection.
+    """
+
+    def __init__(self, *values):
+        self.values = values
+
+    def __json__(self):
+        return to_json(["one_of", list(self.values)])
@@ -1,7 +1,8 @@
 from tinychain.ref import Post, Put
 from tinychain.reflect import is_ref
-from tinychain.state import Map, State
+from tinychain.state import Map, State, Stream
 from tinychain.util import uri
+from tinychain.value import Bool, String
 
 
 class Collection(State):
@@ -10,10 +11,14 @@ class Collection(State):
     __uri__ = uri(State) + "/collection"
 
     @classmethod
-    def copy_from(cls, schema, source):
+    def copy_from(cls, schema, source, on_error="abort"):
         """
         Copy a :class:`Collection` from a :class:`Stream` of values.
 
+        By default the copy is aborted at the first invalid value. If `on_error` is "skip", invalid values
+        are skipped instead, and the result is a :class:`Map` with the new `collection`, the number of values
+        `applied` and `skipped`, and a list of `errors` as `(index, message)` pairs.
+
         Example:
             .. highlight:: python
             .. code-block:: python
@@ -26,7 +31,10 @@ class Collection(State):
                 table = tc.table.copy_from(table_schema, btree.keys())
         """
 
-        return cls(Post(uri(cls) + "/copy_from", Map(schema=schema, source=source)))
+        if on_error == "abort":
+            return cls(Post(uri(cls) + "/copy_from", Map(schema=schema, source=source)))
+
+        return Map(Post(uri(cls) + "/copy_from", Map(schema=schema, source=source, on_error=on_error)))
 
     @classmethod
     def load(cls, schema, data):
@@ -48,3 +56,41 @@ class Collection(State):
             raise ValueError(f"cannot load data {data} (consider calling `Collection.copy_from` instead)")
 
         return cls(Put(cls, schema, data))
+
+    @classmethod
+    def import_archive(cls, archive):
+        """
+        Decode a :class:`Collection` from an `archive` previously returned by :meth:`export_archive`.
+
+        The schema and type of the new :class:`Collection` are read from the archive itself.
+        """
+
+        return cls(Post(uri(Collection) + "/import", Map(archive=archive)))
+
+    def export_archive(self):
+        """
+        Return a portable archive of the schema and contents of this :class:`Collection`.
+
+        The archive is a :class:`Stream` of chunks, beginning with a version header.
+        """
+
+        return self._get("export", rtype=Stream)
+
+    def digest(self):
+        """
+        Return a hex-encoded SHA-256 digest of the contents of this :class:`Collection`.
+
+        Two :class:`Collection` s with the same contents have the same digest, so comparing digests
+        is a cheap way to check whether two replicas have diverged.
+        """
+
+        return self._get("digest", rtype=String)
+
+    def equals(self, other):
+        """
+        Return `True` if this :class:`Collection` has the same schema and contents as `other`.
+
+        Floating-point :class:`Tensor`s are compared within a small tolerance.
+        """
+
+        return self._post("equals", Map(other=other), Bool)
@@ -20,13 +20,28 @@ class Column(object):
 
 
 class BTree(object):
-    """A :class:`BTree` schema which comprises a tuple of :class:`Column` s."""
+    """
+    A :class:`BTree` schema which comprises a tuple of :class:`Column` s.
+
+    An optional `order` sets the maximum number of keys per node (`2 * order - 1`).
+    The order of a `BTree` affects its performance, but never the results of a query.
+
+    If `compress` is set, string keys which share a prefix are front-coded on disk.
+    This trades CPU time for storage space, and also never affects the results of a query.
+    """
 
-    def __init__(self, *columns):
+    def __init__(self, *columns, order=None, compress=False):
         self.columns = columns
+        self.order = order
+        self.compress = compress
 
     def __json__(self):
-        return to_json(self.columns)
+        if self.compress:
+            return to_json([self.columns, self.order, True])
+        elif self.order is None:
+            return to_json(self.columns)
+        else:
+            return to_json([self.columns, self.order])
 
 
 class Graph(object):
@@ -65,14 +80,75 @@ class Table(object):
         self.key = key
         self.values = values
         self.indices = []
+        self.foreign_keys = []
+        self.ttl = None
+        self.tracks_changes = False
+        self.computed = []
 
     def __json__(self):
-        return to_json([[self.key, self.values], Tuple(self.indices)])
+        if self.computed:
+            return to_json([
+                [self.key, self.values], Tuple(self.indices), Tuple(self.foreign_keys), self.ttl, self.tracks_changes,
+                Tuple(self.computed)])
+        elif self.tracks_changes:
+            return to_json([[self.key, self.values], Tuple(self.indices), Tuple(self.foreign_keys), self.ttl, True])
+        elif self.ttl is not None:
+            return to_json([[self.key, self.values], Tuple(self.indices), Tuple(self.foreign_keys), self.ttl])
+        elif self.foreign_keys:
+            return to_json([[self.key, self.values], Tuple(self.indices), Tuple(self.foreign_keys)])
+        else:
+            return to_json([[self.key, self.values], Tuple(self.indices)])
 
     def create_index(self, name, columns):
         self.indices.append((name, columns))
         return self
 
+    def create_computed_column(self, name, dtype, op):
+        """
+        Add a read-only column `name` of type `dtype` which is computed as the rows of a `Table` are read.
+
+        `op` is the path of a POST method, which is called with the values of the other columns of each row.
+        A computed column is never stored, so it can't be indexed, and a `Table` can't be sliced or ordered by it.
+        """
+
+        self.computed.append((name, dtype, str(op)))
+        return self
+
+    def create_foreign_key(self, column, table, foreign_column, on_delete="restrict"):
+        """
+        Require every value of `column` to be present in the primary key `foreign_column` of the `Table` at `table`.
+
+        The referenced `Table` must be in the same `Cluster`, and `foreign_column` must be its whole primary key.
+
+        `on_delete` may be "restrict" (reject deleting a referenced row) or "cascade" (delete the referencing rows).
+        If `column` doesn't lead the primary key or an index of this `Table`, an index on `column` is added.
+        """
+
+        self.foreign_keys.append((column, str(table), foreign_column, on_delete))
+        return self
+
+    def set_ttl(self, seconds):
+        """
+        Expire each row `seconds` after it's written, unless its "expires_at" column is set explicitly.
+
+        The values of this schema must include a `U64` column named "expires_at", which holds the expiration time
+        of each row in nanoseconds since the Unix epoch. Write `None` to this column to use the default TTL.
+        """
+
+        self.ttl = seconds
+        return self
+
+    def track_changes(self):
+        """
+        Journal each insert, update, and delete of a row of this `Table`.
+
+        The changes are streamed by `GET <table>/changes`, optionally since a given transaction ID,
+        and the changes through a given transaction ID are discarded by `DELETE <table>/changes`.
+        """
+
+        self.tracks_changes = True
+        return self
+
 
 class Tensor(object):
     """
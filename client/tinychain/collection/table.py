@@ -3,7 +3,7 @@ from tinychain.error import BadRequest
 from tinychain.ref import If
 from tinychain.state import Map, Tuple, Stream
 from tinychain.util import uri
-from tinychain.value import Bool, UInt, Nil
+from tinychain.value import Bool, UInt, Nil, Value
 
 from .collection import Collection
 from .bound import Range
@@ -19,6 +19,15 @@ class Table(Collection):
 
         return self._get("", key, rtype=Map)
 
+    def aggregate(self, column, func):
+        """
+        Compute a single aggregate of the given `column` over every row in this `Table`.
+
+        `func` is one of "avg", "count", "max", "min", or "sum". "avg" and "sum" require a numeric column.
+        """
+
+        return self._get("aggregate", (column, func), Value)
+
     def contains(self, key):
         """Return `True` if this `Table` contains the given key."""
 
@@ -33,6 +42,16 @@ class Table(Collection):
 
         return self._get("count", rtype=UInt)
 
+    def describe(self):
+        """
+        Summarize each column of this `Table`.
+
+        A numeric column is summarized by its "count", "min", "max", and "mean", and any other column
+        by its "count" and an approximate number of "distinct" values.
+        """
+
+        return self._get("describe", rtype=Map)
+
     @delete_method
     def delete(self, txn, **where):
         """
@@ -93,6 +112,21 @@ class Table(Collection):
 
         return self._get("order", (columns, reverse), Table)
 
+    def probe(self, where):
+        """
+        Return `True` if any row of this `Table` falls within the specified range, without reading any rows.
+
+        If there is no index which supports the given range, this will raise a :class:`BadRequest` error.
+        """
+
+        where = _handle_where(where)
+        return self._post("probe", where, Bool)
+
+    def purge_expired(self):
+        """Delete every row of this `Table` whose TTL has expired, and return the number of rows deleted."""
+
+        return self._post("purge_expired", Map(), UInt)
+
     def rows(self, **where):
         """Return a :class:`Stream` of the rows in this `Table`."""
 
@@ -103,6 +137,17 @@ class Table(Collection):
 
         return self._get("select", columns, Table)
 
+    def trigger(self, event, op):
+        """
+        Run the given POST `op` with the columns of each affected row as its params
+        whenever `event` occurs.
+
+        `event` is one of "before_insert", "after_insert", or "after_delete".
+        An error raised by a "before_insert" trigger aborts the insert.
+        """
+
+        return self._put("trigger", event, op)
+
     def update(self, where, values):
         """Update the specified rows of this table with the given `values`."""
 
@@ -1,13 +1,14 @@
 """An n-dimensional array of numbers."""
 
 from tinychain import ref
-from tinychain.state import Map
+from tinychain.state import Map, Tuple
 from tinychain.util import is_python_literal, uri
-from tinychain.value import Bool, F32, Number
+from tinychain.value import Bool, F32, Number, UInt
 
 from . import schema
 from .bound import Range
 from .collection import Collection
+from .table import Table
 
 
 class Tensor(Collection):
@@ -73,8 +74,17 @@ class Tensor(Collection):
     def abs(self):
         return self._get("abs", rtype=self.__class__)
 
-    def add(self, other):
-        return self._post("add", Map(r=other), Tensor)
+    def add(self, other, non_finite=None, checked=False):
+        """
+        Add another `Tensor` to this one, broadcasting if necessary.
+
+        `non_finite` controls what happens when the result is `NaN` or infinite:
+        "propagate" (the default), "error", or "zero".
+
+        If `checked` is `True`, an integer overflow raises a `BadRequest` error instead of wrapping.
+        """
+
+        return self._post("add", _math_params(other, non_finite, checked), Tensor)
 
     def all(self):
         """Return `True` if all elements in this `Tensor` are nonzero."""
@@ -86,21 +96,79 @@ class Tensor(Collection):
 
         return self._get("any", rtype=Bool)
 
-    def div(self, other):
-        """Divide this `Tensor` by another, broadcasting if necessary."""
+    def div(self, other, non_finite=None):
+        """
+        Divide this `Tensor` by another, broadcasting if necessary.
+
+        `non_finite` controls what happens when the result is `NaN` or infinite:
+        "propagate" (the default), "error", or "zero".
+        """
+
+        return self._post("div", _math_params(other, non_finite), Tensor)
+
+    def cast(self, dtype, saturate=False):
+        """
+        Cast this `Tensor` to the given `dtype`.
+
+        If `saturate` is `True`, a value which is out of range of an integer `dtype` is clamped to
+        its minimum or maximum value instead of wrapping around.
+        """
+
+        if saturate:
+            params = Map(dtype=dtype, saturate=True)
+        else:
+            params = Map(dtype=dtype)
+
+        return self._post("cast", params, self.__class__)
+
+    def chunk(self, chunks, axis=0):
+        """Divide this `Tensor` along the given `axis` into the given number of `chunks` of equal size."""
+
+        return self._post("chunk", Map(chunks=chunks, axis=axis), Tuple)
 
-        return self._post("div", Map(r=other), Tensor)
+    def clip_by_norm(self, max_norm):
+        """Rescale this `Tensor`, if needed, so that its L2 norm does not exceed `max_norm`."""
+
+        return self._get("clip_by_norm", max_norm, self.__class__)
+
+    def conv2d(self, kernel, stride=(1, 1), padding=(0, 0)):
+        """
+        Compute the cross-correlation of this 2-dimensional `Tensor` with a 2-dimensional `kernel`,
+        after zero-padding this `Tensor` by `padding` on each side.
+        """
+
+        return self._post("conv2d", Map(kernel=kernel, stride=stride, padding=padding), Dense)
+
+    def dot(self, other):
+        """Return the dot product of this 1-dimensional `Tensor` with another of the same length."""
+
+        return self._post("dot", Map(r=other), Number)
 
     def eq(self, other):
         """Return a boolean `Tensor` with element-wise equality values."""
 
         return self._post("eq", Map(r=other), Tensor)
 
+    def exp(self):
+        """Return the element-wise exponential `e^x` of this `Tensor`."""
+
+        return self._get("exp", rtype=self.__class__)
+
     def expand_dims(self, axis):
         """Return a view of this `Tensor` with an extra dimension of size 1 at the given axis."""
 
         return self._get("expand_dims", axis, self.__class__)
 
+    def fft(self):
+        """Return the discrete Fourier transform of this 1-dimensional `Tensor`, as a complex `Dense` tensor."""
+
+        return self._get("fft", rtype=Dense)
+
+    def flatten(self):
+        """Return a 1-dimensional copy of this `Tensor` with the same elements, in row-major order."""
+
+        return self._get("flatten", rtype=self.__class__)
+
     def gt(self, other):
         """Return a boolean `Tensor` with element-wise greater-than values."""
 
@@ -111,6 +179,16 @@ class Tensor(Collection):
 
         return self._post("gte", Map(r=other), Tensor)
 
+    def ifft(self):
+        """Return the inverse discrete Fourier transform of this 1-dimensional `Tensor`, as a complex `Dense` tensor."""
+
+        return self._get("ifft", rtype=Dense)
+
+    def kron(self, other):
+        """Return the Kronecker product of this 2-dimensional `Tensor` with another."""
+
+        return self._post("kron", Map(r=other), Dense)
+
     def lt(self, other):
         """Return a boolean `Tensor` with element-wise less-than values."""
 
@@ -121,6 +199,21 @@ class Tensor(Collection):
 
         return self._post("lte", Map(r=other), Tensor)
 
+    def ln(self):
+        """Return the element-wise natural logarithm of this `Tensor`."""
+
+        return self._get("ln", rtype=self.__class__)
+
+    def log2(self):
+        """Return the element-wise base-2 logarithm of this `Tensor`."""
+
+        return self._get("log2", rtype=self.__class__)
+
+    def log10(self):
+        """Return the element-wise base-10 logarithm of this `Tensor`."""
+
+        return self._get("log10", rtype=self.__class__)
+
     def logical_and(self, other):
         """Return a boolean `Tensor` with element-wise logical and values."""
 
@@ -141,26 +234,134 @@ class Tensor(Collection):
 
         return self._post("xor", Map(r=other), Tensor)
 
-    def mul(self, other):
-        """Multiply this `Tensor` by another, broadcasting if necessary."""
+    def masked_fill(self, mask, value):
+        """
+        Write `value` wherever the given `mask` is nonzero, broadcasting `mask` if necessary.
+
+        Unlike `set_where`, only the filled elements of a `Sparse` mask are read.
+        """
+
+        return self._put("masked_fill", value, mask)
+
+    def maximum(self, other):
+        """Return the element-wise maximum of this `Tensor` and `other`, broadcasting if needed."""
+
+        return self._post("maximum", Map(r=other), Tensor)
+
+    def minimum(self, other):
+        """Return the element-wise minimum of this `Tensor` and `other`, broadcasting if needed."""
+
+        return self._post("minimum", Map(r=other), Tensor)
 
-        return self._post("mul", Map(r=other), self.__class__)
+    def mean(self, axis=None):
+        """Calculate the mean of this `Tensor` along the given `axis`, or the overall mean if no axis is given."""
+
+        rtype = Number if axis is None else self.__class__
+        return self._get("mean", axis, rtype)
+
+    def mul(self, other, non_finite=None, checked=False):
+        """
+        Multiply this `Tensor` by another, broadcasting if necessary.
+
+        `non_finite` controls what happens when the result is `NaN` or infinite:
+        "propagate" (the default), "error", or "zero".
+
+        If `checked` is `True`, an integer overflow raises a `BadRequest` error instead of wrapping.
+        """
+
+        return self._post("mul", _math_params(other, non_finite, checked), self.__class__)
+
+    def nan_to_num(self, nan=None, posinf=None, neginf=None):
+        """
+        Replace each `NaN` in this `Tensor` with `nan` (zero by default), each positive infinity
+        with `posinf`, and each negative infinity with `neginf`
+        (by default, the largest and smallest finite values of this `Tensor`'s data type).
+        """
+
+        return self._post("nan_to_num", Map(nan=nan, posinf=posinf, neginf=neginf), self.__class__)
 
     def ne(self, other):
         """Return a boolean `Tensor` with element-wise not-equal values."""
 
         return self._post("ne", Map(r=other), self.__class__)
 
+    def nnz(self):
+        """Return the number of nonzero elements in this `Tensor`."""
+
+        return self._get("nnz", rtype=UInt)
+
+    def normalize(self, axis=0, norm="l2", strict=False):
+        """
+        Divide each lane of this `Tensor` along `axis` by its `norm` ("l1", "l2", or "max").
+
+        A lane whose norm is zero is left unchanged, unless `strict` is `True`, in which case
+        a `BadRequest` error is raised.
+        """
+
+        return self._post("normalize", Map(axis=axis, norm=norm, strict=strict), Dense)
+
+    def one_hot(self, depth):
+        """Encode this 1-dimensional `Tensor` of integer class indices as a 2-dimensional `Sparse` tensor."""
+
+        return self._get("one_hot", depth, Sparse)
+
     def product(self, axis=None):
         """Calculate the product of this `Tensor` along the given `axis`, or the total product if no axis is given."""
 
         rtype = Number if axis is None else self.__class__
         return self._get("product", axis, rtype)
 
-    def sub(self, other):
-        """Subtract another `Tensor` from this one, broadcasting if necessary."""
+    def repeat_interleave(self, repeats, axis=0):
+        """
+        Repeat each element of this `Tensor` `repeats` times consecutively along the given `axis`,
+        like NumPy's `repeat`.
+        """
+
+        return self._post("repeat_interleave", Map(repeats=repeats, axis=axis), self.__class__)
+
+    def set_where(self, mask, value):
+        """Set this `Tensor` to `value` wherever the given `mask` is nonzero, broadcasting `mask` if necessary."""
+
+        return self._put("set_where", value, mask)
+
+    def split(self, sizes, axis=0):
+        """Divide this `Tensor` along the given `axis` into consecutive slices of the given `sizes`."""
+
+        return self._post("split", Map(sizes=sizes, axis=axis), Tuple)
+
+    def roll(self, shift, axis=0):
+        """Cyclically shift the elements of this `Tensor` by `shift` along the given `axis`."""
+
+        return self._post("roll", Map(shift=shift, axis=axis), self.__class__)
 
-        return self._post("sub", Map(r=other), Tensor)
+    def squeeze(self, axis=None):
+        """
+        Remove the given `axis` of this `Tensor`, which must have size 1,
+        or every axis of size 1 if no `axis` is given.
+        """
+
+        return self._get("squeeze", axis, self.__class__)
+
+    def std(self, axis=None):
+        """
+        Calculate the standard deviation of this `Tensor` along the given `axis`,
+        or the overall standard deviation if no axis is given.
+        """
+
+        rtype = Number if axis is None else self.__class__
+        return self._get("std", axis, rtype)
+
+    def sub(self, other, non_finite=None, checked=False):
+        """
+        Subtract another `Tensor` from this one, broadcasting if necessary.
+
+        `non_finite` controls what happens when the result is `NaN` or infinite:
+        "propagate" (the default), "error", or "zero".
+
+        If `checked` is `True`, an integer overflow raises a `BadRequest` error instead of wrapping.
+        """
+
+        return self._post("sub", _math_params(other, non_finite, checked), Tensor)
 
     def sum(self, axis=None):
         """Calculate the sum of this `Tensor` along the given `axis`, or the total sum if no axis is given."""
@@ -168,6 +369,22 @@ class Tensor(Collection):
         rtype = Number if axis is None else self.__class__
         return self._get("sum", axis, rtype)
 
+    def take_along_axis(self, indices, axis=0):
+        """
+        Select an element of this `Tensor` along the given `axis` for each element of the integer
+        `Tensor` `indices`, like NumPy's `take_along_axis`.
+        """
+
+        return self._post("take_along_axis", Map(indices=indices, axis=axis), Dense)
+
+    def topk(self, k, axis=0, largest=True):
+        """
+        Return a `Tuple` of the `k` greatest (or, if `largest` is `False`, least) values of this `Tensor`
+        along the given `axis`, in order, and a `U64` `Tensor` of their indices along `axis`.
+        """
+
+        return self._post("topk", Map(k=k, axis=axis, largest=largest), Tuple)
+
     def transpose(self, permutation=None):
         """
         Return a view of this `Tensor` with its axes transposed according to the given permutation.
@@ -177,6 +394,15 @@ class Tensor(Collection):
 
         return self._get("transpose", permutation, self.__class__)
 
+    def var(self, axis=None):
+        """
+        Calculate the variance of this `Tensor` along the given `axis`,
+        or the overall variance if no axis is given.
+        """
+
+        rtype = Number if axis is None else self.__class__
+        return self._get("var", axis, rtype)
+
     def write(self, *args):
         """
         Write a `Tensor` or `Number` to the given slice of this one.
@@ -235,6 +461,15 @@ class Dense(Tensor):
 
         return cls.constant(shape, dtype(0))
 
+    def matmul(self, other):
+        """
+        Return the matrix product of this `Dense` tensor with `other`.
+
+        Any leading axes are treated as a batch of matrices and broadcast together.
+        """
+
+        return self._post("matmul", Map(r=other), Dense)
+
 
 class Sparse(Tensor):
     """An n-dimensional array of numbers stored as a :class:`Table` of coordinates and values."""
@@ -251,6 +486,49 @@ class Sparse(Tensor):
 
         return cls(schema.Tensor(shape, dtype))
 
+    @classmethod
+    def concatenate(cls, tensors, axis=0):
+        """Concatenate the given sparse `tensors` along the given `axis` without densifying them."""
+
+        return cls(ref.Post(uri(cls) + "/concatenate", Map(tensors=tensors, axis=axis)))
+
+    @classmethod
+    def matmul(cls, a, b):
+        """
+        Return the matrix product of the 2-dimensional sparse tensors `a` and `b`.
+
+        Only the filled elements of `a` and `b` are visited, and the product is also sparse.
+        """
+
+        return cls(ref.Post(uri(cls) + "/matmul", Map(a=a, b=b)))
+
+    @classmethod
+    def outer(cls, a, b):
+        """Return the outer product of the 1-dimensional sparse tensors `a` and `b`."""
+
+        return cls(ref.Post(uri(cls) + "/outer", Map(a=a, b=b)))
+
+    @classmethod
+    def from_table(cls, table, shape, dtype=F32):
+        """
+        Construct a sparse tensor with the given `shape` and `dtype` from the rows of `table`.
+
+        The `table` must have one coordinate column per axis followed by a value column,
+        like the table returned by :meth:`Sparse.as_table`.
+        """
+
+        return cls(ref.Post(uri(cls) + "/from_table", Map(table=table, schema=schema.Tensor(shape, dtype))))
+
+    def as_table(self):
+        """
+        Return a :class:`Table` of the filled elements of this sparse tensor.
+
+        The key of the table is one column per axis, named by its axis number,
+        and its only value column is the element "value".
+        """
+
+        return self._get("as_table", rtype=Table)
+
 
 def einsum(fmt, tensors):
     return Tensor(ref.Post(uri(Tensor) + "/einsum", Map(format=fmt, tensors=tensors)))
@@ -268,3 +546,15 @@ def _handle_bounds(bounds):
     return [
         Range.from_slice(x) if isinstance(x, slice)
         else x for x in bounds]
+
+
+def _math_params(other, non_finite, checked=False):
+    params = {"r": other}
+
+    if non_finite is not None:
+        params["non_finite"] = non_finite
+
+    if checked:
+        params["checked"] = True
+
+    return Map(params)
@@ -40,3 +40,38 @@ def post_op(form):
 def delete_op(form):
     """Annotation for a callable function specifying that it is a DELETE :class:`Op`."""
     return op.Delete(form)
+
+
+def timeout(seconds):
+    """Annotation for an :class:`Op` specifying the maximum time it may take to execute, in seconds."""
+
+    def annotate(op_def):
+        op_def.timeout = seconds
+        return op_def
+
+    return annotate
+
+
+def signature(input=None, output=None):
+    """
+    Annotation for an :class:`Op` declaring the expected type of each of its named parameters and of its result.
+
+    The host returns a :class:`BadRequest` error if the :class:`Op` is called with a parameter of the wrong type,
+    or if its result does not have the declared type.
+
+    Example:
+        .. highlight:: python
+        .. code-block:: python
+
+            @tc.signature(input={"x": tc.Number}, output=tc.Number)
+            @tc.post_op
+            def double(txn, x: tc.Number) -> tc.Number:
+                return x * 2
+    """
+
+    def annotate(op_def):
+        op_def.input = input
+        op_def.output = output
+        return op_def
+
+    return annotate
@@ -8,19 +8,33 @@ class TinychainError(Exception):
 
     __uri__ = URI("/error")
 
-    def __init__(self, message):
+    def __init__(self, message, code=None, status=None):
         self.message = message
+        self.code = code
+        self.status = status
 
     def __json__(self):
         return {str(uri(self)): [str(self.message)]}
 
 
+class BadGateway(TinychainError):
+    """Error indicating that an upstream host, such as a replica of a cluster, could not be reached."""
+
+    __uri__ = uri(TinychainError) + "/bad_gateway"
+
+
 class BadRequest(TinychainError):
     """Error indicating receipt of a request which is badly-constructed or nonsensical."""
 
     __uri__ = uri(TinychainError) + "/bad_request"
 
 
+class Conflict(TinychainError):
+    """Error indicating that the requested resource is locked by a concurrent transaction."""
+
+    __uri__ = uri(TinychainError) + "/conflict"
+
+
 class Forbidden(TinychainError):
     """Error indicating that the requestor is not authorized to access a requested resource."""
 
@@ -51,6 +65,12 @@ class NotImplemented(TinychainError):
     __uri__ = uri(TinychainError) + "/not_implemented"
 
 
+class Timeout(TinychainError):
+    """Error indicating that a request took longer than its allotted time to execute."""
+
+    __uri__ = uri(TinychainError) + "/timeout"
+
+
 class Unauthorized(TinychainError):
     """Error indicating that the requestor's credentials are missing or invalid."""
 
@@ -4,6 +4,7 @@ import json
 import logging
 import os
 import pathlib
+import random
 import requests
 import subprocess
 import time
@@ -42,73 +43,172 @@ class Host(object):
             return response
         elif status == 204:
             return None
-        elif status == 400:
-            raise BadRequest(response)
+
+        error = {}
+        if isinstance(response, dict) and "message" in response:
+            error = {"code": response.get("code"), "status": response.get("status")}
+            response = response["message"]
+
+        if status == 400:
+            raise BadRequest(response, **error)
         elif status == 401:
-            raise Unauthorized(response)
+            raise Unauthorized(response, **error)
         elif status == 403:
-            raise Forbidden(response)
+            raise Forbidden(response, **error)
         elif status == 404:
-            raise NotFound(response)
+            raise NotFound(response, **error)
         elif status == 405:
-            raise MethodNotAllowed(response)
+            raise MethodNotAllowed(response, **error)
+        elif status == 408:
+            raise Timeout(response, **error)
+        elif status == 409:
+            raise Conflict(response, **error)
         elif status == 501:
-            raise NotImplemented(response)
+            raise NotImplemented(response, **error)
+        elif status == 502:
+            raise BadGateway(response, **error)
         else:
-            raise UnknownError(f"HTTP error code {status}: {response}")
+            raise UnknownError(f"HTTP error code {status}: {response}", **error)
 
     def link(self, path):
         """Return a link to the given path at this host."""
 
         return uri(self) + path
 
-    def get(self, path, key=None, auth=None):
-        """Execute a GET request."""
+    def get(self, path, key=None, auth=None, read_only=False, metadata=None, pointer=None, txn_id=None):
+        """
+        Execute a GET request.
+
+        If a JSON `pointer` like "/a/b/0" is given, only the nested member of the response at that pointer is returned.
+        If a `txn_id` is given, the request executes within that transaction, which is held open (see :meth:`commit`).
+        """
 
         url = self.link(path)
-        headers = auth_header(auth)
+        headers = request_headers(auth, metadata)
+        params = query_params(read_only=read_only, pointer=pointer, txn_id=txn_id)
         if key:
-            key = json.dumps(to_json(key)).encode(ENCODING)
-            request = lambda: requests.get(url, params={"key": key}, headers=headers)
-        else:
-            request = lambda: requests.get(url, headers=headers)
+            params["key"] = json.dumps(to_json(key)).encode(ENCODING)
+
+        request = lambda: requests.get(url, params=params, headers=headers)
 
         return self._handle(request)
 
-    def put(self, path, key=None, value=None, auth=None):
-        """Execute a PUT request."""
+    def put(self, path, key=None, value=None, auth=None, read_only=False, metadata=None, pointer=None, txn_id=None):
+        """
+        Execute a PUT request.
+
+        If a JSON `pointer` like "/a/b/0" is given, the PUT is handled by the nested member at that pointer.
+        If a `txn_id` is given, the request executes within that transaction, which is held open (see :meth:`commit`).
+        """
 
         url = self.link(path)
-        headers = auth_header(auth)
+        headers = request_headers(auth, metadata)
         key = json.dumps(to_json(key)).encode(ENCODING)
+        params = query_params(key=key, read_only=read_only, pointer=pointer, txn_id=txn_id)
         value = json.dumps(to_json(value)).encode(ENCODING)
-        request = lambda: requests.put(url, params={"key": key}, data=value, headers=headers)
+        request = lambda: requests.put(url, params=params, data=value, headers=headers)
 
         return self._handle(request)
 
-    def post(self, path, data={}, auth=None):
-        """Execute a POST request."""
+    def patch(self, path, key=None, value=None, auth=None, read_only=False, metadata=None):
+        """Execute a PATCH request."""
+
+        url = self.link(path)
+        headers = request_headers(auth, metadata)
+        params = query_params(key=json.dumps(to_json(key)).encode(ENCODING), read_only=read_only)
+        value = json.dumps(to_json(value)).encode(ENCODING)
+        request = lambda: requests.patch(url, params=params, data=value, headers=headers)
+
+        return self._handle(request)
+
+    def post(self, path, data={}, auth=None, read_only=False, metadata=None, txn_id=None):
+        """
+        Execute a POST request.
+
+        If a `txn_id` is given, the request executes within that transaction, which is held open (see :meth:`commit`).
+        """
 
         url = self.link(path)
         data = json.dumps(to_json(data)).encode(ENCODING)
-        headers = auth_header(auth)
-        request = lambda: requests.post(url, data=data, headers=headers)
+        headers = request_headers(auth, metadata)
+        params = query_params(read_only=read_only, txn_id=txn_id)
+        request = lambda: requests.post(url, params=params, data=data, headers=headers)
+
+        return self._handle(request)
+
+    def post_stream(self, path, params, name, values, auth=None, read_only=False, metadata=None):
+        """
+        Execute a POST request whose parameter `name` is streamed from an iterable of `values`,
+        so that a large import (e.g. the rows of a `Table` to `copy_from`) is never buffered.
+
+        The other `params` are sent in the query string.
+        """
+
+        url = self.link(path)
+        headers = request_headers(auth, metadata)
+        params = {k: json.dumps(to_json(v)).encode(ENCODING) for k, v in params.items()}
+        params = query_params(read_only=read_only, stream=name, **params)
+
+        def encode():
+            yield b"["
+            for i, value in enumerate(values):
+                prefix = b"," if i else b""
+                yield prefix + json.dumps(to_json(value)).encode(ENCODING)
+            yield b"]"
+
+        request = lambda: requests.post(url, params=params, data=encode(), headers=headers)
 
         return self._handle(request)
 
-    def delete(self, path, key=None, auth=None):
-        """Execute a DELETE request."""
+    def delete(self, path, key=None, auth=None, read_only=False, metadata=None, txn_id=None):
+        """
+        Execute a DELETE request.
+
+        If a `txn_id` is given, the request executes within that transaction, which is held open (see :meth:`commit`).
+        """
 
         url = self.link(path)
-        headers = auth_header(auth)
+        headers = request_headers(auth, metadata)
+        params = query_params(read_only=read_only, txn_id=txn_id)
         if key:
-            key = json.dumps(to_json(key)).encode(ENCODING)
-            request = lambda: requests.delete(url, params={"key": key}, headers=headers)
-        else:
-            request = lambda: requests.delete(url, headers=headers)
+            params["key"] = json.dumps(to_json(key)).encode(ENCODING)
+
+        request = lambda: requests.delete(url, params=params, headers=headers)
 
         return self._handle(request)
 
+    def commit(self, txn_id, auth=None):
+        """
+        Commit every write made under the given `txn_id`, which was held open across multiple requests.
+
+        Raises :class:`NotFound` if there is no such transaction, e.g. because it already expired.
+
+        Example:
+            .. highlight:: python
+            .. code-block:: python
+
+                txn_id = tc.host.new_txn_id()
+                host.put("/app/example/value", value=1, txn_id=txn_id)
+                host.put("/app/example/other", value=2, txn_id=txn_id)
+                host.commit(txn_id)
+        """
+
+        return self.post(f"/txn/{txn_id}/commit", auth=auth)
+
+    def abort(self, txn_id, auth=None):
+        """Roll back every write made under the given `txn_id`, which was held open across multiple requests."""
+
+        return self.post(f"/txn/{txn_id}/abort", auth=auth)
+
+    def metrics(self):
+        """Return the request metrics of this host, in the Prometheus text exposition format."""
+
+        response = requests.get(self.link("/metrics"))
+        if response.status_code != 200:
+            raise UnknownError(f"HTTP error code {response.status_code}: {response.text}")
+
+        return response.text
+
 
 class Local(Host):
     """A local Tinychain host."""
@@ -125,6 +225,19 @@ class Local(Host):
             port=DEFAULT_PORT,
             log_level="warn",
             cache_size="1G",
+            max_op_steps=None,
+            max_op_states=None,
+            high_priority_concurrency=None,
+            quorum=None,
+            replica_retries=None,
+            replica_retry_delay=None,
+            replica_concurrency=None,
+            replica_queue_size=None,
+            balance_reads=False,
+            op_cache_size=None,
+            op_cache_ttl=None,
+            tls_cert=None,
+            tls_key=None,
             force_create=False):
 
         # set _process first so it's available to __del__ in case of an exception
@@ -141,7 +254,8 @@ class Local(Host):
         if data_dir:
             maybe_create_dir(data_dir, force_create)
 
-        address = "http://{}:{}".format(self.ADDRESS, port)
+        protocol = "https" if tls_cert else "http"
+        address = "{}://{}:{}".format(protocol, self.ADDRESS, port)
         Host.__init__(self, address)
 
         args = [
@@ -155,6 +269,45 @@ class Local(Host):
         if data_dir:
             args.append(f"--data_dir={data_dir}")
 
+        if max_op_steps:
+            args.append(f"--max_op_steps={max_op_steps}")
+
+        if max_op_states:
+            args.append(f"--max_op_states={max_op_states}")
+
+        if high_priority_concurrency:
+            args.append(f"--high_priority_concurrency={high_priority_concurrency}")
+
+        if quorum:
+            args.append(f"--quorum={quorum}")
+
+        if replica_retries is not None:
+            args.append(f"--replica_retries={replica_retries}")
+
+        if replica_retry_delay is not None:
+            args.append(f"--replica_retry_delay={replica_retry_delay}")
+
+        if replica_concurrency:
+            args.append(f"--replica_concurrency={replica_concurrency}")
+
+        if replica_queue_size is not None:
+            args.append(f"--replica_queue_size={replica_queue_size}")
+
+        if balance_reads:
+            args.append("--balance_reads")
+
+        if op_cache_size:
+            args.append(f"--op_cache_size={op_cache_size}")
+
+        if op_cache_ttl is not None:
+            args.append(f"--op_cache_ttl={op_cache_ttl}")
+
+        if tls_cert:
+            args.append(f"--tls_cert={tls_cert}")
+
+        if tls_key:
+            args.append(f"--tls_key={tls_key}")
+
         args.extend([f"--cluster={cluster}" for cluster in clusters])
 
         self._args = args
@@ -200,6 +353,40 @@ def auth_header(token):
     return {"Authorization": f"Bearer {token}"} if token else {}
 
 
+def request_headers(token, metadata=None):
+    """
+    Construct the headers of a request with the given auth `token` and transaction `metadata`.
+
+    `metadata` is a dict of application-defined values (such as a tenant ID) which an `Op` can read as `$meta`.
+    """
+
+    headers = auth_header(token)
+    if metadata:
+        headers["X-TC-Metadata"] = json.dumps(to_json(metadata))
+
+    return headers
+
+
+def new_txn_id():
+    """Generate a new transaction ID, to hold a transaction open across multiple requests."""
+
+    return f"{time.time_ns()}-{random.randrange(2 ** 16)}"
+
+
+def query_params(read_only=False, pointer=None, txn_id=None, **params):
+    if read_only:
+        params["read_only"] = "true"
+
+    if txn_id is not None:
+        params["txn_id"] = txn_id
+        params["hold"] = "true"
+
+    if pointer is not None:
+        params["pointer"] = pointer
+
+    return params
+
+
 def maybe_create_dir(path, force):
     path = pathlib.Path(path)
     if path.exists() and path.is_dir():
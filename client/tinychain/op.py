@@ -1,7 +1,7 @@
 """User-defined ops"""
 
 from tinychain import ref
-from tinychain.state import Scalar
+from tinychain.state import Map, Scalar
 from tinychain.util import uri
 
 
@@ -10,6 +10,16 @@ class Op(Scalar):
 
     __uri__ = uri(Scalar) + "/op"
 
+    def then(self, next):
+        """
+        Return a new :class:`Op` with the same signature as this one, which passes its result to `next`.
+
+        `next` must be a :class:`Get` op, which receives the result as its key,
+        or a :class:`Post` op which expects exactly one parameter.
+        """
+
+        return self._post("then", Map(next=next), self.__class__)
+
 
 class Get(Op):
     """A function which can be called via a GET request."""
@@ -35,7 +45,12 @@ class Post(Op):
     __uri__ = uri(Op) + "/post"
 
     def __call__(self, **params):
-        return ref.Post(self, **params)
+        return ref.Post(self, params)
+
+    def partial(self, **params):
+        """Bind some of the parameters of this :class:`Op`, returning a new :class:`Op` which expects the rest."""
+
+        return self._post("partial", Map(params), Post)
 
 
 class Delete(Op):
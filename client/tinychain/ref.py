@@ -168,6 +168,14 @@ class Post(Op):
     def __init__(self, subject, args):
         Op.__init__(self, subject, args)
 
+    def __json__(self):
+        subject = uri(self.subject)
+        if to_json(self.args) or subject is None or str(subject).startswith('$'):
+            return Op.__json__(self)
+
+        # a link with an empty map of parameters would otherwise decode as the link itself
+        return {str(uri(self)): [str(subject), {}]}
+
     def __repr__(self):
         return f"POST Op ref {self.subject} {self.args}"
 
@@ -16,9 +16,23 @@ class Op(object):
 
     def __init__(self, form):
         self.form = form
+        self.timeout = None
+        self.input = None
+        self.output = None
 
     def __json__(self):
-        return {str(uri(self)): to_json(form_of(self))}
+        encoded = {str(uri(self)): to_json(form_of(self))}
+
+        if self.timeout is not None:
+            encoded["timeout"] = self.timeout
+
+        if self.input is not None:
+            encoded["input"] = [[name, str(uri(dtype))] for name, dtype in self.input.items()]
+
+        if self.output is not None:
+            encoded["output"] = str(uri(self.output))
+
+        return encoded
 
     def dtype(self):
         return self.__class__.__name__
@@ -1,7 +1,7 @@
 """:class:`Value` types such as :class:`Nil`, :class:`Number`, and :class:`String`."""
 
 from tinychain.ref import Ref
-from tinychain.state import Scalar
+from tinychain.state import Scalar, Tuple
 from tinychain.util import form_of, to_json, uri, URI
 
 
@@ -70,6 +70,21 @@ class String(Value):
 
     __uri__ = uri(Value) + "/string"
 
+    def concat(self, other):
+        """Return the concatenation of this `String` with `other`."""
+
+        return self._get("concat", other, String)
+
+    def format(self, args):
+        """Replace each `{}` placeholder in this `String` with the next of the given `args`."""
+
+        return self._get("format", tuple(args), String)
+
+    def split(self, separator):
+        """Split this `String` into a `Tuple` of substrings at each occurrence of `separator`."""
+
+        return self._get("split", separator, Tuple)
+
 
 # Numeric types
 
@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::fmt;
 #[cfg(debug_assertions)]
 use std::iter::FromIterator;
@@ -12,14 +13,15 @@ use collate::Collate;
 use destream::{de, en};
 use futures::future::{self, try_join, try_join_all, Future, TryFutureExt};
 use futures::join;
-use futures::stream::{self, FuturesOrdered, TryStreamExt};
+use futures::stream::{self, FuturesOrdered, Stream, TryStreamExt};
 use log::debug;
+use safecast::TryCastInto;
 use uuid::Uuid;
 
 use tc_error::*;
 use tc_transact::fs::*;
 use tc_transact::lock::TxnLock;
-use tc_transact::{Transact, Transaction, TxnId};
+use tc_transact::{Rollback, Transact, Transaction, TxnId};
 use tc_value::{Value, ValueCollator};
 use tcgeneric::{Instance, TCBoxTryFuture, TCBoxTryStream, Tuple};
 
@@ -32,6 +34,9 @@ type Selection<'a> = FuturesOrdered<
 const DEFAULT_BLOCK_SIZE: usize = 4_000;
 const BLOCK_ID_SIZE: usize = 128; // UUIDs are 128-bit
 
+/// The minimum order (maximum number of keys per node is `(2 * order) - 1`) of a [`BTreeFile`]
+pub const MIN_ORDER: usize = 2;
+
 type NodeId = BlockId;
 
 #[derive(Clone, Eq, PartialEq)]
@@ -98,20 +103,30 @@ pub struct Node {
     keys: Vec<NodeKey>,
     parent: Option<NodeId>,
     children: Vec<NodeId>,
-    rebalance: bool, // TODO: implement rebalancing to clear deleted values
+    rebalance: bool,      // TODO: implement rebalancing to clear deleted values
+    order: Option<usize>, // only recorded in the root node
+    compressed: bool,     // if set, string keys are front-coded when serialized
 }
 
 impl Node {
-    fn new(leaf: bool, parent: Option<NodeId>) -> Node {
+    fn new(leaf: bool, parent: Option<NodeId>, compressed: bool) -> Node {
         Node {
             leaf,
             keys: vec![],
             parent,
             children: vec![],
             rebalance: false,
+            order: None,
+            compressed,
         }
     }
 
+    fn root(leaf: bool, order: usize, compressed: bool) -> Node {
+        let mut root = Node::new(leaf, None, compressed);
+        root.order = Some(order);
+        root
+    }
+
     #[cfg(debug_assertions)]
     fn validate(&self, schema: &[super::Column], range: &Range) {
         debug!("validate {}", self);
@@ -136,19 +151,73 @@ impl BlockData for Node {
     }
 }
 
+struct NodeVisitor;
+
+#[async_trait]
+impl de::Visitor for NodeVisitor {
+    type Value = Node;
+
+    fn expecting() -> &'static str {
+        "a BTree node"
+    }
+
+    async fn visit_seq<A: de::SeqAccess>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let leaf = seq
+            .next_element(())
+            .await?
+            .ok_or_else(|| de::Error::invalid_length(0, Self::expecting()))?;
+
+        let keys = seq
+            .next_element(())
+            .await?
+            .ok_or_else(|| de::Error::invalid_length(1, Self::expecting()))?;
+
+        let parent = seq
+            .next_element(())
+            .await?
+            .ok_or_else(|| de::Error::invalid_length(2, Self::expecting()))?;
+
+        let children = seq
+            .next_element(())
+            .await?
+            .ok_or_else(|| de::Error::invalid_length(3, Self::expecting()))?;
+
+        let rebalance = seq
+            .next_element(())
+            .await?
+            .ok_or_else(|| de::Error::invalid_length(4, Self::expecting()))?;
+
+        // nodes written before the order was recorded have only five fields
+        let order = seq.next_element::<Option<usize>>(()).await?.flatten();
+
+        // nodes written before prefix compression was supported have only six fields
+        let compressed = seq.next_element::<bool>(()).await?.unwrap_or(false);
+
+        let keys = if compressed {
+            decompress_keys(keys).map_err(de::Error::custom)?
+        } else {
+            keys
+        };
+
+        Ok(Node {
+            leaf,
+            keys,
+            parent,
+            children,
+            rebalance,
+            order,
+            compressed,
+        })
+    }
+}
+
 #[async_trait]
 impl de::FromStream for Node {
     type Context = ();
 
-    async fn from_stream<D: de::Decoder>(cxt: (), decoder: &mut D) -> Result<Self, D::Error> {
-        de::FromStream::from_stream(cxt, decoder)
-            .map_ok(|(leaf, keys, parent, children, rebalance)| Self {
-                leaf,
-                keys,
-                parent,
-                children,
-                rebalance,
-            })
+    async fn from_stream<D: de::Decoder>(_cxt: (), decoder: &mut D) -> Result<Self, D::Error> {
+        decoder
+            .decode_seq(NodeVisitor)
             .map_err(|e| de::Error::custom(format!("error decoding BTree node: {}", e)))
             .await
     }
@@ -156,34 +225,174 @@ impl de::FromStream for Node {
 
 impl<'en> en::ToStream<'en> for Node {
     fn to_stream<E: en::Encoder<'en>>(&'en self, encoder: E) -> Result<E::Ok, E::Error> {
-        en::IntoStream::into_stream(
-            (
-                &self.leaf,
-                &self.keys,
-                &self.parent,
-                &self.children,
-                &self.rebalance,
-            ),
-            encoder,
-        )
+        if self.compressed {
+            en::IntoStream::into_stream(
+                (
+                    &self.leaf,
+                    compress_keys(&self.keys),
+                    &self.parent,
+                    &self.children,
+                    &self.rebalance,
+                    &self.order,
+                    &self.compressed,
+                ),
+                encoder,
+            )
+        } else {
+            en::IntoStream::into_stream(
+                (
+                    &self.leaf,
+                    &self.keys,
+                    &self.parent,
+                    &self.children,
+                    &self.rebalance,
+                    &self.order,
+                    &self.compressed,
+                ),
+                encoder,
+            )
+        }
     }
 }
 
 impl<'en> en::IntoStream<'en> for Node {
     fn into_stream<E: en::Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
+        let keys = if self.compressed {
+            compress_keys(&self.keys)
+        } else {
+            self.keys
+        };
+
         en::IntoStream::into_stream(
             (
                 self.leaf,
-                self.keys,
+                keys,
                 self.parent,
                 self.children,
                 self.rebalance,
+                self.order,
+                self.compressed,
             ),
             encoder,
         )
     }
 }
 
+// Front-code the string values in the given `keys`: each string is replaced by a tuple of
+// the length of the prefix it shares with the same column of the previous key, and its suffix.
+// Every other value is wrapped in a tuple of length one, so that decoding is unambiguous.
+fn compress_keys(keys: &[NodeKey]) -> Vec<NodeKey> {
+    let mut compressed = Vec::with_capacity(keys.len());
+    let mut previous: Option<&NodeKey> = None;
+
+    for key in keys {
+        let value = key
+            .value
+            .iter()
+            .enumerate()
+            .map(|(i, value)| match value {
+                Value::String(string) => {
+                    let prefix_len = match previous.and_then(|prev| prev.value.get(i)) {
+                        Some(Value::String(prev)) => shared_prefix_len(prev, string),
+                        _ => 0,
+                    };
+
+                    Value::from(vec![
+                        Value::from(prefix_len as u64),
+                        Value::String(string[prefix_len..].to_string()),
+                    ])
+                }
+                other => Value::from(vec![other.clone()]),
+            })
+            .collect();
+
+        compressed.push(NodeKey {
+            deleted: key.deleted,
+            value,
+        });
+
+        previous = Some(key);
+    }
+
+    compressed
+}
+
+fn decompress_keys(keys: Vec<NodeKey>) -> Result<Vec<NodeKey>, String> {
+    let mut decompressed: Vec<NodeKey> = Vec::with_capacity(keys.len());
+
+    for key in keys {
+        let mut value = Vec::with_capacity(key.value.len());
+
+        for (i, coded) in key.value.into_iter().enumerate() {
+            let mut coded = match coded {
+                Value::Tuple(coded) => coded.into_inner(),
+                other => return Err(format!("invalid front-coded BTree key: {}", other)),
+            };
+
+            if coded.len() == 1 {
+                value.extend(coded.pop());
+                continue;
+            } else if coded.len() != 2 {
+                return Err(format!(
+                    "invalid front-coded BTree key: {}",
+                    Value::from(coded)
+                ));
+            }
+
+            let suffix = coded.pop().expect("suffix");
+            let prefix_len = coded.pop().expect("prefix length");
+            let (prefix_len, suffix) = match (prefix_len, suffix) {
+                (Value::Number(prefix_len), Value::String(suffix)) => {
+                    let prefix_len: u64 = Value::Number(prefix_len)
+                        .opt_cast_into()
+                        .ok_or("invalid front-coded BTree key prefix length")?;
+
+                    (prefix_len as usize, suffix)
+                }
+                (prefix_len, suffix) => {
+                    return Err(format!(
+                        "invalid front-coded BTree key: {}",
+                        Value::from(vec![prefix_len, suffix])
+                    ))
+                }
+            };
+
+            let prefix = match decompressed.last().and_then(|prev| prev.value.get(i)) {
+                Some(Value::String(prev)) => prev.as_str(),
+                _ => "",
+            };
+
+            if prefix_len > prefix.len() || !prefix.is_char_boundary(prefix_len) {
+                return Err(format!(
+                    "invalid front-coded BTree key prefix length: {}",
+                    prefix_len
+                ));
+            }
+
+            value.push(Value::String(format!(
+                "{}{}",
+                &prefix[..prefix_len],
+                suffix
+            )));
+        }
+
+        decompressed.push(NodeKey {
+            deleted: key.deleted,
+            value,
+        });
+    }
+
+    Ok(decompressed)
+}
+
+fn shared_prefix_len(left: &str, right: &str) -> usize {
+    left.chars()
+        .zip(right.chars())
+        .take_while(|(l, r)| l == r)
+        .map(|(c, _)| c.len_utf8())
+        .sum()
+}
+
 #[cfg(debug_assertions)]
 impl fmt::Debug for Node {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -214,6 +423,7 @@ struct Inner<F, D, T> {
     file: F,
     schema: RowSchema,
     order: usize,
+    compressed: bool,
     collator: ValueCollator,
     root: TxnLock<NodeId>,
     dir: PhantomData<D>,
@@ -230,12 +440,13 @@ impl<F: File<Node>, D: Dir, T: Transaction<D>> BTreeFile<F, D, T>
 where
     Self: Clone,
 {
-    fn new(file: F, schema: RowSchema, order: usize, root: NodeId) -> Self {
+    fn new(file: F, schema: RowSchema, order: usize, compressed: bool, root: NodeId) -> Self {
         BTreeFile {
             inner: Arc::new(Inner {
                 file,
                 schema,
                 order,
+                compressed,
                 collator: ValueCollator::default(),
                 root: TxnLock::new("BTree root", root.into()),
                 dir: PhantomData,
@@ -244,22 +455,251 @@ where
         }
     }
 
-    /// Create a new `BTreeFile`.
+    /// Create a new `BTreeFile`, with an order computed from the size of its keys.
     pub async fn create(file: F, schema: RowSchema, txn_id: TxnId) -> TCResult<Self> {
+        Self::create_with_options(file, schema, None, false, txn_id).await
+    }
+
+    /// Create a new `BTreeFile` with the given `order`.
+    ///
+    /// A smaller order means smaller nodes and a deeper tree, which may suit a workload with very
+    /// large keys, and a larger order means larger nodes and a shallower tree, which may suit a
+    /// workload with very small keys. The order is recorded with the tree and does not affect the
+    /// results of any query.
+    pub async fn create_with_order(
+        file: F,
+        schema: RowSchema,
+        order: usize,
+        txn_id: TxnId,
+    ) -> TCResult<Self> {
+        Self::create_with_options(file, schema, Some(order), false, txn_id).await
+    }
+
+    /// Create a new `BTreeFile` with the given `order` (or a default order computed from the size
+    /// of its keys), optionally with prefix compression.
+    ///
+    /// If `compressed` is set, the string values of each node's keys are front-coded when the node
+    /// is written to disk, and decompressed when it's read. This saves space when keys share long
+    /// prefixes, at the cost of CPU time to encode and decode each node, and does not affect the
+    /// results of any query.
+    pub async fn create_with_options(
+        file: F,
+        schema: RowSchema,
+        order: Option<usize>,
+        compressed: bool,
+        txn_id: TxnId,
+    ) -> TCResult<Self> {
         if !file.is_empty(&txn_id).await? {
             return Err(TCError::internal(
                 "Tried to create a new BTree without a new File",
             ));
         }
 
-        let order = validate_schema(&schema)?;
+        let default_order = validate_schema(&schema)?;
+        let order = order.unwrap_or(default_order);
+        validate_order(order)?;
 
         let root: BlockId = Uuid::new_v4().into();
         file.clone()
-            .create_block(txn_id, root.clone(), Node::new(true, None))
+            .create_block(txn_id, root.clone(), Node::root(true, order, compressed))
+            .await?;
+
+        Ok(BTreeFile::new(file, schema, order, compressed, root))
+    }
+
+    /// Build a new `BTreeFile` bottom-up from a stream of `keys` which is already sorted.
+    ///
+    /// Each node is written exactly once, with one free slot left for a subsequent insert, so
+    /// this is much faster than inserting the same keys one at a time, which would split nodes
+    /// repeatedly. Duplicate keys are skipped, and an out-of-order key is an error.
+    pub async fn bulk_load<S>(
+        file: F,
+        schema: RowSchema,
+        mut keys: S,
+        txn_id: TxnId,
+    ) -> TCResult<Self>
+    where
+        S: Stream<Item = TCResult<Key>> + Send + Unpin,
+    {
+        if !file.is_empty(&txn_id).await? {
+            return Err(TCError::internal(
+                "Tried to bulk-load a BTree without a new File",
+            ));
+        }
+
+        let order = validate_schema(&schema)?;
+        let capacity = (2 * order) - 2;
+        let collator = ValueCollator::default();
+
+        // the node currently being filled at each level of the tree, starting from the leaves
+        let mut levels = vec![(file.unique_id(&txn_id).await?, Node::new(true, None, false))];
+        let mut last: Option<Key> = None;
+
+        while let Some(key) = keys.try_next().await? {
+            let key = validate_key(key, &schema)?;
+
+            if let Some(last) = &last {
+                match collator.compare_slice(last, &key) {
+                    Ordering::Less => {}
+                    Ordering::Equal => continue,
+                    Ordering::Greater => {
+                        return Err(TCError::bad_request(
+                            "cannot bulk-load a BTree from unsorted keys, found out-of-order key",
+                            Tuple::from(key),
+                        ))
+                    }
+                }
+            }
+
+            last = Some(key.to_vec());
+
+            let key = NodeKey::new(key);
+            let mut height = 0;
+            while levels[height].1.keys.len() == capacity {
+                // this node is full, so write it and pass the key up to separate it from the next
+                if height + 1 == levels.len() {
+                    let parent_id = file.unique_id(&txn_id).await?;
+                    levels.push((parent_id, Node::new(false, None, false)));
+                }
+
+                let leaf = levels[height].1.leaf;
+                let next = (file.unique_id(&txn_id).await?, Node::new(leaf, None, false));
+                let (node_id, mut node) = std::mem::replace(&mut levels[height], next);
+                node.parent = Some(levels[height + 1].0.clone());
+                file.create_block(txn_id, node_id.clone(), node).await?;
+
+                levels[height + 1].1.children.push(node_id);
+                height += 1;
+            }
+
+            levels[height].1.keys.push(key);
+        }
+
+        // write the last node at each level, which is the rightmost child of the one above it
+        let mut levels = levels.into_iter();
+        let (mut node_id, mut node) = levels.next().expect("leaf");
+        for (parent_id, mut parent) in levels {
+            node.parent = Some(parent_id.clone());
+            file.create_block(txn_id, node_id.clone(), node).await?;
+            parent.children.push(node_id);
+
+            node_id = parent_id;
+            node = parent;
+        }
+
+        node.order = Some(order);
+        file.create_block(txn_id, node_id.clone(), node).await?;
+
+        Ok(BTreeFile::new(file, schema, order, false, node_id))
+    }
+
+    /// Return the order of this `BTreeFile`.
+    pub fn order(&self) -> usize {
+        self.inner.order
+    }
+
+    /// Return `true` if the keys of this `BTreeFile` are prefix-compressed on disk.
+    pub fn is_compressed(&self) -> bool {
+        self.inner.compressed
+    }
+
+    /// Walk this `BTreeFile` and return a description of each violation of its structural
+    /// invariants: unsorted keys, a key outside the range of its parent, an overfull node, an
+    /// unbalanced depth, or a reference to a node which does not exist.
+    ///
+    /// A healthy tree has no problems. Nodes are checked one at a time, so this does not
+    /// load the whole tree into memory.
+    pub async fn verify(&self, txn_id: TxnId) -> TCResult<Vec<String>> {
+        self.walk(txn_id, None).await
+    }
+
+    /// Rebuild this `BTreeFile` from the live keys in its nodes, if [`Self::verify`] finds any
+    /// problem, and return the problems found.
+    ///
+    /// This is an error if any node is missing, since its keys could not be recovered.
+    pub async fn repair(&self, txn_id: TxnId) -> TCResult<Vec<String>> {
+        let mut keys = Vec::new();
+        let problems = self.walk(txn_id, Some(&mut keys)).await?;
+
+        if problems.is_empty() {
+            return Ok(problems);
+        } else if problems.iter().any(|problem| problem.starts_with(DANGLING)) {
+            return Err(TCError::unsupported(format!(
+                "cannot safely repair a BTree with missing nodes: {}",
+                problems.join("; ")
+            )));
+        }
+
+        let collator = &self.inner.collator;
+        keys.sort_by(|l, r| collator.compare_slice(l, r));
+        keys.dedup_by(|l, r| collator.compare_slice(l, r) == Ordering::Equal);
+
+        self.delete(txn_id).await?;
+        self.try_insert_from(txn_id, stream::iter(keys.into_iter().map(TCResult::Ok)))
             .await?;
 
-        Ok(BTreeFile::new(file, schema, order, root))
+        Ok(problems)
+    }
+
+    /// Check every node reachable from the root, returning the problems found.
+    /// If `keys` is given, the live keys of each node are collected into it.
+    async fn walk(&self, txn_id: TxnId, mut keys: Option<&mut Vec<Key>>) -> TCResult<Vec<String>> {
+        let file = &self.inner.file;
+        let root_id = self.inner.root.read(&txn_id).await?;
+
+        let mut problems = Vec::new();
+        let mut leaf_depth = None;
+        let mut unvisited = vec![((*root_id).clone(), None, None, 0)];
+
+        while let Some((node_id, lower, upper, depth)) = unvisited.pop() {
+            if !file.contains_block(&txn_id, &node_id).await? {
+                problems.push(format!("{} {}", DANGLING, node_id));
+                continue;
+            }
+
+            let node = file.read_block(txn_id, node_id.clone()).await?;
+            let bounds = (lower.as_deref(), upper.as_deref());
+            for problem in check_node(&node, self.inner.order, &self.inner.collator, bounds) {
+                problems.push(format!("node {}: {}", node_id, problem));
+            }
+
+            if let Some(keys) = keys.as_mut() {
+                keys.extend(
+                    node.keys
+                        .iter()
+                        .filter(|key| !key.deleted)
+                        .map(|key| key.value.to_vec()),
+                );
+            }
+
+            if node.leaf {
+                match leaf_depth {
+                    None => leaf_depth = Some(depth),
+                    Some(expected) if expected != depth => problems.push(format!(
+                        "node {}: leaf at depth {} but expected depth {}",
+                        node_id, depth, expected
+                    )),
+                    Some(_) => {}
+                }
+            } else if node.children.len() == node.keys.len() + 1 {
+                for (i, child_id) in node.children.iter().enumerate() {
+                    let lower = if i == 0 {
+                        lower.clone()
+                    } else {
+                        Some(node.keys[i - 1].value.to_vec())
+                    };
+
+                    let upper = match node.keys.get(i) {
+                        Some(key) => Some(key.value.to_vec()),
+                        None => upper.clone(),
+                    };
+
+                    unvisited.push((child_id.clone(), lower, upper, depth + 1));
+                }
+            }
+        }
+
+        Ok(problems)
     }
 
     fn _delete_range<'a>(
@@ -388,6 +828,163 @@ where
         })
     }
 
+    // Return a read lock on the root node, splitting it first if it's full,
+    // so that an insert can descend from it without splitting it again.
+    async fn root_for_insert(
+        &self,
+        txn_id: TxnId,
+    ) -> TCResult<<F::Block as Block<Node, F>>::ReadLock> {
+        let file = &self.inner.file;
+        let order = self.inner.order;
+
+        // get a write lock on the root_id while we check if a split_child is needed,
+        // to avoid getting out of sync in the case of a concurrent insert in the same txn
+        let mut root_id = self.inner.root.write(txn_id).await?;
+        debug!("insert into BTree with root node ID {}", *root_id);
+
+        let root = file.read_block(txn_id, (*root_id).clone()).await?;
+
+        #[cfg(debug_assertions)]
+        debug!(
+            "root node {} has {} keys and {} children (order is {})",
+            *root_id,
+            root.keys.len(),
+            root.children.len(),
+            order
+        );
+
+        #[cfg(debug_assertions)]
+        debug!("root node {} is {}", *root_id, *root);
+
+        if root.leaf {
+            assert!(root.children.is_empty());
+        } else {
+            assert!(!root.children.is_empty());
+        }
+
+        if root.keys.len() == (2 * order) - 1 {
+            // split_child will need a write lock on root, so release the read lock
+            std::mem::drop(root);
+
+            debug!("split root node");
+
+            let old_root_id = (*root_id).clone();
+
+            (*root_id) = file.unique_id(&txn_id).await?;
+
+            let mut new_root = Node::root(false, order, self.inner.compressed);
+            new_root.children.push(old_root_id.clone());
+
+            let new_root = file
+                .create_block(txn_id, (*root_id).clone(), new_root)
+                .await?;
+
+            let new_root = new_root.write().await;
+            self.split_child(txn_id, old_root_id, new_root, 0).await
+        } else {
+            Ok(root)
+        }
+    }
+
+    /// Insert each of the given `keys`, acquiring a write lock on each leaf node only once for
+    /// each group of keys which belong in it, rather than once for each key.
+    pub async fn insert_all(&self, txn_id: TxnId, keys: Vec<Key>) -> TCResult<()> {
+        let mut keys = keys
+            .into_iter()
+            .map(|key| validate_key(key, &self.inner.schema))
+            .collect::<TCResult<Vec<Key>>>()?;
+
+        let collator = &self.inner.collator;
+        keys.sort_by(|l, r| collator.compare_slice(l, r));
+
+        let mut keys = VecDeque::from(keys);
+        while !keys.is_empty() {
+            let root = self.root_for_insert(txn_id).await?;
+            self._insert_group(txn_id, root, &mut keys, None).await?;
+        }
+
+        Ok(())
+    }
+
+    // Insert the first of the given sorted `keys`, followed by as many of the next `keys` as
+    // belong in the same leaf node (i.e. are less than `bound`) and will fit in it.
+    fn _insert_group<'a>(
+        &'a self,
+        txn_id: TxnId,
+        node: <F::Block as Block<Node, F>>::ReadLock,
+        keys: &'a mut VecDeque<Key>,
+        bound: Option<Key>,
+    ) -> TCBoxTryFuture<'a, ()> {
+        Box::pin(async move {
+            let collator = &self.inner.collator;
+            let file = &self.inner.file;
+            let order = self.inner.order;
+
+            let key = keys.front().expect("key to insert");
+            let i = collator.bisect_left(&node.keys, key);
+            if i < node.keys.len() && collator.compare_slice(&node.keys[i], key) == Ordering::Equal
+            {
+                if node.keys[i].deleted {
+                    let mut node = node.upgrade(file).await?;
+                    node.keys[i].deleted = false;
+                }
+
+                keys.pop_front();
+                return Ok(());
+            }
+
+            if node.leaf {
+                let mut node = node.upgrade(file).await?;
+
+                while let Some(key) = keys.front() {
+                    if node.keys.len() == (2 * order) - 1 {
+                        break;
+                    } else if let Some(bound) = &bound {
+                        if collator.compare_slice(key, bound) != Ordering::Less {
+                            break;
+                        }
+                    }
+
+                    let key = keys.pop_front().expect("key to insert");
+                    let i = collator.bisect_left(&node.keys, &key);
+                    if i < node.keys.len()
+                        && collator.compare_slice(&node.keys[i], &key) == Ordering::Equal
+                    {
+                        node.keys[i].deleted = false;
+                    } else {
+                        node.keys.insert(i, NodeKey::new(key));
+                    }
+                }
+
+                Ok(())
+            } else {
+                let child_id = node.children[i].clone();
+                let child = file.read_block(txn_id, child_id).await?;
+
+                if child.keys.len() == (2 * order) - 1 {
+                    // split_child will need a write lock on child, so drop the read lock
+                    std::mem::drop(child);
+
+                    let child_id = node.children[i].clone();
+                    let node = self
+                        .split_child(txn_id, child_id, node.upgrade(file).await?, i)
+                        .await?;
+
+                    // the split moved a key up into this node, so find the child to descend into again
+                    self._insert_group(txn_id, node, keys, bound).await
+                } else {
+                    let bound = if i < node.keys.len() {
+                        Some(node.keys[i].value.to_vec())
+                    } else {
+                        bound
+                    };
+
+                    self._insert_group(txn_id, child, keys, bound).await
+                }
+            }
+        })
+    }
+
     fn _slice<'a, B: Deref<Target = Node>>(
         self,
         txn_id: TxnId,
@@ -556,7 +1153,7 @@ where
         node.children.insert(i + 1, new_node_id.clone());
         node.keys.insert(i, child.keys.remove(order - 1));
 
-        let mut new_node = Node::new(child.leaf, Some(node_id));
+        let mut new_node = Node::new(child.leaf, Some(node_id), child.compressed);
         new_node.keys = child.keys.drain((order - 1)..).collect();
 
         if child.leaf {
@@ -622,7 +1219,11 @@ where
 
         self.inner
             .file
-            .create_block(txn_id, (*root).clone(), Node::new(true, None))
+            .create_block(
+                txn_id,
+                (*root).clone(),
+                Node::root(true, self.inner.order, self.inner.compressed),
+            )
             .await?;
 
         Ok(())
@@ -631,60 +1232,14 @@ where
     async fn insert(&self, txn_id: TxnId, key: Key) -> TCResult<()> {
         let key = validate_key(key, &self.inner.schema)?;
 
-        let file = &self.inner.file;
-        let order = self.inner.order;
-
-        // get a write lock on the root_id while we check if a split_child is needed,
-        // to avoid getting out of sync in the case of a concurrent insert in the same txn
-        let mut root_id = self.inner.root.write(txn_id).await?;
-        debug!("insert into BTree with root node ID {}", *root_id);
-
-        let root = file.read_block(txn_id, (*root_id).clone()).await?;
-
         #[cfg(debug_assertions)]
         debug!(
-            "insert {} into BTree, root node {} has {} keys and {} children (order is {})",
+            "insert {} into BTree",
             <Tuple<Value> as std::iter::FromIterator<Value>>::from_iter(key.to_vec()),
-            *root_id,
-            root.keys.len(),
-            root.children.len(),
-            order
         );
 
-        #[cfg(debug_assertions)]
-        debug!("root node {} is {}", *root_id, *root);
-
-        if root.leaf {
-            assert!(root.children.is_empty());
-        } else {
-            assert!(!root.children.is_empty());
-        }
-
-        if root.keys.len() == (2 * order) - 1 {
-            // split_child will need a write lock on root, so release the read lock
-            std::mem::drop(root);
-
-            debug!("split root node");
-
-            let old_root_id = (*root_id).clone();
-
-            (*root_id) = file.unique_id(&txn_id).await?;
-
-            let mut new_root = Node::new(false, None);
-            new_root.children.push(old_root_id.clone());
-
-            let new_root = file
-                .create_block(txn_id, (*root_id).clone(), new_root)
-                .await?;
-
-            let new_root = new_root.write().await;
-            let new_root = self.split_child(txn_id, old_root_id, new_root, 0).await?;
-            self._insert(txn_id, new_root, key).await
-        } else {
-            // no need to keep this write lock since we're not splitting the root node
-            std::mem::drop(root_id);
-            self._insert(txn_id, root, key).await
-        }
+        let root = self.root_for_insert(txn_id).await?;
+        self._insert(txn_id, root, key).await
     }
 
     async fn keys<'a>(self, txn_id: TxnId) -> TCResult<TCBoxTryStream<'a, Key>> {
@@ -709,6 +1264,27 @@ impl<F: File<Node> + Transact, D: Dir, T: Transaction<D>> Transact for BTreeFile
     }
 }
 
+#[async_trait]
+impl<F: File<Node> + Rollback, D: Dir, T: Transaction<D>> Rollback for BTreeFile<F, D, T> {
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        try_join(
+            self.inner.file.savepoint(txn_id, ordinal),
+            self.inner.root.savepoint(txn_id, ordinal),
+        )
+        .map_ok(|_| ())
+        .await
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        try_join(
+            self.inner.file.rollback_to(txn_id, ordinal),
+            self.inner.root.rollback_to(txn_id, ordinal),
+        )
+        .map_ok(|_| ())
+        .await
+    }
+}
+
 #[async_trait]
 impl<F: File<Node>, D: Dir, T: Transaction<D>> Persist<D> for BTreeFile<F, D, T> {
     type Schema = RowSchema;
@@ -720,21 +1296,24 @@ impl<F: File<Node>, D: Dir, T: Transaction<D>> Persist<D> for BTreeFile<F, D, T>
     }
 
     async fn load(txn: &T, schema: RowSchema, file: F) -> TCResult<Self> {
-        let order = validate_schema(&schema)?;
+        let default_order = validate_schema(&schema)?;
 
         let txn_id = *txn.id();
         let mut root = None;
         for block_id in file.block_ids(&txn_id).await? {
             let block = file.read_block(txn_id, block_id.clone()).await?;
             if block.parent.is_none() {
-                root = Some(block_id);
+                root = Some((block_id, block.order, block.compressed));
                 break;
             }
         }
 
-        let root = root.ok_or_else(|| TCError::internal("BTree corrupted (missing root block)"))?;
+        let (root, order, compressed) =
+            root.ok_or_else(|| TCError::internal("BTree corrupted (missing root block)"))?;
 
-        Ok(BTreeFile::new(file, schema, order, root))
+        let order = order.unwrap_or(default_order);
+
+        Ok(BTreeFile::new(file, schema, order, compressed, root))
     }
 }
 
@@ -747,6 +1326,18 @@ impl<F: File<Node>, D: Dir, T: Transaction<D>> Restore<D> for BTreeFile<F, D, T>
             ));
         }
 
+        if self.inner.order != backup.inner.order {
+            return Err(TCError::unsupported(
+                "cannot restore a BTree from a backup with a different order",
+            ));
+        }
+
+        if self.inner.compressed != backup.inner.compressed {
+            return Err(TCError::unsupported(
+                "cannot restore a BTree from a backup with a different compression setting",
+            ));
+        }
+
         let mut root_id = self.inner.root.write(txn_id).await?;
         self.inner.file.truncate(txn_id).await?;
         *root_id = backup.inner.root.read(&txn_id).await?.clone();
@@ -813,6 +1404,71 @@ fn validate_schema(schema: &RowSchema) -> TCResult<usize> {
     Ok(order)
 }
 
+const DANGLING: &str = "missing node";
+
+/// Return a description of each problem with the given `node` itself, whose keys must fall
+/// strictly within the given (lower, upper) bounds.
+fn check_node(
+    node: &Node,
+    order: usize,
+    collator: &ValueCollator,
+    bounds: (Option<&[Value]>, Option<&[Value]>),
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if node.keys.len() > (2 * order) - 1 {
+        problems.push(format!(
+            "{} keys exceeds the maximum of {}",
+            node.keys.len(),
+            (2 * order) - 1
+        ));
+    }
+
+    if node.leaf && !node.children.is_empty() {
+        problems.push(format!("leaf has {} children", node.children.len()));
+    } else if !node.leaf && node.children.len() != node.keys.len() + 1 {
+        problems.push(format!(
+            "{} keys but {} children",
+            node.keys.len(),
+            node.children.len()
+        ));
+    }
+
+    for (i, pair) in node.keys.windows(2).enumerate() {
+        if collator.compare_slice(&pair[0], &pair[1]) != Ordering::Less {
+            problems.push(format!("key {} is not less than key {}", i, i + 1));
+        }
+    }
+
+    let (lower, upper) = bounds;
+    for (i, key) in node.keys.iter().enumerate() {
+        if let Some(lower) = lower {
+            if collator.compare_slice(key, lower) != Ordering::Greater {
+                problems.push(format!("key {} is not greater than its parent's key", i));
+            }
+        }
+
+        if let Some(upper) = upper {
+            if collator.compare_slice(key, upper) != Ordering::Less {
+                problems.push(format!("key {} is not less than its parent's key", i));
+            }
+        }
+    }
+
+    problems
+}
+
+fn validate_order(order: usize) -> TCResult<()> {
+    if order < MIN_ORDER {
+        Err(TCError::bad_request(
+            format!("BTree order must be at least {}, not", MIN_ORDER),
+            order,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[inline]
 fn validate_key(key: Key, schema: &RowSchema) -> TCResult<Key> {
     if key.len() != schema.len() {
@@ -827,3 +1483,70 @@ fn validate_key(key: Key, schema: &RowSchema) -> TCResult<Key> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    fn node(compressed: bool) -> Node {
+        let mut node = Node::root(true, MIN_ORDER, compressed);
+        node.keys = (0..64u64)
+            .map(|i| {
+                NodeKey::new(vec![
+                    Value::String(format!("/app/users/account-{:04}", i)),
+                    Value::from(i),
+                ])
+            })
+            .collect();
+
+        node.keys[7].deleted = true;
+        node
+    }
+
+    #[test]
+    fn test_prefix_compression() {
+        let plain = node(false);
+        let compressed = node(true);
+
+        let plain_size = block_on(plain.size()).expect("size");
+        let compressed_size = block_on(compressed.size()).expect("size");
+        assert!(compressed_size < plain_size);
+
+        let encoded = tbon::en::encode(&compressed).expect("encode");
+        let decoded: Node = block_on(tbon::de::try_decode((), encoded)).expect("decode");
+        assert!(decoded.compressed);
+        assert!(decoded.keys == plain.keys);
+    }
+
+    #[test]
+    fn test_check_node() {
+        let collator = ValueCollator::default();
+        let key = |i: u64| NodeKey::new(vec![Value::from(i)]);
+
+        let mut leaf = Node::new(true, None, false);
+        leaf.keys = vec![key(4), key(5), key(6)];
+
+        let lower = [Value::from(3u64)];
+        let upper = [Value::from(7u64)];
+        let bounds = (Some(&lower[..]), Some(&upper[..]));
+        assert!(check_node(&leaf, MIN_ORDER, &collator, bounds).is_empty());
+
+        leaf.keys.swap(0, 1);
+        let problems = check_node(&leaf, MIN_ORDER, &collator, bounds);
+        assert_eq!(problems, vec!["key 0 is not less than key 1"]);
+
+        let upper = [Value::from(5u64)];
+        let bounds = (Some(&lower[..]), Some(&upper[..]));
+        leaf.keys.swap(0, 1);
+        let problems = check_node(&leaf, MIN_ORDER, &collator, bounds);
+        assert_eq!(
+            problems,
+            vec![
+                "key 1 is not less than its parent's key",
+                "key 2 is not less than its parent's key",
+            ]
+        );
+    }
+}
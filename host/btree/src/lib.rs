@@ -16,7 +16,7 @@ use tc_transact::{IntoView, Transaction, TxnId};
 use tc_value::{NumberType, Value, ValueCollator, ValueType};
 use tcgeneric::*;
 
-pub use file::{BTreeFile, Node};
+pub use file::{BTreeFile, Node, MIN_ORDER};
 pub use slice::BTreeSlice;
 
 mod file;
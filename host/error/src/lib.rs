@@ -2,12 +2,15 @@
 //!
 //! This crate is a part of Tinychain: [http://github.com/haydnv/tinychain](http://github.com/haydnv/tinychain)
 
+use std::error::Error;
 use std::fmt;
 
 use destream::{en, EncodeMap, Encoder};
 
 pub type TCResult<T> = Result<T, TCError>;
 
+type Source = Box<dyn Error + Send + Sync + 'static>;
+
 /// The category of a `TCError`.
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum ErrorType {
@@ -23,6 +26,24 @@ pub enum ErrorType {
     Unauthorized,
 }
 
+impl ErrorType {
+    /// The numeric HTTP status code which corresponds to this category of error.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::BadGateway => 502,
+            Self::BadRequest => 400,
+            Self::Conflict => 409,
+            Self::Forbidden => 403,
+            Self::Internal => 500,
+            Self::MethodNotAllowed => 405,
+            Self::NotFound => 404,
+            Self::NotImplemented => 501,
+            Self::Timeout => 408,
+            Self::Unauthorized => 401,
+        }
+    }
+}
+
 impl<'en> en::IntoStream<'en> for ErrorType {
     fn into_stream<E: Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
         format!(
@@ -71,12 +92,17 @@ impl fmt::Display for ErrorType {
 pub struct TCError {
     code: ErrorType,
     message: String,
+    source: Option<Source>,
 }
 
 impl TCError {
     /// Returns a new error with the given code and message.
     pub fn new(code: ErrorType, message: String) -> Self {
-        Self { code, message }
+        Self {
+            code,
+            message,
+            source: None,
+        }
     }
 
     /// Error indicating that the an upstream server send an invalid response.
@@ -84,6 +110,7 @@ impl TCError {
         Self {
             code: ErrorType::BadGateway,
             message: cause.to_string(),
+            source: None,
         }
     }
 
@@ -92,6 +119,7 @@ impl TCError {
         Self {
             code: ErrorType::BadRequest,
             message: format!("{}: {}", message, cause),
+            source: None,
         }
     }
 
@@ -101,6 +129,7 @@ impl TCError {
         Self {
             code: ErrorType::Conflict,
             message: String::default(),
+            source: None,
         }
     }
 
@@ -110,6 +139,7 @@ impl TCError {
         Self {
             code: ErrorType::Forbidden,
             message: format!("{}: {}", message, id),
+            source: None,
         }
     }
 
@@ -119,6 +149,7 @@ impl TCError {
         Self {
             code: ErrorType::Internal,
             message: info.to_string(),
+            source: None,
         }
     }
 
@@ -131,6 +162,7 @@ impl TCError {
         Self {
             code: ErrorType::MethodNotAllowed,
             message: format!("{} endpoint {} does not support {}", subject, path, method),
+            source: None,
         }
     }
 
@@ -139,6 +171,7 @@ impl TCError {
         Self {
             code: ErrorType::NotFound,
             message: locator.to_string(),
+            source: None,
         }
     }
 
@@ -147,6 +180,7 @@ impl TCError {
         Self {
             code: ErrorType::NotImplemented,
             message: feature.to_string(),
+            source: None,
         }
     }
 
@@ -155,6 +189,7 @@ impl TCError {
         Self {
             code: ErrorType::Timeout,
             message: info.to_string(),
+            source: None,
         }
     }
 
@@ -163,6 +198,7 @@ impl TCError {
         Self {
             code: ErrorType::Unauthorized,
             message: format!("invalid credentials: {}", info),
+            source: None,
         }
     }
 
@@ -171,6 +207,23 @@ impl TCError {
         Self {
             code: ErrorType::BadRequest,
             message: info.to_string(),
+            source: None,
+        }
+    }
+
+    /// Attach the lower-level error which caused this error, to be returned by
+    /// [`Error::source`]. The message of this error is not changed.
+    pub fn with_source<E: Error + Send + Sync + 'static>(mut self, source: E) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Convert this error into its structured form,
+    /// encoded as `{"code": <string>, "message": <string>, "status": <number>}`.
+    pub fn describe(self) -> ErrorDescription {
+        ErrorDescription {
+            code: self.code,
+            message: self.message,
         }
     }
 
@@ -186,11 +239,19 @@ impl TCError {
         Self {
             code: self.code,
             message: format!("{}: {}", info, self.message),
+            source: self.source,
         }
     }
 }
 
-impl std::error::Error for TCError {}
+impl Error for TCError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.source {
+            Some(source) => Some(source.as_ref()),
+            None => None,
+        }
+    }
+}
 
 impl<'en> en::ToStream<'en> for TCError {
     fn to_stream<E: Encoder<'en>>(&'en self, encoder: E) -> Result<E::Ok, E::Error> {
@@ -208,12 +269,39 @@ impl<'en> en::IntoStream<'en> for TCError {
     }
 }
 
+/// The structured form of a [`TCError`], for clients which expect a machine-parseable error.
+pub struct ErrorDescription {
+    code: ErrorType,
+    message: String,
+}
+
+impl<'en> en::IntoStream<'en> for ErrorDescription {
+    fn into_stream<E: Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
+        let mut map = encoder.encode_map(Some(3))?;
+        map.encode_entry("code", self.code)?;
+        map.encode_entry("message", self.message)?;
+        map.encode_entry("status", self.code.status())?;
+        map.end()
+    }
+}
+
+impl From<std::io::Error> for TCError {
+    fn from(cause: std::io::Error) -> Self {
+        Self {
+            code: ErrorType::Internal,
+            message: format!("I/O error: {}", cause),
+            source: Some(Box::new(cause)),
+        }
+    }
+}
+
 #[cfg(feature = "tensor")]
 impl From<afarray::ArrayError> for TCError {
     fn from(cause: afarray::ArrayError) -> Self {
         Self {
             code: ErrorType::Internal,
             message: format!("tensor error: {}", cause),
+            source: None,
         }
     }
 }
@@ -226,6 +314,61 @@ impl fmt::Debug for TCError {
 
 impl fmt::Display for TCError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}: {}", self.code, self.message)
+        write!(f, "{}: {}", self.code, self.message)?;
+
+        if f.alternate() {
+            let mut source = Error::source(self);
+            while let Some(cause) = source {
+                write!(f, "\ncaused by: {}", cause)?;
+                source = cause.source();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_request_code() {
+        assert!(TCError::bad_request("x", "y").code() == ErrorType::BadRequest);
+        assert!(TCError::unsupported("x").code() == ErrorType::BadRequest);
+        assert_eq!(TCError::bad_request("x", "y").message(), "x: y");
+        assert_eq!(TCError::bad_request("x", "y").code().status(), 400);
+    }
+
+    #[test]
+    fn test_source() {
+        let cause = std::io::Error::other("disk full");
+        let err = TCError::internal(&cause).with_source(cause);
+        assert_eq!(err.message(), "disk full");
+
+        let err = err.consume("write failed");
+        let source = Error::source(&err).expect("source");
+        assert!(source.downcast_ref::<std::io::Error>().is_some());
+        assert_eq!(
+            format!("{:#}", err),
+            "internal error: write failed: disk full\ncaused by: disk full"
+        );
+
+        assert!(Error::source(&TCError::conflict()).is_none());
+    }
+
+    #[test]
+    fn test_io_error_source() {
+        let err = TCError::from(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "read-only filesystem",
+        ));
+
+        assert!(err.code() == ErrorType::Internal);
+        assert_eq!(err.message(), "I/O error: read-only filesystem");
+
+        let source = Error::source(&err).expect("source");
+        let source = source.downcast_ref::<std::io::Error>().expect("io::Error");
+        assert_eq!(source.kind(), std::io::ErrorKind::PermissionDenied);
     }
 }
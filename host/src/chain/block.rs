@@ -9,19 +9,19 @@ use futures::join;
 use log::debug;
 
 use tc_error::*;
-use tc_transact::fs::{BlockData, Persist, Store};
+use tc_transact::fs::{BlockData, HashAlgo, Persist, Store};
 use tc_transact::{IntoView, Transact};
 use tcgeneric::TCPathBuf;
 
 use crate::fs;
 use crate::route::Public;
-use crate::scalar::{Link, Value};
+use crate::scalar::{Link, Scalar, Value};
 use crate::state::State;
 use crate::transact::Transaction;
 use crate::txn::{Txn, TxnId};
 
 use super::data::History;
-use super::{Chain, ChainInstance, ChainType, Schema, Subject, CHAIN};
+use super::{Chain, ChainInstance, ChainType, ReplicationProgress, Schema, Subject, CHAIN};
 
 /// A [`Chain`] which stores every mutation of its [`Subject`] in a series of `ChainBlock`s
 #[derive(Clone)]
@@ -39,12 +39,61 @@ impl BlockChain {
             history,
         }
     }
+
+    /// Load a `BlockChain` from the given `dir`, or create a new one if `dir` is empty.
+    ///
+    /// The blocks of a new `BlockChain` are linked using the given [`HashAlgo`].
+    /// An existing `BlockChain` must have been created with the same [`HashAlgo`].
+    pub async fn load_with_hash_algo(
+        txn: &Txn,
+        schema: Schema,
+        algo: HashAlgo,
+        dir: fs::Dir,
+    ) -> TCResult<Self> {
+        let is_new = dir.is_empty(txn.id()).await?;
+        let subject = Subject::load(txn, schema.clone(), &dir).await?;
+
+        let history = if is_new {
+            History::create(*txn.id(), dir, ChainType::Block, algo).await?
+        } else {
+            History::load(txn, (), dir).await?
+        };
+
+        if history.hash_algo() != algo {
+            return Err(TCError::bad_request(
+                format!(
+                    "BlockChain was created with hash algorithm {}, not",
+                    history.hash_algo()
+                ),
+                algo,
+            ));
+        }
+
+        history.apply_last(txn, &subject).await?;
+
+        Ok(BlockChain::new(schema, subject, history))
+    }
+
+    /// Append a batch of PUT mutations within a single transaction, acquiring the latest block
+    /// only once and rolling over to a new block if it grows too large mid-batch.
+    pub async fn append_batch(
+        &self,
+        txn_id: TxnId,
+        entries: Vec<(TCPathBuf, Value, Scalar)>,
+    ) -> TCResult<()> {
+        self.history.append_batch(txn_id, entries).await
+    }
+
+    /// Borrow the [`History`] of this `BlockChain`.
+    pub(crate) fn history(&self) -> &History {
+        &self.history
+    }
 }
 
 #[async_trait]
 impl ChainInstance for BlockChain {
-    async fn append_delete(&self, txn_id: TxnId, path: TCPathBuf, key: Value) -> TCResult<()> {
-        self.history.append_delete(txn_id, path, key).await
+    async fn append_delete(&self, txn: &Txn, path: TCPathBuf, key: Value) -> TCResult<()> {
+        self.history.append_delete(*txn.id(), path, key).await
     }
 
     async fn append_put(
@@ -65,7 +114,12 @@ impl ChainInstance for BlockChain {
         &self.subject
     }
 
-    async fn replicate(&self, txn: &Txn, source: Link) -> TCResult<()> {
+    async fn replicate(
+        &self,
+        txn: &Txn,
+        source: Link,
+        progress: &ReplicationProgress,
+    ) -> TCResult<()> {
         let chain = match txn.get(source.append(CHAIN.into()), Value::None).await? {
             State::Chain(Chain::Block(chain)) => chain,
             other => {
@@ -77,7 +131,7 @@ impl ChainInstance for BlockChain {
         };
 
         self.history
-            .replicate(txn, &self.subject, chain.history)
+            .replicate(txn, &self.subject, chain.history, progress)
             .await
     }
 
@@ -112,18 +166,7 @@ impl Persist<fs::Dir> for BlockChain {
     }
 
     async fn load(txn: &Txn, schema: Schema, dir: fs::Dir) -> TCResult<Self> {
-        let is_new = dir.is_empty(txn.id()).await?;
-        let subject = Subject::load(txn, schema.clone(), &dir).await?;
-
-        let history = if is_new {
-            History::create(*txn.id(), dir, ChainType::Block).await?
-        } else {
-            History::load(txn, (), dir).await?
-        };
-
-        history.apply_last(txn, &subject).await?;
-
-        Ok(BlockChain::new(schema, subject, history))
+        Self::load_with_hash_algo(txn, schema, HashAlgo::default(), dir).await
     }
 }
 
@@ -204,7 +247,7 @@ async fn validate(txn: Txn, schema: Schema, history: History) -> TCResult<BlockC
                 let result = match mutation {
                     Mutation::Delete(path, key) => {
                         debug!("replay DELETE op: {}: {}", path, key);
-                        subject.delete(&txn, &path, key).await
+                        subject.delete(&txn, &path, key).await.map(|_| ())
                     }
                     Mutation::Put(path, key, value) => {
                         debug!("replay PUT op: {}: {} <- {}", path, key, value);
@@ -1,13 +1,15 @@
 use std::collections::btree_map::{BTreeMap, Entry};
+use std::collections::VecDeque;
 use std::fmt;
 use std::iter::FromIterator;
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use destream::{de, en};
-use futures::TryFutureExt;
+use futures::{future, TryFutureExt, TryStreamExt};
 use log::debug;
 
+use tc_error::*;
 use tc_transact::fs::BlockData;
 use tc_transact::TxnId;
 use tcgeneric::{TCPathBuf, Tuple};
@@ -151,6 +153,29 @@ impl ChainBlock {
         self.append(txn_id, Mutation::Put(path, key, value))
     }
 
+    /// Move `entries` from the front of the given queue into this `ChainBlock` until it's empty
+    /// or the encoded size of this block reaches `max_size`, whichever comes first.
+    pub async fn append_batch(
+        &mut self,
+        txn_id: TxnId,
+        entries: &mut VecDeque<Mutation>,
+        max_size: u64,
+    ) -> TCResult<()> {
+        let mut size = self.size().await?;
+
+        while size < max_size {
+            let mutation = match entries.pop_front() {
+                Some(mutation) => mutation,
+                None => break,
+            };
+
+            size += encoded_size(&mutation).await?;
+            self.append(txn_id, mutation);
+        }
+
+        Ok(())
+    }
+
     /// Delete all mutations listed in this `ChainBlock` prior to the given `TxnId`.
     pub fn clear_until(&mut self, txn_id: &TxnId) {
         let old_txn_ids: Vec<TxnId> = self
@@ -239,3 +264,56 @@ impl fmt::Display for ChainBlock {
         )
     }
 }
+
+async fn encoded_size(mutation: &Mutation) -> TCResult<u64> {
+    let encoded =
+        tbon::en::encode(mutation).map_err(|e| TCError::bad_request("serialization error", e))?;
+
+    encoded
+        .map_err(|e| TCError::bad_request("serialization error", e))
+        .try_fold(0, |size, chunk| {
+            future::ready(Ok(size + chunk.len() as u64))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use tcgeneric::NetworkTime;
+
+    use super::*;
+
+    #[test]
+    fn test_append_batch_spans_blocks() {
+        let txn_id = TxnId::new(NetworkTime::now());
+        let path: TCPathBuf = "/subject".parse().unwrap();
+
+        let batch = (0..100u64)
+            .map(|i| Mutation::Put(path.clone(), Value::from(i), Value::from(i).into()))
+            .collect::<Vec<Mutation>>();
+
+        let mut first = ChainBlock::new(Bytes::new());
+        let max_size =
+            block_on(first.size()).unwrap() + block_on(encoded_size(&batch[0])).unwrap() * 50;
+
+        let mut entries = batch.iter().cloned().collect::<VecDeque<Mutation>>();
+        block_on(first.append_batch(txn_id, &mut entries, max_size)).unwrap();
+
+        assert!(!entries.is_empty());
+        assert!(block_on(first.size()).unwrap() >= max_size);
+
+        let mut second = ChainBlock::new(Bytes::new());
+        block_on(second.append_batch(txn_id, &mut entries, max_size)).unwrap();
+        assert!(entries.is_empty());
+
+        let recorded = first.mutations()[&txn_id]
+            .iter()
+            .chain(&second.mutations()[&txn_id])
+            .cloned()
+            .collect::<Vec<Mutation>>();
+
+        assert_eq!(recorded, batch);
+    }
+}
@@ -1,6 +1,7 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
 use std::iter::FromIterator;
+use std::path::PathBuf;
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -17,16 +18,18 @@ use tc_table::TableInstance;
 use tc_tensor::TensorAccess;
 use tc_transact::fs::*;
 use tc_transact::lock::TxnLock;
-use tc_transact::{IntoView, Transact, Transaction, TxnId};
+use tc_transact::{IntoView, Rollback, Transact, Transaction, TxnId};
 use tcgeneric::{
     label, Id, Instance, Label, Map, NativeClass, TCBoxStream, TCBoxTryStream, TCPathBuf, Tuple,
 };
 
-use crate::chain::{ChainType, Subject, CHAIN, NULL_HASH};
+use crate::chain::{
+    ChainType, ReplicationProgress, Subject, BLOCK_SIZE, CHAIN, HASH_ALGO, NULL_HASH,
+};
 use crate::collection::*;
 use crate::fs;
 use crate::route::Public;
-use crate::scalar::{OpRef, Scalar, TCRef, Value};
+use crate::scalar::{OpRef, Scalar, TCRef, Value, ValueType};
 use crate::state::{State, StateView};
 use crate::txn::Txn;
 
@@ -39,22 +42,54 @@ pub struct History {
     dir: fs::Dir,
     file: fs::File<ChainBlock>,
     latest: TxnLock<u64>,
+    algo: HashAlgo,
 }
 
 impl History {
-    fn new(latest: u64, dir: fs::Dir, file: fs::File<ChainBlock>) -> Self {
+    fn new(latest: u64, dir: fs::Dir, file: fs::File<ChainBlock>, algo: HashAlgo) -> Self {
         let latest = TxnLock::new("latest block ordinal", latest);
-        Self { dir, latest, file }
+        Self {
+            dir,
+            latest,
+            file,
+            algo,
+        }
     }
 
-    pub async fn create(txn_id: TxnId, dir: fs::Dir, class: ChainType) -> TCResult<Self> {
+    pub async fn create(
+        txn_id: TxnId,
+        dir: fs::Dir,
+        class: ChainType,
+        algo: HashAlgo,
+    ) -> TCResult<Self> {
         let file: fs::File<ChainBlock> = dir.create_file(txn_id, CHAIN.into(), class).await?;
         file.create_block(txn_id, 0u64.into(), ChainBlock::new(NULL_HASH))
             .await?;
 
+        if algo != HashAlgo::default() {
+            let algo_file: fs::File<Value> = dir
+                .create_file(txn_id, HASH_ALGO.into(), ValueType::String)
+                .await?;
+
+            let name = Value::String(algo.name().to_string());
+            algo_file
+                .create_block(txn_id, HASH_ALGO.into(), name)
+                .await?;
+        }
+
         let dir = dir.create_dir(txn_id, DATA.into()).await?;
 
-        Ok(Self::new(0, dir, file))
+        Ok(Self::new(0, dir, file, algo))
+    }
+
+    /// The path of this `History` on the filesystem, which uniquely identifies its `Chain`.
+    pub fn path(&self) -> &PathBuf {
+        self.file.path()
+    }
+
+    /// The [`HashAlgo`] used to link the blocks in this `History`.
+    pub fn hash_algo(&self) -> HashAlgo {
+        self.algo
     }
 
     pub async fn append_delete(&self, txn_id: TxnId, path: TCPathBuf, key: Value) -> TCResult<()> {
@@ -85,6 +120,51 @@ impl History {
         Ok(())
     }
 
+    /// Append a batch of PUT mutations to this `History`, acquiring the latest block only once.
+    ///
+    /// If the latest block grows past `BLOCK_SIZE` mid-batch, the remaining entries are
+    /// appended to a new block. Note that a savepoint only tracks the mutations in the latest block,
+    /// so a savepoint created before such a batch cannot roll back the entries in earlier blocks.
+    pub async fn append_batch(
+        &self,
+        txn_id: TxnId,
+        entries: Vec<(TCPathBuf, Value, Scalar)>,
+    ) -> TCResult<()> {
+        debug!(
+            "History::append_batch {} ({} entries)",
+            txn_id,
+            entries.len()
+        );
+
+        let mut entries = entries
+            .into_iter()
+            .map(|(path, key, value)| Mutation::Put(path, key, value))
+            .collect::<VecDeque<Mutation>>();
+
+        let mut latest = self.latest.write(txn_id).await?;
+        let mut block = self.write_block(txn_id, *latest).await?;
+
+        loop {
+            block.append_batch(txn_id, &mut entries, BLOCK_SIZE).await?;
+
+            if entries.is_empty() {
+                break Ok(());
+            }
+
+            let hash = block.hash_with(self.algo).await?;
+            std::mem::drop(block);
+
+            (*latest) += 1;
+            debug!("creating next chain block {} mid-batch", *latest);
+
+            self.file
+                .create_block(txn_id, (*latest).into(), ChainBlock::new(hash))
+                .await?;
+
+            block = self.write_block(txn_id, *latest).await?;
+        }
+    }
+
     async fn save_state(&self, txn: &Txn, state: State) -> TCResult<Scalar> {
         if state.is_ref() {
             return Err(TCError::bad_request(
@@ -128,7 +208,7 @@ impl History {
                         debug!("Table with hash {} is already saved", hash);
                     } else {
                         let dir = self.dir.create_dir(txn_id, hash.clone()).await?;
-                        TableIndex::copy_from(table, dir, txn).await?;
+                        TableIndex::copy_from(*table, dir, txn).await?;
                         debug!("saved Table with hash {}", hash);
                     }
 
@@ -208,7 +288,7 @@ impl History {
     pub async fn create_next_block(&self, txn_id: TxnId) -> TCResult<fs::Block<ChainBlock>> {
         let mut latest = self.latest.write(txn_id).await?;
         let last_block = self.read_block(txn_id, (*latest).into()).await?;
-        let hash = last_block.hash().await?;
+        let hash = last_block.hash_with(self.algo).await?;
         let block = ChainBlock::new(hash);
 
         (*latest) += 1;
@@ -257,7 +337,9 @@ impl History {
         if let Some((last_txn_id, ops)) = last_block.mutations().iter().last() {
             for op in ops {
                 let result = match op {
-                    Mutation::Delete(path, key) => subject.delete(txn, path, key.clone()).await,
+                    Mutation::Delete(path, key) => {
+                        subject.delete(txn, path, key.clone()).await.map(|_| ())
+                    }
                     Mutation::Put(path, key, value) => {
                         self.resolve(txn, value.clone())
                             .and_then(|value| subject.put(txn, path, key.clone(), value))
@@ -277,7 +359,13 @@ impl History {
         Ok(())
     }
 
-    pub async fn replicate(&self, txn: &Txn, subject: &Subject, other: Self) -> TCResult<()> {
+    pub async fn replicate(
+        &self,
+        txn: &Txn,
+        subject: &Subject,
+        other: Self,
+        progress: &ReplicationProgress,
+    ) -> TCResult<()> {
         debug!("replicate chain history");
 
         let txn_id = *txn.id();
@@ -301,6 +389,8 @@ impl History {
             }
         }
 
+        progress.extend(*other_latest - *latest + 1);
+
         let mut i = *latest;
         loop {
             debug!("copy history from block {}", i);
@@ -319,7 +409,7 @@ impl History {
                                 dest.append_delete(*past_txn_id, path.clone(), key.clone());
                             }
 
-                            subject.delete(txn, &path, key).await
+                            subject.delete(txn, &path, key).await.map(|_| ())
                         }
                         Mutation::Put(path, key, value) => {
                             let value = other.resolve(txn, value).await?;
@@ -342,7 +432,8 @@ impl History {
                 }
             }
 
-            let (source_hash, dest_hash) = try_join!(source.hash(), dest.hash())?;
+            let (source_hash, dest_hash) =
+                try_join!(source.hash_with(other.algo), dest.hash_with(other.algo))?;
             if source_hash != dest_hash {
                 debug!("source {:?}", &*source);
                 debug!("dest {:?}", &*dest);
@@ -353,6 +444,7 @@ impl History {
                 ));
             }
 
+            progress.advance();
             i += 1;
 
             if other.contains_block(txn.id(), i).await? {
@@ -437,7 +529,7 @@ impl History {
                 })?;
 
                 let table = TableIndex::load(txn, schema, dir).await?;
-                Ok(Collection::Table(table.into()))
+                Ok(Collection::from(table))
             }
 
             #[cfg(feature = "tensor")]
@@ -494,6 +586,20 @@ impl Persist<fs::Dir> for History {
             .await?
             .ok_or_else(|| TCError::internal("Chain has no history file"))?;
 
+        let algo = if let Some(algo_file) = dir
+            .get_file::<fs::File<Value>>(txn_id, &HASH_ALGO.into())
+            .await?
+        {
+            let name = algo_file.read_block(*txn_id, HASH_ALGO.into()).await?;
+            match &*name {
+                Value::String(name) => HashAlgo::from_name(name),
+                _ => None,
+            }
+            .ok_or_else(|| TCError::internal(format!("invalid Chain hash algorithm: {}", &*name)))?
+        } else {
+            HashAlgo::default()
+        };
+
         let dir = dir
             .get_dir(txn_id, &DATA.into())
             .await?
@@ -505,7 +611,7 @@ impl Persist<fs::Dir> for History {
         loop {
             let block = file.read_block(*txn_id, latest.into()).await?;
             if block.last_hash() == &last_hash {
-                last_hash = block.last_hash().clone();
+                last_hash = block.hash_with(algo).await?;
             } else {
                 return Err(TCError::internal(format!(
                     "block {} hash does not match previous block",
@@ -520,7 +626,7 @@ impl Persist<fs::Dir> for History {
             }
         }
 
-        Ok(History::new(latest, dir, file))
+        Ok(History::new(latest, dir, file, algo))
     }
 }
 
@@ -535,6 +641,27 @@ impl Transact for History {
     }
 }
 
+#[async_trait]
+impl Rollback for History {
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        try_join!(
+            self.file.savepoint(txn_id, ordinal),
+            self.latest.savepoint(txn_id, ordinal)
+        )?;
+
+        Ok(())
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        try_join!(
+            self.file.rollback_to(txn_id, ordinal),
+            self.latest.rollback_to(txn_id, ordinal)
+        )?;
+
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl de::FromStream for History {
     type Context = Txn;
@@ -575,7 +702,7 @@ impl de::Visitor for HistoryVisitor {
             .map_err(de::Error::custom)
             .await?;
 
-        let history = History::new(0, dir, file);
+        let history = History::new(0, dir, file, HashAlgo::default());
 
         let subcontext = |i: u64| self.txn.subcontext(i.into()).map_err(de::Error::custom);
 
@@ -3,17 +3,21 @@
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::iter::FromIterator;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use destream::{de, en};
 use futures::future::TryFutureExt;
+use futures::try_join;
 use log::debug;
 use safecast::{CastInto, TryCastFrom, TryCastInto};
+use tokio::io::{AsyncRead, AsyncWrite};
 
 use tc_btree::{BTreeType, Column};
 use tc_error::*;
-use tc_transact::fs::{Dir, File, Persist, Restore, Store};
-use tc_transact::{IntoView, Transact, Transaction, TxnId};
+use tc_transact::fs::{migrate_from, Dir, File, HashAlgo, Persist, Restore, Store};
+use tc_transact::{IntoView, Rollback, Transact, Transaction, TxnId};
 use tcgeneric::*;
 
 use crate::collection::{
@@ -32,6 +36,8 @@ pub use block::BlockChain;
 pub use data::ChainBlock;
 pub use sync::SyncChain;
 
+use data::History;
+
 mod block;
 mod data;
 
@@ -39,12 +45,17 @@ mod sync;
 
 const BLOCK_SIZE: u64 = 1_000_000;
 const CHAIN: Label = label("chain");
+const HASH_ALGO: Label = label("hash_algo");
 const NULL_HASH: Vec<u8> = vec![];
 const PREFIX: PathLabel = path_label(&["state", "chain"]);
+const SCHEMA: Label = label("schema");
 
 /// The name of the file containing a [`Chain`]'s [`Subject`]'s data.
 pub const SUBJECT: Label = label("subject");
 
+/// The name of the file recording the schema version of a [`Chain`]'s [`Subject`].
+pub const SCHEMA_VERSION: Label = label("schema_version");
+
 /// The file extension of a directory of [`ChainBlock`]s on disk.
 pub const EXT: &str = "chain";
 
@@ -52,7 +63,7 @@ pub const EXT: &str = "chain";
 #[derive(Clone)]
 pub enum Schema {
     BTree(tc_btree::RowSchema),
-    Table(tc_table::TableSchema),
+    Table(Box<tc_table::TableSchema>),
     #[cfg(feature = "tensor")]
     Dense(tc_tensor::Schema),
     #[cfg(feature = "tensor")]
@@ -88,7 +99,7 @@ impl Schema {
                                     TCError::bad_request("invalid Table schema", s)
                                 })?;
 
-                                Ok(Self::Table(schema))
+                                Ok(Self::Table(Box::new(schema)))
                             }
 
                             #[cfg(feature = "tensor")]
@@ -115,6 +126,31 @@ impl Schema {
             other => Err(TCError::bad_request("invalid Chain schema", other)),
         }
     }
+
+    /// Parse a `Schema` from the given `scalar`, which is either the schema of a [`Chain`]'s
+    /// subject, or a [`Map`] of that `schema` and the name of the [`HashAlgo`] (`hash_algo`)
+    /// used to link the blocks of a [`BlockChain`].
+    pub fn from_scalar_with_hash_algo(scalar: Scalar) -> TCResult<(Self, HashAlgo)> {
+        match scalar {
+            Scalar::Map(mut params) => {
+                let schema = params
+                    .remove(&SCHEMA.into())
+                    .ok_or_else(|| TCError::bad_request("missing Chain parameter", SCHEMA))?;
+
+                let algo: Value = params.require(&HASH_ALGO.into())?;
+                params.expect_empty()?;
+
+                let algo = match &algo {
+                    Value::String(name) => HashAlgo::from_name(name),
+                    _ => None,
+                }
+                .ok_or_else(|| TCError::bad_request("unknown hash algorithm", algo))?;
+
+                Self::from_scalar(schema).map(|schema| (schema, algo))
+            }
+            scalar => Self::from_scalar(scalar).map(|schema| (schema, HashAlgo::default())),
+        }
+    }
 }
 
 #[async_trait]
@@ -139,7 +175,7 @@ impl<'en> en::IntoStream<'en> for Schema {
             }
             Self::Table(schema) => {
                 let mut map = encoder.encode_map(Some(1))?;
-                map.encode_entry(TableType::default().path(), (schema,))?;
+                map.encode_entry(TableType::default().path(), (*schema,))?;
                 map.end()
             }
             #[cfg(feature = "tensor")]
@@ -193,7 +229,7 @@ impl Subject {
                     .await
             }
             Schema::Table(schema) => {
-                TableIndex::create(dir, schema, txn_id)
+                TableIndex::create(dir, *schema, txn_id)
                     .map_ok(Self::Table)
                     .await
             }
@@ -230,6 +266,13 @@ impl Subject {
     }
 
     async fn load(txn: &Txn, schema: Schema, dir: &fs::Dir) -> TCResult<Self> {
+        let is_new = dir.is_empty(txn.id()).await?;
+        let subject = Self::load_subject(txn, schema, dir).await?;
+        subject.check_version(txn, dir, is_new).await?;
+        Ok(subject)
+    }
+
+    async fn load_subject(txn: &Txn, schema: Schema, dir: &fs::Dir) -> TCResult<Self> {
         match schema {
             Schema::BTree(schema) => {
                 if let Some(file) = dir.get_file(txn.id(), &SUBJECT.into()).await? {
@@ -242,7 +285,7 @@ impl Subject {
                 if dir.is_empty(txn.id()).await? {
                     Self::create(Schema::Table(schema), dir, *txn.id()).await
                 } else {
-                    TableIndex::load(txn, schema, dir.clone())
+                    TableIndex::load(txn, *schema, dir.clone())
                         .map_ok(Self::Table)
                         .await
                 }
@@ -277,6 +320,20 @@ impl Subject {
         }
     }
 
+    /// Migrate this `Subject` if the schema version recorded in `dir` is out of date.
+    async fn check_version(&self, txn: &Txn, dir: &fs::Dir, is_new: bool) -> TCResult<()> {
+        match self {
+            Self::BTree(btree) => check_version(btree, txn, dir, is_new).await,
+            Self::Table(table) => check_version(table, txn, dir, is_new).await,
+            #[cfg(feature = "tensor")]
+            Self::Dense(tensor) => check_version(tensor, txn, dir, is_new).await,
+            #[cfg(feature = "tensor")]
+            Self::Sparse(tensor) => check_version(tensor, txn, dir, is_new).await,
+            // a Value has no persisted format to migrate
+            Self::Value(_) => Ok(()),
+        }
+    }
+
     async fn restore(&self, txn: &Txn, backup: State) -> TCResult<()> {
         let txn_id = *txn.id();
         match self {
@@ -287,9 +344,10 @@ impl Subject {
                 other => Err(TCError::bad_request("cannot restore a BTree from", other)),
             },
             Self::Table(table) => match backup {
-                State::Collection(Collection::Table(Table::Table(backup))) => {
-                    table.restore(&backup, txn_id).await
-                }
+                State::Collection(Collection::Table(backup)) => match *backup {
+                    Table::Table(backup) => table.restore(&backup, txn_id).await,
+                    other => Err(TCError::bad_request("cannot restore a Table from", other)),
+                },
                 other => Err(TCError::bad_request("cannot restore a Table from", other)),
             },
             #[cfg(feature = "tensor")]
@@ -362,6 +420,35 @@ impl Transact for Subject {
     }
 }
 
+#[async_trait]
+impl Rollback for Subject {
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        match self {
+            Self::BTree(btree) => btree.savepoint(txn_id, ordinal).await,
+            Self::Table(table) => table.savepoint(txn_id, ordinal).await,
+            #[cfg(feature = "tensor")]
+            Self::Dense(tensor) => tensor.savepoint(txn_id, ordinal).await,
+            #[cfg(feature = "tensor")]
+            Self::Sparse(tensor) => tensor.savepoint(txn_id, ordinal).await,
+            Self::Value(file) => file.savepoint(txn_id, ordinal).await,
+        }
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        debug!("roll back chain subject");
+
+        match self {
+            Self::BTree(btree) => btree.rollback_to(txn_id, ordinal).await,
+            Self::Table(table) => table.rollback_to(txn_id, ordinal).await,
+            #[cfg(feature = "tensor")]
+            Self::Dense(tensor) => tensor.rollback_to(txn_id, ordinal).await,
+            #[cfg(feature = "tensor")]
+            Self::Sparse(tensor) => tensor.rollback_to(txn_id, ordinal).await,
+            Self::Value(file) => file.rollback_to(txn_id, ordinal).await,
+        }
+    }
+}
+
 #[async_trait]
 impl de::FromStream for Subject {
     type Context = Txn;
@@ -404,6 +491,54 @@ impl<'en> IntoView<'en, fs::Dir> for Subject {
     }
 }
 
+/// Migrate the given `state` if the schema version recorded in `dir` is older than its
+/// [`Persist::SCHEMA_VERSION`], recording the new version. The version is only locked for
+/// writing if a migration is needed.
+async fn check_version<P>(state: &P, txn: &Txn, dir: &fs::Dir, is_new: bool) -> TCResult<()>
+where
+    P: Persist<fs::Dir, Txn = Txn> + Sync,
+{
+    let txn_id = *txn.id();
+    let current = P::SCHEMA_VERSION;
+
+    let file: fs::File<Value> =
+        if let Some(file) = dir.get_file(&txn_id, &SCHEMA_VERSION.into()).await? {
+            file
+        } else {
+            // a subject persisted before its schema version was recorded has version 1
+            let stored = Value::from(if is_new { current } else { 1 } as u64);
+            let file: fs::File<Value> = dir
+                .create_file(txn_id, SCHEMA_VERSION.into(), stored.class())
+                .await?;
+
+            file.create_block(txn_id, SCHEMA_VERSION.into(), stored)
+                .await?;
+
+            file
+        };
+
+    let stored = {
+        let version = file.read_block(txn_id, SCHEMA_VERSION.into()).await?;
+        u64::try_cast_from((*version).clone(), |v| {
+            TCError::internal(format!("invalid schema version: {}", v))
+        })?
+    };
+
+    if let Some(from_version) = migrate_from(stored as u32, current)? {
+        debug!(
+            "migrating Chain subject from schema version {} to {}",
+            from_version, current
+        );
+
+        state.migrate(from_version, txn).await?;
+
+        let mut version = file.write_block(txn_id, SCHEMA_VERSION.into()).await?;
+        *version = Value::from(current as u64);
+    }
+
+    Ok(())
+}
+
 impl fmt::Display for Subject {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -418,11 +553,60 @@ impl fmt::Display for Subject {
     }
 }
 
+/// A handle to track the progress of a [`Chain`] replicating from another, in blocks.
+///
+/// A clone of a `ReplicationProgress` shares its counts with the original, so that the same
+/// progress can be reported while replication is still underway.
+#[derive(Clone, Default)]
+pub struct ReplicationProgress {
+    current: Arc<AtomicU64>,
+    target: Arc<AtomicU64>,
+}
+
+impl ReplicationProgress {
+    /// Add the given number of `blocks` to the number which must be replicated.
+    pub fn extend(&self, blocks: u64) {
+        self.target.fetch_add(blocks, Ordering::SeqCst);
+    }
+
+    /// Record that one more block has been replicated.
+    pub fn advance(&self) {
+        self.current.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Return the number of blocks replicated so far and the number to replicate in total.
+    pub fn status(&self) -> (u64, u64) {
+        (
+            self.current.load(Ordering::SeqCst),
+            self.target.load(Ordering::SeqCst),
+        )
+    }
+
+    /// Return the fraction of blocks replicated so far, or 1 if there are none to replicate.
+    pub fn fraction(&self) -> f64 {
+        match self.status() {
+            (_, 0) => 1.,
+            (current, target) => current as f64 / target as f64,
+        }
+    }
+}
+
+impl From<(u64, u64)> for ReplicationProgress {
+    fn from(status: (u64, u64)) -> Self {
+        let (current, target) = status;
+
+        Self {
+            current: Arc::new(AtomicU64::new(current)),
+            target: Arc::new(AtomicU64::new(target)),
+        }
+    }
+}
+
 /// Trait defining methods common to any instance of a [`Chain`], such as a [`SyncChain`].
 #[async_trait]
 pub trait ChainInstance {
     /// Append the given DELETE op to the latest block in this `Chain`.
-    async fn append_delete(&self, txn_id: TxnId, path: TCPathBuf, key: Value) -> TCResult<()>;
+    async fn append_delete(&self, txn: &Txn, path: TCPathBuf, key: Value) -> TCResult<()>;
 
     /// Append the given PUT op to the latest block in this `Chain`.
     async fn append_put(
@@ -438,8 +622,14 @@ pub trait ChainInstance {
     /// Borrow the [`Subject`] of this [`Chain`] immutably.
     fn subject(&self) -> &Subject;
 
-    /// Replicate this [`Chain`] from the [`Chain`] at the given [`Link`].
-    async fn replicate(&self, txn: &Txn, source: Link) -> TCResult<()>;
+    /// Replicate this [`Chain`] from the [`Chain`] at the given [`Link`],
+    /// reporting each block replicated to the given [`ReplicationProgress`].
+    async fn replicate(
+        &self,
+        txn: &Txn,
+        source: Link,
+        progress: &ReplicationProgress,
+    ) -> TCResult<()>;
 
     async fn write_ahead(&self, txn_id: &TxnId);
 }
@@ -550,6 +740,83 @@ pub enum Chain {
     Sync(sync::SyncChain),
 }
 
+impl Chain {
+    fn history(&self) -> &History {
+        match self {
+            Self::Block(chain) => chain.history(),
+            Self::Sync(chain) => chain.history(),
+        }
+    }
+
+    /// Return `true` if `self` and `other` are handles to the same `Chain`.
+    pub fn is(&self, other: &Self) -> bool {
+        self.history().path() == other.history().path()
+    }
+
+    /// Bring the given read-only `index` of this `Chain`'s [`Table`] subject up to date,
+    /// where `since` is the transaction as of which the `index` was taken.
+    ///
+    /// If the subject tracks its changes, only the [`tc_table::Change`]s made after `since` are
+    /// applied to the `index`. Otherwise, or if `since` is not given, the `index` is rebuilt.
+    pub async fn refresh_index(
+        &self,
+        txn: &Txn,
+        index: Table,
+        since: Option<TxnId>,
+    ) -> TCResult<Table> {
+        let table = match self.subject() {
+            Subject::Table(table) => table.clone(),
+            other => {
+                return Err(TCError::bad_request(
+                    "only the index of a Table can be refreshed, not",
+                    other,
+                ))
+            }
+        };
+
+        let mut index = match index {
+            Table::ROIndex(index) => index,
+            other => {
+                return Err(TCError::bad_request(
+                    "expected a read-only Table index but found",
+                    other,
+                ))
+            }
+        };
+
+        let changes = match since {
+            Some(since) if Persist::schema(&table).tracks_changes() => {
+                Some(table.changes(*txn.id(), Some(since)).await?)
+            }
+            _ => None,
+        };
+
+        index.refresh(table, changes, txn.clone()).await?;
+        Ok(Table::ROIndex(index))
+    }
+
+    /// Write this `Chain`, including its history and subject, to the given `writer`
+    /// as a single portable archive which can be restored with [`Chain::import_archive`].
+    pub async fn export_archive<W: AsyncWrite + Send + Unpin>(
+        &self,
+        txn: &Txn,
+        writer: W,
+    ) -> TCResult<()> {
+        let view = self.clone().into_view(txn.clone()).await?;
+        fs::write_archive(view, writer).await
+    }
+
+    /// Restore a `Chain` from an archive written by [`Chain::export_archive`],
+    /// storing its data in a new subdirectory of the given transaction's context.
+    pub async fn import_archive<R: AsyncRead + Send + Unpin>(
+        reader: R,
+        txn: &Txn,
+    ) -> TCResult<Self> {
+        let txn = txn.subcontext_tmp().await?;
+        fs::read_archive(txn, reader).await
+    }
+}
+
 impl Instance for Chain {
     type Class = ChainType;
 
@@ -563,10 +830,13 @@ impl Instance for Chain {
 
 #[async_trait]
 impl ChainInstance for Chain {
-    async fn append_delete(&self, txn_id: TxnId, path: TCPathBuf, key: Value) -> TCResult<()> {
+    async fn append_delete(&self, txn: &Txn, path: TCPathBuf, key: Value) -> TCResult<()> {
+        // every logged write passes through here, so this is where to mark any open savepoints
+        txn.before_write(self).await?;
+
         match self {
-            Self::Block(chain) => chain.append_delete(txn_id, path, key).await,
-            Self::Sync(chain) => chain.append_delete(txn_id, path, key).await,
+            Self::Block(chain) => chain.append_delete(txn, path, key).await,
+            Self::Sync(chain) => chain.append_delete(txn, path, key).await,
         }
     }
 
@@ -577,6 +847,8 @@ impl ChainInstance for Chain {
         key: Value,
         value: State,
     ) -> TCResult<()> {
+        txn.before_write(self).await?;
+
         match self {
             Self::Block(chain) => chain.append_put(txn, path, key, value).await,
             Self::Sync(chain) => chain.append_put(txn, path, key, value).await,
@@ -597,10 +869,15 @@ impl ChainInstance for Chain {
         }
     }
 
-    async fn replicate(&self, txn: &Txn, source: Link) -> TCResult<()> {
+    async fn replicate(
+        &self,
+        txn: &Txn,
+        source: Link,
+        progress: &ReplicationProgress,
+    ) -> TCResult<()> {
         match self {
-            Self::Block(chain) => chain.replicate(txn, source).await,
-            Self::Sync(chain) => chain.replicate(txn, source).await,
+            Self::Block(chain) => chain.replicate(txn, source, progress).await,
+            Self::Sync(chain) => chain.replicate(txn, source, progress).await,
         }
     }
 
@@ -629,6 +906,27 @@ impl Transact for Chain {
     }
 }
 
+#[async_trait]
+impl Rollback for Chain {
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        try_join!(
+            self.subject().savepoint(txn_id, ordinal),
+            self.history().savepoint(txn_id, ordinal)
+        )?;
+
+        Ok(())
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        try_join!(
+            self.subject().rollback_to(txn_id, ordinal),
+            self.history().rollback_to(txn_id, ordinal)
+        )?;
+
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl de::FromStream for Chain {
     type Context = Txn;
@@ -702,10 +1000,16 @@ impl<'en> en::IntoStream<'en> for ChainView<'en> {
 }
 
 /// Load a [`Chain`] from disk.
-pub async fn load(txn: &Txn, class: ChainType, schema: Schema, dir: fs::Dir) -> TCResult<Chain> {
+pub async fn load(
+    txn: &Txn,
+    class: ChainType,
+    schema: Schema,
+    algo: HashAlgo,
+    dir: fs::Dir,
+) -> TCResult<Chain> {
     match class {
         ChainType::Block => {
-            BlockChain::load(txn, schema, dir)
+            BlockChain::load_with_hash_algo(txn, schema, algo, dir)
                 .map_ok(Chain::Block)
                 .await
         }
@@ -760,3 +1064,108 @@ impl de::Visitor for ChainVisitor {
         self.visit_map_value(class, &mut map).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use crate::collection::tests::new_txn;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Versioned {
+        migrated_from: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Persist<fs::Dir> for Versioned {
+        type Schema = ();
+        type Store = fs::Dir;
+        type Txn = Txn;
+
+        const SCHEMA_VERSION: u32 = 2;
+
+        fn schema(&self) -> &() {
+            &()
+        }
+
+        async fn load(_txn: &Txn, _schema: (), _store: fs::Dir) -> TCResult<Self> {
+            Ok(Self::default())
+        }
+
+        async fn migrate(&self, from_version: u32, _txn: &Txn) -> TCResult<()> {
+            self.migrated_from.store(from_version, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrate() {
+        let txn = new_txn().await;
+        let txn_id = *txn.id();
+        let dir = txn.context().create_dir_tmp(txn_id).await.unwrap();
+
+        // a store written with schema version 1
+        let stored = Value::from(1u64);
+        let file: fs::File<Value> = dir
+            .create_file(txn_id, SCHEMA_VERSION.into(), stored.class())
+            .await
+            .unwrap();
+
+        file.create_block(txn_id, SCHEMA_VERSION.into(), stored)
+            .await
+            .unwrap();
+
+        let state = Versioned::default();
+        check_version(&state, &txn, &dir, false).await.unwrap();
+        assert_eq!(state.migrated_from.load(Ordering::Relaxed), 1);
+
+        let version = file
+            .read_block(txn_id, SCHEMA_VERSION.into())
+            .await
+            .unwrap();
+        assert_eq!(*version, Value::from(2u64));
+        std::mem::drop(version);
+
+        // the store is now up to date, so loading it again does not migrate it
+        let state = Versioned::default();
+        check_version(&state, &txn, &dir, false).await.unwrap();
+        assert_eq!(state.migrated_from.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reload_hash_algo() {
+        let txn = new_txn().await;
+        let txn_id = *txn.id();
+
+        for (algo, other, hash_len) in [
+            (HashAlgo::Sha256, HashAlgo::Sha512, 32),
+            (HashAlgo::Sha512, HashAlgo::Sha256, 64),
+        ] {
+            let dir = txn.context().create_dir_tmp(txn_id).await.unwrap();
+            let schema = Schema::Value(Value::from(0u64));
+
+            let chain = BlockChain::load_with_hash_algo(&txn, schema.clone(), algo, dir.clone())
+                .await
+                .map(Chain::Block)
+                .unwrap();
+
+            std::mem::drop(chain.history().create_next_block(txn_id).await.unwrap());
+
+            let block = chain.history().read_block(txn_id, 1).await.unwrap();
+            assert_eq!(block.last_hash().len(), hash_len);
+            std::mem::drop(block);
+
+            // the link between the blocks is validated on reload using the stored algorithm
+            BlockChain::load_with_hash_algo(&txn, schema.clone(), algo, dir.clone())
+                .await
+                .unwrap();
+
+            match BlockChain::load_with_hash_algo(&txn, schema, other, dir).await {
+                Ok(_) => panic!("reloaded a {} BlockChain with {}", algo, other),
+                Err(cause) => assert!(cause.code() == ErrorType::BadRequest),
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ use futures::future::TryFutureExt;
 use futures::join;
 
 use tc_error::*;
-use tc_transact::fs::{Persist, Store};
+use tc_transact::fs::{HashAlgo, Persist, Store};
 use tc_transact::{IntoView, Transact, Transaction, TxnId};
 use tcgeneric::TCPathBuf;
 
@@ -17,7 +17,9 @@ use crate::state::{State, StateView};
 use crate::txn::Txn;
 
 use super::data::History;
-use super::{ChainBlock, ChainInstance, ChainType, Schema, Subject, NULL_HASH};
+use super::{
+    ChainBlock, ChainInstance, ChainType, ReplicationProgress, Schema, Subject, NULL_HASH,
+};
 
 /// A [`super::Chain`] which keeps only the data needed to recover the state of its subject in the
 /// event of a transaction failure.
@@ -28,12 +30,19 @@ pub struct SyncChain {
     history: History,
 }
 
+impl SyncChain {
+    /// Borrow the [`History`] of this `SyncChain`.
+    pub(crate) fn history(&self) -> &History {
+        &self.history
+    }
+}
+
 #[async_trait]
 impl ChainInstance for SyncChain {
-    async fn append_delete(&self, txn_id: TxnId, path: TCPathBuf, key: Value) -> TCResult<()> {
-        let mut block = self.history.write_latest(txn_id).await?;
-        block.clear_until(&txn_id);
-        block.append_delete(txn_id, path, key);
+    async fn append_delete(&self, txn: &Txn, path: TCPathBuf, key: Value) -> TCResult<()> {
+        let mut block = self.history.write_latest(*txn.id()).await?;
+        block.clear_until(txn.id());
+        block.append_delete(*txn.id(), path, key);
         Ok(())
     }
 
@@ -60,13 +69,22 @@ impl ChainInstance for SyncChain {
         &self.subject
     }
 
-    async fn replicate(&self, txn: &Txn, source: Link) -> TCResult<()> {
+    async fn replicate(
+        &self,
+        txn: &Txn,
+        source: Link,
+        progress: &ReplicationProgress,
+    ) -> TCResult<()> {
+        // a SyncChain copies its whole subject at once, so it counts as a single block
+        progress.extend(1);
+
         let subject = txn.get(source, Value::None).await?;
         self.subject.restore(txn, subject).await?;
 
         let mut block = self.history.write_latest(*txn.id()).await?;
         *block = ChainBlock::with_txn(NULL_HASH, *txn.id());
 
+        progress.advance();
         Ok(())
     }
 
@@ -91,7 +109,7 @@ impl Persist<fs::Dir> for SyncChain {
         let subject = Subject::load(txn, schema.clone(), &dir).await?;
 
         let history = if is_new {
-            History::create(*txn.id(), dir, ChainType::Sync).await?
+            History::create(*txn.id(), dir, ChainType::Sync, HashAlgo::default()).await?
         } else {
             History::load(txn, (), dir).await?
         };
@@ -148,9 +166,14 @@ impl de::Visitor for ChainVisitor {
     }
 
     async fn visit_seq<A: de::SeqAccess>(self, mut seq: A) -> Result<Self::Value, A::Error> {
-        let history = History::create(*self.txn.id(), self.txn.context().clone(), ChainType::Sync)
-            .map_err(de::Error::custom)
-            .await?;
+        let history = History::create(
+            *self.txn.id(),
+            self.txn.context().clone(),
+            ChainType::Sync,
+            HashAlgo::default(),
+        )
+        .map_err(de::Error::custom)
+        .await?;
 
         let schema = seq
             .next_element(())
@@ -2,16 +2,20 @@ use std::collections::HashMap;
 use std::fmt;
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use destream::de;
-use futures::future::TryFutureExt;
-use futures::stream::{FuturesUnordered, TryStreamExt};
+use futures::future::{self, TryFutureExt};
+use futures::stream::{self, FuturesUnordered, TryStreamExt};
+
+use safecast::TryCastFrom;
 
 use tc_error::*;
+use tc_table::{Row, Trigger};
 use tc_transact::IntoView;
 use tcgeneric::{Id, Map, Tuple};
 
 use crate::fs;
-use crate::scalar::{OpDef, Scalar};
+use crate::scalar::{OpConfig, OpDef, Scalar, Value};
 use crate::state::{State, StateView};
 use crate::txn::Txn;
 
@@ -36,6 +40,47 @@ impl Closure {
     pub fn into_inner(self) -> (Map<State>, OpDef) {
         (self.context, self.op)
     }
+
+    /// Return the [`OpConfig`] of this `Closure`'s [`OpDef`].
+    pub fn config(&self) -> &OpConfig {
+        self.op.config()
+    }
+}
+
+#[async_trait]
+impl Trigger<Txn> for Closure {
+    async fn load(txn: &Txn, definition: Value) -> TCResult<Self> {
+        let encoded = Bytes::try_cast_from(definition, |v| {
+            TCError::internal(format!("invalid Table trigger definition: {}", v))
+        })?;
+
+        let encoded = stream::once(future::ready(Ok::<Bytes, TCError>(encoded)));
+        tbon::de::try_decode(txn.clone(), encoded)
+            .map_err(|e| TCError::internal(format!("invalid Table trigger definition: {}", e)))
+            .await
+    }
+
+    async fn definition(&self, txn: &Txn) -> TCResult<Value> {
+        let view = self.clone().into_view(txn.clone()).await?;
+        let encoded = tbon::en::encode(view).map_err(TCError::internal)?;
+
+        encoded
+            .map_err(TCError::internal)
+            .try_fold(Vec::new(), |mut buffer, chunk| {
+                buffer.extend_from_slice(&chunk);
+                future::ready(Ok(buffer))
+            })
+            .map_ok(Bytes::from)
+            .map_ok(Value::Bytes)
+            .await
+    }
+
+    async fn fire(&self, txn: &Txn, row: Row) -> TCResult<()> {
+        let params = row.into_iter().map(|(id, value)| (id, State::from(value)));
+        let (context, op_def) = self.clone().into_callable(State::Map(params.collect()))?;
+        OpDef::call(op_def, txn, context, self.config()).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,51 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::scalar::Link;
+
+/// Selects the replica to serve each balanced read of a [`super::Cluster`], in round-robin order.
+#[derive(Default)]
+pub struct ReadBalancer {
+    next: AtomicUsize,
+}
+
+impl ReadBalancer {
+    /// Return the next of the given `replicas` in round-robin order, or `None` if there are none.
+    pub fn select(&self, replicas: &HashSet<Link>) -> Option<Link> {
+        if replicas.is_empty() {
+            return None;
+        }
+
+        let mut replicas: Vec<&Link> = replicas.iter().collect();
+        replicas.sort();
+
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % replicas.len();
+        Some(replicas[i].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_round_robin() {
+        let replicas: HashSet<Link> = (0..3)
+            .map(|i| format!("http://127.0.0.1:870{}/app", i).parse().unwrap())
+            .collect();
+
+        let balancer = ReadBalancer::default();
+        let mut reads = HashMap::new();
+        for _ in 0..9 {
+            let replica = balancer.select(&replicas).unwrap();
+            *reads.entry(replica).or_insert(0) += 1;
+        }
+
+        assert_eq!(reads.len(), 3);
+        assert!(reads.values().all(|count| *count == 3));
+
+        assert!(balancer.select(&HashSet::new()).is_none());
+    }
+}
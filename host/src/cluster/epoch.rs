@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use safecast::TryCastFrom;
+
+use tc_error::*;
+use tc_transact::fs::{Dir, File};
+use tc_transact::{Transact, Transaction};
+use tc_value::{NumberType, UIntType, ValueType};
+use tcgeneric::Id;
+
+use crate::fs;
+use crate::scalar::Value;
+use crate::txn::{Txn, TxnId};
+
+use super::EPOCH;
+
+/// The epoch of a [`super::Cluster`]'s replica set, persisted so that a restart of this host
+/// does not reset it.
+#[derive(Clone)]
+pub struct Epoch {
+    file: fs::File<Value>,
+}
+
+impl Epoch {
+    /// Load the epoch stored in the given `dir`, or start a new one at zero if there is none.
+    pub async fn load(txn: &Txn, dir: &fs::Dir, name: Id) -> TCResult<Self> {
+        let txn_id = *txn.id();
+
+        let file = if let Some(file) = dir.get_file(&txn_id, &name).await? {
+            file
+        } else {
+            let class = ValueType::Number(NumberType::UInt(UIntType::U64));
+            let file: fs::File<Value> = dir.create_file(txn_id, name, class).await?;
+            file.create_block(txn_id, EPOCH.into(), Value::from(0u64))
+                .await?;
+
+            file
+        };
+
+        Ok(Self { file })
+    }
+
+    /// Return the current epoch.
+    pub async fn get(&self, txn_id: TxnId) -> TCResult<u64> {
+        let epoch = self.file.read_block(txn_id, EPOCH.into()).await?;
+        u64::try_cast_from((*epoch).clone(), |v| {
+            TCError::internal(format!("invalid replica set epoch: {}", v))
+        })
+    }
+
+    /// Set the current epoch.
+    pub async fn set(&self, txn_id: TxnId, epoch: u64) -> TCResult<()> {
+        let mut block = self.file.write_block(txn_id, EPOCH.into()).await?;
+        *block = Value::from(epoch);
+        Ok(())
+    }
+
+    /// Increment the current epoch, and return the new epoch.
+    pub async fn bump(&self, txn_id: TxnId) -> TCResult<u64> {
+        let mut block = self.file.write_block(txn_id, EPOCH.into()).await?;
+        let epoch = u64::try_cast_from((*block).clone(), |v| {
+            TCError::internal(format!("invalid replica set epoch: {}", v))
+        })? + 1;
+
+        *block = Value::from(epoch);
+        Ok(epoch)
+    }
+}
+
+#[async_trait]
+impl Transact for Epoch {
+    async fn commit(&self, txn_id: &TxnId) {
+        self.file.commit(txn_id).await
+    }
+
+    async fn finalize(&self, txn_id: &TxnId) {
+        self.file.finalize(txn_id).await
+    }
+}
@@ -1,22 +1,26 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use log::debug;
 use uplock::RwLock;
 
 use tc_error::*;
+use tc_table::{Reference, TableInstance};
+use tc_transact::fs::HashAlgo;
 use tc_transact::lock::TxnLock;
 use tc_transact::Transaction;
 use tcgeneric::*;
 
-use crate::chain::{self, Chain, ChainType, Schema};
+use crate::chain::{self, Chain, ChainInstance, ChainType, Schema, Subject};
 use crate::fs;
 use crate::object::{InstanceClass, InstanceExt};
-use crate::scalar::{Link, LinkHost, OpRef, Refer, Scalar, Value};
+use crate::scalar::{Link, LinkHost, OpDef, OpRef, Refer, Scalar, Value};
 use crate::txn::{Actor, Txn, TxnId};
 
-use super::Cluster;
+use super::balance::ReadBalancer;
+use super::epoch::Epoch;
+use super::{Cluster, Config, WriteQueue, EPOCH, MISSED, REPLICAS};
 
 /// Load a cluster from the filesystem, or instantiate a new one.
 pub async fn instantiate(
@@ -24,6 +28,7 @@ pub async fn instantiate(
     host: LinkHost,
     class: InstanceClass,
     data_dir: fs::Dir,
+    config: Config,
 ) -> TCResult<InstanceExt<Cluster>> {
     let (link, proto) = class.into_inner();
     let link = link.ok_or_else(|| {
@@ -37,6 +42,13 @@ pub async fn instantiate(
     for (id, scalar) in proto.into_iter() {
         debug!("Cluster member: {}", scalar);
 
+        if id == REPLICAS {
+            return Err(TCError::bad_request(
+                "a Cluster member may not have the reserved name",
+                id,
+            ));
+        }
+
         match scalar {
             Scalar::Ref(tc_ref) => {
                 let op_ref = OpRef::try_from(*tc_ref)?;
@@ -47,8 +59,15 @@ pub async fn instantiate(
                             .ok_or_else(|| TCError::bad_request("not a Chain", classpath))?;
 
                         debug!("an instance of {} with schema {}", ct, schema);
-                        let schema = Schema::from_scalar(schema)?;
-                        chain_schema.insert(id, (ct, schema));
+                        let (schema, algo) = Schema::from_scalar_with_hash_algo(schema)?;
+                        if ct == ChainType::Sync && algo != HashAlgo::default() {
+                            return Err(TCError::bad_request(
+                                "a SyncChain has no blocks to link with hash algorithm",
+                                algo,
+                            ));
+                        }
+
+                        chain_schema.insert(id, (ct, schema, algo));
                     }
                     OpRef::Post((extends, proto)) => {
                         let extends = extends.try_into()?;
@@ -96,25 +115,38 @@ pub async fn instantiate(
     replicas.insert((host, link.path().clone()).into());
 
     let mut chains = Map::<Chain>::new();
-    for (id, (class, schema)) in chain_schema.into_iter() {
+    for (id, (class, schema, algo)) in chain_schema.into_iter() {
         debug!("load chain {} of type {} with schema {}", id, class, schema);
 
         let dir = dir.get_or_create_dir(txn_id, id.clone()).await?;
-        let chain = chain::load(txn, class, schema, dir).await?;
+        let chain = chain::load(txn, class, schema, algo, dir).await?;
         chains.insert(id, chain);
     }
 
+    register_references(link.path(), &chains)?;
+    validate_computed(link.path(), &chains, &cluster_proto)?;
+
+    let replicas_dir = dir.get_or_create_dir(txn_id, REPLICAS.into()).await?;
+    let missed = WriteQueue::load(txn, &replicas_dir, MISSED.into()).await?;
+    let epoch = Epoch::load(txn, &replicas_dir, EPOCH.into()).await?;
+
     let actor_id = Value::from(Link::default());
 
     let cluster = Cluster {
         link: link.clone(),
         actor: Arc::new(Actor::new(actor_id)),
+        config,
         chains,
         classes,
         confirmed: RwLock::new(txn_id),
         owned: RwLock::new(HashMap::new()),
         installed: TxnLock::new(format!("Cluster {} installed deps", link), HashMap::new()),
         replicas: TxnLock::new(format!("Cluster {} replicas", link), replicas),
+        epoch,
+        missed,
+        progress: Mutex::new(HashMap::new()),
+        diverged: Mutex::new(HashSet::new()),
+        balancer: ReadBalancer::default(),
     };
 
     let class = InstanceClass::new(Some(link), cluster_proto.into());
@@ -122,6 +154,98 @@ pub async fn instantiate(
     Ok(InstanceExt::new(cluster, class))
 }
 
+/// Register each foreign key of a table in this cluster with the table it references, so that
+/// the constraint can be enforced when a row is deleted.
+///
+/// Returns a "bad request" error if a foreign key references a table outside this cluster, or a
+/// column other than the primary key of the referenced table.
+fn register_references(path: &[PathSegment], chains: &Map<Chain>) -> TCResult<()> {
+    for (id, chain) in chains.iter() {
+        let table = match chain.subject() {
+            Subject::Table(table) => table,
+            _ => continue,
+        };
+
+        for fk in table.schema().foreign_keys() {
+            let name = match fk.table().split_last() {
+                Some((name, prefix)) if prefix == path => name,
+                _ => {
+                    return Err(TCError::bad_request(
+                        "a foreign key must reference a table in the same cluster, not",
+                        fk.table(),
+                    ))
+                }
+            };
+
+            let referenced = match chains.get(name).map(|c| c.subject()) {
+                Some(Subject::Table(referenced)) => referenced,
+                _ => {
+                    return Err(TCError::bad_request(
+                        "foreign key refers to a nonexistent table",
+                        fk.table(),
+                    ))
+                }
+            };
+
+            match referenced.key() {
+                [key] if key.name() == fk.foreign_column() => {}
+                _ => {
+                    return Err(TCError::bad_request(
+                        "foreign key must reference the primary key of",
+                        fk.table(),
+                    ))
+                }
+            }
+
+            let referrer = TCPathBuf::from(path.to_vec()).append(id.clone());
+            referenced.add_reference(Reference::new(referrer, table.clone(), fk.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that each computed column of a table in this cluster is computed by a read-only POST
+/// method of this cluster, which is safe to call once per row without claiming the transaction.
+fn validate_computed(
+    path: &[PathSegment],
+    chains: &Map<Chain>,
+    cluster_proto: &Map<Scalar>,
+) -> TCResult<()> {
+    for chain in chains.values() {
+        let table = match chain.subject() {
+            Subject::Table(table) => table,
+            _ => continue,
+        };
+
+        for computed in table.schema().computed() {
+            let name = computed
+                .op()
+                .split_last()
+                .filter(|(_, prefix)| *prefix == path)
+                .map(|(name, _)| name)
+                .ok_or_else(|| {
+                    TCError::bad_request(
+                        "a computed column must be computed by a method of the same cluster, not",
+                        computed.op(),
+                    )
+                })?;
+
+            match cluster_proto.get(name) {
+                Some(Scalar::Op(op_def @ OpDef::Post(..))) if op_def.is_read_only() => {}
+                _ => {
+                    return Err(TCError::bad_request(
+                        "a computed column must be computed by a read-only POST method, not",
+                        computed.op(),
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 async fn get_or_create_dir(
     data_dir: fs::Dir,
     txn_id: TxnId,
@@ -5,11 +5,13 @@ use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::future::{join_all, try_join_all, Future, FutureExt};
-use futures::{join, StreamExt};
+use futures::{join, stream, Stream, StreamExt};
 use log::{debug, info, warn};
 use safecast::TryCastFrom;
 use uplock::RwLock;
@@ -19,23 +21,39 @@ use tc_transact::lock::TxnLock;
 use tc_transact::{Transact, Transaction};
 use tcgeneric::*;
 
-use crate::chain::{Chain, ChainInstance};
-use crate::object::InstanceClass;
+use crate::chain::{Chain, ChainInstance, ReplicationProgress};
+use crate::object::{InstanceClass, InstanceExt};
 use crate::scalar::{Link, OpDef, Value};
 use crate::state::State;
 use crate::txn::{Actor, Scope, Txn, TxnId};
 
+use balance::ReadBalancer;
+use epoch::Epoch;
 use owner::Owner;
 
 use futures::stream::FuturesUnordered;
 pub use load::instantiate;
+pub use write::{MissedWrite, Write, WriteQueue};
 
+mod balance;
+mod epoch;
 mod load;
 mod owner;
+mod write;
 
-/// The name of the endpoint which serves a [`Link`] to each of this [`Cluster`]'s replicas.
+/// The name of the endpoint which serves a [`Link`] to each of this [`Cluster`]'s replicas,
+/// or the replication status of the replica with a given [`Link`].
 pub const REPLICAS: Label = label("replicas");
 
+/// The name of the endpoint which serves the writes queued for an unreachable replica.
+pub const MISSED: Label = label("missed");
+
+/// The name of the endpoint which serves the epoch of a [`Cluster`]'s replica set.
+pub const EPOCH: Label = label("epoch");
+
+/// The name of the commit parameter which asks a replica only to confirm that it can commit.
+pub const PREPARE: Label = label("prepare");
+
 /// The [`Class`] of a [`Cluster`].
 pub struct ClusterType;
 
@@ -47,16 +65,82 @@ impl fmt::Display for ClusterType {
     }
 }
 
+/// The number of replicas which must acknowledge a write for it to succeed.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum Quorum {
+    All,
+    Majority,
+}
+
+impl Quorum {
+    /// The number of the given number of `replicas` which may fail without losing the quorum.
+    pub fn max_failures(&self, replicas: usize) -> usize {
+        match self {
+            Self::All => 0,
+            Self::Majority => replicas / 2,
+        }
+    }
+}
+
+impl FromStr for Quorum {
+    type Err = TCError;
+
+    fn from_str(s: &str) -> TCResult<Self> {
+        match s {
+            "all" => Ok(Self::All),
+            "majority" => Ok(Self::Majority),
+            other => Err(TCError::bad_request("invalid quorum policy", other)),
+        }
+    }
+}
+
+impl fmt::Display for Quorum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::All => "all",
+            Self::Majority => "majority",
+        })
+    }
+}
+
+/// The replication settings of a [`Cluster`].
+#[derive(Clone)]
+pub struct Config {
+    /// The number of replicas which must acknowledge a write
+    pub quorum: Quorum,
+
+    /// The number of times to retry a write to a replica which fails
+    pub retries: usize,
+
+    /// How long to wait before retrying a failed write to a replica
+    pub retry_delay: Duration,
+
+    /// The maximum number of replicas to write to concurrently
+    pub concurrency: usize,
+
+    /// The maximum number of writes to queue for an unreachable replica, or 0 to drop it instead
+    pub queue_size: usize,
+
+    /// Whether to spread the GET requests of read-only transactions across replicas
+    pub balance_reads: bool,
+}
+
 /// The data structure responsible for maintaining consensus per-transaction.
 pub struct Cluster {
     link: Link,
     actor: Arc<Actor>,
+    config: Config,
     chains: Map<Chain>,
     classes: Map<InstanceClass>,
     confirmed: RwLock<TxnId>,
     owned: RwLock<HashMap<TxnId, Owner>>,
     installed: TxnLock<HashMap<Link, HashSet<Scope>>>,
     replicas: TxnLock<HashSet<Link>>,
+    epoch: Epoch,
+    missed: WriteQueue,
+    progress: Mutex<HashMap<Link, ReplicationProgress>>,
+    diverged: Mutex<HashSet<Link>>,
+    balancer: ReadBalancer,
 }
 
 impl Cluster {
@@ -80,6 +164,11 @@ impl Cluster {
         &self.link
     }
 
+    /// Borrow the replication settings of this cluster.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Return the path of this cluster, relative to this host.
     pub fn path(&'_ self) -> &'_ [PathSegment] {
         self.link.path()
@@ -96,6 +185,131 @@ impl Cluster {
         Ok(replicas.deref().clone())
     }
 
+    /// Return the replica which should serve a GET request for the given `path` under the given
+    /// [`Txn`], and the last commit of this replica which it must have made to serve it,
+    /// or `None` if this replica should serve it.
+    ///
+    /// Only the reads of a read-only `Txn` are balanced, and only if configured to, since a
+    /// replica may not yet have committed the writes of a `Txn` which is still in progress.
+    /// Only reads of a [`Chain`] are balanced, never those of the cluster's own control paths,
+    /// and never to a replica which is known to be missing writes.
+    pub async fn read_replica(
+        &self,
+        txn: &Txn,
+        path: &[PathSegment],
+    ) -> TCResult<Option<(Link, TxnId)>> {
+        if !self.config.balance_reads
+            || !txn.is_read_only()
+            || txn.is_balanced()
+            || txn.min_commit().is_some()
+        {
+            return Ok(None);
+        }
+
+        match path.first() {
+            Some(name) if self.chain(name).is_some() => {}
+            _ => return Ok(None),
+        }
+
+        let mut replicas = self.replicas(txn.id()).await?;
+        if self.config.queue_size > 0 {
+            for replica in replicas.clone() {
+                if self.missed.contains(*txn.id(), &replica).await? {
+                    replicas.remove(&replica);
+                }
+            }
+        }
+
+        let this = txn.link(self.link.path().clone());
+        let since = *self.confirmed.read().await;
+        Ok(self
+            .balancer
+            .select(&replicas)
+            .filter(|replica| replica != &this)
+            .map(|replica| (replica, since)))
+    }
+
+    /// Return a `Conflict` error if this replica has not yet committed the oldest commit which
+    /// the given [`Txn`] requires to serve a read forwarded from another replica.
+    pub async fn validate_min_commit(&self, txn: &Txn) -> TCResult<()> {
+        if let Some(min_commit) = txn.min_commit() {
+            let confirmed = self.confirmed.read().await;
+            if &*confirmed < min_commit {
+                return Err(TCError::conflict().consume(format!(
+                    "{} has only committed up to {}, not {}",
+                    self, *confirmed, min_commit
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the epoch of this cluster's replica set, which increases with every membership change.
+    pub async fn epoch(&self, txn_id: &TxnId) -> TCResult<u64> {
+        self.epoch.get(*txn_id).await
+    }
+
+    /// Return the number of blocks which the given `replica` has replicated so far, and the
+    /// number it must replicate in total, if it has replicated this cluster since starting up.
+    pub fn replication_progress(&self, replica: &Link) -> Option<(u64, u64)> {
+        let progress = self.progress.lock().expect("replication progress");
+        progress.get(replica).map(ReplicationProgress::status)
+    }
+
+    /// Record the replication progress which the given `replica` reported to this cluster.
+    pub fn report_progress(&self, replica: Link, current: u64, target: u64) {
+        let mut progress = self.progress.lock().expect("replication progress");
+        progress.insert(replica, ReplicationProgress::from((current, target)));
+    }
+
+    /// Return the number of writes queued for the given `replica` which it has not yet replayed.
+    pub async fn missed_count(&self, txn_id: TxnId, replica: &Link) -> TCResult<u64> {
+        if self.config.queue_size == 0 {
+            return Ok(0);
+        }
+
+        self.missed.len(txn_id, replica).await
+    }
+
+    /// Return a `Conflict` error if the given `epoch` of the replica set is older than this
+    /// cluster's, which means that the sender was partitioned from a membership change.
+    pub async fn validate_epoch(&self, txn_id: &TxnId, epoch: u64) -> TCResult<()> {
+        let current = self.epoch(txn_id).await?;
+        check_epoch(current, epoch).map_err(|cause| cause.consume(self))
+    }
+
+    /// Return `Unauthorized` unless the given [`Txn`] is owned by another replica of this cluster,
+    /// e.g. a host which is joining the replica set.
+    pub async fn authorize_replica(&self, txn: &Txn) -> TCResult<()> {
+        let self_link = txn.link(self.link.path().clone());
+        let replicas = self.replicas.read(txn.id()).await?;
+
+        match txn.owner() {
+            Some(owner) if owner != &self_link && replicas.contains(owner) => Ok(()),
+            _ => Err(TCError::unauthorized(format!(
+                "only another replica of {} can update its replica set",
+                self
+            ))),
+        }
+    }
+
+    /// Advance the epoch of this replica set to the given `epoch`, unless it's already later.
+    pub async fn adopt_epoch(&self, txn_id: TxnId, epoch: u64) -> TCResult<()> {
+        if epoch > self.epoch(&txn_id).await? {
+            self.epoch.set(txn_id, epoch).await?;
+            debug!("{} replica set is now at epoch {}", self, epoch);
+        }
+
+        Ok(())
+    }
+
+    async fn bump_epoch(&self, txn_id: TxnId) -> TCResult<()> {
+        let epoch = self.epoch.bump(txn_id).await?;
+        debug!("{} replica set is now at epoch {}", self, epoch);
+        Ok(())
+    }
+
     /// Claim ownership of the given [`Txn`].
     pub async fn claim(&self, txn: &Txn) -> TCResult<Txn> {
         self.validate_txn_id(txn.id()).await?;
@@ -161,7 +375,8 @@ impl Cluster {
             .grant(&self.actor, self.link.path().clone(), vec![scope])
             .await?;
 
-        OpDef::call(op.into_form(), &txn, context).await
+        let config = op.config().clone();
+        OpDef::call(op.into_form(), &txn, context, &config).await
     }
 
     /// Trust the `Cluster` at the given [`Link`] to issue the given auth [`Scope`]s.
@@ -200,51 +415,81 @@ impl Cluster {
         debug!("cluster at {} adding replica {}...", self_link, replica);
 
         if replica == self_link {
-            if self.link.host().is_none() || self.link == self_link {
-                debug!("{} cannot replicate itself", self);
-                return Ok(());
+            if let Some(replicas) = self.join(txn).await? {
+                self.replicate(txn, &replicas).await?;
             }
+        } else {
+            debug!("add replica {}", replica);
+            if (*self.replicas.write(*txn.id()).await?).insert(replica) {
+                self.bump_epoch(*txn.id()).await?;
+            }
+        }
 
-            debug!(
-                "{} replica at {} got add request for self: {}",
-                self, replica, self_link
-            );
+        Ok(())
+    }
 
-            let replicas = txn
-                .get(self.link.clone().append(REPLICAS.into()), Value::None)
-                .await?;
+    /// Add this host to the replica set of this cluster.
+    ///
+    /// Returns the other replicas of this cluster, or `None` if this host cannot replicate itself.
+    async fn join(&self, txn: &Txn) -> TCResult<Option<HashSet<Link>>> {
+        let self_link = txn.link(self.link.path().clone());
+
+        if self.link.host().is_none() || self.link == self_link {
+            debug!("{} cannot replicate itself", self);
+            return Ok(None);
+        }
 
-            if replicas.is_some() {
-                let replicas = Tuple::<Link>::try_cast_from(replicas, |s| {
-                    TCError::bad_request("invalid replica set", s)
-                })?;
+        debug!("{} replica at {} joining", self, self_link);
 
-                debug!("{} has replicas: {}", self, replicas);
+        let replicas = txn
+            .get(self.link.clone().append(REPLICAS.into()), Value::None)
+            .await?;
 
-                let mut replicas: HashSet<Link> = HashSet::from_iter(replicas);
-                replicas.remove(&self_link);
+        if replicas.is_none() {
+            warn!("{} has no other replicas", self);
+            return Ok(Some(HashSet::new()));
+        }
 
-                try_join_all(replicas.iter().map(|replica| {
-                    txn.put(
-                        replica.clone().append(REPLICAS.into()),
-                        Value::None,
-                        self_link.clone().into(),
-                    )
-                }))
-                .await?;
+        let replicas = Tuple::<Link>::try_cast_from(replicas, |s| {
+            TCError::bad_request("invalid replica set", s)
+        })?;
 
-                (*self.replicas.write(*txn.id()).await?).extend(replicas);
-            } else {
-                warn!("{} has no other replicas", self);
-            }
+        debug!("{} has replicas: {}", self, replicas);
 
-            self.replicate(txn).await?;
-        } else {
-            debug!("add replica {}", replica);
-            (*self.replicas.write(*txn.id()).await?).insert(replica);
-        }
+        let mut replicas: HashSet<Link> = HashSet::from_iter(replicas);
+        replicas.remove(&self_link);
 
-        Ok(())
+        try_join_all(replicas.iter().map(|replica| {
+            txn.put(
+                replica.clone().append(REPLICAS.into()),
+                Value::None,
+                self_link.clone().into(),
+            )
+        }))
+        .await?;
+
+        (*self.replicas.write(*txn.id()).await?).extend(replicas.iter().cloned());
+
+        // a replica only bumps its epoch if it didn't already know about this host (e.g. if this
+        // host is restarting), so the replicas have to agree on the latest epoch of any of them
+        let epochs =
+            try_join_all(replicas.iter().map(|replica| replica_epoch(txn, replica))).await?;
+        let epoch = epochs
+            .into_iter()
+            .fold(self.epoch(txn.id()).await?, u64::max);
+
+        try_join_all(replicas.iter().map(|replica| {
+            txn.put(
+                replica.clone().append(REPLICAS.into()).append(EPOCH.into()),
+                Value::None,
+                Value::from(epoch).into(),
+            )
+        }))
+        .await?;
+
+        self.adopt_epoch(*txn.id(), epoch).await?;
+
+        Ok(Some(replicas))
     }
 
     /// Remove a replica from this cluster.
@@ -252,17 +497,78 @@ impl Cluster {
         let self_link = txn.link(self.link.path().clone());
         let mut replicas = self.replicas.write(*txn.id()).await?;
 
+        let mut removed = false;
         for replica in to_remove {
             if replica == &self_link {
                 panic!("{} received remove replica request for itself", self);
             }
 
-            replicas.remove(replica);
+            removed |= replicas.remove(replica);
+        }
+
+        if removed {
+            self.bump_epoch(*txn.id()).await?;
+        }
+
+        if self.config.queue_size > 0 {
+            // a replica which was dropped must fully resynchronize when it rejoins
+            for replica in to_remove {
+                self.missed.remove(*txn.id(), replica).await?;
+            }
         }
 
         Ok(())
     }
 
+    /// Queue a `write` for a `replica` which could not be reached, to replay when it rejoins.
+    ///
+    /// Returns `false` if queueing is disabled or the replica's queue is full, in which case the
+    /// replica's queue is discarded and it must be dropped from the replica set.
+    pub async fn enqueue_missed(&self, txn: &Txn, replica: Link, write: Write) -> TCResult<bool> {
+        if self.config.queue_size == 0 {
+            return Ok(false);
+        }
+
+        let txn_id = *txn.id();
+        if self.missed.len(txn_id, &replica).await? < self.config.queue_size as u64 {
+            debug!("queue missed {} for replica {}", write, replica);
+            self.missed
+                .push(txn, replica, MissedWrite { txn_id, write })
+                .await?;
+
+            Ok(true)
+        } else {
+            warn!("write queue for replica {} is full", replica);
+            self.missed.remove(txn_id, &replica).await?;
+            Ok(false)
+        }
+    }
+
+    /// Return the writes queued for the given `replica`, in the order they were made.
+    ///
+    /// The writes stay queued until the replica acknowledges them with [`Self::acknowledge_missed`].
+    pub async fn missed(&self, txn: &Txn, replica: &Link) -> TCResult<Vec<MissedWrite>> {
+        if self.config.queue_size == 0 {
+            return Ok(vec![]);
+        }
+
+        self.missed.read(txn, replica).await
+    }
+
+    /// Remove the first `count` writes queued for the given `replica`, which it has replayed.
+    pub async fn acknowledge_missed(
+        &self,
+        txn_id: TxnId,
+        replica: &Link,
+        count: u64,
+    ) -> TCResult<()> {
+        if self.config.queue_size == 0 {
+            return Ok(());
+        }
+
+        self.missed.acknowledge(txn_id, replica, count).await
+    }
+
     async fn validate_txn_id(&self, txn_id: &TxnId) -> TCResult<()> {
         let last_commit = self.confirmed.read().await;
         if txn_id <= &*last_commit {
@@ -275,69 +581,155 @@ impl Cluster {
         }
     }
 
-    async fn replicate(&self, txn: &Txn) -> TCResult<()> {
-        let replication = self.chains.iter().map(|(name, chain)| {
-            let mut path = self.link.path().to_vec();
-            path.push(name.clone());
+    /// Replicate the state of this cluster from the given `replicas`, then report the
+    /// progress of this replica to each of them.
+    async fn replicate(&self, txn: &Txn, replicas: &HashSet<Link>) -> TCResult<()> {
+        let self_link = txn.link(self.link.path().clone());
+        let progress = ReplicationProgress::default();
+
+        self.progress
+            .lock()
+            .expect("replication progress")
+            .insert(self_link.clone(), progress.clone());
 
-            chain.replicate(txn, self.link.clone().append(name.clone()))
+        let replication = self.chains.iter().map(|(name, chain)| {
+            chain.replicate(txn, self.link.clone().append(name.clone()), &progress)
         });
 
         try_join_all(replication).await?;
 
+        let (current, target) = progress.status();
+        let status = Value::Tuple(vec![Value::from(current), Value::from(target)].into());
+        try_join_all(replicas.iter().map(|replica| {
+            txn.put(
+                replica.clone().append(REPLICAS.into()),
+                self_link.clone().into(),
+                status.clone().into(),
+            )
+        }))
+        .await?;
+
         Ok(())
     }
 
-    pub async fn replicate_write<F: Future<Output = TCResult<()>>, W: Fn(Link) -> F>(
-        &self,
-        txn: Txn,
-        write: W,
-    ) -> TCResult<()> {
+    pub async fn replicate_write(&self, txn: Txn, write: Write) -> TCResult<()> {
         let mut replicas = self.replicas(txn.id()).await?;
         replicas.remove(&txn.link(self.link().path().clone()));
         debug!("replicating write to {} replicas", replicas.len());
 
-        let max_failures = replicas.len() / 2;
+        if !replicas.is_empty() {
+            // this write can no longer be rolled back to a savepoint on this host alone
+            txn.after_replicate().await;
+        }
+
+        let max_failures = self.config.quorum.max_failures(replicas.len());
+
+        // a replica which missed a commit can't catch up by replaying a queue, so it's dropped
+        let diverged = {
+            let diverged = self.diverged.lock().expect("diverged replicas");
+            replicas
+                .iter()
+                .filter(|replica| diverged.contains(replica))
+                .cloned()
+                .collect::<Vec<Link>>()
+        };
+
+        for replica in &diverged {
+            replicas.remove(replica);
+        }
+
+        // a replica with queued writes is unreachable, and the order of its writes must be kept
+        let mut unreachable = HashSet::new();
+        if self.config.queue_size > 0 {
+            for replica in &replicas {
+                if self.missed.contains(*txn.id(), replica).await? {
+                    unreachable.insert(replica.clone());
+                }
+            }
+        }
+
+        if unreachable.len() + diverged.len() > max_failures {
+            return Err(TCError::bad_gateway(format!(
+                "{} replicas of {} are unreachable",
+                unreachable.len() + diverged.len(),
+                self
+            )));
+        }
+
+        for replica in &unreachable {
+            replicas.remove(replica);
+        }
+
         let mut failed = HashSet::with_capacity(replicas.len());
         let mut succeeded = HashSet::with_capacity(replicas.len());
 
+        // include the epoch so that a replica can reject a write from a stale replica set
+        let epoch = self.epoch(txn.id()).await?;
+        let replicated = txn.clone().with_epoch(epoch);
+
         {
-            let mut results = FuturesUnordered::from_iter(
-                replicas
-                    .into_iter()
-                    .map(|link| write(link.clone()).map(|result| (link, result))),
-            );
+            let write = &write;
+            let txn = &replicated;
+            let retries = self.config.retries;
+            let retry_delay = self.config.retry_delay;
+
+            let mut results = stream::iter(replicas)
+                .map(|link| async move {
+                    let result =
+                        retry(|| write.send(txn, link.clone()), retries, retry_delay).await;
+
+                    (link, result)
+                })
+                .buffer_unordered(self.config.concurrency.max(1));
 
             while let Some((replica, result)) = results.next().await {
                 match result {
                     Err(cause) if cause.code() == ErrorType::Conflict => return Err(cause),
-                    Err(ref cause) => {
+                    Err(cause) => {
                         debug!("replica at {} failed: {}", replica, cause);
                         failed.insert(replica);
+
+                        if failed.len() + unreachable.len() + diverged.len() > max_failures {
+                            return Err(cause);
+                        }
                     }
                     Ok(()) => {
                         debug!("replica at {} succeeded", replica);
                         succeeded.insert(replica);
                     }
                 };
+            }
+        }
 
-                if failed.len() > max_failures {
-                    assert!(result.is_err());
-                    return result;
-                }
+        let mut dropped = diverged.to_vec();
+        for replica in unreachable.into_iter().chain(failed) {
+            if !self
+                .enqueue_missed(&txn, replica.clone(), write.clone())
+                .await?
+            {
+                dropped.push(replica);
             }
         }
 
-        if !failed.is_empty() {
-            let failed = Value::from_iter(failed);
+        if !dropped.is_empty() {
+            self.remove_replicas(&txn, &dropped).await?;
+
+            let dropped = Value::from_iter(dropped);
             try_join_all(
                 succeeded
                     .into_iter()
-                    .map(|replica| txn.delete(replica.append(REPLICAS.into()), failed.clone())),
+                    .map(|replica| txn.delete(replica.append(REPLICAS.into()), dropped.clone())),
             )
             .await?;
         }
 
+        if !diverged.is_empty() {
+            let mut pending = self.diverged.lock().expect("diverged replicas");
+            for replica in &diverged {
+                pending.remove(replica);
+            }
+        }
+
         Ok(())
     }
 
@@ -366,57 +758,116 @@ impl Cluster {
         Ok(())
     }
 
+    /// Commit the given [`Txn`] here and at each other replica of this cluster.
+    ///
+    /// First every replica is asked to prepare to commit. If more replicas fail to prepare than
+    /// the [`Quorum`] allows, the transaction is rolled back and this returns an error. Otherwise
+    /// the transaction is committed here and this succeeds, even if some replicas then fail to
+    /// acknowledge the commit. Each of those is dropped from the replica set by the next
+    /// replicated write, so that it fully resynchronizes when it rejoins.
     pub async fn distribute_commit(&self, txn: &Txn) -> TCResult<()> {
-        let replicas = self.replicas.read(txn.id()).await?;
+        let self_link = txn.link(self.link.path().clone());
+        let replicas = self
+            .replicas
+            .read(txn.id())
+            .await?
+            .iter()
+            .filter(|replica| *replica != &self_link)
+            .cloned()
+            .collect::<Vec<Link>>();
+
+        let max_failures = self.config.quorum.max_failures(replicas.len());
+
+        // include the epoch so that a replica can reject a commit from a stale replica set
+        let epoch = self.epoch(txn.id()).await?;
+        let commit: Map<State> = std::iter::once((EPOCH.into(), epoch.into())).collect();
+
+        // make sure a quorum of replicas is able to commit before committing anything here,
+        // so that a failure to reach the quorum doesn't leave this host diverged from the others
+        let mut prepare = commit.clone();
+        prepare.insert(PREPARE.into(), true.into());
+
+        // the transaction is still active, so a failure here rolls back its participants but
+        // leaves it to be finalized once it ends
+        let prepared = post_replicas(txn, &replicas, prepare);
+        if let Err(cause) = await_acks(prepared, max_failures).await {
+            self.rollback_participants(txn).await;
+            return Err(cause);
+        }
 
-        if let Some(owner) = self.owned.read().await.get(txn.id()) {
-            owner.commit(txn).await?;
+        let owner = self.owned.read().await.get(txn.id()).cloned();
+        if let Some(owner) = owner {
+            if let Err(cause) = owner.commit(txn).await {
+                self.rollback_participants(txn).await;
+                return Err(cause);
+            }
         }
 
         self.write_ahead(txn.id()).await;
 
-        let self_link = txn.link(self.link.path().clone());
-        let mut replica_commits = FuturesUnordered::from_iter(
-            replicas
-                .iter()
-                .filter(|replica| *replica != &self_link)
-                .map(|replica| {
-                    debug!("commit replica {}...", replica);
-                    txn.post(replica.clone(), State::Map(Map::default()))
-                }),
-        );
-
-        while let Some(result) = replica_commits.next().await {
-            match result {
-                Ok(_) => {}
-                Err(cause) => log::error!("commit failure: {}", cause),
+        // a quorum of replicas has prepared, so the transaction is committed from here on, even if
+        // a replica fails to acknowledge the commit message itself
+        let mut committed = post_replicas(txn, &replicas, commit);
+        let mut missed = Vec::new();
+        while let Some((replica, result)) = committed.next().await {
+            if let Err(cause) = result {
+                warn!("replica at {} failed to commit: {}", replica, cause);
+                missed.push(replica);
             }
         }
 
         self.commit(txn.id()).await;
 
+        if !missed.is_empty() {
+            // such a replica is missing this transaction, so it's dropped from the replica set
+            // by the next replicated write, and must fully resynchronize when it rejoins
+            let mut diverged = self.diverged.lock().expect("diverged replicas");
+            diverged.extend(missed);
+        }
+
         Ok(())
     }
 
     pub async fn distribute_rollback(&self, txn: &Txn) {
-        let replicas = self.replicas.read(txn.id()).await;
+        self.rollback_participants(txn).await;
+        self.finalize(txn.id()).await;
+    }
+
+    /// Roll back the dependencies and the other replicas of this cluster in the given [`Txn`].
+    async fn rollback_participants(&self, txn: &Txn) {
+        // rollback messages are not writes, so they're allowed in a read-only transaction
+        let txn = &txn.control();
 
-        if let Some(owner) = self.owned.read().await.get(txn.id()) {
+        let owner = self.owned.read().await.get(txn.id()).cloned();
+        if let Some(owner) = owner {
             owner.rollback(txn).await;
         }
 
-        if let Ok(replicas) = replicas {
-            let self_link = txn.link(self.link.path().clone());
-            join_all(
-                replicas
-                    .iter()
-                    .filter(|replica| *replica != &self_link)
-                    .map(|replica| txn.delete(replica.clone(), Value::None)),
-            )
-            .await;
-        }
+        let self_link = txn.link(self.link.path().clone());
+        let replicas = match self.replicas.read(txn.id()).await {
+            Ok(replicas) => replicas
+                .iter()
+                .filter(|replica| *replica != &self_link)
+                .cloned()
+                .collect::<Vec<Link>>(),
+            Err(cause) => {
+                warn!("{} could not read its replica set: {}", self, cause);
+                return;
+            }
+        };
 
-        self.finalize(txn.id()).await;
+        join_all(
+            replicas
+                .into_iter()
+                .map(|replica| txn.delete(replica, Value::None)),
+        )
+        .await;
+    }
+
+    /// Check that this replica is able to commit the transaction with the given [`TxnId`],
+    /// i.e. that it has not already committed a later transaction.
+    pub async fn prepare(&self, txn_id: &TxnId) -> TCResult<()> {
+        self.validate_txn_id(txn_id).await
     }
 
     pub async fn write_ahead(&self, txn_id: &TxnId) {
@@ -424,6 +875,186 @@ impl Cluster {
     }
 }
 
+/// Call `send` until it succeeds, up to `retries` more times, waiting `retry_delay` in between.
+/// A [`ErrorType::Conflict`] is returned immediately since retrying it cannot succeed.
+async fn retry<F, Fut>(send: F, retries: usize, retry_delay: Duration) -> TCResult<()>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = TCResult<()>>,
+{
+    let mut attempts = 0;
+    loop {
+        match send().await {
+            Err(cause) if cause.code() != ErrorType::Conflict && attempts < retries => {
+                debug!("retrying write to replica: {}", cause);
+                attempts += 1;
+                tokio::time::sleep(retry_delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Send the given commit `params` to each of the given `replicas`.
+fn post_replicas<'a>(
+    txn: &'a Txn,
+    replicas: &'a [Link],
+    params: Map<State>,
+) -> impl Stream<Item = (Link, TCResult<()>)> + Unpin + 'a {
+    replicas
+        .iter()
+        .map(move |replica| {
+            debug!("commit replica {}...", replica);
+
+            txn.post(replica.clone(), State::Map(params.clone()))
+                .map(move |result| (replica.clone(), result.map(|_| ())))
+        })
+        .collect::<FuturesUnordered<_>>()
+}
+
+/// Collect the commit acknowledgments of a set of replicas, returning the number which
+/// acknowledged, or an error if more than `max_failures` did not.
+///
+/// Every acknowledgment is awaited, even after the quorum is lost, so that each replica
+/// receives its commit message.
+async fn await_acks<S>(mut acks: S, max_failures: usize) -> TCResult<usize>
+where
+    S: Stream<Item = (Link, TCResult<()>)> + Unpin,
+{
+    let mut acknowledged = 0;
+    let mut failed = 0;
+
+    while let Some((replica, result)) = acks.next().await {
+        match result {
+            Ok(()) => acknowledged += 1,
+            Err(cause) => {
+                log::error!("replica at {} failed to commit: {}", replica, cause);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > max_failures {
+        Err(TCError::bad_gateway(format!(
+            "{} replicas failed to acknowledge the commit",
+            failed
+        )))
+    } else {
+        Ok(acknowledged)
+    }
+}
+
+/// Look up the epoch of the replica set of the cluster at the given `replica`.
+async fn replica_epoch(txn: &Txn, replica: &Link) -> TCResult<u64> {
+    let epoch = txn
+        .get(
+            replica.clone().append(REPLICAS.into()).append(EPOCH.into()),
+            Value::None,
+        )
+        .await?;
+
+    let epoch = Value::try_cast_from(epoch, |s| {
+        TCError::bad_request("invalid replica set epoch", s)
+    })?;
+
+    u64::try_cast_from(epoch, |v| {
+        TCError::bad_request("invalid replica set epoch", v)
+    })
+}
+
+/// Return a `Conflict` error if the replica set `epoch` of a request is older than the `current`
+/// epoch of the receiving replica.
+fn check_epoch(current: u64, epoch: u64) -> TCResult<()> {
+    if epoch < current {
+        Err(TCError::new(
+            ErrorType::Conflict,
+            format!(
+                "rejected a write from replica set epoch {}, which is older than the current epoch {}",
+                epoch, current
+            ),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+impl InstanceExt<Cluster> {
+    /// Join the replica set of this cluster, either by replaying the writes it missed while this
+    /// host was unreachable or, if there are none, by fully resynchronizing.
+    pub async fn rejoin(&self, txn: &Txn) -> TCResult<()> {
+        if let Some(replicas) = self.join(txn).await? {
+            if self.replay(txn, &replicas).await? == 0 {
+                self.replicate(txn, &replicas).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replay the writes which the given `replicas` queued for this host while it was unreachable.
+    ///
+    /// Returns the number of writes replayed. If this is zero, this host must fully resynchronize.
+    pub async fn replay(&self, txn: &Txn, replicas: &HashSet<Link>) -> TCResult<usize> {
+        if self.config().queue_size == 0 {
+            return Ok(0);
+        }
+
+        let self_link = txn.link(self.link().path().clone());
+        let sources: Vec<Link> = replicas
+            .iter()
+            .map(|replica| {
+                replica
+                    .clone()
+                    .append(REPLICAS.into())
+                    .append(MISSED.into())
+            })
+            .collect();
+
+        let queues = try_join_all(
+            sources
+                .iter()
+                .map(|source| txn.get(source.clone(), self_link.clone().into())),
+        )
+        .await?;
+
+        let mut missed = Vec::new();
+        let mut counts = Vec::with_capacity(sources.len());
+        for queue in queues {
+            let queue = Tuple::<MissedWrite>::try_cast_from(queue, |s| {
+                TCError::bad_request("invalid write queue", s)
+            })?;
+
+            counts.push(queue.len() as u64);
+            missed.extend(queue);
+        }
+
+        // the sort is stable, so writes within a single transaction stay in order
+        missed.sort_by_key(|missed| missed.txn_id);
+
+        let replayed = missed.len();
+        info!("{} replaying {} missed writes", self, replayed);
+
+        for MissedWrite { write, .. } in missed {
+            write.apply(txn, self).await?;
+        }
+
+        // only now that every write has been applied can the replicas drop their queues
+        try_join_all(
+            sources
+                .into_iter()
+                .zip(counts)
+                .filter(|(_, count)| *count > 0)
+                .map(|(source, count)| {
+                    let ack = Value::Tuple(vec![self_link.clone().into(), count.into()].into());
+                    txn.delete(source, ack)
+                }),
+        )
+        .await?;
+
+        Ok(replayed)
+    }
+}
+
 impl Eq for Cluster {}
 
 impl PartialEq for Cluster {
@@ -458,7 +1089,12 @@ impl Transact for Cluster {
         }
 
         join_all(self.chains.values().map(|chain| chain.commit(txn_id))).await;
-        join!(self.installed.commit(txn_id), self.replicas.commit(txn_id));
+        join!(
+            self.installed.commit(txn_id),
+            self.replicas.commit(txn_id),
+            self.epoch.commit(txn_id),
+            self.missed.commit(txn_id)
+        );
 
         {
             debug!(
@@ -477,7 +1113,9 @@ impl Transact for Cluster {
         self.owned.write().await.remove(txn_id);
         join!(
             self.installed.finalize(txn_id),
-            self.replicas.finalize(txn_id)
+            self.replicas.finalize(txn_id),
+            self.epoch.finalize(txn_id),
+            self.missed.finalize(txn_id)
         );
     }
 }
@@ -487,3 +1125,136 @@ impl fmt::Display for Cluster {
         write!(f, "Cluster {}", self.link.path())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::executor::block_on;
+    use futures::future;
+
+    use super::*;
+
+    fn acks(results: Vec<bool>) -> impl Stream<Item = (Link, TCResult<()>)> + Unpin {
+        let acks = results
+            .into_iter()
+            .enumerate()
+            .map(|(i, ok)| {
+                let replica: Link = format!("http://127.0.0.1:{}/app", 8702 + i)
+                    .parse()
+                    .unwrap();
+
+                if ok {
+                    (replica, Ok(()))
+                } else {
+                    (replica, Err(TCError::bad_gateway("replica is unreachable")))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        stream::iter(acks)
+    }
+
+    fn flaky(
+        failures: usize,
+        code: ErrorType,
+    ) -> (Arc<AtomicUsize>, impl Fn() -> future::Ready<TCResult<()>>) {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let send = move || {
+            if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                future::ready(Err(TCError::new(code, "replica failed".to_string())))
+            } else {
+                future::ready(Ok(()))
+            }
+        };
+
+        (attempts, send)
+    }
+
+    #[test]
+    fn test_quorum() {
+        assert!(Quorum::from_str("all").unwrap() == Quorum::All);
+        assert!(Quorum::from_str("majority").unwrap() == Quorum::Majority);
+        assert!(Quorum::from_str("most").is_err());
+
+        assert_eq!(Quorum::All.max_failures(5), 0);
+        assert_eq!(Quorum::Majority.max_failures(5), 2);
+        assert_eq!(Quorum::Majority.max_failures(4), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let delay = Duration::from_millis(1);
+
+        let (attempts, send) = flaky(2, ErrorType::BadGateway);
+        assert!(retry(send, 2, delay).await.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let (attempts, send) = flaky(3, ErrorType::BadGateway);
+        let result = retry(send, 2, delay).await;
+        assert!(result.unwrap_err().code() == ErrorType::BadGateway);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let (attempts, send) = flaky(1, ErrorType::Conflict);
+        let result = retry(send, 2, delay).await;
+        assert!(result.unwrap_err().code() == ErrorType::Conflict);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_commit_minority_failure() {
+        let max_failures = Quorum::Majority.max_failures(5);
+        let acknowledged = block_on(await_acks(
+            acks(vec![true, false, true, false, true]),
+            max_failures,
+        ))
+        .unwrap();
+
+        assert_eq!(acknowledged, 3);
+    }
+
+    #[test]
+    fn test_commit_majority_failure() {
+        let max_failures = Quorum::Majority.max_failures(5);
+        let result = block_on(await_acks(
+            acks(vec![false, true, false, false, true]),
+            max_failures,
+        ));
+
+        assert!(result.unwrap_err().code() == ErrorType::BadGateway);
+    }
+
+    #[test]
+    fn test_stale_epoch() {
+        // a replica which saw a membership change is at epoch 2,
+        // but the leader on the other side of a partition is still at epoch 1
+        assert!(check_epoch(2, 2).is_ok());
+        assert!(check_epoch(2, 3).is_ok());
+
+        let result = check_epoch(2, 1);
+        assert!(result.unwrap_err().code() == ErrorType::Conflict);
+    }
+
+    #[test]
+    fn test_replication_progress() {
+        let progress = ReplicationProgress::default();
+        assert_eq!(progress.status(), (0, 0));
+
+        // replicating two chains, one with three blocks to copy and one with one
+        let reported = progress.clone();
+        progress.extend(3);
+        progress.extend(1);
+
+        let mut fractions = vec![reported.fraction()];
+        for _ in 0..4 {
+            progress.advance();
+            fractions.push(reported.fraction());
+        }
+
+        assert_eq!(reported.status(), (4, 4));
+        assert_eq!(fractions.first(), Some(&0.));
+        assert_eq!(fractions.last(), Some(&1.));
+        assert!(fractions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -39,13 +39,14 @@ impl Owner {
             debug!("no dependencies to commit");
         }
 
-        let mutated = mutated.drain();
-        try_join_all(mutated.into_iter().map(|link| {
+        // keep the dependencies until they've all committed, so that a failure can be rolled back
+        try_join_all(mutated.iter().cloned().map(|link| {
             debug!("sending commit message to dependency at {}", link);
             txn.post(link, Map::<State>::default().into())
         }))
         .await?;
 
+        mutated.clear();
         Ok(())
     }
 
@@ -0,0 +1,332 @@
+use std::fmt;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::{self, TryFutureExt};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use log::debug;
+use safecast::{TryCastFrom, TryCastInto};
+
+use tc_btree::{BTreeInstance, BTreeType, Column, Node};
+use tc_error::*;
+use tc_transact::fs::{Dir, Persist};
+use tc_transact::{IntoView, Transact, Transaction};
+use tc_value::{NumberType, UIntType, ValueType};
+use tcgeneric::{label, Id, Label, TCPathBuf, Tuple};
+
+use crate::collection::BTreeFile;
+use crate::fs;
+use crate::object::InstanceExt;
+use crate::route::Public;
+use crate::scalar::{Link, Value};
+use crate::state::State;
+use crate::txn::{Txn, TxnId};
+
+use super::Cluster;
+
+type BTreeSlice = tc_btree::BTreeSlice<fs::File<Node>, fs::Dir, Txn>;
+
+const DELETE: Label = label("delete");
+const ORDINAL: Label = label("ordinal");
+const REPLICA: Label = label("replica");
+const WRITE: Label = label("write");
+const PATCH: Label = label("patch");
+const PUT: Label = label("put");
+
+const REPLICA_LEN: usize = 256;
+const WRITE_LEN: usize = 1_024;
+
+/// A write to a [`Cluster`] which must be replicated to each of its replicas.
+#[derive(Clone)]
+pub enum Write {
+    Delete(TCPathBuf, Value),
+    Patch(TCPathBuf, Value, State),
+    Put(TCPathBuf, Value, State),
+}
+
+impl Write {
+    fn path(&self) -> &TCPathBuf {
+        match self {
+            Self::Delete(path, _) => path,
+            Self::Patch(path, _, _) => path,
+            Self::Put(path, _, _) => path,
+        }
+    }
+
+    /// Send this write to the given `replica`.
+    pub async fn send(&self, txn: &Txn, replica: Link) -> TCResult<()> {
+        let mut target = replica;
+        target.extend(self.path().to_vec());
+
+        debug!("replicate {} to {}", self, target);
+
+        match self {
+            Self::Delete(_, key) => txn.delete(target, key.clone()).map_ok(|_| ()).await,
+            Self::Patch(_, key, value) => txn.patch(target, key.clone(), value.clone()).await,
+            Self::Put(_, key, value) => txn.put(target, key.clone(), value.clone()).await,
+        }
+    }
+
+    /// Apply this write to the local copy of the given [`Cluster`], without replicating it.
+    pub async fn apply(self, txn: &Txn, cluster: &InstanceExt<Cluster>) -> TCResult<()> {
+        debug!("apply missed {} to {}", self, cluster);
+
+        match self {
+            Self::Delete(path, key) => {
+                Public::delete(cluster, txn, &path, key)
+                    .map_ok(|_| ())
+                    .await
+            }
+            Self::Patch(path, key, value) => Public::patch(cluster, txn, &path, key, value).await,
+            Self::Put(path, key, value) => Public::put(cluster, txn, &path, key, value).await,
+        }
+    }
+}
+
+impl fmt::Display for Write {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Delete(path, key) => write!(f, "DELETE {}: {}", path, key),
+            Self::Patch(path, key, value) => write!(f, "PATCH {}: {} <- {}", path, key, value),
+            Self::Put(path, key, value) => write!(f, "PUT {}: {} <- {}", path, key, value),
+        }
+    }
+}
+
+/// A [`Write`] queued for a replica which could not be reached, with the ID of its transaction.
+#[derive(Clone)]
+pub struct MissedWrite {
+    pub txn_id: TxnId,
+    pub write: Write,
+}
+
+impl From<MissedWrite> for State {
+    fn from(missed: MissedWrite) -> Self {
+        let txn_id = State::from(Value::String(missed.txn_id.to_string()));
+
+        let op = |name: Label, path: TCPathBuf, key: Value| {
+            vec![
+                txn_id.clone(),
+                Value::from(Id::from(name)).into(),
+                Value::String(path.to_string()).into(),
+                key.into(),
+            ]
+        };
+
+        let tuple = match missed.write {
+            Write::Delete(path, key) => op(DELETE, path, key),
+            Write::Patch(path, key, value) => {
+                let mut tuple = op(PATCH, path, key);
+                tuple.push(value);
+                tuple
+            }
+            Write::Put(path, key, value) => {
+                let mut tuple = op(PUT, path, key);
+                tuple.push(value);
+                tuple
+            }
+        };
+
+        State::Tuple(tuple.into())
+    }
+}
+
+impl TryCastFrom<State> for MissedWrite {
+    fn can_cast_from(state: &State) -> bool {
+        match state {
+            State::Tuple(tuple) => tuple.len() == 4 || tuple.len() == 5,
+            _ => false,
+        }
+    }
+
+    fn opt_cast_from(state: State) -> Option<Self> {
+        let mut tuple: Tuple<State> = state.opt_cast_into()?;
+        let value = if tuple.len() == 5 { tuple.pop() } else { None };
+        let key: Value = tuple.pop()?.opt_cast_into()?;
+        let path: TCPathBuf = tuple.pop().and_then(|path| {
+            let path: Value = path.opt_cast_into()?;
+            path.opt_cast_into()
+        })?;
+
+        let op: Id = tuple.pop().and_then(|op| {
+            let op: Value = op.opt_cast_into()?;
+            op.opt_cast_into()
+        })?;
+
+        let txn_id = tuple.pop().and_then(|txn_id| {
+            let txn_id: Value = txn_id.opt_cast_into()?;
+            let txn_id: String = txn_id.opt_cast_into()?;
+            TxnId::from_str(&txn_id).ok()
+        })?;
+
+        let write = match (op.as_str(), value) {
+            ("delete", None) => Write::Delete(path, key),
+            ("patch", Some(value)) => Write::Patch(path, key, value),
+            ("put", Some(value)) => Write::Put(path, key, value),
+            _ => return None,
+        };
+
+        Some(Self { txn_id, write })
+    }
+}
+
+/// The [`MissedWrite`]s queued for each unreachable replica of a [`Cluster`], persisted so that
+/// a restart of this host does not lose them.
+#[derive(Clone)]
+pub struct WriteQueue {
+    btree: BTreeFile,
+}
+
+impl WriteQueue {
+    /// Load the queue stored in the given `dir`, or create a new one if there is none.
+    pub async fn load(txn: &Txn, dir: &fs::Dir, name: Id) -> TCResult<Self> {
+        let btree = if let Some(file) = dir.get_file(txn.id(), &name).await? {
+            BTreeFile::load(txn, Self::schema(), file).await?
+        } else {
+            let file = dir
+                .create_file(*txn.id(), name, BTreeType::default())
+                .await?;
+
+            BTreeFile::create(file, Self::schema(), *txn.id()).await?
+        };
+
+        Ok(Self { btree })
+    }
+
+    fn schema() -> Vec<Column> {
+        vec![
+            (REPLICA.into(), ValueType::Link, REPLICA_LEN).into(),
+            (
+                ORDINAL.into(),
+                ValueType::Number(NumberType::UInt(UIntType::U64)),
+            )
+                .into(),
+            (WRITE.into(), ValueType::Bytes, WRITE_LEN).into(),
+        ]
+    }
+
+    fn queue(&self, replica: &Link, reverse: bool) -> TCResult<BTreeSlice> {
+        let range = tc_btree::Range::with_prefix(vec![replica.clone().into()]);
+        self.btree.clone().slice(range, reverse)
+    }
+
+    /// Return `true` if there are writes queued for the given `replica`.
+    pub async fn contains(&self, txn_id: TxnId, replica: &Link) -> TCResult<bool> {
+        let queue = self.queue(replica, false)?;
+        queue.is_empty(txn_id).map_ok(|empty| !empty).await
+    }
+
+    /// Return the number of writes queued for the given `replica`.
+    pub async fn len(&self, txn_id: TxnId, replica: &Link) -> TCResult<u64> {
+        self.queue(replica, false)?.count(txn_id).await
+    }
+
+    /// Append the given `write` to the queue of the given `replica`.
+    pub async fn push(&self, txn: &Txn, replica: Link, write: MissedWrite) -> TCResult<()> {
+        let txn_id = *txn.id();
+
+        let mut last = self.queue(&replica, true)?.keys(txn_id).await?;
+        let ordinal = match last.try_next().await? {
+            Some(key) => {
+                u64::try_cast_from(key[1].clone(), |v| {
+                    TCError::internal(format!("invalid write queue ordinal: {}", v))
+                })? + 1
+            }
+            None => 0,
+        };
+
+        let view = State::from(write).into_view(txn.clone()).await?;
+        let encoded = tbon::en::encode(view).map_err(TCError::internal)?;
+        let encoded = encoded
+            .map_err(TCError::internal)
+            .try_fold(Vec::new(), |mut buffer, chunk| {
+                buffer.extend_from_slice(&chunk);
+                future::ready(Ok(buffer))
+            })
+            .await?;
+
+        let key = vec![
+            replica.into(),
+            Value::from(ordinal),
+            Value::Bytes(Bytes::from(encoded)),
+        ];
+
+        self.btree.insert(txn_id, key).await
+    }
+
+    /// Return the writes queued for the given `replica`, in the order they were queued.
+    ///
+    /// The writes stay queued until acknowledged by a call to [`Self::acknowledge`].
+    pub async fn read(&self, txn: &Txn, replica: &Link) -> TCResult<Vec<MissedWrite>> {
+        let keys: Vec<Vec<Value>> = self
+            .queue(replica, false)?
+            .keys(*txn.id())
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut writes = Vec::with_capacity(keys.len());
+        for mut key in keys {
+            let encoded = key
+                .pop()
+                .ok_or_else(|| TCError::internal("missed write has no content"))?;
+
+            let encoded = Bytes::try_cast_from(encoded, |v| {
+                TCError::internal(format!("invalid missed write: {}", v))
+            })?;
+
+            let encoded = stream::once(future::ready(Ok::<Bytes, TCError>(encoded)));
+            let state: State = tbon::de::try_decode(txn.clone(), encoded)
+                .map_err(|e| TCError::internal(format!("invalid missed write: {}", e)))
+                .await?;
+
+            let write = MissedWrite::try_cast_from(state, |s| {
+                TCError::internal(format!("invalid missed write: {}", s))
+            })?;
+
+            writes.push(write);
+        }
+
+        Ok(writes)
+    }
+
+    /// Remove the first `count` writes queued for the given `replica`, once it has applied them.
+    pub async fn acknowledge(&self, txn_id: TxnId, replica: &Link, count: u64) -> TCResult<()> {
+        let keys: Vec<Vec<Value>> = self
+            .queue(replica, false)?
+            .keys(txn_id)
+            .await?
+            .take(count as usize)
+            .try_collect()
+            .await?;
+
+        for mut key in keys {
+            key.pop();
+            let range = tc_btree::Range::with_prefix(key);
+            self.btree
+                .clone()
+                .slice(range, false)?
+                .delete(txn_id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Discard the queue of the given `replica`.
+    pub async fn remove(&self, txn_id: TxnId, replica: &Link) -> TCResult<()> {
+        self.queue(replica, false)?.delete(txn_id).await
+    }
+}
+
+#[async_trait]
+impl Transact for WriteQueue {
+    async fn commit(&self, txn_id: &TxnId) {
+        self.btree.commit(txn_id).await
+    }
+
+    async fn finalize(&self, txn_id: &TxnId) {
+        self.btree.finalize(txn_id).await
+    }
+}
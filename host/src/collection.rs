@@ -1,20 +1,22 @@
 //! A [`Collection`] such as a [`BTree`] or [`Table`].
 
 /// The `Collection` enum used in `State::Collection`.
+use std::convert::TryInto;
 use std::fmt;
 
 use async_trait::async_trait;
 use destream::{de, en};
-use futures::TryFutureExt;
+use futures::{Stream, TryFutureExt, TryStreamExt};
 use log::debug;
 use safecast::{CastInto, TryCastFrom};
+use tokio::io::{AsyncRead, AsyncWrite};
 
-use tc_btree::BTreeView;
+use tc_btree::{BTreeInstance, BTreeView};
 use tc_error::*;
-use tc_table::TableView;
+use tc_table::{TableInstance, TableView};
 #[cfg(feature = "tensor")]
-use tc_tensor::TensorView;
-use tc_transact::fs::Dir;
+use tc_tensor::{TensorAccess, TensorView};
+use tc_transact::fs::{Dir, Hash};
 use tc_transact::{IntoView, Transaction};
 use tcgeneric::{
     path_label, Class, Instance, NativeClass, PathLabel, PathSegment, TCPath, TCPathBuf,
@@ -55,6 +57,11 @@ pub type SparseTable =
 
 pub const PREFIX: PathLabel = path_label(&["state", "collection"]);
 
+#[cfg(feature = "tensor")]
+const RTOL: f64 = 1e-5;
+#[cfg(feature = "tensor")]
+const ATOL: f64 = 1e-8;
+
 /// The [`Class`] of a [`Collection`].
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum CollectionType {
@@ -160,7 +167,7 @@ impl fmt::Display for CollectionType {
 #[derive(Clone)]
 pub enum Collection {
     BTree(BTree),
-    Table(Table),
+    Table(Box<Table>),
     #[cfg(feature = "tensor")]
     Tensor(Tensor),
 }
@@ -178,6 +185,101 @@ impl Instance for Collection {
     }
 }
 
+impl Collection {
+    /// Return `true` if this `Collection` has the same schema and contents as `other`.
+    ///
+    /// Floating-point `Tensor`s are compared using `allclose` with a small default tolerance.
+    pub async fn equals(&self, other: &Self, txn: &Txn) -> TCResult<bool> {
+        let txn_id = *txn.id();
+
+        match (self, other) {
+            (Self::BTree(this), Self::BTree(that)) => {
+                if this.schema() != that.schema() {
+                    return Ok(false);
+                }
+
+                let this = this.clone().keys(txn_id).await?;
+                let that = that.clone().keys(txn_id).await?;
+                streams_equal(this, that).await
+            }
+            (Self::Table(this), Self::Table(that)) => {
+                if this.schema() != that.schema() {
+                    return Ok(false);
+                }
+
+                let this = this.clone().rows(txn_id).await?;
+                let that = that.clone().rows(txn_id).await?;
+                streams_equal(this, that).await
+            }
+            #[cfg(feature = "tensor")]
+            (Self::Tensor(this), Self::Tensor(that)) => {
+                if this.dtype() != that.dtype() || this.shape() != that.shape() {
+                    return Ok(false);
+                }
+
+                this.clone()
+                    .allclose(that.clone(), RTOL, ATOL, txn.clone())
+                    .await
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Compute a SHA-256 digest of the contents of this `Collection`, in canonical order.
+    ///
+    /// Two replicas of a `Collection` with the same contents have the same digest,
+    /// so comparing digests is a cheap way to detect divergence without streaming every row.
+    pub async fn digest(&self, txn: &Txn) -> TCResult<[u8; 32]> {
+        let hash = match self {
+            Self::BTree(btree) => btree.hash(txn).await?,
+            Self::Table(table) => table.hash(txn).await?,
+            #[cfg(feature = "tensor")]
+            Self::Tensor(Tensor::Dense(dense)) => dense.hash(txn).await?,
+            #[cfg(feature = "tensor")]
+            Self::Tensor(Tensor::Sparse(sparse)) => sparse.hash(txn).await?,
+        };
+
+        hash[..]
+            .try_into()
+            .map_err(|_| TCError::internal(format!("invalid SHA-256 digest: {:?}", hash)))
+    }
+
+    /// Write this `Collection`, schema and data, to the given `writer` as a single portable
+    /// archive which can be restored with [`Collection::import_archive`].
+    pub async fn export_archive<W: AsyncWrite + Send + Unpin>(
+        &self,
+        txn: &Txn,
+        writer: W,
+    ) -> TCResult<()> {
+        let view = self.clone().into_view(txn.clone()).await?;
+        fs::write_archive(view, writer).await
+    }
+
+    /// Restore a `Collection` from an archive written by [`Collection::export_archive`],
+    /// storing its data in a new subdirectory of the given transaction's context.
+    pub async fn import_archive<R: AsyncRead + Send + Unpin>(
+        reader: R,
+        txn: &Txn,
+    ) -> TCResult<Self> {
+        let txn = txn.subcontext_tmp().await?;
+        fs::read_archive(txn, reader).await
+    }
+}
+
+async fn streams_equal<T, S>(mut left: S, mut right: S) -> TCResult<bool>
+where
+    T: PartialEq,
+    S: Stream<Item = TCResult<T>> + Unpin,
+{
+    loop {
+        match (left.try_next().await?, right.try_next().await?) {
+            (Some(l), Some(r)) if l == r => {}
+            (None, None) => return Ok(true),
+            _ => return Ok(false),
+        }
+    }
+}
+
 impl From<BTree> for Collection {
     fn from(btree: BTree) -> Self {
         Self::BTree(btree)
@@ -192,13 +294,13 @@ impl From<BTreeFile> for Collection {
 
 impl From<Table> for Collection {
     fn from(table: Table) -> Self {
-        Self::Table(table)
+        Self::Table(Box::new(table))
     }
 }
 
 impl From<TableIndex> for Collection {
     fn from(table: TableIndex) -> Self {
-        Self::Table(table.into())
+        Self::Table(Box::new(table.into()))
     }
 }
 
@@ -276,7 +378,13 @@ impl CollectionVisitor {
                     .await
             }
 
-            CollectionType::Table(_) => access.next_value(self.txn).map_ok(Collection::Table).await,
+            CollectionType::Table(_) => {
+                access
+                    .next_value(self.txn)
+                    .map_ok(Box::new)
+                    .map_ok(Collection::Table)
+                    .await
+            }
 
             #[cfg(feature = "tensor")]
             CollectionType::Tensor(tt) => match tt {
@@ -331,7 +439,11 @@ impl<'en> IntoView<'en, fs::Dir> for Collection {
     async fn into_view(self, txn: Self::Txn) -> TCResult<Self::View> {
         match self {
             Self::BTree(btree) => btree.into_view(txn).map_ok(CollectionView::BTree).await,
-            Self::Table(table) => table.into_view(txn).map_ok(CollectionView::Table).await,
+            Self::Table(table) => {
+                let schema = table.schema();
+                let rows = crate::stream::table_rows(*table, txn).await?;
+                Ok(CollectionView::Table(TableView::new(schema, rows)))
+            }
             #[cfg(feature = "tensor")]
             Self::Tensor(tensor) => tensor.into_view(txn).map_ok(CollectionView::Tensor).await,
         }
@@ -385,3 +497,488 @@ impl<'en> en::IntoStream<'en> for CollectionView<'en> {
         map.end()
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::net::Ipv4Addr;
+    use std::time::Duration;
+
+    use tc_transact::TxnId;
+    use uuid::Uuid;
+
+    use crate::gateway::{self, Gateway};
+    use crate::kernel::Kernel;
+    use crate::scalar::NumberType;
+    use crate::txn::{new_request_id, TxnServer};
+
+    use super::*;
+
+    pub(crate) async fn new_txn() -> Txn {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let workspace = fs::Dir::load(fs::Cache::new(1_000_000), path, vec![])
+            .await
+            .unwrap();
+
+        let config = gateway::Config {
+            addr: Ipv4Addr::LOCALHOST.into(),
+            http_port: 8702,
+            request_ttl: Duration::from_secs(30),
+            max_op_steps: 1_000,
+            max_op_states: 1_000,
+            compaction_interval: Duration::from_secs(60),
+            compaction_age: Duration::from_secs(60),
+            op_cache_size: 0,
+            op_cache_ttl: Duration::from_secs(0),
+            high_priority_concurrency: 1,
+            low_priority_concurrency: 1,
+            reduce_concurrency: 1,
+            tls: None,
+        };
+
+        let txn_server = TxnServer::new(workspace).await;
+        let gateway = Gateway::new(config, Kernel::new(vec![]), txn_server);
+        let txn_id = TxnId::new(Gateway::time());
+        gateway
+            .new_txn(txn_id, None, new_request_id())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_select_covering_index() {
+        use tc_btree::BTreeInstance;
+        use tc_table::{Bounds, ColumnBound, IndexSchema, TableSchema};
+        use tc_value::{Bound, Range, ValueType};
+        use tcgeneric::Id;
+
+        let txn = new_txn().await;
+        let txn_id = *txn.id();
+
+        let name: Id = "name".parse().unwrap();
+        let views: Id = "views".parse().unwrap();
+
+        let primary: IndexSchema = (
+            vec![(name.clone(), ValueType::String, 16).into()],
+            vec![(views.clone(), ValueType::Number(NumberType::uint64())).into()],
+        )
+            .into();
+
+        let schema = TableSchema::new(primary, vec![(views.clone(), vec![views.clone()])]);
+
+        let dir = txn.context().create_dir_tmp(txn_id).await.unwrap();
+        let table = TableIndex::create(&dir, schema, txn_id).await.unwrap();
+        for i in 0..20u64 {
+            let key = vec![Value::String(format!("row {:02}", i))];
+            table
+                .upsert(txn_id, key, vec![Value::from(i)])
+                .await
+                .unwrap();
+        }
+
+        // spy on the primary index by emptying it, so that any read of it will come up short
+        table.primary().btree().delete(txn_id).await.unwrap();
+
+        let range = Range {
+            start: Bound::In(Value::from(5u64)),
+            end: Bound::Ex(Value::from(10u64)),
+        };
+
+        let bounds: Bounds = vec![(views.clone(), ColumnBound::In(range))]
+            .into_iter()
+            .collect();
+
+        let slice = table.slice(bounds).unwrap();
+
+        // reading whole rows of the slice looks up each key in the (now empty) primary index
+        let all: Vec<Vec<Value>> = slice
+            .clone()
+            .rows(txn_id)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(all.is_empty());
+
+        // but the auxiliary index on "views" covers both "name" and "views"
+        let selected: Vec<Vec<Value>> = slice
+            .select(vec![name, views])
+            .unwrap()
+            .rows(txn_id)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let expected: Vec<Vec<Value>> = (5..10u64)
+            .map(|i| vec![Value::String(format!("row {:02}", i)), Value::from(i)])
+            .collect();
+
+        assert_eq!(selected, expected);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_index() {
+        use futures::stream;
+        use tc_btree::BTreeInstance;
+        use tc_table::{Change, IndexSchema, TableSchema};
+        use tc_value::ValueType;
+        use tcgeneric::{Id, Map};
+
+        let txn = new_txn().await;
+        let txn_id = *txn.id();
+
+        let name: Id = "name".parse().unwrap();
+        let views: Id = "views".parse().unwrap();
+
+        let primary: IndexSchema = (
+            vec![(name.clone(), ValueType::String, 16).into()],
+            vec![(views.clone(), ValueType::Number(NumberType::uint64())).into()],
+        )
+            .into();
+
+        let dir = txn.context().create_dir_tmp(txn_id).await.unwrap();
+        let table = TableIndex::create(&dir, TableSchema::from(primary), txn_id)
+            .await
+            .unwrap();
+
+        for i in 0..10u64 {
+            let key = vec![Value::String(format!("row {:02}", i))];
+            table
+                .upsert(txn_id, key, vec![Value::from(i)])
+                .await
+                .unwrap();
+        }
+
+        let mut index = table
+            .clone()
+            .index(txn.clone(), Some(vec![views.clone()]))
+            .await
+            .unwrap();
+
+        let new_row = [Value::String("row 10".to_string()), Value::from(10u64)];
+        table
+            .upsert(txn_id, vec![new_row[0].clone()], vec![new_row[1].clone()])
+            .await
+            .unwrap();
+
+        let change: Map<Value> = vec![name.clone(), views.clone()]
+            .into_iter()
+            .zip(new_row.iter().cloned())
+            .collect();
+
+        // spy on the source by emptying it, so that any read of it will come up short
+        table.primary().btree().delete(txn_id).await.unwrap();
+
+        let changes = stream::iter(vec![Ok(Change::Insert(change))]);
+        index
+            .refresh(table.clone(), Some(changes), txn.clone())
+            .await
+            .unwrap();
+
+        // only the changed row was inserted
+        let rows: Vec<Vec<Value>> = index
+            .clone()
+            .rows(txn_id)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(rows.len(), 11);
+        assert_eq!(
+            rows.last(),
+            Some(&vec![
+                Value::from(10u64),
+                Value::String("row 10".to_string())
+            ])
+        );
+
+        // without a change stream, the index is rebuilt from its (now empty) source
+        let changes: Option<stream::Iter<std::vec::IntoIter<TCResult<Change>>>> = None;
+        index.refresh(table, changes, txn.clone()).await.unwrap();
+        assert!(index.is_empty(&txn).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_track_changes() {
+        use tc_table::{Change, IndexSchema, TableSchema};
+        use tc_transact::Transact;
+        use tc_value::ValueType;
+        use tcgeneric::{Id, Map};
+
+        let txn = new_txn().await;
+        let first = *txn.id();
+
+        let name: Id = "name".parse().unwrap();
+        let views: Id = "views".parse().unwrap();
+
+        let primary: IndexSchema = (
+            vec![(name.clone(), ValueType::String, 16).into()],
+            vec![(views.clone(), ValueType::Number(NumberType::uint64())).into()],
+        )
+            .into();
+
+        let dir = txn.context().create_dir_tmp(first).await.unwrap();
+        let untracked = TableIndex::create(&dir, TableSchema::from(primary.clone()), first)
+            .await
+            .unwrap();
+
+        assert!(untracked.changes(first, None).await.is_err());
+
+        let dir = txn.context().create_dir_tmp(first).await.unwrap();
+        let schema = TableSchema::from(primary).with_change_tracking();
+        let table = TableIndex::create(&dir, schema, first).await.unwrap();
+
+        for i in 0..2u64 {
+            let key = vec![Value::String(format!("row {}", i))];
+            table
+                .upsert(first, key, vec![Value::from(i)])
+                .await
+                .unwrap();
+        }
+
+        table.commit(&first).await;
+
+        let second = TxnId::new(Gateway::time());
+        let key = vec![Value::String("row 0".to_string())];
+        table
+            .upsert(second, key, vec![Value::from(5u64)])
+            .await
+            .unwrap();
+
+        let row: Map<Value> = vec![
+            (name, Value::String("row 1".to_string())),
+            (views, Value::from(1u64)),
+        ]
+        .into_iter()
+        .collect();
+
+        table.delete_row(second, row).await.unwrap();
+
+        let describe = |change: &Change| match change {
+            Change::Insert(_) => "insert",
+            Change::Delete(_) => "delete",
+            Change::Update(_, _) => "update",
+        };
+
+        let all: Vec<Change> = table
+            .changes(second, None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let all: Vec<&str> = all.iter().map(describe).collect();
+        assert_eq!(all, ["insert", "insert", "update", "delete"]);
+
+        let since: Vec<Change> = table
+            .changes(second, Some(first))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        let since: Vec<&str> = since.iter().map(describe).collect();
+        assert_eq!(since, ["update", "delete"]);
+
+        // compaction discards the changes made by the first transaction
+        table.compact_changes(second, first).await.unwrap();
+
+        let compacted: Vec<Change> = table
+            .changes(second, None)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(compacted.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_verify_btree() {
+        use tc_btree::{BTreeInstance, Node};
+        use tc_transact::fs::File;
+        use tc_value::ValueType;
+        use tcgeneric::Id;
+
+        type Encoded = (
+            bool,
+            Vec<(bool, Vec<Value>)>,
+            Option<Id>,
+            Vec<Id>,
+            bool,
+            Option<usize>,
+            bool,
+        );
+
+        let txn = new_txn().await;
+        let txn_id = *txn.id();
+
+        let dir = txn.context().create_dir_tmp(txn_id).await.unwrap();
+        let file: fs::File<Node> = dir
+            .create_file(txn_id, "btree".parse().unwrap(), BTreeType::default())
+            .await
+            .unwrap();
+
+        let schema = vec![(
+            "n".parse().unwrap(),
+            ValueType::Number(NumberType::uint64()),
+        )
+            .into()];
+        let btree = BTreeFile::create(file.clone(), schema, txn_id)
+            .await
+            .unwrap();
+        for i in 0..3u64 {
+            btree.insert(txn_id, vec![Value::from(i)]).await.unwrap();
+        }
+
+        assert!(btree.verify(txn_id).await.unwrap().is_empty());
+
+        // corrupt the ordering of the (only) root node by rewriting its block with reversed keys
+        let block_ids = file.block_ids(&txn_id).await.unwrap();
+        assert_eq!(block_ids.len(), 1);
+
+        let root_id = block_ids.into_iter().next().unwrap();
+        let mut root = file.write_block(txn_id, root_id).await.unwrap();
+
+        let encoded = tbon::en::encode((*root).clone()).unwrap();
+        let mut node: Encoded = tbon::de::try_decode((), encoded).await.unwrap();
+        node.1.reverse();
+
+        let encoded = tbon::en::encode(node).unwrap();
+        *root = tbon::de::try_decode((), encoded).await.unwrap();
+        std::mem::drop(root);
+
+        let problems = btree.verify(txn_id).await.unwrap();
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].ends_with("key 0 is not less than key 1"));
+        assert!(problems[1].ends_with("key 1 is not less than key 2"));
+    }
+
+    #[cfg(feature = "tensor")]
+    #[tokio::test]
+    async fn test_sparse_filled_at() {
+        use tc_tensor::{
+            AxisBounds, Bounds, Coord, Schema, SparseTable, TensorIO, TensorTransform,
+        };
+
+        use crate::scalar::Number;
+
+        let txn = new_txn().await;
+        let txn_id = *txn.id();
+
+        let dir = txn.context().create_dir_tmp(txn_id).await.unwrap();
+        let schema = Schema {
+            shape: vec![4, 5].into(),
+            dtype: NumberType::uint64(),
+        };
+
+        let tensor: SparseTensor<SparseTable<_, _, _, Txn>> =
+            SparseTensor::create(&dir, schema, txn_id).await.unwrap();
+        for (i, j) in vec![(0, 1), (1, 0), (1, 3), (2, 2), (2, 4), (3, 3)] {
+            let value = Number::from(i * 10 + j);
+            tensor
+                .write_value_at(txn_id, vec![i, j], value)
+                .await
+                .unwrap();
+        }
+
+        let bounds = Bounds::from(vec![AxisBounds::In(1..3), AxisBounds::In(1..5)]);
+
+        // the coordinates of a slice are relative to its bounds, so offset them to compare
+        let expected: Vec<(Coord, Number)> = tensor
+            .clone()
+            .slice(bounds.clone())
+            .unwrap()
+            .into_inner()
+            .filled(txn.clone())
+            .await
+            .unwrap()
+            .map_ok(|(coord, value)| (vec![coord[0] + 1, coord[1] + 1], value))
+            .try_collect()
+            .await
+            .unwrap();
+
+        let actual: Vec<(Coord, Number)> = tensor
+            .filled_at(bounds, txn)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            actual
+                .iter()
+                .map(|(coord, _)| coord.to_vec())
+                .collect::<Vec<_>>(),
+            vec![vec![1, 3], vec![2, 2], vec![2, 4]]
+        );
+
+        assert_eq!(actual.len(), expected.len());
+        for ((actual_coord, actual_value), (expected_coord, expected_value)) in
+            actual.into_iter().zip(expected)
+        {
+            assert_eq!(actual_coord, expected_coord);
+            assert!(actual_value == expected_value);
+        }
+    }
+
+    #[cfg(feature = "tensor")]
+    #[tokio::test]
+    async fn test_sparse_tbon_round_trip() {
+        use tc_tensor::{Coord, Schema, TensorIO};
+
+        use crate::scalar::{FloatType, Number};
+
+        let txn = new_txn().await;
+        let txn_id = *txn.id();
+
+        let dir = txn.context().create_dir_tmp(txn_id).await.unwrap();
+        let schema = Schema {
+            shape: vec![2, 3].into(),
+            dtype: NumberType::Float(FloatType::F32),
+        };
+
+        let tensor: SparseTensor<SparseTable> =
+            SparseTensor::create(&dir, schema, txn_id).await.unwrap();
+
+        let filled = vec![
+            (vec![0u64, 1], Number::from(1.5f32)),
+            (vec![1u64, 2], Number::from(-2f32)),
+        ];
+
+        for (coord, value) in filled.iter().cloned() {
+            tensor.write_value_at(txn_id, coord, value).await.unwrap();
+        }
+
+        let view = tensor.clone().into_view(txn.clone()).await.unwrap();
+        let encoded = tbon::en::encode(view).unwrap();
+        let decoded: SparseTensor<SparseTable> =
+            tbon::de::try_decode(txn.clone(), encoded).await.unwrap();
+
+        assert_eq!(decoded.shape().to_vec(), tensor.shape().to_vec());
+        assert!(decoded.dtype() == tensor.dtype());
+
+        let actual: Vec<(Coord, Number)> = decoded
+            .into_inner()
+            .filled(txn)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(actual.len(), filled.len());
+        for ((coord, value), (expected_coord, expected_value)) in actual.into_iter().zip(filled) {
+            assert_eq!(coord, expected_coord);
+            assert!(value == expected_value);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! A portable, single-file archive of a whole `Collection` or `Chain`: a version header followed
+//! by its schema and data, encoded as TBON exactly as it would be sent over the network.
+
+use bytes::{Bytes, BytesMut};
+use destream::{de, en};
+use futures::stream::{self, Stream, StreamExt};
+use futures::{future, TryFutureExt, TryStreamExt};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
+
+use tc_error::*;
+use tcgeneric::TCBoxTryStream;
+
+/// The version of the archive format written by this host.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const MAGIC: &[u8] = b"TCAR";
+const HEADER_LEN: usize = 8;
+
+/// Encode the given `view` as a stream of archive chunks, beginning with the version header.
+pub fn encode_archive<'en, V>(view: V) -> TCResult<TCBoxTryStream<'en, Bytes>>
+where
+    V: en::IntoStream<'en> + 'en,
+{
+    let mut header = BytesMut::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&ARCHIVE_VERSION.to_be_bytes());
+
+    let body = tbon::en::encode(view)
+        .map_err(TCError::internal)?
+        .map_err(TCError::internal);
+
+    let header = stream::once(future::ready(Ok(header.freeze())));
+    Ok(Box::pin(header.chain(body)))
+}
+
+/// Decode an instance of `T` from a stream of archive chunks, as written by [`encode_archive`].
+pub async fn decode_archive<T, S>(context: T::Context, mut chunks: S) -> TCResult<T>
+where
+    T: de::FromStream,
+    S: Stream<Item = TCResult<Bytes>> + Send + Unpin,
+{
+    let mut header = BytesMut::with_capacity(HEADER_LEN);
+    while header.len() < HEADER_LEN {
+        match chunks.try_next().await? {
+            Some(chunk) => header.extend_from_slice(&chunk),
+            None => {
+                return Err(TCError::bad_request(
+                    "archive is too short to contain a header of length",
+                    HEADER_LEN,
+                ))
+            }
+        }
+    }
+
+    let body = header.split_off(HEADER_LEN).freeze();
+
+    if &header[..MAGIC.len()] != MAGIC {
+        return Err(TCError::bad_request(
+            "not a Tinychain archive",
+            String::from_utf8_lossy(&header[..MAGIC.len()]),
+        ));
+    }
+
+    let mut version = [0u8; 4];
+    version.copy_from_slice(&header[MAGIC.len()..]);
+    let version = u32::from_be_bytes(version);
+    if version != ARCHIVE_VERSION {
+        return Err(TCError::bad_request(
+            format!("expected archive version {} but found", ARCHIVE_VERSION),
+            version,
+        ));
+    }
+
+    let chunks = stream::once(future::ready(Ok(body))).chain(chunks);
+    tbon::de::try_decode(context, chunks)
+        .map_err(|e| TCError::bad_request("invalid archive", e))
+        .await
+}
+
+/// Encode the given `view` and write it to `writer` as it's encoded, without buffering it.
+pub async fn write_archive<'en, V, W>(view: V, mut writer: W) -> TCResult<()>
+where
+    V: en::IntoStream<'en> + 'en,
+    W: AsyncWrite + Send + Unpin,
+{
+    let mut archive = encode_archive(view)?;
+
+    while let Some(chunk) = archive.try_next().await? {
+        writer.write_all(&chunk).map_err(io_error).await?;
+    }
+
+    writer.flush().map_err(io_error).await
+}
+
+/// Decode an instance of `T` from the archive read from `reader`.
+pub async fn read_archive<T, R>(context: T::Context, reader: R) -> TCResult<T>
+where
+    T: de::FromStream,
+    R: AsyncRead + Send + Unpin,
+{
+    let chunks = ReaderStream::new(reader).map_err(io_error);
+    decode_archive(context, chunks).await
+}
+
+fn io_error(cause: std::io::Error) -> TCError {
+    TCError::internal(format!("archive I/O error: {}", cause)).with_source(cause)
+}
@@ -2,6 +2,8 @@
 
 use std::convert::{TryFrom, TryInto};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 #[cfg(feature = "tensor")]
 use afarray::Array;
@@ -222,6 +224,7 @@ struct Evict;
 pub struct Cache {
     tx: mpsc::Sender<Evict>,
     lfu: RwLock<LFU>,
+    writes: Arc<AtomicU64>,
 }
 
 impl Cache {
@@ -233,12 +236,22 @@ impl Cache {
         let cache = Self {
             tx,
             lfu: RwLock::new(LFU::new(max_size, Policy)),
+            writes: Arc::new(AtomicU64::new(0)),
         };
 
         spawn_cleanup_thread(cache.lfu.clone(), rx);
         cache
     }
 
+    /// Return the number of blocks created or locked for writing through this `Cache`.
+    pub fn block_writes(&self) -> u64 {
+        self.writes.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn record_write(&self) {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+    }
+
     async fn _read_and_insert<B: BlockData>(
         mut cache: RwLockWriteGuard<LFU>,
         path: PathBuf,
@@ -322,6 +335,14 @@ impl Cache {
             .await
     }
 
+    /// Lock the cache for writing before moving the given filesystem directory.
+    pub async fn rename_dir(&self, from: PathBuf, to: PathBuf) -> TCResult<()> {
+        let _lock = self.lfu.write().await;
+        tokio::fs::rename(&from, &to)
+            .map_err(|e| io_err(e, format!("move from {:?} to {:?}", from, to)))
+            .await
+    }
+
     async fn _sync(cache: &mut LFU, path: &PathBuf) -> TCResult<bool> {
         debug!("sync block at {:?} with filesystem", &path);
 
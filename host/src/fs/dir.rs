@@ -9,7 +9,8 @@ use std::pin::Pin;
 #[cfg(feature = "tensor")]
 use afarray::Array;
 use async_trait::async_trait;
-use futures::future::{join_all, Future, TryFutureExt};
+use futures::future::{join_all, try_join_all, Future, TryFutureExt};
+use futures::try_join;
 use log::debug;
 use uuid::Uuid;
 
@@ -20,7 +21,7 @@ use tc_tensor::TensorType;
 use tc_transact::fs::{self, BlockData};
 use tc_transact::lock::TxnLock;
 use tc_transact::{Transact, TxnId};
-use tcgeneric::{Id, PathSegment};
+use tcgeneric::{Id, PathSegment, TCBoxTryFuture, TCPath};
 
 use crate::chain::{self, ChainBlock};
 use crate::collection::CollectionType;
@@ -100,6 +101,39 @@ impl FileEntry {
             ))),
         }
     }
+
+    fn path(&self) -> &PathBuf {
+        match self {
+            Self::BTree(file) => file.path(),
+            Self::Chain(file) => file.path(),
+            Self::Value(file) => file.path(),
+
+            #[cfg(feature = "tensor")]
+            Self::Tensor(file) => file.path(),
+        }
+    }
+
+    async fn relocate(&self, txn_id: &TxnId, path: PathBuf) -> TCResult<Self> {
+        match self {
+            Self::BTree(file) => file.relocate(txn_id, path).map_ok(Self::BTree).await,
+            Self::Chain(file) => file.relocate(txn_id, path).map_ok(Self::Chain).await,
+            Self::Value(file) => file.relocate(txn_id, path).map_ok(Self::Value).await,
+
+            #[cfg(feature = "tensor")]
+            Self::Tensor(file) => file.relocate(txn_id, path).map_ok(Self::Tensor).await,
+        }
+    }
+
+    async fn size_bytes(&self, txn_id: TxnId) -> TCResult<u64> {
+        match self {
+            Self::BTree(file) => file.size_bytes(txn_id).await,
+            Self::Chain(file) => file.size_bytes(txn_id).await,
+            Self::Value(file) => file.size_bytes(txn_id).await,
+
+            #[cfg(feature = "tensor")]
+            Self::Tensor(file) => file.size_bytes(txn_id).await,
+        }
+    }
 }
 
 impl From<File<ChainBlock>> for FileEntry {
@@ -190,6 +224,36 @@ enum DirEntry {
     File(FileEntry),
 }
 
+impl DirEntry {
+    fn path(&self) -> &PathBuf {
+        match self {
+            Self::Dir(dir) => &dir.path,
+            Self::File(file) => file.path(),
+        }
+    }
+
+    /// The path on the host filesystem where this entry belongs, if it's named `name` in `parent`.
+    fn path_in(&self, parent: &PathBuf, name: &PathSegment) -> PathBuf {
+        let mut path = fs_path(parent, name);
+        if let Self::File(file) = self {
+            if let Some(ext) = file.path().extension() {
+                path.set_extension(ext);
+            }
+        }
+
+        path
+    }
+
+    fn relocate<'a>(&'a self, txn_id: &'a TxnId, path: PathBuf) -> TCBoxTryFuture<'a, Self> {
+        Box::pin(async move {
+            match self {
+                Self::Dir(dir) => dir.relocate(txn_id, path).map_ok(Self::Dir).await,
+                Self::File(file) => file.relocate(txn_id, path).map_ok(Self::File).await,
+            }
+        })
+    }
+}
+
 impl fmt::Display for DirEntry {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -262,6 +326,16 @@ impl Dir {
         Ok(contents.keys().cloned().collect())
     }
 
+    /// Return the [`Cache`] through which the blocks in this `Dir` are read and written.
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    /// Return the [`TxnId`] of each transaction with uncommitted, unlocked changes to this `Dir`.
+    pub fn pending_txns(&self) -> Vec<TxnId> {
+        self.contents.idle_versions()
+    }
+
     pub async fn get_or_create_dir(&self, txn_id: TxnId, name: PathSegment) -> TCResult<Self> {
         if let Some(dir) = fs::Dir::get_dir(self, &txn_id, &name).await? {
             Ok(dir)
@@ -270,6 +344,151 @@ impl Dir {
         }
     }
 
+    /// Move the entry at `from` to `to` within the given transaction.
+    ///
+    /// Both paths are relative to this `Dir`, and the parent directory of `to` must already exist.
+    /// The move is applied to the host filesystem when the transaction commits; handles to the
+    /// moved entry (or anything under it) should not be held across the commit.
+    pub async fn rename(
+        &self,
+        txn_id: TxnId,
+        from: &[PathSegment],
+        to: &[PathSegment],
+    ) -> TCResult<()> {
+        let (from_name, from_parent) = from
+            .split_last()
+            .ok_or_else(|| TCError::bad_request("cannot move", "the root directory"))?;
+
+        let (to_name, to_parent) = to
+            .split_last()
+            .ok_or_else(|| TCError::bad_request("cannot replace", "the root directory"))?;
+
+        if to.len() > from.len() && to.starts_with(from) {
+            return Err(TCError::bad_request(
+                "cannot move a directory into itself",
+                TCPath::from(to),
+            ));
+        }
+
+        let source = self.resolve(&txn_id, from_parent).await?;
+        let dest = self.resolve(&txn_id, to_parent).await?;
+
+        if source.path == dest.path {
+            let mut contents = source.contents.write(txn_id).await?;
+            if contents.contains_key(to_name) {
+                return Err(TCError::bad_request(
+                    "filesystem entry already exists",
+                    TCPath::from(to),
+                ));
+            }
+
+            let entry = contents
+                .remove(from_name)
+                .ok_or_else(|| TCError::not_found(TCPath::from(from)))?;
+
+            contents.insert(to_name.clone(), entry);
+        } else {
+            let (mut source, mut dest) =
+                try_join!(source.contents.write(txn_id), dest.contents.write(txn_id))?;
+
+            if dest.contains_key(to_name) {
+                return Err(TCError::bad_request(
+                    "filesystem entry already exists",
+                    TCPath::from(to),
+                ));
+            }
+
+            let entry = source
+                .remove(from_name)
+                .ok_or_else(|| TCError::not_found(TCPath::from(from)))?;
+
+            dest.insert(to_name.clone(), entry);
+        }
+
+        Ok(())
+    }
+
+    async fn resolve(&self, txn_id: &TxnId, path: &[PathSegment]) -> TCResult<Self> {
+        let mut dir = self.clone();
+        for name in path {
+            dir = fs::Dir::get_dir(&dir, txn_id, name)
+                .await?
+                .ok_or_else(|| TCError::not_found(name))?;
+        }
+
+        Ok(dir)
+    }
+
+    fn relocate<'a>(&'a self, txn_id: &'a TxnId, path: PathBuf) -> TCBoxTryFuture<'a, Self> {
+        Box::pin(async move {
+            let contents = self.contents.read(txn_id).await?;
+
+            let mut relocated = HashMap::with_capacity(contents.len());
+            for (name, entry) in contents.iter() {
+                let entry = entry.relocate(txn_id, entry.path_in(&path, name)).await?;
+                relocated.insert(name.clone(), entry);
+            }
+
+            let lock_name = format!("contents of {:?}", path);
+            Ok(Dir {
+                path,
+                cache: self.cache.clone(),
+                contents: TxnLock::new(lock_name, relocated),
+            })
+        })
+    }
+
+    /// Move any entries renamed in the given transaction to their new location on the host
+    /// filesystem, before they are committed.
+    async fn commit_moves(&self, txn_id: &TxnId) -> TCResult<()> {
+        let moved = {
+            let contents = self.contents.read(txn_id).await?;
+            contents
+                .iter()
+                .filter(|(name, entry)| entry.path() != &entry.path_in(&self.path, name))
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<PathSegment>>()
+        };
+
+        if moved.is_empty() {
+            return Ok(());
+        }
+
+        let mut contents = self.contents.write(*txn_id).await?;
+        for name in moved {
+            let entry = contents.remove(&name).expect("moved filesystem entry");
+            let old_path = entry.path().clone();
+            let new_path = entry.path_in(&self.path, &name);
+            debug!("move filesystem entry {:?} to {:?}", old_path, new_path);
+
+            let entry = entry.relocate(txn_id, new_path.clone()).await?;
+            if old_path.exists() {
+                self.cache.rename_dir(old_path, new_path).await?;
+            }
+
+            contents.insert(name, entry);
+        }
+
+        Ok(())
+    }
+
+    /// Return the total encoded size in bytes of every file in this directory and its
+    /// subdirectories, as of the given [`TxnId`].
+    pub fn size_bytes<'a>(&'a self, txn_id: &'a TxnId) -> TCBoxTryFuture<'a, u64> {
+        Box::pin(async move {
+            let contents = self.contents.read(txn_id).await?;
+            let sizes = try_join_all(contents.values().map(|entry| async move {
+                match entry {
+                    DirEntry::Dir(dir) => dir.size_bytes(txn_id).await,
+                    DirEntry::File(file) => file.size_bytes(*txn_id).await,
+                }
+            }))
+            .await?;
+
+            Ok(sizes.into_iter().sum())
+        })
+    }
+
     pub async fn unique_id(&self, txn_id: &TxnId) -> TCResult<PathSegment> {
         let existing_ids = self.entry_ids(&txn_id).await?;
         loop {
@@ -409,6 +628,10 @@ impl Transact for Dir {
             }
         }
 
+        self.commit_moves(txn_id)
+            .await
+            .expect("move renamed filesystem entries");
+
         {
             let contents = self.contents.read(&txn_id).await.unwrap();
 
@@ -465,3 +688,204 @@ fn is_empty(contents: &DirContents) -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::stream::{self, TryStreamExt};
+    use tc_btree::{BTreeFile, BTreeInstance, BTreeType, Key, Range, RowSchema};
+    use tc_transact::fs::Dir as _;
+    use tcgeneric::NetworkTime;
+
+    use crate::scalar::{NumberType, ValueType};
+    use crate::txn::Txn;
+
+    use super::*;
+
+    type BTree = BTreeFile<File<Node>, Dir, Txn>;
+
+    #[tokio::test]
+    async fn test_rename_dir() {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let root = Dir::load(Cache::new(1_000_000), path, vec![])
+            .await
+            .unwrap();
+        let txn_id = TxnId::new(NetworkTime::now());
+
+        let old_name: PathSegment = "old".parse().unwrap();
+        let new_name: PathSegment = "new".parse().unwrap();
+        let subdir = root.create_dir(txn_id, old_name.clone()).await.unwrap();
+        subdir
+            .create_dir(txn_id, "child".parse().unwrap())
+            .await
+            .unwrap();
+
+        root.rename(
+            txn_id,
+            std::slice::from_ref(&old_name),
+            std::slice::from_ref(&new_name),
+        )
+        .await
+        .unwrap();
+
+        assert!(root.get_dir(&txn_id, &old_name).await.unwrap().is_none());
+
+        let renamed = root.get_dir(&txn_id, &new_name).await.unwrap().unwrap();
+        assert!(renamed
+            .contains(&txn_id, &"child".parse().unwrap())
+            .await
+            .unwrap());
+
+        assert!(root
+            .rename(
+                txn_id,
+                std::slice::from_ref(&old_name),
+                std::slice::from_ref(&new_name)
+            )
+            .await
+            .is_err());
+
+        assert!(root
+            .rename(
+                txn_id,
+                std::slice::from_ref(&new_name),
+                &[new_name.clone(), old_name]
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_btree_bulk_load() {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let root = Dir::load(Cache::new(1_000_000), path, vec![])
+            .await
+            .unwrap();
+        let txn_id = TxnId::new(NetworkTime::now());
+
+        let schema: RowSchema = vec![
+            ("group".parse::<Id>().unwrap(), NumberType::uint64()).into(),
+            ("member".parse::<Id>().unwrap(), NumberType::uint64()).into(),
+        ];
+
+        let keys: Vec<Key> = (0..1_000u64)
+            .map(|i| vec![Value::from(i / 10), Value::from(i % 10)])
+            .collect();
+
+        let file = root
+            .create_file_tmp(txn_id, BTreeType::default())
+            .await
+            .unwrap();
+        let incremental = BTree::create(file, schema.clone(), txn_id).await.unwrap();
+        for key in keys.iter().rev() {
+            incremental.insert(txn_id, key.to_vec()).await.unwrap();
+        }
+
+        let file = root
+            .create_file_tmp(txn_id, BTreeType::default())
+            .await
+            .unwrap();
+        let sorted = stream::iter(keys.clone().into_iter().map(TCResult::Ok));
+        let bulk = BTree::bulk_load(file, schema.clone(), sorted, txn_id)
+            .await
+            .unwrap();
+
+        let ranges = vec![
+            Range::default(),
+            Range::with_prefix(vec![Value::from(42u64)]),
+        ];
+
+        for range in ranges {
+            for reverse in [false, true] {
+                let expected: Vec<Key> = incremental
+                    .clone()
+                    .slice(range.clone(), reverse)
+                    .unwrap()
+                    .keys(txn_id)
+                    .await
+                    .unwrap()
+                    .try_collect()
+                    .await
+                    .unwrap();
+
+                let actual: Vec<Key> = bulk
+                    .clone()
+                    .slice(range.clone(), reverse)
+                    .unwrap()
+                    .keys(txn_id)
+                    .await
+                    .unwrap()
+                    .try_collect()
+                    .await
+                    .unwrap();
+
+                assert!(!actual.is_empty());
+                assert_eq!(actual, expected);
+            }
+        }
+
+        // a bulk-loaded BTree still accepts new keys
+        bulk.insert(txn_id, vec![Value::from(1_000u64), Value::from(0u64)])
+            .await
+            .unwrap();
+        assert!(!bulk.is_empty(txn_id).await.unwrap());
+
+        let file = root
+            .create_file_tmp(txn_id, BTreeType::default())
+            .await
+            .unwrap();
+        let unsorted = stream::iter(keys.into_iter().rev().map(TCResult::Ok));
+        assert!(BTree::bulk_load(file, schema, unsorted, txn_id)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_size_bytes() {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let root = Dir::load(Cache::new(1_000_000), path, vec![])
+            .await
+            .unwrap();
+        let txn_id = TxnId::new(NetworkTime::now());
+
+        assert_eq!(root.size_bytes(&txn_id).await.unwrap(), 0);
+
+        let subdir = root
+            .create_dir(txn_id, "subdir".parse().unwrap())
+            .await
+            .unwrap();
+
+        let values = [
+            Value::from(1u64),
+            Value::String("hello, world".to_string()),
+            Value::from(vec![Value::from(2u64), Value::from(true)]),
+        ];
+
+        let mut expected = 0;
+        for (i, dir) in [&root, &subdir].iter().enumerate() {
+            let file: File<Value> = dir
+                .create_file(
+                    txn_id,
+                    format!("store{}", i).parse().unwrap(),
+                    ValueType::Value,
+                )
+                .await
+                .unwrap();
+
+            for (j, value) in values.iter().enumerate() {
+                let block_id = format!("block{}", j).parse().unwrap();
+                fs::File::create_block(&file, txn_id, block_id, value.clone())
+                    .await
+                    .unwrap();
+
+                expected += value.clone().into_size().await.unwrap();
+            }
+        }
+
+        assert!(expected > 0);
+        assert_eq!(
+            subdir.size_bytes(&txn_id).await.unwrap() * 2,
+            root.size_bytes(&txn_id).await.unwrap()
+        );
+        assert_eq!(root.size_bytes(&txn_id).await.unwrap(), expected);
+    }
+}
@@ -1,7 +1,7 @@
 //! A transactional file
 
 use std::collections::hash_map::{Entry, HashMap};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt;
 use std::iter::FromIterator;
@@ -21,7 +21,7 @@ use uuid::Uuid;
 use tc_error::*;
 use tc_transact::fs::{self, BlockData, BlockId, Store};
 use tc_transact::lock::TxnLock;
-use tc_transact::{Transact, TxnId};
+use tc_transact::{Rollback, Transact, TxnId};
 use tcgeneric::TCBoxTryFuture;
 
 use super::cache::*;
@@ -116,6 +116,12 @@ where
     }
 }
 
+// the contents of a `File` as of a savepoint, with a copy of each block changed since
+struct Saved<B> {
+    contents: HashSet<BlockId>,
+    blocks: HashMap<BlockId, B>,
+}
+
 /// A transactional file
 #[derive(Clone)]
 pub struct File<B> {
@@ -123,6 +129,7 @@ pub struct File<B> {
     cache: Cache,
     contents: TxnLock<HashSet<BlockId>>,
     mutated: RwLock<HashMap<TxnId, HashSet<BlockId>>>,
+    saved: RwLock<HashMap<TxnId, BTreeMap<usize, Saved<B>>>>,
     phantom: PhantomData<B>,
 }
 
@@ -135,6 +142,7 @@ impl<B: BlockData> File<B> {
             cache,
             contents: TxnLock::new(lock_name, block_ids),
             mutated: RwLock::new(HashMap::new()),
+            saved: RwLock::new(HashMap::new()),
             phantom: PhantomData,
         }
     }
@@ -152,6 +160,43 @@ impl<B: BlockData> File<B> {
         Self::_new(cache, path, HashSet::new())
     }
 
+    /// The path of this `File` on the filesystem.
+    pub(crate) fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Return a handle to this `File` at a new `path`, as of the given [`TxnId`].
+    ///
+    /// Any blocks cached under the current path are synchronized and evicted first,
+    /// so the caller can then move the file on the host filesystem.
+    pub(super) async fn relocate(&self, txn_id: &TxnId, path: PathBuf) -> TCResult<Self> {
+        let contents = self.contents.read(txn_id).await?;
+        let mutated = self.mutated.read().await;
+
+        for block_id in contents.iter() {
+            self.cache.delete(&fs_path(&self.path, block_id)).await;
+        }
+
+        if let Some(blocks) = mutated.get(txn_id) {
+            for block_id in blocks {
+                let version_path = block_version(&self.path, txn_id, block_id);
+                self.cache.sync(&version_path).await?;
+                self.cache.delete(&version_path).await;
+            }
+        }
+
+        let lock_name = format!("file contents at {:?}", &path);
+
+        Ok(File {
+            path,
+            cache: self.cache.clone(),
+            contents: TxnLock::new(lock_name, HashSet::clone(&contents)),
+            mutated: RwLock::new(HashMap::clone(&mutated)),
+            saved: RwLock::new(HashMap::new()),
+            phantom: PhantomData,
+        })
+    }
+
     pub fn load(cache: Cache, path: PathBuf, contents: DirContents) -> TCResult<Self> {
         if contents.iter().all(|(_, meta)| meta.is_file()) {
             let contents = contents
@@ -184,6 +229,62 @@ impl<B: BlockData> File<B> {
 
         fs::File::write_block(self, txn_id, name).await
     }
+
+    // copy the given block, as of the given transaction, into each savepoint which needs it
+    async fn save<'en>(&self, txn_id: TxnId, name: &BlockId) -> TCResult<()>
+    where
+        B: en::IntoStream<'en> + 'en,
+        CacheBlock: From<CacheLock<B>>,
+        CacheLock<B>: TryFrom<CacheBlock, Error = TCError>,
+    {
+        let mut saved = self.saved.write().await;
+        let pending = if let Some(savepoints) = saved.get_mut(&txn_id) {
+            savepoints
+                .values_mut()
+                .filter(|saved| saved.contents.contains(name))
+                .filter(|saved| !saved.blocks.contains_key(name))
+                .collect::<Vec<&mut Saved<B>>>()
+        } else {
+            return Ok(());
+        };
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let version = block_version(&self.path, &txn_id, name);
+        let lock = if let Some(lock) = self.cache.read(&version).await? {
+            lock
+        } else {
+            let canon = fs_path(&self.path, name);
+            let lock = self.cache.read(&canon).await?;
+            lock.ok_or_else(|| TCError::internal("failed reading block"))?
+        };
+
+        let block: B = lock.read().await.deref().clone();
+        for saved in pending {
+            saved.blocks.insert(name.clone(), block.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Return the total encoded size in bytes of the blocks in this file at the given [`TxnId`].
+    pub async fn size_bytes<'en>(&self, txn_id: TxnId) -> TCResult<u64>
+    where
+        B: en::IntoStream<'en> + 'en,
+        CacheBlock: From<CacheLock<B>>,
+        CacheLock<B>: TryFrom<CacheBlock, Error = TCError>,
+    {
+        let block_ids = fs::File::block_ids(self, &txn_id).await?;
+        let sizes = try_join_all(block_ids.into_iter().map(|block_id| async move {
+            let block = fs::File::read_block(self, txn_id, block_id).await?;
+            block.deref().clone().into_size().await
+        }))
+        .await?;
+
+        Ok(sizes.into_iter().sum())
+    }
 }
 
 #[async_trait]
@@ -232,17 +333,23 @@ where
         let (new_block_ids, mut contents) =
             try_join!(other.contents.read(&txn_id), self.contents.write(txn_id))?;
 
+        for block_id in new_block_ids.iter() {
+            self.save(txn_id, block_id).await?;
+        }
+
         let mut copied_block_ids = HashSet::with_capacity(new_block_ids.len());
 
-        let mut block_copies =
-            FuturesUnordered::from_iter(new_block_ids.iter().cloned().map(|block_id| {
-                let path = block_version(&self.path, &txn_id, &block_id);
+        let mut block_copies = new_block_ids
+            .iter()
+            .map(|block_id| {
+                let path = block_version(&self.path, &txn_id, block_id);
 
                 other
                     .read_block(txn_id, block_id.clone())
                     .and_then(|source| self.cache.write(path, source.clone()))
-                    .map_ok(|_lock| block_id)
-            }));
+                    .map_ok(move |_lock| block_id.clone())
+            })
+            .collect::<FuturesUnordered<_>>();
 
         while let Some(block_id) = block_copies.try_next().await? {
             contents.insert(block_id.clone());
@@ -278,6 +385,8 @@ where
         contents.insert(name.clone());
 
         self.mutate(txn_id, name.clone()).await;
+        self.cache.record_write();
+
         self.cache
             .write(version, initial_value)
             .map_ok(|lock| Block { name, txn_id, lock })
@@ -290,6 +399,8 @@ where
             return Err(TCError::not_found(name));
         }
 
+        self.save(txn_id, &name).await?;
+
         let version = block_version(&self.path, &txn_id, &name);
         self.mutate(txn_id, name).await;
         self.cache.delete(&version).await;
@@ -339,12 +450,19 @@ where
     async fn write_block(&self, txn_id: TxnId, name: BlockId) -> TCResult<BlockWrite<B>> {
         debug!("File::write_block");
         let block = self.get_block(txn_id, name.clone()).await?;
+        self.save(txn_id, &name).await?;
         self.mutate(txn_id, name).await;
+        self.cache.record_write();
+
         Ok(fs::Block::write(block).await)
     }
 
     async fn truncate(&self, txn_id: TxnId) -> TCResult<()> {
         let mut contents = self.contents.write(txn_id).await?;
+        for block_id in contents.iter() {
+            self.save(txn_id, block_id).await?;
+        }
+
         let deletes = FuturesUnordered::from_iter(contents.drain().map(|block_id| async move {
             let path = block_version(&self.path, &txn_id, &block_id);
             self.cache.delete(&path).await;
@@ -413,6 +531,8 @@ where
             }
         }
 
+        self.saved.write().await.remove(txn_id);
+
         let version = file_version(&self.path, txn_id);
         if version.exists() {
             cache
@@ -426,6 +546,62 @@ where
     }
 }
 
+#[async_trait]
+impl<'en, B: BlockData + 'en> Rollback for File<B>
+where
+    B: en::IntoStream<'en>,
+    CacheBlock: From<CacheLock<B>>,
+    CacheLock<B>: TryFrom<CacheBlock, Error = TCError>,
+{
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        let contents = self.contents.read(txn_id).await?;
+        let mut saved = self.saved.write().await;
+        let savepoints = saved.entry(*txn_id).or_insert_with(BTreeMap::new);
+        savepoints.entry(ordinal).or_insert_with(|| Saved {
+            contents: HashSet::clone(&contents),
+            blocks: HashMap::new(),
+        });
+
+        Ok(())
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        let saved = {
+            let mut saved = self.saved.write().await;
+            saved
+                .get_mut(txn_id)
+                .and_then(|savepoints| savepoints.split_off(&ordinal).remove(&ordinal))
+        };
+
+        let saved = if let Some(saved) = saved {
+            saved
+        } else {
+            return Ok(());
+        };
+
+        debug!("roll back file {:?} at {}", &self.path, txn_id);
+
+        let mut contents = self.contents.write(*txn_id).await?;
+        for block_id in contents.difference(&saved.contents) {
+            self.cache
+                .delete(&block_version(&self.path, txn_id, block_id))
+                .await;
+        }
+
+        for (block_id, block) in saved.blocks {
+            let version = block_version(&self.path, txn_id, &block_id);
+            if let Some(lock) = self.cache.read::<B>(&version).await? {
+                *lock.write().await = block;
+            } else {
+                self.cache.write(version, block).await?;
+            }
+        }
+
+        *contents = saved.contents;
+        Ok(())
+    }
+}
+
 impl<B> fmt::Display for File<B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "file at {:?}", &self.path)
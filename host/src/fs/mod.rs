@@ -12,10 +12,12 @@ use tokio::fs;
 use tc_error::*;
 use tcgeneric::{label, Label, PathSegment};
 
+pub use archive::*;
 pub use cache::*;
 pub use dir::*;
 pub use file::*;
 
+mod archive;
 mod cache;
 mod dir;
 mod file;
@@ -101,7 +103,7 @@ fn fs_path(mount_point: &PathBuf, name: &PathSegment) -> PathBuf {
 }
 
 fn io_err<I: fmt::Debug + Send>(err: io::Error, info: I) -> TCError {
-    match err.kind() {
+    let tc_err = match err.kind() {
         io::ErrorKind::NotFound => {
             TCError::internal(format!("host filesystem has no such entry {:?}", info))
         }
@@ -110,5 +112,7 @@ fn io_err<I: fmt::Debug + Send>(err: io::Error, info: I) -> TCError {
             info
         )),
         other => TCError::internal(format!("host filesystem error: {:?}: {}", other, err)),
-    }
+    };
+
+    tc_err.with_source(err)
 }
@@ -0,0 +1,160 @@
+//! Aggregate request [`Metrics`] for a [`super::Gateway`].
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tc_error::ErrorType;
+
+use crate::fs;
+
+const METHODS: [&str; 5] = ["GET", "PUT", "POST", "PATCH", "DELETE"];
+
+/// The upper bounds of the request latency histogram buckets, in seconds.
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10.];
+
+const ERROR_TYPES: [(ErrorType, &str); 10] = [
+    (ErrorType::BadGateway, "bad_gateway"),
+    (ErrorType::BadRequest, "bad_request"),
+    (ErrorType::Conflict, "conflict"),
+    (ErrorType::Forbidden, "forbidden"),
+    (ErrorType::Internal, "internal"),
+    (ErrorType::MethodNotAllowed, "method_not_allowed"),
+    (ErrorType::NotFound, "not_found"),
+    (ErrorType::NotImplemented, "not_implemented"),
+    (ErrorType::Timeout, "timeout"),
+    (ErrorType::Unauthorized, "unauthorized"),
+];
+
+#[derive(Default)]
+struct MethodMetrics {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    latency_micros: AtomicU64,
+    buckets: [AtomicU64; BUCKETS.len()],
+}
+
+/// Request counts, latency histograms, and error counts, updated with atomic counters only.
+pub struct Metrics {
+    methods: [MethodMetrics; METHODS.len()],
+    errors: [AtomicU64; ERROR_TYPES.len()],
+    cache: fs::Cache,
+}
+
+impl Metrics {
+    /// Construct a new `Metrics` which also reports the block writes made through `cache`.
+    pub fn new(cache: fs::Cache) -> Self {
+        Self {
+            methods: Default::default(),
+            errors: Default::default(),
+            cache,
+        }
+    }
+
+    /// Record a request with the given HTTP `method` which took `elapsed` to handle,
+    /// and which failed with the given `error`, if any.
+    ///
+    /// Requests with an unrecognized method are only counted by their error type.
+    pub fn record(&self, method: &str, elapsed: Duration, error: Option<ErrorType>) {
+        if let Some(error) = error {
+            if let Some(i) = ERROR_TYPES.iter().position(|(et, _)| *et == error) {
+                self.errors[i].fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let metrics = match METHODS.iter().position(|m| *m == method) {
+            Some(i) => &self.methods[i],
+            None => return,
+        };
+
+        metrics.requests.fetch_add(1, Ordering::Relaxed);
+
+        if error.is_some() {
+            metrics.errors.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        metrics.latency_micros.fetch_add(micros, Ordering::Relaxed);
+
+        let seconds = elapsed.as_secs_f64();
+        if let Some(i) = BUCKETS.iter().position(|le| seconds <= *le) {
+            metrics.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Render these `Metrics` in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP tc_requests_total The number of requests handled, by method.\n");
+        out.push_str("# TYPE tc_requests_total counter\n");
+        for (method, metrics) in METHODS.iter().zip(&self.methods) {
+            let count = metrics.requests.load(Ordering::Relaxed);
+            writeln!(out, "tc_requests_total{{method=\"{}\"}} {}", method, count).unwrap();
+        }
+
+        out.push_str("# HELP tc_request_errors_total The number of failed requests, by method.\n");
+        out.push_str("# TYPE tc_request_errors_total counter\n");
+        for (method, metrics) in METHODS.iter().zip(&self.methods) {
+            let count = metrics.errors.load(Ordering::Relaxed);
+            writeln!(
+                out,
+                "tc_request_errors_total{{method=\"{}\"}} {}",
+                method, count
+            )
+            .unwrap();
+        }
+
+        out.push_str("# HELP tc_request_duration_seconds The time taken to handle a request.\n");
+        out.push_str("# TYPE tc_request_duration_seconds histogram\n");
+        for (method, metrics) in METHODS.iter().zip(&self.methods) {
+            let mut cumulative = 0;
+            for (le, bucket) in BUCKETS.iter().zip(&metrics.buckets) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                writeln!(
+                    out,
+                    "tc_request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    method, le, cumulative
+                )
+                .unwrap();
+            }
+
+            let count = metrics.requests.load(Ordering::Relaxed);
+            let sum = metrics.latency_micros.load(Ordering::Relaxed) as f64 / 1_000_000.;
+
+            writeln!(
+                out,
+                "tc_request_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                method, count
+            )
+            .unwrap();
+
+            writeln!(
+                out,
+                "tc_request_duration_seconds_sum{{method=\"{}\"}} {}",
+                method, sum
+            )
+            .unwrap();
+
+            writeln!(
+                out,
+                "tc_request_duration_seconds_count{{method=\"{}\"}} {}",
+                method, count
+            )
+            .unwrap();
+        }
+
+        out.push_str("# HELP tc_errors_total The number of failed requests, by error type.\n");
+        out.push_str("# TYPE tc_errors_total counter\n");
+        for ((_, name), count) in ERROR_TYPES.iter().zip(&self.errors) {
+            let count = count.load(Ordering::Relaxed);
+            writeln!(out, "tc_errors_total{{type=\"{}\"}} {}", name, count).unwrap();
+        }
+
+        out.push_str("# HELP tc_block_writes_total The number of blocks created or written.\n");
+        out.push_str("# TYPE tc_block_writes_total counter\n");
+        writeln!(out, "tc_block_writes_total {}", self.cache.block_writes()).unwrap();
+
+        out
+    }
+}
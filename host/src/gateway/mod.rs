@@ -0,0 +1,525 @@
+//! [`Gateway`] handles network traffic.
+
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::{join_all, Future, TryFutureExt};
+use futures::try_join;
+use log::debug;
+use tokio_rustls::rustls::ServerConfig;
+
+use tc_error::*;
+use tc_transact::{Transact, Transaction};
+use tcgeneric::{
+    label, path_label, Label, Map, NetworkTime, PathLabel, PathSegment, TCBoxTryFuture, TCPath,
+    TCPathBuf,
+};
+
+use crate::http;
+use crate::kernel::Kernel;
+use crate::scalar::{Link, LinkHost, LinkProtocol, OpCache, Value};
+use crate::state::State;
+use crate::txn::*;
+
+pub use crate::http::load_tls_config;
+pub use metrics::Metrics;
+pub use queue::{Priority, WorkQueue};
+
+mod metrics;
+mod queue;
+
+const TXN_STATS: PathLabel = path_label(&["cluster", "txn_stats"]);
+
+const TXN: Label = label("txn");
+const COMMIT: Label = label("commit");
+const ABORT: Label = label("abort");
+
+/// Configuration for [`Gateway`].
+#[derive(Clone)]
+pub struct Config {
+    pub addr: IpAddr,
+    pub http_port: u16,
+    pub request_ttl: Duration,
+    pub max_op_steps: usize,
+    pub max_op_states: usize,
+    pub compaction_interval: Duration,
+    pub compaction_age: Duration,
+    pub op_cache_size: usize,
+    pub op_cache_ttl: Duration,
+    pub high_priority_concurrency: usize,
+    pub low_priority_concurrency: usize,
+    pub reduce_concurrency: usize,
+    pub tls: Option<Arc<ServerConfig>>,
+}
+
+/// A client used by [`Gateway`]
+#[async_trait]
+pub trait Client {
+    /// Read a simple value.
+    async fn fetch<T: destream::FromStream<Context = ()>>(
+        &self,
+        txn_id: &TxnId,
+        link: &Link,
+        key: &Value,
+    ) -> TCResult<T>;
+
+    /// Read a [`State`].
+    async fn get(&self, txn: Txn, link: Link, key: Value) -> TCResult<State>;
+
+    /// Set `key` = `value` within the state referred to by `link`.
+    async fn put(&self, txn: Txn, link: Link, key: Value, value: State) -> TCResult<()>;
+
+    /// Execute a remote POST op.
+    async fn post(&self, txn: Txn, link: Link, params: State) -> TCResult<State>;
+
+    /// Delete `key` from the state referred to by `link`, returning the response of the remote
+    /// host, if any.
+    async fn delete(&self, txn: &Txn, link: Link, key: Value) -> TCResult<Value>;
+
+    /// Partially update `key` with `value` within the state referred to by `link`.
+    async fn patch(&self, txn: Txn, link: Link, key: Value, value: State) -> TCResult<()>;
+}
+
+/// A server used by [`Gateway`].
+#[async_trait]
+pub trait Server {
+    type Error: std::error::Error;
+
+    /// Handle incoming requests.
+    async fn listen(self, addr: SocketAddr) -> Result<(), Self::Error>;
+}
+
+/// Responsible for handling inbound and outbound traffic over the network.
+pub struct Gateway {
+    config: Config,
+    kernel: Kernel,
+    txn_server: TxnServer,
+    root: LinkHost,
+    client: http::Client,
+    actor: Actor,
+    metrics: Metrics,
+    op_cache: OpCache,
+    queue: WorkQueue,
+}
+
+impl Gateway {
+    /// Return the current timestamp.
+    pub fn time() -> NetworkTime {
+        NetworkTime::now()
+    }
+
+    /// Initialize a new `Gateway`
+    pub fn new(config: Config, kernel: Kernel, txn_server: TxnServer) -> Arc<Self> {
+        let root = LinkHost::from((LinkProtocol::HTTP, config.addr, Some(config.http_port)));
+
+        let metrics = Metrics::new(txn_server.cache().clone());
+        let op_cache = OpCache::new(config.op_cache_size, config.op_cache_ttl);
+        let queue = WorkQueue::new(
+            config.high_priority_concurrency,
+            config.low_priority_concurrency,
+        );
+
+        Arc::new(Self {
+            config,
+            kernel,
+            txn_server,
+            root,
+            client: http::Client::new(),
+            actor: Actor::new(Link::default().into()),
+            metrics,
+            op_cache,
+            queue,
+        })
+    }
+
+    /// Return the configured maximum request time-to-live (timeout duration).
+    pub fn request_ttl(&self) -> Duration {
+        self.config.request_ttl
+    }
+
+    /// Return the maximum number of references an `OpDef` may resolve.
+    pub fn max_op_steps(&self) -> usize {
+        self.config.max_op_steps
+    }
+
+    /// Return the maximum number of intermediate states the `OpDef`s of a transaction may hold.
+    pub fn max_op_states(&self) -> usize {
+        self.config.max_op_states
+    }
+
+    /// Return the maximum number of blocks which a single reduction may process concurrently.
+    pub fn reduce_concurrency(&self) -> usize {
+        match self.config.reduce_concurrency {
+            0 => num_cpus::get(),
+            limit => limit,
+        }
+    }
+
+    /// Return the network address of this `Gateway`
+    pub fn root(&self) -> &LinkHost {
+        &self.root
+    }
+
+    /// Return the request [`Metrics`] of this `Gateway`.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Return the [`OpCache`] of this `Gateway`, which memoizes the results of pure ops.
+    pub fn op_cache(&self) -> &OpCache {
+        &self.op_cache
+    }
+
+    /// Return the [`TxnServer`] which tracks the active transactions of this `Gateway`.
+    pub fn txn_server(&self) -> &TxnServer {
+        &self.txn_server
+    }
+
+    /// Return the [`WorkQueue`] of this `Gateway`, which bounds concurrent requests by priority.
+    pub fn queue(&self) -> &WorkQueue {
+        &self.queue
+    }
+
+    /// Return a [`Link`] to the given path at this host.
+    pub fn link(&self, path: TCPathBuf) -> Link {
+        Link::from((self.root.clone(), path))
+    }
+
+    /// Return a new, signed auth token with no claims.
+    pub fn new_token(&self, txn_id: &TxnId) -> TCResult<(String, Claims)> {
+        let token = Token::new(
+            self.root.clone().into(),
+            txn_id.time().into(),
+            self.config.request_ttl,
+            self.actor.id().clone(),
+            vec![],
+        );
+
+        let signed = self.actor.sign_token(&token).map_err(TCError::internal)?;
+        let claims = token.claims();
+        Ok((signed, claims))
+    }
+
+    /// Authorize a transaction to execute on this host.
+    pub async fn new_txn(
+        self: &Arc<Self>,
+        txn_id: TxnId,
+        token: Option<String>,
+        request_id: String,
+    ) -> TCResult<Txn> {
+        let bearer = token.clone();
+        let token = if let Some(token) = token {
+            use rjwt::Resolve;
+            Resolver::new(self, &self.root().clone().into(), &txn_id)
+                .consume_and_sign(&self.actor, vec![], token, txn_id.time().into())
+                .map_err(TCError::unauthorized)
+                .await?
+        } else {
+            self.new_token(&txn_id)?
+        };
+
+        self.txn_server
+            .new_txn(self.clone(), txn_id, token, bearer, request_id)
+            .await
+    }
+
+    /// Read a simple value.
+    pub async fn fetch<T: destream::FromStream<Context = ()>>(
+        &self,
+        txn_id: &TxnId,
+        link: &Link,
+        key: &Value,
+    ) -> TCResult<T> {
+        self.client.fetch(txn_id, link, key).await
+    }
+
+    /// Read the [`State`] with the given `key` at `link`.
+    pub fn get<'a>(&'a self, txn: &'a Txn, link: Link, key: Value) -> TCBoxTryFuture<'a, State> {
+        Box::pin(async move {
+            debug!(
+                "GET {}: {} (request {})",
+                link,
+                key,
+                txn.request().request_id()
+            );
+            let is_local = match link.host() {
+                None => true,
+                Some(host) => host == self.root(),
+            };
+
+            if is_local && link.path() == &TCPathBuf::from(TXN_STATS) {
+                return if key.is_none() {
+                    Ok(State::from(self.txn_server.stats()))
+                } else {
+                    Err(TCError::not_found(key))
+                };
+            }
+
+            match link.host() {
+                None if link.path().is_empty() && key.is_none() => {
+                    let public_key = Bytes::from(self.actor.public_key().as_bytes().to_vec());
+                    Ok(State::from(Value::from(public_key)))
+                }
+                None => self.kernel.get(txn, link.path(), key).await,
+                Some(host) if host == self.root() => self.kernel.get(txn, link.path(), key).await,
+                _ => self.client.get(txn.clone(), link, key).await,
+            }
+        })
+    }
+
+    /// Update the [`State`] with the given `key` at `link` to `value`.
+    pub fn put<'a>(
+        &'a self,
+        txn: &'a Txn,
+        link: Link,
+        key: Value,
+        value: State,
+    ) -> TCBoxTryFuture<'a, ()> {
+        Box::pin(async move {
+            debug!(
+                "PUT {}: {} <- {} (request {})",
+                link,
+                key,
+                value,
+                txn.request().request_id()
+            );
+
+            check_writable(txn, "PUT", &link)?;
+
+            match link.host() {
+                None => self.kernel.put(txn, link.path(), key, value).await,
+                Some(host) if host == self.root() => {
+                    self.kernel.put(txn, link.path(), key, value).await
+                }
+                _ => self.client.put(txn.clone(), link, key, value).await,
+            }
+        })
+    }
+
+    /// Execute the POST op at `subject` with the `params`
+    pub async fn post(&self, txn: &Txn, link: Link, params: State) -> TCResult<State> {
+        debug!(
+            "POST to {} with params {} (request {})",
+            link,
+            params,
+            txn.request().request_id()
+        );
+
+        let is_local = match link.host() {
+            None => true,
+            Some(host) => host == self.root(),
+        };
+
+        if is_local {
+            if let Some((txn_id, commit)) = txn_control(link.path())? {
+                return self.finish(txn, txn_id, commit).await;
+            }
+        }
+
+        match link.host() {
+            None => self.kernel.post(txn, link.path(), params).await,
+            Some(host) if host == self.root() => self.kernel.post(txn, link.path(), params).await,
+            _ => self.client.post(txn.clone(), link, params).await,
+        }
+    }
+
+    /// Call the read-only POST method at `path` of a cluster hosted on this host.
+    pub(crate) async fn compute(
+        &self,
+        txn: &Txn,
+        path: &TCPathBuf,
+        params: Map<State>,
+    ) -> TCResult<State> {
+        debug!("compute {} with params {}", path, params);
+        self.kernel.compute(txn, path, params).await
+    }
+
+    /// Delete the [`State`] with the given `key` at `link`.
+    pub fn delete<'a>(&'a self, txn: &'a Txn, link: Link, key: Value) -> TCBoxTryFuture<'a, Value> {
+        Box::pin(async move {
+            debug!(
+                "DELETE {}: {} (request {})",
+                link,
+                key,
+                txn.request().request_id()
+            );
+
+            check_writable(txn, "DELETE", &link)?;
+            match link.host() {
+                None => self.kernel.delete(txn, link.path(), key).await,
+                Some(host) if host == self.root() => {
+                    self.kernel.delete(txn, link.path(), key).await
+                }
+                _ => self.client.delete(txn, link, key).await,
+            }
+        })
+    }
+
+    /// Partially update the [`State`] with the given `key` at `link` with `value`.
+    pub fn patch<'a>(
+        &'a self,
+        txn: &'a Txn,
+        link: Link,
+        key: Value,
+        value: State,
+    ) -> TCBoxTryFuture<'a, ()> {
+        Box::pin(async move {
+            debug!(
+                "PATCH {}: {} <- {} (request {})",
+                link,
+                key,
+                value,
+                txn.request().request_id()
+            );
+
+            check_writable(txn, "PATCH", &link)?;
+
+            match link.host() {
+                None => self.kernel.patch(txn, link.path(), key, value).await,
+                Some(host) if host == self.root() => {
+                    self.kernel.patch(txn, link.path(), key, value).await
+                }
+                _ => self.client.patch(txn.clone(), link, key, value).await,
+            }
+        })
+    }
+
+    /// Commit or abort the transaction with the given [`TxnId`], held open across requests.
+    async fn finish(&self, txn: &Txn, txn_id: TxnId, commit: bool) -> TCResult<State> {
+        debug!(
+            "{} held transaction {}",
+            if commit { "commit" } else { "abort" },
+            txn_id
+        );
+
+        let held = self.txn_server.release(txn, &txn_id).await?;
+
+        let now = Self::time();
+        let expired = held.iter().any(|(_, txn)| match txn.request().expires() {
+            Ok(expires) => expires < now,
+            Err(_) => true,
+        });
+
+        if commit && expired {
+            self.kernel.finish(held, false).await?;
+            Err(TCError::timeout(format!(
+                "transaction {} expired before it was committed",
+                txn_id
+            )))
+        } else {
+            self.kernel.finish(held, commit).await?;
+            Ok(State::default())
+        }
+    }
+
+    /// Roll back each cluster which claimed a held transaction which has expired.
+    pub(crate) async fn rollback_held(&self, held: Vec<(TCPathBuf, Txn)>) -> TCResult<()> {
+        self.kernel.finish(held, false).await
+    }
+
+    /// Finalize the given transaction in each hosted cluster, once it's no longer active.
+    pub(crate) async fn finalize(&self, txn_id: &TxnId) {
+        join_all(self.kernel.hosted().map(|cluster| cluster.finalize(txn_id))).await;
+    }
+
+    /// Start this `Gateway`'s server
+    pub fn listen(
+        self: Arc<Self>,
+    ) -> Pin<Box<impl Future<Output = Result<(), Box<dyn std::error::Error>>> + 'static>> {
+        Box::pin(async move {
+            self.txn_server.spawn_compaction(
+                self.clone(),
+                self.config.compaction_interval,
+                self.config.compaction_age,
+            );
+
+            match try_join!(self.clone().http_listen(), self.clone().replicate()) {
+                Ok(_) => Ok(()),
+                Err(cause) => Err(cause),
+            }
+        })
+    }
+
+    async fn replicate(self: Arc<Self>) -> Result<(), Box<dyn std::error::Error>> {
+        let result = async move {
+            for cluster in self.kernel.hosted() {
+                let gateway = self.clone();
+
+                if cluster.link().host().is_none() {
+                    continue;
+                }
+
+                log::info!("replicating {}", cluster);
+
+                let txn_id = TxnId::new(Self::time());
+                let txn = gateway.new_txn(txn_id, None, new_request_id()).await?;
+                let txn = cluster.claim(&txn).await?;
+
+                cluster.rejoin(&txn).await?;
+
+                // send a commit message
+                cluster.distribute_commit(&txn).await?;
+
+                log::info!("{} is now online", cluster);
+            }
+
+            TCResult::Ok(())
+        };
+
+        match result.await {
+            Ok(()) => Result::<(), Box<dyn std::error::Error>>::Ok(()),
+            Err(cause) => {
+                let e: Box<dyn std::error::Error> = Box::new(cause);
+                Err(e)
+            }
+        }
+    }
+
+    fn http_listen(
+        self: Arc<Self>,
+    ) -> std::pin::Pin<Box<impl futures::Future<Output = Result<(), Box<dyn std::error::Error>>>>>
+    {
+        let http_addr = (self.config.addr, self.config.http_port).into();
+        let tls = self.config.tls.clone();
+        let server = crate::http::HTTPServer::new(self, tls);
+        let listener = server.listen(http_addr).map_err(|e| {
+            let e: Box<dyn std::error::Error> = Box::new(e);
+            e
+        });
+
+        Box::pin(listener)
+    }
+}
+
+/// Parse the [`TxnId`] and action of a `/txn/{txn_id}/commit` or `/txn/{txn_id}/abort` path.
+fn txn_control(path: &[PathSegment]) -> TCResult<Option<(TxnId, bool)>> {
+    if path.len() != 3 || path[0] != TXN {
+        return Ok(None);
+    }
+
+    let txn_id = path[1].as_str().parse()?;
+
+    if path[2] == COMMIT {
+        Ok(Some((txn_id, true)))
+    } else if path[2] == ABORT {
+        Ok(Some((txn_id, false)))
+    } else {
+        Err(TCError::not_found(TCPath::from(path)))
+    }
+}
+
+fn check_writable(txn: &Txn, method: &str, link: &Link) -> TCResult<()> {
+    if txn.is_read_only() {
+        Err(TCError::method_not_allowed(
+            method,
+            format!("read-only transaction {}:", txn.id()),
+            link,
+        ))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+//! A two-tier [`WorkQueue`] which lets interactive requests take precedence over analytical ones.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use tokio::sync::{Notify, Semaphore, SemaphorePermit};
+
+use tc_error::*;
+use tc_transact::TxnId;
+
+/// The priority of a request, given by its `X-Priority` header.
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum Priority {
+    #[default]
+    High,
+    Low,
+}
+
+impl FromStr for Priority {
+    type Err = TCError;
+
+    fn from_str(priority: &str) -> TCResult<Self> {
+        match priority.trim().to_lowercase().as_str() {
+            "high" => Ok(Self::High),
+            "low" => Ok(Self::Low),
+            other => Err(TCError::bad_request("invalid request priority", other)),
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::High => f.write_str("high"),
+            Self::Low => f.write_str("low"),
+        }
+    }
+}
+
+/// Bounds the number of requests of each [`Priority`] handled concurrently.
+///
+/// A low-priority request does not start while any high-priority request is waiting for a slot.
+/// A request which belongs to a transaction that already holds a slot does not need one of its
+/// own, since its parent request may be waiting on it.
+pub struct WorkQueue {
+    high: Semaphore,
+    low: Semaphore,
+    high_waiting: AtomicUsize,
+    idle: Notify,
+    admitted: Mutex<HashMap<TxnId, usize>>,
+}
+
+/// A slot in a [`WorkQueue`], which is released when dropped.
+pub struct Admission<'a> {
+    queue: &'a WorkQueue,
+    txn_id: TxnId,
+    _permit: Option<SemaphorePermit<'a>>,
+}
+
+impl<'a> Drop for Admission<'a> {
+    fn drop(&mut self) {
+        let mut admitted = self.queue.admitted.lock().expect("admitted transactions");
+        if let Some(count) = admitted.get_mut(&self.txn_id) {
+            *count -= 1;
+            if *count == 0 {
+                admitted.remove(&self.txn_id);
+            }
+        }
+    }
+}
+
+impl WorkQueue {
+    /// Construct a new `WorkQueue` which handles at most `high` high-priority requests
+    /// and at most `low` low-priority requests at once.
+    pub fn new(high: usize, low: usize) -> Self {
+        Self {
+            high: Semaphore::new(high.max(1)),
+            low: Semaphore::new(low.max(1)),
+            high_waiting: AtomicUsize::new(0),
+            idle: Notify::new(),
+            admitted: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a slot to handle a request of the given transaction with the given [`Priority`].
+    ///
+    /// The request should be handled while the returned [`Admission`] is held. A request of a
+    /// transaction which has already been admitted is admitted immediately.
+    pub async fn acquire(&self, txn_id: TxnId, priority: Priority) -> TCResult<Admission<'_>> {
+        if self.admit(txn_id, false) {
+            return Ok(Admission {
+                queue: self,
+                txn_id,
+                _permit: None,
+            });
+        }
+
+        let permit = self.acquire_permit(priority).await?;
+        self.admit(txn_id, true);
+
+        Ok(Admission {
+            queue: self,
+            txn_id,
+            _permit: Some(permit),
+        })
+    }
+
+    fn admit(&self, txn_id: TxnId, force: bool) -> bool {
+        let mut admitted = self.admitted.lock().expect("admitted transactions");
+        if let Some(count) = admitted.get_mut(&txn_id) {
+            *count += 1;
+            true
+        } else if force {
+            admitted.insert(txn_id, 1);
+            true
+        } else {
+            false
+        }
+    }
+
+    async fn acquire_permit(&self, priority: Priority) -> TCResult<SemaphorePermit<'_>> {
+        match priority {
+            Priority::High => {
+                self.high_waiting.fetch_add(1, Ordering::SeqCst);
+                let permit = self.high.acquire().await;
+                if self.high_waiting.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    self.idle.notify_waiters();
+                }
+
+                permit.map_err(TCError::internal)
+            }
+            Priority::Low => {
+                loop {
+                    let idle = self.idle.notified();
+                    if self.high_waiting.load(Ordering::SeqCst) == 0 {
+                        break;
+                    }
+
+                    idle.await;
+                }
+
+                self.low.acquire().await.map_err(TCError::internal)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use crate::gateway::Gateway;
+
+    use super::*;
+
+    fn txn_id() -> TxnId {
+        TxnId::new(Gateway::time())
+    }
+
+    #[tokio::test]
+    async fn test_priority() {
+        let queue = Arc::new(WorkQueue::new(1, 1));
+
+        // saturate the low-priority tier
+        let low = queue.acquire(txn_id(), Priority::Low).await.unwrap();
+
+        // a high-priority request completes promptly anyway
+        let high = timeout(
+            Duration::from_millis(50),
+            queue.acquire(txn_id(), Priority::High),
+        )
+        .await
+        .expect("high-priority request");
+
+        // another low-priority request is throttled
+        let throttled = timeout(
+            Duration::from_millis(50),
+            queue.acquire(txn_id(), Priority::Low),
+        )
+        .await;
+        assert!(throttled.is_err());
+
+        drop(low);
+
+        // a low-priority request yields to a waiting high-priority request
+        let waiting = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                let _permit = queue.acquire(txn_id(), Priority::High).await.unwrap();
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let yielded = timeout(
+            Duration::from_millis(50),
+            queue.acquire(txn_id(), Priority::Low),
+        )
+        .await;
+        assert!(yielded.is_err());
+
+        drop(high);
+        waiting.await.unwrap();
+
+        timeout(
+            Duration::from_millis(50),
+            queue.acquire(txn_id(), Priority::Low),
+        )
+        .await
+        .expect("low-priority request")
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nested() {
+        let queue = WorkQueue::new(1, 1);
+        let parent = txn_id();
+
+        let outer = queue.acquire(parent, Priority::High).await.unwrap();
+
+        // a nested request of an admitted transaction does not wait on its parent's slot
+        let inner = timeout(
+            Duration::from_millis(50),
+            queue.acquire(parent, Priority::High),
+        )
+        .await
+        .expect("nested request")
+        .unwrap();
+
+        // but a request of another transaction still has to wait
+        let other = timeout(
+            Duration::from_millis(50),
+            queue.acquire(txn_id(), Priority::High),
+        );
+        assert!(other.await.is_err());
+
+        drop(outer);
+
+        // the transaction is still admitted while its nested request is in progress
+        let other = timeout(
+            Duration::from_millis(50),
+            queue.acquire(txn_id(), Priority::High),
+        );
+        assert!(other.await.is_ok());
+
+        drop(inner);
+    }
+}
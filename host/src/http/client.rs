@@ -14,7 +14,7 @@ use tcgeneric::label;
 
 use crate::http::Encoding;
 use crate::state::State;
-use crate::txn::Txn;
+use crate::txn::{new_request_id, Txn};
 
 const IDLE_TIMEOUT: u64 = 30;
 const ERR_NO_OWNER: &str = "an ownerless transaction may not make outgoing requests";
@@ -34,6 +34,49 @@ impl Client {
 
         Self { client }
     }
+
+    async fn write(
+        &self,
+        method: &str,
+        txn: Txn,
+        link: Link,
+        key: Value,
+        value: State,
+    ) -> TCResult<()> {
+        if txn.owner().is_none() {
+            return Err(TCError::unsupported(ERR_NO_OWNER));
+        }
+
+        let uri = replicated(url(&link, txn.id(), &key)?, &txn);
+        let req = req_builder(
+            method,
+            uri,
+            Some(txn.request().token()),
+            txn.request().request_id(),
+        )
+        .header(hyper::header::CONTENT_TYPE, Encoding::Tbon.to_string());
+
+        let txn = txn.subcontext_tmp().await?;
+        let view = value.into_view(txn).await?;
+        let body = tbon::en::encode(view)
+            .map_err(|e| TCError::bad_request("unable to encode stream", e))?;
+
+        let response = self
+            .client
+            .request(
+                req.body(Body::wrap_stream(body.map_err(TCError::internal)))
+                    .unwrap(),
+            )
+            .map_err(|e| TCError::bad_gateway(&e).with_source(e))
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let err = transform_error(&link, response).await;
+            Err(err)
+        }
+    }
 }
 
 #[async_trait]
@@ -46,18 +89,18 @@ impl crate::gateway::Client for Client {
     ) -> TCResult<T> {
         let uri = url(link, txn_id, key)?;
         debug!("FETCH {}", uri);
-        let req = req_builder("GET", uri, None);
+        let req = req_builder("GET", uri, None, &new_request_id());
 
         let response = self
             .client
             .request(req.body(Body::empty()).unwrap())
-            .map_err(|e| TCError::bad_gateway(e))
+            .map_err(|e| TCError::bad_gateway(&e).with_source(e))
             .await?;
 
         if response.status().is_success() {
             let body = response.into_body();
             tbon::de::try_decode((), body)
-                .map_err(|e| TCError::bad_gateway(e))
+                .map_err(|e| TCError::bad_gateway(&e).with_source(e))
                 .await
         } else {
             let err = transform_error(link, response).await;
@@ -66,18 +109,24 @@ impl crate::gateway::Client for Client {
     }
 
     async fn get(&self, txn: Txn, link: Link, key: Value) -> TCResult<State> {
-        if txn.owner().is_none() {
+        // a read forwarded to another replica has nothing to commit, so it needs no owner
+        if txn.owner().is_none() && !txn.is_balanced() {
             return Err(TCError::unsupported(ERR_NO_OWNER));
         }
 
-        let uri = url(&link, txn.id(), &key)?;
-        let req = req_builder("GET", uri, Some(txn.request().token()));
+        let uri = read_only(url(&link, txn.id(), &key)?, &txn);
+        let req = req_builder(
+            "GET",
+            uri,
+            Some(txn.request().token()),
+            txn.request().request_id(),
+        );
 
         let txn = txn.subcontext_tmp().await?;
         let response = self
             .client
             .request(req.body(Body::empty()).unwrap())
-            .map_err(|e| TCError::bad_gateway(e))
+            .map_err(|e| TCError::bad_gateway(&e).with_source(e))
             .await?;
 
         if response.status().is_success() {
@@ -96,34 +145,11 @@ impl crate::gateway::Client for Client {
     }
 
     async fn put(&self, txn: Txn, link: Link, key: Value, value: State) -> TCResult<()> {
-        if txn.owner().is_none() {
-            return Err(TCError::unsupported(ERR_NO_OWNER));
-        }
-
-        let uri = url(&link, txn.id(), &key)?;
-        let req = req_builder("PUT", uri, Some(txn.request().token()))
-            .header(hyper::header::CONTENT_TYPE, Encoding::Tbon.to_string());
-
-        let txn = txn.subcontext_tmp().await?;
-        let view = value.into_view(txn).await?;
-        let body = tbon::en::encode(view)
-            .map_err(|e| TCError::bad_request("unable to encode stream", e))?;
-
-        let response = self
-            .client
-            .request(
-                req.body(Body::wrap_stream(body.map_err(TCError::internal)))
-                    .unwrap(),
-            )
-            .map_err(|e| TCError::bad_gateway(e))
-            .await?;
+        self.write("PUT", txn, link, key, value).await
+    }
 
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            let err = transform_error(&link, response).await;
-            Err(err)
-        }
+    async fn patch(&self, txn: Txn, link: Link, key: Value, value: State) -> TCResult<()> {
+        self.write("PATCH", txn, link, key, value).await
     }
 
     async fn post(&self, txn: Txn, link: Link, params: State) -> TCResult<State> {
@@ -131,9 +157,14 @@ impl crate::gateway::Client for Client {
             return Err(TCError::unsupported(ERR_NO_OWNER));
         }
 
-        let uri = url(&link, txn.id(), &Value::default())?;
-        let req = req_builder("POST", uri, Some(txn.request().token()))
-            .header(hyper::header::CONTENT_TYPE, Encoding::Tbon.to_string());
+        let uri = read_only(url(&link, txn.id(), &Value::default())?, &txn);
+        let req = req_builder(
+            "POST",
+            uri,
+            Some(txn.request().token()),
+            txn.request().request_id(),
+        )
+        .header(hyper::header::CONTENT_TYPE, Encoding::Tbon.to_string());
 
         let txn = txn.subcontext_tmp().await?;
         let subcontext = txn.subcontext(label("_params").into()).await?;
@@ -147,7 +178,7 @@ impl crate::gateway::Client for Client {
                 req.body(Body::wrap_stream(body.map_err(TCError::internal)))
                     .unwrap(),
             )
-            .map_err(|e| TCError::bad_gateway(e))
+            .map_err(|e| TCError::bad_gateway(&e).with_source(e))
             .await?;
 
         if response.status().is_success() {
@@ -165,22 +196,30 @@ impl crate::gateway::Client for Client {
         }
     }
 
-    async fn delete(&self, txn: &Txn, link: Link, key: Value) -> TCResult<()> {
+    async fn delete(&self, txn: &Txn, link: Link, key: Value) -> TCResult<Value> {
         if txn.owner().is_none() {
             return Err(TCError::unsupported(ERR_NO_OWNER));
         }
 
-        let uri = url(&link, txn.id(), &key)?;
-        let req = req_builder("DELETE", uri, Some(txn.request().token()));
+        let uri = replicated(url(&link, txn.id(), &key)?, txn);
+        let req = req_builder(
+            "DELETE",
+            uri,
+            Some(txn.request().token()),
+            txn.request().request_id(),
+        );
 
         let response = self
             .client
             .request(req.body(Body::empty()).unwrap())
-            .map_err(|e| TCError::bad_gateway(e))
+            .map_err(|e| TCError::bad_gateway(&e).with_source(e))
             .await?;
 
         if response.status().is_success() {
-            Ok(())
+            let body = response.into_body();
+            tbon::de::try_decode((), body)
+                .map_err(TCError::bad_gateway)
+                .await
         } else {
             let err = transform_error(&link, response).await;
             Err(err)
@@ -188,6 +227,31 @@ impl crate::gateway::Client for Client {
     }
 }
 
+// propagate the read-only flag of the given `txn`, if set, so the remote host also rejects writes
+fn read_only(mut url: Url, txn: &Txn) -> Url {
+    if txn.is_read_only() {
+        url.query_pairs_mut().append_pair("read_only", "true");
+    }
+
+    if let Some(since) = txn.balanced_since() {
+        url.query_pairs_mut()
+            .append_pair("min_commit", &since.to_string());
+    }
+
+    url
+}
+
+// propagate the replica set epoch of the given `txn`, if set, so the replica can reject a write
+// replicated from a stale replica set
+fn replicated(mut url: Url, txn: &Txn) -> Url {
+    if let Some(epoch) = txn.epoch() {
+        url.query_pairs_mut()
+            .append_pair("epoch", &epoch.to_string());
+    }
+
+    url
+}
+
 fn url(link: &Link, txn_id: &TxnId, key: &Value) -> TCResult<Url> {
     let mut url =
         Url::parse(&link.to_string()).map_err(|e| TCError::bad_request("invalid URL", e))?;
@@ -205,10 +269,18 @@ fn url(link: &Link, txn_id: &TxnId, key: &Value) -> TCResult<Url> {
     Ok(url)
 }
 
-fn req_builder(method: &str, url: Url, auth: Option<&str>) -> http::request::Builder {
+pub(super) fn req_builder(
+    method: &str,
+    url: Url,
+    auth: Option<&str>,
+    request_id: &str,
+) -> http::request::Builder {
+    debug!("request {}: {} {}", request_id, method, url);
+
     let req = hyper::Request::builder()
         .method(method)
         .header(hyper::header::ACCEPT_ENCODING, Encoding::Tbon.to_string())
+        .header(super::REQUEST_ID, request_id)
         .uri(url.to_string());
 
     if let Some(token) = auth {
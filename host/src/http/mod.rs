@@ -1,18 +1,79 @@
 //! The HTTP interface for `Gateway`.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
 
 use hyper::header::HeaderValue;
 
 use tc_error::*;
+use tcgeneric::{Id, Map};
+
+use crate::gateway::Priority;
+use crate::scalar::Value;
 
 mod client;
+mod pointer;
 mod server;
 
 pub use client::*;
 pub use server::*;
 
+/// The header used to correlate a request with the requests it makes to other hosts.
+const REQUEST_ID: &str = "x-request-id";
+
+/// Return the request ID given in the `X-Request-Id` header, or a new one if there is none.
+fn request_id(header: Option<&HeaderValue>) -> TCResult<String> {
+    if let Some(header) = header {
+        let request_id = header
+            .to_str()
+            .map_err(|e| TCError::bad_request("invalid X-Request-Id header", e))?
+            .trim();
+
+        if !request_id.is_empty() {
+            return Ok(request_id.to_string());
+        }
+    }
+
+    Ok(crate::txn::new_request_id())
+}
+
+/// The header used to attach application-defined metadata to a transaction, as a JSON object.
+const METADATA: &str = "x-tc-metadata";
+
+/// Parse the transaction metadata given in the `X-TC-Metadata` header, if any.
+fn metadata(header: Option<&HeaderValue>) -> TCResult<Map<Value>> {
+    let header = match header {
+        Some(header) => header
+            .to_str()
+            .map_err(|e| TCError::bad_request("invalid X-TC-Metadata header", e))?,
+        None => return Ok(Map::default()),
+    };
+
+    let metadata: HashMap<String, Value> = serde_json::from_str(header)
+        .map_err(|e| TCError::bad_request("invalid X-TC-Metadata header", &e).with_source(e))?;
+
+    metadata
+        .into_iter()
+        .map(|(name, value)| name.parse().map(|name: Id| (name, value)))
+        .collect()
+}
+
+/// The header used to set the priority of a request, either "high" (the default) or "low".
+const PRIORITY: &str = "x-priority";
+
+/// Parse the request priority given in the `X-Priority` header, if any.
+fn priority(header: Option<&HeaderValue>) -> TCResult<Priority> {
+    if let Some(header) = header {
+        header
+            .to_str()
+            .map_err(|e| TCError::bad_request("invalid X-Priority header", e))?
+            .parse()
+    } else {
+        Ok(Priority::default())
+    }
+}
+
 trait Accept: Default + FromStr {
     fn parse_header(header: Option<&HeaderValue>) -> TCResult<Self> {
         let header = if let Some(header) = header {
@@ -39,7 +100,8 @@ trait Accept: Default + FromStr {
                 }
 
                 let format = opt[0].parse();
-                let q = opt[1].parse().map_err(|e| {
+                let q = opt[1].trim();
+                let q = q.strip_prefix("q=").unwrap_or(q).parse().map_err(|e| {
                     TCError::bad_request("invalid quality value in Accept-Encoding header", e)
                 })?;
 
@@ -97,3 +159,55 @@ impl fmt::Display for Encoding {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(header: &'static str) -> TCResult<Encoding> {
+        Encoding::parse_header(Some(&HeaderValue::from_static(header)))
+    }
+
+    #[test]
+    fn test_parse_accept_encoding() {
+        assert!(Encoding::parse_header(None).unwrap() == Encoding::Json);
+        assert!(parse("application/tbon").unwrap() == Encoding::Tbon);
+        assert!(parse("gzip, application/tbon").unwrap() == Encoding::Tbon);
+        assert!(parse("application/json;q=0.5, application/tbon;q=0.9").unwrap() == Encoding::Tbon);
+        assert!(parse("application/tbon;q=0.5, application/json;q=0.9").unwrap() == Encoding::Json);
+        assert!(parse("application/tbon;q=high").is_err());
+    }
+
+    #[test]
+    fn test_request_id_propagation() {
+        let inbound = request_id(Some(&HeaderValue::from_static("abc-123"))).unwrap();
+        assert_eq!(inbound, "abc-123");
+
+        let url = url::Url::parse("http://127.0.0.1:8702/app/replica").unwrap();
+        let outbound = client::req_builder("PUT", url, None, &inbound)
+            .body(hyper::Body::empty())
+            .unwrap();
+
+        assert_eq!(outbound.headers()[REQUEST_ID], "abc-123");
+
+        let generated = request_id(None).unwrap();
+        assert!(!generated.is_empty());
+        assert_ne!(generated, request_id(None).unwrap());
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        assert!(metadata(None).unwrap().is_empty());
+
+        let header = HeaderValue::from_static(r#"{"tenant": "acme"}"#);
+        let parsed = metadata(Some(&header)).unwrap();
+        let tenant: Id = "tenant".parse().unwrap();
+        assert_eq!(
+            parsed.get(&tenant),
+            Some(&Value::String("acme".to_string()))
+        );
+
+        assert!(metadata(Some(&HeaderValue::from_static("[1, 2]"))).is_err());
+        assert!(metadata(Some(&HeaderValue::from_static(r#"{"not an id": 1}"#))).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! A JSON [`Pointer`] to a nested [`State`], e.g. `/address/zip/0`.
+
+use std::str::FromStr;
+
+use tc_error::*;
+use tcgeneric::{Id, Map, TCPathBuf, Tuple};
+
+use crate::object::Object;
+use crate::scalar::{Scalar, Value};
+use crate::state::State;
+
+/// A JSON pointer (RFC 6901) to a nested member of a [`Map`] or [`Tuple`] [`State`].
+pub struct Pointer {
+    segments: Vec<String>,
+}
+
+impl Pointer {
+    /// Return the member of the given `state` at this `Pointer`.
+    pub fn resolve(&self, state: State) -> TCResult<State> {
+        self.segments
+            .iter()
+            .try_fold(state, |state, segment| get(state, segment))
+    }
+
+    /// Return the path of the member at this `Pointer` of the resource at the given `path`,
+    /// so that a write to it is handled by the member itself.
+    pub fn append_to(&self, path: TCPathBuf) -> TCResult<TCPathBuf> {
+        self.segments.iter().try_fold(path, |path, segment| {
+            let segment: Id = segment
+                .parse()
+                .map_err(|_| TCError::bad_request("invalid path segment", segment))?;
+
+            Ok(path.append(segment))
+        })
+    }
+}
+
+impl FromStr for Pointer {
+    type Err = TCError;
+
+    fn from_str(pointer: &str) -> TCResult<Self> {
+        if pointer.is_empty() {
+            return Ok(Self { segments: vec![] });
+        } else if !pointer.starts_with('/') {
+            return Err(TCError::bad_request(
+                "a JSON pointer must begin with a '/'",
+                pointer,
+            ));
+        }
+
+        let segments = pointer[1..]
+            .split('/')
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect();
+
+        Ok(Self { segments })
+    }
+}
+
+fn get(state: State, segment: &str) -> TCResult<State> {
+    match state {
+        State::Map(mut map) => remove_entry(&mut map, segment),
+        State::Tuple(mut tuple) => remove_item(&mut tuple, segment),
+        State::Object(Object::Instance(instance)) => get(instance.parent().clone(), segment),
+        State::Scalar(scalar) => get_scalar(scalar, segment).map(State::Scalar),
+        _ => Err(TCError::not_found(segment)),
+    }
+}
+
+fn get_scalar(scalar: Scalar, segment: &str) -> TCResult<Scalar> {
+    match scalar {
+        Scalar::Map(mut map) => remove_entry(&mut map, segment),
+        Scalar::Tuple(mut tuple) => remove_item(&mut tuple, segment),
+        Scalar::Value(Value::Tuple(mut tuple)) => {
+            remove_item(&mut tuple, segment).map(Scalar::Value)
+        }
+        _ => Err(TCError::not_found(segment)),
+    }
+}
+
+fn remove_entry<T>(map: &mut Map<T>, segment: &str) -> TCResult<T> {
+    let id = entry_id(segment)?;
+    map.remove(&id).ok_or_else(|| TCError::not_found(segment))
+}
+
+fn remove_item<T>(tuple: &mut Tuple<T>, segment: &str) -> TCResult<T> {
+    let i = item_index(segment)?;
+    if i < tuple.len() {
+        Ok(tuple.remove(i))
+    } else {
+        Err(TCError::not_found(segment))
+    }
+}
+
+fn entry_id(segment: &str) -> TCResult<Id> {
+    segment.parse().map_err(|_| TCError::not_found(segment))
+}
+
+fn item_index(segment: &str) -> TCResult<usize> {
+    segment.parse().map_err(|_| TCError::not_found(segment))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn test_pointer() {
+        let zip: Value = vec![Value::from(7u64), Value::from(5u64)].into();
+
+        let mut address = Map::new();
+        address.insert("zip".parse().unwrap(), Scalar::Value(zip));
+
+        let mut profile = Map::new();
+        profile.insert("address".parse().unwrap(), Scalar::Map(address));
+        let profile = State::Scalar(Scalar::Map(profile));
+
+        let pointer: Pointer = "/address/zip/1".parse().unwrap();
+        let zip = pointer.resolve(profile.clone()).unwrap();
+        assert_eq!(Value::try_from(zip).unwrap(), Value::from(5u64));
+
+        let path: TCPathBuf = "/app/profile".parse().unwrap();
+        let path = pointer.append_to(path).unwrap();
+        assert_eq!(path.to_string(), "/app/profile/address/zip/1");
+
+        let missing: Pointer = "/address/city".parse().unwrap();
+        let cause = missing.resolve(profile).unwrap_err();
+        assert!(cause.code() == ErrorType::NotFound);
+
+        assert!("address".parse::<Pointer>().is_err());
+    }
+}
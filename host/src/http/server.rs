@@ -1,79 +1,141 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::io::BufReader;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use futures::future::{self, TryFutureExt};
+use destream::de;
+use futures::channel::mpsc;
+use futures::future::{self, Future, TryFutureExt};
+use futures::sink::SinkExt;
 use futures::stream::{self, Stream, StreamExt, TryStreamExt};
-use hyper::service::{make_service_fn, service_fn};
+use hyper::server::accept::{self, Accept as _};
+use hyper::server::conn::{AddrIncoming, AddrStream};
 use hyper::{Body, Response};
+use log::debug;
 use serde::de::DeserializeOwned;
+use tokio_rustls::rustls::internal::pemfile;
+use tokio_rustls::rustls::{NoClientAuth, ServerConfig};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
 
 use tc_error::*;
-use tc_transact::{IntoView, TxnId};
-use tcgeneric::{NetworkTime, TCPathBuf};
+use tc_transact::{IntoView, Transaction, TxnId};
+use tcgeneric::{Id, Map, NetworkTime, TCBoxTryStream, TCPathBuf};
 
-use crate::gateway::Gateway;
+use crate::gateway::{Gateway, Priority};
+use crate::scalar::Value;
 use crate::state::State;
+use crate::stream::{RequestBody, TCStream};
 use crate::txn::*;
 
+use super::pointer::Pointer;
 use super::{Accept, Encoding};
 
 type GetParams = HashMap<String, String>;
 
+/// The maximum number of TLS handshakes to perform concurrently.
+const TLS_HANDSHAKE_CONCURRENCY: usize = 256;
+
+/// The maximum time to wait for a client to complete a TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The path at which request [`crate::gateway::Metrics`] are served.
+const METRICS: &str = "/metrics";
+
+/// The content type of the Prometheus text exposition format.
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
 /// Tinychain's HTTP server. Should only be used through a [`Gateway`].
 pub struct HTTPServer {
     gateway: Arc<Gateway>,
+    tls: Option<Arc<ServerConfig>>,
 }
 
 impl HTTPServer {
-    pub fn new(gateway: Arc<Gateway>) -> Self {
-        Self { gateway }
+    /// Construct a new `HTTPServer`, which serves HTTPS if a TLS configuration is given.
+    pub fn new(gateway: Arc<Gateway>, tls: Option<Arc<ServerConfig>>) -> Self {
+        Self { gateway, tls }
     }
 
     async fn handle_timeout(
         self: Arc<Self>,
         request: hyper::Request<Body>,
     ) -> Result<Response<Body>, hyper::Error> {
-        match tokio::time::timeout(self.gateway.request_ttl(), self.handle(request)).await {
-            Ok(result) => result,
-            Err(cause) => Ok(transform_error(
-                TCError::timeout(cause),
-                Encoding::default(),
-            )),
-        }
+        let method = request.method().to_string();
+        let start = Instant::now();
+
+        let gateway = self.gateway.clone();
+        let (response, error) =
+            match tokio::time::timeout(gateway.request_ttl(), self.handle(request)).await {
+                Ok(Ok(response)) => (response, None),
+                Ok(Err((cause, encoding))) => {
+                    let code = cause.code();
+                    (transform_error(cause, encoding), Some(code))
+                }
+                Err(cause) => (
+                    transform_error(TCError::timeout(cause), Encoding::default()),
+                    Some(ErrorType::Timeout),
+                ),
+            };
+
+        gateway.metrics().record(&method, start.elapsed(), error);
+
+        Ok(response)
     }
 
     async fn handle(
         self: Arc<Self>,
         request: hyper::Request<Body>,
-    ) -> Result<Response<Body>, hyper::Error> {
-        let (params, txn, accept_encoding, request_encoding) =
+    ) -> Result<Response<Body>, (TCError, Encoding)> {
+        let (params, txn, priority, accept_encoding, request_encoding) =
             match self.process_headers(&request).await {
                 Ok(header_data) => header_data,
-                Err(cause) => return Ok(transform_error(cause, Encoding::default())),
+                Err(cause) => return Err((cause, Encoding::default())),
             };
 
-        let state = match self.route(request_encoding, &txn, params, request).await {
+        let _admission = match self.gateway.queue().acquire(*txn.id(), priority).await {
+            Ok(permit) => permit,
+            Err(cause) => return Err((cause, accept_encoding)),
+        };
+
+        // metrics are served as plain text for a Prometheus scraper, not as an encoded State
+        if request.method() == hyper::Method::GET && request.uri().path() == METRICS {
+            let mut response = Response::new(Body::from(self.gateway.metrics().render()));
+            response.headers_mut().insert(
+                hyper::header::CONTENT_TYPE,
+                METRICS_CONTENT_TYPE.parse().expect("content type header"),
+            );
+
+            return Ok(response);
+        }
+
+        let path = request.uri().path().to_string();
+        let response = self.route(request_encoding, &txn, params, request);
+        let state = match self.gateway.txn_server().track(path, response).await {
             Ok(state) => state,
-            Err(cause) => return Ok(transform_error(cause, accept_encoding)),
+            Err(cause) => return Err((cause, accept_encoding)),
         };
 
         let view = match state.into_view(txn).await {
             Ok(view) => view,
-            Err(cause) => return Ok(transform_error(cause, accept_encoding)),
+            Err(cause) => return Err((cause, accept_encoding)),
         };
 
         let body = match accept_encoding {
             Encoding::Json => match destream_json::encode(view) {
                 Ok(response) => Body::wrap_stream(response.chain(delimiter(b"\n"))),
-                Err(cause) => return Ok(transform_error(TCError::internal(cause), Encoding::Json)),
+                Err(cause) => return Err((TCError::internal(cause), Encoding::Json)),
             },
             Encoding::Tbon => match tbon::en::encode(view) {
                 Ok(response) => Body::wrap_stream(response.map_err(TCError::internal)),
-                Err(cause) => return Ok(transform_error(TCError::internal(cause), Encoding::Tbon)),
+                Err(cause) => return Err((TCError::internal(cause), Encoding::Tbon)),
             },
         };
 
@@ -93,7 +155,7 @@ impl HTTPServer {
     async fn process_headers(
         &self,
         http_request: &hyper::Request<Body>,
-    ) -> TCResult<(GetParams, Txn, Encoding, Encoding)> {
+    ) -> TCResult<(GetParams, Txn, Priority, Encoding, Encoding)> {
         let content_type =
             if let Some(header) = http_request.headers().get(hyper::header::CONTENT_TYPE) {
                 header
@@ -107,6 +169,16 @@ impl HTTPServer {
         let accept_encoding = http_request.headers().get(hyper::header::ACCEPT_ENCODING);
         let accept_encoding = Encoding::parse_header(accept_encoding)?;
 
+        let request_id = super::request_id(http_request.headers().get(super::REQUEST_ID))?;
+        let metadata = super::metadata(http_request.headers().get(super::METADATA))?;
+        let priority = super::priority(http_request.headers().get(super::PRIORITY))?;
+        debug!(
+            "request {}: {} {}",
+            request_id,
+            http_request.method(),
+            http_request.uri().path()
+        );
+
         let mut params = http_request
             .uri()
             .query()
@@ -140,8 +212,59 @@ impl HTTPServer {
             TxnId::new(NetworkTime::now())
         };
 
-        let txn = self.gateway.new_txn(txn_id, token).await?;
-        Ok((params, txn, accept_encoding, content_type))
+        let read_only = if let Some(read_only) = params.remove("read_only") {
+            read_only
+                .parse()
+                .map_err(|_| TCError::bad_request("invalid read_only flag", read_only))?
+        } else {
+            false
+        };
+
+        let min_commit: Option<TxnId> = if let Some(min_commit) = params.remove("min_commit") {
+            Some(min_commit.parse()?)
+        } else {
+            None
+        };
+
+        let epoch: Option<u64> = if let Some(epoch) = params.remove("epoch") {
+            let epoch = epoch
+                .parse()
+                .map_err(|_| TCError::bad_request("invalid replica set epoch", epoch))?;
+
+            Some(epoch)
+        } else {
+            None
+        };
+
+        let hold = if let Some(hold) = params.remove("hold") {
+            hold.parse()
+                .map_err(|_| TCError::bad_request("invalid hold flag", hold))?
+        } else {
+            false
+        };
+
+        let txn = self.gateway.new_txn(txn_id, token, request_id).await?;
+        if hold {
+            // the client will send an explicit commit or abort message
+            txn.hold()?;
+        }
+
+        let txn = if let Some(min_commit) = min_commit {
+            txn.with_min_commit(min_commit)
+        } else if read_only {
+            txn.into_read_only()
+        } else {
+            txn
+        };
+        let txn = if let Some(epoch) = epoch {
+            txn.with_epoch(epoch)
+        } else {
+            txn
+        };
+
+        let txn = txn.with_context_metadata(metadata);
+
+        Ok((params, txn, priority, accept_encoding, content_type))
     }
 
     async fn route(
@@ -156,12 +279,28 @@ impl HTTPServer {
         match http_request.method() {
             &hyper::Method::GET => {
                 let key = get_param(&mut params, "key")?.unwrap_or_default();
-                self.gateway.get(txn, path.into(), key).await
+                let state = self.gateway.get(txn, path.into(), key).await?;
+
+                if let Some(pointer) = params.remove("pointer") {
+                    let pointer: Pointer = pointer.parse()?;
+                    pointer.resolve(state)
+                } else {
+                    Ok(state)
+                }
             }
 
             &hyper::Method::PUT => {
                 let key = get_param(&mut params, "key")?.unwrap_or_default();
                 let value = destream_body(http_request.into_body(), encoding, txn.clone()).await?;
+
+                // a write to a nested member is handled by the member itself
+                let path = if let Some(pointer) = params.remove("pointer") {
+                    let pointer: Pointer = pointer.parse()?;
+                    pointer.append_to(path)?
+                } else {
+                    path
+                };
+
                 self.gateway
                     .put(txn, path.into(), key, value)
                     .map_ok(State::from)
@@ -169,10 +308,25 @@ impl HTTPServer {
             }
 
             &hyper::Method::POST => {
-                let data = destream_body(http_request.into_body(), encoding, txn.clone()).await?;
+                let body = http_request.into_body();
+                let data = if let Some(name) = params.remove("stream") {
+                    stream_body(body, encoding, txn.clone(), params, name).await?
+                } else {
+                    destream_body(body, encoding, txn.clone()).await?
+                };
+
                 self.gateway.post(txn, path.into(), data).await
             }
 
+            &hyper::Method::PATCH => {
+                let key = get_param(&mut params, "key")?.unwrap_or_default();
+                let value = destream_body(http_request.into_body(), encoding, txn.clone()).await?;
+                self.gateway
+                    .patch(txn, path.into(), key, value)
+                    .map_ok(State::from)
+                    .await
+            }
+
             &hyper::Method::DELETE => {
                 let key = get_param(&mut params, "key")?.unwrap_or_default();
                 self.gateway
@@ -191,23 +345,68 @@ impl crate::gateway::Server for HTTPServer {
     type Error = hyper::Error;
 
     async fn listen(self, addr: SocketAddr) -> Result<(), Self::Error> {
-        println!("HTTP server listening on {}", &addr);
-        let server = Arc::new(self);
-
-        let new_service = make_service_fn(move |_| {
-            let server = server.clone();
-            async {
-                Ok::<_, hyper::Error>(service_fn(move |req| {
-                    let server = server.clone();
-                    HTTPServer::handle_timeout(server, req)
-                }))
-            }
-        });
+        let tls = self.tls.clone();
+        let new_service = MakeService {
+            server: Arc::new(self),
+        };
 
-        hyper::Server::bind(&addr)
-            .serve(new_service)
-            .with_graceful_shutdown(shutdown_signal())
-            .await
+        if let Some(tls) = tls {
+            println!("HTTPS server listening on {}", &addr);
+
+            let incoming = tls_incoming(AddrIncoming::bind(&addr)?, TlsAcceptor::from(tls));
+
+            hyper::Server::builder(accept::from_stream(incoming))
+                .serve(new_service)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+        } else {
+            println!("HTTP server listening on {}", &addr);
+
+            hyper::Server::bind(&addr)
+                .serve(new_service)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+        }
+    }
+}
+
+/// Constructs a [`Service`] to handle the requests of each new connection, over any transport.
+struct MakeService {
+    server: Arc<HTTPServer>,
+}
+
+impl<'a, T> hyper::service::Service<&'a T> for MakeService {
+    type Response = Service;
+    type Error = hyper::Error;
+    type Future = future::Ready<Result<Service, hyper::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _conn: &'a T) -> Self::Future {
+        future::ready(Ok(Service {
+            server: self.server.clone(),
+        }))
+    }
+}
+
+/// Handles the requests of a single connection with an [`HTTPServer`].
+struct Service {
+    server: Arc<HTTPServer>,
+}
+
+impl hyper::service::Service<hyper::Request<Body>> for Service {
+    type Response = Response<Body>;
+    type Error = hyper::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<Body>, hyper::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: hyper::Request<Body>) -> Self::Future {
+        Box::pin(HTTPServer::handle_timeout(self.server.clone(), request))
     }
 }
 
@@ -223,24 +422,114 @@ async fn destream_body(body: hyper::Body, encoding: Encoding, txn: Txn) -> TCRes
     match encoding {
         Encoding::Json => {
             destream_json::try_decode(txn, body)
-                .map_err(|e| TCError::bad_request(ERR_DESERIALIZE, e))
+                .map_err(|e| TCError::bad_request(ERR_DESERIALIZE, &e).with_source(e))
                 .await
         }
         Encoding::Tbon => {
             tbon::de::try_decode(txn, body)
-                .map_err(|e| TCError::bad_request(ERR_DESERIALIZE, e))
+                .map_err(|e| TCError::bad_request(ERR_DESERIALIZE, &e).with_source(e))
                 .await
         }
     }
 }
 
+/// Decode the parameters of a POST request whose body is a list of [`Value`]s
+/// to stream into the parameter `name` without buffering it, e.g. the rows of a large `Table`.
+///
+/// The other parameters of the request are JSON-encoded in its query string.
+async fn stream_body(
+    body: hyper::Body,
+    encoding: Encoding,
+    txn: Txn,
+    params: GetParams,
+    name: String,
+) -> TCResult<State> {
+    if encoding != Encoding::Json {
+        return Err(TCError::unsupported(
+            "a streaming request body must be JSON-encoded",
+        ));
+    }
+
+    let mut data = Map::<State>::new();
+    for (param, value) in params {
+        let param: Id = param.parse()?;
+        let value = destream_body(Body::from(value), Encoding::Json, txn.clone()).await?;
+        data.insert(param, value);
+    }
+
+    let stream = TCStream::from(RequestBody::new(body_values(body)));
+    data.insert(name.parse()?, State::from(stream));
+
+    Ok(State::Map(data))
+}
+
+/// Decode the JSON list in the given `body` one [`Value`] at a time, as it's received.
+fn body_values(body: hyper::Body) -> TCBoxTryStream<'static, Value> {
+    const ERR_DESERIALIZE: &str = "error deserializing HTTP request body";
+
+    // the channel is bounded, so the body is only read as fast as its values are consumed
+    let (tx, rx) = mpsc::channel(1);
+    let mut on_err = tx.clone();
+
+    tokio::spawn(async move {
+        if let Err(cause) = destream_json::try_decode::<_, _, ValueSink>(tx, body).await {
+            // if the receiver has hung up there's nobody left to report the error to
+            let _ = on_err
+                .send(Err(TCError::bad_request(ERR_DESERIALIZE, cause)))
+                .await;
+        }
+    });
+
+    Box::pin(rx)
+}
+
+/// A JSON list whose [`Value`]s are sent to a channel as they're decoded, instead of collected.
+struct ValueSink;
+
+#[async_trait]
+impl de::FromStream for ValueSink {
+    type Context = mpsc::Sender<TCResult<Value>>;
+
+    async fn from_stream<D: de::Decoder>(
+        tx: Self::Context,
+        decoder: &mut D,
+    ) -> Result<Self, D::Error> {
+        decoder.decode_seq(ValueSinkVisitor { tx }).await
+    }
+}
+
+struct ValueSinkVisitor {
+    tx: mpsc::Sender<TCResult<Value>>,
+}
+
+#[async_trait]
+impl de::Visitor for ValueSinkVisitor {
+    type Value = ValueSink;
+
+    fn expecting() -> &'static str {
+        "a list of Values"
+    }
+
+    async fn visit_seq<A: de::SeqAccess>(mut self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(value) = seq.next_element(()).await? {
+            if self.tx.send(Ok(value)).await.is_err() {
+                // the receiver has hung up, so there's no need to read the rest of the body
+                break;
+            }
+        }
+
+        Ok(ValueSink)
+    }
+}
+
 fn get_param<T: DeserializeOwned>(
     params: &mut HashMap<String, String>,
     name: &str,
 ) -> TCResult<Option<T>> {
     if let Some(param) = params.remove(name) {
         let val: T = serde_json::from_str(&param).map_err(|e| {
-            TCError::bad_request(&format!("Unable to parse URI parameter '{}'", name), e)
+            TCError::bad_request(&format!("Unable to parse URI parameter '{}'", name), &e)
+                .with_source(e)
         })?;
 
         Ok(Some(val))
@@ -250,22 +539,12 @@ fn get_param<T: DeserializeOwned>(
 }
 
 fn transform_error(err: TCError, encoding: Encoding) -> hyper::Response<Body> {
-    let code = match err.code() {
-        BadGateway => StatusCode::BAD_GATEWAY,
-        BadRequest => StatusCode::BAD_REQUEST,
-        Forbidden => StatusCode::FORBIDDEN,
-        Conflict => StatusCode::CONFLICT,
-        Internal => StatusCode::INTERNAL_SERVER_ERROR,
-        MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
-        NotFound => StatusCode::NOT_FOUND,
-        NotImplemented => StatusCode::NOT_IMPLEMENTED,
-        Timeout => StatusCode::REQUEST_TIMEOUT,
-        Unauthorized => StatusCode::UNAUTHORIZED,
-    };
+    let code = hyper::StatusCode::from_u16(err.code().status())
+        .unwrap_or(hyper::StatusCode::INTERNAL_SERVER_ERROR);
 
     let body = match encoding {
         Encoding::Json => {
-            let encoded = destream_json::encode(err).expect("encode error");
+            let encoded = destream_json::encode(err.describe()).expect("encode error");
             let encoded = encoded.chain(delimiter(b"\n"));
             Body::wrap_stream(encoded)
         }
@@ -279,13 +558,77 @@ fn transform_error(err: TCError, encoding: Encoding) -> hyper::Response<Body> {
         encoding.to_string().parse().expect("content type header"),
     );
 
-    use hyper::StatusCode;
-    use tc_error::ErrorType::*;
     *response.status_mut() = code;
 
     response
 }
 
+/// Load a TLS configuration from a PEM-encoded certificate chain and private key.
+///
+/// The private key may be either PKCS #8 or RSA.
+pub fn load_tls_config(cert: &Path, key: &Path) -> TCResult<ServerConfig> {
+    let open = |path: &Path| {
+        std::fs::File::open(path).map(BufReader::new).map_err(|e| {
+            TCError::bad_request(format!("unable to read {:?}", path), &e).with_source(e)
+        })
+    };
+
+    let certs = pemfile::certs(&mut open(cert)?)
+        .map_err(|()| TCError::bad_request("invalid TLS certificate", cert.display()))?;
+
+    let mut keys = pemfile::pkcs8_private_keys(&mut open(key)?)
+        .map_err(|()| TCError::bad_request("invalid TLS private key", key.display()))?;
+
+    if keys.is_empty() {
+        keys = pemfile::rsa_private_keys(&mut open(key)?)
+            .map_err(|()| TCError::bad_request("invalid TLS private key", key.display()))?;
+    }
+
+    let key = keys
+        .pop()
+        .ok_or_else(|| TCError::bad_request("no TLS private key found in", key.display()))?;
+
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config
+        .set_single_cert(certs, key)
+        .map_err(|e| TCError::bad_request("invalid TLS configuration", e))?;
+
+    Ok(config)
+}
+
+/// Accept TCP connections from `incoming` and perform a TLS handshake with each one,
+/// dropping any connection whose handshake fails or does not complete in time.
+fn tls_incoming(
+    mut incoming: AddrIncoming,
+    acceptor: TlsAcceptor,
+) -> impl Stream<Item = Result<TlsStream<AddrStream>, std::io::Error>> {
+    stream::poll_fn(move |cx| Pin::new(&mut incoming).poll_accept(cx))
+        .filter_map(|conn| {
+            future::ready(match conn {
+                Ok(conn) => Some(conn),
+                Err(cause) => {
+                    debug!("error accepting TCP connection: {}", cause);
+                    None
+                }
+            })
+        })
+        .map(move |conn| tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(conn)))
+        .buffer_unordered(TLS_HANDSHAKE_CONCURRENCY)
+        .filter_map(|conn| {
+            future::ready(match conn {
+                Ok(Ok(conn)) => Some(Ok(conn)),
+                Ok(Err(cause)) => {
+                    debug!("TLS handshake failed: {}", cause);
+                    None
+                }
+                Err(_) => {
+                    debug!("TLS handshake timed out after {:?}", TLS_HANDSHAKE_TIMEOUT);
+                    None
+                }
+            })
+        })
+}
+
 async fn shutdown_signal() {
     tokio::signal::ctrl_c().await.expect("SIGTERM handler")
 }
@@ -293,3 +636,52 @@ async fn shutdown_signal() {
 fn delimiter<E>(content: &'static [u8]) -> impl Stream<Item = Result<Bytes, E>> {
     stream::once(future::ready(Ok(Bytes::from_static(content))))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_body_values() {
+        // an endless list of rows, which could never be buffered in its entirety
+        let sent = Arc::new(AtomicUsize::new(0));
+        let counter = sent.clone();
+        let rows = stream::iter(0u64..).map(move |i| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            let row = format!("{}[{}, {}]", if i == 0 { "[" } else { "," }, i, i * 2);
+            Ok::<Bytes, std::io::Error>(Bytes::from(row))
+        });
+
+        let values: Vec<Value> = body_values(Body::wrap_stream(rows))
+            .take(100)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(values.len(), 100);
+        assert!(values.iter().all(|row| matches!(row, Value::Tuple(_))));
+
+        // only a bounded number of rows past those consumed were read from the request body
+        assert!(sent.load(Ordering::Relaxed) < 200);
+    }
+
+    #[tokio::test]
+    async fn test_transform_error() {
+        let err = TCError::bad_request("expected a non-empty String separator, not", "");
+        let response = transform_error(err, Encoding::Json);
+        assert_eq!(response.status(), hyper::StatusCode::BAD_REQUEST);
+
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "code": "/error/bad_request",
+                "message": "expected a non-empty String separator, not: ",
+                "status": 400,
+            })
+        );
+    }
+}
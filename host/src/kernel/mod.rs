@@ -5,15 +5,16 @@ use std::fmt;
 use std::pin::Pin;
 
 use bytes::Bytes;
-use futures::future::Future;
-use log::debug;
+use futures::future::{join_all, Future};
+use log::{debug, warn};
 use safecast::*;
 
 use tc_error::*;
+use tc_transact::Transaction;
 use tcgeneric::*;
 
-use crate::cluster::Cluster;
-use crate::object::InstanceExt;
+use crate::cluster::{Cluster, Write};
+use crate::object::{InstanceExt, Object};
 use crate::route::{Public, Static};
 use crate::scalar::*;
 use crate::state::*;
@@ -23,7 +24,9 @@ use hosted::Hosted;
 
 mod hosted;
 
+const DEBUG: PathLabel = path_label(&["debug"]);
 const HYPOTHETICAL: PathLabel = path_label(&["transact", "hypothetical"]);
+const SAVEPOINT: PathLabel = path_label(&["transact", "savepoint"]);
 
 type ExeScope<'a> = crate::scalar::Scope<'a, State>;
 
@@ -55,6 +58,26 @@ impl Kernel {
             } else {
                 Err(TCError::not_found(key))
             }
+        } else if path == &DEBUG[..] {
+            let target = TCPathBuf::try_cast_from(key, |k| {
+                TCError::bad_request("invalid path to debug", k)
+            })?;
+
+            let state = if let Some((suffix, cluster)) = self.hosted.get(&target) {
+                cluster.get(txn, suffix, Value::None).await?
+            } else {
+                Static.get(txn, &target, Value::None).await?
+            };
+
+            let description = state.describe(txn).await?;
+            Ok(Value::String(description).into())
+        } else if path == &SAVEPOINT[..] {
+            // creating a savepoint changes the state of the transaction, so it requires a POST
+            Err(TCError::method_not_allowed(
+                OpRefType::Get,
+                self,
+                TCPath::from(path),
+            ))
         } else if let Some(class) = StateType::from_path(path) {
             class.try_cast_from_value(key)
         } else if let Some((suffix, cluster)) = self.hosted.get(path) {
@@ -65,6 +88,27 @@ impl Kernel {
                 cluster
             );
 
+            if let Some((replica, since)) = cluster.read_replica(txn, suffix).await? {
+                let link = suffix
+                    .iter()
+                    .cloned()
+                    .fold(replica.clone(), |link, segment| link.append(segment));
+
+                match txn
+                    .clone()
+                    .into_balanced(since)
+                    .get(link, key.clone())
+                    .await
+                {
+                    Ok(state) => return Ok(state),
+                    Err(cause) => warn!(
+                        "balanced read from replica {} failed, reading locally: {}",
+                        replica, cause
+                    ),
+                }
+            }
+
+            cluster.validate_min_commit(txn).await?;
             cluster.get(&txn, suffix, key).await
         } else {
             Static.get(txn, path, key).await
@@ -99,6 +143,10 @@ impl Kernel {
                 TCPath::from(path),
             ))
         } else if let Some((suffix, cluster)) = self.hosted.get(path) {
+            if let State::Object(Object::Class(class)) = &value {
+                class.validate()?;
+            }
+
             debug!(
                 "PUT {}: {} <- {} to cluster {}",
                 TCPath::from(suffix),
@@ -133,19 +181,61 @@ impl Kernel {
                     TCPath::from(suffix)
                 );
 
-                let write = |replica_link: Link| {
-                    let mut target = replica_link.clone();
-                    target.extend(suffix.to_vec());
+                let write = Write::Put(suffix.to_vec().into(), key, value);
+                cluster.replicate_write(txn.clone(), write).await
+            })
+            .await
+        } else {
+            Static.put(txn, path, key, value).await
+        }
+    }
 
-                    debug!("replicate PUT to {}", target);
-                    txn.put(target, key.clone(), value.clone())
-                };
+    /// Route a PATCH request.
+    pub async fn patch(
+        &self,
+        txn: &Txn,
+        path: &[PathSegment],
+        key: Value,
+        value: State,
+    ) -> TCResult<()> {
+        if path.is_empty() {
+            Err(TCError::method_not_allowed(
+                "PATCH",
+                self,
+                TCPath::from(path),
+            ))
+        } else if let Some(class) = StateType::from_path(path) {
+            Err(TCError::method_not_allowed(
+                "PATCH",
+                class,
+                TCPath::from(path),
+            ))
+        } else if let Some((suffix, cluster)) = self.hosted.get(path) {
+            debug!(
+                "PATCH {}: {} <- {} to cluster {}",
+                TCPath::from(suffix),
+                key,
+                value,
+                cluster
+            );
+
+            let txn = maybe_claim_leadership(cluster, txn).await?;
 
+            execute(txn, cluster, |txn, cluster| async move {
+                cluster
+                    .patch(&txn, suffix, key.clone(), value.clone())
+                    .await?;
+
+                if !txn.is_leader(cluster.path()) {
+                    return Ok(());
+                }
+
+                let write = Write::Patch(suffix.to_vec().into(), key, value);
                 cluster.replicate_write(txn.clone(), write).await
             })
             .await
         } else {
-            Static.put(txn, path, key, value).await
+            Static.patch(txn, path, key, value).await
         }
     }
 
@@ -162,19 +252,31 @@ impl Kernel {
                     TCPath::from(path),
                 ))
             }
+        } else if path == &SAVEPOINT[..] {
+            if Map::try_from(data)?.is_empty() {
+                let savepoint = txn.savepoint().await?;
+                Ok(Value::from(savepoint.ordinal() as u64).into())
+            } else {
+                Err(TCError::bad_request(
+                    "creating a savepoint does not accept any",
+                    "parameters",
+                ))
+            }
         } else if path == &HYPOTHETICAL[..] {
             let txn = txn.clone().claim(&self.actor, TCPathBuf::default()).await?;
             let context = Map::<State>::default();
 
             if Vec::<(Id, State)>::can_cast_from(&data) {
                 let op_def: Vec<(Id, State)> = data.opt_cast_into().unwrap();
-                OpDef::call(op_def, &txn, context).await
+                OpDef::call(op_def, &txn, context, &OpConfig::default()).await
             } else {
                 data.resolve(&ExeScope::new(None, context), &txn).await
             }
         } else if let Some(class) = StateType::from_path(path) {
+            // the whole path names the class itself, so there is no path left to route
+            // within it, i.e. this is a request to the class's own POST handler
             let params = data.try_into()?;
-            class.post(txn, path, params).await
+            class.post(txn, &[], params).await
         } else if let Some((suffix, cluster)) = self.hosted.get(path) {
             let params: Map<State> = data.try_into()?;
 
@@ -201,12 +303,36 @@ impl Kernel {
         }
     }
 
+    /// Call the POST method at `path` of a hosted cluster within the given transaction.
+    ///
+    /// Unlike [`Self::post`] this does not claim the transaction on behalf of the cluster,
+    /// so the cluster must validate that the method is read-only when it loads.
+    pub async fn compute(
+        &self,
+        txn: &Txn,
+        path: &[PathSegment],
+        params: Map<State>,
+    ) -> TCResult<State> {
+        if let Some((suffix, cluster)) = self.hosted.get(path) {
+            cluster.post(txn, suffix, params).await
+        } else {
+            Err(TCError::not_found(TCPath::from(path)))
+        }
+    }
+
     /// Route a DELETE request.
-    pub async fn delete(&self, txn: &Txn, path: &[PathSegment], key: Value) -> TCResult<()> {
-        if path.is_empty() || StateType::from_path(path).is_some() {
+    pub async fn delete(&self, txn: &Txn, path: &[PathSegment], key: Value) -> TCResult<Value> {
+        if path == &SAVEPOINT[..] {
+            let ordinal =
+                u64::try_cast_from(key, |k| TCError::bad_request("invalid savepoint", k))?;
+
+            let savepoint = Savepoint::new(*txn.id(), ordinal as usize);
+            txn.rollback_to(savepoint).await?;
+            Ok(Value::None)
+        } else if path.is_empty() || StateType::from_path(path).is_some() {
             if key.is_none() {
                 // it's a rollback message for a hypothetical transaction
-                Ok(())
+                Ok(Value::None)
             } else {
                 Err(TCError::method_not_allowed(
                     OpRefType::Delete,
@@ -235,7 +361,7 @@ impl Kernel {
 
             let txn = maybe_claim_leadership(cluster, txn).await?;
             execute(txn, cluster, |txn, cluster| async move {
-                cluster.delete(&txn, suffix, key.clone()).await?;
+                let deleted = cluster.delete(&txn, suffix, key.clone()).await?;
 
                 let txn = if !txn.has_leader(cluster.path()) {
                     cluster.lead(txn).await?
@@ -246,7 +372,7 @@ impl Kernel {
                 let self_link = txn.link(cluster.path().to_vec().into());
                 if suffix.is_empty() {
                     // it's a synchronization message
-                    return Ok(());
+                    return Ok(deleted);
                 } else if !txn.is_leader(cluster.path()) {
                     debug!(
                         "{} successfully replicated DELETE {}",
@@ -254,7 +380,7 @@ impl Kernel {
                         TCPath::from(suffix)
                     );
 
-                    return Ok(());
+                    return Ok(deleted);
                 }
 
                 debug!(
@@ -262,21 +388,37 @@ impl Kernel {
                     self_link,
                     TCPath::from(suffix)
                 );
-                let write = |replica_link: Link| {
-                    let mut target = replica_link.clone();
-                    target.extend(suffix.to_vec());
-
-                    debug!("replicate DELETE to {}", target);
-                    txn.delete(target, key.clone())
-                };
-
-                cluster.replicate_write(txn.clone(), write).await
+                let write = Write::Delete(suffix.to_vec().into(), key);
+                cluster.replicate_write(txn.clone(), write).await?;
+                Ok(deleted)
             })
             .await
         } else {
             Static.delete(txn, path, key).await
         }
     }
+
+    /// Commit (or roll back, if `commit` is `false`) each cluster claimed by a transaction
+    /// which was held open across multiple requests.
+    pub async fn finish(&self, held: Vec<(TCPathBuf, Txn)>, commit: bool) -> TCResult<()> {
+        let finished = held.iter().map(|(cluster_path, txn)| async move {
+            let (_, cluster) = self.hosted.get(cluster_path).ok_or_else(|| {
+                TCError::internal(format!("no hosted cluster at {}", cluster_path))
+            })?;
+
+            if commit {
+                debug!("commit {}", cluster);
+                cluster.distribute_commit(txn).await
+            } else {
+                debug!("rollback {}", cluster);
+                cluster.distribute_rollback(txn).await;
+                Ok(())
+            }
+        });
+
+        // every cluster must receive its message even if another fails
+        join_all(finished).await.into_iter().collect()
+    }
 }
 
 impl fmt::Display for Kernel {
@@ -296,12 +438,22 @@ fn execute<
     handler: F,
 ) -> Pin<Box<dyn Future<Output = TCResult<R>> + Send + 'a>> {
     Box::pin(async move {
+        // a replicated write carries the epoch of the sender's replica set, which only applies
+        // to this cluster, so it must not be forwarded with any request made while handling it
+        let txn = if let Some(epoch) = txn.epoch() {
+            cluster.validate_epoch(txn.id(), epoch).await?;
+            txn.without_epoch()
+        } else {
+            txn
+        };
+
         if let Some(owner) = txn.owner() {
             if owner.path() == cluster.path() {
                 debug!("{} owns this transaction, no need to notify", cluster);
             } else if txn.is_leader(cluster.path()) {
                 let self_link = txn.link(cluster.path().to_vec().into());
-                txn.put(owner.clone(), Value::None, self_link.into())
+                txn.control()
+                    .put(owner.clone(), Value::None, self_link.into())
                     .await?;
             } else {
                 let self_link = txn.link(cluster.path().to_vec().into());
@@ -312,6 +464,17 @@ fn execute<
             }
 
             handler(txn.clone(), cluster).await
+        } else if txn.is_held() {
+            // Claim or resume the transaction, and wait for an explicit commit or abort message
+            let txn = if let Some(txn) = txn.resume(cluster.path()) {
+                txn
+            } else {
+                let txn = cluster.claim(&txn).await?;
+                txn.hold_claim(cluster.path().to_vec().into());
+                txn
+            };
+
+            handler(txn, cluster).await
         } else {
             // Claim and execute the transaction
             let txn = cluster.claim(&txn).await?;
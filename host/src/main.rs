@@ -1,6 +1,7 @@
 use std::net::IpAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use bytes::Bytes;
 use destream::de::FromStream;
@@ -45,6 +46,12 @@ fn duration(flag: &str) -> TCResult<Duration> {
         .map_err(|_| TCError::bad_request("invalid duration", flag))
 }
 
+fn millis(flag: &str) -> TCResult<Duration> {
+    u64::from_str(flag)
+        .map(Duration::from_millis)
+        .map_err(|_| TCError::bad_request("invalid duration in milliseconds", flag))
+}
+
 #[derive(Clone, StructOpt)]
 struct Config {
     #[structopt(
@@ -86,14 +93,172 @@ struct Config {
 
     #[structopt(long = "http_port", default_value = "8702")]
     pub http_port: u16,
+
+    #[structopt(
+        long = "tls_cert",
+        about = "path to a PEM-encoded TLS certificate chain (requires --tls_key)"
+    )]
+    pub tls_cert: Option<PathBuf>,
+
+    #[structopt(
+        long = "tls_key",
+        about = "path to a PEM-encoded TLS private key (requires --tls_cert)"
+    )]
+    pub tls_key: Option<PathBuf>,
+
+    #[structopt(
+        long = "max_op_steps",
+        default_value = "100000",
+        about = "maximum number of references an op may resolve"
+    )]
+    pub max_op_steps: usize,
+
+    #[structopt(
+        long = "max_op_states",
+        default_value = "100000",
+        about = "maximum number of intermediate states the ops of one transaction may hold in scope"
+    )]
+    pub max_op_states: usize,
+
+    #[structopt(
+        long = "compaction_interval",
+        default_value = "1",
+        parse(try_from_str = duration),
+        about = "how often to finalize expired transactions and clean up the workspace"
+    )]
+    pub compaction_interval: Duration,
+
+    #[structopt(
+        long = "compaction_age",
+        default_value = "2",
+        parse(try_from_str = duration),
+        about = "how long to retain an expired transaction before finalizing it"
+    )]
+    pub compaction_age: Duration,
+
+    #[structopt(
+        long = "op_cache_size",
+        default_value = "0",
+        about = "maximum number of results of pure ops to memoize (0 to disable)"
+    )]
+    pub op_cache_size: usize,
+
+    #[structopt(
+        long = "op_cache_ttl",
+        default_value = "1",
+        parse(try_from_str = duration),
+        about = "how long to memoize the result of a pure op"
+    )]
+    pub op_cache_ttl: Duration,
+
+    #[structopt(
+        long = "high_priority_concurrency",
+        default_value = "1024",
+        about = "maximum number of high-priority requests to handle concurrently"
+    )]
+    pub high_priority_concurrency: usize,
+
+    #[structopt(
+        long = "low_priority_concurrency",
+        default_value = "16",
+        about = "maximum number of low-priority requests (\"X-Priority: low\") to handle concurrently"
+    )]
+    pub low_priority_concurrency: usize,
+
+    #[structopt(
+        long = "quorum",
+        default_value = "majority",
+        about = "the number of replicas which must acknowledge a write (\"all\" or \"majority\")"
+    )]
+    pub quorum: cluster::Quorum,
+
+    #[structopt(
+        long = "replica_retries",
+        default_value = "0",
+        about = "the number of times to retry a failed write to a replica"
+    )]
+    pub replica_retries: usize,
+
+    #[structopt(
+        long = "replica_retry_delay",
+        default_value = "100",
+        parse(try_from_str = millis),
+        about = "milliseconds to wait before retrying a failed write to a replica"
+    )]
+    pub replica_retry_delay: Duration,
+
+    #[structopt(
+        long = "replica_concurrency",
+        default_value = "16",
+        about = "maximum number of replicas to write to concurrently"
+    )]
+    pub replica_concurrency: usize,
+
+    #[structopt(
+        long = "replica_queue_size",
+        default_value = "0",
+        about = "maximum number of writes to queue for an unreachable replica (0 to drop it instead)"
+    )]
+    pub replica_queue_size: usize,
+
+    #[structopt(
+        long = "balance_reads",
+        about = "spread the GET requests of read-only transactions across the replicas of a cluster"
+    )]
+    pub balance_reads: bool,
+
+    #[cfg(feature = "tensor")]
+    #[structopt(
+        long = "tensor_reduce_concurrency",
+        default_value = "0",
+        about = "maximum number of dense tensor blocks to reduce concurrently (0 for one per CPU)"
+    )]
+    pub tensor_reduce_concurrency: usize,
 }
 
 impl Config {
-    fn gateway(&self) -> gateway::Config {
-        gateway::Config {
+    fn gateway(&self) -> TCResult<gateway::Config> {
+        let tls = match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => Some(Arc::new(gateway::load_tls_config(cert, key)?)),
+            (None, None) => None,
+            _ => {
+                return Err(TCError::bad_request(
+                    "serving HTTPS requires both --tls_cert and",
+                    "--tls_key",
+                ))
+            }
+        };
+
+        #[cfg(feature = "tensor")]
+        let reduce_concurrency = self.tensor_reduce_concurrency;
+        #[cfg(not(feature = "tensor"))]
+        let reduce_concurrency = 0;
+
+        Ok(gateway::Config {
             addr: self.address,
             http_port: self.http_port,
             request_ttl: self.request_ttl,
+            max_op_steps: self.max_op_steps,
+            max_op_states: self.max_op_states,
+            compaction_interval: self.compaction_interval,
+            compaction_age: self.compaction_age,
+            op_cache_size: self.op_cache_size,
+            op_cache_ttl: self.op_cache_ttl,
+            high_priority_concurrency: self.high_priority_concurrency,
+            low_priority_concurrency: self.low_priority_concurrency,
+            reduce_concurrency,
+            tls,
+        })
+    }
+
+    fn cluster(&self) -> cluster::Config {
+        cluster::Config {
+            quorum: self.quorum,
+            retries: self.replica_retries,
+            retry_delay: self.replica_retry_delay,
+            concurrency: self.replica_concurrency,
+            queue_size: self.replica_queue_size,
+            balance_reads: self.balance_reads,
         }
     }
 }
@@ -101,7 +266,8 @@ impl Config {
 #[tokio::main]
 async fn main() -> Result<(), TokioError> {
     let config = Config::from_args();
-    let gateway_config = config.gateway();
+    let gateway_config = config.gateway()?;
+    let cluster_config = config.cluster();
 
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(config.log_level))
         .init();
@@ -136,7 +302,10 @@ async fn main() -> Result<(), TokioError> {
         let gateway = Gateway::new(gateway_config.clone(), kernel, txn_server.clone());
         let txn_id = TxnId::new(Gateway::time());
         let token = gateway.new_token(&txn_id)?;
-        let txn = txn_server.new_txn(gateway, txn_id, token).await?;
+        let request_id = tinychain::txn::new_request_id();
+        let txn = txn_server
+            .new_txn(gateway, txn_id, token, None, request_id)
+            .await?;
 
         let data_dir = data_dir.ok_or_else(|| {
             TCError::internal("the --data_dir option is required to host a Cluster")
@@ -159,7 +328,14 @@ async fn main() -> Result<(), TokioError> {
 
             let cluster = match InstanceClass::from_stream((), &mut decoder).await {
                 Ok(class) => {
-                    cluster::instantiate(&txn, host.clone(), class, data_dir.clone()).await?
+                    cluster::instantiate(
+                        &txn,
+                        host.clone(),
+                        class,
+                        data_dir.clone(),
+                        cluster_config.clone(),
+                    )
+                    .await?
                 }
                 Err(cause) => panic!("error parsing cluster config {:?}: {}", path, cause),
             };
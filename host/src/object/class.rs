@@ -5,6 +5,7 @@ use std::fmt;
 use async_trait::async_trait;
 use destream::{de, en};
 
+use tc_error::*;
 use tcgeneric::{path_label, Id, Map, PathLabel, TCPathBuf};
 
 use crate::scalar::*;
@@ -44,6 +45,35 @@ impl InstanceClass {
     pub fn proto(&'_ self) -> &'_ Map<Scalar> {
         &self.proto
     }
+
+    /// Check that this class extends a valid class [`Link`] and that each method in its
+    /// prototype is a well-formed [`OpDef`].
+    ///
+    /// This is checked when a class is defined, not when it's decoded, so that a class which
+    /// has already been stored can still be loaded if validation becomes stricter.
+    pub fn validate(&self) -> TCResult<()> {
+        if let Some(extends) = &self.extends {
+            if extends.path().is_empty() {
+                return Err(TCError::bad_request(
+                    "a class cannot extend a Link with an empty path",
+                    extends,
+                ));
+            }
+        }
+
+        for (name, member) in self.proto.iter() {
+            if let Scalar::Op(op_def) = member {
+                op_def.validate().map_err(|cause| {
+                    TCError::bad_request(
+                        format!("invalid method {} of {}", name, self),
+                        cause.message(),
+                    )
+                })?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl tcgeneric::Class for InstanceClass {}
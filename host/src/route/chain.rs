@@ -5,11 +5,18 @@ use tc_error::*;
 use tc_transact::fs::File;
 use tc_transact::Transaction;
 use tc_value::Value;
-use tcgeneric::{Instance, PathSegment, TCPath};
+use tcgeneric::{label, Instance, Label, PathSegment, TCPath};
 
 use crate::chain::{Chain, ChainInstance, ChainType, Subject, SUBJECT};
+use crate::collection::Collection;
+use crate::state::State;
+use crate::stream::{Archive, TCStream};
 
-use super::{DeleteHandler, GetHandler, Handler, PostHandler, Public, PutHandler, Route};
+use super::{
+    DeleteHandler, GetHandler, Handler, PatchHandler, PostHandler, Public, PutHandler, Route,
+};
+
+pub const PREFIX: Label = label("chain");
 
 impl Route for ChainType {
     fn route<'a>(&'a self, _path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
@@ -123,6 +130,23 @@ impl<'a> Handler<'a> for SubjectHandler<'a> {
         }))
     }
 
+    fn patch<'b>(self: Box<Self>) -> Option<PatchHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        match self.subject {
+            Subject::Table(table) => {
+                let table = table.clone();
+                let path = self.path;
+
+                Some(Box::new(move |txn, key, value| {
+                    Box::pin(async move { Public::patch(&table, txn, path, key, value).await })
+                }))
+            }
+            _ => None,
+        }
+    }
+
     fn delete<'b>(self: Box<Self>) -> Option<DeleteHandler<'a, 'b>>
     where
         'b: 'a,
@@ -139,7 +163,7 @@ impl<'a> Handler<'a> for SubjectHandler<'a> {
                     Subject::Value(file) if self.path.is_empty() => {
                         let mut subject = file.write_block(*txn.id(), SUBJECT.into()).await?;
                         *subject = Value::None;
-                        Ok(())
+                        Ok(Value::None)
                     }
                     Subject::Value(file) => {
                         let subject = file.read_block(*txn.id(), SUBJECT.into()).await?;
@@ -157,6 +181,10 @@ impl Route for Chain {
 
         if path.len() == 1 && path[0].as_str() == "chain" {
             Some(Box::new(ChainHandler::from(self)))
+        } else if path.len() == 1 && path[0].as_str() == "export" {
+            Some(Box::new(ExportHandler::from(self)))
+        } else if path.len() == 1 && path[0].as_str() == "refresh" {
+            Some(Box::new(RefreshHandler::from(self)))
         } else {
             Some(Box::new(AppendHandler::new(self, path)))
         }
@@ -230,7 +258,7 @@ impl<'a> Handler<'a> for AppendHandler<'a> {
                         debug!("Chain::delete {}", key);
 
                         self.chain
-                            .append_delete(*txn.id(), self.path.to_vec().into(), key.clone())
+                            .append_delete(txn, self.path.to_vec().into(), key.clone())
                             .await?;
 
                         delete_handler(txn, key).await
@@ -241,6 +269,31 @@ impl<'a> Handler<'a> for AppendHandler<'a> {
             None => None,
         }
     }
+
+    fn patch<'b>(self: Box<Self>) -> Option<PatchHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        match self.chain.subject().route(self.path) {
+            Some(handler) => match handler.patch() {
+                Some(patch_handler) => Some(Box::new(|txn, key, value| {
+                    Box::pin(async move {
+                        debug!("Chain::patch {} <- {}", key, value);
+
+                        // a partial update is recorded as a PUT of a map of column values
+                        let path = self.path.to_vec().into();
+                        self.chain
+                            .append_put(txn, path, key.clone(), value.clone())
+                            .await?;
+
+                        patch_handler(txn, key, value).await
+                    })
+                })),
+                None => None,
+            },
+            None => None,
+        }
+    }
 }
 
 struct ChainHandler<'a> {
@@ -269,3 +322,112 @@ impl<'a> Handler<'a> for ChainHandler<'a> {
         }))
     }
 }
+
+struct RefreshHandler<'a> {
+    chain: &'a Chain,
+}
+
+impl<'a> From<&'a Chain> for RefreshHandler<'a> {
+    fn from(chain: &'a Chain) -> Self {
+        Self { chain }
+    }
+}
+
+impl<'a> Handler<'a> for RefreshHandler<'a> {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let index: State = params.require(&label("index").into())?;
+                let since: Value = params.or_default(&label("since").into())?;
+                params.expect_empty()?;
+                txn.check_writable("refresh")?;
+
+                let index = match index {
+                    State::Collection(Collection::Table(index)) => *index,
+                    other => {
+                        return Err(TCError::bad_request(
+                            "expected a read-only Table index but found",
+                            other,
+                        ))
+                    }
+                };
+
+                let since = if since.is_none() {
+                    None
+                } else {
+                    since.to_string().parse().map(Some)?
+                };
+
+                self.chain
+                    .refresh_index(txn, index, since)
+                    .await
+                    .map(|index| State::Collection(Collection::from(index)))
+            })
+        }))
+    }
+}
+
+struct ExportHandler<'a> {
+    chain: &'a Chain,
+}
+
+impl<'a> From<&'a Chain> for ExportHandler<'a> {
+    fn from(chain: &'a Chain) -> Self {
+        Self { chain }
+    }
+}
+
+impl<'a> Handler<'a> for ExportHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                if key.is_some() {
+                    return Err(TCError::bad_request(
+                        "Chain export does not accept a key",
+                        key,
+                    ));
+                }
+
+                let archive = Archive::Chain(Box::new(self.chain.clone()));
+                Ok(TCStream::from(archive).into())
+            })
+        }))
+    }
+}
+
+struct ImportHandler;
+
+impl<'a> Handler<'a> for ImportHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let archive: State = params.require(&label("archive").into())?;
+                params.expect_empty()?;
+                txn.check_writable("import")?;
+
+                Archive::decode(txn, archive).await.map(State::Chain)
+            })
+        }))
+    }
+}
+
+pub struct Static;
+
+impl Route for Static {
+    fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
+        if path.len() == 1 && path[0].as_str() == "import" {
+            Some(Box::new(ImportHandler))
+        } else {
+            None
+        }
+    }
+}
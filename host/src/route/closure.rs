@@ -1,3 +1,4 @@
+use tc_value::Value;
 use tcgeneric::{Map, PathSegment};
 
 use crate::closure::Closure;
@@ -17,11 +18,11 @@ impl<'a> Handler<'a> for ClosureHandler {
         'b: 'a,
     {
         let mut context = self.context;
-        if let OpDef::Get((key_name, op_def)) = self.op_def {
+        if let OpDef::Get((key_name, op_def), config) = self.op_def {
             Some(Box::new(|txn, key| {
                 Box::pin(async move {
                     context.insert(key_name, key.into());
-                    OpDef::call(op_def, txn, context).await
+                    OpDef::call(op_def, txn, context, &config).await
                 })
             }))
         } else {
@@ -34,12 +35,12 @@ impl<'a> Handler<'a> for ClosureHandler {
         'b: 'a,
     {
         let mut context = self.context;
-        if let OpDef::Put((key_name, value_name, op_def)) = self.op_def {
+        if let OpDef::Put((key_name, value_name, op_def), config) = self.op_def {
             Some(Box::new(|txn, key, value| {
                 Box::pin(async move {
                     context.insert(key_name, key.into());
                     context.insert(value_name, value.into());
-                    OpDef::call(op_def, txn, context).await?;
+                    OpDef::call(op_def, txn, context, &config).await?;
                     Ok(())
                 })
             }))
@@ -53,10 +54,10 @@ impl<'a> Handler<'a> for ClosureHandler {
         'b: 'a,
     {
         let mut context = self.context;
-        if let OpDef::Post(op_def) = self.op_def {
+        if let OpDef::Post(op_def, config) = self.op_def {
             Some(Box::new(|txn, params| {
                 context.extend(params);
-                Box::pin(async move { OpDef::call(op_def, txn, context).await })
+                Box::pin(async move { OpDef::call(op_def, txn, context, &config).await })
             }))
         } else {
             None
@@ -68,12 +69,12 @@ impl<'a> Handler<'a> for ClosureHandler {
         'b: 'a,
     {
         let mut context = self.context;
-        if let OpDef::Delete((key_name, op_def)) = self.op_def {
+        if let OpDef::Delete((key_name, op_def), config) = self.op_def {
             Some(Box::new(|txn, key| {
                 Box::pin(async move {
                     context.insert(key_name, key.into());
-                    OpDef::call(op_def, txn, context).await?;
-                    Ok(())
+                    OpDef::call(op_def, txn, context, &config).await?;
+                    Ok(Value::None)
                 })
             }))
         } else {
@@ -7,9 +7,9 @@ use safecast::{TryCastFrom, TryCastInto};
 
 use tc_error::*;
 use tc_transact::{Transact, Transaction};
-use tcgeneric::{label, Id, Tuple};
+use tcgeneric::{label, Id, Map, Tuple};
 
-use crate::cluster::Cluster;
+use crate::cluster::{Cluster, EPOCH, MISSED, PREPARE, REPLICAS};
 use crate::route::*;
 use crate::scalar::{Link, Value};
 use crate::state::State;
@@ -43,6 +43,55 @@ impl<'a> From<&'a Cluster> for AuthorizeHandler<'a> {
     }
 }
 
+struct ConfigHandler<'a> {
+    cluster: &'a Cluster,
+}
+
+impl<'a> Handler<'a> for ConfigHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                key.expect_none()?;
+
+                let config = self.cluster.config();
+                let replicas = self.cluster.replicas(txn.id()).await?;
+                let epoch = self.cluster.epoch(txn.id()).await?;
+                let retry_delay = config.retry_delay.as_millis() as u64;
+
+                let config: Vec<(Id, Value)> = vec![
+                    (
+                        label("quorum").into(),
+                        Value::String(config.quorum.to_string()),
+                    ),
+                    (label("retries").into(), config.retries.into()),
+                    (label("retry_delay").into(), retry_delay.into()),
+                    (label("replicas").into(), replicas.len().into()),
+                    (EPOCH.into(), epoch.into()),
+                    (label("concurrency").into(), config.concurrency.into()),
+                    (label("queue_size").into(), config.queue_size.into()),
+                    (label("balance_reads").into(), config.balance_reads.into()),
+                ];
+
+                let config = config
+                    .into_iter()
+                    .map(|(id, value)| (id, State::from(value)))
+                    .collect::<Map<State>>();
+
+                Ok(State::Map(config))
+            })
+        }))
+    }
+}
+
+impl<'a> From<&'a Cluster> for ConfigHandler<'a> {
+    fn from(cluster: &'a Cluster) -> Self {
+        Self { cluster }
+    }
+}
+
 pub struct ClusterHandler<'a> {
     cluster: &'a Cluster,
 }
@@ -97,10 +146,12 @@ impl<'a> Handler<'a> for ClusterHandler<'a> {
     where
         'b: 'a,
     {
-        Some(Box::new(|txn, params| {
+        Some(Box::new(|txn, mut params| {
             Box::pin(async move {
                 // TODO: authorize request using a scope
 
+                let epoch: Value = params.or_default(&EPOCH.into())?;
+                let prepare: bool = params.or_default(&PREPARE.into())?;
                 if !params.is_empty() {
                     return Err(TCError::bad_request(
                         "unrecognized commit parameters",
@@ -108,6 +159,19 @@ impl<'a> Handler<'a> for ClusterHandler<'a> {
                     ));
                 }
 
+                if epoch.is_some() {
+                    let epoch = u64::try_cast_from(epoch, |v| {
+                        TCError::bad_request("invalid replica set epoch", v)
+                    })?;
+
+                    self.cluster.validate_epoch(txn.id(), epoch).await?;
+                }
+
+                if prepare {
+                    self.cluster.prepare(txn.id()).await?;
+                    return Ok(State::default());
+                }
+
                 if txn.is_leader(self.cluster.path()) {
                     self.cluster.distribute_commit(txn).await?;
                 } else {
@@ -134,7 +198,7 @@ impl<'a> Handler<'a> for ClusterHandler<'a> {
                     self.cluster.finalize(txn.id()).await;
                 }
 
-                Ok(())
+                Ok(Value::None)
             })
         }))
     }
@@ -218,11 +282,41 @@ impl<'a> Handler<'a> for ReplicaHandler<'a> {
     {
         Some(Box::new(|txn, key| {
             Box::pin(async move {
-                key.expect_none()?;
-
                 let replicas = self.cluster.replicas(txn.id()).await?;
                 assert!(replicas.contains(&txn.link(self.cluster.link().path().clone())));
-                Ok(Value::from_iter(replicas).into())
+
+                if key.is_none() {
+                    return Ok(Value::from_iter(replicas).into());
+                }
+
+                let replica = key.try_cast_into(|v| {
+                    TCError::bad_request("expected a Link to a Cluster, not", v)
+                })?;
+
+                if !replicas.contains(&replica) {
+                    return Err(TCError::not_found(format!("replica {}", replica)));
+                }
+
+                let progress = match self.cluster.replication_progress(&replica) {
+                    Some((current, target)) => {
+                        Value::Tuple(vec![Value::from(current), Value::from(target)].into())
+                    }
+                    None => Value::None,
+                };
+
+                let missed = self.cluster.missed_count(*txn.id(), &replica).await?;
+
+                let status: Vec<(Id, Value)> = vec![
+                    (label("progress").into(), progress),
+                    (MISSED.into(), missed.into()),
+                ];
+
+                let status = status
+                    .into_iter()
+                    .map(|(id, value)| (id, State::from(value)))
+                    .collect::<Map<State>>();
+
+                Ok(State::Map(status))
             })
         }))
     }
@@ -231,15 +325,31 @@ impl<'a> Handler<'a> for ReplicaHandler<'a> {
     where
         'b: 'a,
     {
-        Some(Box::new(|txn, key, link| {
+        Some(Box::new(|txn, key, value| {
             Box::pin(async move {
-                key.expect_none()?;
+                if key.is_none() {
+                    let link = value.try_cast_into(|v| {
+                        TCError::bad_request("expected a Link to a Cluster, not", v)
+                    })?;
 
-                let link = link.try_cast_into(|v| {
+                    return self.cluster.add_replica(txn, link).await;
+                }
+
+                // a replica reports its replication progress once it has caught up
+                let replica = key.try_cast_into(|v| {
                     TCError::bad_request("expected a Link to a Cluster, not", v)
                 })?;
 
-                self.cluster.add_replica(&txn, link).await
+                let progress = Value::try_cast_from(value, |s| {
+                    TCError::bad_request("expected replication progress, not", s)
+                })?;
+
+                let (current, target): (u64, u64) = progress.try_cast_into(|v| {
+                    TCError::bad_request("expected replication progress, not", v)
+                })?;
+
+                self.cluster.report_progress(replica, current, target);
+                Ok(())
             })
         }))
     }
@@ -254,7 +364,8 @@ impl<'a> Handler<'a> for ReplicaHandler<'a> {
                     TCError::bad_request("expected a Link to a Cluster, not", v)
                 })?;
 
-                self.cluster.remove_replicas(txn, &replicas).await
+                self.cluster.remove_replicas(txn, &replicas).await?;
+                Ok(Value::None)
             })
         }))
     }
@@ -266,6 +377,103 @@ impl<'a> From<&'a Cluster> for ReplicaHandler<'a> {
     }
 }
 
+struct MissedHandler<'a> {
+    cluster: &'a Cluster,
+}
+
+impl<'a> Handler<'a> for MissedHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, replica| {
+            Box::pin(async move {
+                let replica = replica.try_cast_into(|v| {
+                    TCError::bad_request("expected a Link to a Cluster, not", v)
+                })?;
+
+                let missed = self.cluster.missed(txn, &replica).await?;
+                let missed = missed.into_iter().map(State::from).collect::<Vec<State>>();
+                Ok(State::Tuple(missed.into()))
+            })
+        }))
+    }
+
+    fn delete<'b>(self: Box<Self>) -> Option<DeleteHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let (replica, count): (Link, u64) = key.try_cast_into(|v| {
+                    TCError::bad_request("expected a replica Link and a write count, not", v)
+                })?;
+
+                self.cluster
+                    .acknowledge_missed(*txn.id(), &replica, count)
+                    .await?;
+
+                Ok(Value::None)
+            })
+        }))
+    }
+}
+
+impl<'a> From<&'a Cluster> for MissedHandler<'a> {
+    fn from(cluster: &'a Cluster) -> Self {
+        Self { cluster }
+    }
+}
+
+struct EpochHandler<'a> {
+    cluster: &'a Cluster,
+}
+
+impl<'a> Handler<'a> for EpochHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                key.expect_none()?;
+
+                let epoch = self.cluster.epoch(txn.id()).await?;
+                Ok(Value::from(epoch).into())
+            })
+        }))
+    }
+
+    fn put<'b>(self: Box<Self>) -> Option<PutHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key, value| {
+            Box::pin(async move {
+                key.expect_none()?;
+                self.cluster.authorize_replica(txn).await?;
+
+                // a joining replica sets the epoch which the replica set agreed on
+                let epoch = Value::try_cast_from(value, |s| {
+                    TCError::bad_request("invalid replica set epoch", s)
+                })?;
+
+                let epoch = u64::try_cast_from(epoch, |v| {
+                    TCError::bad_request("invalid replica set epoch", v)
+                })?;
+
+                self.cluster.adopt_epoch(*txn.id(), epoch).await
+            })
+        }))
+    }
+}
+
+impl<'a> From<&'a Cluster> for EpochHandler<'a> {
+    fn from(cluster: &'a Cluster) -> Self {
+        Self { cluster }
+    }
+}
+
 impl Route for Cluster {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
         if path.is_empty() {
@@ -279,11 +487,16 @@ impl Route for Cluster {
         } else if path.len() == 1 {
             match path[0].as_str() {
                 "authorize" => Some(Box::new(AuthorizeHandler::from(self))),
+                "config" => Some(Box::new(ConfigHandler::from(self))),
                 "grant" => Some(Box::new(GrantHandler::from(self))),
                 "install" => Some(Box::new(InstallHandler::from(self))),
                 "replicas" => Some(Box::new(ReplicaHandler::from(self))),
                 _ => None,
             }
+        } else if path.len() == 2 && path[0] == REPLICAS && path[1] == MISSED {
+            Some(Box::new(MissedHandler::from(self)))
+        } else if path.len() == 2 && path[0] == REPLICAS && path[1] == EPOCH {
+            Some(Box::new(EpochHandler::from(self)))
         } else {
             None
         }
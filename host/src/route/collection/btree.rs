@@ -1,6 +1,6 @@
 use std::iter::FromIterator;
 
-use futures::{future, StreamExt, TryFutureExt, TryStreamExt};
+use futures::{TryFutureExt, TryStreamExt};
 use safecast::{Match, TryCastFrom, TryCastInto};
 
 use tc_btree::{BTreeInstance, BTreeType, Range};
@@ -16,6 +16,8 @@ use crate::scalar::Scalar;
 use crate::state::State;
 use crate::stream::TCStream;
 
+use super::bulk::{self, OnError};
+
 impl Route for BTreeType {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
         if self == &Self::default() {
@@ -35,7 +37,48 @@ impl<'a> Handler<'a> for CreateHandler {
     {
         Some(Box::new(|txn, value| {
             Box::pin(async move {
-                let schema = tc_btree::RowSchema::try_cast_from(value, |v| {
+                // accept either a schema, a (schema, order) tuple,
+                // or a (schema, order, compressed) tuple
+                let (schema, order, compressed) = match value {
+                    Value::Tuple(tuple)
+                        if tuple.len() == 2 && matches!(tuple[1], Value::Number(_)) =>
+                    {
+                        let mut tuple = tuple.into_inner();
+                        let order = tuple.pop().expect("BTree order");
+                        let schema = tuple.pop().expect("BTree schema");
+                        let order: usize = order
+                            .try_cast_into(|v| TCError::bad_request("invalid BTree order", v))?;
+
+                        (schema, Some(order), false)
+                    }
+                    Value::Tuple(tuple)
+                        if tuple.len() == 3
+                            && matches!(tuple[1], Value::Number(_) | Value::None)
+                            && matches!(tuple[2], Value::Number(_)) =>
+                    {
+                        let mut tuple = tuple.into_inner();
+                        let compressed = tuple.pop().expect("BTree compression flag");
+                        let order = tuple.pop().expect("BTree order");
+                        let schema = tuple.pop().expect("BTree schema");
+
+                        let compressed: bool = compressed.try_cast_into(|v| {
+                            TCError::bad_request("invalid BTree compression flag", v)
+                        })?;
+
+                        let order: Option<usize> = if order.is_none() {
+                            None
+                        } else {
+                            order
+                                .try_cast_into(|v| TCError::bad_request("invalid BTree order", v))
+                                .map(Some)?
+                        };
+
+                        (schema, order, compressed)
+                    }
+                    schema => (schema, None, false),
+                };
+
+                let schema = tc_btree::RowSchema::try_cast_from(schema, |v| {
                     TCError::bad_request("invalid BTree schema", v)
                 })?;
 
@@ -44,10 +87,11 @@ impl<'a> Handler<'a> for CreateHandler {
                     .create_file_tmp(*txn.id(), BTreeType::default())
                     .await?;
 
-                BTreeFile::create(file, schema, *txn.id())
-                    .map_ok(Collection::from)
-                    .map_ok(State::from)
-                    .await
+                let btree =
+                    BTreeFile::create_with_options(file, schema, order, compressed, *txn.id())
+                        .await?;
+
+                Ok(Collection::from(btree).into())
             })
         }))
     }
@@ -129,7 +173,9 @@ where
                     .clone()
                     .slice(range, false)?
                     .delete(*txn.id())
-                    .await
+                    .await?;
+
+                Ok(Value::None)
             })
         }))
     }
@@ -156,7 +202,13 @@ impl<'a> Handler<'a> for CopyHandler {
                 })?;
 
                 let source: TCStream = params.require(&label("source").into())?;
+                let on_error: Value = params.or_default(&label("on_error").into())?;
+                let on_error = OnError::try_cast_from(on_error, |v| {
+                    TCError::bad_request("invalid error policy", v)
+                })?;
+
                 params.expect_empty()?;
+                txn.check_writable("copy_from")?;
 
                 let txn_id = *txn.id();
 
@@ -168,29 +220,79 @@ impl<'a> Handler<'a> for CopyHandler {
                 let btree = BTreeFile::create(file, schema, txn_id).await?;
 
                 let keys = source.into_stream(txn.clone()).await?;
-                keys.map(|r| {
-                    r.and_then(|state| {
-                        Value::try_cast_from(state, |s| {
-                            TCError::bad_request("invalid BTree key", s)
-                        })
-                    })
-                })
-                .map(|r| {
-                    r.and_then(|value| {
-                        value.try_cast_into(|v| TCError::bad_request("invalid BTree key", v))
-                    })
-                })
-                .map_ok(|key| btree.insert(txn_id, key))
-                .try_buffer_unordered(num_cpus::get())
-                .try_fold((), |(), ()| future::ready(Ok(())))
-                .await?;
-
-                Ok(State::Collection(btree.into()))
+                let cast = |state: State| -> TCResult<Vec<Value>> {
+                    let value = Value::try_cast_from(state, |s| {
+                        TCError::bad_request("invalid BTree key", s)
+                    })?;
+
+                    value.try_cast_into(|v| TCError::bad_request("invalid BTree key", v))
+                };
+
+                let report =
+                    bulk::apply_all(keys, on_error, cast, |key| btree.insert(txn_id, key)).await?;
+
+                match on_error {
+                    OnError::Abort => Ok(State::Collection(btree.into())),
+                    OnError::Skip => Ok(report.into_state(btree.into())),
+                }
+            })
+        }))
+    }
+}
+
+struct VerifyHandler<'a> {
+    btree: &'a BTreeFile,
+}
+
+impl<'a> Handler<'a> for VerifyHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                key.expect_none()?;
+
+                let problems = self.btree.verify(*txn.id()).await?;
+                Ok(Value::from_iter(problems.into_iter().map(Value::String)).into())
             })
         }))
     }
 }
 
+impl<'a> From<&'a BTreeFile> for VerifyHandler<'a> {
+    fn from(btree: &'a BTreeFile) -> Self {
+        Self { btree }
+    }
+}
+
+struct RepairHandler<'a> {
+    btree: &'a BTreeFile,
+}
+
+impl<'a> Handler<'a> for RepairHandler<'a> {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, params| {
+            Box::pin(async move {
+                params.expect_empty()?;
+                txn.check_writable("repair")?;
+
+                let problems = self.btree.repair(*txn.id()).await?;
+                Ok(Value::from_iter(problems.into_iter().map(Value::String)).into())
+            })
+        }))
+    }
+}
+
+impl<'a> From<&'a BTreeFile> for RepairHandler<'a> {
+    fn from(btree: &'a BTreeFile) -> Self {
+        Self { btree }
+    }
+}
+
 struct StreamHandler<T> {
     btree: T,
 }
@@ -327,13 +429,22 @@ impl<T> From<T> for ReverseHandler<T> {
 
 impl Route for BTree {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
-        route(self, path)
+        match self {
+            Self::File(file) if path == ["verify"] || path == ["repair"] => file.route(path),
+            _ => route(self, path),
+        }
     }
 }
 
 impl Route for BTreeFile {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
-        route(self, path)
+        if path == ["verify"] {
+            Some(Box::new(VerifyHandler::from(self)))
+        } else if path == ["repair"] {
+            Some(Box::new(RepairHandler::from(self)))
+        } else {
+            route(self, path)
+        }
     }
 }
 
@@ -0,0 +1,179 @@
+//! Shared handling of invalid items in bulk operations like `copy_from`.
+
+use futures::future::{self, Future, FutureExt};
+use futures::stream::{Stream, StreamExt};
+use safecast::TryCastFrom;
+
+use tc_error::*;
+use tcgeneric::{label, Id, Label, Map, Tuple};
+
+use crate::collection::Collection;
+use crate::scalar::Value;
+use crate::state::State;
+
+const ABORT: Label = label("abort");
+const SKIP: Label = label("skip");
+
+/// The policy of a bulk operation toward an invalid item, given by its `on_error` parameter.
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum OnError {
+    /// Abort the whole operation at the first error.
+    #[default]
+    Abort,
+
+    /// Skip the invalid item and report it when the operation completes.
+    Skip,
+}
+
+impl TryCastFrom<Value> for OnError {
+    fn can_cast_from(value: &Value) -> bool {
+        Self::opt_cast_from(value.clone()).is_some()
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        if value.is_none() {
+            return Some(Self::default());
+        }
+
+        let id = Id::opt_cast_from(value)?;
+        if id == ABORT {
+            Some(Self::Abort)
+        } else if id == SKIP {
+            Some(Self::Skip)
+        } else {
+            None
+        }
+    }
+}
+
+/// The outcome of a bulk operation.
+#[derive(Default)]
+pub struct BulkReport {
+    applied: u64,
+    errors: Vec<(u64, TCError)>,
+}
+
+impl BulkReport {
+    /// Construct a summary [`State`] of this report and the resulting `collection`.
+    pub fn into_state(self, collection: Collection) -> State {
+        let skipped = self.errors.len() as u64;
+        let errors = self
+            .errors
+            .into_iter()
+            .map(|(i, cause)| {
+                let error = Value::Tuple(vec![i.into(), Value::String(cause.to_string())].into());
+                State::from(error)
+            })
+            .collect::<Tuple<State>>();
+
+        let summary = vec![
+            (label("collection").into(), State::Collection(collection)),
+            (label("applied").into(), self.applied.into()),
+            (label("skipped").into(), skipped.into()),
+            (label("errors").into(), errors.into()),
+        ];
+
+        State::Map(summary.into_iter().collect::<Map<State>>())
+    }
+}
+
+/// Validate each of the given `items` with `cast` and apply `op` to it, in parallel.
+///
+/// With [`OnError::Abort`] this returns the first error encountered. With [`OnError::Skip`]
+/// the index and cause of each invalid item (i.e. each `BadRequest` error returned by `cast` or
+/// `op`) are collected in the returned [`BulkReport`] instead. Any other error, including an
+/// error reading the `items` stream itself, always aborts the operation.
+pub async fn apply_all<I, T, S, C, F, Fut>(
+    items: S,
+    on_error: OnError,
+    cast: C,
+    op: F,
+) -> TCResult<BulkReport>
+where
+    S: Stream<Item = TCResult<I>> + Unpin,
+    C: Fn(I) -> TCResult<T>,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = TCResult<()>>,
+{
+    let mut results = items
+        .enumerate()
+        .map(|(i, item)| match item {
+            Ok(item) => {
+                let result = match cast(item) {
+                    Ok(item) => op(item).left_future(),
+                    Err(cause) => future::ready(Err(cause)).right_future(),
+                };
+
+                result
+                    .map(move |result| Ok((i as u64, result)))
+                    .left_future()
+            }
+            Err(cause) => future::ready(Err(cause)).right_future(),
+        })
+        .buffer_unordered(num_cpus::get());
+
+    let mut report = BulkReport::default();
+    while let Some(result) = results.next().await {
+        let (i, result) = result?;
+        match result {
+            Ok(()) => report.applied += 1,
+            Err(cause) if on_error == OnError::Skip && cause.code() == ErrorType::BadRequest => {
+                report.errors.push((i, cause))
+            }
+            Err(cause) => return Err(cause),
+        }
+    }
+
+    report.errors.sort_by_key(|(i, _)| *i);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn validate(item: i64) -> TCResult<i64> {
+        if item < 0 {
+            Err(TCError::bad_request("invalid item", item))
+        } else {
+            Ok(item)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skip_invalid() {
+        let items = stream::iter(vec![Ok(1), Ok(-1), Ok(2)]);
+        let report = apply_all(items, OnError::Skip, validate, |_| future::ready(Ok(())))
+            .await
+            .expect("report");
+
+        assert_eq!(report.applied, 2);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_skip_aborts_on_other_errors() {
+        let items = stream::iter(vec![Ok(1), Ok(2)]);
+        let result = apply_all(items, OnError::Skip, validate, |item| {
+            if item == 2 {
+                future::ready(Err(TCError::conflict()))
+            } else {
+                future::ready(Ok(()))
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+
+        // an error reading the source aborts the operation even if it's a bad request
+        let items = stream::iter(vec![
+            Ok(1),
+            Err(TCError::bad_request("invalid encoding", "source")),
+        ]);
+        let result = apply_all(items, OnError::Skip, validate, |_| future::ready(Ok(()))).await;
+        assert!(result.is_err());
+    }
+}
@@ -1,10 +1,15 @@
+use tc_error::*;
 use tcgeneric::{label, Label, PathSegment};
 
 use crate::collection::{Collection, CollectionType};
+use crate::scalar::Value;
+use crate::state::State;
+use crate::stream::{Archive, TCStream};
 
-use super::{Handler, Route};
+use super::{GetHandler, Handler, PostHandler, Route};
 
 mod btree;
+mod bulk;
 mod table;
 
 #[cfg(feature = "tensor")]
@@ -23,8 +28,106 @@ impl Route for CollectionType {
     }
 }
 
+struct DigestHandler<'a> {
+    collection: &'a Collection,
+}
+
+impl<'a> Handler<'a> for DigestHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                if key.is_some() {
+                    return Err(TCError::bad_request(
+                        "Collection digest does not accept a key",
+                        key,
+                    ));
+                }
+
+                let digest = self.collection.digest(txn).await?;
+                Ok(Value::String(hex::encode(digest)).into())
+            })
+        }))
+    }
+}
+
+struct EqualsHandler<'a> {
+    collection: &'a Collection,
+}
+
+impl<'a> Handler<'a> for EqualsHandler<'a> {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let other: Collection = params.require(&label("other").into())?;
+                params.expect_empty()?;
+
+                self.collection.equals(&other, txn).await.map(State::from)
+            })
+        }))
+    }
+}
+
+struct ExportHandler<'a> {
+    collection: &'a Collection,
+}
+
+impl<'a> Handler<'a> for ExportHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                if key.is_some() {
+                    return Err(TCError::bad_request(
+                        "Collection export does not accept a key",
+                        key,
+                    ));
+                }
+
+                let archive = Archive::Collection(Box::new(self.collection.clone()));
+                Ok(TCStream::from(archive).into())
+            })
+        }))
+    }
+}
+
+struct ImportHandler;
+
+impl<'a> Handler<'a> for ImportHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let archive: State = params.require(&label("archive").into())?;
+                params.expect_empty()?;
+                txn.check_writable("import")?;
+
+                Archive::decode(txn, archive).await.map(State::Collection)
+            })
+        }))
+    }
+}
+
 impl Route for Collection {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
+        if path.len() == 1 {
+            match path[0].as_str() {
+                "digest" => return Some(Box::new(DigestHandler { collection: self })),
+                "equals" => return Some(Box::new(EqualsHandler { collection: self })),
+                "export" => return Some(Box::new(ExportHandler { collection: self })),
+                _ => {}
+            }
+        }
+
         match self {
             Self::BTree(btree) => btree.route(path),
             Self::Table(table) => table.route(path),
@@ -44,6 +147,7 @@ impl Route for Static {
 
         match path[0].as_str() {
             "btree" => btree::Static.route(&path[1..]),
+            "import" if path.len() == 1 => Some(Box::new(ImportHandler)),
             "table" => table::Static.route(&path[1..]),
             #[cfg(feature = "tensor")]
             "tensor" => tensor::Static.route(&path[1..]),
@@ -1,24 +1,35 @@
 use std::iter::FromIterator;
 
-use futures::{future, StreamExt, TryFutureExt, TryStreamExt};
+use futures::{future, TryFutureExt, TryStreamExt};
 use safecast::*;
 
 use tc_btree::Node;
 use tc_error::*;
-use tc_table::{Bounds, ColumnBound, TableInstance, TableType};
+use tc_table::{
+    AggregateFn, Bounds, ColumnBound, ForeignKey, OnDelete, Reference, TableEvent, TableInstance,
+    TableSchema, TableType,
+};
 use tc_transact::fs::Dir;
-use tc_transact::Transaction;
+use tc_transact::{Transaction, TxnId};
 use tc_value::{Bound, Value};
-use tcgeneric::{label, Id, Map, PathSegment, Tuple};
+use tcgeneric::{label, Id, Label, Map, PathSegment, Tuple};
 
+use crate::closure::Closure;
 use crate::collection::{Collection, Table, TableIndex};
 use crate::fs;
-use crate::route::{DeleteHandler, GetHandler, Handler, PostHandler, PutHandler, Route};
-use crate::scalar::Scalar;
+use crate::route::{
+    DeleteHandler, GetHandler, Handler, PatchHandler, PostHandler, PutHandler, Route,
+};
+use crate::scalar::{OpDef, Scalar};
 use crate::state::State;
 use crate::stream::TCStream;
 use crate::txn::Txn;
 
+use super::bulk::{self, OnError};
+
+const MATCHES: Label = label("matches");
+const ONE_OF: Label = label("one_of");
+
 struct CopyHandler;
 
 impl<'a> Handler<'a> for CopyHandler {
@@ -34,7 +45,13 @@ impl<'a> Handler<'a> for CopyHandler {
                 })?;
 
                 let source: TCStream = params.require(&label("source").into())?;
+                let on_error: Value = params.or_default(&label("on_error").into())?;
+                let on_error = OnError::try_cast_from(on_error, |v| {
+                    TCError::bad_request("invalid error policy", v)
+                })?;
+
                 params.expect_empty()?;
+                txn.check_writable("copy_from")?;
 
                 let txn_id = *txn.id();
 
@@ -42,30 +59,70 @@ impl<'a> Handler<'a> for CopyHandler {
                 let table = TableIndex::create(&dir, schema, *txn.id()).await?;
 
                 let rows = source.into_stream(txn.clone()).await?;
-                rows.map(|r| {
-                    r.and_then(|state| {
-                        Value::try_cast_from(state, |s| {
-                            TCError::bad_request("invalid Table row", s)
-                        })
-                    })
-                })
-                .map(|r| {
-                    r.and_then(|value| {
-                        value.try_cast_into(|v| TCError::bad_request("invalid Table row", v))
-                    })
+                let cast = |state: State| -> TCResult<(Vec<Value>, Vec<Value>)> {
+                    let value = Value::try_cast_from(state, |s| {
+                        TCError::bad_request("invalid Table row", s)
+                    })?;
+
+                    let row =
+                        value.try_cast_into(|v| TCError::bad_request("invalid Table row", v))?;
+
+                    table.schema().primary().key_values_from_tuple(row)
+                };
+
+                let schema = table.schema();
+                let report = bulk::apply_all(rows, on_error, cast, |(key, values)| {
+                    let (schema, table) = (&schema, &table);
+                    async move {
+                        check_foreign_keys(txn, schema, &key, &values).await?;
+                        table.upsert(txn_id, key, values).await
+                    }
                 })
-                .map(|r| r.and_then(|row| table.schema().primary().key_values_from_tuple(row)))
-                .map_ok(|(key, values)| table.upsert(txn_id, key, values))
-                .try_buffer_unordered(num_cpus::get())
-                .try_fold((), |(), ()| future::ready(Ok(())))
                 .await?;
 
-                Ok(State::Collection(table.into()))
+                match on_error {
+                    OnError::Abort => Ok(State::Collection(table.into())),
+                    OnError::Skip => Ok(report.into_state(table.into())),
+                }
             })
         }))
     }
 }
 
+struct AggregateHandler<T> {
+    table: T,
+}
+
+impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn> + 'a> Handler<'a> for AggregateHandler<T> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let (column, func): (Id, Value) = key.try_cast_into(|v| {
+                    TCError::bad_request("expected a (column, function) pair, not", v)
+                })?;
+
+                let func = AggregateFn::try_cast_from(func, |v| {
+                    TCError::bad_request("unrecognized aggregate function", v)
+                })?;
+
+                self.table
+                    .aggregate(column, func, *txn.id())
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for AggregateHandler<T> {
+    fn from(table: T) -> Self {
+        Self { table }
+    }
+}
+
 struct CreateHandler;
 
 impl<'a> Handler<'a> for CreateHandler {
@@ -99,34 +156,88 @@ impl Route for TableType {
     }
 }
 
-struct ContainsHandler<T> {
-    table: T,
+struct ChangesHandler {
+    table: TableIndex,
 }
 
-impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn> + 'a> Handler<'a> for ContainsHandler<T> {
+impl<'a> Handler<'a> for ChangesHandler {
     fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let since = if key.is_none() {
+                    None
+                } else {
+                    key.to_string().parse().map(Some)?
+                };
+
+                let changes = TCStream::Changes(self.table, since);
+                Ok(State::from(changes))
+            })
+        }))
+    }
+
+    fn delete<'b>(self: Box<Self>) -> Option<DeleteHandler<'a, 'b>>
     where
         'b: 'a,
     {
         Some(Box::new(|txn, key| {
             Box::pin(async move {
-                let key = primary_key(key, &self.table)?;
-                let slice = self.table.slice(key)?;
+                let through: TxnId = key.to_string().parse()?;
+                self.table.compact_changes(*txn.id(), through).await?;
+                Ok(Value::None)
+            })
+        }))
+    }
+}
 
-                let mut rows = slice.rows(*txn.id()).await?;
-                rows.try_next()
-                    .map_ok(|row| row.is_some())
-                    .map_ok(Value::from)
-                    .map_ok(State::from)
-                    .await
+impl From<TableIndex> for ChangesHandler {
+    fn from(table: TableIndex) -> Self {
+        Self { table }
+    }
+}
+
+struct ContainsHandler {
+    table: Table,
+}
+
+impl<'a> Handler<'a> for ContainsHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let contains = match self.table {
+                    // look up the key in the primary index instead of slicing the whole table
+                    Table::Table(table) => {
+                        let key =
+                            key.try_cast_into(|v| TCError::bad_request("invalid Table key", v))?;
+
+                        table.contains_key(*txn.id(), key).await?
+                    }
+                    table => {
+                        let key = primary_key(key, &table)?;
+                        let slice = table.slice(key)?;
+
+                        let mut rows = slice.rows(*txn.id()).await?;
+                        rows.try_next().map_ok(|row| row.is_some()).await?
+                    }
+                };
+
+                Ok(Value::from(contains).into())
             })
         }))
     }
 }
 
-impl<T> From<T> for ContainsHandler<T> {
+impl<T: Into<Table>> From<T> for ContainsHandler {
     fn from(table: T) -> Self {
-        Self { table }
+        Self {
+            table: table.into(),
+        }
     }
 }
 
@@ -160,6 +271,49 @@ impl<T> From<T> for CountHandler<T> {
     }
 }
 
+struct DescribeHandler<T> {
+    table: T,
+}
+
+impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn> + 'a> Handler<'a> for DescribeHandler<T> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                if key.is_some() {
+                    return Err(TCError::bad_request(
+                        "Table::describe does not accept a key (call Table::slice first)",
+                        key,
+                    ));
+                }
+
+                let summary = self.table.describe(*txn.id()).await?;
+                let summary = summary
+                    .into_iter()
+                    .map(|(column, stats)| {
+                        let stats = Map::<Value>::try_cast_from(stats, |v| {
+                            TCError::internal(format!("invalid column summary: {}", v))
+                        })?;
+
+                        let stats = stats.into_iter().map(|(k, v)| (k, Scalar::Value(v)));
+                        Ok((column, Scalar::Map(stats.collect())))
+                    })
+                    .collect::<TCResult<Map<Scalar>>>()?;
+
+                Ok(State::from(summary))
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for DescribeHandler<T> {
+    fn from(table: T) -> Self {
+        Self { table }
+    }
+}
+
 struct GroupHandler<T> {
     table: T,
 }
@@ -176,7 +330,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn> + 'a> Handler<'a> for Gr
                 })?;
 
                 let grouped = self.table.group_by(columns)?;
-                Ok(Collection::Table(grouped.into()).into())
+                Ok(Collection::Table(Box::new(grouped.into())).into())
             })
         }))
     }
@@ -237,7 +391,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn> + 'a> Handler<'a> for Li
                     TCError::bad_request("limit must be a positive integer, not", v)
                 })?;
 
-                Ok(Collection::Table(self.table.limit(limit).into()).into())
+                Ok(Collection::Table(Box::new(self.table.limit(limit).into())).into())
             })
         }))
     }
@@ -270,7 +424,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn> + 'a> Handler<'a> for Or
                     return Err(TCError::bad_request("invalid column list to order by", key));
                 };
 
-                Ok(Collection::Table(ordered.into()).into())
+                Ok(Collection::Table(Box::new(ordered.into())).into())
             })
         }))
     }
@@ -311,7 +465,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>> Handler<'a> for TableHa
                         Err(TCError::not_found(key))
                     }
                 } else {
-                    Ok(Collection::Table(self.table.clone().into()).into())
+                    Ok(Collection::Table(Box::new(self.table.clone().into())).into())
                 }
             })
         }))
@@ -331,7 +485,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>> Handler<'a> for TableHa
 
                         return rows
                             .map_ok(|mut row| (row.drain(..key_len).collect(), row))
-                            .map_ok(|(key, values)| self.table.upsert(txn_id, key, values))
+                            .map_ok(|(key, values)| upsert(txn, self.table, key, values))
                             .try_buffer_unordered(num_cpus::get())
                             .try_fold((), |(), ()| future::ready(Ok(())))
                             .await;
@@ -340,8 +494,17 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>> Handler<'a> for TableHa
 
                 if values.matches::<Map<Value>>() {
                     let values = values.opt_cast_into().unwrap();
-                    let bounds = cast_into_bounds(Scalar::Value(key))?;
-                    self.table.clone().slice(bounds)?.update(&txn, values).await
+                    if key.is_none() {
+                        update(txn, self.table.clone(), values).await
+                    } else if key.matches::<Map<Value>>() {
+                        let bounds = cast_into_bounds(Scalar::Value(key))?;
+                        update(txn, self.table.clone().slice(bounds)?, values).await
+                    } else {
+                        Err(TCError::bad_request(
+                            "to update some columns of a single row use PATCH, not PUT with key",
+                            key,
+                        ))
+                    }
                 } else if values.matches::<Value>() {
                     let key = key
                         .try_cast_into(|k| TCError::bad_request("invalid key for Table row", k))?;
@@ -354,7 +517,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>> Handler<'a> for TableHa
                         TCError::bad_request("invalid values for Table row", v)
                     })?;
 
-                    self.table.upsert(*txn.id(), key, values).await
+                    upsert(txn, self.table, key, values).await
                 } else {
                     Err(TCError::bad_request("invalid row value", values))
                 }
@@ -362,6 +525,21 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>> Handler<'a> for TableHa
         }))
     }
 
+    fn patch<'b>(self: Box<Self>) -> Option<PatchHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key, values| {
+            Box::pin(async move {
+                let values = values.try_cast_into(|s| {
+                    TCError::bad_request("expected a map of column values, not", s)
+                })?;
+
+                patch(txn, self.table, key, values).await
+            })
+        }))
+    }
+
     fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
         'b: 'a,
@@ -375,7 +553,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>> Handler<'a> for TableHa
                 let bounds = cast_into_bounds(bounds)?;
 
                 let table = self.table.clone().slice(bounds).map(|slice| slice.into())?;
-                Ok(Collection::Table(table).into())
+                Ok(Collection::from(table).into())
             })
         }))
     }
@@ -386,17 +564,125 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>> Handler<'a> for TableHa
     {
         Some(Box::new(|txn, key| {
             Box::pin(async move {
-                if key.is_some() {
-                    let key = primary_key(key, self.table)?;
-                    self.table.clone().slice(key)?.delete(*txn.id()).await
+                let deleted = if key.is_none() {
+                    delete(txn, self.table.clone()).await?
+                } else if key.matches::<Map<Value>>() {
+                    let bounds = cast_into_bounds(Scalar::Value(key))?;
+                    delete(txn, self.table.clone().slice(bounds)?).await?
                 } else {
-                    self.table.delete(*txn.id()).await
+                    let key = primary_key(key, self.table)?;
+                    let slice = self.table.clone().slice(key)?;
+                    delete(txn, slice).await?
+                };
+
+                Ok(Value::from(deleted))
+            })
+        }))
+    }
+}
+
+struct TriggerHandler {
+    table: TableIndex,
+}
+
+impl<'a> Handler<'a> for TriggerHandler {
+    fn put<'b>(self: Box<Self>) -> Option<PutHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key, value| {
+            Box::pin(async move {
+                let event = TableEvent::try_cast_from(key, |v| {
+                    TCError::bad_request("invalid Table trigger event", v)
+                })?;
+
+                let op: Closure = value.try_cast_into(|s| {
+                    TCError::bad_request("a Table trigger must be an Op, not", s)
+                })?;
+
+                match op.clone().into_inner() {
+                    (_, OpDef::Post(..)) => {}
+                    (_, other) => {
+                        return Err(TCError::bad_request(
+                            "a Table trigger requires a POST Op, not",
+                            other,
+                        ))
+                    }
                 }
+
+                self.table.add_trigger(txn, event, &op).await
             })
         }))
     }
 }
 
+impl From<TableIndex> for TriggerHandler {
+    fn from(table: TableIndex) -> Self {
+        Self { table }
+    }
+}
+
+struct ProbeHandler {
+    table: TableIndex,
+}
+
+impl<'a> Handler<'a> for ProbeHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, params| {
+            Box::pin(async move {
+                let bounds = Scalar::try_cast_from(State::Map(params), |s| {
+                    TCError::bad_request("invalid Table bounds", s)
+                })?;
+
+                let bounds = cast_into_bounds(bounds)?;
+
+                self.table
+                    .probe(*txn.id(), bounds)
+                    .map_ok(Value::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl From<TableIndex> for ProbeHandler {
+    fn from(table: TableIndex) -> Self {
+        Self { table }
+    }
+}
+
+struct PurgeExpiredHandler {
+    table: TableIndex,
+}
+
+impl<'a> Handler<'a> for PurgeExpiredHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, params| {
+            Box::pin(async move {
+                params.expect_empty()?;
+                txn.check_writable("purge_expired")?;
+
+                let expired = self.table.expired(txn.id().time())?;
+                let deleted = delete(txn, expired).await?;
+                Ok(Value::from(deleted).into())
+            })
+        }))
+    }
+}
+
+impl From<TableIndex> for PurgeExpiredHandler {
+    fn from(table: TableIndex) -> Self {
+        Self { table }
+    }
+}
+
 struct SelectHandler<T> {
     table: T,
 }
@@ -411,7 +697,7 @@ impl<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn> + 'a> Handler<'a> for Se
                 let columns =
                     key.try_cast_into(|v| TCError::bad_request("invalid column list", v))?;
 
-                Ok(Collection::Table(self.table.select(columns)?.into()).into())
+                Ok(Collection::Table(Box::new(self.table.select(columns)?.into())).into())
             })
         }))
     }
@@ -463,13 +749,37 @@ impl<T> From<T> for UpdateHandler<T> {
 
 impl Route for Table {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
-        route(self, path)
+        match self {
+            Self::Table(table) if path == ["changes"] => {
+                Some(Box::new(ChangesHandler::from(table.clone())))
+            }
+            Self::Table(table) if path == ["probe"] => {
+                Some(Box::new(ProbeHandler::from(table.clone())))
+            }
+            Self::Table(table) if path == ["trigger"] => {
+                Some(Box::new(TriggerHandler::from(table.clone())))
+            }
+            Self::Table(table) if path == ["purge_expired"] => {
+                Some(Box::new(PurgeExpiredHandler::from(table.clone())))
+            }
+            _ => route(self, path),
+        }
     }
 }
 
 impl Route for TableIndex {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
-        route(self, path)
+        if path == ["changes"] {
+            Some(Box::new(ChangesHandler::from(self.clone())))
+        } else if path == ["probe"] {
+            Some(Box::new(ProbeHandler::from(self.clone())))
+        } else if path == ["trigger"] {
+            Some(Box::new(TriggerHandler::from(self.clone())))
+        } else if path == ["purge_expired"] {
+            Some(Box::new(PurgeExpiredHandler::from(self.clone())))
+        } else {
+            route(self, path)
+        }
     }
 }
 
@@ -488,8 +798,10 @@ fn route<'a, T: TableInstance<fs::File<Node>, fs::Dir, Txn>>(
         let table = table.clone();
 
         match path[0].as_str() {
+            "aggregate" => Some(Box::new(AggregateHandler::from(table))),
             "contains" => Some(Box::new(ContainsHandler::from(table))),
             "count" => Some(Box::new(CountHandler::from(table))),
+            "describe" => Some(Box::new(DescribeHandler::from(table))),
             "limit" => Some(Box::new(LimitHandler::from(table))),
             "group" => Some(Box::new(GroupHandler::from(table))),
             "order" => Some(Box::new(OrderHandler::from(table))),
@@ -521,14 +833,23 @@ fn cast_into_bounds(scalar: Scalar) -> TCResult<Bounds> {
         return Ok(Bounds::default());
     }
 
-    let scalar = Map::<Value>::try_cast_from(scalar, |s| {
-        TCError::bad_request("invalid selection bounds for Table", s)
-    })?;
+    let scalar = match scalar {
+        Scalar::Value(value) => Map::<Value>::try_cast_from(value, |v| {
+            TCError::bad_request("invalid selection bounds for Table", v)
+        })?,
+        scalar => Map::<Value>::try_cast_from(scalar, |s| {
+            TCError::bad_request("invalid selection bounds for Table", s)
+        })?,
+    };
 
     scalar
         .into_iter()
         .map(|(col_name, bound)| {
-            if bound.matches::<(Bound, Bound)>()
+            if let Some(pattern) = regex_pattern(&bound) {
+                ColumnBound::matches(&pattern)
+            } else if let Some(values) = one_of(&bound) {
+                Ok(ColumnBound::OneOf(values))
+            } else if bound.matches::<(Bound, Bound)>()
                 || bound.matches::<(Bound, Value)>()
                 || bound.matches::<(Value, Bound)>()
             {
@@ -543,7 +864,179 @@ fn cast_into_bounds(scalar: Scalar) -> TCResult<Bounds> {
         .collect()
 }
 
+/// Return the pattern of a column bound of the form `("matches", <regex>)`, if `bound` is one.
+fn regex_pattern(bound: &Value) -> Option<String> {
+    let (op, pattern): (Id, String) = bound.clone().opt_cast_into()?;
+    if op == MATCHES {
+        Some(pattern)
+    } else {
+        None
+    }
+}
+
+/// Return the values of a column bound of the form `("one_of", [<value>...])`, if `bound` is one.
+fn one_of(bound: &Value) -> Option<Vec<Value>> {
+    let (op, values): (Id, Vec<Value>) = bound.clone().opt_cast_into()?;
+    if op == ONE_OF {
+        Some(values)
+    } else {
+        None
+    }
+}
+
 #[inline]
+async fn upsert<T: TableInstance<fs::File<Node>, fs::Dir, Txn>>(
+    txn: &Txn,
+    table: &T,
+    key: Vec<Value>,
+    values: Vec<Value>,
+) -> TCResult<()> {
+    check_foreign_keys(txn, &table.schema(), &key, &values).await?;
+    table.upsert_triggered::<Closure>(txn, key, values).await
+}
+
+async fn delete<T: TableInstance<fs::File<Node>, fs::Dir, Txn>>(
+    txn: &Txn,
+    table: T,
+) -> TCResult<u64> {
+    for reference in table.references() {
+        delete_references(txn, &table, &reference).await?;
+    }
+
+    table.delete_triggered::<Closure>(txn).await
+}
+
+/// Enforce the given [`Reference`] to each row of `table`, before those rows are deleted.
+///
+/// Returns a "bad request" error if a row is referenced under [`OnDelete::Restrict`],
+/// and deletes the referencing rows under [`OnDelete::Cascade`].
+async fn delete_references<T: TableInstance<fs::File<Node>, fs::Dir, Txn>>(
+    txn: &Txn,
+    table: &T,
+    reference: &Reference<fs::File<Node>, fs::Dir, Txn>,
+) -> TCResult<()> {
+    let txn_id = *txn.id();
+    let fk = reference.foreign_key();
+
+    let mut values = table
+        .clone()
+        .select(vec![fk.foreign_column().clone()])?
+        .rows(txn_id)
+        .await?;
+
+    while let Some(mut row) = values.try_next().await? {
+        let value = row.pop().ok_or_else(|| {
+            TCError::internal(format!("missing value for {}", fk.foreign_column()))
+        })?;
+
+        match fk.on_delete() {
+            OnDelete::Restrict => {
+                // the schema guarantees that the referencing column is indexed,
+                // so this only checks the index for a match instead of reading the rows
+                let bounds = Bounds::from_iter(vec![(fk.column().clone(), value.clone())]);
+
+                if reference.table().probe(txn_id, bounds).await? {
+                    return Err(TCError::bad_request(
+                        format!("cannot delete {} since it is referenced by", value),
+                        reference.path(),
+                    ));
+                }
+            }
+            OnDelete::Cascade => {
+                // delete through the referencing chain so that the deletes are recorded
+                let link = txn.link(reference.path().clone());
+                let bounds =
+                    Value::Tuple(vec![Value::cast_from((fk.column().clone(), value))].into());
+                txn.delete(link, bounds).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn patch<T: TableInstance<fs::File<Node>, fs::Dir, Txn>>(
+    txn: &Txn,
+    table: &T,
+    key: Value,
+    values: Map<Value>,
+) -> TCResult<()> {
+    let bounds = primary_key(key.clone(), table)?;
+    let slice = table.clone().slice(bounds)?;
+    if slice.clone().count(*txn.id()).await? == 0 {
+        return Err(TCError::not_found(key));
+    }
+
+    update(txn, slice, values).await
+}
+
+async fn update<T: TableInstance<fs::File<Node>, fs::Dir, Txn>>(
+    txn: &Txn,
+    table: T,
+    values: Map<Value>,
+) -> TCResult<()> {
+    for fk in table.schema().foreign_keys() {
+        if let Some(value) = values.get(fk.column()) {
+            check_foreign_key(txn, fk, value).await?;
+        }
+    }
+
+    table.update(txn, values).await
+}
+
+/// Check each foreign key of the given `schema` against the row with the given `key` and
+/// `values`, before writing it by any path (e.g. a PUT or a bulk copy).
+async fn check_foreign_keys(
+    txn: &Txn,
+    schema: &TableSchema,
+    key: &[Value],
+    values: &[Value],
+) -> TCResult<()> {
+    for fk in schema.foreign_keys() {
+        let column = schema
+            .primary()
+            .column_names()
+            .position(|name| name == fk.column())
+            .ok_or_else(|| TCError::internal(format!("invalid foreign key {}", fk)))?;
+
+        let value = if column < key.len() {
+            &key[column]
+        } else {
+            values
+                .get(column - key.len())
+                .ok_or_else(|| TCError::bad_request("missing value for column", fk.column()))?
+        };
+
+        check_foreign_key(txn, fk, value).await?;
+    }
+
+    Ok(())
+}
+
+async fn check_foreign_key(txn: &Txn, fk: &ForeignKey, value: &Value) -> TCResult<()> {
+    if value.is_none() {
+        return Ok(());
+    }
+
+    // the cluster which hosts this table has already checked that the foreign key
+    // references the primary key of another table in the same cluster
+    let link = txn.link(fk.table().clone().append(label("contains")));
+    let exists = txn
+        .get(link, Value::Tuple(vec![value.clone()].into()))
+        .await?;
+
+    if bool::try_cast_from(exists, |s| {
+        TCError::bad_request("expected a boolean, not", s)
+    })? {
+        Ok(())
+    } else {
+        Err(TCError::bad_request(
+            format!("{} violates foreign key", value),
+            fk,
+        ))
+    }
+}
+
 fn primary_key<T: TableInstance<fs::File<Node>, fs::Dir, Txn>>(
     key: Value,
     table: &T,
@@ -6,20 +6,101 @@ use safecast::{Match, TryCastFrom, TryCastInto};
 use tc_btree::Node;
 use tc_error::*;
 use tc_tensor::*;
-use tc_transact::fs::Dir;
+use tc_transact::fs::{CopyFrom, Dir};
 use tc_transact::Transaction;
-use tcgeneric::{label, PathSegment, TCBoxTryFuture, Tuple};
+use tcgeneric::{label, NativeClass, PathSegment, TCBoxTryFuture, TCPathBuf, Tuple};
 
-use crate::collection::{Collection, DenseTensor, DenseTensorFile, Tensor};
+use crate::collection::{
+    Collection, DenseTensor, DenseTensorFile, SparseTable, SparseTensor, Tensor,
+};
 use crate::fs;
 use crate::route::{GetHandler, PostHandler, PutHandler};
-use crate::scalar::{Bound, Number, NumberClass, Range, Value};
-use crate::state::State;
+use crate::scalar::{
+    Bound, Float, FloatType, Number, NumberClass, NumberInstance, NumberType, Range, Value,
+    ValueType,
+};
+use crate::state::{Lazy, State, StateType};
 use crate::stream::TCStream;
 use crate::txn::Txn;
 
 use super::{Handler, Route};
 
+struct AsTableHandler {
+    tensor: SparseTensor<SparseAccessor<fs::File<Array>, fs::File<Node>, fs::Dir, Txn>>,
+}
+
+impl<'a> Handler<'a> for AsTableHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                key.expect_none()?;
+
+                let table = match self.tensor.into_inner() {
+                    SparseAccessor::Table(table) => SparseTensor::from(table).as_table()?,
+                    other => {
+                        let txn_id = *txn.id();
+                        let dir = txn.context().create_dir_tmp(txn_id).await?;
+                        let copy: SparseTensor<SparseTable> =
+                            CopyFrom::copy_from(SparseTensor::from(other), dir, txn).await?;
+
+                        copy.as_table()?
+                    }
+                };
+
+                Ok(Collection::from(table).into())
+            })
+        }))
+    }
+}
+
+impl From<SparseTensor<SparseAccessor<fs::File<Array>, fs::File<Node>, fs::Dir, Txn>>>
+    for AsTableHandler
+{
+    fn from(
+        tensor: SparseTensor<SparseAccessor<fs::File<Array>, fs::File<Node>, fs::Dir, Txn>>,
+    ) -> Self {
+        Self { tensor }
+    }
+}
+
+struct ConcatenateHandler;
+
+impl<'a> Handler<'a> for ConcatenateHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let tensors: Vec<Tensor> = params.require(&label("tensors").into())?;
+                let axis: Value = params.or_default(&label("axis").into())?;
+                let axis = cast_axis(axis)?;
+                params.expect_empty()?;
+
+                let tensors = tensors
+                    .into_iter()
+                    .map(|tensor| match tensor {
+                        Tensor::Sparse(sparse) => Ok(sparse),
+                        other => Err(TCError::bad_request(
+                            "sparse concatenation requires sparse Tensors, not",
+                            other,
+                        )),
+                    })
+                    .collect::<TCResult<Vec<_>>>()?;
+
+                sparse_concatenate(tensors, axis, txn.clone())
+                    .map_ok(Tensor::from)
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
 struct ConstantHandler;
 
 impl<'a> Handler<'a> for ConstantHandler {
@@ -57,6 +138,7 @@ impl<'a> Handler<'a> for CopyDenseHandler {
 
                 let source: TCStream = params.require(&label("source").into())?;
                 params.expect_empty()?;
+                txn.check_writable("copy_from")?;
 
                 let elements = source.into_stream(txn.clone()).await?;
                 let elements = elements.map(|r| {
@@ -94,6 +176,7 @@ impl<'a> Handler<'a> for CopySparseHandler {
 
                 let source: TCStream = params.require(&label("source").into())?;
                 params.expect_empty()?;
+                txn.check_writable("copy_from")?;
 
                 let elements = source.into_stream(txn.clone()).await?;
 
@@ -189,171 +272,1146 @@ impl<'a> Handler<'a> for EinsumHandler {
     }
 }
 
-struct ExpandHandler<T> {
-    tensor: T,
+struct CastHandler<T> {
+    tensor: T,
+}
+
+impl<'a, T> Handler<'a> for CastHandler<T>
+where
+    T: TensorTransform + Send + 'a,
+    Tensor: From<T::Cast>,
+{
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let dtype: Value = params.require(&label("dtype").into())?;
+                let dtype = TCPathBuf::try_cast_from(dtype, |v| {
+                    TCError::bad_request("invalid Tensor data type", v)
+                })?;
+
+                let dtype = match ValueType::from_path(&dtype) {
+                    Some(ValueType::Number(dtype)) => dtype,
+                    _ => return Err(TCError::bad_request("invalid Tensor data type", dtype)),
+                };
+
+                let saturate: Value = params.or_default(&label("saturate").into())?;
+                let saturate = if saturate.is_none() {
+                    false
+                } else {
+                    saturate.try_cast_into(|v| {
+                        TCError::bad_request("invalid saturating cast flag", v)
+                    })?
+                };
+
+                params.expect_empty()?;
+
+                let cast = if saturate {
+                    self.tensor.cast_into_saturating(dtype)
+                } else {
+                    self.tensor.cast_into(dtype)
+                };
+
+                cast.map(Tensor::from)
+                    .map(Collection::from)
+                    .map(State::from)
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for CastHandler<T> {
+    fn from(tensor: T) -> Self {
+        Self { tensor }
+    }
+}
+
+struct ExpandHandler<T> {
+    tensor: T,
+}
+
+impl<'a, T> Handler<'a> for ExpandHandler<T>
+where
+    T: TensorTransform + Send + 'a,
+    Tensor: From<T::Expand>,
+{
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let axis = key.try_cast_into(|v| TCError::bad_request("invalid tensor axis", v))?;
+
+                self.tensor
+                    .expand_dims(axis)
+                    .map(Tensor::from)
+                    .map(Collection::from)
+                    .map(State::from)
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for ExpandHandler<T> {
+    fn from(tensor: T) -> Self {
+        Self { tensor }
+    }
+}
+
+struct OuterHandler;
+
+impl<'a> Handler<'a> for OuterHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let a: Tensor = params.require(&label("a").into())?;
+                let b: Tensor = params.require(&label("b").into())?;
+                params.expect_empty()?;
+
+                match (a, b) {
+                    (Tensor::Sparse(a), Tensor::Sparse(b)) => {
+                        SparseTensor::outer(a, b, txn.clone())
+                            .map_ok(Tensor::from)
+                            .map_ok(Collection::from)
+                            .map_ok(State::from)
+                            .await
+                    }
+                    (a, b) => Err(TCError::bad_request(
+                        "sparse outer product requires two sparse Tensors, not",
+                        format!("{} and {}", a, b),
+                    )),
+                }
+            })
+        }))
+    }
+}
+
+struct SparseMatmulHandler;
+
+impl<'a> Handler<'a> for SparseMatmulHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let a: Tensor = params.require(&label("a").into())?;
+                let b: Tensor = params.require(&label("b").into())?;
+                params.expect_empty()?;
+
+                match (a, b) {
+                    (Tensor::Sparse(a), Tensor::Sparse(b)) => {
+                        sparse_matmul(a, b, txn.clone())
+                            .map_ok(Tensor::from)
+                            .map_ok(Collection::from)
+                            .map_ok(State::from)
+                            .await
+                    }
+                    (a, b) => Err(TCError::bad_request(
+                        "sparse matrix multiplication requires two sparse Tensors, not",
+                        format!("{} and {}", a, b),
+                    )),
+                }
+            })
+        }))
+    }
+}
+
+struct FromTableHandler;
+
+impl<'a> Handler<'a> for FromTableHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let schema: Value = params.require(&label("schema").into())?;
+                let schema: Schema =
+                    schema.try_cast_into(|v| TCError::bad_request("invalid Tensor schema", v))?;
+
+                let table: Collection = params.require(&label("table").into())?;
+                params.expect_empty()?;
+
+                match table {
+                    Collection::Table(table) => {
+                        SparseTensor::from_table(*table, schema, txn.clone())
+                            .map_ok(Tensor::from)
+                            .map_ok(Collection::from)
+                            .map_ok(State::from)
+                            .await
+                    }
+                    other => Err(TCError::bad_request(
+                        "a sparse Tensor can only be constructed from a Table, not",
+                        other,
+                    )),
+                }
+            })
+        }))
+    }
+}
+
+struct RangeHandler;
+
+impl<'a> Handler<'a> for RangeHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                if key.matches::<(Vec<u64>, Number, Number)>() {
+                    let (shape, start, stop): (Vec<u64>, Number, Number) =
+                        key.opt_cast_into().unwrap();
+
+                    let file = create_file(&txn).await?;
+
+                    DenseTensor::range(file, *txn.id(), shape, start, stop)
+                        .map_ok(Tensor::from)
+                        .map_ok(Collection::from)
+                        .map_ok(State::from)
+                        .await
+                } else {
+                    Err(TCError::bad_request("invalid schema for range tensor", key))
+                }
+            })
+        }))
+    }
+}
+
+struct TransposeHandler<T> {
+    tensor: T,
+}
+
+impl<'a, T> Handler<'a> for TransposeHandler<T>
+where
+    T: TensorTransform + Send + 'a,
+    Tensor: From<T::Transpose>,
+{
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let transpose = if key.is_none() {
+                    self.tensor.transpose(None)
+                } else {
+                    let permutation = key.try_cast_into(|v| {
+                        TCError::bad_request("invalid permutation for transpose", v)
+                    })?;
+
+                    self.tensor.transpose(Some(permutation))
+                };
+
+                transpose
+                    .map(Tensor::from)
+                    .map(Collection::from)
+                    .map(State::from)
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for TransposeHandler<T> {
+    fn from(tensor: T) -> Self {
+        Self { tensor }
+    }
+}
+
+impl Route for TensorType {
+    fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
+        if path.is_empty() {
+            return Some(Box::new(CreateHandler { class: *self }));
+        } else if path.len() != 1 {
+            return None;
+        }
+
+        match self {
+            Self::Dense => match path[0].as_str() {
+                "copy_from" => Some(Box::new(CopyDenseHandler)),
+                "constant" => Some(Box::new(ConstantHandler)),
+                "range" => Some(Box::new(RangeHandler)),
+                _ => None,
+            },
+            Self::Sparse => match path[0].as_str() {
+                "copy_from" => Some(Box::new(CopySparseHandler)),
+                "concatenate" => Some(Box::new(ConcatenateHandler)),
+                "from_table" => Some(Box::new(FromTableHandler)),
+                "matmul" => Some(Box::new(SparseMatmulHandler)),
+                "outer" => Some(Box::new(OuterHandler)),
+                _ => None,
+            },
+        }
+    }
+}
+
+struct DualHandler {
+    tensor: Tensor,
+    op: fn(Tensor, Tensor) -> TCResult<Tensor>,
+}
+
+impl DualHandler {
+    fn new<T>(tensor: T, op: fn(Tensor, Tensor) -> TCResult<Tensor>) -> Self
+    where
+        Tensor: From<T>,
+    {
+        Self {
+            tensor: tensor.into(),
+            op,
+        }
+    }
+}
+
+impl<'a> Handler<'a> for DualHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let l = self.tensor;
+                let r: Tensor = params.require(&label("r").into())?;
+                params.expect_empty()?;
+
+                if l.shape() == r.shape() {
+                    debug!("tensor dual op with shapes {} {}", l.shape(), r.shape());
+                    (self.op)(l, r).map(Collection::from).map(State::from)
+                } else {
+                    let (l, r) = broadcast(l, r)?;
+                    debug!("tensor dual op with shapes {} {}", l.shape(), r.shape());
+                    (self.op)(l, r).map(Collection::from).map(State::from)
+                }
+            })
+        }))
+    }
+}
+
+struct MathHandler {
+    tensor: Tensor,
+    op: fn(Tensor, Tensor, NonFinite) -> TCResult<Tensor>,
+    checked: Option<fn(Tensor, Tensor, NonFinite) -> TCResult<Tensor>>,
+}
+
+impl MathHandler {
+    fn new<T>(
+        tensor: T,
+        op: fn(Tensor, Tensor, NonFinite) -> TCResult<Tensor>,
+        checked: Option<fn(Tensor, Tensor, NonFinite) -> TCResult<Tensor>>,
+    ) -> Self
+    where
+        Tensor: From<T>,
+    {
+        Self {
+            tensor: tensor.into(),
+            op,
+            checked,
+        }
+    }
+}
+
+impl<'a> Handler<'a> for MathHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let l = self.tensor;
+                let r: Tensor = params.require(&label("r").into())?;
+                let non_finite: Value = params.or_default(&label("non_finite").into())?;
+                let non_finite = NonFinite::try_cast_from(non_finite, |v| {
+                    TCError::bad_request("invalid non-finite value policy", v)
+                })?;
+
+                let checked: Value = params.or_default(&label("checked").into())?;
+                let checked = if checked.is_none() {
+                    false
+                } else {
+                    checked.try_cast_into(|v| {
+                        TCError::bad_request("invalid checked arithmetic flag", v)
+                    })?
+                };
+
+                params.expect_empty()?;
+
+                let (l, r) = if l.shape() == r.shape() {
+                    (l, r)
+                } else {
+                    broadcast(l, r)?
+                };
+
+                let op = if checked {
+                    self.checked.ok_or_else(|| {
+                        TCError::unsupported("this tensor math operation has no checked mode")
+                    })?
+                } else {
+                    self.op
+                };
+
+                debug!(
+                    "tensor math op with shapes {} {} ({} non-finite values)",
+                    l.shape(),
+                    r.shape(),
+                    non_finite
+                );
+
+                op(l, r, non_finite).map(Collection::from).map(State::from)
+            })
+        }))
+    }
+}
+
+struct ClipByNormHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for ClipByNormHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let max_norm: Number =
+                    key.try_cast_into(|v| TCError::bad_request("invalid max norm", v))?;
+
+                let max_norm = match max_norm.into_type(NumberType::Float(FloatType::F64)) {
+                    Number::Float(Float::F64(max_norm)) => max_norm,
+                    other => return Err(TCError::bad_request("invalid max norm", other)),
+                };
+
+                self.tensor
+                    .clip_by_norm(max_norm, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for ClipByNormHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct NormalizeHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for NormalizeHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let axis: Value = params.or_default(&label("axis").into())?;
+                let norm: Value = params.or_default(&label("norm").into())?;
+                let strict: Value = params.or_default(&label("strict").into())?;
+                params.expect_empty()?;
+
+                let axis = cast_axis(axis)?;
+                let norm = NormKind::try_cast_from(norm, |v| {
+                    TCError::bad_request("invalid norm (expected l1, l2, or max)", v)
+                })?;
+
+                let strict = if strict.is_none() {
+                    false
+                } else {
+                    strict.try_cast_into(|v| TCError::bad_request("invalid value for strict", v))?
+                };
+
+                self.tensor
+                    .normalize(axis, norm, strict, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for NormalizeHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct OneHotHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for OneHotHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let depth = key.try_cast_into(|v| TCError::bad_request("invalid depth", v))?;
+
+                self.tensor
+                    .one_hot(depth, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for OneHotHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct Conv2dHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for Conv2dHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let kernel: Tensor = params.require(&label("kernel").into())?;
+                let stride: Value = params.or_default(&label("stride").into())?;
+                let padding: Value = params.or_default(&label("padding").into())?;
+                params.expect_empty()?;
+
+                let stride = match stride {
+                    Value::None => (1, 1),
+                    stride => {
+                        stride.try_cast_into(|v| TCError::bad_request("invalid stride", v))?
+                    }
+                };
+
+                let padding = match padding {
+                    Value::None => (0, 0),
+                    padding => {
+                        padding.try_cast_into(|v| TCError::bad_request("invalid padding", v))?
+                    }
+                };
+
+                self.tensor
+                    .conv2d(kernel, stride, padding, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for Conv2dHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct NanToNumHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for NanToNumHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let nan: Value = params.or_default(&label("nan").into())?;
+                let posinf: Value = params.or_default(&label("posinf").into())?;
+                let neginf: Value = params.or_default(&label("neginf").into())?;
+                params.expect_empty()?;
+
+                // by default, replace infinities with the extreme finite values of the data type
+                let dtype = self.tensor.dtype();
+                let (max, min) = match dtype {
+                    NumberType::Float(FloatType::F32) => (f32::MAX.into(), f32::MIN.into()),
+                    NumberType::Complex(_) | NumberType::Float(_) => {
+                        (f64::MAX.into(), f64::MIN.into())
+                    }
+                    _ => (dtype.zero(), dtype.zero()),
+                };
+
+                let substitute = |value: Value, default: Number| match value {
+                    Value::None => Ok(default),
+                    value => value.try_cast_into(|v| {
+                        TCError::bad_request("invalid substitute for a non-finite value", v)
+                    }),
+                };
+
+                let nan = substitute(nan, dtype.zero())?;
+                let posinf = substitute(posinf, max)?;
+                let neginf = substitute(neginf, min)?;
+
+                self.tensor
+                    .nan_to_num(nan, posinf, neginf, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for NanToNumHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct DotHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for DotHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let r: Tensor = params.require(&label("r").into())?;
+                params.expect_empty()?;
+
+                self.tensor
+                    .dot(r, txn.clone())
+                    .map_ok(Value::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for DotHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct KronHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for KronHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let r: Tensor = params.require(&label("r").into())?;
+                params.expect_empty()?;
+
+                self.tensor
+                    .kron(r, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for KronHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct MatmulHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for MatmulHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let r: Tensor = params.require(&label("r").into())?;
+                params.expect_empty()?;
+
+                // the product is always dense, so its class is known without computing it
+                let class = StateType::Collection(TensorType::Dense.into());
+                let tensor = self.tensor;
+                let product = Lazy::new(class, move |txn: Txn| -> TCBoxTryFuture<State> {
+                    Box::pin(async move {
+                        tensor
+                            .matmul(r, txn)
+                            .map_ok(Collection::from)
+                            .map_ok(State::from)
+                            .await
+                    })
+                });
+
+                Ok(State::from(product))
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for MatmulHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct FlattenHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for FlattenHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                if key.is_some() {
+                    return Err(TCError::bad_request(
+                        "Tensor::flatten does not accept a key",
+                        key,
+                    ));
+                }
+
+                self.tensor
+                    .flatten(txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for FlattenHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct RollHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for RollHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let shift: Value = params.require(&label("shift").into())?;
+                let axis: Value = params.or_default(&label("axis").into())?;
+                params.expect_empty()?;
+
+                let shift = shift.try_cast_into(|v| TCError::bad_request("invalid shift", v))?;
+
+                let axis = cast_axis(axis)?;
+
+                self.tensor
+                    .roll(shift, axis, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for RollHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct SqueezeHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for SqueezeHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let axis = match key {
+                    Value::None => None,
+                    axis => Some(
+                        axis.try_cast_into(|v| TCError::bad_request("invalid tensor axis", v))?,
+                    ),
+                };
+
+                self.tensor
+                    .squeeze(axis, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for SqueezeHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct NnzHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for NnzHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                if key.is_some() {
+                    return Err(TCError::bad_request(
+                        "Tensor::nnz does not accept a key",
+                        key,
+                    ));
+                }
+
+                self.tensor
+                    .nnz(txn.clone())
+                    .map_ok(Value::from)
+                    .map_ok(State::from)
+                    .await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for NnzHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct FourierHandler {
+    tensor: Tensor,
+    inverse: bool,
+}
+
+impl FourierHandler {
+    fn new<T>(tensor: T, inverse: bool) -> Self
+    where
+        Tensor: From<T>,
+    {
+        Self {
+            tensor: tensor.into(),
+            inverse,
+        }
+    }
+}
+
+impl<'a> Handler<'a> for FourierHandler {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                if key.is_some() {
+                    return Err(TCError::bad_request(
+                        "the Fourier transform does not accept a key",
+                        key,
+                    ));
+                }
+
+                let txn = txn.clone();
+                let transformed = if self.inverse {
+                    self.tensor.ifft(txn).await?
+                } else {
+                    self.tensor.fft(txn).await?
+                };
+
+                Ok(State::from(Collection::from(transformed)))
+            })
+        }))
+    }
+}
+
+struct SplitHandler {
+    tensor: Tensor,
 }
 
-impl<'a, T> Handler<'a> for ExpandHandler<T>
-where
-    T: TensorTransform + Send + 'a,
-    Tensor: From<T::Expand>,
-{
-    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+impl<'a> Handler<'a> for SplitHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
         'b: 'a,
     {
-        Some(Box::new(|_txn, key| {
+        Some(Box::new(|_txn, mut params| {
             Box::pin(async move {
-                let axis = key.try_cast_into(|v| TCError::bad_request("invalid tensor axis", v))?;
+                let sizes: Value = params.require(&label("sizes").into())?;
+                let axis: Value = params.or_default(&label("axis").into())?;
+                params.expect_empty()?;
 
-                self.tensor
-                    .expand_dims(axis)
-                    .map(Tensor::from)
-                    .map(Collection::from)
-                    .map(State::from)
+                let sizes =
+                    sizes.try_cast_into(|v| TCError::bad_request("invalid split sizes", v))?;
+                let axis = cast_axis(axis)?;
+
+                let pieces = self.tensor.split(axis, sizes)?;
+                Ok(split_into_state(pieces))
             })
         }))
     }
 }
 
-impl<T> From<T> for ExpandHandler<T> {
+impl<T> From<T> for SplitHandler
+where
+    Tensor: From<T>,
+{
     fn from(tensor: T) -> Self {
-        Self { tensor }
+        Self {
+            tensor: tensor.into(),
+        }
     }
 }
 
-struct RangeHandler;
+struct ChunkHandler {
+    tensor: Tensor,
+}
 
-impl<'a> Handler<'a> for RangeHandler {
-    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+impl<'a> Handler<'a> for ChunkHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
         'b: 'a,
     {
-        Some(Box::new(|txn, key| {
+        Some(Box::new(|_txn, mut params| {
             Box::pin(async move {
-                if key.matches::<(Vec<u64>, Number, Number)>() {
-                    let (shape, start, stop): (Vec<u64>, Number, Number) =
-                        key.opt_cast_into().unwrap();
+                let chunks: Value = params.require(&label("chunks").into())?;
+                let axis: Value = params.or_default(&label("axis").into())?;
+                params.expect_empty()?;
 
-                    let file = create_file(&txn).await?;
+                let chunks = chunks
+                    .try_cast_into(|v| TCError::bad_request("invalid number of chunks", v))?;
 
-                    DenseTensor::range(file, *txn.id(), shape, start, stop)
-                        .map_ok(Tensor::from)
-                        .map_ok(Collection::from)
-                        .map_ok(State::from)
-                        .await
-                } else {
-                    Err(TCError::bad_request("invalid schema for range tensor", key))
-                }
+                let axis = cast_axis(axis)?;
+
+                let pieces = self.tensor.chunk(axis, chunks)?;
+                Ok(split_into_state(pieces))
             })
         }))
     }
 }
 
-struct TransposeHandler<T> {
-    tensor: T,
-}
-
-impl<'a, T> Handler<'a> for TransposeHandler<T>
+impl<T> From<T> for ChunkHandler
 where
-    T: TensorTransform + Send + 'a,
-    Tensor: From<T::Transpose>,
+    Tensor: From<T>,
 {
-    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct RepeatInterleaveHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for RepeatInterleaveHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
         'b: 'a,
     {
-        Some(Box::new(|_txn, key| {
+        Some(Box::new(|txn, mut params| {
             Box::pin(async move {
-                let transpose = if key.is_none() {
-                    self.tensor.transpose(None)
-                } else {
-                    let permutation = key.try_cast_into(|v| {
-                        TCError::bad_request("invalid permutation for transpose", v)
-                    })?;
+                let repeats: Value = params.require(&label("repeats").into())?;
+                let repeats = repeats
+                    .try_cast_into(|v| TCError::bad_request("invalid number of repeats", v))?;
 
-                    self.tensor.transpose(Some(permutation))
-                };
+                let axis: Value = params.or_default(&label("axis").into())?;
+                params.expect_empty()?;
 
-                transpose
-                    .map(Tensor::from)
-                    .map(Collection::from)
-                    .map(State::from)
+                let axis = cast_axis(axis)?;
+
+                self.tensor
+                    .repeat_interleave(axis, repeats, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
             })
         }))
     }
 }
 
-impl<T> From<T> for TransposeHandler<T> {
+impl<T> From<T> for RepeatInterleaveHandler
+where
+    Tensor: From<T>,
+{
     fn from(tensor: T) -> Self {
-        Self { tensor }
-    }
-}
-
-impl Route for TensorType {
-    fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
-        if path.is_empty() {
-            return Some(Box::new(CreateHandler { class: *self }));
-        } else if path.len() != 1 {
-            return None;
-        }
-
-        match self {
-            Self::Dense => match path[0].as_str() {
-                "copy_from" => Some(Box::new(CopyDenseHandler)),
-                "constant" => Some(Box::new(ConstantHandler)),
-                "range" => Some(Box::new(RangeHandler)),
-                _ => None,
-            },
-            Self::Sparse => match path[0].as_str() {
-                "copy_from" => Some(Box::new(CopySparseHandler)),
-                _ => None,
-            },
+        Self {
+            tensor: tensor.into(),
         }
     }
 }
 
-struct DualHandler {
+struct TopKHandler {
     tensor: Tensor,
-    op: fn(Tensor, Tensor) -> TCResult<Tensor>,
 }
 
-impl DualHandler {
-    fn new<T>(tensor: T, op: fn(Tensor, Tensor) -> TCResult<Tensor>) -> Self
+impl<'a> Handler<'a> for TopKHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
-        Tensor: From<T>,
+        'b: 'a,
     {
+        Some(Box::new(|txn, mut params| {
+            Box::pin(async move {
+                let k: Value = params.require(&label("k").into())?;
+                let k = k.try_cast_into(|v| TCError::bad_request("invalid k for topk", v))?;
+
+                let axis: Value = params.or_default(&label("axis").into())?;
+                let largest: Value = params.or_default(&label("largest").into())?;
+                params.expect_empty()?;
+
+                let axis = cast_axis(axis)?;
+                let largest = if largest.is_none() {
+                    true
+                } else {
+                    largest
+                        .try_cast_into(|v| TCError::bad_request("invalid value for largest", v))?
+                };
+
+                let (values, indices) = self.tensor.topk(axis, k, largest, txn.clone()).await?;
+                Ok(split_into_state(vec![values, indices]))
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for TopKHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
         Self {
             tensor: tensor.into(),
-            op,
         }
     }
 }
 
-impl<'a> Handler<'a> for DualHandler {
+struct TakeAlongAxisHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for TakeAlongAxisHandler {
     fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
     where
         'b: 'a,
     {
-        Some(Box::new(|_txn, mut params| {
+        Some(Box::new(|txn, mut params| {
             Box::pin(async move {
-                let l = self.tensor;
-                let r: Tensor = params.require(&label("r").into())?;
+                let indices: Tensor = params.require(&label("indices").into())?;
+                let axis: Value = params.or_default(&label("axis").into())?;
                 params.expect_empty()?;
 
-                if l.shape() == r.shape() {
-                    debug!("tensor dual op with shapes {} {}", l.shape(), r.shape());
-                    (self.op)(l, r).map(Collection::from).map(State::from)
-                } else {
-                    let (l, r) = broadcast(l, r)?;
-                    debug!("tensor dual op with shapes {} {}", l.shape(), r.shape());
-                    (self.op)(l, r).map(Collection::from).map(State::from)
-                }
+                let axis = cast_axis(axis)?;
+
+                self.tensor
+                    .take_along_axis(axis, indices, txn.clone())
+                    .map_ok(Collection::from)
+                    .map_ok(State::from)
+                    .await
             })
         }))
     }
 }
 
+impl<T> From<T> for TakeAlongAxisHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
 struct ReduceHandler<'a, T: TensorReduce<fs::Dir>> {
     tensor: &'a T,
     reduce: fn(T, usize) -> TCResult<<T as TensorReduce<fs::Dir>>::Reduce>,
@@ -401,6 +1459,74 @@ where
     }
 }
 
+struct MaskedFillHandler {
+    tensor: Tensor,
+}
+
+impl<'a> Handler<'a> for MaskedFillHandler {
+    fn put<'b>(self: Box<Self>) -> Option<PutHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key, mask| {
+            Box::pin(async move {
+                let value =
+                    key.try_cast_into(|v| TCError::bad_request("invalid tensor element", v))?;
+
+                let mask = Tensor::try_cast_from(mask, |s| {
+                    TCError::bad_request("expected a Tensor mask but found", s)
+                })?;
+
+                self.tensor.masked_fill(txn.clone(), mask, value).await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for MaskedFillHandler
+where
+    Tensor: From<T>,
+{
+    fn from(tensor: T) -> Self {
+        Self {
+            tensor: tensor.into(),
+        }
+    }
+}
+
+struct SetWhereHandler<T> {
+    tensor: T,
+}
+
+impl<'a, T> Handler<'a> for SetWhereHandler<T>
+where
+    T: TensorDualIO<fs::Dir, Tensor, Txn = Txn> + Send + 'a,
+{
+    fn put<'b>(self: Box<Self>) -> Option<PutHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key, mask| {
+            Box::pin(async move {
+                let value =
+                    key.try_cast_into(|v| TCError::bad_request("invalid tensor element", v))?;
+
+                let mask = Tensor::try_cast_from(mask, |s| {
+                    TCError::bad_request("expected a Tensor mask but found", s)
+                })?;
+
+                self.tensor.set_where(txn.clone(), mask, value).await
+            })
+        }))
+    }
+}
+
+impl<T> From<T> for SetWhereHandler<T> {
+    fn from(tensor: T) -> Self {
+        Self { tensor }
+    }
+}
+
 struct TensorHandler<T> {
     tensor: T,
 }
@@ -523,9 +1649,7 @@ impl<B: DenseAccess<fs::File<Array>, fs::File<Node>, fs::Dir, Txn>> Route for De
     }
 }
 
-impl<A: SparseAccess<fs::File<Array>, fs::File<Node>, fs::Dir, Txn>> Route
-    for SparseTensor<fs::File<Array>, fs::File<Node>, fs::Dir, Txn, A>
-{
+impl<A: SparseAccess<fs::File<Array>, fs::File<Node>, fs::Dir, Txn>> Route for SparseTensor<A> {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
         route(self, path)
     }
@@ -533,7 +1657,12 @@ impl<A: SparseAccess<fs::File<Array>, fs::File<Node>, fs::Dir, Txn>> Route
 
 impl Route for Tensor {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
-        route(self, path)
+        match self {
+            Self::Sparse(sparse) if path == &["as_table"] => {
+                Some(Box::new(AsTableHandler::from(sparse.clone())))
+            }
+            _ => route(self, path),
+        }
     }
 }
 
@@ -555,6 +1684,7 @@ where
     Tensor: From<T>,
     <T as TensorTransform>::Slice: TensorAccess + Send,
     Tensor: From<<T as TensorReduce<fs::Dir>>::Reduce>,
+    Tensor: From<<T as TensorTransform>::Cast>,
     Tensor: From<<T as TensorTransform>::Expand>,
     Tensor: From<<T as TensorTransform>::Slice>,
     Tensor: From<<T as TensorTransform>::Transpose>,
@@ -588,13 +1718,55 @@ where
                 cloned.into(),
                 TensorUnary::any,
             ))),
+            "exp" => Some(Box::new(UnaryHandler::new(cloned.into(), TensorUnary::exp))),
+            "ln" => Some(Box::new(UnaryHandler::new(cloned.into(), TensorUnary::ln))),
+            "log2" => Some(Box::new(UnaryHandler::new(
+                cloned.into(),
+                TensorUnary::log2,
+            ))),
+            "log10" => Some(Box::new(UnaryHandler::new(
+                cloned.into(),
+                TensorUnary::log10,
+            ))),
             "not" => Some(Box::new(UnaryHandler::new(cloned.into(), TensorUnary::not))),
 
             // basic math
-            "add" => Some(Box::new(DualHandler::new(cloned, TensorMath::add))),
-            "div" => Some(Box::new(DualHandler::new(cloned, TensorMath::div))),
-            "mul" => Some(Box::new(DualHandler::new(cloned, TensorMath::mul))),
-            "sub" => Some(Box::new(DualHandler::new(cloned, TensorMath::sub))),
+            "add" => Some(Box::new(MathHandler::new(
+                cloned,
+                TensorMath::add_with,
+                Some(Tensor::add_checked),
+            ))),
+            "div" => Some(Box::new(MathHandler::new(
+                cloned,
+                TensorMath::div_with,
+                None,
+            ))),
+            "mul" => Some(Box::new(MathHandler::new(
+                cloned,
+                TensorMath::mul_with,
+                Some(Tensor::mul_checked),
+            ))),
+            "sub" => Some(Box::new(MathHandler::new(
+                cloned,
+                TensorMath::sub_with,
+                Some(Tensor::sub_checked),
+            ))),
+            "dot" => Some(Box::new(DotHandler::from(cloned))),
+            "kron" => Some(Box::new(KronHandler::from(cloned))),
+            "matmul" => Some(Box::new(MatmulHandler::from(cloned))),
+            "maximum" => Some(Box::new(DualHandler::new(cloned, TensorMath::maximum))),
+            "minimum" => Some(Box::new(DualHandler::new(cloned, TensorMath::minimum))),
+            "fft" => Some(Box::new(FourierHandler::new(cloned, false))),
+            "ifft" => Some(Box::new(FourierHandler::new(cloned, true))),
+            "clip_by_norm" => Some(Box::new(ClipByNormHandler::from(cloned))),
+            "conv2d" => Some(Box::new(Conv2dHandler::from(cloned))),
+            "nan_to_num" => Some(Box::new(NanToNumHandler::from(cloned))),
+            "nnz" => Some(Box::new(NnzHandler::from(cloned))),
+            "normalize" => Some(Box::new(NormalizeHandler::from(cloned))),
+            "one_hot" => Some(Box::new(OneHotHandler::from(cloned))),
+            "repeat_interleave" => Some(Box::new(RepeatInterleaveHandler::from(cloned))),
+            "take_along_axis" => Some(Box::new(TakeAlongAxisHandler::from(cloned))),
+            "topk" => Some(Box::new(TopKHandler::from(cloned))),
 
             // reduce ops
             "product" => Some(Box::new(ReduceHandler::new(
@@ -607,9 +1779,34 @@ where
                 TensorReduce::sum,
                 TensorReduce::sum_all,
             ))),
+            "mean" => Some(Box::new(ReduceHandler::new(
+                tensor,
+                TensorReduce::mean,
+                TensorReduce::mean_all,
+            ))),
+            "std" => Some(Box::new(ReduceHandler::new(
+                tensor,
+                TensorReduce::std,
+                TensorReduce::std_all,
+            ))),
+            "var" => Some(Box::new(ReduceHandler::new(
+                tensor,
+                TensorReduce::var,
+                TensorReduce::var_all,
+            ))),
+
+            // I/O ops
+            "masked_fill" => Some(Box::new(MaskedFillHandler::from(cloned))),
+            "set_where" => Some(Box::new(SetWhereHandler::from(cloned))),
 
             // transforms
+            "cast" => Some(Box::new(CastHandler::from(cloned))),
             "expand_dims" => Some(Box::new(ExpandHandler::from(cloned))),
+            "flatten" => Some(Box::new(FlattenHandler::from(cloned))),
+            "chunk" => Some(Box::new(ChunkHandler::from(cloned))),
+            "split" => Some(Box::new(SplitHandler::from(cloned))),
+            "roll" => Some(Box::new(RollHandler::from(cloned))),
+            "squeeze" => Some(Box::new(SqueezeHandler::from(cloned))),
             "transpose" => Some(Box::new(TransposeHandler::from(cloned))),
 
             _ => None,
@@ -677,6 +1874,23 @@ where
     }
 }
 
+fn cast_axis(axis: Value) -> TCResult<usize> {
+    match axis {
+        Value::None => Ok(0),
+        axis => axis.try_cast_into(|v| TCError::bad_request("invalid tensor axis", v)),
+    }
+}
+
+fn split_into_state(pieces: Vec<Tensor>) -> State {
+    let pieces = pieces
+        .into_iter()
+        .map(Collection::from)
+        .map(State::from)
+        .collect::<Vec<State>>();
+
+    State::Tuple(pieces.into())
+}
+
 async fn create_file(txn: &Txn) -> TCResult<fs::File<afarray::Array>> {
     txn.context()
         .create_file_tmp(*txn.id(), TensorType::Dense)
@@ -729,6 +1943,10 @@ pub fn cast_bounds(shape: &Shape, value: Value) -> TCResult<Bounds> {
 
     match value {
         Value::None => Ok(Bounds::all(shape)),
+        Value::Number(i) if shape.is_empty() => Err(TCError::bad_request(
+            "zero-dimensional Tensor has no axis to index with",
+            i,
+        )),
         Value::Number(i) => {
             let bound = cast_bound(shape[0], i.into())?;
             Ok(Bounds::from(vec![bound]))
@@ -79,11 +79,12 @@ where
             Box::pin(async move {
                 let op: Closure = params.require(&label("op").into())?;
 
+                let config = op.config().clone();
                 let mut tuple = Vec::with_capacity(self.len);
                 let mut mapped = stream::iter(self.items)
                     .map(State::from)
                     .map(|state| op.clone().into_callable(state))
-                    .map_ok(|(context, op_def)| OpDef::call(op_def, txn, context))
+                    .map_ok(|(context, op_def)| OpDef::call(op_def, txn, context, &config))
                     .try_buffered(num_cpus::get());
 
                 while let Some(item) = mapped.try_next().await? {
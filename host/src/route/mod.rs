@@ -30,7 +30,9 @@ pub type PutHandler<'a, 'b> = Box<dyn FnOnce(&'b Txn, Value, State) -> PutFuture
 pub type PostFuture<'a> = Pin<Box<dyn Future<Output = TCResult<State>> + Send + 'a>>;
 pub type PostHandler<'a, 'b> = Box<dyn FnOnce(&'b Txn, Map<State>) -> PostFuture<'a> + Send + 'a>;
 
-pub type DeleteFuture<'a> = Pin<Box<dyn Future<Output = TCResult<()>> + Send + 'a>>;
+pub type PatchHandler<'a, 'b> = PutHandler<'a, 'b>;
+
+pub type DeleteFuture<'a> = Pin<Box<dyn Future<Output = TCResult<Value>> + Send + 'a>>;
 pub type DeleteHandler<'a, 'b> = Box<dyn FnOnce(&'b Txn, Value) -> DeleteFuture<'a> + Send + 'a>;
 
 #[async_trait]
@@ -62,6 +64,13 @@ pub trait Handler<'a>: Send {
     {
         None
     }
+
+    fn patch<'b>(self: Box<Self>) -> Option<PatchHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        None
+    }
 }
 
 pub trait Route: Send + Sync {
@@ -76,7 +85,15 @@ pub trait Public {
 
     async fn post(&self, txn: &Txn, path: &[PathSegment], params: Map<State>) -> TCResult<State>;
 
-    async fn delete(&self, txn: &Txn, path: &[PathSegment], key: Value) -> TCResult<()>;
+    async fn delete(&self, txn: &Txn, path: &[PathSegment], key: Value) -> TCResult<Value>;
+
+    async fn patch(
+        &self,
+        txn: &Txn,
+        path: &[PathSegment],
+        key: Value,
+        value: State,
+    ) -> TCResult<()>;
 }
 
 #[async_trait]
@@ -129,7 +146,7 @@ impl<T: Route + fmt::Display> Public for T {
         }
     }
 
-    async fn delete(&self, txn: &Txn, path: &[PathSegment], key: Value) -> TCResult<()> {
+    async fn delete(&self, txn: &Txn, path: &[PathSegment], key: Value) -> TCResult<Value> {
         let handler = self
             .route(path)
             .ok_or_else(|| TCError::not_found(TCPath::from(path)))?;
@@ -144,6 +161,28 @@ impl<T: Route + fmt::Display> Public for T {
             ))
         }
     }
+
+    async fn patch(
+        &self,
+        txn: &Txn,
+        path: &[PathSegment],
+        key: Value,
+        value: State,
+    ) -> TCResult<()> {
+        let handler = self
+            .route(path)
+            .ok_or_else(|| TCError::not_found(TCPath::from(path)))?;
+
+        if let Some(patch_handler) = handler.patch() {
+            patch_handler(txn, key, value).await
+        } else {
+            Err(TCError::method_not_allowed(
+                "PATCH",
+                self,
+                TCPath::from(path),
+            ))
+        }
+    }
 }
 
 struct EchoHandler;
@@ -3,17 +3,23 @@ use std::iter::FromIterator;
 use log::debug;
 
 use tc_error::*;
-use tcgeneric::{Id, Instance, Map, PathSegment, TCPath, Tuple};
+use tcgeneric::{label, Id, Instance, Label, Map, PathSegment, TCPath, Tuple};
 
 use crate::object::InstanceExt;
-use crate::route::{DeleteHandler, GetHandler, Handler, PostHandler, PutHandler, Route};
+use crate::route::{
+    DeleteHandler, GetHandler, Handler, PatchHandler, PostHandler, PutHandler, Route,
+};
 use crate::scalar::*;
 use crate::state::State;
 use crate::txn::Txn;
 
+/// The name of the PUT method to invoke in response to a PATCH request to an instance.
+const PATCH: Label = label("patch");
+
 struct GetMethod<'a, T: Instance> {
     subject: &'a InstanceExt<T>,
     method: GetOp,
+    config: OpConfig,
     path: &'a [PathSegment],
 }
 
@@ -24,7 +30,7 @@ impl<'a, T: Instance + Route + 'a> GetMethod<'a, T> {
         let mut context = Map::new();
         context.insert(key_name, key.into());
 
-        call_method(txn, self.subject, self.path, context, op_def).await
+        call_method(txn, self.subject, self.path, context, op_def, self.config).await
     }
 }
 
@@ -40,6 +46,7 @@ impl<'a, T: Instance + Route + 'a> Handler<'a> for GetMethod<'a, T> {
 struct PutMethod<'a, T: Instance> {
     subject: &'a InstanceExt<T>,
     method: PutOp,
+    config: OpConfig,
     path: &'a [PathSegment],
 }
 
@@ -51,7 +58,7 @@ impl<'a, T: Instance + Route + 'a> PutMethod<'a, T> {
         context.insert(key_name, key.into());
         context.insert(value_name, value);
 
-        call_method(txn, self.subject, self.path, context, op_def).await?;
+        call_method(txn, self.subject, self.path, context, op_def, self.config).await?;
         Ok(())
     }
 }
@@ -70,12 +77,21 @@ impl<'a, T: Instance + Route + 'a> Handler<'a> for PutMethod<'a, T> {
 struct PostMethod<'a, T: Instance> {
     subject: &'a InstanceExt<T>,
     method: PostOp,
+    config: OpConfig,
     path: &'a [PathSegment],
 }
 
 impl<'a, T: Instance + Route + 'a> PostMethod<'a, T> {
     async fn call(self, txn: &Txn, params: Map<State>) -> TCResult<State> {
-        call_method(txn, self.subject, self.path, params, self.method).await
+        call_method(
+            txn,
+            self.subject,
+            self.path,
+            params,
+            self.method,
+            self.config,
+        )
+        .await
     }
 }
 
@@ -93,18 +109,19 @@ impl<'a, T: Instance + Route + 'a> Handler<'a> for PostMethod<'a, T> {
 struct DeleteMethod<'a, T: Instance> {
     subject: &'a InstanceExt<T>,
     method: DeleteOp,
+    config: OpConfig,
     path: &'a [PathSegment],
 }
 
 impl<'a, T: Instance + Route + 'a> DeleteMethod<'a, T> {
-    async fn call(self, txn: &Txn, key: Value) -> TCResult<()> {
+    async fn call(self, txn: &Txn, key: Value) -> TCResult<Value> {
         let (key_name, op_def) = self.method;
 
         let mut context = Map::new();
         context.insert(key_name, key.into());
 
-        call_method(txn, self.subject, self.path, context, op_def).await?;
-        Ok(())
+        call_method(txn, self.subject, self.path, context, op_def, self.config).await?;
+        Ok(Value::None)
     }
 }
 
@@ -117,32 +134,93 @@ impl<'a, T: Instance + Route + 'a> Handler<'a> for DeleteMethod<'a, T> {
     }
 }
 
+struct PatchMethod<'a, T: Instance> {
+    parent: Option<Box<dyn Handler<'a> + 'a>>,
+    method: PutMethod<'a, T>,
+}
+
+impl<'a, T: Instance + Route + 'a> Handler<'a> for PatchMethod<'a, T> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        self.parent.and_then(|handler| handler.get())
+    }
+
+    fn put<'b>(self: Box<Self>) -> Option<PutHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        self.parent.and_then(|handler| handler.put())
+    }
+
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        self.parent.and_then(|handler| handler.post())
+    }
+
+    fn delete<'b>(self: Box<Self>) -> Option<DeleteHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        self.parent.and_then(|handler| handler.delete())
+    }
+
+    fn patch<'b>(self: Box<Self>) -> Option<PatchHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        let method = self.method;
+        Some(Box::new(move |txn, key, value| {
+            Box::pin(method.call(txn, key, value))
+        }))
+    }
+}
+
 impl<T: Instance + Route> Route for InstanceExt<T> {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
         debug!("InstanceExt::route {}", TCPath::from(path));
 
         if path.is_empty() {
-            self.parent().route(path)
+            if let Some(Scalar::Op(OpDef::Put(put_op, config))) = self.proto().get(&PATCH.into()) {
+                Some(Box::new(PatchMethod {
+                    parent: self.parent().route(path),
+                    method: PutMethod {
+                        subject: self,
+                        method: put_op.clone(),
+                        config: config.clone(),
+                        path,
+                    },
+                }))
+            } else {
+                self.parent().route(path)
+            }
         } else if let Some(member) = self.proto().get(&path[0]) {
             match member {
-                Scalar::Op(OpDef::Get(get_op)) => Some(Box::new(GetMethod {
+                Scalar::Op(OpDef::Get(get_op, config)) => Some(Box::new(GetMethod {
                     subject: self,
                     method: get_op.clone(),
+                    config: config.clone(),
                     path: &path[1..],
                 })),
-                Scalar::Op(OpDef::Put(put_op)) => Some(Box::new(PutMethod {
+                Scalar::Op(OpDef::Put(put_op, config)) => Some(Box::new(PutMethod {
                     subject: self,
                     method: put_op.clone(),
+                    config: config.clone(),
                     path: &path[1..],
                 })),
-                Scalar::Op(OpDef::Post(post_op)) => Some(Box::new(PostMethod {
+                Scalar::Op(OpDef::Post(post_op, config)) => Some(Box::new(PostMethod {
                     subject: self,
                     method: post_op.clone(),
+                    config: config.clone(),
                     path: &path[1..],
                 })),
-                Scalar::Op(OpDef::Delete(delete_op)) => Some(Box::new(DeleteMethod {
+                Scalar::Op(OpDef::Delete(delete_op, config)) => Some(Box::new(DeleteMethod {
                     subject: self,
                     method: delete_op.clone(),
+                    config: config.clone(),
                     path: &path[1..],
                 })),
                 other => other.route(&path[1..]),
@@ -165,6 +243,7 @@ async fn call_method<T: Instance + Route>(
     path: &[PathSegment],
     context: Map<State>,
     form: Vec<(Id, Scalar)>,
+    config: OpConfig,
 ) -> TCResult<State> {
     debug!(
         "call method with form {:?}",
@@ -178,13 +257,21 @@ async fn call_method<T: Instance + Route>(
         return Err(TCError::not_found(TCPath::from(path)));
     }
 
+    config.validate_input(&context)?;
+
     let capture = if let Some((capture, _)) = form.last() {
         capture.clone()
     } else {
-        return Ok(State::default());
+        let result = State::default();
+        config.validate_output(&result)?;
+        return Ok(result);
     };
 
-    Executor::with_context(txn, Some(subject), context.into(), form)
+    let result = Executor::with_context(txn, Some(subject), context, form)?
+        .with_timeout(config.timeout)
         .capture(capture)
-        .await
+        .await?;
+
+    config.validate_output(&result)?;
+    Ok(result)
 }
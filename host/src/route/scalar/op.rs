@@ -1,8 +1,13 @@
 use std::iter;
 
-use tcgeneric::PathSegment;
+use safecast::TryCastFrom;
+
+use tc_error::*;
+use tc_value::Value;
+use tcgeneric::{label, Instance, Map, PathSegment};
 
 use crate::scalar::op::*;
+use crate::scalar::Scalar;
 use crate::state::State;
 
 use crate::route::*;
@@ -16,12 +21,9 @@ impl<'a> Handler<'a> for OpHandler {
     where
         'b: 'a,
     {
-        if let OpDef::Get((key_name, op_def)) = self.op_def {
+        if self.op_def.class() == OpDefType::Get {
             Some(Box::new(|txn, key| {
-                Box::pin(async move {
-                    let context = iter::once((key_name, State::from(key)));
-                    OpDef::call(op_def, txn, context).await
-                })
+                Box::pin(self.op_def.call_memoized(txn, key.into()))
             }))
         } else {
             None
@@ -32,11 +34,11 @@ impl<'a> Handler<'a> for OpHandler {
     where
         'b: 'a,
     {
-        if let OpDef::Put((key_name, value_name, op_def)) = self.op_def {
+        if let OpDef::Put((key_name, value_name, op_def), config) = self.op_def {
             Some(Box::new(|txn, key, value| {
                 Box::pin(async move {
                     let context = vec![(key_name, key.into()), (value_name, value)];
-                    OpDef::call(op_def, txn, context).await?;
+                    OpDef::call(op_def, txn, context, &config).await?;
                     Ok(())
                 })
             }))
@@ -49,9 +51,9 @@ impl<'a> Handler<'a> for OpHandler {
     where
         'b: 'a,
     {
-        if let OpDef::Post(op_def) = self.op_def {
+        if let OpDef::Post(op_def, config) = self.op_def {
             Some(Box::new(|txn, params| {
-                Box::pin(async move { OpDef::call(op_def, txn, params).await })
+                Box::pin(async move { OpDef::call(op_def, txn, params, &config).await })
             }))
         } else {
             None
@@ -62,12 +64,12 @@ impl<'a> Handler<'a> for OpHandler {
     where
         'b: 'a,
     {
-        if let OpDef::Delete((key_name, op_def)) = self.op_def {
+        if let OpDef::Delete((key_name, op_def), config) = self.op_def {
             Some(Box::new(|txn, key| {
                 Box::pin(async move {
                     let context = iter::once((key_name, State::from(key)));
-                    OpDef::call(op_def, txn, context).await?;
-                    Ok(())
+                    OpDef::call(op_def, txn, context, &config).await?;
+                    Ok(Value::None)
                 })
             }))
         } else {
@@ -76,12 +78,67 @@ impl<'a> Handler<'a> for OpHandler {
     }
 }
 
+struct PartialHandler {
+    op_def: OpDef,
+}
+
+impl<'a> Handler<'a> for PartialHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, params| {
+            Box::pin(async move {
+                let bound = params
+                    .into_iter()
+                    .map(|(name, state)| {
+                        Scalar::try_cast_from(state, |s| {
+                            TCError::bad_request("a bound parameter must be a Scalar, not", s)
+                        })
+                        .map(|scalar| (name, scalar))
+                    })
+                    .collect::<TCResult<Map<Scalar>>>()?;
+
+                self.op_def.partial(bound).map(State::from)
+            })
+        }))
+    }
+}
+
+struct ThenHandler {
+    op_def: OpDef,
+}
+
+impl<'a> Handler<'a> for ThenHandler {
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, mut params| {
+            Box::pin(async move {
+                let next: OpDef = params.require(&label("next").into())?;
+                params.expect_empty()?;
+
+                self.op_def.then(next).map(State::from)
+            })
+        }))
+    }
+}
+
 impl Route for OpDef {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
         if path.is_empty() {
             Some(Box::new(OpHandler {
                 op_def: self.clone(),
             }))
+        } else if path.len() == 1 && path[0].as_str() == "partial" {
+            Some(Box::new(PartialHandler {
+                op_def: self.clone(),
+            }))
+        } else if path.len() == 1 && path[0].as_str() == "then" {
+            Some(Box::new(ThenHandler {
+                op_def: self.clone(),
+            }))
         } else {
             None
         }
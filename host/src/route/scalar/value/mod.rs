@@ -2,8 +2,8 @@ use bytes::Bytes;
 use safecast::TryCastFrom;
 use uuid::Uuid;
 
-use tc_error::TCError;
-use tcgeneric::{label, Label, PathSegment};
+use tc_error::*;
+use tcgeneric::{label, Label, PathSegment, Tuple};
 
 use crate::route::{GetHandler, Handler, Route, SelfHandler};
 use crate::scalar::Value;
@@ -34,6 +34,136 @@ impl<F> From<F> for EqHandler<F> {
     }
 }
 
+struct ConcatHandler<'a> {
+    string: &'a str,
+}
+
+impl<'a> Handler<'a> for ConcatHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let suffix = String::try_cast_from(key, |v| {
+                    TCError::bad_request("cannot concatenate a string with", v)
+                })?;
+
+                Ok(Value::String(format!("{}{}", self.string, suffix)).into())
+            })
+        }))
+    }
+}
+
+struct SplitHandler<'a> {
+    string: &'a str,
+}
+
+impl<'a> Handler<'a> for SplitHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let separator = match key {
+                    Value::String(separator) if !separator.is_empty() => separator,
+                    other => {
+                        return Err(TCError::bad_request(
+                            "expected a non-empty String separator, not",
+                            other,
+                        ))
+                    }
+                };
+
+                let pieces = self
+                    .string
+                    .split(separator.as_str())
+                    .map(|piece| Value::String(piece.to_string()))
+                    .collect::<Vec<Value>>();
+
+                Ok(Value::Tuple(pieces.into()).into())
+            })
+        }))
+    }
+}
+
+struct FormatHandler<'a> {
+    template: &'a str,
+}
+
+impl<'a> Handler<'a> for FormatHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|_txn, key| {
+            Box::pin(async move {
+                let args = match key {
+                    Value::Tuple(args) => args,
+                    other => {
+                        return Err(TCError::bad_request(
+                            "expected a Tuple of format arguments, not",
+                            other,
+                        ))
+                    }
+                };
+
+                format(self.template, args)
+                    .map(Value::String)
+                    .map(State::from)
+            })
+        }))
+    }
+}
+
+/// Replace each `{}` placeholder in the given `template` with the next of the given `args`.
+///
+/// A literal brace is written as `{{` or `}}`.
+fn format(template: &str, args: Tuple<Value>) -> TCResult<String> {
+    let num_args = args.len();
+    let mut args = args.into_iter();
+    let mut formatted = String::with_capacity(template.len());
+
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                formatted.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+
+                let arg = args.next().ok_or_else(|| {
+                    TCError::bad_request(
+                        "too few arguments for format string, expected more than",
+                        num_args,
+                    )
+                })?;
+
+                formatted.push_str(&arg.to_string());
+            }
+            ('{', _) | ('}', _) => {
+                return Err(TCError::bad_request(
+                    "unmatched brace in format string",
+                    template,
+                ))
+            }
+            (c, _) => formatted.push(c),
+        }
+    }
+
+    if args.next().is_some() {
+        return Err(TCError::bad_request(
+            "too many arguments for format string",
+            num_args,
+        ));
+    }
+
+    Ok(formatted)
+}
+
 impl Route for Value {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
         let child_handler = match self {
@@ -48,6 +178,18 @@ impl Route for Value {
             match path[0].as_str() {
                 "eq" => Some(Box::new(EqHandler::from(move |other| self == &other))),
                 "ne" => Some(Box::new(EqHandler::from(move |other| self != &other))),
+                "concat" => match self {
+                    Self::String(string) => Some(Box::new(ConcatHandler { string })),
+                    _ => None,
+                },
+                "format" => match self {
+                    Self::String(template) => Some(Box::new(FormatHandler { template })),
+                    _ => None,
+                },
+                "split" => match self {
+                    Self::String(string) => Some(Box::new(SplitHandler { string })),
+                    _ => None,
+                },
                 _ => None,
             }
         } else if path.is_empty() {
@@ -110,3 +252,29 @@ impl Route for Static {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Tuple<Value> {
+        values
+            .iter()
+            .map(|s| Value::String(s.to_string()))
+            .collect::<Vec<Value>>()
+            .into()
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format("{}-{}", strings(&["a", "b"])).unwrap(), "a-b");
+        assert_eq!(
+            format("{{{}}}", Tuple::from(vec![Value::from(1u64)])).unwrap(),
+            "{1}"
+        );
+
+        assert!(format("{}-{}", strings(&["a"])).is_err());
+        assert!(format("{}", strings(&["a", "b"])).is_err());
+        assert!(format("{", strings(&[])).is_err());
+    }
+}
@@ -8,7 +8,7 @@ use tcgeneric::{
 
 use crate::object::{InstanceClass, Object};
 use crate::scalar::Link;
-use crate::state::{State, StateType};
+use crate::state::{Lazy, State, StateType};
 
 use super::*;
 
@@ -45,6 +45,8 @@ impl<'a> Handler<'a> for ClassHandler {
                 }
 
                 let class = InstanceClass::new(Some(self.class.path().into()), proto);
+                class.validate()?;
+
                 Ok(Object::Class(class).into())
             })
         }))
@@ -57,6 +59,61 @@ impl From<StateType> for ClassHandler {
     }
 }
 
+struct LazyHandler<'a> {
+    lazy: &'a Lazy,
+    path: &'a [PathSegment],
+}
+
+impl<'a> Handler<'a> for LazyHandler<'a> {
+    fn get<'b>(self: Box<Self>) -> Option<GetHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let state = self.lazy.force(txn.clone()).await?;
+                state.get(txn, self.path, key).await
+            })
+        }))
+    }
+
+    fn put<'b>(self: Box<Self>) -> Option<PutHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key, value| {
+            Box::pin(async move {
+                let state = self.lazy.force(txn.clone()).await?;
+                state.put(txn, self.path, key, value).await
+            })
+        }))
+    }
+
+    fn post<'b>(self: Box<Self>) -> Option<PostHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, params| {
+            Box::pin(async move {
+                let state = self.lazy.force(txn.clone()).await?;
+                state.post(txn, self.path, params).await
+            })
+        }))
+    }
+
+    fn delete<'b>(self: Box<Self>) -> Option<DeleteHandler<'a, 'b>>
+    where
+        'b: 'a,
+    {
+        Some(Box::new(|txn, key| {
+            Box::pin(async move {
+                let state = self.lazy.force(txn.clone()).await?;
+                state.delete(txn, self.path, key).await
+            })
+        }))
+    }
+}
+
 struct MapHandler;
 
 impl<'a> Handler<'a> for MapHandler {
@@ -121,10 +178,12 @@ impl Route for StateType {
 
 impl Route for State {
     fn route<'a>(&'a self, path: &'a [PathSegment]) -> Option<Box<dyn Handler<'a> + 'a>> {
-        let child_handler = match self {
+        let child_handler: Option<Box<dyn Handler<'a> + 'a>> = match self {
             Self::Chain(chain) => chain.route(path),
             Self::Closure(closure) => closure.route(path),
             Self::Collection(collection) => collection.route(path),
+            Self::Lazy(_) if path == &CLASS[..] => None,
+            Self::Lazy(lazy) => Some(Box::new(LazyHandler { lazy, path })),
             Self::Map(map) => map.route(path),
             Self::Object(object) => object.route(path),
             Self::Scalar(scalar) => scalar.route(path),
@@ -154,7 +213,9 @@ impl Route for Static {
             return Some(Box::new(EchoHandler));
         }
 
-        if path[0] == collection::PREFIX {
+        if path[0] == chain::PREFIX {
+            chain::Static.route(&path[1..])
+        } else if path[0] == collection::PREFIX {
             collection::Static.route(&path[1..])
         } else if path[0] == scalar::PREFIX {
             scalar::Static.route(&path[1..])
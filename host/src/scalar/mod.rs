@@ -183,13 +183,25 @@ impl Scalar {
         match class {
             ST::Map => self.opt_cast_into().map(Self::Map),
             ST::Op(odt) => match odt {
-                ODT::Get => self.opt_cast_into().map(OpDef::Get).map(Self::Op),
+                ODT::Get => self
+                    .opt_cast_into()
+                    .map(|op| OpDef::Get(op, OpConfig::default()))
+                    .map(Self::Op),
 
-                ODT::Put => self.opt_cast_into().map(OpDef::Put).map(Self::Op),
+                ODT::Put => self
+                    .opt_cast_into()
+                    .map(|op| OpDef::Put(op, OpConfig::default()))
+                    .map(Self::Op),
 
-                ODT::Post => self.opt_cast_into().map(OpDef::Post).map(Self::Op),
+                ODT::Post => self
+                    .opt_cast_into()
+                    .map(|op| OpDef::Post(op, OpConfig::default()))
+                    .map(Self::Op),
 
-                ODT::Delete => self.opt_cast_into().map(OpDef::Delete).map(Self::Op),
+                ODT::Delete => self
+                    .opt_cast_into()
+                    .map(|op| OpDef::Delete(op, OpConfig::default()))
+                    .map(Self::Op),
             },
             ST::Range => self.opt_cast_into().map(Self::Range),
             ST::Ref(rt) => match rt {
@@ -239,6 +251,7 @@ impl Scalar {
                                     None
                                 }
                             },
+                            Scalar::Value(Value::Link(link)) => Some((link.into(), key)),
                             other => {
                                 debug!("invalid DELETE subject: {}", other);
                                 None
@@ -353,6 +366,16 @@ impl Refer for Scalar {
         }
     }
 
+    fn is_external(&self) -> bool {
+        match self {
+            Self::Map(map) => map.values().any(|scalar| scalar.is_external()),
+            Self::Op(op_def) => op_def.is_external(),
+            Self::Ref(tc_ref) => tc_ref.is_external(),
+            Self::Tuple(tuple) => tuple.iter().any(|scalar| scalar.is_external()),
+            _ => false,
+        }
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         match self {
             Self::Map(map) => map
@@ -367,6 +390,16 @@ impl Refer for Scalar {
         }
     }
 
+    fn is_write(&self) -> bool {
+        match self {
+            Self::Map(map) => map.values().any(|scalar| scalar.is_write()),
+            Self::Op(op_def) => !op_def.is_read_only(),
+            Self::Ref(tc_ref) => tc_ref.is_write(),
+            Self::Tuple(tuple) => tuple.iter().any(|scalar| scalar.is_write()),
+            _ => false,
+        }
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         match self {
             Self::Map(map) => {
@@ -606,13 +639,21 @@ impl TryCastFrom<Scalar> for OpDef {
             Scalar::Op(op_def) => Some(op_def),
             Scalar::Tuple(tuple) => {
                 if PutOp::can_cast_from(&tuple) {
-                    tuple.opt_cast_into().map(Self::Put)
+                    tuple
+                        .opt_cast_into()
+                        .map(|op| Self::Put(op, OpConfig::default()))
                 } else if GetOp::can_cast_from(&tuple) {
-                    tuple.opt_cast_into().map(Self::Get)
+                    tuple
+                        .opt_cast_into()
+                        .map(|op| Self::Get(op, OpConfig::default()))
                 } else if PostOp::can_cast_from(&tuple) {
-                    tuple.opt_cast_into().map(Self::Post)
+                    tuple
+                        .opt_cast_into()
+                        .map(|op| Self::Post(op, OpConfig::default()))
                 } else if DeleteOp::can_cast_from(&tuple) {
-                    tuple.opt_cast_into().map(Self::Delete)
+                    tuple
+                        .opt_cast_into()
+                        .map(|op| Self::Delete(op, OpConfig::default()))
                 } else {
                     None
                 }
@@ -992,6 +1033,11 @@ impl ScalarVisitor {
         let scalar = access.next_value::<Scalar>(()).await?;
 
         if let Some(scalar) = scalar.clone().into_type(class) {
+            if let Scalar::Op(op_def) = scalar {
+                let config = OpDefVisitor::visit_config(access).await?;
+                return Ok(Scalar::Op(op_def.with_config(config)));
+            }
+
             return Ok(scalar);
         } else {
             debug!("cannot cast into {} from {}", class, scalar);
@@ -1002,7 +1048,10 @@ impl ScalarVisitor {
     }
 
     pub fn visit_subject<E: de::Error>(subject: Subject, params: Scalar) -> Result<Scalar, E> {
-        if params.is_none() {
+        // an empty map is the parameters of a call to a method with no arguments, not a missing key
+        let is_call = matches!(subject, Subject::Ref(..)) && matches!(params, Scalar::Map(_));
+
+        if params.is_none() && !is_call {
             match subject {
                 Subject::Ref(id, path) if path.is_empty() => {
                     Ok(Scalar::Ref(Box::new(TCRef::Id(id))))
@@ -1318,7 +1367,7 @@ impl<'a, T: Instance + Public> Scope<'a, T> {
         subject: &Id,
         path: &[PathSegment],
         key: Value,
-    ) -> TCResult<()> {
+    ) -> TCResult<Value> {
         if subject == &SELF {
             let subject = self.subject()?;
             subject.delete(txn, path, key).await
@@ -0,0 +1,202 @@
+//! A memoization cache for the results of pure [`super::OpDef`]s.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures::Future;
+use log::debug;
+
+use tc_error::*;
+
+use crate::scalar::Scalar;
+use crate::state::State;
+
+/// The key of a memoized result: the canonical hash of an `OpDef` and its encoded inputs.
+pub type OpCacheKey = (Bytes, Bytes);
+
+struct Entry {
+    result: Scalar,
+    expires: Instant,
+    last_used: u64,
+}
+
+struct Inner {
+    entries: HashMap<OpCacheKey, Entry>,
+    clock: u64,
+}
+
+/// A bounded, least-recently-used cache of the results of pure `OpDef`s, each of which
+/// expires after a fixed time-to-live. A cache with a capacity of zero is disabled.
+pub struct OpCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl OpCache {
+    /// Construct a new `OpCache` which holds at most `capacity` results for at most `ttl` each.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let inner = Inner {
+            entries: HashMap::with_capacity(capacity),
+            clock: 0,
+        };
+
+        Self {
+            capacity,
+            ttl,
+            inner: Mutex::new(inner),
+        }
+    }
+
+    /// Return `true` if this `OpCache` can hold any results.
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0 && self.ttl > Duration::default()
+    }
+
+    /// Return the memoized result with the given `key`, if present and not expired.
+    pub fn get(&self, key: &OpCacheKey) -> Option<Scalar> {
+        let mut inner = self.inner.lock().expect("op cache");
+        inner.clock += 1;
+        let now = inner.clock;
+
+        let expired = match inner.entries.get_mut(key) {
+            Some(entry) if entry.expires > Instant::now() => {
+                entry.last_used = now;
+                return Some(entry.result.clone());
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        if expired {
+            inner.entries.remove(key);
+        }
+
+        None
+    }
+
+    /// Memoize the given `result`, evicting the least-recently-used result if this cache is full.
+    pub fn insert(&self, key: OpCacheKey, result: Scalar) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut inner = self.inner.lock().expect("op cache");
+        inner.clock += 1;
+        let last_used = inner.clock;
+
+        if inner.entries.len() >= self.capacity && !inner.entries.contains_key(&key) {
+            let now = Instant::now();
+            inner.entries.retain(|_, entry| entry.expires > now);
+        }
+
+        if inner.entries.len() >= self.capacity && !inner.entries.contains_key(&key) {
+            let lru = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+
+            if let Some(lru) = lru {
+                inner.entries.remove(&lru);
+            }
+        }
+
+        let entry = Entry {
+            result,
+            expires: Instant::now() + self.ttl,
+            last_used,
+        };
+
+        inner.entries.insert(key, entry);
+    }
+
+    /// Return the memoized result with the given `key`, or else execute `call` and memoize its
+    /// result, if it's a [`Scalar`] which a later cache hit could return unchanged.
+    pub async fn call_memoized<F, Fut>(&self, key: OpCacheKey, call: F) -> TCResult<State>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = TCResult<State>>,
+    {
+        if let Some(result) = self.get(&key) {
+            debug!("return the memoized result of a pure Op");
+            return Ok(State::Scalar(result));
+        }
+
+        let result = call().await?;
+
+        if let State::Scalar(memo) = &result {
+            self.insert(key, memo.clone());
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::scalar::Value;
+
+    use super::*;
+
+    fn key(op: &'static str, input: &'static str) -> OpCacheKey {
+        (Bytes::from(op), Bytes::from(input))
+    }
+
+    #[tokio::test]
+    async fn test_call_memoized() {
+        let cache = OpCache::new(2, Duration::from_secs(60));
+        let executions = AtomicUsize::new(0);
+
+        let call = |input: &'static str| {
+            let executions = &executions;
+            cache.call_memoized(key("square", input), move || async move {
+                executions.fetch_add(1, Ordering::SeqCst);
+                let output = Value::String(input.to_string());
+                Ok(State::Scalar(Scalar::Value(output)))
+            })
+        };
+
+        call("2").await.unwrap();
+        call("2").await.unwrap();
+        assert_eq!(executions.load(Ordering::SeqCst), 1);
+
+        call("3").await.unwrap();
+        call("2").await.unwrap();
+        call("4").await.unwrap();
+        assert_eq!(executions.load(Ordering::SeqCst), 3);
+
+        // "3" was the least recently used, so it was evicted
+        call("2").await.unwrap();
+        call("3").await.unwrap();
+        assert_eq!(executions.load(Ordering::SeqCst), 4);
+
+        // a result which is not a Scalar is never memoized
+        let tuple = || {
+            cache.call_memoized(key("tuple", "1"), || async {
+                executions.fetch_add(1, Ordering::SeqCst);
+                Ok(State::Tuple(vec![].into()))
+            })
+        };
+
+        tuple().await.unwrap();
+        tuple().await.unwrap();
+        assert_eq!(executions.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn test_expire() {
+        let cache = OpCache::new(2, Duration::from_millis(1));
+        cache.insert(key("op", "1"), Scalar::Value(Value::None));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get(&key("op", "1")).is_none());
+
+        let disabled = OpCache::new(0, Duration::from_secs(60));
+        disabled.insert(key("op", "1"), Scalar::Value(Value::None));
+        assert!(disabled.get(&key("op", "1")).is_none());
+    }
+}
@@ -1,23 +1,38 @@
 //! User-defined [`OpDef`]s
 
+use std::collections::HashSet;
 use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use destream::de::{Decoder, Error, FromStream, MapAccess, Visitor};
 use destream::en::{EncodeMap, Encoder, IntoStream, ToStream};
+use futures::future::{self, TryFutureExt};
+use futures::stream::TryStreamExt;
 use log::debug;
-use safecast::TryCastInto;
+use safecast::{TryCastFrom, TryCastInto};
+use sha2::{Digest, Sha256};
 
 use tc_error::*;
 use tcgeneric::*;
 
-use crate::scalar::{Executor, Refer, Scalar};
-use crate::state::State;
+use crate::scalar::{Executor, IdRef, OpRef, Refer, Scalar, Subject};
+use crate::state::{State, StateType};
 use crate::txn::Txn;
 
 const PREFIX: PathLabel = path_label(&["state", "scalar", "op"]);
 
+/// The name of the field which holds the optional timeout of an encoded [`OpDef`], in seconds.
+const TIMEOUT: &str = "timeout";
+
+/// The name of the field which holds the optional declared parameter types of an encoded [`OpDef`].
+const INPUT: &str = "input";
+
+/// The name of the field which holds the optional declared result type of an encoded [`OpDef`].
+const OUTPUT: &str = "output";
+
 /// The [`Class`] of a user-defined [`OpDef`].
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum OpDefType {
@@ -87,37 +102,98 @@ pub type PostOp = Vec<(Id, Scalar)>;
 /// A DELETE handler.
 pub type DeleteOp = (Id, Vec<(Id, Scalar)>);
 
-/// A user-defined operation.
+/// The optional configuration of an [`OpDef`]: its maximum execution time,
+/// and the declared types of its parameters and its result.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct OpConfig {
+    pub timeout: Option<Duration>,
+    pub input: Option<Map<StateType>>,
+    pub output: Option<StateType>,
+}
+
+impl OpConfig {
+    /// Return `Ok` if each declared parameter is present in `params` with its declared type.
+    ///
+    /// Parameters which are not declared (e.g. the context of a method) are not checked.
+    pub fn validate_input(&self, params: &Map<State>) -> TCResult<()> {
+        let input = if let Some(input) = &self.input {
+            input
+        } else {
+            return Ok(());
+        };
+
+        for (name, class) in input.iter() {
+            let param = params
+                .get(name)
+                .ok_or_else(|| TCError::bad_request("missing required parameter", name))?;
+
+            if !is_instance(param, class) {
+                return Err(TCError::bad_request(
+                    format!("parameter {} should be a {}, not", name, class),
+                    param.class(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return `Ok` if the given `result` has the declared output type, if any.
+    pub fn validate_output(&self, result: &State) -> TCResult<()> {
+        match &self.output {
+            Some(class) if !is_instance(result, class) => Err(TCError::bad_request(
+                format!("Op should return a {}, not", class),
+                result.class(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        [
+            self.timeout.is_some(),
+            self.input.is_some(),
+            self.output.is_some(),
+        ]
+        .iter()
+        .filter(|declared| **declared)
+        .count()
+    }
+}
+
+/// A user-defined operation, with an optional [`OpConfig`].
 #[derive(Clone, Eq, PartialEq)]
 pub enum OpDef {
-    Get(GetOp),
-    Put(PutOp),
-    Post(PostOp),
-    Delete(DeleteOp),
+    Get(GetOp, OpConfig),
+    Put(PutOp, OpConfig),
+    Post(PostOp, OpConfig),
+    Delete(DeleteOp, OpConfig),
 }
 
 impl OpDef {
     pub fn dereference_self(self, path: &TCPathBuf) -> Self {
         match self {
-            Self::Get((key_name, form)) => Self::Get((key_name, dereference_self(form, path))),
-            Self::Put((key_name, value_name, form)) => {
-                Self::Put((key_name, value_name, dereference_self(form, path)))
+            Self::Get((key_name, form), config) => {
+                Self::Get((key_name, dereference_self(form, path)), config)
             }
-            Self::Post(form) => Self::Post(dereference_self(form, path)),
-            Self::Delete((key_name, form)) => {
-                Self::Delete((key_name, dereference_self(form, path)))
+            Self::Put((key_name, value_name, form), config) => {
+                Self::Put((key_name, value_name, dereference_self(form, path)), config)
+            }
+            Self::Post(form, config) => Self::Post(dereference_self(form, path), config),
+            Self::Delete((key_name, form), config) => {
+                Self::Delete((key_name, dereference_self(form, path)), config)
             }
         }
     }
 
     pub fn into_callable(self, state: State) -> TCResult<(Map<State>, Vec<(Id, Scalar)>)> {
         match self {
-            OpDef::Get((key_name, op_def)) | OpDef::Delete((key_name, op_def)) => {
+            OpDef::Get((key_name, op_def), _) | OpDef::Delete((key_name, op_def), _) => {
                 let mut params = Map::new();
                 params.insert(key_name, state);
                 Ok((params, op_def))
             }
-            OpDef::Put((key_name, value_name, op_def)) => {
+            OpDef::Put((key_name, value_name, op_def), _) => {
                 let (key, value) = state
                     .try_cast_into(|s| TCError::bad_request("invalid params for PUT Op", s))?;
 
@@ -127,7 +203,7 @@ impl OpDef {
 
                 Ok((params, op_def))
             }
-            OpDef::Post(op_def) => {
+            OpDef::Post(op_def, _) => {
                 let params = state
                     .try_cast_into(|s| TCError::bad_request("invalid params for POST Op", s))?;
 
@@ -138,24 +214,56 @@ impl OpDef {
 
     pub fn form(&self) -> impl Iterator<Item = &(Id, Scalar)> {
         match self {
-            Self::Get((_, form)) => form,
-            Self::Put((_, _, form)) => form,
-            Self::Post(form) => form,
-            Self::Delete((_, form)) => form,
+            Self::Get((_, form), _) => form,
+            Self::Put((_, _, form), _) => form,
+            Self::Post(form, _) => form,
+            Self::Delete((_, form), _) => form,
         }
         .iter()
     }
 
     pub fn last(&self) -> Option<&Id> {
         match self {
-            Self::Get((_, form)) => form.last(),
-            Self::Put((_, _, form)) => form.last(),
-            Self::Post(form) => form.last(),
-            Self::Delete((_, form)) => form.last(),
+            Self::Get((_, form), _) => form.last(),
+            Self::Put((_, _, form), _) => form.last(),
+            Self::Post(form, _) => form.last(),
+            Self::Delete((_, form), _) => form.last(),
         }
         .map(|(id, _)| id)
     }
 
+    /// Return the [`OpConfig`] of this `OpDef`.
+    pub fn config(&self) -> &OpConfig {
+        match self {
+            Self::Get(_, config) => config,
+            Self::Put(_, config) => config,
+            Self::Post(_, config) => config,
+            Self::Delete(_, config) => config,
+        }
+    }
+
+    /// Return the maximum time this `OpDef` may take to execute, if it declares one.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.config().timeout
+    }
+
+    /// Set the [`OpConfig`] of this `OpDef`.
+    pub fn with_config(self, config: OpConfig) -> Self {
+        match self {
+            Self::Get(op, _) => Self::Get(op, config),
+            Self::Put(op, _) => Self::Put(op, config),
+            Self::Post(op, _) => Self::Post(op, config),
+            Self::Delete(op, _) => Self::Delete(op, config),
+        }
+    }
+
+    /// Return `true` if any step of this `OpDef` references state outside its own scope.
+    ///
+    /// See [`Refer::is_external`].
+    pub fn is_external(&self) -> bool {
+        self.form().any(|(_, provider)| provider.is_external())
+    }
+
     pub fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         self.form()
             .map(|(_, provider)| provider)
@@ -164,50 +272,268 @@ impl OpDef {
 
     pub fn into_form(self) -> Vec<(Id, Scalar)> {
         match self {
-            Self::Get((_, form)) => form,
-            Self::Put((_, _, form)) => form,
-            Self::Post(form) => form,
-            Self::Delete((_, form)) => form,
+            Self::Get((_, form), _) => form,
+            Self::Put((_, _, form), _) => form,
+            Self::Post(form, _) => form,
+            Self::Delete((_, form), _) => form,
         }
     }
 
     pub fn is_write(&self) -> bool {
         match self {
-            Self::Get(_) => false,
-            Self::Put(_) => true,
-            Self::Post(_) => false,
-            Self::Delete(_) => true,
+            Self::Get(..) => false,
+            Self::Put(..) => true,
+            Self::Post(..) => false,
+            Self::Delete(..) => true,
+        }
+    }
+
+    /// Return `true` if this is a GET or POST `OpDef` none of whose steps references a write op.
+    pub fn is_read_only(&self) -> bool {
+        !self.is_write() && !self.form().any(|(_, provider)| provider.is_write())
+    }
+
+    /// Return `true` if the result of this `OpDef` depends only on its inputs, i.e. it is
+    /// read-only and does not reference any state outside its own scope.
+    ///
+    /// Only a pure `OpDef` is safe to memoize, since its result does not depend on the caller's
+    /// permissions or on the state of any transaction.
+    pub fn is_pure(&self) -> bool {
+        self.is_read_only() && !self.is_external()
+    }
+
+    /// Return the SHA-256 hash of the canonical encoding of this `OpDef`.
+    pub async fn canonical_hash(&self) -> TCResult<Bytes> {
+        let encoded = encode(self).await?;
+        let digest = Sha256::digest(&encoded);
+        Ok(Bytes::from(digest.to_vec()))
+    }
+
+    /// Return the names of the parameters this `OpDef` expects, i.e. the [`Id`]s which its
+    /// form references but does not define.
+    pub fn params(&self) -> HashSet<Id> {
+        let mut deps = HashSet::new();
+        for (_, provider) in self.form() {
+            provider.requires(&mut deps);
+        }
+
+        for (id, _) in self.form() {
+            deps.remove(id);
         }
+
+        deps
+    }
+
+    /// Check that this `OpDef` is well-formed, i.e. that its parameter names are distinct
+    /// and that each step of its form has a unique name which does not shadow a parameter.
+    pub fn validate(&self) -> TCResult<()> {
+        let mut names = HashSet::new();
+
+        let params = match self {
+            Self::Get((key_name, _), _) | Self::Delete((key_name, _), _) => vec![key_name],
+            Self::Put((key_name, value_name, _), _) => vec![key_name, value_name],
+            Self::Post(..) => vec![],
+        };
+
+        for name in params {
+            if !names.insert(name) {
+                return Err(TCError::bad_request(
+                    format!("{} has a duplicate parameter", self),
+                    name,
+                ));
+            }
+        }
+
+        for (id, _) in self.form() {
+            if !names.insert(id) {
+                return Err(TCError::bad_request(format!("{} redefines", self), id));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bind some of the parameters of a POST `OpDef`, returning a new POST `OpDef`
+    /// which expects only the rest.
+    pub fn partial(self, bound: Map<Scalar>) -> TCResult<OpDef> {
+        if self.class() != OpDefType::Post {
+            return Err(TCError::bad_request(
+                "partial application requires a POST Op, not",
+                self,
+            ));
+        }
+
+        let params = self.params();
+        for name in bound.keys() {
+            if !params.contains(name) {
+                return Err(TCError::bad_request(
+                    "cannot bind a value to unknown parameter",
+                    name,
+                ));
+            }
+        }
+
+        // the bound parameters are no longer part of the input schema
+        let mut config = self.config().clone();
+        if let Some(input) = &mut config.input {
+            for name in bound.keys() {
+                input.remove(name);
+            }
+        }
+
+        let form = bound.into_iter().chain(self.into_form()).collect();
+        Ok(Self::Post(form, config))
+    }
+
+    /// Compose this `OpDef` with `next`, returning a new `OpDef` with the same signature as this
+    /// one, which passes the result of this `OpDef` to `next` and returns the result of `next`.
+    ///
+    /// `next` must be a GET `OpDef`, which receives the result as its key,
+    /// or a POST `OpDef` which expects exactly one parameter.
+    pub fn then(self, next: OpDef) -> TCResult<OpDef> {
+        let result = match self.last() {
+            Some(result) => IdRef::from(result.clone()),
+            None => {
+                return Err(TCError::bad_request(
+                    "cannot compose an Op which returns nothing with",
+                    next,
+                ))
+            }
+        };
+
+        let mut names: HashSet<Id> = self.form().map(|(id, _)| id.clone()).collect();
+        names.extend(self.params());
+
+        let next_name = unique_name(&names, "_then")?;
+        names.insert(next_name.clone());
+        let result_name = unique_name(&names, "_then_result")?;
+
+        let subject = Subject::from(IdRef::from(next_name.clone()));
+        let call = match &next {
+            Self::Get(..) => OpRef::Get((subject, result.into())),
+            Self::Post(..) => {
+                let mut params = next.params().into_iter();
+                let param = match (params.next(), params.next()) {
+                    (Some(param), None) => param,
+                    _ => {
+                        return Err(TCError::bad_request(
+                            "the POST Op to compose with must expect exactly one parameter",
+                            &next,
+                        ))
+                    }
+                };
+
+                let params = std::iter::once((param, result.into())).collect();
+                OpRef::Post((subject, params))
+            }
+            other => {
+                return Err(TCError::bad_request(
+                    "cannot compose an Op with a write Op",
+                    other,
+                ))
+            }
+        };
+
+        let next_output = next.config().output;
+        let steps = vec![(next_name, Scalar::Op(next)), (result_name, call.into())];
+
+        let composed = match self {
+            Self::Get((key_name, mut form), config) => {
+                form.extend(steps);
+                Self::Get((key_name, form), config)
+            }
+            Self::Put((key_name, value_name, mut form), config) => {
+                form.extend(steps);
+                Self::Put((key_name, value_name, form), config)
+            }
+            Self::Post(mut form, config) => {
+                form.extend(steps);
+                Self::Post(form, config)
+            }
+            Self::Delete((key_name, mut form), config) => {
+                form.extend(steps);
+                Self::Delete((key_name, form), config)
+            }
+        };
+
+        // the composed Op returns the result of `next`
+        let mut config = composed.config().clone();
+        config.output = next_output;
+        Ok(composed.with_config(config))
     }
 
     pub fn reference_self(self, path: &TCPathBuf) -> Self {
         match self {
-            Self::Get((key_name, form)) => Self::Get((key_name, reference_self(form, path))),
-            Self::Put((key_name, value_name, form)) => {
-                Self::Put((key_name, value_name, reference_self(form, path)))
+            Self::Get((key_name, form), config) => {
+                Self::Get((key_name, reference_self(form, path)), config)
+            }
+            Self::Put((key_name, value_name, form), config) => {
+                Self::Put((key_name, value_name, reference_self(form, path)), config)
+            }
+            Self::Post(form, config) => Self::Post(reference_self(form, path), config),
+            Self::Delete((key_name, form), config) => {
+                Self::Delete((key_name, reference_self(form, path)), config)
             }
-            Self::Post(form) => Self::Post(reference_self(form, path)),
-            Self::Delete((key_name, form)) => Self::Delete((key_name, reference_self(form, path))),
         }
     }
 
+    /// Execute the given `op_def` form with the given `context`, after validating the `context`
+    /// and before validating the result against the declared schema of the given `config`,
+    /// and aborting with a timeout error if `config` has a timeout and execution takes longer.
     pub async fn call<S: Into<State>, I: IntoIterator<Item = (Id, State)>>(
         op_def: Vec<(Id, S)>,
         txn: &Txn,
         context: I,
+        config: &OpConfig,
     ) -> TCResult<State> {
+        let context: Map<State> = context.into_iter().collect();
+        config.validate_input(&context)?;
+
         let capture = if let Some((id, _)) = op_def.last() {
             id.clone()
         } else {
-            return Ok(State::default());
+            let result = State::default();
+            config.validate_output(&result)?;
+            return Ok(result);
         };
 
         let context = context
             .into_iter()
             .chain(op_def.into_iter().map(|(id, s)| (id, s.into())));
 
-        Executor::<Self>::new(txn, None, context)
+        let result = Executor::<Self>::new(txn, None, context)?
+            .with_timeout(config.timeout)
             .capture(capture)
+            .await?;
+
+        config.validate_output(&result)?;
+        Ok(result)
+    }
+
+    /// Call this `OpDef` with the given `args` (see [`Self::into_callable`]).
+    ///
+    /// If this `OpDef` is pure (see [`Self::is_pure`]) and the host's [`OpCache`] is enabled,
+    /// a recent result of calling it with the same `args` is returned without executing it again.
+    pub async fn call_memoized(self, txn: &Txn, args: State) -> TCResult<State> {
+        let config = self.config().clone();
+        let cache = txn.op_cache();
+
+        if !cache.is_enabled() || !self.is_pure() {
+            let (params, form) = self.into_callable(args)?;
+            return Self::call(form, txn, params, &config).await;
+        }
+
+        let hash = self.canonical_hash().await?;
+        let (params, form) = self.into_callable(args)?;
+
+        let inputs = match Scalar::opt_cast_from(State::Map(params.clone())) {
+            Some(inputs) => inputs,
+            None => return Self::call(form, txn, params, &config).await,
+        };
+
+        let key = (hash, encode(&inputs).await?);
+        cache
+            .call_memoized(key, || Self::call(form, txn, params, &config))
             .await
     }
 }
@@ -217,14 +543,27 @@ impl Instance for OpDef {
 
     fn class(&self) -> OpDefType {
         match self {
-            Self::Get(_) => OpDefType::Get,
-            Self::Put(_) => OpDefType::Put,
-            Self::Post(_) => OpDefType::Post,
-            Self::Delete(_) => OpDefType::Delete,
+            Self::Get(..) => OpDefType::Get,
+            Self::Put(..) => OpDefType::Put,
+            Self::Post(..) => OpDefType::Post,
+            Self::Delete(..) => OpDefType::Delete,
         }
     }
 }
 
+async fn encode<'en, T: ToStream<'en> + 'en>(data: &'en T) -> TCResult<Bytes> {
+    let encoded = tbon::en::encode(data).map_err(TCError::internal)?;
+
+    encoded
+        .map_err(TCError::internal)
+        .try_fold(Vec::new(), |mut buffer, chunk| {
+            buffer.extend_from_slice(&chunk);
+            future::ready(Ok(buffer))
+        })
+        .map_ok(Bytes::from)
+        .await
+}
+
 pub struct OpDefVisitor;
 
 impl OpDefVisitor {
@@ -239,22 +578,62 @@ impl OpDefVisitor {
                 debug!("deserialize GET Op");
 
                 let op = map.next_value(()).await?;
-                Ok(OpDef::Get(op))
+                Ok(OpDef::Get(op, OpConfig::default()))
             }
             ODT::Put => {
                 let op = map.next_value(()).await?;
-                Ok(OpDef::Put(op))
+                Ok(OpDef::Put(op, OpConfig::default()))
             }
             ODT::Post => {
                 let op = map.next_value(()).await?;
-                Ok(OpDef::Post(op))
+                Ok(OpDef::Post(op, OpConfig::default()))
             }
             ODT::Delete => {
                 let op = map.next_value(()).await?;
-                Ok(OpDef::Delete(op))
+                Ok(OpDef::Delete(op, OpConfig::default()))
             }
         }
     }
+
+    /// Decode the optional configuration fields (see [`OpConfig`]) which may follow the
+    /// definition of an [`OpDef`].
+    pub async fn visit_config<A: MapAccess>(map: &mut A) -> Result<OpConfig, A::Error> {
+        let mut config = OpConfig::default();
+
+        while let Some(key) = map.next_key::<String>(()).await? {
+            match key.as_str() {
+                TIMEOUT => {
+                    let seconds: f64 = map.next_value(()).await?;
+                    if seconds.is_finite() && seconds >= 0. {
+                        config.timeout = Some(Duration::from_secs_f64(seconds));
+                    } else {
+                        return Err(A::Error::custom(format!("invalid Op timeout: {}", seconds)));
+                    }
+                }
+                INPUT => {
+                    let input: Vec<(Id, String)> = map.next_value(()).await?;
+                    let input = input
+                        .into_iter()
+                        .map(|(name, class)| decode_class(&class).map(|class| (name, class)))
+                        .collect::<Result<Map<StateType>, A::Error>>()?;
+
+                    config.input = Some(input);
+                }
+                OUTPUT => {
+                    let output: String = map.next_value(()).await?;
+                    config.output = Some(decode_class(&output)?);
+                }
+                other => {
+                    return Err(A::Error::custom(format!(
+                        "unexpected field in Op definition: {}",
+                        other
+                    )))
+                }
+            }
+        }
+
+        Ok(config)
+    }
 }
 
 #[async_trait]
@@ -272,7 +651,9 @@ impl Visitor for OpDefVisitor {
         let class = TCPathBuf::from_str(&class).map_err(A::Error::custom)?;
         let class = OpDefType::from_path(&class).ok_or_else(err)?;
 
-        Self::visit_map_value(class, &mut map).await
+        let op_def = Self::visit_map_value(class, &mut map).await?;
+        let config = Self::visit_config(&mut map).await?;
+        Ok(op_def.with_config(config))
     }
 }
 
@@ -287,16 +668,18 @@ impl FromStream for OpDef {
 
 impl<'en> ToStream<'en> for OpDef {
     fn to_stream<E: Encoder<'en>>(&'en self, e: E) -> Result<E::Ok, E::Error> {
-        let class = self.class().to_string();
-        let mut map = e.encode_map(Some(1))?;
+        let class = self.class().path().to_string();
+        let config = self.config();
+        let mut map = e.encode_map(Some(1 + config.len()))?;
 
         match self {
-            Self::Get(def) => map.encode_entry(class, def),
-            Self::Put(def) => map.encode_entry(class, def),
-            Self::Post(def) => map.encode_entry(class, def),
-            Self::Delete(def) => map.encode_entry(class, def),
+            Self::Get(def, _) => map.encode_entry(class, def),
+            Self::Put(def, _) => map.encode_entry(class, def),
+            Self::Post(def, _) => map.encode_entry(class, def),
+            Self::Delete(def, _) => map.encode_entry(class, def),
         }?;
 
+        encode_config(&mut map, config)?;
         map.end()
     }
 }
@@ -304,15 +687,17 @@ impl<'en> ToStream<'en> for OpDef {
 impl<'en> IntoStream<'en> for OpDef {
     fn into_stream<E: Encoder<'en>>(self, e: E) -> Result<E::Ok, E::Error> {
         let class = self.class().path().to_string();
-        let mut map = e.encode_map(Some(1))?;
+        let config = self.config().clone();
+        let mut map = e.encode_map(Some(1 + config.len()))?;
 
         match self {
-            Self::Get(def) => map.encode_entry(class, def),
-            Self::Put(def) => map.encode_entry(class, def),
-            Self::Post(def) => map.encode_entry(class, def),
-            Self::Delete(def) => map.encode_entry(class, def),
+            Self::Get(def, _) => map.encode_entry(class, def),
+            Self::Put(def, _) => map.encode_entry(class, def),
+            Self::Post(def, _) => map.encode_entry(class, def),
+            Self::Delete(def, _) => map.encode_entry(class, def),
         }?;
 
+        encode_config(&mut map, &config)?;
         map.end()
     }
 }
@@ -326,14 +711,47 @@ impl fmt::Debug for OpDef {
 impl fmt::Display for OpDef {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Get(_) => write!(f, "GET Op"),
-            Self::Put(_) => write!(f, "PUT Op"),
-            Self::Post(_) => write!(f, "POST Op"),
-            Self::Delete(_) => write!(f, "DELETE Op"),
+            Self::Get(..) => write!(f, "GET Op"),
+            Self::Put(..) => write!(f, "PUT Op"),
+            Self::Post(..) => write!(f, "POST Op"),
+            Self::Delete(..) => write!(f, "DELETE Op"),
         }
     }
 }
 
+fn encode_config<'en, M: EncodeMap<'en>>(map: &mut M, config: &OpConfig) -> Result<(), M::Error> {
+    if let Some(timeout) = config.timeout {
+        map.encode_entry(TIMEOUT, timeout.as_secs_f64())?;
+    }
+
+    if let Some(input) = &config.input {
+        let input = input
+            .iter()
+            .map(|(name, class)| (name.to_string(), class.path().to_string()))
+            .collect::<Vec<(String, String)>>();
+
+        map.encode_entry(INPUT, input)?;
+    }
+
+    if let Some(output) = &config.output {
+        map.encode_entry(OUTPUT, output.path().to_string())?;
+    }
+
+    Ok(())
+}
+
+fn decode_class<E: Error>(class: &str) -> Result<StateType, E> {
+    TCPathBuf::from_str(class)
+        .ok()
+        .and_then(|path| StateType::from_path(&path))
+        .ok_or_else(|| E::custom(format!("invalid type in Op schema: {}", class)))
+}
+
+/// Return `true` if `state` is an instance of `class` or one of its subclasses.
+fn is_instance(state: &State, class: &StateType) -> bool {
+    state.class().path().starts_with(&class.path())
+}
+
 pub fn dereference_self(form: Vec<(Id, Scalar)>, path: &TCPathBuf) -> Vec<(Id, Scalar)> {
     form.into_iter()
         .map(|(id, scalar)| (id, scalar.dereference_self(path)))
@@ -345,3 +763,15 @@ pub fn reference_self(form: Vec<(Id, Scalar)>, path: &TCPathBuf) -> Vec<(Id, Sca
         .map(|(id, scalar)| (id, scalar.reference_self(path)))
         .collect()
 }
+
+/// Return `base`, or `base` with the lowest numeric suffix, which is not already in `names`.
+fn unique_name(names: &HashSet<Id>, base: &str) -> TCResult<Id> {
+    let mut name: Id = base.parse()?;
+    let mut i = 0;
+    while names.contains(&name) {
+        i += 1;
+        name = format!("{}_{}", base, i).parse()?;
+    }
+
+    Ok(name)
+}
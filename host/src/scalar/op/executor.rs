@@ -1,23 +1,28 @@
 //! An executor for an `OpDef`
 
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
-use futures::future::FutureExt;
+use futures::future::{Future, FutureExt};
 use futures::stream::{FuturesUnordered, StreamExt};
 use log::debug;
 
 use tc_error::*;
-use tcgeneric::{Id, Instance, Map};
+use tcgeneric::{label, Id, Instance, Label, Map};
 
 use crate::route::Public;
 use crate::scalar::{Refer, Scope};
 use crate::state::State;
 use crate::txn::Txn;
 
+/// The reserved name of the [`Txn`]'s application-defined metadata in the execution scope.
+pub const META: Label = label("meta");
+
 /// An `OpDef` executor.
 pub struct Executor<'a, T> {
     txn: &'a Txn,
     scope: Scope<'a, T>,
+    timeout: Option<Duration>,
 }
 
 impl<'a, T: Instance + Public> Executor<'a, T> {
@@ -26,9 +31,9 @@ impl<'a, T: Instance + Public> Executor<'a, T> {
         txn: &'a Txn,
         subject: Option<&'a T>,
         data: I,
-    ) -> Self {
+    ) -> TCResult<Self> {
         let scope = Scope::new(subject, data);
-        Self { txn, scope }
+        Self::from_scope(txn, scope)
     }
 
     pub fn with_context<S: Into<State>, I: IntoIterator<Item = (Id, S)>>(
@@ -36,19 +41,60 @@ impl<'a, T: Instance + Public> Executor<'a, T> {
         subject: Option<&'a T>,
         context: Map<State>,
         iter: I,
-    ) -> Self {
+    ) -> TCResult<Self> {
         let scope = Scope::with_context(subject, context, iter);
-        Self { txn, scope }
+        Self::from_scope(txn, scope)
     }
 
-    pub fn from_scope(txn: &'a Txn, scope: Scope<'a, T>) -> Self {
-        Self { txn, scope }
+    /// Construct a new `Executor` from the given [`Scope`].
+    ///
+    /// Returns a `BadRequest` error if the scope already defines [`META`], which is reserved.
+    pub fn from_scope(txn: &'a Txn, mut scope: Scope<'a, T>) -> TCResult<Self> {
+        if scope.contains_key(&Id::from(META)) {
+            return Err(TCError::bad_request(
+                "cannot define a parameter or step with the reserved name",
+                META,
+            ));
+        }
+
+        let meta = txn
+            .context_metadata()
+            .iter()
+            .map(|(name, value)| (name.clone(), State::from(value.clone())))
+            .collect::<Map<State>>();
+
+        scope.insert(META.into(), State::Map(meta));
+
+        Ok(Self {
+            txn,
+            scope,
+            timeout: None,
+        })
+    }
+
+    /// Abort execution with a timeout error if it takes longer than the given `timeout`, if any.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
     }
 
     /// Resolve the state of the variable `capture`, including any of its dependencies.
-    pub async fn capture(mut self, capture: Id) -> TCResult<State> {
+    pub async fn capture(self, capture: Id) -> TCResult<State> {
+        let timeout = self.timeout;
+        let name = capture.clone();
+        deadline(self.resolve(capture), timeout, &name).await
+    }
+
+    async fn resolve(mut self, capture: Id) -> TCResult<State> {
         debug!("execute op & capture {}", capture);
 
+        let max_steps = self.txn.max_op_steps();
+        let mut steps = 0;
+
+        // nested op calls each hold their own scope, so the budget is shared by the whole txn
+        let _reserved = OpStates::reserve(self.txn, self.scope.len())
+            .map_err(|cause| cause.consume(format!("error resolving {}", capture)))?;
+
         while self.scope.resolve_id(&capture)?.is_ref() {
             let mut pending = Vec::with_capacity(self.scope.len());
             let mut unvisited = VecDeque::with_capacity(self.scope.len());
@@ -91,6 +137,14 @@ impl<'a, T: Instance + Public> Executor<'a, T> {
                 ));
             }
 
+            steps += pending.len();
+            if steps > max_steps {
+                return Err(TCError::bad_request(
+                    format!("op exceeded the maximum of {} steps resolving", max_steps),
+                    capture,
+                ));
+            }
+
             let mut resolved = HashMap::with_capacity(pending.len());
             {
                 let mut providers = FuturesUnordered::new();
@@ -118,3 +172,63 @@ impl<'a, T: Instance + Public> Executor<'a, T> {
             .ok_or_else(|| TCError::not_found(capture))
     }
 }
+
+/// Await the given `op`, or return a timeout error if it takes longer than `timeout`, if any.
+async fn deadline<F, T>(op: F, timeout: Option<Duration>, capture: &Id) -> TCResult<T>
+where
+    F: Future<Output = TCResult<T>>,
+{
+    if let Some(timeout) = timeout {
+        tokio::time::timeout(timeout, op).await.map_err(|_| {
+            TCError::timeout(format!(
+                "Op exceeded its timeout of {:?} resolving {}",
+                timeout, capture
+            ))
+        })?
+    } else {
+        op.await
+    }
+}
+
+/// A reservation of intermediate states from the budget of a [`Txn`], released when dropped.
+struct OpStates<'a> {
+    txn: &'a Txn,
+    count: usize,
+}
+
+impl<'a> OpStates<'a> {
+    fn reserve(txn: &'a Txn, count: usize) -> TCResult<Self> {
+        txn.reserve_op_states(count)?;
+        Ok(Self { txn, count })
+    }
+}
+
+impl<'a> Drop for OpStates<'a> {
+    fn drop(&mut self) {
+        self.txn.release_op_states(self.count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deadline() {
+        let name: Id = "result".parse().unwrap();
+        let timeout = Some(Duration::from_millis(1));
+
+        // an op which never finishes always exceeds its timeout
+        let result = deadline(future::pending::<TCResult<()>>(), timeout, &name).await;
+        assert!(result.unwrap_err().code() == ErrorType::Timeout);
+
+        // an op which is already finished is never cut short, even by a timeout of zero
+        let result = deadline(future::ready(Ok(())), Some(Duration::from_secs(0)), &name).await;
+        assert!(result.is_ok());
+
+        let result = deadline(future::ready(Ok(())), None, &name).await;
+        assert!(result.is_ok());
+    }
+}
@@ -1,7 +1,9 @@
 //! User-defined [`OpDef`]s.
 
+pub use cache::*;
 pub use def::*;
 pub use executor::*;
 
+mod cache;
 mod def;
 mod executor;
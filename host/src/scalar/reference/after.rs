@@ -35,11 +35,19 @@ impl Refer for After {
         }
     }
 
+    fn is_external(&self) -> bool {
+        self.when.is_external() || self.then.is_external()
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         self.when.is_inter_service_write(cluster_path)
             || self.then.is_inter_service_write(cluster_path)
     }
 
+    fn is_write(&self) -> bool {
+        self.when.is_write() || self.then.is_write()
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         Self {
             when: self.when.reference_self(path),
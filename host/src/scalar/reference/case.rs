@@ -46,6 +46,12 @@ impl Refer for Case {
         }
     }
 
+    fn is_external(&self) -> bool {
+        self.cond.is_external()
+            || self.switch.iter().any(|scalar| scalar.is_external())
+            || self.case.iter().any(|scalar| scalar.is_external())
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         self.cond.is_inter_service_write(cluster_path)
             || self
@@ -58,6 +64,12 @@ impl Refer for Case {
                 .any(|scalar| scalar.is_inter_service_write(cluster_path))
     }
 
+    fn is_write(&self) -> bool {
+        self.cond.is_write()
+            || self.switch.iter().any(|scalar| scalar.is_write())
+            || self.case.iter().any(|scalar| scalar.is_write())
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         Self {
             cond: self.cond.reference_self(path),
@@ -14,7 +14,7 @@ use tc_error::*;
 use tcgeneric::{Id, Instance, Label, PathSegment, TCPathBuf};
 
 use crate::route::Public;
-use crate::scalar::{Scope, Value, SELF};
+use crate::scalar::{Scope, Value, META, SELF};
 use crate::state::State;
 use crate::txn::Txn;
 
@@ -48,10 +48,18 @@ impl Refer for IdRef {
         }
     }
 
+    fn is_external(&self) -> bool {
+        self.to == SELF || self.to == META
+    }
+
     fn is_inter_service_write(&self, _cluster_path: &[PathSegment]) -> bool {
         false
     }
 
+    fn is_write(&self) -> bool {
+        false
+    }
+
     fn reference_self(self, _path: &TCPathBuf) -> Self {
         self
     }
@@ -65,10 +73,15 @@ impl Refer for IdRef {
     async fn resolve<'a, T: Instance + Public>(
         self,
         context: &'a Scope<'a, T>,
-        _txn: &'a Txn,
+        txn: &'a Txn,
     ) -> TCResult<State> {
         debug!("IdRef::resolve {}", self);
-        context.resolve_id(self.id())
+
+        match context.resolve_id(self.id())? {
+            // a lazy State passed by reference is about to be consumed, so compute it now
+            State::Lazy(lazy) => lazy.force(txn.clone()).await,
+            state => Ok(state),
+        }
     }
 }
 
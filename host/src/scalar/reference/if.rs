@@ -6,7 +6,7 @@ use std::fmt;
 use async_trait::async_trait;
 use destream::{de, en};
 use log::debug;
-use safecast::{Match, TryCastFrom, TryCastInto};
+use safecast::{TryCastFrom, TryCastInto};
 
 use tc_error::*;
 use tcgeneric::{Id, Instance, PathSegment, TCPathBuf};
@@ -16,12 +16,15 @@ use crate::scalar::{Number, Scalar, Scope, Value};
 use crate::state::State;
 use crate::txn::Txn;
 
-use super::{Refer, TCRef};
+use super::Refer;
 
 /// A conditional reference.
+///
+/// Only the branch selected by `cond` is returned to be resolved, so the side effects of the
+/// other branch never happen.
 #[derive(Clone, Eq, PartialEq)]
 pub struct IfRef {
-    cond: TCRef,
+    cond: Scalar,
     then: Scalar,
     or_else: Scalar,
 }
@@ -36,12 +39,20 @@ impl Refer for IfRef {
         }
     }
 
+    fn is_external(&self) -> bool {
+        self.cond.is_external() || self.then.is_external() || self.or_else.is_external()
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         self.cond.is_inter_service_write(cluster_path)
             || self.then.is_inter_service_write(cluster_path)
             || self.or_else.is_inter_service_write(cluster_path)
     }
 
+    fn is_write(&self) -> bool {
+        self.cond.is_write() || self.then.is_write() || self.or_else.is_write()
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         Self {
             cond: self.cond.reference_self(path),
@@ -81,10 +92,14 @@ impl Refer for IfRef {
 
 impl TryCastFrom<Scalar> for IfRef {
     fn can_cast_from(scalar: &Scalar) -> bool {
-        scalar.matches::<(TCRef, Scalar, Scalar)>()
+        matches!(scalar, Scalar::Tuple(tuple) if tuple.len() == 3)
     }
 
     fn opt_cast_from(scalar: Scalar) -> Option<Self> {
+        if !Self::can_cast_from(&scalar) {
+            return None;
+        }
+
         scalar.opt_cast_into().map(|(cond, then, or_else)| Self {
             cond,
             then,
@@ -99,7 +114,7 @@ impl de::FromStream for IfRef {
 
     async fn from_stream<D: de::Decoder>(context: (), decoder: &mut D) -> Result<Self, D::Error> {
         let (cond, then, or_else) =
-            <(TCRef, Scalar, Scalar) as de::FromStream>::from_stream(context, decoder).await?;
+            <(Scalar, Scalar, Scalar) as de::FromStream>::from_stream(context, decoder).await?;
 
         Ok(Self {
             cond,
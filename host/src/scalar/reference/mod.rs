@@ -44,9 +44,16 @@ pub trait Refer {
     /// This is used to control whether or not an OpDef will be replicated.
     fn dereference_self(self, path: &TCPathBuf) -> Self;
 
+    /// Return `true` if this references any state outside the scope of its op, i.e. a link,
+    /// `$self`, or the request metadata `$meta`, so that its result may differ between requests.
+    fn is_external(&self) -> bool;
+
     /// Return `true` if this references a write operation to a cluster other than the path given.
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool;
 
+    /// Return `true` if this references a write operation (i.e. a PUT or DELETE op).
+    fn is_write(&self) -> bool;
+
     /// Replace the given relative path with "$self".
     ///
     /// This is used to control whether or not an OpDef will be replicated.
@@ -171,6 +178,17 @@ impl Refer for TCRef {
         }
     }
 
+    fn is_external(&self) -> bool {
+        match self {
+            Self::After(after) => after.is_external(),
+            Self::Case(case) => case.is_external(),
+            Self::Id(id_ref) => id_ref.is_external(),
+            Self::If(if_ref) => if_ref.is_external(),
+            Self::Op(op_ref) => op_ref.is_external(),
+            Self::With(with) => with.is_external(),
+        }
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         match self {
             Self::After(after) => after.is_inter_service_write(cluster_path),
@@ -182,6 +200,17 @@ impl Refer for TCRef {
         }
     }
 
+    fn is_write(&self) -> bool {
+        match self {
+            Self::After(after) => after.is_write(),
+            Self::Case(case) => case.is_write(),
+            Self::Id(id_ref) => id_ref.is_write(),
+            Self::If(if_ref) => if_ref.is_write(),
+            Self::Op(op_ref) => op_ref.is_write(),
+            Self::With(with) => with.is_write(),
+        }
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         match self {
             Self::After(after) => {
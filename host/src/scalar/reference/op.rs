@@ -93,6 +93,13 @@ impl Subject {
         }
     }
 
+    fn is_external(&self) -> bool {
+        match self {
+            Self::Link(_) => true,
+            Self::Ref(id_ref, _) => id_ref.is_external(),
+        }
+    }
+
     fn is_self(&self) -> bool {
         match self {
             Self::Ref(id_ref, _) => id_ref.id() == &SELF,
@@ -336,6 +343,19 @@ impl Refer for OpRef {
         }
     }
 
+    fn is_external(&self) -> bool {
+        match self {
+            Self::Get((subject, key)) => subject.is_external() || key.is_external(),
+            Self::Put((subject, key, value)) => {
+                subject.is_external() || key.is_external() || value.is_external()
+            }
+            Self::Post((subject, params)) => {
+                subject.is_external() || params.values().any(|param| param.is_external())
+            }
+            Self::Delete((subject, key)) => subject.is_external() || key.is_external(),
+        }
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         let subject = match self {
             Self::Put((Subject::Link(link), _, _)) => Some(link),
@@ -350,6 +370,15 @@ impl Refer for OpRef {
         }
     }
 
+    fn is_write(&self) -> bool {
+        match self {
+            Self::Get((_, key)) => key.is_write(),
+            Self::Put(_) => true,
+            Self::Post((_, params)) => params.values().any(|param| param.is_write()),
+            Self::Delete(_) => true,
+        }
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         match self {
             Self::Get((subject, key)) if subject.is_self() => {
@@ -37,10 +37,18 @@ impl Refer for With {
         }
     }
 
+    fn is_external(&self) -> bool {
+        self.op.is_external()
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         self.op.is_inter_service_write(cluster_path)
     }
 
+    fn is_write(&self) -> bool {
+        !self.op.is_read_only()
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         Self {
             capture: self.capture,
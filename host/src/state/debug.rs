@@ -0,0 +1,241 @@
+//! A human-readable description of a [`State`], used for debugging and introspection.
+
+use std::fmt;
+use std::pin::Pin;
+
+use futures::future::Future;
+
+use tc_btree::BTreeInstance;
+use tc_error::*;
+use tc_table::TableInstance;
+#[cfg(feature = "tensor")]
+use tc_tensor::{TensorAccess, TensorType};
+use tc_transact::fs::File;
+use tc_transact::Transaction;
+use tcgeneric::Instance;
+
+use crate::chain::{ChainInstance, Subject, SUBJECT};
+use crate::collection::Collection;
+use crate::txn::Txn;
+
+use super::State;
+
+/// The maximum length, in bytes, of the description returned by [`State::describe`].
+pub const MAX_DESCRIPTION_LEN: usize = 16_384;
+
+const INDENT: &str = "  ";
+const TRUNCATED: &str = "...";
+
+type Describe<'a> = Pin<Box<dyn Future<Output = TCResult<()>> + Send + 'a>>;
+
+impl State {
+    /// Construct a human-readable, indented description of the structure of this `State`.
+    ///
+    /// Collections are described by their class, schema, and size, without reading their contents.
+    /// The output is truncated to at most [`MAX_DESCRIPTION_LEN`] bytes.
+    pub async fn describe(&self, txn: &Txn) -> TCResult<String> {
+        let mut out = Description::default();
+        describe_state(self, txn, 0, &mut out).await?;
+        Ok(out.into())
+    }
+}
+
+#[derive(Default)]
+struct Description {
+    text: String,
+    truncated: bool,
+}
+
+impl Description {
+    fn is_full(&self) -> bool {
+        self.truncated
+    }
+
+    fn line<T: fmt::Display>(&mut self, indent: usize, line: T) {
+        for line in line.to_string().lines() {
+            if self.truncated {
+                return;
+            }
+
+            let line = format!("{}{}\n", INDENT.repeat(indent), line);
+            if self.text.len() + line.len() > MAX_DESCRIPTION_LEN - TRUNCATED.len() {
+                self.truncated = true;
+                self.text.push_str(TRUNCATED);
+            } else {
+                self.text.push_str(&line);
+            }
+        }
+    }
+}
+
+impl From<Description> for String {
+    fn from(description: Description) -> Self {
+        description.text
+    }
+}
+
+fn describe_state<'a>(
+    state: &'a State,
+    txn: &'a Txn,
+    indent: usize,
+    out: &'a mut Description,
+) -> Describe<'a> {
+    Box::pin(async move {
+        match state {
+            State::Chain(chain) => {
+                out.line(indent, format!("{} chain", chain.class()));
+                describe_subject(chain.subject(), txn, indent + 1, out).await
+            }
+            State::Collection(collection) => {
+                describe_collection(collection, txn, indent, out).await
+            }
+            State::Map(map) => {
+                out.line(indent, format!("Map ({} entries)", map.len()));
+                for (name, state) in map.iter() {
+                    if out.is_full() {
+                        break;
+                    }
+
+                    out.line(indent + 1, format!("{}:", name));
+                    describe_state(state, txn, indent + 2, out).await?;
+                }
+
+                Ok(())
+            }
+            State::Tuple(tuple) => {
+                out.line(indent, format!("Tuple ({} items)", tuple.len()));
+                for state in tuple.iter() {
+                    if out.is_full() {
+                        break;
+                    }
+
+                    describe_state(state, txn, indent + 1, out).await?;
+                }
+
+                Ok(())
+            }
+            State::Stream(_) => {
+                out.line(indent, "Stream");
+                Ok(())
+            }
+            other => {
+                out.line(indent, format!("{}: {}", other.class(), other));
+                Ok(())
+            }
+        }
+    })
+}
+
+async fn describe_collection(
+    collection: &Collection,
+    txn: &Txn,
+    indent: usize,
+    out: &mut Description,
+) -> TCResult<()> {
+    let txn_id = *txn.id();
+
+    match collection {
+        Collection::BTree(btree) => {
+            let count = btree.count(txn_id).await?;
+            describe_btree(btree.class(), btree.schema(), count, indent, out);
+        }
+        Collection::Table(table) => {
+            let count = table.clone().count(txn_id).await?;
+            describe_table(table.class(), table.schema(), count, indent, out);
+        }
+        #[cfg(feature = "tensor")]
+        Collection::Tensor(tensor) => {
+            describe_tensor(tensor.class(), tensor.shape(), tensor.dtype(), indent, out);
+        }
+    }
+
+    Ok(())
+}
+
+async fn describe_subject(
+    subject: &Subject,
+    txn: &Txn,
+    indent: usize,
+    out: &mut Description,
+) -> TCResult<()> {
+    let txn_id = *txn.id();
+
+    match subject {
+        Subject::BTree(btree) => {
+            let count = btree.count(txn_id).await?;
+            describe_btree(btree.class(), btree.schema(), count, indent, out);
+        }
+        Subject::Table(table) => {
+            let count = table.clone().count(txn_id).await?;
+            describe_table(table.class(), table.schema(), count, indent, out);
+        }
+        #[cfg(feature = "tensor")]
+        Subject::Dense(tensor) => {
+            describe_tensor(
+                TensorType::Dense,
+                tensor.shape(),
+                tensor.dtype(),
+                indent,
+                out,
+            );
+        }
+        #[cfg(feature = "tensor")]
+        Subject::Sparse(tensor) => {
+            describe_tensor(
+                TensorType::Sparse,
+                tensor.shape(),
+                tensor.dtype(),
+                indent,
+                out,
+            );
+        }
+        Subject::Value(file) => {
+            let value = file.read_block(txn_id, SUBJECT.into()).await?;
+            out.line(indent, format!("{}: {}", value.class(), *value));
+        }
+    }
+
+    Ok(())
+}
+
+fn describe_btree<C: fmt::Display>(
+    class: C,
+    schema: &[tc_btree::Column],
+    count: u64,
+    indent: usize,
+    out: &mut Description,
+) {
+    out.line(indent, class);
+    out.line(indent + 1, "schema:");
+    for column in schema {
+        out.line(indent + 2, column);
+    }
+
+    out.line(indent + 1, format!("keys: {}", count));
+}
+
+fn describe_table<C: fmt::Display>(
+    class: C,
+    schema: tc_table::TableSchema,
+    count: u64,
+    indent: usize,
+    out: &mut Description,
+) {
+    out.line(indent, class);
+    out.line(indent + 1, "schema:");
+    out.line(indent + 2, schema);
+    out.line(indent + 1, format!("rows: {}", count));
+}
+
+#[cfg(feature = "tensor")]
+fn describe_tensor<C: fmt::Display>(
+    class: C,
+    shape: &tc_tensor::Shape,
+    dtype: tc_value::NumberType,
+    indent: usize,
+    out: &mut Description,
+) {
+    out.line(indent, class);
+    out.line(indent + 1, format!("shape: {}", shape));
+    out.line(indent + 1, format!("dtype: {}", dtype));
+}
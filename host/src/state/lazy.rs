@@ -0,0 +1,200 @@
+//! A [`Lazy`] [`State`] which defers its (possibly expensive) construction until it's consumed.
+
+use std::fmt;
+use std::sync::Arc;
+
+use futures::lock::Mutex;
+
+use tc_error::*;
+use tcgeneric::{Instance, TCBoxTryFuture};
+
+use crate::txn::Txn;
+
+use super::{State, StateType};
+
+type Thunk<A> = Box<dyn FnOnce(A) -> TCBoxTryFuture<'static, State> + Send>;
+
+enum Inner<A> {
+    Pending(Thunk<A>),
+    Running(TCBoxTryFuture<'static, State>),
+    Ready(Box<State>),
+    Failed(TCError),
+}
+
+/// A [`State`] whose value is only computed, at most once, when it's actually consumed,
+/// e.g. when it's serialized to respond to a request.
+///
+/// The [`StateType`] of a `Lazy` state is known up front, so it's available without forcing it.
+pub struct Lazy<A = Txn> {
+    class: StateType,
+    inner: Arc<Mutex<Inner<A>>>,
+}
+
+impl<A> Lazy<A> {
+    /// Construct a new `Lazy` [`State`] of the given `class`, computed by the given `thunk`.
+    pub fn new<F>(class: StateType, thunk: F) -> Self
+    where
+        F: FnOnce(A) -> TCBoxTryFuture<'static, State> + Send + 'static,
+    {
+        Self {
+            class,
+            inner: Arc::new(Mutex::new(Inner::Pending(Box::new(thunk)))),
+        }
+    }
+
+    /// Return `true` if this `Lazy` [`State`] has already been computed.
+    pub fn is_forced(&self) -> bool {
+        match self.inner.try_lock() {
+            Some(inner) => matches!(&*inner, Inner::Ready(_) | Inner::Failed(_)),
+            None => false,
+        }
+    }
+
+    /// Return the value of this [`State`], if it's already been computed.
+    pub fn ready(&self) -> Option<State> {
+        let inner = self.inner.try_lock()?;
+        match &*inner {
+            Inner::Ready(state) => Some((**state).clone()),
+            _ => None,
+        }
+    }
+
+    /// Compute this [`State`], or return its value if it's already been computed.
+    pub async fn force(&self, arg: A) -> TCResult<State> {
+        let mut inner = self.inner.lock().await;
+
+        match &*inner {
+            Inner::Ready(state) => return Ok((**state).clone()),
+            Inner::Failed(cause) => {
+                return Err(TCError::new(cause.code(), cause.message().to_string()))
+            }
+            Inner::Pending(_) | Inner::Running(_) => {}
+        }
+
+        if matches!(&*inner, Inner::Pending(_)) {
+            let consumed = Inner::Failed(TCError::internal("lazy State thunk panicked"));
+            let thunk = match std::mem::replace(&mut *inner, consumed) {
+                Inner::Pending(thunk) => thunk,
+                _ => unreachable!("forced lazy State"),
+            };
+
+            *inner = Inner::Running((thunk)(arg));
+        }
+
+        // the future stays in place until it completes, so if this call is dropped partway
+        // through (e.g. because the request timed out) the next call resumes it
+        let result = match &mut *inner {
+            Inner::Running(future) => future.await,
+            _ => unreachable!("running lazy State"),
+        };
+
+        match result {
+            Ok(state) => {
+                *inner = Inner::Ready(Box::new(state.clone()));
+                Ok(state)
+            }
+            Err(cause) => {
+                *inner = Inner::Failed(TCError::new(cause.code(), cause.message().to_string()));
+                Err(cause)
+            }
+        }
+    }
+}
+
+impl<A> Clone for Lazy<A> {
+    fn clone(&self) -> Self {
+        Self {
+            class: self.class,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A> Instance for Lazy<A> {
+    type Class = StateType;
+
+    fn class(&self) -> StateType {
+        self.class
+    }
+}
+
+impl<A> fmt::Display for Lazy<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "lazy {}", self.class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::channel::oneshot;
+    use futures::executor::block_on;
+    use futures::future::{self, FutureExt};
+
+    use crate::scalar::{NumberType, UIntType, Value, ValueType};
+
+    use super::*;
+
+    #[test]
+    fn test_force_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let lazy = {
+            let calls = calls.clone();
+            Lazy::<()>::new(
+                ValueType::Number(NumberType::UInt(UIntType::U64)).into(),
+                move |()| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    future::ready(Ok(State::from(Value::from(1u64)))).boxed()
+                },
+            )
+        };
+
+        // querying the type does not run the thunk
+        assert!(
+            lazy.class() == StateType::from(ValueType::Number(NumberType::UInt(UIntType::U64)))
+        );
+        assert!(!lazy.is_forced());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        // consuming the value does, but only once
+        let state = block_on(lazy.clone().force(())).unwrap();
+        assert_eq!(
+            state.to_string(),
+            State::from(Value::from(1u64)).to_string()
+        );
+        assert!(lazy.is_forced());
+
+        block_on(lazy.force(())).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_force_after_cancel() {
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let lazy = Lazy::<()>::new(
+            ValueType::Number(NumberType::UInt(UIntType::U64)).into(),
+            move |()| {
+                Box::pin(async move {
+                    rx.await.map_err(|_| TCError::internal("cancelled"))?;
+                    Ok(State::from(Value::from(2u64)))
+                })
+            },
+        );
+
+        // drop the first call before the thunk finishes, as if the request had timed out
+        assert!(lazy.force(()).now_or_never().is_none());
+        assert!(!lazy.is_forced());
+
+        // the next call should resume the same computation rather than fail
+        tx.send(()).unwrap();
+        let state = block_on(lazy.force(())).unwrap();
+        assert_eq!(
+            state.to_string(),
+            State::from(Value::from(2u64)).to_string()
+        );
+        assert!(lazy.is_forced());
+    }
+}
@@ -26,8 +26,12 @@ use crate::scalar::*;
 use crate::stream::TCStream;
 use crate::txn::Txn;
 
+pub use debug::MAX_DESCRIPTION_LEN;
+pub use lazy::Lazy;
 pub use view::StateView;
 
+mod debug;
+mod lazy;
 mod view;
 
 pub trait StateClass: Class
@@ -199,6 +203,7 @@ pub enum State {
     Collection(Collection),
     Chain(Chain),
     Closure(Closure),
+    Lazy(Lazy),
     Map(Map<Self>),
     Object(Object),
     Scalar(Scalar),
@@ -301,6 +306,15 @@ impl Refer for State {
         }
     }
 
+    fn is_external(&self) -> bool {
+        match self {
+            Self::Map(map) => map.values().any(|state| state.is_external()),
+            Self::Scalar(scalar) => scalar.is_external(),
+            Self::Tuple(tuple) => tuple.iter().any(|state| state.is_external()),
+            _ => true,
+        }
+    }
+
     fn is_inter_service_write(&self, cluster_path: &[PathSegment]) -> bool {
         match self {
             Self::Map(map) => map
@@ -317,6 +331,15 @@ impl Refer for State {
         }
     }
 
+    fn is_write(&self) -> bool {
+        match self {
+            Self::Map(map) => map.values().any(|state| state.is_write()),
+            Self::Scalar(scalar) => scalar.is_write(),
+            Self::Tuple(tuple) => tuple.iter().any(|state| state.is_write()),
+            _ => false,
+        }
+    }
+
     fn reference_self(self, path: &TCPathBuf) -> Self {
         match self {
             Self::Map(map) => {
@@ -410,6 +433,7 @@ impl Instance for State {
             Self::Chain(chain) => StateType::Chain(chain.class()),
             Self::Closure(_) => StateType::Closure,
             Self::Collection(collection) => StateType::Collection(collection.class()),
+            Self::Lazy(lazy) => lazy.class(),
             Self::Map(_) => StateType::Map,
             Self::Object(object) => StateType::Object(object.class()),
             Self::Scalar(scalar) => StateType::Scalar(scalar.class()),
@@ -449,6 +473,12 @@ impl From<Collection> for State {
     }
 }
 
+impl From<Lazy> for State {
+    fn from(lazy: Lazy) -> Self {
+        Self::Lazy(lazy)
+    }
+}
+
 impl From<Link> for State {
     fn from(link: Link) -> Self {
         Self::Scalar(Scalar::from(link))
@@ -630,6 +660,24 @@ impl TryCastFrom<State> for Closure {
     }
 }
 
+impl TryCastFrom<State> for Collection {
+    fn can_cast_from(state: &State) -> bool {
+        match state {
+            State::Collection(_) => true,
+            State::Lazy(lazy) => lazy.ready().is_some_and(|s| Self::can_cast_from(&s)),
+            _ => false,
+        }
+    }
+
+    fn opt_cast_from(state: State) -> Option<Self> {
+        match state {
+            State::Collection(collection) => Some(collection),
+            State::Lazy(lazy) => lazy.ready().and_then(Self::opt_cast_from),
+            _ => None,
+        }
+    }
+}
+
 impl TryCastFrom<State> for String {
     fn can_cast_from(state: &State) -> bool {
         match state {
@@ -877,6 +925,7 @@ impl TryCastFrom<State> for Tensor {
     fn can_cast_from(state: &State) -> bool {
         match state {
             State::Collection(collection) => Self::can_cast_from(collection),
+            State::Lazy(lazy) => lazy.ready().is_some_and(|s| Self::can_cast_from(&s)),
             _ => false,
         }
     }
@@ -884,6 +933,7 @@ impl TryCastFrom<State> for Tensor {
     fn opt_cast_from(state: State) -> Option<Self> {
         match state {
             State::Collection(collection) => Self::opt_cast_from(collection),
+            State::Lazy(lazy) => lazy.ready().and_then(Self::opt_cast_from),
             _ => None,
         }
     }
@@ -949,6 +999,7 @@ impl fmt::Debug for State {
             Self::Chain(chain) => fmt::Debug::fmt(chain, f),
             Self::Closure(closure) => fmt::Debug::fmt(closure, f),
             Self::Collection(collection) => fmt::Debug::fmt(collection, f),
+            Self::Lazy(lazy) => fmt::Display::fmt(lazy, f),
             Self::Map(map) => fmt::Debug::fmt(map, f),
             Self::Object(object) => fmt::Debug::fmt(object, f),
             Self::Scalar(scalar) => fmt::Debug::fmt(scalar, f),
@@ -964,6 +1015,7 @@ impl fmt::Display for State {
             Self::Chain(chain) => fmt::Display::fmt(chain, f),
             Self::Closure(closure) => fmt::Display::fmt(closure, f),
             Self::Collection(collection) => fmt::Display::fmt(collection, f),
+            Self::Lazy(lazy) => fmt::Display::fmt(lazy, f),
             Self::Map(map) => fmt::Display::fmt(map, f),
             Self::Object(object) => fmt::Display::fmt(object, f),
             Self::Scalar(scalar) => fmt::Display::fmt(scalar, f),
@@ -45,6 +45,10 @@ impl<'en> IntoView<'en, fs::Dir> for State {
                     .map_ok(StateView::Collection)
                     .await
             }
+            Self::Lazy(lazy) => {
+                let state = lazy.force(txn.clone()).await?;
+                state.into_view(txn).await
+            }
             Self::Map(map) => {
                 let map_view = stream::iter(map.into_iter())
                     .map(|(key, state)| state.into_view(txn.clone()).map_ok(|view| (key, view)))
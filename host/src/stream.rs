@@ -1,34 +1,102 @@
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
-use destream::en;
-use futures::future;
-use futures::stream::{StreamExt, TryStreamExt};
+use bytes::Bytes;
+use destream::{de, en};
+use futures::future::{self, TryFutureExt};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use safecast::TryCastFrom;
 
 use tc_btree::BTreeInstance;
 use tc_error::*;
-use tc_table::TableInstance;
-use tc_transact::{IntoView, Transaction};
-use tcgeneric::TCBoxTryStream;
+use tc_table::{Change, ComputedColumn, Row, TableInstance};
+use tc_transact::{IntoView, Transaction, TxnId};
+use tcgeneric::{Id, Map, TCBoxTryFuture, TCBoxTryStream};
 
+use crate::chain::Chain;
 use crate::closure::Closure;
-use crate::collection::Collection;
+use crate::collection::{Collection, Table, TableIndex};
 use crate::fs;
 use crate::scalar::OpDef;
 use crate::state::{State, StateView};
 use crate::txn::Txn;
 use crate::value::Value;
 
+/// A stream of [`Value`]s decoded incrementally from the body of a request,
+/// which can only be consumed once.
+#[derive(Clone)]
+pub struct RequestBody {
+    values: Arc<Mutex<Option<TCBoxTryStream<'static, Value>>>>,
+}
+
+impl RequestBody {
+    /// Construct a new `RequestBody` from the given stream of decoded `values`.
+    pub fn new(values: TCBoxTryStream<'static, Value>) -> Self {
+        Self {
+            values: Arc::new(Mutex::new(Some(values))),
+        }
+    }
+
+    fn take(&self) -> TCResult<TCBoxTryStream<'static, Value>> {
+        self.values
+            .lock()
+            .expect("request body")
+            .take()
+            .ok_or_else(|| TCError::unsupported("a request body can only be streamed once"))
+    }
+}
+
+/// A [`Chain`] or [`Collection`] to export as a portable archive, one chunk at a time.
+#[derive(Clone)]
+pub enum Archive {
+    Chain(Box<Chain>),
+    Collection(Box<Collection>),
+}
+
+impl Archive {
+    /// Decode an instance of `T` from an `archive` exported as a [`TCStream::Archive`],
+    /// i.e. a [`TCStream`] or tuple of its chunks, without buffering the whole archive.
+    pub async fn decode<T>(txn: &Txn, archive: State) -> TCResult<T>
+    where
+        T: de::FromStream<Context = Txn>,
+    {
+        fn chunk(state: State) -> TCResult<Bytes> {
+            let chunk =
+                Value::try_cast_from(state, |s| TCError::bad_request("invalid archive chunk", s))?;
+
+            Bytes::try_cast_from(chunk, |v| TCError::bad_request("invalid archive chunk", v))
+        }
+
+        let chunks: TCBoxTryStream<'static, Bytes> = match archive {
+            State::Stream(archive) => {
+                let chunks = archive.into_stream(txn.clone()).await?;
+                Box::pin(chunks.and_then(|state| future::ready(chunk(state))))
+            }
+            State::Tuple(chunks) => Box::pin(stream::iter(chunks.into_iter().map(chunk))),
+            other => return Err(TCError::bad_request("invalid archive", other)),
+        };
+
+        let txn = txn.subcontext_tmp().await?;
+        fs::decode_archive(txn, chunks).await
+    }
+}
+
 #[derive(Clone)]
 pub enum TCStream {
-    Collection(Collection),
+    Archive(Archive),
+    Collection(Box<Collection>),
+    Changes(TableIndex, Option<TxnId>),
+    Body(RequestBody),
 }
 
 impl TCStream {
     pub async fn for_each(self, txn: Txn, op: Closure) -> TCResult<()> {
         let stream = self.into_stream(txn.clone()).await?;
+        let config = op.config().clone();
 
         stream
             .map(move |r| r.and_then(|state| op.clone().into_callable(state)))
-            .map_ok(|(context, op_def)| OpDef::call(op_def, &txn, context))
+            .map_ok(|(context, op_def)| OpDef::call(op_def, &txn, context, &config))
             .try_buffer_unordered(num_cpus::get())
             .try_fold((), |(), _none| future::ready(Ok(())))
             .await
@@ -36,13 +104,31 @@ impl TCStream {
 
     pub async fn into_stream(self, txn: Txn) -> TCResult<TCBoxTryStream<'static, State>> {
         match self {
-            Self::Collection(collection) => match collection {
+            Self::Archive(archive) => {
+                let chunks = match archive {
+                    Archive::Chain(chain) => fs::encode_archive((*chain).into_view(txn).await?)?,
+                    Archive::Collection(collection) => {
+                        fs::encode_archive((*collection).into_view(txn).await?)?
+                    }
+                };
+
+                Ok(Box::pin(chunks.map_ok(Value::Bytes).map_ok(State::from)))
+            }
+            Self::Body(body) => {
+                let values = body.take()?;
+                Ok(Box::pin(values.map_ok(State::from)))
+            }
+            Self::Changes(table, since) => {
+                let changes = table.changes(*txn.id(), since).await?;
+                Ok(Box::pin(changes.map_ok(change_into_state)))
+            }
+            Self::Collection(collection) => match *collection {
                 Collection::BTree(btree) => {
                     let keys = btree.keys(*txn.id()).await?;
                     Ok(Box::pin(keys.map_ok(Value::from).map_ok(State::from)))
                 }
                 Collection::Table(table) => {
-                    let rows = table.rows(*txn.id()).await?;
+                    let rows = table_rows(*table, txn).await?;
                     Ok(Box::pin(rows.map_ok(Value::from).map_ok(State::from)))
                 }
 
@@ -77,11 +163,122 @@ impl<'en> IntoView<'en, fs::Dir> for TCStream {
     }
 }
 
+/// Stream the rows of the given [`Table`], each followed by the values of its
+/// [`ComputedColumn`]s, which are computed as the rows are read.
+pub fn table_rows(
+    table: Table,
+    txn: Txn,
+) -> TCBoxTryFuture<'static, TCBoxTryStream<'static, Vec<Value>>> {
+    Box::pin(async move {
+        let table = match table {
+            Table::Selection(selection) if selection.selects_computed() => {
+                // compute the selected columns from the whole rows of the source
+                let (source, indices) = selection.into_source();
+                let rows = table_rows(source, txn).await?;
+                let selected = rows.map_ok(move |row| {
+                    let selection: Vec<Value> = indices.iter().map(|i| row[*i].clone()).collect();
+                    selection
+                });
+
+                let selected: TCBoxTryStream<'static, Vec<Value>> = Box::pin(selected);
+                return Ok(selected);
+            }
+            table => table,
+        };
+
+        let schema = table.schema();
+        let rows = table.rows(*txn.id()).await?;
+        if schema.computed().is_empty() {
+            return Ok(rows);
+        }
+
+        let names: Vec<Id> = schema.primary().column_names().cloned().collect();
+        let computed = schema.computed().to_vec();
+
+        let rows = rows.and_then(move |mut values| {
+            let row: Row = names.iter().cloned().zip(values.iter().cloned()).collect();
+
+            let computed = computed
+                .iter()
+                .map(|col| compute(txn.clone(), col.clone(), row.clone()));
+
+            future::try_join_all(computed).map_ok(move |computed| {
+                values.extend(computed);
+                values
+            })
+        });
+
+        let rows: TCBoxTryStream<'static, Vec<Value>> = Box::pin(rows);
+        Ok(rows)
+    })
+}
+
+async fn compute(txn: Txn, computed: ComputedColumn, row: Row) -> TCResult<Value> {
+    let params: Map<State> = row
+        .into_iter()
+        .map(|(id, value)| (id, State::from(value)))
+        .collect();
+
+    let value = txn.compute(computed.op(), params).await?;
+
+    let column = computed.column();
+    let value = Value::try_cast_from(value, |s| {
+        TCError::bad_request(
+            format!("computed column {} must be a Value, not", column.name),
+            s,
+        )
+    })?;
+
+    value.into_type(column.dtype).ok_or_else(|| {
+        TCError::bad_request(
+            format!(
+                "invalid value for computed column {}, expected",
+                column.name
+            ),
+            column.dtype,
+        )
+    })
+}
+
+/// Encode a [`Change`] as a tuple of its type and the affected row(s),
+/// e.g. `("update", old_row, new_row)`.
+fn change_into_state(change: Change) -> State {
+    fn row_into_state(row: Row) -> State {
+        State::Map(
+            row.into_iter()
+                .map(|(name, value)| (name, State::from(value)))
+                .collect(),
+        )
+    }
+
+    let name = |name: &str| State::from(Value::String(name.to_string()));
+
+    let change = match change {
+        Change::Insert(row) => vec![name("insert"), row_into_state(row)],
+        Change::Delete(row) => vec![name("delete"), row_into_state(row)],
+        Change::Update(old, new) => vec![name("update"), row_into_state(old), row_into_state(new)],
+    };
+
+    State::Tuple(change.into())
+}
+
+impl From<Archive> for TCStream {
+    fn from(archive: Archive) -> Self {
+        Self::Archive(archive)
+    }
+}
+
+impl From<RequestBody> for TCStream {
+    fn from(body: RequestBody) -> Self {
+        Self::Body(body)
+    }
+}
+
 impl<T> From<T> for TCStream
 where
     Collection: From<T>,
 {
     fn from(collection: T) -> Self {
-        Self::Collection(collection.into())
+        Self::Collection(Box::new(collection.into()))
     }
 }
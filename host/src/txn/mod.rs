@@ -1,8 +1,11 @@
 //! The transaction context [`Txn`].
 
+use std::collections::HashMap;
+use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use futures::future::TryFutureExt;
@@ -11,29 +14,45 @@ use log::debug;
 use tc_error::*;
 use tc_transact::fs::Dir;
 use tc_transact::Transaction;
-use tcgeneric::{Id, NetworkTime, PathSegment, TCPathBuf, Tuple};
+use tcgeneric::{Id, Map, NetworkTime, PathSegment, TCPathBuf, Tuple};
 
+use crate::chain::Chain;
 use crate::fs;
 use crate::gateway::Gateway;
-use crate::scalar::{Link, Value};
+use crate::scalar::{Link, OpCache, Value};
 use crate::state::State;
 
 pub use request::*;
+pub use savepoint::Savepoint;
 pub use server::*;
 pub use tc_transact::TxnId;
 
 mod request;
+mod savepoint;
 mod server;
 
 struct Active {
     expires: NetworkTime,
     scope: Scope,
+    savepoints: savepoint::Savepoints,
+    held: AtomicBool,
+    holder: Mutex<Option<String>>,
+    claims: Mutex<HashMap<TCPathBuf, Arc<Request>>>,
+    op_states: AtomicUsize,
 }
 
 impl Active {
     fn new(txn_id: &TxnId, expires: NetworkTime) -> Self {
         let scope = TCPathBuf::from(txn_id.to_id());
-        Self { expires, scope }
+        Self {
+            expires,
+            scope,
+            savepoints: savepoint::Savepoints::new(),
+            held: AtomicBool::new(false),
+            holder: Mutex::new(None),
+            claims: Mutex::new(HashMap::new()),
+            op_states: AtomicUsize::new(0),
+        }
     }
 
     fn expires(&self) -> &NetworkTime {
@@ -43,6 +62,26 @@ impl Active {
     fn scope(&self) -> &Scope {
         &self.scope
     }
+
+    fn is_held(&self) -> bool {
+        self.held.load(Ordering::Acquire)
+    }
+
+    /// Return `true` if the given `bearer` token is the one which first held this transaction.
+    fn is_holder(&self, bearer: Option<&str>) -> bool {
+        let holder = self.holder.lock().expect("transaction holder");
+        holder.as_deref() == bearer
+    }
+
+    /// Stop holding this transaction open, returning the claims of each cluster it touched.
+    fn release(&self) -> Option<Vec<(TCPathBuf, Arc<Request>)>> {
+        if self.held.swap(false, Ordering::AcqRel) {
+            let mut claims = self.claims.lock().expect("transaction claims");
+            Some(claims.drain().collect())
+        } else {
+            None
+        }
+    }
 }
 
 /// A transaction context.
@@ -52,6 +91,11 @@ pub struct Txn {
     gateway: Arc<Gateway>,
     request: Arc<Request>,
     dir: fs::Dir,
+    read_only: bool,
+    balanced: Option<TxnId>,
+    min_commit: Option<TxnId>,
+    epoch: Option<u64>,
+    context_metadata: Arc<Map<Value>>,
 }
 
 impl Txn {
@@ -63,9 +107,169 @@ impl Txn {
             gateway,
             request,
             dir,
+            read_only: false,
+            balanced: None,
+            min_commit: None,
+            epoch: None,
+            context_metadata: Arc::new(Map::default()),
+        }
+    }
+
+    /// Return a copy of this `Txn` with the given application-defined metadata,
+    /// such as a tenant ID or trace flags.
+    pub fn with_context_metadata(self, context_metadata: Map<Value>) -> Self {
+        Self {
+            context_metadata: Arc::new(context_metadata),
+            ..self
+        }
+    }
+
+    /// Return the application-defined metadata of this `Txn`, available to an `OpDef` as `$meta`.
+    pub fn context_metadata(&self) -> &Map<Value> {
+        &self.context_metadata
+    }
+
+    /// Return a copy of this `Txn` under which any write is rejected.
+    pub fn into_read_only(self) -> Self {
+        Self {
+            read_only: true,
+            ..self
+        }
+    }
+
+    /// Return `true` if writes are not allowed under this `Txn`.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Return an error if this `Txn` is read-only, before a POST handler writes to its subject.
+    ///
+    /// The gateway itself rejects a read-only PUT, PATCH, or DELETE, but a POST may be a read,
+    /// so each POST handler which writes must call this first.
+    pub fn check_writable<P: fmt::Display>(&self, path: P) -> TCResult<()> {
+        if self.read_only {
+            Err(TCError::method_not_allowed(
+                "POST",
+                format!("read-only transaction {}:", self.request.txn_id()),
+                path,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Return a read-only copy of this `Txn` with which to forward a read to another replica of
+    /// a cluster whose last commit was `since`.
+    ///
+    /// This is only ever set internally, never by a request.
+    pub(crate) fn into_balanced(self, since: TxnId) -> Self {
+        Self {
+            read_only: true,
+            balanced: Some(since),
+            ..self
+        }
+    }
+
+    /// Return `true` if this `Txn` forwards a read to another replica.
+    pub fn is_balanced(&self) -> bool {
+        self.balanced.is_some()
+    }
+
+    /// Return the last commit of the replica which forwarded a read with this `Txn`, if any.
+    pub fn balanced_since(&self) -> Option<&TxnId> {
+        self.balanced.as_ref()
+    }
+
+    /// Return a read-only copy of this `Txn` for a read forwarded from another replica, which
+    /// this replica must serve itself, and only if it has committed at least `min_commit`.
+    pub fn with_min_commit(self, min_commit: TxnId) -> Self {
+        Self {
+            read_only: true,
+            min_commit: Some(min_commit),
+            ..self
+        }
+    }
+
+    /// Return the oldest commit which a replica must have made to serve a read under this `Txn`.
+    pub fn min_commit(&self) -> Option<&TxnId> {
+        self.min_commit.as_ref()
+    }
+
+    /// Return a copy of this `Txn` with which to replicate a write from a cluster whose replica
+    /// set is at the given `epoch`, so that a replica which has seen a later epoch can reject it.
+    pub fn with_epoch(self, epoch: u64) -> Self {
+        Self {
+            epoch: Some(epoch),
+            ..self
+        }
+    }
+
+    /// Return a copy of this `Txn` with no replica set epoch, once the epoch has been validated.
+    pub fn without_epoch(self) -> Self {
+        Self {
+            epoch: None,
+            ..self
+        }
+    }
+
+    /// Return the replica set epoch of the cluster which replicated a write with this `Txn`, if any.
+    pub fn epoch(&self) -> Option<u64> {
+        self.epoch
+    }
+
+    /// Return a copy of this `Txn` which can send transaction control messages (i.e. notifying
+    /// the owner of a participant or rolling back a replica) even if this `Txn` is read-only.
+    pub(crate) fn control(&self) -> Self {
+        Self {
+            read_only: false,
+            ..self.clone()
         }
     }
 
+    /// Hold this transaction open, so that the clusters it writes to don't commit at the end of
+    /// each request but wait for an explicit commit or abort message.
+    ///
+    /// The bearer token of the first request to hold a transaction identifies its holder; a later
+    /// request which presents a different token is `Unauthorized`.
+    pub fn hold(&self) -> TCResult<()> {
+        let mut holder = self.active.holder.lock().expect("transaction holder");
+
+        if self.active.is_held() {
+            if holder.as_deref() != self.request.bearer() {
+                return Err(TCError::unauthorized(format!(
+                    "transaction {} is held by another client",
+                    self.id()
+                )));
+            }
+        } else {
+            *holder = self.request.bearer().map(String::from);
+            self.active.held.store(true, Ordering::Release);
+        }
+
+        Ok(())
+    }
+
+    /// Return `true` if this transaction is held open until an explicit commit or abort.
+    pub fn is_held(&self) -> bool {
+        self.active.is_held()
+    }
+
+    /// Record the claim of the cluster at `cluster_path` on this held transaction,
+    /// so that later requests to the same cluster can resume it.
+    pub(crate) fn hold_claim(&self, cluster_path: TCPathBuf) {
+        let mut claims = self.active.claims.lock().expect("transaction claims");
+        claims.insert(cluster_path, self.request.clone());
+    }
+
+    /// Resume the claim of the cluster at `cluster_path` on this held transaction, if any.
+    pub(crate) fn resume(&self, cluster_path: &[PathSegment]) -> Option<Self> {
+        let claims = self.active.claims.lock().expect("transaction claims");
+        claims.get(cluster_path).map(|request| Self {
+            request: request.clone(),
+            ..self.clone()
+        })
+    }
+
     /// Return the current number of strong references to this `Txn`.
     pub fn ref_count(&self) -> usize {
         Arc::strong_count(&self.active)
@@ -105,6 +309,7 @@ impl Txn {
     ) -> TCResult<Self> {
         let token = self.request.token().to_string();
         let txn_id = self.request.txn_id();
+        let request_id = self.request.request_id().to_string();
 
         use rjwt::Resolve;
         let host = self.gateway.link(cluster_path);
@@ -125,7 +330,12 @@ impl Txn {
             active: self.active.clone(),
             gateway: self.gateway.clone(),
             dir: self.dir.clone(),
-            request: Arc::new(Request::new(*txn_id, token, claims)),
+            request: Arc::new(Request::new(*txn_id, token, claims, request_id)),
+            read_only: self.read_only,
+            balanced: self.balanced,
+            min_commit: self.min_commit,
+            epoch: self.epoch,
+            context_metadata: self.context_metadata.clone(),
         })
     }
 
@@ -218,11 +428,81 @@ impl Txn {
             .fold(None, |_, host| Some(host))
     }
 
+    /// Create a new [`Savepoint`] in this transaction.
+    pub async fn savepoint(&self) -> TCResult<Savepoint> {
+        Ok(self.active.savepoints.create(*self.id()).await)
+    }
+
+    /// Undo the writes to [`Chain`]s made on this host since the given [`Savepoint`],
+    /// keeping the writes made before it.
+    ///
+    /// The rollback is local to this host, so it's rejected if any write since the given
+    /// [`Savepoint`] was replicated to another host.
+    pub async fn rollback_to(&self, savepoint: Savepoint) -> TCResult<()> {
+        self.active.savepoints.rollback_to(self, savepoint).await
+    }
+
+    /// Mark the open [`Savepoint`]s, if any, on the given [`Chain`] before writing to it.
+    pub(crate) async fn before_write(&self, chain: &Chain) -> TCResult<()> {
+        self.active.savepoints.before_write(self, chain).await
+    }
+
+    /// Mark the open [`Savepoint`]s, if any, as having seen a write replicated to another host.
+    pub(crate) async fn after_replicate(&self) {
+        self.active.savepoints.after_replicate().await
+    }
+
     /// Return a link to the given path on this host.
     pub fn link(&self, path: TCPathBuf) -> Link {
         self.gateway.link(path)
     }
 
+    /// Return the maximum number of references an `OpDef` may resolve in this transaction.
+    pub fn max_op_steps(&self) -> usize {
+        self.gateway.max_op_steps()
+    }
+
+    /// Return the maximum number of intermediate states which all the `OpDef`s executing in
+    /// this transaction on this host may hold in scope at once.
+    pub fn max_op_states(&self) -> usize {
+        self.gateway.max_op_states()
+    }
+
+    /// Reserve `count` more intermediate `OpDef` states from the budget of this transaction.
+    ///
+    /// Returns a `BadRequest` error, without reserving anything, if this would exceed
+    /// [`Txn::max_op_states`]. Each reservation must be released with [`Txn::release_op_states`].
+    pub fn reserve_op_states(&self, count: usize) -> TCResult<()> {
+        let max_states = self.max_op_states();
+        let op_states = &self.active.op_states;
+
+        let mut reserved = op_states.load(Ordering::SeqCst);
+        loop {
+            let total = reserved + count;
+            if total > max_states {
+                return Err(TCError::bad_request(
+                    "transaction exceeded its maximum number of op states",
+                    max_states,
+                ));
+            }
+
+            match op_states.compare_exchange(reserved, total, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => return Ok(()),
+                Err(actual) => reserved = actual,
+            }
+        }
+    }
+
+    /// Release `count` intermediate `OpDef` states reserved with [`Txn::reserve_op_states`].
+    pub fn release_op_states(&self, count: usize) {
+        self.active.op_states.fetch_sub(count, Ordering::SeqCst);
+    }
+
+    /// Return the cache of the results of read-only `OpDef`s on this host.
+    pub fn op_cache(&self) -> &OpCache {
+        self.gateway.op_cache()
+    }
+
     /// Return the [`Request`] which initiated this transaction on this host.
     pub fn request(&'_ self) -> &'_ Request {
         &self.request
@@ -243,10 +523,21 @@ impl Txn {
         self.gateway.post(self, link, params).await
     }
 
+    /// Call the read-only POST method at `path` of a cluster hosted on this host, without
+    /// claiming this transaction on behalf of the cluster, e.g. to compute a column of a table.
+    pub(crate) async fn compute(&self, path: &TCPathBuf, params: Map<State>) -> TCResult<State> {
+        self.gateway.compute(self, path, params).await
+    }
+
     /// Resolve a DELETE op within this transaction context.
-    pub async fn delete(&self, link: Link, key: Value) -> TCResult<()> {
+    pub async fn delete(&self, link: Link, key: Value) -> TCResult<Value> {
         self.gateway.delete(self, link, key).await
     }
+
+    /// Resolve a PATCH op within this transaction context.
+    pub async fn patch(&self, link: Link, key: Value, value: State) -> TCResult<()> {
+        self.gateway.patch(self, link, key, value).await
+    }
 }
 
 #[async_trait]
@@ -272,6 +563,11 @@ impl Transaction<fs::Dir> for Txn {
             gateway: self.gateway.clone(),
             request: self.request.clone(),
             dir,
+            read_only: self.read_only,
+            balanced: self.balanced,
+            min_commit: self.min_commit,
+            epoch: self.epoch,
+            context_metadata: self.context_metadata.clone(),
         })
     }
 
@@ -279,6 +575,10 @@ impl Transaction<fs::Dir> for Txn {
         let id = self.dir.unique_id(self.id()).await?;
         self.subcontext(id).await
     }
+
+    fn reduce_concurrency(&self) -> usize {
+        self.gateway.reduce_concurrency()
+    }
 }
 
 impl Hash for Txn {
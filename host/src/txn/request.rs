@@ -5,6 +5,7 @@ use std::convert::TryInto;
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::TryFutureExt;
+use uuid::Uuid;
 
 use tc_error::*;
 use tc_transact::TxnId;
@@ -18,23 +19,42 @@ pub type Claims = rjwt::Claims<Link, Value, Vec<Scope>>;
 pub type Scope = TCPathBuf;
 pub type Token = rjwt::Token<Link, Value, Vec<Scope>>;
 
+/// Generate a new, unique ID to correlate the requests made on behalf of a single inbound request.
+pub fn new_request_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
 /// A `Txn`'s authorization.
 pub struct Request {
     token: String,
     claims: Claims,
     txn_id: TxnId,
+    request_id: String,
+    bearer: Option<String>,
 }
 
 impl Request {
     /// Construct a new `Request`.
-    pub fn new(txn_id: TxnId, token: String, claims: Claims) -> Self {
+    pub fn new(txn_id: TxnId, token: String, claims: Claims, request_id: String) -> Self {
         Self {
             token,
             claims,
             txn_id,
+            request_id,
+            bearer: None,
         }
     }
 
+    /// Record the bearer token which the client presented with this request, if any.
+    pub fn with_bearer(self, bearer: Option<String>) -> Self {
+        Self { bearer, ..self }
+    }
+
+    /// Return the bearer token which the client presented with this request, if any.
+    pub fn bearer(&self) -> Option<&str> {
+        self.bearer.as_deref()
+    }
+
     pub fn expires(&self) -> TCResult<NetworkTime> {
         self.claims
             .expires()
@@ -52,6 +72,11 @@ impl Request {
         &self.token
     }
 
+    /// Return the ID used to correlate this request with the requests it makes to other hosts.
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
     #[inline]
     pub fn txn_id(&self) -> &TxnId {
         &self.txn_id
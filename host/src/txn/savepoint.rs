@@ -0,0 +1,137 @@
+//! Savepoints within a transaction, used to roll back some but not all of its writes.
+
+use std::fmt;
+
+use futures::future::try_join_all;
+use log::debug;
+use uplock::RwLock;
+
+use tc_error::*;
+use tc_transact::{Rollback, Transaction};
+
+use crate::chain::Chain;
+
+use super::{Txn, TxnId};
+
+/// A point within a transaction to which its writes can be rolled back.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Savepoint {
+    txn_id: TxnId,
+    ordinal: usize,
+}
+
+impl Savepoint {
+    /// Construct a handle to the savepoint with the given `ordinal` in the given transaction.
+    pub fn new(txn_id: TxnId, ordinal: usize) -> Self {
+        Self { txn_id, ordinal }
+    }
+
+    /// The ID of the transaction to which this `Savepoint` belongs.
+    pub fn txn_id(&self) -> &TxnId {
+        &self.txn_id
+    }
+
+    /// The position of this `Savepoint` among the savepoints of its transaction.
+    pub fn ordinal(&self) -> usize {
+        self.ordinal
+    }
+}
+
+impl fmt::Display for Savepoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "savepoint {} of transaction {}",
+            self.ordinal, self.txn_id
+        )
+    }
+}
+
+// the [`Chain`]s written to since a savepoint was created, and whether any write since then
+// was replicated to another host, in which case it can no longer be rolled back locally
+#[derive(Default)]
+struct Level {
+    chains: Vec<Chain>,
+    replicated: bool,
+}
+
+/// The savepoints of a single transaction, each with the [`Chain`]s written to after it was created.
+pub(super) struct Savepoints {
+    levels: RwLock<Vec<Level>>,
+}
+
+impl Savepoints {
+    pub fn new() -> Self {
+        Self {
+            levels: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Create a new [`Savepoint`] in the given transaction.
+    pub async fn create(&self, txn_id: TxnId) -> Savepoint {
+        let mut levels = self.levels.write().await;
+        levels.push(Level::default());
+        Savepoint::new(txn_id, levels.len() - 1)
+    }
+
+    /// Mark each savepoint which hasn't seen a write to the given [`Chain`] yet on that [`Chain`].
+    pub async fn before_write(&self, txn: &Txn, chain: &Chain) -> TCResult<()> {
+        let mut levels = self.levels.write().await;
+        for (ordinal, level) in levels.iter_mut().enumerate() {
+            if !level.chains.iter().any(|saved| saved.is(chain)) {
+                chain.savepoint(txn.id(), ordinal).await?;
+                level.chains.push(chain.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Mark each open savepoint as having seen a write which was replicated to another host.
+    pub async fn after_replicate(&self) {
+        let mut levels = self.levels.write().await;
+        for level in levels.iter_mut() {
+            level.replicated = true;
+        }
+    }
+
+    /// Undo every write made since the given [`Savepoint`].
+    ///
+    /// The given [`Savepoint`] remains valid, but any later savepoints are discarded.
+    ///
+    /// Returns a "bad request" error if a write made since the given [`Savepoint`] was replicated
+    /// to another host, since a rollback only applies to this host.
+    pub async fn rollback_to(&self, txn: &Txn, savepoint: Savepoint) -> TCResult<()> {
+        if savepoint.txn_id() != txn.id() {
+            return Err(TCError::bad_request(
+                format!("cannot roll back transaction {} to", txn.id()),
+                savepoint,
+            ));
+        }
+
+        let mut levels = self.levels.write().await;
+        if savepoint.ordinal() >= levels.len() {
+            return Err(TCError::not_found(savepoint));
+        }
+
+        if levels[savepoint.ordinal()].replicated {
+            return Err(TCError::bad_request(
+                "cannot roll back a write to a replicated cluster to",
+                savepoint,
+            ));
+        }
+
+        debug!("roll back to {}", savepoint);
+
+        levels.truncate(savepoint.ordinal() + 1);
+        let chains = std::mem::take(&mut levels[savepoint.ordinal()].chains);
+        try_join_all(
+            chains
+                .iter()
+                .map(|chain| chain.rollback_to(txn.id(), savepoint.ordinal())),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -5,50 +5,140 @@ use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::Duration;
 
-use futures::TryFutureExt;
-use log::debug;
+use futures::{Future, TryFutureExt};
+use log::{debug, warn};
 use uplock::RwLock;
 
 use tc_error::*;
 use tc_transact::fs::Dir;
+use tc_transact::lock::LockStats;
 use tc_transact::Transact;
+use tcgeneric::{label, Label, Map, NetworkTime, TCPathBuf};
 
 use crate::fs;
 use crate::gateway::Gateway;
+use crate::scalar::Value;
+use crate::state::State;
 
 use super::request::*;
 use super::{Active, Txn, TxnId};
 
-const GRACE: Duration = Duration::from_secs(2);
-const INTERVAL: Duration = Duration::from_secs(1);
+const CONFLICTS: Label = label("conflicts");
+const CONTENDED: Label = label("contended");
+const LOCK_WAIT: Label = label("lock_wait");
 
 /// Server to keep track of the transactions currently active for this host.
 #[derive(Clone)]
 pub struct TxnServer {
     active: RwLock<HashMap<TxnId, Arc<Active>>>,
     workspace: fs::Dir,
+    lock_stats: Arc<LockStats>,
 }
 
 impl TxnServer {
     /// Construct a new `TxnServer`.
     pub async fn new(workspace: fs::Dir) -> Self {
         let active = RwLock::new(HashMap::new());
+        let lock_stats = Arc::new(LockStats::default());
 
-        spawn_cleanup_thread(workspace.clone(), active.clone());
+        Self {
+            active,
+            workspace,
+            lock_stats,
+        }
+    }
+
+    /// Handle the given `request` for the resource at `path`, counting any lock conflicts
+    /// or wait time it incurs toward the [`Self::stats`] of this `TxnServer`.
+    pub async fn track<F: Future>(&self, path: String, request: F) -> F::Output {
+        self.lock_stats.clone().scope(path, request).await
+    }
 
-        Self { active, workspace }
+    /// Spawn a background task which calls [`Self::compact`] every `interval`.
+    pub fn spawn_compaction(&self, gateway: Arc<Gateway>, interval: Duration, age: Duration) {
+        let server = self.clone();
+        let mut interval = tokio::time::interval(interval);
+
+        tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                server.compact(Some(&gateway), age, Gateway::time()).await;
+            }
+        });
+    }
+
+    /// Finalize every transaction which expired more than `age` before `now`, as well as any
+    /// orphaned transactional changes to the workspace (e.g. a temporary subcontext directory)
+    /// more than `age` old which don't belong to an active transaction.
+    ///
+    /// An expired transaction which is still held open is first rolled back by each cluster
+    /// which claimed it, via the given `gateway`, so that their locks are released. Then each
+    /// cluster hosted by the `gateway` finalizes the expired transaction.
+    pub async fn compact(&self, gateway: Option<&Arc<Gateway>>, age: Duration, now: NetworkTime) {
+        let expired = {
+            let mut active = self.active.write().await;
+            let expired = active
+                .iter()
+                .filter(|(_, txn)| txn.expires() + age < now)
+                .map(|(txn_id, _)| *txn_id)
+                .collect::<Vec<TxnId>>();
+
+            expired
+                .into_iter()
+                .filter_map(|txn_id| active.remove(&txn_id).map(|txn| (txn_id, txn)))
+                .collect::<Vec<(TxnId, Arc<Active>)>>()
+        };
+
+        for (txn_id, active) in expired.into_iter() {
+            debug!("transaction {} has expired", txn_id);
+
+            if let (Some(gateway), Some(claims)) = (gateway, active.release()) {
+                debug!("roll back expired held transaction {}", txn_id);
+                let held = self.claimed(gateway.clone(), active, claims);
+                if let Err(cause) = gateway.rollback_held(held).await {
+                    warn!("error rolling back transaction {}: {}", txn_id, cause);
+                }
+            }
+
+            if let Some(gateway) = gateway {
+                // e.g. a commit which was rolled back because its replicas failed to prepare
+                gateway.finalize(&txn_id).await;
+            }
+
+            debug!("finalize expired transaction {}", txn_id);
+            self.workspace.finalize(&txn_id).await;
+        }
+
+        let orphaned = {
+            let active = self.active.read().await;
+            self.workspace
+                .pending_txns()
+                .into_iter()
+                .filter(|txn_id| !active.contains_key(txn_id))
+                .filter(|txn_id| txn_id.time() + age < now)
+                .collect::<Vec<TxnId>>()
+        };
+
+        for txn_id in orphaned.into_iter() {
+            debug!("finalize orphaned workspace changes at {}", txn_id);
+            self.workspace.finalize(&txn_id).await;
+        }
     }
 
     /// Return the active `Txn` with the given [`TxnId`], or initiate a new [`Txn`].
+    ///
+    /// The `bearer` token is the one presented by the client, if any.
     pub async fn new_txn(
         &self,
         gateway: Arc<Gateway>,
         txn_id: TxnId,
         token: (String, Claims),
+        bearer: Option<String>,
+        request_id: String,
     ) -> TCResult<Txn> {
         let expires = token.1.expires().try_into()?;
         let dir = self.txn_dir(txn_id).await?;
-        let request = Request::new(txn_id, token.0, token.1);
+        let request = Request::new(txn_id, token.0, token.1, request_id).with_bearer(bearer);
         let mut active = self.active.write().await;
 
         match active.entry(txn_id) {
@@ -65,6 +155,95 @@ impl TxnServer {
         }
     }
 
+    /// Stop holding open the transaction with the given [`TxnId`], returning a [`Txn`] claimed by
+    /// each cluster which it wrote to, so that the caller can commit or roll back each of them.
+    ///
+    /// Returns `NotFound` if there is no such active transaction, e.g. because it has expired
+    /// and been compacted, or it was already committed or aborted, and `Unauthorized` if `txn`
+    /// does not present the bearer token of the client which holds the transaction.
+    pub async fn release(&self, txn: &Txn, txn_id: &TxnId) -> TCResult<Vec<(TCPathBuf, Txn)>> {
+        let active = {
+            let mut active = self.active.write().await;
+            match active.get(txn_id) {
+                Some(held) if held.is_held() => {
+                    if !held.is_holder(txn.request().bearer()) {
+                        return Err(TCError::unauthorized(format!(
+                            "transaction {} is held by another client",
+                            txn_id
+                        )));
+                    }
+
+                    active.remove(txn_id).expect("held transaction")
+                }
+                Some(_) => {
+                    return Err(TCError::bad_request(
+                        "transaction is not held open for an explicit commit",
+                        txn_id,
+                    ))
+                }
+                None => return Err(TCError::not_found(format!("transaction {}", txn_id))),
+            }
+        };
+
+        let claims = active.release().unwrap_or_default();
+        Ok(self.claimed(txn.gateway.clone(), active, claims))
+    }
+
+    /// Construct a [`Txn`] for each claim of a released transaction.
+    fn claimed(
+        &self,
+        gateway: Arc<Gateway>,
+        active: Arc<Active>,
+        claims: Vec<(TCPathBuf, Arc<Request>)>,
+    ) -> Vec<(TCPathBuf, Txn)> {
+        claims
+            .into_iter()
+            .map(|(cluster_path, request)| {
+                let txn = Txn {
+                    active: active.clone(),
+                    gateway: gateway.clone(),
+                    request,
+                    dir: self.workspace.clone(),
+                    read_only: false,
+                    balanced: None,
+                    min_commit: None,
+                    epoch: None,
+                    context_metadata: Arc::new(Map::default()),
+                };
+
+                (cluster_path, txn)
+            })
+            .collect()
+    }
+
+    /// Return the [`fs::Cache`] of the workspace of this `TxnServer`.
+    pub fn cache(&self) -> &fs::Cache {
+        self.workspace.cache()
+    }
+
+    /// Return the total number of transaction conflicts on this host, the aggregate time
+    /// in seconds spent waiting to acquire a transactional lock, and the number of conflicts
+    /// of each contended resource.
+    pub fn stats(&self) -> Map<State> {
+        let stats = &self.lock_stats;
+
+        let contended = stats
+            .contended()
+            .into_iter()
+            .map(|(path, count)| Value::from(vec![Value::String(path), Value::from(count)]))
+            .collect::<Vec<Value>>();
+
+        let mut map = Map::new();
+        map.insert(CONFLICTS.into(), Value::from(stats.conflicts()).into());
+        map.insert(CONTENDED.into(), Value::from(contended).into());
+        map.insert(
+            LOCK_WAIT.into(),
+            Value::Number(stats.wait_time().as_secs_f64().into()).into(),
+        );
+
+        map
+    }
+
     pub async fn shutdown(self) -> TCResult<()> {
         tokio::spawn(async move {
             let result = loop {
@@ -86,38 +265,65 @@ impl TxnServer {
     }
 }
 
-fn spawn_cleanup_thread(workspace: fs::Dir, active: RwLock<HashMap<TxnId, Arc<Active>>>) {
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(INTERVAL.as_secs()));
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
 
-    tokio::spawn(async move {
-        loop {
-            interval.tick().await;
-            cleanup(&workspace, &active).await;
-        }
-    });
-}
+    use crate::fs::Cache;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compact() {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let workspace = fs::Dir::load(Cache::new(1_000_000), path, vec![])
+            .await
+            .unwrap();
+
+        let server = TxnServer::new(workspace.clone()).await;
 
-async fn cleanup(workspace: &fs::Dir, txn_pool: &RwLock<HashMap<TxnId, Arc<Active>>>) {
-    let expired = {
         let now = Gateway::time();
-        let mut txn_pool = txn_pool.write().await;
-        let mut expired = Vec::with_capacity(txn_pool.len());
-        for (txn_id, txn) in txn_pool.iter() {
-            if txn.expires() + GRACE < now {
-                debug!("transaction {} has expired", txn_id);
-                expired.push(*txn_id);
-            }
-        }
+        let aged = TxnId::new(NetworkTime::from_nanos(now.as_nanos() - 3_600_000_000_000));
+        let fresh = TxnId::new(now.clone());
 
-        for txn_id in &expired {
-            txn_pool.remove(txn_id);
-        }
+        workspace.create_dir_tmp(aged).await.unwrap();
+        workspace.create_dir_tmp(fresh).await.unwrap();
+
+        server.compact(None, Duration::from_secs(60), now).await;
+
+        let pending = workspace.pending_txns();
+        assert!(!pending.contains(&aged));
+        assert!(pending.contains(&fresh));
+
+        assert_eq!(workspace.entry_ids(&fresh).await.unwrap().len(), 1);
+        assert!(workspace.entry_ids(&aged).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compact_held() {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let workspace = fs::Dir::load(Cache::new(1_000_000), path, vec![])
+            .await
+            .unwrap();
+
+        let server = TxnServer::new(workspace).await;
+
+        let now = Gateway::time();
+        let aged = TxnId::new(NetworkTime::from_nanos(now.as_nanos() - 3_600_000_000_000));
+        let held = Active::new(&aged, aged.time());
+        held.held.store(true, std::sync::atomic::Ordering::Release);
+        *held.holder.lock().unwrap() = Some("holder".to_string());
+
+        assert!(held.is_holder(Some("holder")));
+        assert!(!held.is_holder(Some("other")));
+        assert!(!held.is_holder(None));
+
+        let held = Arc::new(held);
+        server.active.write().await.insert(aged, held.clone());
 
-        expired
-    };
+        server.compact(None, Duration::from_secs(60), now).await;
 
-    for txn_id in expired.into_iter() {
-        debug!("finalize expired transaction {}", txn_id);
-        workspace.finalize(&txn_id).await;
+        assert!(server.active.read().await.is_empty());
+        assert!(!held.is_held());
     }
 }
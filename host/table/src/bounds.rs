@@ -6,6 +6,7 @@ use std::ops::{Deref, DerefMut};
 
 use collate::Collate;
 use log::debug;
+use regex::Regex;
 
 use tc_error::*;
 use tc_value::{Bound, Range, Value, ValueCollator, ValueType};
@@ -18,9 +19,29 @@ use super::Column;
 pub enum ColumnBound {
     Is(Value),
     In(Range),
+
+    /// Match a `String` column against a regular expression.
+    ///
+    /// A regular expression can't be used to seek within an index, so this is treated like
+    /// an unbounded range on its column and evaluated as a filter on each row in that range.
+    /// To keep the number of rows scanned small, combine it with bounds on the columns which
+    /// precede it in an index.
+    Matches(Regex),
+
+    /// Match any one of the given values, like `IN (...)` in SQL.
+    ///
+    /// This is evaluated with one index lookup per distinct value, in index order.
+    OneOf(Vec<Value>),
 }
 
 impl ColumnBound {
+    /// Construct a [`ColumnBound::Matches`] bound by compiling the given regular expression.
+    pub fn matches(pattern: &str) -> TCResult<Self> {
+        Regex::new(pattern).map(Self::Matches).map_err(|cause| {
+            TCError::bad_request(format!("invalid regular expression {}", pattern), cause)
+        })
+    }
+
     /// Return true if the given [`ColumnBound`] falls within this one,
     /// according to the given [`ValueCollator`].
     fn contains(&self, inner: &Self, collator: &ValueCollator) -> bool {
@@ -35,19 +56,44 @@ impl ColumnBound {
                 }) => {
                     collator.compare(outer, start) == Equal && collator.compare(outer, end) == Equal
                 }
+                Self::OneOf(inner) => inner
+                    .iter()
+                    .all(|value| collator.compare(outer, value) == Equal),
                 _ => false,
             },
             Self::In(outer) => match inner {
                 Self::Is(inner) => outer.contains_value(inner, collator),
                 Self::In(inner) => outer.contains_range(inner, collator),
+                Self::Matches(_) => false,
+                Self::OneOf(inner) => inner
+                    .iter()
+                    .all(|value| outer.contains_value(value, collator)),
+            },
+            Self::Matches(outer) => match inner {
+                Self::Is(Value::String(inner)) => outer.is_match(inner),
+                Self::Matches(inner) => outer.as_str() == inner.as_str(),
+                _ => false,
             },
+            Self::OneOf(outer) => {
+                let is_member = |value: &Value| {
+                    outer
+                        .iter()
+                        .any(|member| collator.compare(member, value) == Equal)
+                };
+
+                match inner {
+                    Self::Is(inner) => is_member(inner),
+                    Self::OneOf(inner) => inner.iter().all(is_member),
+                    _ => false,
+                }
+            }
         }
     }
 
     /// Return false if this `ColumnBound` is a single [`Value`].
     pub fn is_range(&self) -> bool {
         match self {
-            ColumnBound::In(_) => true,
+            ColumnBound::In(_) | ColumnBound::Matches(_) | ColumnBound::OneOf(_) => true,
             _ => false,
         }
     }
@@ -76,6 +122,8 @@ impl fmt::Display for ColumnBound {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Is(value) => write!(f, "{}", value),
+            Self::Matches(regex) => write!(f, "matches {}", regex),
+            Self::OneOf(values) => write!(f, "one of {}", Tuple::<&Value>::from_iter(values)),
             Self::In(Range {
                 start: Bound::Un,
                 end: Bound::Un,
@@ -137,6 +185,8 @@ impl Bounds {
                 Some(ColumnBound::In(Range { start, end })) => {
                     break (prefix, start.into(), end.into()).into()
                 }
+                Some(ColumnBound::Matches(_)) => break tc_btree::Range::with_prefix(prefix),
+                Some(ColumnBound::OneOf(_)) => break tc_btree::Range::with_prefix(prefix),
                 Some(ColumnBound::Is(value)) => prefix.push(value),
             }
 
@@ -150,6 +200,40 @@ impl Bounds {
         }
     }
 
+    /// Convert these `Bounds` into a list of [`tc_btree::Range`]s, in order, whose union is
+    /// equivalent to these `Bounds`, with one range per distinct value of a
+    /// [`ColumnBound::OneOf`] bound.
+    pub fn into_btree_ranges(
+        self,
+        columns: &[Column],
+        collator: &ValueCollator,
+    ) -> TCResult<Vec<tc_btree::Range>> {
+        let one_of = columns
+            .iter()
+            .find_map(|column| match self.get(column.name()) {
+                Some(ColumnBound::OneOf(values)) => Some((column.name().clone(), values.to_vec())),
+                _ => None,
+            });
+
+        let (name, mut values) = if let Some(one_of) = one_of {
+            one_of
+        } else {
+            return self.into_btree_range(columns).map(|range| vec![range]);
+        };
+
+        values.sort_by(|l, r| collator.compare(l, r));
+        values.dedup_by(|l, r| collator.compare(l, r) == Ordering::Equal);
+
+        values
+            .into_iter()
+            .map(|value| {
+                let mut bounds = self.clone();
+                bounds.insert(name.clone(), ColumnBound::Is(value));
+                bounds.into_btree_range(columns)
+            })
+            .collect()
+    }
+
     /// Merge these `Bounds` with the given `other`.
     pub fn merge(&mut self, other: Self, collator: &ValueCollator) -> TCResult<()> {
         for (col_name, inner) in other.inner.into_iter() {
@@ -185,6 +269,21 @@ impl Bounds {
                         let end = try_cast_bound(end, *dtype)?;
                         ColumnBound::In(Range { start, end })
                     }
+                    ColumnBound::Matches(regex) => {
+                        if *dtype != ValueType::String {
+                            return Err(TCError::bad_request(
+                                "cannot match a regular expression against a column of type",
+                                dtype,
+                            ));
+                        }
+
+                        ColumnBound::Matches(regex)
+                    }
+                    ColumnBound::OneOf(values) => values
+                        .into_iter()
+                        .map(|value| dtype.try_cast(value))
+                        .collect::<TCResult<Vec<Value>>>()
+                        .map(ColumnBound::OneOf)?,
                 };
 
                 validated.insert(name, bound);
@@ -195,6 +294,27 @@ impl Bounds {
 
         Ok(validated.into())
     }
+
+    /// Construct a [`RowFilter`] for the [`ColumnBound::Matches`] and [`ColumnBound::OneOf`]
+    /// bounds in these `Bounds`, given rows with the given `columns`,
+    /// or return `None` if there are no such bounds.
+    pub fn row_filter(&self, columns: &[Column]) -> Option<RowFilter> {
+        let mut patterns = Vec::new();
+        let mut sets = Vec::new();
+        for (i, column) in columns.iter().enumerate() {
+            match self.get(column.name()) {
+                Some(ColumnBound::Matches(regex)) => patterns.push((i, regex.clone())),
+                Some(ColumnBound::OneOf(values)) => sets.push((i, values.to_vec())),
+                _ => {}
+            }
+        }
+
+        if patterns.is_empty() && sets.is_empty() {
+            None
+        } else {
+            Some(RowFilter { patterns, sets })
+        }
+    }
 }
 
 impl Deref for Bounds {
@@ -233,3 +353,27 @@ impl fmt::Display for Bounds {
         fmt::Display::fmt(&Map::from_iter(self.inner.clone()), f)
     }
 }
+
+/// A filter on a stream of rows, which checks the [`ColumnBound::Matches`]
+/// and [`ColumnBound::OneOf`] bounds of a [`Bounds`].
+#[derive(Clone)]
+pub struct RowFilter {
+    patterns: Vec<(usize, Regex)>,
+    sets: Vec<(usize, Vec<Value>)>,
+}
+
+impl RowFilter {
+    /// Return `true` if the given `row` matches every pattern and set in this filter.
+    pub fn matches(&self, row: &[Value]) -> bool {
+        let patterns = self.patterns.iter().all(|(i, regex)| match row.get(*i) {
+            Some(Value::String(s)) => regex.is_match(s),
+            _ => false,
+        });
+
+        patterns
+            && self.sets.iter().all(|(i, values)| match row.get(*i) {
+                Some(value) => values.contains(value),
+                None => false,
+            })
+    }
+}
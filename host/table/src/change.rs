@@ -0,0 +1,83 @@
+//! A [`Change`] to the rows of a [`crate::TableIndex`], for change-data-capture.
+
+use std::fmt;
+
+use tc_error::*;
+use tc_value::Value;
+use tcgeneric::{label, Label};
+
+use super::{IndexSchema, Row};
+
+const INSERT: Label = label("insert");
+const DELETE: Label = label("delete");
+const UPDATE: Label = label("update");
+
+/// A change to a single [`Row`] of a [`crate::TableIndex`].
+#[derive(Clone)]
+pub enum Change {
+    /// A new row was inserted.
+    Insert(Row),
+
+    /// An existing row was deleted.
+    Delete(Row),
+
+    /// An existing row was updated; the first row is the old value and the second is the new.
+    Update(Row, Row),
+}
+
+impl Change {
+    /// Encode this `Change` as the values of a row in a change journal with the given `schema`,
+    /// i.e. `[type, old_row, new_row]` where an absent row is `Value::None`.
+    pub(crate) fn into_values(self, schema: &IndexSchema) -> TCResult<Vec<Value>> {
+        let encode = |row: Row| -> TCResult<Value> {
+            schema
+                .values_from_row(row, true)
+                .map(|values| Value::Tuple(values.into()))
+        };
+
+        let (kind, old, new) = match self {
+            Self::Insert(row) => (INSERT, Value::None, encode(row)?),
+            Self::Delete(row) => (DELETE, encode(row)?, Value::None),
+            Self::Update(old, new) => (UPDATE, encode(old)?, encode(new)?),
+        };
+
+        Ok(vec![Value::Id(kind.into()), old, new])
+    }
+
+    /// Decode a `Change` from the values of a row in a change journal with the given `schema`.
+    pub(crate) fn from_values(schema: &IndexSchema, mut values: Vec<Value>) -> TCResult<Self> {
+        let invalid = || TCError::internal("invalid change journal entry");
+
+        let decode = |row: Value| -> TCResult<Row> {
+            match row {
+                Value::Tuple(row) => schema.row_from_values(row.into_inner()),
+                _ => Err(invalid()),
+            }
+        };
+
+        let (kind, old, new) = match (values.pop(), values.pop(), values.pop()) {
+            (Some(new), Some(old), Some(Value::Id(kind))) => (kind, old, new),
+            _ => return Err(invalid()),
+        };
+
+        if kind == INSERT {
+            decode(new).map(Self::Insert)
+        } else if kind == DELETE {
+            decode(old).map(Self::Delete)
+        } else if kind == UPDATE {
+            Ok(Self::Update(decode(old)?, decode(new)?))
+        } else {
+            Err(TCError::internal(format!("invalid change type: {}", kind)))
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Insert(row) => write!(f, "insert {}", row),
+            Self::Delete(row) => write!(f, "delete {}", row),
+            Self::Update(old, new) => write!(f, "update {} -> {}", old, new),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Summary statistics of the columns of a [`super::Table`], computed in a single pass.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use collate::Collate;
+use safecast::{CastFrom, TryCastFrom};
+
+use tc_error::*;
+use tc_value::{Number, Value, ValueCollator, ValueType};
+use tcgeneric::{label, Label, Tuple};
+
+const COUNT: Label = label("count");
+const DISTINCT: Label = label("distinct");
+const MAX: Label = label("max");
+const MEAN: Label = label("mean");
+const MIN: Label = label("min");
+
+// 2^10 registers gives a standard error of about 3%
+const PRECISION: u32 = 10;
+
+/// An approximate count of distinct values, using the HyperLogLog algorithm.
+pub(crate) struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << PRECISION],
+        }
+    }
+
+    /// Record the given `value`.
+    pub fn insert(&mut self, value: &Value) {
+        let mut hasher = DefaultHasher::new();
+        value.to_string().hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - PRECISION)) as usize;
+        let rank = ((hash << PRECISION).leading_zeros() + 1).min(64 - PRECISION + 1) as u8;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Estimate the number of distinct values recorded.
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1. + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|rank| 2f64.powi(-(*rank as i32)))
+            .sum();
+
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|rank| **rank == 0).count();
+
+        if estimate <= 2.5 * m && zeros > 0 {
+            // linear counting is more accurate for small cardinalities
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+/// The running summary of a single column.
+pub(crate) enum Summary {
+    Numeric {
+        count: u64,
+        min: Option<Value>,
+        max: Option<Value>,
+        sum: f64,
+    },
+    Other {
+        count: u64,
+        distinct: HyperLogLog,
+    },
+}
+
+impl Summary {
+    pub fn new(dtype: ValueType) -> Self {
+        match dtype {
+            ValueType::Number(_) => Self::Numeric {
+                count: 0,
+                min: None,
+                max: None,
+                sum: 0.,
+            },
+            _ => Self::Other {
+                count: 0,
+                distinct: HyperLogLog::new(),
+            },
+        }
+    }
+
+    /// Add the given `value` to this summary, ignoring nulls.
+    pub fn update(&mut self, collator: &ValueCollator, value: Value) -> TCResult<()> {
+        if value.is_none() {
+            return Ok(());
+        }
+
+        match self {
+            Self::Numeric {
+                count,
+                min,
+                max,
+                sum,
+            } => {
+                let n = Number::try_cast_from(value.clone(), |v| {
+                    TCError::bad_request("expected a number but found", v)
+                })?;
+
+                *count += 1;
+                // accumulate in f64 so that the sum of a narrow integer column can't overflow
+                *sum += f64::cast_from(n);
+
+                let less = match min {
+                    Some(min) => collator.compare(&value, min) == Ordering::Less,
+                    None => true,
+                };
+
+                if less {
+                    *min = Some(value.clone());
+                }
+
+                let greater = match max {
+                    Some(max) => collator.compare(&value, max) == Ordering::Greater,
+                    None => true,
+                };
+
+                if greater {
+                    *max = Some(value);
+                }
+            }
+            Self::Other { count, distinct } => {
+                *count += 1;
+                distinct.insert(&value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Summary> for Value {
+    fn from(summary: Summary) -> Self {
+        let stats = match summary {
+            Summary::Numeric {
+                count,
+                min,
+                max,
+                sum,
+            } => {
+                let mean = if count == 0 {
+                    Value::None
+                } else {
+                    let mean = sum / count as f64;
+                    Value::from(Number::from(mean))
+                };
+
+                vec![
+                    (COUNT, Value::from(count)),
+                    (MIN, min.unwrap_or_default()),
+                    (MAX, max.unwrap_or_default()),
+                    (MEAN, mean),
+                ]
+            }
+            Summary::Other { count, distinct } => vec![
+                (COUNT, Value::from(count)),
+                (DISTINCT, Value::from(distinct.estimate())),
+            ],
+        };
+
+        stats
+            .into_iter()
+            .map(|(name, value)| Value::Tuple(Tuple::from(vec![Value::Id(name.into()), value])))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tc_value::{NumberType, UInt, UIntType};
+
+    use super::*;
+
+    #[test]
+    fn test_distinct_estimate() {
+        for &n in &[10u64, 1_000, 100_000] {
+            let mut distinct = HyperLogLog::new();
+            for i in 0..n {
+                // insert every value twice
+                distinct.insert(&Value::String(format!("value {}", i)));
+                distinct.insert(&Value::String(format!("value {}", i)));
+            }
+
+            let error = (distinct.estimate() as f64 - n as f64).abs() / n as f64;
+            assert!(
+                error < 0.1,
+                "estimated {} distinct values of {}",
+                distinct.estimate(),
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn test_mean_of_narrow_ints() {
+        let collator = ValueCollator::default();
+        let dtype = ValueType::Number(NumberType::UInt(UIntType::U8));
+
+        // the sum of these values would overflow a u8
+        let mut summary = Summary::new(dtype);
+        for n in &[200u8, 100, 150] {
+            let n = Number::UInt(UInt::U8(*n));
+            summary.update(&collator, Value::Number(n)).unwrap();
+        }
+
+        match summary {
+            Summary::Numeric { count, sum, .. } => {
+                assert_eq!(count, 3);
+                assert_eq!(sum / count as f64, 150.);
+            }
+            Summary::Other { .. } => panic!("expected a numeric summary"),
+        }
+    }
+}
@@ -2,27 +2,42 @@ use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 use std::fmt;
 use std::iter::FromIterator;
-use std::sync::Arc;
+use std::ops;
+use std::sync::{Arc, Mutex, RwLock};
 
 use async_trait::async_trait;
 use futures::future::{self, join_all, try_join_all, TryFutureExt};
-use futures::stream::{FuturesUnordered, StreamExt, TryStreamExt};
+use futures::stream::{self, FuturesOrdered, FuturesUnordered, Stream, StreamExt, TryStreamExt};
 use log::debug;
 
 use tc_btree::{BTreeFile, BTreeInstance, BTreeType, Node};
 use tc_error::*;
 use tc_transact::fs::{CopyFrom, Dir, File, Persist, Restore};
-use tc_transact::{Transact, Transaction, TxnId};
-use tc_value::Value;
-use tcgeneric::{label, Id, Instance, Label, TCBoxTryStream, Tuple};
+use tc_transact::{Rollback, Transact, Transaction, TxnId};
+use tc_value::{Bound, Number, NumberType, Range, UIntType, Value, ValueType};
+use tcgeneric::{label, Id, Instance, Label, NetworkTime, TCBoxTryStream, TCPathBuf, Tuple};
 
-use super::view::{MergeSource, Merged, TableSlice};
+use super::view::{covered_columns, filter_rows, slice_keys, MergeSource, Merged, TableSlice};
 use super::{
-    Bounds, Column, ColumnBound, IndexSchema, IndexSlice, Row, Table, TableInstance, TableSchema,
-    TableType,
+    Bounds, Change, Column, ColumnBound, ForeignKey, IndexSchema, IndexSlice, Row, Table,
+    TableEvent, TableInstance, TableSchema, TableType, Trigger, EXPIRES_AT,
 };
 
 const PRIMARY_INDEX: Label = label("primary");
+const TRIGGERS: Label = label("triggers");
+const TRIGGER_EVENT: Label = label("event");
+const TRIGGER_ORDINAL: Label = label("ordinal");
+const TRIGGER_DEFINITION: Label = label("definition");
+const TRIGGER_EVENT_LEN: usize = 32;
+const TRIGGER_DEFINITION_LEN: usize = 1_024;
+const CHANGES: Label = label("changes");
+const CHANGE_TIME: Label = label("txn_time");
+const CHANGE_NONCE: Label = label("txn_nonce");
+const CHANGE_ORDINAL: Label = label("ordinal");
+const CHANGE_TYPE: Label = label("type");
+const CHANGE_OLD: Label = label("old");
+const CHANGE_NEW: Label = label("new");
+const CHANGE_TYPE_LEN: usize = 8;
 
 #[derive(Clone)]
 pub struct Index<F, D, Txn> {
@@ -42,12 +57,44 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> Index<F, D, Txn> {
     }
 
     pub async fn get(&self, txn_id: TxnId, key: Vec<Value>) -> TCResult<Option<Vec<Value>>> {
-        let key = self.schema.validate_key(key)?;
-        let range = tc_btree::Range::with_prefix(key);
+        let range = key_range(&self.schema, key)?;
         let mut rows = self.btree.clone().slice(range, false)?.keys(txn_id).await?;
         rows.try_next().await
     }
 
+    /// Return `true` if this `Index` has a row with the given `key`, without reading the row.
+    pub async fn contains_key(&self, txn_id: TxnId, key: Vec<Value>) -> TCResult<bool> {
+        let range = key_range(&self.schema, key)?;
+        let slice = self.btree.clone().slice(range, false)?;
+        slice.is_empty(txn_id).map_ok(|empty| !empty).await
+    }
+
+    /// Return `true` if any row of this `Index` matches the given [`Bounds`].
+    ///
+    /// Unless the bounds require a row filter, this checks each B-tree range for emptiness
+    /// instead of streaming the matching rows.
+    pub async fn probe(&self, txn_id: TxnId, bounds: Bounds) -> TCResult<bool> {
+        self.validate_bounds(&bounds)?;
+        let columns = self.schema.columns();
+        let filter = bounds.row_filter(&columns);
+        let ranges = bounds.into_btree_ranges(&columns, self.btree.collator())?;
+
+        if filter.is_some() {
+            let rows = slice_keys(self.btree.clone(), ranges, false, txn_id).await?;
+            let mut rows = filter_rows(rows, filter);
+            return rows.try_next().map_ok(|row| row.is_some()).await;
+        }
+
+        for range in ranges {
+            let slice = self.btree.clone().slice(range, false)?;
+            if !slice.is_empty(txn_id).await? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
     pub async fn is_empty(&self, txn: &Txn) -> TCResult<bool> {
         self.btree.is_empty(*txn.id()).await
     }
@@ -63,6 +110,23 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> Index<F, D, Txn> {
         self.btree.insert(txn_id, key).await
     }
 
+    async fn insert_all(
+        &self,
+        txn_id: TxnId,
+        rows: &[Row],
+        reject_extra_columns: bool,
+    ) -> TCResult<()> {
+        let keys = rows
+            .iter()
+            .map(|row| {
+                self.schema()
+                    .values_from_row(row.clone(), reject_extra_columns)
+            })
+            .collect::<TCResult<Vec<_>>>()?;
+
+        self.btree.insert_all(txn_id, keys).await
+    }
+
     pub fn schema(&'_ self) -> &'_ IndexSchema {
         &self.schema
     }
@@ -88,8 +152,11 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> Index<F, D, Txn> {
         reverse: bool,
     ) -> TCResult<TCBoxTryStream<'a, Vec<Value>>> {
         self.validate_bounds(&bounds)?;
-        let range = bounds.into_btree_range(&self.schema.columns())?;
-        self.btree.slice(range, reverse)?.keys(txn_id).await
+        let columns = self.schema.columns();
+        let filter = bounds.row_filter(&columns);
+        let ranges = bounds.into_btree_ranges(&columns, self.btree.collator())?;
+        let rows = slice_keys(self.btree, ranges, reverse, txn_id).await?;
+        Ok(filter_rows(rows, filter))
     }
 }
 
@@ -114,6 +181,11 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn> for In
         self.btree.count(txn_id).await
     }
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        covered_columns(&self.schema, columns)
+            .map(|_| IndexSlice::all(self.btree.clone(), self.schema.clone(), false))
+    }
+
     async fn delete(&self, txn_id: TxnId) -> TCResult<()> {
         self.btree.delete(txn_id).await
     }
@@ -228,6 +300,17 @@ impl<F: File<Node> + Transact, D: Dir, Txn: Transaction<D>> Transact for Index<F
     }
 }
 
+#[async_trait]
+impl<F: File<Node> + Rollback, D: Dir, Txn: Transaction<D>> Rollback for Index<F, D, Txn> {
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.btree.savepoint(txn_id, ordinal).await
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.btree.rollback_to(txn_id, ordinal).await
+    }
+}
+
 #[async_trait]
 impl<F: File<Node>, D: Dir, Txn: Transaction<D>> Persist<D> for Index<F, D, Txn>
 where
@@ -288,17 +371,10 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> ReadOnly<F, D, Txn> {
 
         let (schema, btree) = if let Some(columns) = key_columns {
             let schema = source_schema.auxiliary(&columns)?;
-            let btree = BTreeFile::create(file, schema.clone().into(), *txn.id()).await?;
-
-            let source = source.select(columns)?;
-            let rows = source.rows(*txn.id()).await?;
-            btree.try_insert_from(*txn.id(), rows).await?;
+            let btree = copy_rows(source, &schema, file, *txn.id()).await?;
             (schema, btree)
         } else {
-            let btree = BTreeFile::create(file, source_schema.clone().into(), *txn.id()).await?;
-
-            let rows = source.rows(*txn.id()).await?;
-            btree.try_insert_from(*txn.id(), rows).await?;
+            let btree = copy_rows(source, &source_schema, file, *txn.id()).await?;
             (source_schema, btree)
         };
 
@@ -309,6 +385,68 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> ReadOnly<F, D, Txn> {
             .map(|index| ReadOnly { index })
     }
 
+    /// Bring this snapshot up to date with its `source`.
+    ///
+    /// If the `changes` made to the `source` since this snapshot was taken are given, only the
+    /// rows which changed are deleted or inserted. Otherwise the snapshot is rebuilt from scratch.
+    pub async fn refresh<T, S>(&mut self, source: T, changes: Option<S>, txn: Txn) -> TCResult<()>
+    where
+        T: TableInstance<F, D, Txn>,
+        S: Stream<Item = TCResult<Change>> + Send + Unpin,
+        F: TryFrom<D::File, Error = TCError>,
+        D::FileClass: From<BTreeType>,
+    {
+        let txn_id = *txn.id();
+        let schema = self.index.schema();
+        let columns: Vec<Id> = schema.columns().into_iter().map(|c| c.name).collect();
+
+        let mut changes = if let Some(changes) = changes {
+            changes
+        } else {
+            debug!("ReadOnly::refresh falling back to a full rebuild");
+
+            let source_columns: Vec<Id> = source
+                .key()
+                .iter()
+                .chain(source.values())
+                .map(|c| c.name.clone())
+                .collect();
+
+            let key_columns = if source_columns == columns {
+                None
+            } else {
+                Some(schema.key().iter().map(|c| c.name.clone()).collect())
+            };
+
+            *self = Self::copy_from(source, txn, key_columns).await?;
+            return Ok(());
+        };
+
+        let btree = self.index.btree().clone();
+        while let Some(change) = changes.try_next().await? {
+            let (deleted, inserted) = match change {
+                Change::Insert(row) => (None, Some(row)),
+                Change::Delete(row) => (Some(row), None),
+                Change::Update(old, new) => (Some(old), Some(new)),
+            };
+
+            if let Some(row) = deleted {
+                let key = project(&columns, row)?;
+                btree
+                    .clone()
+                    .slice(tc_btree::Range::with_prefix(key), false)?
+                    .delete(txn_id)
+                    .await?;
+            }
+
+            if let Some(row) = inserted {
+                btree.insert(txn_id, project(&columns, row)?).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn into_reversed(self) -> Self {
         ReadOnly {
             index: self.index.into_reversed(),
@@ -320,6 +458,53 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> ReadOnly<F, D, Txn> {
     }
 }
 
+/// Copy the rows of `source` into a new [`BTreeFile`] with the given `schema`.
+///
+/// If the `source` can be read in the column order of `schema`, the [`BTreeFile`] is bulk-loaded
+/// bottom-up. Otherwise its rows are inserted one at a time.
+async fn copy_rows<F, D, Txn, T>(
+    source: T,
+    schema: &IndexSchema,
+    file: F,
+    txn_id: TxnId,
+) -> TCResult<BTreeFile<F, D, Txn>>
+where
+    F: File<Node>,
+    D: Dir,
+    Txn: Transaction<D>,
+    T: TableInstance<F, D, Txn>,
+{
+    let columns: Vec<Id> = schema.column_names().cloned().collect();
+
+    if source.validate_order(&columns).is_ok() {
+        let rows = source
+            .order_by(columns.to_vec(), false)?
+            .select(columns)?
+            .rows(txn_id)
+            .await?;
+
+        BTreeFile::bulk_load(file, schema.clone().into(), rows, txn_id).await
+    } else {
+        debug!("ReadOnly::copy_from falling back to incremental inserts");
+
+        let btree = BTreeFile::create(file, schema.clone().into(), txn_id).await?;
+        let rows = source.select(columns)?.rows(txn_id).await?;
+        btree.try_insert_from(txn_id, rows).await?;
+        Ok(btree)
+    }
+}
+
+// select the given `columns` of a changed `row`, in order
+fn project(columns: &[Id], mut row: Row) -> TCResult<Vec<Value>> {
+    columns
+        .iter()
+        .map(|name| {
+            row.remove(name)
+                .ok_or_else(|| TCError::bad_request("change is missing a value for column", name))
+        })
+        .collect()
+}
+
 impl<F: File<Node>, D: Dir, Txn: Transaction<D>> Instance for ReadOnly<F, D, Txn> {
     type Class = TableType;
 
@@ -338,6 +523,10 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn> for Re
         self.index.count(txn_id).await
     }
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        self.index.covering_index(columns)
+    }
+
     fn key(&self) -> &[Column] {
         self.index.key()
     }
@@ -391,10 +580,105 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> From<ReadOnly<F, D, Txn>> for T
     }
 }
 
+/// The range of an index's B-tree which holds the row with the given primary `key`, if any.
+///
+/// The key must be complete, so that a lookup only reads the leaf which would hold the row.
+fn key_range(schema: &IndexSchema, key: Vec<Value>) -> TCResult<tc_btree::Range> {
+    schema.validate_key(key).map(tc_btree::Range::with_prefix)
+}
+
+/// The schema of the index which stores the [`Trigger`]s of a [`TableIndex`].
+fn trigger_schema() -> IndexSchema {
+    let key = vec![
+        (TRIGGER_EVENT.into(), ValueType::Id, TRIGGER_EVENT_LEN).into(),
+        (
+            TRIGGER_ORDINAL.into(),
+            ValueType::Number(NumberType::UInt(UIntType::U64)),
+        )
+            .into(),
+    ];
+
+    let values = vec![(
+        TRIGGER_DEFINITION.into(),
+        ValueType::Value,
+        TRIGGER_DEFINITION_LEN,
+    )
+        .into()];
+
+    (key, values).into()
+}
+
+/// The schema of the index which journals each [`Change`] to a [`TableIndex`] with the given
+/// `primary` schema, keyed by the transaction which made it and its order within that transaction.
+fn change_schema(primary: &IndexSchema) -> IndexSchema {
+    // each encoded row holds every column of the table, plus a tuple header
+    let row_len = primary
+        .columns()
+        .iter()
+        .map(|col| col.dtype().size().or(*col.max_len()).unwrap_or_default() + 2)
+        .sum::<usize>()
+        + 2;
+
+    let u64 = ValueType::Number(NumberType::UInt(UIntType::U64));
+    let u16 = ValueType::Number(NumberType::UInt(UIntType::U16));
+
+    let key = vec![
+        (CHANGE_TIME.into(), u64).into(),
+        (CHANGE_NONCE.into(), u16).into(),
+        (CHANGE_ORDINAL.into(), u64).into(),
+    ];
+
+    let values = vec![
+        (CHANGE_TYPE.into(), ValueType::Id, CHANGE_TYPE_LEN).into(),
+        (CHANGE_OLD.into(), ValueType::Value, row_len).into(),
+        (CHANGE_NEW.into(), ValueType::Value, row_len).into(),
+    ];
+
+    (key, values).into()
+}
+
 struct Inner<F, D, Txn> {
     schema: TableSchema,
     primary: Index<F, D, Txn>,
     auxiliary: Vec<(Id, Index<F, D, Txn>)>,
+    triggers: Index<F, D, Txn>,
+    changes: Option<Index<F, D, Txn>>,
+    change_ordinals: Mutex<HashMap<TxnId, u64>>,
+    references: RwLock<Vec<Reference<F, D, Txn>>>,
+}
+
+/// A [`ForeignKey`] of another [`TableIndex`] which references the primary key of this one.
+#[derive(Clone)]
+pub struct Reference<F, D, Txn> {
+    path: TCPathBuf,
+    table: TableIndex<F, D, Txn>,
+    foreign_key: ForeignKey,
+}
+
+impl<F, D, Txn> Reference<F, D, Txn> {
+    /// Construct a new `Reference` from the given `foreign_key` of the `table` at `path`.
+    pub fn new(path: TCPathBuf, table: TableIndex<F, D, Txn>, foreign_key: ForeignKey) -> Self {
+        Self {
+            path,
+            table,
+            foreign_key,
+        }
+    }
+
+    /// The path of the referencing table.
+    pub fn path(&self) -> &TCPathBuf {
+        &self.path
+    }
+
+    /// The referencing table.
+    pub fn table(&self) -> &TableIndex<F, D, Txn> {
+        &self.table
+    }
+
+    /// The [`ForeignKey`] constraint of the referencing table.
+    pub fn foreign_key(&self) -> &ForeignKey {
+        &self.foreign_key
+    }
 }
 
 /// The base type of a [`Table`].
@@ -414,6 +698,10 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableIndex<F, D, Txn> {
         F: TryFrom<D::File, Error = TCError>,
         D::FileClass: From<BTreeType>,
     {
+        schema.validate_foreign_keys()?;
+        schema.validate_ttl()?;
+        schema.validate_computed()?;
+
         let primary_file = context
             .create_file(txn_id, PRIMARY_INDEX.into(), BTreeType::default())
             .await?;
@@ -427,10 +715,10 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableIndex<F, D, Txn> {
                 .iter()
                 .map(|(name, column_names)| (name.clone(), column_names.to_vec()))
                 .map(|(name, column_names)| async {
-                    if name == PRIMARY_INDEX {
+                    if name == PRIMARY_INDEX || name == TRIGGERS || name == CHANGES {
                         return Err(TCError::bad_request(
                             "cannot create an auxiliary index with reserved name",
-                            PRIMARY_INDEX,
+                            name,
                         ));
                     }
 
@@ -447,11 +735,31 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableIndex<F, D, Txn> {
         .into_iter()
         .collect();
 
+        let triggers_file = context
+            .create_file(txn_id, TRIGGERS.into(), BTreeType::default())
+            .await?;
+
+        let triggers = Index::create(triggers_file, trigger_schema(), txn_id).await?;
+
+        let changes = if schema.tracks_changes() {
+            let file = context
+                .create_file(txn_id, CHANGES.into(), BTreeType::default())
+                .await?;
+
+            Some(Index::create(file, change_schema(schema.primary()), txn_id).await?)
+        } else {
+            None
+        };
+
         Ok(TableIndex {
             inner: Arc::new(Inner {
                 schema,
                 primary,
                 auxiliary,
+                triggers,
+                changes,
+                change_ordinals: Mutex::default(),
+                references: RwLock::default(),
             }),
         })
     }
@@ -486,11 +794,217 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableIndex<F, D, Txn> {
         Ok(merged)
     }
 
+    /// Register a [`Trigger`] to run whenever the given `event` occurs.
+    ///
+    /// The trigger's definition is stored in this table, so it's persisted, and committed or
+    /// rolled back, along with the current transaction.
+    /// Triggers run in the order in which they were added.
+    pub async fn add_trigger<T: Trigger<Txn>>(
+        &self,
+        txn: &Txn,
+        event: TableEvent,
+        trigger: &T,
+    ) -> TCResult<()> {
+        let txn_id = *txn.id();
+        let definition = trigger.definition(txn).await?;
+
+        let event = Value::from(event.as_id());
+        let triggers = self.inner.triggers.btree();
+        let range = tc_btree::Range::with_prefix(vec![event.clone()]);
+        let ordinal = triggers.clone().slice(range, false)?.count(txn_id).await?;
+
+        triggers
+            .insert(txn_id, vec![event, Value::from(ordinal), definition])
+            .await
+    }
+
+    /// Load the [`Trigger`]s to run when the given `event` occurs, in the order they were added.
+    pub(crate) async fn triggers<T: Trigger<Txn>>(
+        &self,
+        txn: &Txn,
+        event: TableEvent,
+    ) -> TCResult<Vec<T>> {
+        let range = tc_btree::Range::with_prefix(vec![Value::from(event.as_id())]);
+        let slice = self.inner.triggers.btree().clone().slice(range, false)?;
+        let keys: Vec<Vec<Value>> = slice.keys(*txn.id()).await?.try_collect().await?;
+
+        let mut triggers = Vec::with_capacity(keys.len());
+        for mut key in keys {
+            let definition = key
+                .pop()
+                .ok_or_else(|| TCError::internal("Table trigger has no definition"))?;
+
+            triggers.push(T::load(txn, definition).await?);
+        }
+
+        Ok(triggers)
+    }
+
+    /// Delete the given `rows` of this table, one at a time, firing the given `triggers` after
+    /// each row is deleted. Returns the number of rows deleted.
+    pub(crate) async fn delete_rows<'a, T: Trigger<Txn>>(
+        &self,
+        txn: &Txn,
+        mut rows: TCBoxTryStream<'a, Vec<Value>>,
+        triggers: &[T],
+    ) -> TCResult<u64> {
+        let schema = self.inner.primary.schema();
+
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await? {
+            let row = schema.row_from_values(row)?;
+            self.delete_row(*txn.id(), row.clone()).await?;
+
+            for trigger in triggers {
+                trigger.fire(txn, row.clone()).await?;
+            }
+
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Stream each [`Change`] to the rows of this table made after the transaction `since`
+    /// (or every journaled [`Change`], if `since` is `None`), in the order they were made.
+    ///
+    /// Returns a "bad request" error if the schema of this table does not track changes.
+    pub async fn changes<'a>(
+        &self,
+        txn_id: TxnId,
+        since: Option<TxnId>,
+    ) -> TCResult<TCBoxTryStream<'a, Change>> {
+        let journal = self.journal()?.btree().clone();
+
+        let entries = if let Some(since) = since {
+            let time = Value::from(since.time().as_nanos());
+            let nonce = Value::from(Number::from(since.nonce()));
+
+            // the later transactions with the same timestamp as `since`, then those after it
+            let concurrent = tc_btree::Range::from((
+                vec![time.clone()],
+                ops::Bound::Excluded(nonce),
+                ops::Bound::Unbounded,
+            ));
+
+            let later =
+                tc_btree::Range::from((vec![], ops::Bound::Excluded(time), ops::Bound::Unbounded));
+
+            let concurrent = journal
+                .clone()
+                .slice(concurrent, false)?
+                .keys(txn_id)
+                .await?;
+            let later = journal.slice(later, false)?.keys(txn_id).await?;
+            Box::pin(concurrent.chain(later))
+        } else {
+            journal.keys(txn_id).await?
+        };
+
+        let schema = self.inner.primary.schema().clone();
+        let changes = entries.and_then(move |mut entry| {
+            let values = entry.split_off(3);
+            future::ready(Change::from_values(&schema, values))
+        });
+
+        Ok(Box::pin(changes))
+    }
+
+    /// Discard the journaled [`Change`]s made by the transaction `through` and each before it.
+    ///
+    /// Returns a "bad request" error if the schema of this table does not track changes.
+    pub async fn compact_changes(&self, txn_id: TxnId, through: TxnId) -> TCResult<()> {
+        let journal = self.journal()?.btree();
+        let time = Value::from(through.time().as_nanos());
+        let nonce = Value::from(Number::from(through.nonce()));
+
+        let earlier = tc_btree::Range::from((
+            vec![],
+            ops::Bound::Unbounded,
+            ops::Bound::Excluded(time.clone()),
+        ));
+
+        let concurrent = tc_btree::Range::from((
+            vec![time],
+            ops::Bound::Unbounded,
+            ops::Bound::Included(nonce),
+        ));
+
+        journal
+            .clone()
+            .slice(earlier, false)?
+            .delete(txn_id)
+            .await?;
+        journal
+            .clone()
+            .slice(concurrent, false)?
+            .delete(txn_id)
+            .await
+    }
+
+    fn journal(&self) -> TCResult<&Index<F, D, Txn>> {
+        self.inner.changes.as_ref().ok_or_else(|| {
+            TCError::bad_request(
+                "this Table does not track changes; enable change tracking in its",
+                "schema",
+            )
+        })
+    }
+
+    /// Journal the given [`Change`], if this table tracks its changes.
+    async fn record_change(&self, txn_id: TxnId, change: Change) -> TCResult<()> {
+        let journal = match &self.inner.changes {
+            Some(journal) => journal,
+            None => return Ok(()),
+        };
+
+        let ordinal = {
+            let mut ordinals = self
+                .inner
+                .change_ordinals
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            let next = ordinals.entry(txn_id).or_default();
+            *next += 1;
+            *next - 1
+        };
+
+        let mut entry = vec![
+            Value::from(txn_id.time().as_nanos()),
+            Value::from(Number::from(txn_id.nonce())),
+            Value::from(ordinal),
+        ];
+
+        entry.extend(change.into_values(self.inner.primary.schema())?);
+        journal.btree().insert(txn_id, entry).await
+    }
+
+    /// Register a [`Reference`] to this table, to enforce whenever a row is deleted.
+    pub fn add_reference(&self, reference: Reference<F, D, Txn>) {
+        let mut references = self
+            .inner
+            .references
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        references.push(reference);
+    }
+
     /// Borrow the primary `Index` of this `TableIndex`.
     pub fn primary(&self) -> &Index<F, D, Txn> {
         &self.inner.primary
     }
 
+    /// Return a read-only view of the rows of this `TableIndex`, in the order of its primary key.
+    pub fn read_only(&self) -> TCResult<ReadOnly<F, D, Txn>> {
+        self.inner
+            .primary
+            .clone()
+            .index_slice(Bounds::default())
+            .map(|index| ReadOnly { index })
+    }
+
     /// Return an index which supports the given [`Bounds`], or an error if there is none.
     pub fn supporting_index(&self, bounds: &Bounds) -> TCResult<Index<F, D, Txn>> {
         if self.inner.primary.validate_bounds(bounds).is_ok() {
@@ -514,14 +1028,37 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableIndex<F, D, Txn> {
         self.inner.primary.get(txn_id, key).await
     }
 
+    /// Return `true` if this table has a row with the given primary `key`.
+    ///
+    /// Unlike `get`, this only checks the primary index for the key and does not return the row.
+    pub async fn contains_key(&self, txn_id: TxnId, key: Vec<Value>) -> TCResult<bool> {
+        self.inner.primary.contains_key(txn_id, key).await
+    }
+
+    /// Return `true` if any row of this table matches the given [`Bounds`].
+    ///
+    /// This uses the first index which supports `bounds`, so that a join can skip a key with no
+    /// matching rows without reading any row.
+    pub async fn probe(&self, txn_id: TxnId, bounds: Bounds) -> TCResult<bool> {
+        let index = self.supporting_index(&bounds)?;
+        index.probe(txn_id, bounds).await
+    }
+
     /// Insert a new row into this `TableIndex`, or update the row at the given `key` with `values`.
+    ///
+    /// If this table has a TTL and the row's expiration time is unset (zero or `None`),
+    /// the row will expire one TTL after the time of this transaction.
     pub async fn upsert(&self, txn_id: TxnId, key: Vec<Value>, values: Vec<Value>) -> TCResult<()> {
         let primary = &self.inner.primary;
+        let values = self.set_expiration(txn_id, values);
 
-        if let Some(row) = self.get(txn_id, key.to_vec()).await? {
+        let old = if let Some(row) = self.get(txn_id, key.to_vec()).await? {
             let row = primary.schema.row_from_values(row.to_vec())?;
-            self.delete_row(txn_id, row.clone()).await?;
-        }
+            self.remove_row(txn_id, row.clone()).await?;
+            Some(row)
+        } else {
+            None
+        };
 
         let row = primary.schema().row_from_key_values(key, values)?;
         let mut inserts = FuturesUnordered::new();
@@ -533,9 +1070,139 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableIndex<F, D, Txn> {
 
         while let Some(()) = inserts.try_next().await? {}
 
+        let change = match old {
+            Some(old) => Change::Update(old, row),
+            None => Change::Insert(row),
+        };
+
+        self.record_change(txn_id, change).await
+    }
+
+    /// Insert or update each of the given `(key, values)` rows, as if by [`Self::upsert`].
+    ///
+    /// The existing rows are removed first, then the new rows are written to each index with one
+    /// write per leaf node, rather than one per row as when upserting each row individually.
+    /// Each key should appear at most once in `rows`.
+    pub async fn upsert_all(
+        &self,
+        txn_id: TxnId,
+        rows: Vec<(Vec<Value>, Vec<Value>)>,
+    ) -> TCResult<()> {
+        let primary = &self.inner.primary;
+
+        let existing = rows
+            .iter()
+            .map(|(key, _)| self.get(txn_id, key.to_vec()))
+            .collect::<FuturesOrdered<_>>()
+            .map(|row| {
+                row?.map(|row| primary.schema.row_from_values(row))
+                    .transpose()
+            })
+            .try_collect::<Vec<Option<Row>>>()
+            .await?;
+
+        stream::iter(existing.iter().flatten().cloned())
+            .map(|row| self.remove_row(txn_id, row))
+            .buffer_unordered(num_cpus::get())
+            .try_fold((), |(), ()| future::ready(Ok(())))
+            .await?;
+
+        let rows = rows
+            .into_iter()
+            .map(|(key, values)| {
+                let values = self.set_expiration(txn_id, values);
+                primary.schema().row_from_key_values(key, values)
+            })
+            .collect::<TCResult<Vec<Row>>>()?;
+
+        let mut inserts = FuturesUnordered::new();
+        inserts.push(primary.insert_all(txn_id, &rows, true));
+
+        for (_, index) in &self.inner.auxiliary {
+            inserts.push(index.insert_all(txn_id, &rows, false));
+        }
+
+        while let Some(()) = inserts.try_next().await? {}
+        std::mem::drop(inserts);
+
+        for (old, row) in existing.into_iter().zip(rows) {
+            let change = match old {
+                Some(old) => Change::Update(old, row),
+                None => Change::Insert(row),
+            };
+
+            self.record_change(txn_id, change).await?;
+        }
+
         Ok(())
     }
 
+    /// Return a slice of every row whose expiration time is at or before `now`.
+    ///
+    /// Returns an error if this table has no TTL.
+    pub fn expired(&self, now: NetworkTime) -> TCResult<Merged<F, D, Txn>> {
+        if self.inner.schema.ttl().is_none() {
+            return Err(TCError::bad_request(
+                "cannot purge expired rows from a Table with no",
+                "TTL",
+            ));
+        }
+
+        let expired = Range {
+            start: Bound::Un,
+            end: Bound::In(Value::from(now.as_nanos())),
+        };
+
+        let bounds: HashMap<Id, ColumnBound> =
+            std::iter::once((EXPIRES_AT.into(), ColumnBound::In(expired))).collect();
+
+        self.clone().slice(bounds.into())
+    }
+
+    // delete the given `row` from each index of this table, without journaling the change
+    async fn remove_row(&self, txn_id: TxnId, row: Row) -> TCResult<()> {
+        let aux = &self.inner.auxiliary;
+        let row = self.inner.primary.schema().validate_row(row)?;
+
+        let mut deletes = Vec::with_capacity(aux.len() + 1);
+        for (_, index) in aux {
+            deletes.push(index.delete_row(txn_id, row.clone()));
+        }
+        deletes.push(self.inner.primary.delete_row(txn_id, row));
+        try_join_all(deletes).await?;
+
+        Ok(())
+    }
+
+    fn set_expiration(&self, txn_id: TxnId, mut values: Vec<Value>) -> Vec<Value> {
+        let ttl = match self.inner.schema.ttl() {
+            Some(ttl) => ttl,
+            None => return values,
+        };
+
+        let position = self
+            .inner
+            .primary
+            .schema()
+            .values()
+            .iter()
+            .position(|col| col.name() == &EXPIRES_AT);
+
+        if let Some(i) = position {
+            let is_unset = match values.get(i) {
+                Some(Value::None) => true,
+                Some(Value::Number(n)) => *n == Number::from(0u64),
+                _ => false,
+            };
+
+            if is_unset {
+                values[i] = Value::from((txn_id.time() + ttl).as_nanos());
+            }
+        }
+
+        values
+    }
+
     /// Stream the rows within the given [`Bounds`] from the primary index of this `TableIndex`.
     pub async fn slice_rows<'a>(
         self,
@@ -571,7 +1238,23 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn>
         self.inner.primary.clone().count(txn_id).await
     }
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        // the rows of a whole table are read in primary key order, so only the primary index
+        // can stand in for it, even if an auxiliary index also covers the given columns
+        self.inner.primary.covering_index(columns)
+    }
+
     async fn delete(&self, txn_id: TxnId) -> TCResult<()> {
+        if self.inner.changes.is_some() {
+            let schema = self.inner.primary.schema();
+            let rows = self.inner.primary.clone().rows(txn_id).await?;
+
+            rows.map(|row| row.and_then(|row| schema.row_from_values(row)))
+                .and_then(|row| self.record_change(txn_id, Change::Delete(row)))
+                .try_fold((), |(), ()| future::ready(Ok(())))
+                .await?;
+        }
+
         let aux = &self.inner.auxiliary;
 
         let mut deletes = Vec::with_capacity(aux.len() + 1);
@@ -584,18 +1267,21 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn>
         Ok(())
     }
 
-    async fn delete_row(&self, txn_id: TxnId, row: Row) -> TCResult<()> {
-        let aux = &self.inner.auxiliary;
-        let row = self.inner.primary.schema().validate_row(row)?;
+    async fn delete_counted(self, txn_id: TxnId) -> TCResult<u64> {
+        let schema = self.inner.primary.schema();
+        let rows = self.clone().rows(txn_id).await?;
 
-        let mut deletes = Vec::with_capacity(aux.len() + 1);
-        for (_, index) in aux {
-            deletes.push(index.delete_row(txn_id, row.clone()));
-        }
-        deletes.push(self.inner.primary.delete_row(txn_id, row));
-        try_join_all(deletes).await?;
+        rows.map(|row| row.and_then(|row| schema.row_from_values(row)))
+            .map_ok(|row| self.delete_row(txn_id, row))
+            .try_buffer_unordered(num_cpus::get())
+            .try_fold(0, |count, ()| future::ready(Ok(count + 1)))
+            .await
+    }
 
-        Ok(())
+    async fn delete_row(&self, txn_id: TxnId, row: Row) -> TCResult<()> {
+        let row = self.inner.primary.schema().validate_row(row)?;
+        self.remove_row(txn_id, row.clone()).await?;
+        self.record_change(txn_id, Change::Delete(row)).await
     }
 
     fn key(&self) -> &[Column] {
@@ -884,13 +1570,69 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn>
     async fn upsert(&self, txn_id: TxnId, key: Vec<Value>, values: Vec<Value>) -> TCResult<()> {
         TableIndex::upsert(self, txn_id, key, values).await
     }
+
+    async fn delete_triggered<T: Trigger<Txn>>(self, txn: &Txn) -> TCResult<u64> {
+        let triggers = self.triggers::<T>(txn, TableEvent::AfterDelete).await?;
+        if triggers.is_empty() {
+            return self.delete_counted(*txn.id()).await;
+        }
+
+        let rows = self.clone().rows(*txn.id()).await?;
+        self.delete_rows(txn, rows, &triggers).await
+    }
+
+    async fn upsert_triggered<T: Trigger<Txn>>(
+        &self,
+        txn: &Txn,
+        key: Vec<Value>,
+        values: Vec<Value>,
+    ) -> TCResult<()> {
+        let before = self.triggers::<T>(txn, TableEvent::BeforeInsert).await?;
+        let after = self.triggers::<T>(txn, TableEvent::AfterInsert).await?;
+        if before.is_empty() && after.is_empty() {
+            return self.upsert(*txn.id(), key, values).await;
+        }
+
+        let row = self
+            .inner
+            .primary
+            .schema()
+            .row_from_key_values(key.to_vec(), values.to_vec())?;
+
+        for trigger in &before {
+            trigger.fire(txn, row.clone()).await?;
+        }
+
+        self.upsert(*txn.id(), key, values).await?;
+
+        for trigger in &after {
+            trigger.fire(txn, row.clone()).await?;
+        }
+
+        Ok(())
+    }
+
+    fn references(&self) -> Vec<Reference<F, D, Txn>> {
+        let references = self
+            .inner
+            .references
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        references.to_vec()
+    }
 }
 
 #[async_trait]
 impl<F: File<Node> + Transact, D: Dir, Txn: Transaction<D>> Transact for TableIndex<F, D, Txn> {
     async fn commit(&self, txn_id: &TxnId) {
-        let mut commits = Vec::with_capacity(self.inner.auxiliary.len() + 1);
+        let mut commits = Vec::with_capacity(self.inner.auxiliary.len() + 2);
         commits.push(self.inner.primary.commit(txn_id));
+        commits.push(self.inner.triggers.commit(txn_id));
+        if let Some(changes) = &self.inner.changes {
+            commits.push(changes.commit(txn_id));
+        }
+
         for (_, index) in &self.inner.auxiliary {
             commits.push(index.commit(txn_id));
         }
@@ -899,13 +1641,61 @@ impl<F: File<Node> + Transact, D: Dir, Txn: Transaction<D>> Transact for TableIn
     }
 
     async fn finalize(&self, txn_id: &TxnId) {
-        let mut cleanups = Vec::with_capacity(self.inner.auxiliary.len() + 1);
+        let mut cleanups = Vec::with_capacity(self.inner.auxiliary.len() + 2);
         cleanups.push(self.inner.primary.finalize(txn_id));
+        cleanups.push(self.inner.triggers.finalize(txn_id));
+        if let Some(changes) = &self.inner.changes {
+            cleanups.push(changes.finalize(txn_id));
+        }
+
         for (_, index) in &self.inner.auxiliary {
             cleanups.push(index.finalize(txn_id));
         }
 
         join_all(cleanups).await;
+
+        let mut ordinals = self
+            .inner
+            .change_ordinals
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        ordinals.remove(txn_id);
+    }
+}
+
+#[async_trait]
+impl<F: File<Node> + Rollback, D: Dir, Txn: Transaction<D>> Rollback for TableIndex<F, D, Txn> {
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        let mut savepoints = Vec::with_capacity(self.inner.auxiliary.len() + 2);
+        savepoints.push(self.inner.primary.savepoint(txn_id, ordinal));
+        savepoints.push(self.inner.triggers.savepoint(txn_id, ordinal));
+        if let Some(changes) = &self.inner.changes {
+            savepoints.push(changes.savepoint(txn_id, ordinal));
+        }
+
+        for (_, index) in &self.inner.auxiliary {
+            savepoints.push(index.savepoint(txn_id, ordinal));
+        }
+
+        try_join_all(savepoints).await?;
+        Ok(())
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        let mut rollbacks = Vec::with_capacity(self.inner.auxiliary.len() + 2);
+        rollbacks.push(self.inner.primary.rollback_to(txn_id, ordinal));
+        rollbacks.push(self.inner.triggers.rollback_to(txn_id, ordinal));
+        if let Some(changes) = &self.inner.changes {
+            rollbacks.push(changes.rollback_to(txn_id, ordinal));
+        }
+
+        for (_, index) in &self.inner.auxiliary {
+            rollbacks.push(index.rollback_to(txn_id, ordinal));
+        }
+
+        try_join_all(rollbacks).await?;
+        Ok(())
     }
 }
 
@@ -943,11 +1733,37 @@ where
             auxiliary.push((name.clone(), index));
         }
 
+        // a table created before triggers were persisted has no triggers file yet
+        let triggers = if let Some(file) = store.get_file(txn.id(), &TRIGGERS.into()).await? {
+            Index::load(txn, trigger_schema(), file).await?
+        } else {
+            let file = store
+                .create_file(*txn.id(), TRIGGERS.into(), BTreeType::default())
+                .await?;
+
+            Index::create(file, trigger_schema(), *txn.id()).await?
+        };
+
+        let changes = if schema.tracks_changes() {
+            let file = store
+                .get_file(txn.id(), &CHANGES.into())
+                .await?
+                .ok_or_else(|| TCError::internal("cannot load Table: change journal is missing"))?;
+
+            Some(Index::load(txn, change_schema(schema.primary()), file).await?)
+        } else {
+            None
+        };
+
         Ok(Self {
             inner: Arc::new(Inner {
                 schema,
                 primary,
                 auxiliary,
+                triggers,
+                changes,
+                change_ordinals: Mutex::default(),
+                references: RwLock::default(),
             }),
         })
     }
@@ -966,8 +1782,14 @@ where
             ));
         }
 
-        let mut restores = Vec::with_capacity(self.inner.auxiliary.len() + 1);
+        let mut restores = Vec::with_capacity(self.inner.auxiliary.len() + 2);
         restores.push(self.inner.primary.restore(&backup.inner.primary, txn_id));
+        restores.push(self.inner.triggers.restore(&backup.inner.triggers, txn_id));
+
+        // the schemas match, so either both tables track changes or neither does
+        if let (Some(changes), Some(backup)) = (&self.inner.changes, &backup.inner.changes) {
+            restores.push(changes.restore(backup, txn_id));
+        }
 
         let mut backup_indices = BTreeMap::from_iter(
             backup
@@ -1023,3 +1845,32 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> fmt::Display for TableIndex<F,
         f.write_str("a Table")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &'static str, dtype: ValueType) -> Column {
+        (label(name).into(), dtype).into()
+    }
+
+    #[test]
+    fn test_key_range() {
+        let views = ValueType::Number(NumberType::UInt(UIntType::U64));
+        let schema: IndexSchema = (
+            vec![column("user", ValueType::String), column("day", views)],
+            vec![column("views", views)],
+        )
+            .into();
+
+        // a complete key is a point lookup, never a scan of the whole B-tree
+        let key = vec![Value::String("alice".to_string()), Value::from(3u64)];
+        let range = key_range(&schema, key.clone()).expect("range");
+        assert!(range != tc_btree::Range::default());
+        assert!(range == tc_btree::Range::with_prefix(key));
+
+        // an incomplete key would have to scan every row with its prefix
+        assert!(key_range(&schema, vec![Value::String("alice".to_string())]).is_err());
+        assert!(key_range(&schema, vec![]).is_err());
+    }
+}
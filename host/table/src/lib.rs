@@ -1,34 +1,43 @@
 //! A [`Table`], an ordered collection of [`Row`]s which supports `BTree`-based indexing
 
+use std::cmp::Ordering;
 use std::convert::TryFrom;
 use std::fmt;
 use std::marker::PhantomData;
 
 use async_trait::async_trait;
+use collate::Collate;
 use destream::{de, en};
 use futures::future::{self, TryFutureExt};
-use futures::stream::TryStreamExt;
+use futures::stream::{StreamExt, TryStreamExt};
+use safecast::{CastFrom, TryCastFrom};
 
 use tc_btree::{BTreeType, Node};
 use tc_error::*;
 use tc_transact::fs::{Dir, File, Hash};
 use tc_transact::{IntoView, Transaction, TxnId};
-use tc_value::Value;
+use tc_value::{Number, NumberClass, Value, ValueCollator, ValueType};
 use tcgeneric::{
-    path_label, Class, Id, Instance, NativeClass, PathLabel, PathSegment, TCBoxTryStream, TCPathBuf,
+    label, path_label, Class, Id, Instance, Label, Map, NativeClass, PathLabel, PathSegment,
+    TCBoxTryStream, TCPathBuf,
 };
 
 use index::*;
 use view::*;
 
 pub use bounds::*;
-pub use index::TableIndex;
+pub use change::Change;
+pub use index::{Reference, TableIndex};
 pub use schema::*;
+pub use trigger::{TableEvent, Trigger};
 pub use view::Merged;
 
 mod bounds;
+mod change;
+mod describe;
 mod index;
 mod schema;
+mod trigger;
 mod view;
 
 const PATH: PathLabel = path_label(&["state", "collection", "table"]);
@@ -59,11 +68,152 @@ pub trait TableInstance<F: File<Node>, D: Dir, Txn: Transaction<D>>:
             .await
     }
 
+    /// Compute a single aggregate [`Value`] of the given `column` over every row in this `Table`.
+    ///
+    /// Null values are ignored, except by [`AggregateFn::Count`]. The aggregate of an empty
+    /// `Table` is `Value::None`, except for [`AggregateFn::Count`], which is zero.
+    async fn aggregate(self, column: Id, func: AggregateFn, txn_id: TxnId) -> TCResult<Value> {
+        let dtype = self
+            .key()
+            .iter()
+            .chain(self.values())
+            .find(|col| col.name == column)
+            .map(|col| col.dtype)
+            .ok_or_else(|| TCError::not_found(format!("Column {}", column)))?;
+
+        let number_type = match dtype {
+            ValueType::Number(number_type) => Some(number_type),
+            _ => None,
+        };
+
+        if func.is_numeric() && number_type.is_none() {
+            return Err(TCError::bad_request(
+                format!("cannot compute the {} of a column of type", func),
+                dtype,
+            ));
+        }
+
+        let rows = self.select(vec![column])?.rows(txn_id).await?;
+        let values = rows.map_ok(|mut row| row.pop().unwrap_or_default());
+
+        match func {
+            AggregateFn::Count => {
+                values
+                    .try_fold(0u64, |count, _| future::ready(Ok(count + 1)))
+                    .map_ok(Value::from)
+                    .await
+            }
+            AggregateFn::Max | AggregateFn::Min => {
+                let collator = ValueCollator::default();
+                let extremum = values
+                    .try_filter(|value| future::ready(value.is_some()))
+                    .try_fold(None, |extremum: Option<Value>, value| {
+                        let extremum = match extremum {
+                            None => value,
+                            Some(extremum) => {
+                                let ordering = collator.compare(&value, &extremum);
+                                match func {
+                                    AggregateFn::Max if ordering == Ordering::Greater => value,
+                                    AggregateFn::Min if ordering == Ordering::Less => value,
+                                    _ => extremum,
+                                }
+                            }
+                        };
+
+                        future::ready(Ok(Some(extremum)))
+                    })
+                    .await?;
+
+                Ok(extremum.unwrap_or_default())
+            }
+            AggregateFn::Avg | AggregateFn::Sum => {
+                let zero = number_type.expect("numeric column type").zero();
+                let (sum, count) = values
+                    .try_filter(|value| future::ready(value.is_some()))
+                    .map(|r| {
+                        r.and_then(|value| {
+                            Number::try_cast_from(value, |v| {
+                                TCError::bad_request("expected a number but found", v)
+                            })
+                        })
+                    })
+                    .try_fold((zero, 0u64), |(sum, count), n| {
+                        future::ready(Ok((sum + n, count + 1)))
+                    })
+                    .await?;
+
+                if func == AggregateFn::Sum {
+                    Ok(Value::from(sum))
+                } else if count == 0 {
+                    Ok(Value::None)
+                } else {
+                    let avg = f64::cast_from(sum) / count as f64;
+                    Ok(Value::from(Number::from(avg)))
+                }
+            }
+        }
+    }
+
+    /// Summarize each column of this `Table` in a single pass over its rows.
+    ///
+    /// A numeric column is summarized by its `count`, `min`, `max`, and `mean`, and any other
+    /// column by its `count` and an approximate number of `distinct` values. Null values are
+    /// ignored.
+    async fn describe(self, txn_id: TxnId) -> TCResult<Map<Value>> {
+        let columns: Vec<Column> = self.key().iter().chain(self.values()).cloned().collect();
+        let summaries: Vec<describe::Summary> = columns
+            .iter()
+            .map(|col| describe::Summary::new(col.dtype))
+            .collect();
+
+        let collator = ValueCollator::default();
+        let rows = self.rows(txn_id).await?;
+        let summaries = rows
+            .try_fold(summaries, |mut summaries, row| {
+                let updated = summaries
+                    .iter_mut()
+                    .zip(row)
+                    .map(|(summary, value)| summary.update(&collator, value))
+                    .collect::<TCResult<()>>();
+
+                future::ready(updated.map(|()| summaries))
+            })
+            .await?;
+
+        Ok(columns
+            .into_iter()
+            .map(|col| col.name)
+            .zip(summaries.into_iter().map(Value::from))
+            .collect())
+    }
+
+    /// Return a slice of a single index which contains all the given `columns`, if there is one,
+    /// so that they can be read without looking up each row in the primary index.
+    fn covering_index(&self, _columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        None
+    }
+
     /// Delete all rows in this `Table`.
     async fn delete(&self, _txn_id: TxnId) -> TCResult<()> {
         Err(TCError::bad_request(ERR_DELETE, self.class()))
     }
 
+    /// Delete all rows in this `Table` and return the number of rows deleted.
+    async fn delete_counted(self, txn_id: TxnId) -> TCResult<u64>
+    where
+        Self: Sync,
+    {
+        let schema: IndexSchema = (self.key().to_vec(), self.values().to_vec()).into();
+
+        let rows = self.clone().rows(txn_id).await?;
+
+        rows.map(|row| row.and_then(|row| schema.row_from_values(row)))
+            .map_ok(|row| self.delete_row(txn_id, row))
+            .try_buffer_unordered(num_cpus::get())
+            .try_fold(0, |count, ()| future::ready(Ok(count + 1)))
+            .await
+    }
+
     /// Delete the given [`Row`] from this table, if present.
     async fn delete_row(&self, _txn_id: TxnId, _row: Row) -> TCResult<()> {
         Err(TCError::bad_request(ERR_DELETE, self.class()))
@@ -124,6 +274,9 @@ pub trait TableInstance<F: File<Node>, D: Dir, Txn: Transaction<D>>:
     fn validate_order(&self, order: &[Id]) -> TCResult<()>;
 
     /// Update the values of the columns in this `Table` to match the given [`Row`].
+    ///
+    /// Columns absent from the [`Row`] are left unchanged, and columns set to [`Value::None`]
+    /// are reset to the default value of their type.
     async fn update(&self, _txn: &Txn, _value: Row) -> TCResult<()>
     where
         F: TryFrom<D::File, Error = TCError>,
@@ -141,6 +294,103 @@ pub trait TableInstance<F: File<Node>, D: Dir, Txn: Transaction<D>>:
     async fn upsert(&self, _txn_id: TxnId, _key: Vec<Value>, _value: Vec<Value>) -> TCResult<()> {
         Err(TCError::bad_request(ERR_INSERT, self.class()))
     }
+
+    /// Delete all rows in this `Table` as if by `delete_counted`, running the
+    /// [`TableEvent::AfterDelete`] [`Trigger`]s of its source [`TableIndex`] for each row.
+    async fn delete_triggered<T: Trigger<Txn>>(self, txn: &Txn) -> TCResult<u64>
+    where
+        Self: Sync,
+    {
+        self.delete_counted(*txn.id()).await
+    }
+
+    /// Insert or update the given row as if by `upsert`, running the
+    /// [`TableEvent::BeforeInsert`] and [`TableEvent::AfterInsert`] [`Trigger`]s of the source
+    /// [`TableIndex`] of this `Table`.
+    async fn upsert_triggered<T: Trigger<Txn>>(
+        &self,
+        txn: &Txn,
+        key: Vec<Value>,
+        values: Vec<Value>,
+    ) -> TCResult<()> {
+        self.upsert(*txn.id(), key, values).await
+    }
+
+    /// Return the [`Reference`]s to the source [`TableIndex`] of this `Table` by other tables.
+    fn references(&self) -> Vec<Reference<F, D, Txn>> {
+        Vec::new()
+    }
+}
+
+const AVG: Label = label("avg");
+const COUNT: Label = label("count");
+const MAX: Label = label("max");
+const MIN: Label = label("min");
+const SUM: Label = label("sum");
+
+/// An aggregate function to compute over a single column of a [`Table`]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum AggregateFn {
+    /// The arithmetic mean of a numeric column.
+    Avg,
+
+    /// The number of rows.
+    Count,
+
+    /// The greatest value of a column.
+    Max,
+
+    /// The least value of a column.
+    Min,
+
+    /// The total of a numeric column.
+    Sum,
+}
+
+impl AggregateFn {
+    /// Return `true` if this function can only be computed over a numeric column.
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Avg | Self::Sum)
+    }
+
+    fn as_id(&self) -> Id {
+        match self {
+            Self::Avg => AVG.into(),
+            Self::Count => COUNT.into(),
+            Self::Max => MAX.into(),
+            Self::Min => MIN.into(),
+            Self::Sum => SUM.into(),
+        }
+    }
+}
+
+impl TryCastFrom<Value> for AggregateFn {
+    fn can_cast_from(value: &Value) -> bool {
+        Self::opt_cast_from(value.clone()).is_some()
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        let id = Id::opt_cast_from(value)?;
+        if id == AVG {
+            Some(Self::Avg)
+        } else if id == COUNT {
+            Some(Self::Count)
+        } else if id == MAX {
+            Some(Self::Max)
+        } else if id == MIN {
+            Some(Self::Min)
+        } else if id == SUM {
+            Some(Self::Sum)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for AggregateFn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_id(), f)
+    }
 }
 
 /// The [`Class`] of a [`Table`].
@@ -255,6 +505,20 @@ where
         }
     }
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        match self {
+            Self::Index(index) => index.covering_index(columns),
+            Self::ROIndex(index) => index.covering_index(columns),
+            Self::Table(table) => table.covering_index(columns),
+            Self::Aggregate(aggregate) => aggregate.covering_index(columns),
+            Self::IndexSlice(slice) => slice.covering_index(columns),
+            Self::Limit(limited) => limited.covering_index(columns),
+            Self::Merge(merge) => merge.covering_index(columns),
+            Self::Selection(selection) => selection.covering_index(columns),
+            Self::TableSlice(slice) => slice.covering_index(columns),
+        }
+    }
+
     async fn delete(&self, txn_id: TxnId) -> TCResult<()> {
         match self {
             Self::Index(index) => index.delete(txn_id).await,
@@ -269,6 +533,20 @@ where
         }
     }
 
+    async fn delete_counted(self, txn_id: TxnId) -> TCResult<u64> {
+        match self {
+            Self::Index(index) => index.delete_counted(txn_id).await,
+            Self::ROIndex(index) => index.delete_counted(txn_id).await,
+            Self::Table(table) => table.delete_counted(txn_id).await,
+            Self::Aggregate(aggregate) => aggregate.delete_counted(txn_id).await,
+            Self::IndexSlice(slice) => slice.delete_counted(txn_id).await,
+            Self::Limit(limit) => limit.delete_counted(txn_id).await,
+            Self::Merge(merge) => merge.delete_counted(txn_id).await,
+            Self::Selection(selection) => selection.delete_counted(txn_id).await,
+            Self::TableSlice(slice) => slice.delete_counted(txn_id).await,
+        }
+    }
+
     async fn delete_row(&self, txn_id: TxnId, row: Row) -> TCResult<()> {
         match self {
             Self::Index(index) => index.delete_row(txn_id, row).await,
@@ -486,6 +764,44 @@ where
             Self::TableSlice(slice) => slice.upsert(txn_id, key, values).await,
         }
     }
+
+    async fn delete_triggered<T: Trigger<Txn>>(self, txn: &Txn) -> TCResult<u64> {
+        match self {
+            Self::Table(table) => table.delete_triggered::<T>(txn).await,
+            Self::Merge(merge) => merge.delete_triggered::<T>(txn).await,
+            Self::TableSlice(slice) => slice.delete_triggered::<T>(txn).await,
+            Self::Limit(limited) => limited.delete_triggered::<T>(txn).await,
+            // these views have no source TableIndex whose triggers could fire, and a Selection
+            // or an Aggregate does not support deleting rows at all
+            Self::Index(_)
+            | Self::ROIndex(_)
+            | Self::IndexSlice(_)
+            | Self::Aggregate(_)
+            | Self::Selection(_) => self.delete_counted(*txn.id()).await,
+        }
+    }
+
+    async fn upsert_triggered<T: Trigger<Txn>>(
+        &self,
+        txn: &Txn,
+        key: Vec<Value>,
+        values: Vec<Value>,
+    ) -> TCResult<()> {
+        match self {
+            Self::Table(table) => table.upsert_triggered::<T>(txn, key, values).await,
+            other => other.upsert(*txn.id(), key, values).await,
+        }
+    }
+
+    fn references(&self) -> Vec<Reference<F, D, Txn>> {
+        match self {
+            Self::Table(table) => table.references(),
+            Self::Limit(limited) => limited.references(),
+            Self::Merge(merge) => merge.references(),
+            Self::TableSlice(slice) => slice.references(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 #[async_trait]
@@ -602,7 +918,15 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> de::Visitor for RowVisitor<F, D
     async fn visit_seq<A: de::SeqAccess>(self, mut seq: A) -> Result<Self::Value, A::Error> {
         let schema = self.table.primary().schema();
 
-        while let Some(row) = seq.next_element(()).await? {
+        // a view of a Table includes the values of its computed columns, which aren't stored
+        let stored = schema.key().len() + schema.values().len();
+        let computed = self.table.schema().computed().len();
+
+        while let Some(mut row) = seq.next_element::<Vec<Value>>(()).await? {
+            if computed > 0 && row.len() == stored + computed {
+                row.truncate(stored);
+            }
+
             let row = schema.row_from_values(row).map_err(de::Error::custom)?;
             let (key, values) = schema.key_values_from_row(row).map_err(de::Error::custom)?;
             self.table
@@ -634,6 +958,13 @@ pub struct TableView<'en> {
     rows: TCBoxTryStream<'en, Vec<Value>>,
 }
 
+impl<'en> TableView<'en> {
+    /// Construct a new `TableView` of the given `rows`, which must match the given `schema`.
+    pub fn new(schema: TableSchema, rows: TCBoxTryStream<'en, Vec<Value>>) -> Self {
+        Self { schema, rows }
+    }
+}
+
 impl<'en> en::IntoStream<'en> for TableView<'en> {
     fn into_stream<E: en::Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
         (self.schema, en::SeqStream::from(self.rows)).into_stream(encoder)
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::iter::FromIterator;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use destream::{de, en};
@@ -8,8 +9,8 @@ use futures::TryFutureExt;
 use safecast::*;
 
 use tc_error::*;
-use tc_value::{Value, ValueType};
-use tcgeneric::{Id, Map, Tuple};
+use tc_value::{Number, NumberType, UIntType, Value, ValueType};
+use tcgeneric::{label, Id, Label, Map, NativeClass, TCPathBuf, Tuple};
 
 pub use tc_btree::Column;
 
@@ -201,6 +202,10 @@ impl IndexSchema {
     }
 
     /// Return an error if the given [`Row`] has any extra fields or incompatible values.
+    ///
+    /// An explicit [`Value::None`] is replaced with the default value of its column's type,
+    /// so that a partial update can clear a column without having to omit it. A column whose
+    /// type has no default value, like an `Id` or a `Link`, cannot be cleared.
     pub fn validate_row_partial(&self, row: Row) -> TCResult<Row> {
         let mut validated = Row::new();
         let columns: HashMap<Id, ValueType> = self
@@ -214,7 +219,13 @@ impl IndexSchema {
                 .get(&col_name)
                 .ok_or(TCError::bad_request("No such column", &col_name))?;
 
-            let value = dtype.try_cast(value)?;
+            let value = match value {
+                Value::None => dtype.default_value().ok_or_else(|| {
+                    TCError::bad_request("this column has no default value to reset to", &col_name)
+                })?,
+                value => dtype.try_cast(value)?,
+            };
+
             validated.insert(col_name, value);
         }
 
@@ -366,11 +377,284 @@ impl fmt::Display for IndexSchema {
     }
 }
 
+/// The name of the column which holds the expiration time of a row in a `Table` with a TTL,
+/// in nanoseconds since the Unix epoch.
+pub const EXPIRES_AT: Label = label("expires_at");
+
+const RESTRICT: Label = label("restrict");
+const CASCADE: Label = label("cascade");
+
+/// The action to take when a row referenced by a [`ForeignKey`] is deleted.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum OnDelete {
+    /// Reject the delete as long as any row still references the deleted row.
+    Restrict,
+
+    /// Delete every row which references the deleted row.
+    Cascade,
+}
+
+impl Default for OnDelete {
+    fn default() -> Self {
+        Self::Restrict
+    }
+}
+
+impl OnDelete {
+    fn as_id(&self) -> Id {
+        match self {
+            Self::Restrict => RESTRICT.into(),
+            Self::Cascade => CASCADE.into(),
+        }
+    }
+}
+
+impl TryCastFrom<Value> for OnDelete {
+    fn can_cast_from(value: &Value) -> bool {
+        Self::opt_cast_from(value.clone()).is_some()
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        let id = Id::opt_cast_from(value)?;
+        if id == RESTRICT {
+            Some(Self::Restrict)
+        } else if id == CASCADE {
+            Some(Self::Cascade)
+        } else {
+            None
+        }
+    }
+}
+
+#[async_trait]
+impl de::FromStream for OnDelete {
+    type Context = ();
+
+    async fn from_stream<D: de::Decoder>(cxt: (), decoder: &mut D) -> Result<Self, D::Error> {
+        let id: Id = de::FromStream::from_stream(cxt, decoder).await?;
+        Self::opt_cast_from(Value::Id(id.clone()))
+            .ok_or_else(|| de::Error::invalid_value(id, "restrict or cascade"))
+    }
+}
+
+impl<'en> en::IntoStream<'en> for OnDelete {
+    fn into_stream<E: en::Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
+        self.as_id().into_stream(encoder)
+    }
+}
+
+impl fmt::Display for OnDelete {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_id(), f)
+    }
+}
+
+/// A referential integrity constraint on a `Table` column.
+///
+/// Every value of `column` must be present in `foreign_column` of the `Table` at `table`,
+/// which must be that table's (single-column) primary key.
+#[derive(Clone, Eq, PartialEq)]
+pub struct ForeignKey {
+    column: Id,
+    table: TCPathBuf,
+    foreign_column: Id,
+    on_delete: OnDelete,
+}
+
+impl ForeignKey {
+    /// Construct a new `ForeignKey` constraint.
+    pub fn new(column: Id, table: TCPathBuf, foreign_column: Id, on_delete: OnDelete) -> Self {
+        Self {
+            column,
+            table,
+            foreign_column,
+            on_delete,
+        }
+    }
+
+    /// The name of the constrained column.
+    pub fn column(&self) -> &Id {
+        &self.column
+    }
+
+    /// The path of the referenced `Table`.
+    pub fn table(&self) -> &TCPathBuf {
+        &self.table
+    }
+
+    /// The name of the referenced column.
+    pub fn foreign_column(&self) -> &Id {
+        &self.foreign_column
+    }
+
+    /// The action to take when a referenced row is deleted.
+    pub fn on_delete(&self) -> OnDelete {
+        self.on_delete
+    }
+}
+
+impl TryCastFrom<Value> for ForeignKey {
+    fn can_cast_from(value: &Value) -> bool {
+        value.matches::<(Id, TCPathBuf, Id)>()
+            || value.matches::<Tuple<Value>>() && {
+                let tuple: Tuple<Value> = value.clone().opt_cast_into().unwrap();
+                tuple.matches::<(Id, TCPathBuf, Id, OnDelete)>()
+            }
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        if value.matches::<(Id, TCPathBuf, Id)>() {
+            let (column, table, foreign_column) = value.opt_cast_into().unwrap();
+            Some(Self::new(
+                column,
+                table,
+                foreign_column,
+                OnDelete::default(),
+            ))
+        } else {
+            let tuple: Tuple<Value> = value.opt_cast_into()?;
+            let (column, table, foreign_column, on_delete) = tuple.opt_cast_into()?;
+            Some(Self::new(column, table, foreign_column, on_delete))
+        }
+    }
+}
+
+impl CastFrom<ForeignKey> for Value {
+    fn cast_from(fk: ForeignKey) -> Self {
+        Value::Tuple(
+            vec![
+                fk.column.into(),
+                fk.table.into(),
+                fk.foreign_column.into(),
+                fk.on_delete.as_id().into(),
+            ]
+            .into(),
+        )
+    }
+}
+
+#[async_trait]
+impl de::FromStream for ForeignKey {
+    type Context = ();
+
+    async fn from_stream<D: de::Decoder>(cxt: (), decoder: &mut D) -> Result<Self, D::Error> {
+        de::FromStream::from_stream(cxt, decoder)
+            .map_ok(|(column, table, foreign_column, on_delete)| Self {
+                column,
+                table,
+                foreign_column,
+                on_delete,
+            })
+            .await
+    }
+}
+
+impl<'en> en::IntoStream<'en> for ForeignKey {
+    fn into_stream<E: en::Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
+        (self.column, self.table, self.foreign_column, self.on_delete).into_stream(encoder)
+    }
+}
+
+impl fmt::Display for ForeignKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} references {}: {} (on delete {})",
+            self.column, self.table, self.foreign_column, self.on_delete
+        )
+    }
+}
+
+/// A read-only `Table` column which is never stored, but computed as the rows of the table are
+/// streamed by calling the POST op at `op` with the values of the other columns of each row.
+///
+/// A computed column can't be indexed, so a `Table` can't be sliced or ordered by it.
+#[derive(Clone, Eq, PartialEq)]
+pub struct ComputedColumn {
+    column: Column,
+    op: TCPathBuf,
+}
+
+impl ComputedColumn {
+    /// Construct a new `ComputedColumn`.
+    pub fn new(name: Id, dtype: ValueType, op: TCPathBuf) -> Self {
+        Self {
+            column: (name, dtype).into(),
+            op,
+        }
+    }
+
+    /// The name and data type of this column.
+    pub fn column(&self) -> &Column {
+        &self.column
+    }
+
+    /// The path of the POST op which computes the value of this column.
+    pub fn op(&self) -> &TCPathBuf {
+        &self.op
+    }
+}
+
+impl TryCastFrom<Value> for ComputedColumn {
+    fn can_cast_from(value: &Value) -> bool {
+        value.matches::<(Id, ValueType, TCPathBuf)>()
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        let (name, dtype, op) = value.opt_cast_into()?;
+        Some(Self::new(name, dtype, op))
+    }
+}
+
+impl CastFrom<ComputedColumn> for Value {
+    fn cast_from(computed: ComputedColumn) -> Self {
+        Value::Tuple(
+            vec![
+                computed.column.name.into(),
+                Value::from(computed.column.dtype.path()),
+                computed.op.into(),
+            ]
+            .into(),
+        )
+    }
+}
+
+#[async_trait]
+impl de::FromStream for ComputedColumn {
+    type Context = ();
+
+    async fn from_stream<D: de::Decoder>(cxt: (), decoder: &mut D) -> Result<Self, D::Error> {
+        de::FromStream::from_stream(cxt, decoder)
+            .map_ok(|(name, dtype, op)| Self::new(name, dtype, op))
+            .await
+    }
+}
+
+impl<'en> en::IntoStream<'en> for ComputedColumn {
+    fn into_stream<E: en::Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
+        (self.column.name, self.column.dtype, self.op).into_stream(encoder)
+    }
+}
+
+impl fmt::Display for ComputedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} computed by {}",
+            self.column.name, self.column.dtype, self.op
+        )
+    }
+}
+
 /// The schema of a `Table`.
 #[derive(Clone, Eq, PartialEq)]
 pub struct TableSchema {
     primary: IndexSchema,
     indices: Vec<(Id, Vec<Id>)>,
+    foreign_keys: Vec<ForeignKey>,
+    ttl: Option<Duration>,
+    track_changes: bool,
+    computed: Vec<ComputedColumn>,
 }
 
 impl TableSchema {
@@ -379,9 +663,95 @@ impl TableSchema {
         Self {
             primary,
             indices: indices.into_iter().collect(),
+            foreign_keys: vec![],
+            ttl: None,
+            track_changes: false,
+            computed: vec![],
         }
     }
 
+    /// Expire each row of a `Table` with this schema `ttl` after it's written,
+    /// unless its [`EXPIRES_AT`] column is set explicitly.
+    ///
+    /// This also adds an index on the [`EXPIRES_AT`] column, if there isn't one already,
+    /// so that expired rows can be found without a full table scan.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        let expires_at: Id = EXPIRES_AT.into();
+        if !self
+            .indices
+            .iter()
+            .any(|(_, columns)| columns.first() == Some(&expires_at))
+        {
+            self.indices.push((expires_at.clone(), vec![expires_at]));
+        }
+
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Return the time-to-live of a row in a `Table` with this schema, if any.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Journal each [`crate::Change`] to the rows of a `Table` with this schema,
+    /// so that the changes made since a given transaction can be streamed.
+    pub fn with_change_tracking(mut self) -> Self {
+        self.track_changes = true;
+        self
+    }
+
+    /// Return `true` if a `Table` with this schema journals the changes to its rows.
+    pub fn tracks_changes(&self) -> bool {
+        self.track_changes
+    }
+
+    /// Add the given [`ComputedColumn`]s to this schema.
+    pub fn with_computed_columns<I: IntoIterator<Item = ComputedColumn>>(
+        mut self,
+        computed: I,
+    ) -> Self {
+        self.computed.extend(computed);
+        self
+    }
+
+    /// Return the [`ComputedColumn`]s of this schema, which follow its stored columns in a row.
+    pub fn computed(&self) -> &[ComputedColumn] {
+        &self.computed
+    }
+
+    /// Add the given [`ForeignKey`] constraints to this schema.
+    ///
+    /// This also adds an index on each referencing column which doesn't lead the primary key or
+    /// an existing index, so that the rows which reference a deleted row can be found without a
+    /// full table scan.
+    pub fn with_foreign_keys<I: IntoIterator<Item = ForeignKey>>(
+        mut self,
+        foreign_keys: I,
+    ) -> Self {
+        for fk in foreign_keys {
+            let column = fk.column();
+            let indexed = self.primary.key().first().map(|col| col.name()) == Some(column)
+                || self
+                    .indices
+                    .iter()
+                    .any(|(_, columns)| columns.first() == Some(column));
+
+            if !indexed {
+                self.indices.push((column.clone(), vec![column.clone()]));
+            }
+
+            self.foreign_keys.push(fk);
+        }
+
+        self
+    }
+
+    /// Return the [`ForeignKey`] constraints of this schema.
+    pub fn foreign_keys(&self) -> &[ForeignKey] {
+        &self.foreign_keys
+    }
+
     /// Return a list of index names and the names of the columns they index.
     pub fn indices(&self) -> &[(Id, Vec<Id>)] {
         &self.indices
@@ -391,50 +761,243 @@ impl TableSchema {
     pub fn primary(&self) -> &IndexSchema {
         &self.primary
     }
+
+    /// Return an error if any [`ForeignKey`] of this schema refers to a nonexistent column.
+    pub fn validate_foreign_keys(&self) -> TCResult<()> {
+        for fk in &self.foreign_keys {
+            if !self.primary.column_names().any(|name| name == fk.column()) {
+                return Err(TCError::bad_request(
+                    "foreign key refers to a nonexistent column",
+                    fk.column(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return an error if a [`ComputedColumn`] of this schema reuses the name of another column
+    /// or is indexed.
+    pub fn validate_computed(&self) -> TCResult<()> {
+        let mut names: HashSet<&Id> = self.primary.column_names().collect();
+
+        for computed in &self.computed {
+            let name = &computed.column.name;
+            if !names.insert(name) {
+                return Err(TCError::bad_request(
+                    "computed column name is already in use",
+                    name,
+                ));
+            }
+
+            if self
+                .indices
+                .iter()
+                .any(|(_, columns)| columns.contains(name))
+            {
+                return Err(TCError::bad_request(
+                    "a computed column cannot be indexed",
+                    name,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return an error if this schema has a TTL but no `u64` [`EXPIRES_AT`] value column.
+    pub fn validate_ttl(&self) -> TCResult<()> {
+        if self.ttl.is_none() {
+            return Ok(());
+        }
+
+        let expires_at = self
+            .primary
+            .values()
+            .iter()
+            .find(|col| col.name() == &EXPIRES_AT);
+
+        match expires_at {
+            Some(col) if col.dtype == ValueType::Number(NumberType::UInt(UIntType::U64)) => Ok(()),
+            _ => Err(TCError::bad_request(
+                "a Table with a TTL requires a U64 value column named",
+                EXPIRES_AT,
+            )),
+        }
+    }
+}
+
+struct TableSchemaVisitor;
+
+#[async_trait]
+impl de::Visitor for TableSchemaVisitor {
+    type Value = TableSchema;
+
+    fn expecting() -> &'static str {
+        "a Table schema"
+    }
+
+    async fn visit_seq<A: de::SeqAccess>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let primary = seq
+            .next_element(())
+            .await?
+            .ok_or_else(|| de::Error::invalid_length(0, "a primary index schema"))?;
+
+        let indices: Vec<(Id, Vec<Id>)> = seq
+            .next_element(())
+            .await?
+            .ok_or_else(|| de::Error::invalid_length(1, "a list of auxiliary indices"))?;
+
+        let foreign_keys: Vec<ForeignKey> = seq.next_element(()).await?.unwrap_or_default();
+
+        let mut schema = TableSchema::new(primary, indices).with_foreign_keys(foreign_keys);
+
+        if let Some(ttl) = seq.next_element::<Option<f64>>(()).await?.flatten() {
+            let ttl = ttl_from_secs(ttl).map_err(de::Error::custom)?;
+            schema = schema.with_ttl(ttl);
+        }
+
+        if seq.next_element::<bool>(()).await?.unwrap_or_default() {
+            schema = schema.with_change_tracking();
+        }
+
+        if let Some(computed) = seq.next_element::<Vec<ComputedColumn>>(()).await? {
+            schema = schema.with_computed_columns(computed);
+        }
+
+        Ok(schema)
+    }
 }
 
 #[async_trait]
 impl de::FromStream for TableSchema {
     type Context = ();
 
-    async fn from_stream<D: de::Decoder>(cxt: (), decoder: &mut D) -> Result<Self, D::Error> {
-        de::FromStream::from_stream(cxt, decoder)
-            .map_ok(|(primary, indices)| Self { primary, indices })
-            .await
+    async fn from_stream<D: de::Decoder>(_cxt: (), decoder: &mut D) -> Result<Self, D::Error> {
+        decoder.decode_seq(TableSchemaVisitor).await
     }
 }
 
 impl<'en> en::IntoStream<'en> for TableSchema {
     fn into_stream<E: en::Encoder<'en>>(self, encoder: E) -> Result<E::Ok, E::Error> {
-        (self.primary, self.indices).into_stream(encoder)
+        let ttl = self.ttl.map(|ttl| ttl.as_secs_f64());
+
+        if !self.computed.is_empty() {
+            let computed = self.computed;
+            let track_changes = self.track_changes;
+            (
+                self.primary,
+                self.indices,
+                self.foreign_keys,
+                ttl,
+                track_changes,
+                computed,
+            )
+                .into_stream(encoder)
+        } else if self.track_changes {
+            (self.primary, self.indices, self.foreign_keys, ttl, true).into_stream(encoder)
+        } else if let Some(ttl) = ttl {
+            (self.primary, self.indices, self.foreign_keys, ttl).into_stream(encoder)
+        } else if !self.foreign_keys.is_empty() {
+            (self.primary, self.indices, self.foreign_keys).into_stream(encoder)
+        } else {
+            (self.primary, self.indices).into_stream(encoder)
+        }
     }
 }
 
 impl From<IndexSchema> for TableSchema {
     fn from(schema: IndexSchema) -> TableSchema {
-        TableSchema {
-            primary: schema,
-            indices: vec![],
-        }
+        TableSchema::new(schema, vec![])
     }
 }
 
 impl TryCastFrom<Value> for TableSchema {
     fn can_cast_from(value: &Value) -> bool {
-        value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>)>() || value.matches::<IndexSchema>()
+        if let Value::Tuple(tuple) = value {
+            if tuple.len() == 6 {
+                return Self::opt_cast_from(value.clone()).is_some();
+            }
+        }
+
+        value.matches::<(
+            IndexSchema,
+            Vec<(Id, Vec<Id>)>,
+            Vec<ForeignKey>,
+            Value,
+            bool,
+        )>() || value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>, Vec<ForeignKey>, Number)>()
+            || value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>, Vec<ForeignKey>)>()
+            || value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>)>()
+            || value.matches::<IndexSchema>()
     }
 
     fn opt_cast_from(value: Value) -> Option<TableSchema> {
-        if value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>)>() {
+        // there's no cast for a 6-tuple, so split off the computed columns, if any, first
+        let value = match value {
+            Value::Tuple(tuple) if tuple.len() == 6 => {
+                let mut tuple = tuple.into_inner();
+                let computed: Vec<ComputedColumn> = tuple.pop()?.opt_cast_into()?;
+                let schema = Self::opt_cast_from(Value::Tuple(tuple.into()))?;
+                return Some(schema.with_computed_columns(computed));
+            }
+            value => value,
+        };
+
+        if value.matches::<(
+            IndexSchema,
+            Vec<(Id, Vec<Id>)>,
+            Vec<ForeignKey>,
+            Value,
+            bool,
+        )>() {
+            let (primary, indices, foreign_keys, ttl, track_changes): (
+                IndexSchema,
+                Vec<(Id, Vec<Id>)>,
+                Vec<ForeignKey>,
+                Value,
+                bool,
+            ) = value.opt_cast_into().unwrap();
+
+            let mut schema = TableSchema::new(primary, indices).with_foreign_keys(foreign_keys);
+
+            if !ttl.is_none() {
+                let ttl = Number::opt_cast_from(ttl)?;
+                schema = schema.with_ttl(ttl_from_secs(f64::cast_from(ttl)).ok()?);
+            }
+
+            if track_changes {
+                schema = schema.with_change_tracking();
+            }
+
+            Some(schema)
+        } else if value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>, Vec<ForeignKey>, Number)>() {
+            let (primary, indices, foreign_keys, ttl): (
+                IndexSchema,
+                Vec<(Id, Vec<Id>)>,
+                Vec<ForeignKey>,
+                Number,
+            ) = value.opt_cast_into().unwrap();
+
+            let ttl = ttl_from_secs(f64::cast_from(ttl)).ok()?;
+            let schema = TableSchema::new(primary, indices).with_foreign_keys(foreign_keys);
+            Some(schema.with_ttl(ttl))
+        } else if value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>, Vec<ForeignKey>)>() {
+            let (primary, indices, foreign_keys): (
+                IndexSchema,
+                Vec<(Id, Vec<Id>)>,
+                Vec<ForeignKey>,
+            ) = value.opt_cast_into().unwrap();
+
+            Some(TableSchema::new(primary, indices).with_foreign_keys(foreign_keys))
+        } else if value.matches::<(IndexSchema, Vec<(Id, Vec<Id>)>)>() {
             let (primary, indices): (IndexSchema, Vec<(Id, Vec<Id>)>) =
                 value.opt_cast_into().unwrap();
 
-            let indices = indices.into_iter().collect();
-            Some(TableSchema { primary, indices })
+            Some(TableSchema::new(primary, indices))
         } else if value.matches::<IndexSchema>() {
-            let primary = value.opt_cast_into().unwrap();
-            let indices = vec![];
-            Some(TableSchema { primary, indices })
+            let primary: IndexSchema = value.opt_cast_into().unwrap();
+            Some(primary.into())
         } else {
             None
         }
@@ -449,7 +1012,33 @@ impl CastFrom<TableSchema> for Value {
             .map(|(id, col_names)| (Value::from(id), Tuple::<Value>::from_iter(col_names)))
             .map(|(id, col_names)| Value::Tuple(vec![id, col_names.into()].into()));
 
-        Self::Tuple(vec![schema.primary.cast_into(), Value::from_iter(indices)].into())
+        let foreign_keys = schema.foreign_keys.into_iter().map(Value::cast_from);
+
+        let mut tuple = vec![
+            schema.primary.cast_into(),
+            Value::from_iter(indices),
+            Value::from_iter(foreign_keys),
+        ];
+
+        let ttl = schema
+            .ttl
+            .map(|ttl| Value::from(Number::from(ttl.as_secs_f64())));
+
+        if !schema.computed.is_empty() {
+            let computed = schema.computed.into_iter().map(Value::cast_from);
+            tuple.push(ttl.unwrap_or_default());
+            tuple.push(schema.track_changes.into());
+            tuple.push(Value::from_iter(computed));
+        } else if schema.track_changes {
+            tuple.push(ttl.unwrap_or_default());
+            tuple.push(true.into());
+        } else if let Some(ttl) = ttl {
+            tuple.push(ttl);
+        } else if tuple[2].is_none() {
+            tuple.pop();
+        }
+
+        Self::Tuple(tuple.into())
     }
 }
 
@@ -463,6 +1052,39 @@ impl fmt::Display for TableSchema {
             }
         }
 
+        if !self.foreign_keys.is_empty() {
+            writeln!(f, "foreign keys:")?;
+            for fk in &self.foreign_keys {
+                writeln!(f, "{}", fk)?;
+            }
+        }
+
+        if let Some(ttl) = self.ttl {
+            writeln!(f, "ttl: {:?}", ttl)?;
+        }
+
+        if self.track_changes {
+            writeln!(f, "tracks changes")?;
+        }
+
+        if !self.computed.is_empty() {
+            writeln!(f, "computed columns:")?;
+            for computed in &self.computed {
+                writeln!(f, "{}", computed)?;
+            }
+        }
+
         Ok(())
     }
 }
+
+fn ttl_from_secs(ttl: f64) -> TCResult<Duration> {
+    if ttl.is_finite() && ttl > 0. {
+        Ok(Duration::from_secs_f64(ttl))
+    } else {
+        Err(TCError::bad_request(
+            "a Table TTL must be a positive number of seconds, not",
+            ttl,
+        ))
+    }
+}
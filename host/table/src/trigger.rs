@@ -0,0 +1,80 @@
+//! Triggers which run when a [`Row`] is inserted into or deleted from a [`crate::TableIndex`].
+
+use std::fmt;
+
+use async_trait::async_trait;
+use safecast::TryCastFrom;
+
+use tc_error::*;
+use tc_value::Value;
+use tcgeneric::{label, Id, Label};
+
+use super::Row;
+
+const BEFORE_INSERT: Label = label("before_insert");
+const AFTER_INSERT: Label = label("after_insert");
+const AFTER_DELETE: Label = label("after_delete");
+
+/// The event which causes a [`Trigger`] to fire.
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TableEvent {
+    /// Fire before a row is inserted; an error aborts the insert.
+    BeforeInsert,
+
+    /// Fire after a row is inserted.
+    AfterInsert,
+
+    /// Fire after a row is deleted.
+    AfterDelete,
+}
+
+impl TableEvent {
+    pub(crate) fn as_id(&self) -> Id {
+        match self {
+            Self::BeforeInsert => BEFORE_INSERT.into(),
+            Self::AfterInsert => AFTER_INSERT.into(),
+            Self::AfterDelete => AFTER_DELETE.into(),
+        }
+    }
+}
+
+impl TryCastFrom<Value> for TableEvent {
+    fn can_cast_from(value: &Value) -> bool {
+        Self::opt_cast_from(value.clone()).is_some()
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        let id = Id::opt_cast_from(value)?;
+        if id == BEFORE_INSERT {
+            Some(Self::BeforeInsert)
+        } else if id == AFTER_INSERT {
+            Some(Self::AfterInsert)
+        } else if id == AFTER_DELETE {
+            Some(Self::AfterDelete)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for TableEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_id(), f)
+    }
+}
+
+/// An action to take within the current transaction when a [`TableEvent`] occurs.
+///
+/// A `Trigger` is stored in its [`crate::TableIndex`] as a [`Value`] definition, so that it's
+/// persisted, and added or rolled back transactionally, along with the rows of the table.
+#[async_trait]
+pub trait Trigger<Txn: Send + Sync>: Sized + Send + Sync + 'static {
+    /// Decode a `Trigger` from its stored definition.
+    async fn load(txn: &Txn, definition: Value) -> TCResult<Self>;
+
+    /// Encode this `Trigger` as a definition to store.
+    async fn definition(&self, txn: &Txn) -> TCResult<Value>;
+
+    /// Run this trigger for the given [`Row`].
+    async fn fire(&self, txn: &Txn, row: Row) -> TCResult<()>;
+}
@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 
 use async_trait::async_trait;
 use futures::future::{self, TryFutureExt};
-use futures::stream::{StreamExt, TryStreamExt};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use log::debug;
 
 use tc_btree::{BTreeFile, BTreeInstance, Node};
@@ -15,8 +15,11 @@ use tc_transact::{Transaction, TxnId};
 use tc_value::Value;
 use tcgeneric::{Id, Instance, TCBoxTryStream};
 
-use super::index::TableIndex;
-use super::{Bounds, Column, IndexSchema, Row, Table, TableInstance, TableSchema, TableType};
+use super::index::{Reference, TableIndex};
+use super::{
+    Bounds, Column, ColumnBound, ComputedColumn, IndexSchema, Row, RowFilter, Table, TableEvent,
+    TableInstance, TableSchema, TableType, Trigger,
+};
 
 const ERR_AGGREGATE_SLICE: &str = "Table aggregate does not support slicing. \
 Consider aggregating a slice of the source table.";
@@ -28,6 +31,8 @@ Consider ordering the source or indexing the selection.";
 const ERR_LIMITED_REVERSE: &str = "Cannot reverse a limited selection. \
 Consider reversing a slice before limiting";
 
+const ERR_COMPUTED: &str = "A computed column can only be read from the stream of a Table's rows";
+
 #[derive(Clone)]
 pub struct Aggregate<F, D, Txn, T> {
     source: Selection<F, D, Txn, T>,
@@ -107,6 +112,7 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInstance<F, D, Txn>>
         let source = Selection {
             source: aggregate.source.source.into(),
             schema: aggregate.source.schema,
+            computed: aggregate.source.computed,
             columns: aggregate.source.columns,
             indices: aggregate.source.indices,
             phantom: Phantom::default(),
@@ -125,6 +131,7 @@ pub struct IndexSlice<F, D, Txn> {
     schema: IndexSchema,
     bounds: Bounds,
     range: tc_btree::Range,
+    filter: Option<Box<RowFilter>>,
     reverse: bool,
 }
 
@@ -135,6 +142,7 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> IndexSlice<F, D, Txn> {
             schema,
             bounds: Bounds::default(),
             range: tc_btree::Range::default(),
+            filter: None,
             reverse,
         }
     }
@@ -151,15 +159,19 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> IndexSlice<F, D, Txn> {
 
         let bounds = bounds.validate(&columns)?;
         let range = bounds.clone().into_btree_range(&columns)?;
+        let filter = bounds.row_filter(&columns);
 
         debug!("bounds {} == range {:?}", bounds, range);
-        assert_eq!(bounds.len(), range.len());
+        if filter.is_none() {
+            assert_eq!(bounds.len(), range.len());
+        }
 
         Ok(IndexSlice {
             source,
             schema,
             bounds,
             range,
+            filter: filter.map(Box::new),
             reverse: false,
         })
     }
@@ -168,6 +180,10 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> IndexSlice<F, D, Txn> {
         &self.bounds
     }
 
+    pub(crate) fn btree(&'_ self) -> &'_ BTreeFile<F, D, Txn> {
+        &self.source
+    }
+
     pub fn schema(&'_ self) -> &'_ IndexSchema {
         &self.schema
     }
@@ -178,6 +194,11 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> IndexSlice<F, D, Txn> {
     }
 
     pub async fn is_empty(&self, txn: &Txn) -> TCResult<bool> {
+        if self.filter.is_some() {
+            let mut rows = self.clone().rows(*txn.id()).await?;
+            return rows.try_next().map_ok(|row| row.is_none()).await;
+        }
+
         self.source
             .clone()
             .slice(self.range.clone(), self.reverse)?
@@ -209,8 +230,11 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> IndexSlice<F, D, Txn> {
         reverse: bool,
     ) -> TCResult<TCBoxTryStream<'a, Vec<Value>>> {
         let reverse = self.reverse ^ reverse;
-        let range = bounds.into_btree_range(&self.schema.columns())?;
-        self.source.slice(range, reverse)?.keys(txn_id).await
+        let columns = self.schema.columns();
+        let filter = bounds.row_filter(&columns);
+        let ranges = bounds.into_btree_ranges(&columns, self.source.collator())?;
+        let rows = slice_keys(self.source, ranges, reverse, txn_id).await?;
+        Ok(filter_rows(rows, filter))
     }
 }
 
@@ -236,7 +260,18 @@ where
     type Reverse = Self;
     type Slice = Table<F, D, Txn>;
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        covered_columns(&self.schema, columns).map(|_| self.clone())
+    }
+
     async fn count(self, txn_id: TxnId) -> TCResult<u64> {
+        if self.filter.is_some() {
+            let rows = self.rows(txn_id).await?;
+            return rows
+                .try_fold(0u64, |count, _| future::ready(Ok(count + 1)))
+                .await;
+        }
+
         self.source
             .slice(self.range.clone(), false)?
             .count(txn_id)
@@ -244,6 +279,19 @@ where
     }
 
     async fn delete(&self, txn_id: TxnId) -> TCResult<()> {
+        if self.filter.is_some() {
+            let rows = self.clone().rows(txn_id).await?;
+            return rows
+                .map_ok(|row| {
+                    let range = tc_btree::Range::with_prefix(row);
+                    let source = self.source.clone();
+                    async move { source.slice(range, false)?.delete(txn_id).await }
+                })
+                .try_buffer_unordered(num_cpus::get())
+                .try_fold((), |(), ()| future::ready(Ok(())))
+                .await;
+        }
+
         self.source
             .clone()
             .slice(self.range.clone(), false)?
@@ -251,6 +299,38 @@ where
             .await
     }
 
+    async fn delete_counted(self, txn_id: TxnId) -> TCResult<u64> {
+        let rows = self.clone().rows(txn_id).await?;
+        rows.map_ok(|row| {
+            let range = tc_btree::Range::with_prefix(row);
+            let source = self.source.clone();
+            async move { source.slice(range, false)?.delete(txn_id).await }
+        })
+        .try_buffer_unordered(num_cpus::get())
+        .try_fold(0, |count, ()| future::ready(Ok(count + 1)))
+        .await
+    }
+
+    async fn delete_row(&self, txn_id: TxnId, row: Row) -> TCResult<()> {
+        let key = self.schema.values_from_row(row, false)?;
+        if let Some(filter) = &self.filter {
+            if !filter.matches(&key) {
+                return Ok(());
+            }
+        }
+
+        let range = tc_btree::Range::with_prefix(key);
+        if self.range.contains(&range, self.source.collator()) {
+            self.source
+                .clone()
+                .slice(range, false)?
+                .delete(txn_id)
+                .await
+        } else {
+            Ok(())
+        }
+    }
+
     fn key(&self) -> &[Column] {
         self.schema.key()
     }
@@ -278,10 +358,17 @@ where
     }
 
     async fn rows<'a>(self, txn_id: TxnId) -> TCResult<TCBoxTryStream<'a, Vec<Value>>> {
-        self.source
-            .slice(self.range.clone(), self.reverse)?
-            .keys(txn_id)
-            .await
+        let one_of = |bound: &ColumnBound| matches!(bound, ColumnBound::OneOf(_));
+        let ranges = if self.bounds.values().any(one_of) {
+            let columns = self.schema.columns();
+            let collator = self.source.collator();
+            self.bounds.clone().into_btree_ranges(&columns, collator)?
+        } else {
+            vec![self.range.clone()]
+        };
+
+        let rows = slice_keys(self.source, ranges, self.reverse, txn_id).await?;
+        Ok(filter_rows(rows, self.filter.map(|filter| *filter)))
     }
 
     fn validate_bounds(&self, bounds: &Bounds) -> TCResult<()> {
@@ -321,6 +408,19 @@ impl<F, D, Txn> From<IndexSlice<F, D, Txn>> for Table<F, D, Txn> {
     }
 }
 
+/// Return the [`TableIndex`] whose rows the given `table` deletes, if any.
+fn source_index<F: File<Node>, D: Dir, Txn: Transaction<D>>(
+    table: &Table<F, D, Txn>,
+) -> Option<&TableIndex<F, D, Txn>> {
+    match table {
+        Table::Table(table) => Some(table),
+        Table::Limit(limited) => source_index(&limited.source),
+        Table::Merge(merged) => Some(merged.source()),
+        Table::TableSlice(slice) => Some(slice.source()),
+        _ => None,
+    }
+}
+
 #[derive(Clone)]
 pub struct Limited<F, D, Txn> {
     source: Table<F, D, Txn>,
@@ -368,6 +468,19 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn> for Li
             .await
     }
 
+    async fn delete_counted(self, txn_id: TxnId) -> TCResult<u64> {
+        let source = &self.source;
+        let schema: IndexSchema = (source.key().to_vec(), source.values().to_vec()).into();
+
+        let rows = self.clone().rows(txn_id).await?;
+
+        rows.map(|row| row.and_then(|row| schema.row_from_values(row)))
+            .map_ok(|row| source.delete_row(txn_id, row))
+            .try_buffer_unordered(num_cpus::get())
+            .try_fold(0, |count, ()| future::ready(Ok(count + 1)))
+            .await
+    }
+
     fn key(&self) -> &[Column] {
         self.source.key()
     }
@@ -416,6 +529,27 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn> for Li
 
         Ok(())
     }
+
+    async fn delete_triggered<T: Trigger<Txn>>(self, txn: &Txn) -> TCResult<u64> {
+        let source = match source_index(&self.source) {
+            Some(source) => source.clone(),
+            None => return self.delete_counted(*txn.id()).await,
+        };
+
+        let triggers = source.triggers::<T>(txn, TableEvent::AfterDelete).await?;
+        if triggers.is_empty() {
+            return self.delete_counted(*txn.id()).await;
+        }
+
+        let rows = self.clone().rows(*txn.id()).await?;
+        source.delete_rows(txn, rows, &triggers).await
+    }
+
+    fn references(&self) -> Vec<Reference<F, D, Txn>> {
+        source_index(&self.source)
+            .map(|source| source.references())
+            .unwrap_or_default()
+    }
 }
 
 impl<F, D, Txn> From<Limited<F, D, Txn>> for Table<F, D, Txn> {
@@ -547,6 +681,24 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn> for Me
     type Reverse = Self;
     type Slice = Self;
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        // the keys of this merge are streamed from the right-hand index slice, so it can only
+        // stand in for the whole merge if it covers all the bounds of the merge, too
+        let schema = self.right.schema();
+        let bounded: Vec<Id> = self.bounds.keys().cloned().collect();
+        covered_columns(schema, &bounded)?;
+        covered_columns(schema, columns)?;
+
+        let btree = self.right.btree().clone();
+        let slice = IndexSlice::new(btree, schema.clone(), self.bounds.clone()).ok()?;
+
+        if self.right.reverse {
+            Some(slice.into_reversed())
+        } else {
+            Some(slice)
+        }
+    }
+
     async fn delete(&self, txn_id: TxnId) -> TCResult<()> {
         let schema: IndexSchema = (self.key().to_vec(), self.values().to_vec()).into();
 
@@ -655,6 +807,21 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> TableInstance<F, D, Txn> for Me
             MergeSource::Merge(merged) => merged.update_row(txn_id, row, value).await,
         }
     }
+
+    async fn delete_triggered<T: Trigger<Txn>>(self, txn: &Txn) -> TCResult<u64> {
+        let source = self.source().clone();
+        let triggers = source.triggers::<T>(txn, TableEvent::AfterDelete).await?;
+        if triggers.is_empty() {
+            return self.delete_counted(*txn.id()).await;
+        }
+
+        let rows = self.clone().rows(*txn.id()).await?;
+        source.delete_rows(txn, rows, &triggers).await
+    }
+
+    fn references(&self) -> Vec<Reference<F, D, Txn>> {
+        self.source().references()
+    }
 }
 
 impl<F, D, Txn> From<Merged<F, D, Txn>> for Table<F, D, Txn> {
@@ -667,6 +834,7 @@ impl<F, D, Txn> From<Merged<F, D, Txn>> for Table<F, D, Txn> {
 pub struct Selection<F, D, Txn, T> {
     source: T,
     schema: IndexSchema,
+    computed: Vec<ComputedColumn>,
     columns: Vec<Id>,
     indices: Vec<usize>,
     phantom: Phantom<F, D, Txn>,
@@ -679,9 +847,12 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInstance<F, D, Txn>>
         let column_set: HashSet<&Id> = columns.iter().collect();
         let mut indices: Vec<usize> = Vec::with_capacity(columns.len());
 
-        let source_columns = source.schema().primary().columns();
+        // the computed columns of the source, if any, follow its stored columns in a row
+        let source_schema = source.schema();
+        let source_columns = source_schema.primary().columns();
         let source_indices: HashMap<&Id, usize> = source_columns
             .iter()
+            .chain(source_schema.computed().iter().map(|col| col.column()))
             .enumerate()
             .map(|(i, col)| (&col.name, i))
             .collect();
@@ -710,9 +881,17 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInstance<F, D, Txn>>
 
         let schema = (key, values).into();
 
+        let computed = source_schema
+            .computed()
+            .iter()
+            .filter(|col| column_set.contains(&col.column().name))
+            .cloned()
+            .collect();
+
         Ok(Selection {
             source,
             schema,
+            computed,
             columns,
             indices,
             phantom: Phantom::default(),
@@ -720,6 +899,19 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInstance<F, D, Txn>>
     }
 }
 
+impl<F, D, Txn, T> Selection<F, D, Txn, T> {
+    /// Return `true` if this `Selection` includes a [`ComputedColumn`] of its source.
+    pub fn selects_computed(&self) -> bool {
+        !self.computed.is_empty()
+    }
+
+    /// Destructure this `Selection` into its source and the position of each selected column
+    /// in a row of the source followed by the values of its [`ComputedColumn`]s.
+    pub fn into_source(self) -> (T, Vec<usize>) {
+        (self.source, self.indices)
+    }
+}
+
 impl<F, D, Txn, T> Instance for Selection<F, D, Txn, T>
 where
     Self: Send + Sync,
@@ -747,6 +939,10 @@ where
         self.source.count(txn_id).await
     }
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        self.source.covering_index(columns)
+    }
+
     fn key(&self) -> &[Column] {
         self.schema.key()
     }
@@ -769,7 +965,8 @@ where
 
         let key = select(source.key());
         let values = select(source.values());
-        IndexSchema::from((key, values)).into()
+        TableSchema::from(IndexSchema::from((key, values)))
+            .with_computed_columns(self.computed.to_vec())
     }
 
     fn order_by(self, order: Vec<Id>, reverse: bool) -> TCResult<Self::OrderBy> {
@@ -780,6 +977,7 @@ where
         Ok(Selection {
             source,
             schema: self.schema,
+            computed: self.computed,
             columns: self.columns,
             indices: self.indices,
             phantom: Phantom::default(),
@@ -791,8 +989,25 @@ where
     }
 
     async fn rows<'a>(self, txn_id: TxnId) -> TCResult<TCBoxTryStream<'a, Vec<Value>>> {
-        let indices = self.indices.to_vec();
-        let selected = self.source.rows(txn_id).await?.map_ok(move |row| {
+        if let Some(col) = self.computed.first() {
+            return Err(TCError::bad_request(ERR_COMPUTED, &col.column().name));
+        }
+
+        let covering = self.source.covering_index(&self.columns).and_then(|index| {
+            covered_columns(index.schema(), &self.columns).map(|indices| (index, indices))
+        });
+
+        let (rows, indices) = if let Some((index, indices)) = covering {
+            debug!(
+                "selecting {} columns from a covering index",
+                self.columns.len()
+            );
+            (index.rows(txn_id).await?, indices)
+        } else {
+            (self.source.rows(txn_id).await?, self.indices)
+        };
+
+        let selected = rows.map_ok(move |row| {
             let selection: Vec<Value> = indices.iter().map(|i| row[*i].clone()).collect();
             selection
         });
@@ -857,6 +1072,7 @@ where
         Table::Selection(Box::new(Selection {
             source: selection.source.into(),
             schema: selection.schema,
+            computed: selection.computed,
             columns: selection.columns,
             indices: selection.indices,
             phantom: Phantom::default(),
@@ -933,6 +1149,10 @@ where
         self.slice.count(txn_id).await
     }
 
+    fn covering_index(&self, columns: &[Id]) -> Option<IndexSlice<F, D, Txn>> {
+        self.slice.covering_index(columns)
+    }
+
     async fn delete(&self, txn_id: TxnId) -> TCResult<()> {
         let schema: IndexSchema = (self.key().to_vec(), self.values().to_vec()).into();
 
@@ -1015,6 +1235,21 @@ where
     async fn update_row(&self, txn_id: TxnId, row: Row, value: Row) -> TCResult<()> {
         self.source().update_row(txn_id, row, value).await
     }
+
+    async fn delete_triggered<T: Trigger<Txn>>(self, txn: &Txn) -> TCResult<u64> {
+        let source = self.source().clone();
+        let triggers = source.triggers::<T>(txn, TableEvent::AfterDelete).await?;
+        if triggers.is_empty() {
+            return self.delete_counted(*txn.id()).await;
+        }
+
+        let rows = self.clone().rows(*txn.id()).await?;
+        source.delete_rows(txn, rows, &triggers).await
+    }
+
+    fn references(&self) -> Vec<Reference<F, D, Txn>> {
+        self.source().references()
+    }
 }
 
 impl<F, D, Txn> From<TableSlice<F, D, Txn>> for Table<F, D, Txn> {
@@ -1036,6 +1271,65 @@ pub fn group_by<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInstance<F,
     })
 }
 
+/// Stream the keys of `source` within each of the given `ranges`, in order.
+pub(crate) async fn slice_keys<'a, F, D, Txn>(
+    source: BTreeFile<F, D, Txn>,
+    mut ranges: Vec<tc_btree::Range>,
+    reverse: bool,
+    txn_id: TxnId,
+) -> TCResult<TCBoxTryStream<'a, Vec<Value>>>
+where
+    F: File<Node>,
+    D: Dir,
+    Txn: Transaction<D>,
+{
+    if ranges.len() == 1 {
+        let range = ranges.pop().expect("range");
+        return source.slice(range, reverse)?.keys(txn_id).await;
+    }
+
+    if reverse {
+        ranges.reverse();
+    }
+
+    let slices = ranges
+        .into_iter()
+        .map(|range| source.clone().slice(range, reverse))
+        .collect::<TCResult<Vec<_>>>()?;
+
+    let keys = stream::iter(slices)
+        .then(move |slice| slice.keys(txn_id))
+        .try_flatten();
+
+    Ok(Box::pin(keys))
+}
+
+/// Return the position of each of the given `columns` in the given `schema`,
+/// or `None` if the schema does not contain them all.
+pub(crate) fn covered_columns(schema: &IndexSchema, columns: &[Id]) -> Option<Vec<usize>> {
+    let positions: HashMap<&Id, usize> = schema
+        .column_names()
+        .enumerate()
+        .map(|(i, name)| (name, i))
+        .collect();
+
+    columns
+        .iter()
+        .map(|name| positions.get(name).copied())
+        .collect()
+}
+
+/// Apply the given [`RowFilter`], if any, to a stream of rows.
+pub(crate) fn filter_rows<'a>(
+    rows: TCBoxTryStream<'a, Vec<Value>>,
+    filter: Option<RowFilter>,
+) -> TCBoxTryStream<'a, Vec<Value>> {
+    match filter {
+        Some(filter) => Box::pin(rows.try_filter(move |row| future::ready(filter.matches(row)))),
+        None => rows,
+    }
+}
+
 #[derive(Clone)]
 struct Phantom<F, D, Txn> {
     file: PhantomData<F>,
@@ -1052,3 +1346,27 @@ impl<F: File<Node>, D: Dir, Txn: Transaction<D>> Default for Phantom<F, D, Txn>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tc_value::ValueType;
+    use tcgeneric::label;
+
+    use super::*;
+
+    fn column(name: &'static str) -> Column {
+        (label(name).into(), ValueType::String).into()
+    }
+
+    #[test]
+    fn test_covered_columns() {
+        // the schema of an auxiliary index on "views" of a table keyed by "name"
+        let schema: IndexSchema = (vec![column("views")], vec![column("name")]).into();
+
+        let covered = covered_columns(&schema, &[label("name").into(), label("views").into()]);
+        assert_eq!(covered, Some(vec![1, 0]));
+
+        let covered = covered_columns(&schema, &[label("views").into(), label("likes").into()]);
+        assert_eq!(covered, None);
+    }
+}
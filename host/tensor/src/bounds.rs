@@ -10,7 +10,24 @@ use tc_value::Value;
 
 use super::Coord;
 
-pub type Coords = MultiProduct<AxisIter>;
+/// An iterator over the [`Coord`]s within a set of [`Bounds`].
+#[derive(Clone)]
+pub enum Coords {
+    /// The single (empty) coordinate of a zero-dimensional `Tensor`
+    Scalar(iter::Once<Coord>),
+    Product(MultiProduct<AxisIter>),
+}
+
+impl Iterator for Coords {
+    type Item = Coord;
+
+    fn next(&mut self) -> Option<Coord> {
+        match self {
+            Self::Scalar(coord) => coord.next(),
+            Self::Product(coords) => coords.next(),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub enum AxisIter {
@@ -164,6 +181,10 @@ impl Bounds {
 
     /// Return an iterator over all the [`Coord`]s within these `Bounds`.
     pub fn affected(&self) -> Coords {
+        if self.is_empty() {
+            return Coords::Scalar(iter::once(vec![]));
+        }
+
         use AxisBounds::*;
         let mut axes = Vec::with_capacity(self.len());
         for axis in 0..self.len() {
@@ -174,7 +195,7 @@ impl Bounds {
             });
         }
 
-        axes.iter().cloned().multi_cartesian_product()
+        Coords::Product(axes.iter().cloned().multi_cartesian_product())
     }
 
     /// Return `true` if these `bounds` contain the given coordinate.
@@ -464,6 +485,14 @@ impl Shape {
 
     /// Return a `TCError` if the given `coord` doesn't fit within this `Shape`.
     pub fn validate_coord(&self, coord: &[u64]) -> TCResult<()> {
+        if coord.len() != self.len() {
+            return Err(TCError::unsupported(format!(
+                "Tensor of shape {} does not contain {}",
+                self,
+                Value::from_iter(coord.to_vec())
+            )));
+        }
+
         for (axis, index) in coord.iter().enumerate() {
             if index >= &self[axis] {
                 return Err(TCError::unsupported(format!(
@@ -17,7 +17,10 @@ use tcgeneric::{TCBoxStream, TCBoxTryFuture, TCBoxTryStream};
 use crate::sparse::{SparseAccess, SparseAccessor};
 use crate::stream::{Read, ReadValueAt};
 use crate::transform;
-use crate::{Bounds, Coord, Phantom, Shape, TensorAccess, TensorType, ERR_NONBIJECTIVE_WRITE};
+use crate::{
+    checked_block, checked_value, saturating_cast, saturating_cast_block, Bounds, Coord, NonFinite,
+    Phantom, Shape, TensorAccess, TensorType, ERR_NONBIJECTIVE_WRITE,
+};
 
 use super::file::{BlockListFile, BlockListFileSlice};
 use super::stream::SparseValueStream;
@@ -357,7 +360,9 @@ pub struct BlockListCombine<FD, FS, D, T, L, R> {
     right: R,
     combinator: fn(&Array, &Array) -> Array,
     value_combinator: fn(Number, Number) -> Number,
+    checked: Option<fn(Number, Number) -> Option<Number>>,
     dtype: NumberType,
+    non_finite: NonFinite,
     phantom: Phantom<FD, FS, D, T>,
 }
 
@@ -376,6 +381,7 @@ where
         combinator: fn(&Array, &Array) -> Array,
         value_combinator: fn(Number, Number) -> Number,
         dtype: NumberType,
+        non_finite: NonFinite,
     ) -> TCResult<Self> {
         if left.shape() != right.shape() {
             return Err(TCError::bad_request(
@@ -389,10 +395,22 @@ where
             right,
             combinator,
             value_combinator,
+            checked: None,
             dtype,
+            non_finite,
             phantom: Phantom::default(),
         })
     }
+
+    /// Combine integer values with the given `checked` operation instead of the `combinator`,
+    /// so that an overflow is returned as an error from the block (or value) which overflowed.
+    pub fn checked(mut self, checked: fn(Number, Number) -> Option<Number>) -> Self {
+        if let NumberType::UInt(_) | NumberType::Int(_) = self.dtype {
+            self.checked = Some(checked);
+        }
+
+        self
+    }
 }
 
 impl<FD, FS, D, T, L, R> TensorAccess for BlockListCombine<FD, FS, D, T, L, R>
@@ -443,7 +461,9 @@ where
             right,
             combinator: self.combinator,
             value_combinator: self.value_combinator,
+            checked: self.checked,
             dtype: self.dtype,
+            non_finite: self.non_finite,
             phantom: self.phantom,
         };
 
@@ -459,10 +479,19 @@ where
             let (left, right) = try_join!(left, right)?;
 
             let combinator = self.combinator;
+            let checked = self.checked;
+            let dtype = self.dtype;
+            let non_finite = self.non_finite;
             let blocks = left
                 .zip(right)
                 .map(|(l, r)| Ok((l?, r?)))
-                .map_ok(move |(l, r)| combinator(&l, &r));
+                .and_then(move |(l, r)| {
+                    future::ready(match checked {
+                        Some(checked) => checked_block(l, r, checked, dtype),
+                        None => Ok(combinator(&l, &r)),
+                    })
+                })
+                .and_then(move |block| future::ready(non_finite.apply_block(block, dtype)));
 
             let blocks: TCBoxTryStream<'a, Array> = Box::pin(blocks);
             Ok(blocks)
@@ -473,13 +502,19 @@ where
         let left = self.left.slice(bounds.clone())?;
         let right = self.right.slice(bounds)?;
 
-        BlockListCombine::new(
+        let combine = BlockListCombine::new(
             left,
             right,
             self.combinator,
             self.value_combinator,
             self.dtype,
-        )
+            self.non_finite,
+        )?;
+
+        Ok(BlockListCombine {
+            checked: self.checked,
+            ..combine
+        })
     }
 
     fn transpose(self, permutation: Option<Vec<usize>>) -> TCResult<Self::Transpose> {
@@ -493,13 +528,19 @@ where
         let left = self.left.transpose(permutation.clone())?;
         let right = self.right.transpose(permutation)?;
 
-        BlockListCombine::new(
+        let combine = BlockListCombine::new(
             left,
             right,
             self.combinator,
             self.value_combinator,
             self.dtype,
-        )
+            self.non_finite,
+        )?;
+
+        Ok(BlockListCombine {
+            checked: self.checked,
+            ..combine
+        })
     }
 
     async fn read_values(self, txn: Self::Txn, coords: Coords) -> TCResult<Array> {
@@ -508,7 +549,12 @@ where
             self.right.read_values(txn, coords)
         )?;
 
-        Ok((self.combinator)(&left, &right))
+        let values = match self.checked {
+            Some(checked) => checked_block(left, right, checked, self.dtype)?,
+            None => (self.combinator)(&left, &right),
+        };
+
+        self.non_finite.apply_block(values, self.dtype)
     }
 
     async fn write<V: DenseAccess<FD, FS, D, T>>(
@@ -541,7 +587,12 @@ where
             let left = self.left.read_value_at(txn.clone(), coord.to_vec());
             let right = self.right.read_value_at(txn, coord);
             let ((coord, left), (_, right)) = try_join!(left, right)?;
-            let value = (self.value_combinator)(left, right);
+            let value = match self.checked {
+                Some(checked) => checked_value(left, right, checked, self.dtype)?,
+                None => (self.value_combinator)(left, right),
+            };
+
+            let value = self.non_finite.apply(value, self.dtype)?;
             Ok((coord, value))
         })
     }
@@ -708,6 +759,7 @@ where
 pub struct BlockListCast<FD, FS, D, T, B> {
     source: B,
     dtype: NumberType,
+    saturate: bool,
     phantom: Phantom<FD, FS, D, T>,
 }
 
@@ -723,6 +775,17 @@ where
         Self {
             source,
             dtype,
+            saturate: false,
+            phantom: Phantom::default(),
+        }
+    }
+
+    /// Cast the given `source` to `dtype`, clamping out-of-range integers instead of wrapping them.
+    pub fn saturating(source: B, dtype: NumberType) -> Self {
+        Self {
+            source,
+            dtype,
+            saturate: true,
             phantom: Phantom::default(),
         }
     }
@@ -737,7 +800,7 @@ where
     B: DenseAccess<FD, FS, D, T>,
 {
     fn dtype(&self) -> NumberType {
-        self.source.dtype()
+        self.dtype
     }
 
     fn ndim(&self) -> usize {
@@ -767,36 +830,52 @@ where
     type Transpose = BlockListCast<FD, FS, D, T, B::Transpose>;
 
     fn accessor(self) -> DenseAccessor<FD, FS, D, T> {
-        let cast = BlockListCast::new(self.source.accessor(), self.dtype);
+        let cast = BlockListCast {
+            source: self.source.accessor(),
+            dtype: self.dtype,
+            saturate: self.saturate,
+            phantom: self.phantom,
+        };
+
         DenseAccessor::Cast(Box::new(cast))
     }
 
     fn block_stream<'a>(self, txn: T) -> TCBoxTryFuture<'a, TCBoxTryStream<'a, Array>> {
         Box::pin(async move {
             let dtype = self.dtype;
+            let saturate = self.saturate;
             let blocks: TCBoxStream<'a, TCResult<Array>> = self.source.block_stream(txn).await?;
-            let cast = blocks.map_ok(move |array| array.cast_into(dtype));
+            let cast = blocks.map_ok(move |array| cast_block(array, dtype, saturate));
             let cast: TCBoxTryStream<'a, Array> = Box::pin(cast);
             Ok(cast)
         })
     }
 
     fn slice(self, bounds: Bounds) -> TCResult<Self::Slice> {
-        let slice = self.source.slice(bounds)?;
-        Ok(BlockListCast::new(slice, self.dtype))
+        Ok(BlockListCast {
+            source: self.source.slice(bounds)?,
+            dtype: self.dtype,
+            saturate: self.saturate,
+            phantom: self.phantom,
+        })
     }
 
     fn transpose(self, permutation: Option<Vec<usize>>) -> TCResult<Self::Transpose> {
-        let transpose = self.source.transpose(permutation)?;
-        Ok(BlockListCast::new(transpose, self.dtype))
+        Ok(BlockListCast {
+            source: self.source.transpose(permutation)?,
+            dtype: self.dtype,
+            saturate: self.saturate,
+            phantom: self.phantom,
+        })
     }
 
     async fn read_values(self, txn: Self::Txn, coords: Coords) -> TCResult<Array> {
         let dtype = self.dtype;
+        let saturate = self.saturate;
 
         self.source
             .read_values(txn, coords)
-            .map_ok(|values| values.cast_into(dtype))
+            .map_ok(|values| cast_block(values, dtype, saturate))
             .await
     }
 
@@ -822,15 +901,34 @@ where
 
     fn read_value_at<'a>(self, txn: Self::Txn, coord: Coord) -> Read<'a> {
         let dtype = self.dtype;
+        let saturate = self.saturate;
         let read = self
             .source
             .read_value_at(txn, coord)
-            .map_ok(move |(coord, value)| (coord, value.into_type(dtype)));
+            .map_ok(move |(coord, value)| (coord, cast_value(value, dtype, saturate)));
 
         Box::pin(read)
     }
 }
 
+#[inline]
+fn cast_block(block: Array, dtype: NumberType, saturate: bool) -> Array {
+    if saturate {
+        saturating_cast_block(block, dtype)
+    } else {
+        block.cast_into(dtype)
+    }
+}
+
+#[inline]
+fn cast_value(value: Number, dtype: NumberType, saturate: bool) -> Number {
+    if saturate {
+        saturating_cast(value, dtype)
+    } else {
+        value.into_type(dtype)
+    }
+}
+
 #[derive(Clone)]
 pub struct BlockListExpand<FD, FS, D, T, B> {
     source: B,
@@ -972,6 +1070,7 @@ pub struct BlockListReduce<FD, FS, D, T, B> {
     source: B,
     rebase: transform::Reduce,
     reductor: Reductor<FD, FS, D, T>,
+    dtype: NumberType,
 }
 
 impl<FD, FS, D, T, B> BlockListReduce<FD, FS, D, T, B>
@@ -983,12 +1082,24 @@ where
     B: DenseAccess<FD, FS, D, T>,
 {
     pub fn new(source: B, axis: usize, reductor: Reductor<FD, FS, D, T>) -> TCResult<Self> {
+        let dtype = source.dtype();
+        Self::with_dtype(source, axis, reductor, dtype)
+    }
+
+    /// Construct a new reduction whose output has the given `dtype`.
+    pub fn with_dtype(
+        source: B,
+        axis: usize,
+        reductor: Reductor<FD, FS, D, T>,
+        dtype: NumberType,
+    ) -> TCResult<Self> {
         let rebase = transform::Reduce::new(source.shape().clone(), axis)?;
 
         Ok(BlockListReduce {
             source,
             rebase,
             reductor,
+            dtype,
         })
     }
 }
@@ -1002,7 +1113,7 @@ where
     B: DenseAccess<FD, FS, D, T>,
 {
     fn dtype(&self) -> NumberType {
-        self.source.dtype()
+        self.dtype
     }
 
     fn ndim(&self) -> usize {
@@ -1036,6 +1147,7 @@ where
             source: self.source.accessor(),
             rebase: self.rebase,
             reductor: self.reductor,
+            dtype: self.dtype,
         };
 
         DenseAccessor::Reduce(Box::new(reduce))
@@ -1066,7 +1178,7 @@ where
         let reduce_axis = self.rebase.reduce_axis(&bounds);
         let source_bounds = self.rebase.invert_bounds(bounds);
         let slice = self.source.slice(source_bounds)?;
-        BlockListReduce::new(slice, reduce_axis, self.reductor)
+        BlockListReduce::with_dtype(slice, reduce_axis, self.reductor, self.dtype)
     }
 
     fn transpose(self, permutation: Option<Vec<usize>>) -> TCResult<Self::Transpose> {
@@ -1400,8 +1512,9 @@ where
             .await
     }
 
-    async fn write_value(&self, txn_id: TxnId, bounds: Bounds, number: Number) -> TCResult<()> {
+    async fn write_value(&self, txn_id: TxnId, mut bounds: Bounds, number: Number) -> TCResult<()> {
         self.shape().validate_bounds(&bounds)?;
+        bounds.normalize(self.shape());
         stream::iter(bounds.affected())
             .map(|coord| self.source.write_value(txn_id, coord, number))
             .buffer_unordered(num_cpus::get())
@@ -15,7 +15,7 @@ use strided::Stride;
 use tc_btree::Node;
 use tc_error::*;
 use tc_transact::fs::{BlockId, CopyFrom, Dir, File, Persist, Restore};
-use tc_transact::{Transact, Transaction, TxnId};
+use tc_transact::{Rollback, Transact, Transaction, TxnId};
 use tc_value::{Number, NumberClass, NumberInstance, NumberType};
 use tcgeneric::{TCBoxTryFuture, TCBoxTryStream};
 
@@ -464,6 +464,23 @@ where
     }
 }
 
+#[async_trait]
+impl<FD, FS, D, T> Rollback for BlockListFile<FD, FS, D, T>
+where
+    FD: File<Array> + Rollback,
+    FS: File<Node>,
+    D: Dir,
+    T: Transaction<D>,
+{
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.file.savepoint(txn_id, ordinal).await
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.file.rollback_to(txn_id, ordinal).await
+    }
+}
+
 #[async_trait]
 impl<FD, FS, D, T, B> CopyFrom<D, B> for BlockListFile<FD, FS, D, T>
 where
@@ -1,12 +1,16 @@
+use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::fmt;
+use std::iter;
 use std::marker::PhantomData;
 use std::ops::{Add, Div, Mul, Sub};
+use std::thread;
 
-use afarray::{Array, ArrayInstance};
+use afarray::{Array, ArrayExt, ArrayInstance};
 use arrayfire as af;
 use async_trait::async_trait;
 use destream::{de, en, EncodeSeq};
+use futures::channel::oneshot;
 use futures::future::{self, TryFutureExt};
 use futures::stream::{Stream, TryStreamExt};
 use log::debug;
@@ -14,16 +18,17 @@ use log::debug;
 use tc_btree::Node;
 use tc_error::*;
 use tc_transact::fs::{CopyFrom, Dir, File, Hash, Persist, Restore};
-use tc_transact::{IntoView, Transact, Transaction, TxnId};
+use tc_transact::{IntoView, Rollback, Transact, Transaction, TxnId};
 use tc_value::{Number, NumberClass, NumberInstance, NumberType};
 use tcgeneric::{TCBoxTryFuture, TCBoxTryStream};
 
-use super::sparse::{DenseToSparse, SparseTensor};
+use super::sparse::{DenseToSparse, SparseAccess, SparseTensor};
 use super::stream::{Read, ReadValueAt};
 use super::{
-    Bounds, Coord, Phantom, Schema, Shape, Tensor, TensorAccess, TensorBoolean, TensorCompare,
-    TensorDualIO, TensorIO, TensorInstance, TensorMath, TensorReduce, TensorTransform, TensorType,
-    TensorUnary,
+    float_op, float_op_block, float_type, max_array, max_option, max_value, min_array, min_value,
+    ordered, statistic, AxisBounds, Bounds, Coord, Moments, NonFinite, Phantom, Schema, Shape,
+    Tensor, TensorAccess, TensorBoolean, TensorCompare, TensorDot, TensorDualIO, TensorIO,
+    TensorInstance, TensorMath, TensorReduce, TensorTransform, TensorType, TensorUnary,
 };
 
 use access::*;
@@ -38,6 +43,33 @@ mod stream;
 /// The number of elements per dense tensor block, equal to (1 mebibyte / 64 bits).
 pub const PER_BLOCK: usize = 131_072;
 
+/// Reduce each of the given `blocks` on its own worker thread, with at most `concurrency`
+/// reductions in flight, then combine the partial results with `combine` in block order,
+/// so that the result does not depend on the order in which the reductions complete.
+async fn reduce_blocks<'a, R, C>(
+    blocks: TCBoxTryStream<'a, Array>,
+    init: Number,
+    reduce: R,
+    combine: C,
+    concurrency: usize,
+) -> TCResult<Number>
+where
+    R: Fn(Array) -> Number + Copy + Send + 'static,
+    C: Fn(Number, Number) -> Number,
+{
+    blocks
+        .map_ok(move |block| {
+            let (tx, rx) = oneshot::channel();
+            thread::spawn(move || tx.send(reduce(block)));
+            rx.map_err(|_| TCError::internal("tensor reduction worker exited without a result"))
+        })
+        .try_buffered(concurrency.max(1))
+        .try_fold(init, |reduced, partial| {
+            future::ready(Ok(combine(reduced, partial)))
+        })
+        .await
+}
+
 /// A `Tensor` stored as a [`File`] of dense [`Array`] blocks
 #[derive(Clone)]
 pub struct DenseTensor<FD, FS, D, T, B> {
@@ -52,6 +84,22 @@ impl<FD, FS, D, T, B> DenseTensor<FD, FS, D, T, B> {
     }
 }
 
+impl<FD, FS, D, T, L, R> DenseTensor<FD, FS, D, T, BlockListCombine<FD, FS, D, T, L, R>>
+where
+    FD: File<Array>,
+    FS: File<Node>,
+    D: Dir,
+    T: Transaction<D>,
+    L: DenseAccess<FD, FS, D, T>,
+    R: DenseAccess<FD, FS, D, T>,
+{
+    /// Check each integer block of this combination for overflow as it's computed, using the
+    /// given `checked` operation.
+    pub fn checked(self, checked: fn(Number, Number) -> Option<Number>) -> Self {
+        DenseTensor::from(self.blocks.checked(checked))
+    }
+}
+
 impl<FD, FS, D, T, B> DenseTensor<FD, FS, D, T, B>
 where
     FD: File<Array> + TryFrom<D::File, Error = TCError>,
@@ -66,6 +114,7 @@ where
         combinator: fn(&Array, &Array) -> Array,
         value_combinator: fn(Number, Number) -> Number,
         dtype: NumberType,
+        non_finite: NonFinite,
     ) -> TCResult<DenseTensor<FD, FS, D, T, BlockListCombine<FD, FS, D, T, B, OT>>> {
         if self.shape() != other.shape() {
             return Err(TCError::unsupported(format!(
@@ -81,10 +130,336 @@ where
             combinator,
             value_combinator,
             dtype,
+            non_finite,
         )?;
 
         Ok(DenseTensor::from(blocks))
     }
+
+    async fn moments(&self, txn: T) -> TCResult<Moments> {
+        let blocks = self.blocks.clone().block_stream(txn).await?;
+        blocks
+            .try_fold(Moments::default(), |moments, block| {
+                future::ready(Ok(moments.update(block)))
+            })
+            .await
+    }
+}
+
+impl<FD, FS, D, T, B> DenseTensor<FD, FS, D, T, B>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    B: DenseAccess<FD, FS, D, T>,
+    D::FileClass: From<TensorType>,
+{
+    /// Compute the 2-dimensional cross-correlation of this `DenseTensor` with the given `kernel`,
+    /// after zero-padding this `DenseTensor` by `padding` on each side.
+    ///
+    /// The output is computed one row at a time, so only the `kernel` and as many rows of this
+    /// `DenseTensor` as the `kernel` has are held in memory.
+    pub async fn conv2d<K: DenseAccess<FD, FS, D, T>>(
+        self,
+        kernel: DenseTensor<FD, FS, D, T, K>,
+        stride: (u64, u64),
+        padding: (u64, u64),
+        txn: T,
+    ) -> TCResult<DenseTensor<FD, FS, D, T, BlockListFile<FD, FS, D, T>>> {
+        if self.ndim() != 2 || kernel.ndim() != 2 {
+            return Err(TCError::bad_request(
+                "conv2d requires a 2-dimensional input and kernel, not",
+                format!("{} and {}", self.shape(), kernel.shape()),
+            ));
+        }
+
+        if kernel.shape().contains(&0) {
+            return Err(TCError::bad_request(
+                "conv2d requires a nonempty kernel, not",
+                kernel.shape(),
+            ));
+        }
+
+        if stride.0 == 0 || stride.1 == 0 {
+            return Err(TCError::bad_request(
+                "conv2d stride must be nonzero, not",
+                format!("{:?}", stride),
+            ));
+        }
+
+        let (height, width) = (self.shape()[0], self.shape()[1]);
+        let (kernel_height, kernel_width) = (kernel.shape()[0], kernel.shape()[1]);
+        if kernel_height > height + 2 * padding.0 || kernel_width > width + 2 * padding.1 {
+            return Err(TCError::bad_request(
+                format!("kernel with shape {} does not fit input", kernel.shape()),
+                self.shape(),
+            ));
+        }
+
+        let out_height = (height + 2 * padding.0 - kernel_height) / stride.0 + 1;
+        let out_width = (width + 2 * padding.1 - kernel_width) / stride.1 + 1;
+
+        let txn_id = *txn.id();
+        let dtype = float_type(Ord::max(self.dtype(), kernel.dtype()));
+        let shape = Shape::from(vec![out_height, out_width]);
+
+        let kernel: Vec<Number> = kernel
+            .blocks
+            .value_stream(txn.clone())
+            .await?
+            .map_ok(|n| n.into_type(dtype))
+            .try_collect()
+            .await?;
+
+        // stream the input one row at a time, zero-padded on each side
+        let padded_width = (width + 2 * padding.1) as usize;
+        let zero = dtype.zero();
+        let pad = vec![zero; padding.1 as usize];
+        let rows: TCBoxTryStream<Vec<Number>> = if width == 0 {
+            let rows = iter::repeat(Vec::new()).take(height as usize).map(Ok);
+            Box::pin(futures::stream::iter(rows))
+        } else {
+            let values = self.blocks.value_stream(txn.clone()).await?;
+            Box::pin(
+                values
+                    .chunks(width as usize)
+                    .map(|row| row.into_iter().collect::<TCResult<Vec<Number>>>()),
+            )
+        };
+
+        let rows = rows.map_ok(move |row| {
+            let mut padded = Vec::with_capacity(padded_width);
+            padded.extend_from_slice(&pad);
+            padded.extend(row.into_iter().map(|n| n.into_type(dtype)));
+            padded.extend_from_slice(&pad);
+            padded
+        });
+
+        let padding_rows = || {
+            let pad_row = vec![zero; padded_width];
+            futures::stream::iter(iter::repeat(pad_row).take(padding.0 as usize).map(Ok))
+        };
+
+        let rows = padding_rows().chain(rows).chain(padding_rows());
+
+        // hold only the last `kernel_height` rows, which is the window of the next output row
+        let (kernel_height, kernel_width) = (kernel_height as usize, kernel_width as usize);
+        let (stride_rows, stride_cols) = (stride.0 as usize, stride.1 as usize);
+        let mut window = VecDeque::with_capacity(kernel_height);
+        let mut row_num = 0;
+
+        let values = rows
+            .map_ok(move |row| {
+                window.push_back(row);
+                if window.len() > kernel_height {
+                    window.pop_front();
+                }
+
+                row_num += 1;
+
+                let mut values = Vec::new();
+                if window.len() == kernel_height && (row_num - kernel_height) % stride_rows == 0 {
+                    values.reserve(out_width as usize);
+
+                    for j in 0..(out_width as usize) {
+                        let start = j * stride_cols;
+                        let mut sum = zero;
+                        for (input_row, kernel_row) in
+                            window.iter().zip(kernel.chunks(kernel_width))
+                        {
+                            for (x, w) in input_row[start..start + kernel_width]
+                                .iter()
+                                .zip(kernel_row)
+                            {
+                                sum = sum + (*x * *w);
+                            }
+                        }
+
+                        values.push(sum);
+                    }
+                }
+
+                futures::stream::iter(values.into_iter().map(TCResult::Ok))
+            })
+            .try_flatten();
+
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        BlockListFile::from_values(file, txn_id, shape, dtype, Box::pin(values))
+            .map_ok(DenseTensor::from)
+            .await
+    }
+
+    /// Compute the Kronecker product of this 2-dimensional `DenseTensor` with shape `(m, n)` and
+    /// `other` with shape `(p, q)`, which has shape `(m * p, n * q)`.
+    ///
+    /// The output is computed one block of `p` rows at a time, so only `other` and a single row
+    /// of this `DenseTensor` are held in memory.
+    pub async fn kron<O: DenseAccess<FD, FS, D, T>>(
+        self,
+        other: DenseTensor<FD, FS, D, T, O>,
+        txn: T,
+    ) -> TCResult<DenseTensor<FD, FS, D, T, BlockListFile<FD, FS, D, T>>> {
+        if self.ndim() != 2 || other.ndim() != 2 {
+            return Err(TCError::bad_request(
+                "Kronecker product requires two 2-dimensional Tensors, not",
+                format!("{} and {}", self.shape(), other.shape()),
+            ));
+        }
+
+        let (m, n) = (self.shape()[0], self.shape()[1]);
+        let (p, q) = (other.shape()[0], other.shape()[1]);
+        let shape = Shape::from(vec![m * p, n * q]);
+        let dtype = Ord::max(self.dtype(), other.dtype());
+
+        let txn_id = *txn.id();
+
+        if shape.size() == 0 {
+            let file = txn
+                .context()
+                .create_file_tmp(txn_id, TensorType::Dense)
+                .await?;
+
+            return DenseTensor::create(file, Schema { shape, dtype }, txn_id).await;
+        }
+        let block: Vec<Number> = other
+            .blocks
+            .value_stream(txn.clone())
+            .await?
+            .try_collect()
+            .await?;
+
+        let rows = self
+            .blocks
+            .value_stream(txn.clone())
+            .await?
+            .chunks(n as usize)
+            .map(|row| row.into_iter().collect::<TCResult<Vec<Number>>>());
+
+        let values = rows
+            .map_ok(move |row| {
+                let mut values = Vec::with_capacity(block.len() * row.len());
+                for block_row in block.chunks(q as usize) {
+                    for a in &row {
+                        for b in block_row {
+                            values.push((*a * *b).into_type(dtype));
+                        }
+                    }
+                }
+
+                futures::stream::iter(values.into_iter().map(TCResult::Ok))
+            })
+            .try_flatten();
+
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        BlockListFile::from_values(file, txn_id, shape, dtype, Box::pin(values))
+            .map_ok(DenseTensor::from)
+            .await
+    }
+}
+
+/// Return the shape of the matrix product of tensors with shapes `left` and `right`.
+fn matmul_shape(left: &Shape, right: &Shape) -> TCResult<Vec<u64>> {
+    let mismatch = || {
+        TCError::unsupported(format!(
+            "cannot compute the matrix product of tensors with shapes {} and {}",
+            left, right
+        ))
+    };
+
+    if left.len() < 2 || right.len() < 2 {
+        return Err(mismatch());
+    }
+
+    let (m, k) = (left[left.len() - 2], left[left.len() - 1]);
+    let (j, n) = (right[right.len() - 2], right[right.len() - 1]);
+    if k != j {
+        return Err(mismatch());
+    }
+
+    let left_batch = &left[..left.len() - 2];
+    let right_batch = &right[..right.len() - 2];
+    let ndim = Ord::max(left_batch.len(), right_batch.len());
+
+    let mut shape = Vec::with_capacity(ndim + 2);
+    for axis in 0..ndim {
+        let l = batch_dim(left_batch, ndim, axis);
+        let r = batch_dim(right_batch, ndim, axis);
+
+        if l == r || r == 1 {
+            shape.push(l);
+        } else if l == 1 {
+            shape.push(r);
+        } else {
+            return Err(mismatch());
+        }
+    }
+
+    shape.push(m);
+    shape.push(n);
+    Ok(shape)
+}
+
+/// Return the dimension of the given `axis` of a batch shape broadcast to `ndim` dimensions.
+fn batch_dim(batch: &[u64], ndim: usize, axis: usize) -> u64 {
+    let offset = ndim - batch.len();
+    if axis < offset {
+        1
+    } else {
+        batch[axis - offset]
+    }
+}
+
+/// Read the `rows` x `cols` matrix at the given batch `coord` of `tensor` into memory.
+async fn read_matrix<FD, FS, D, T, B>(
+    tensor: DenseTensor<FD, FS, D, T, B>,
+    coord: Coord,
+    rows: u64,
+    cols: u64,
+    txn: T,
+) -> TCResult<Array>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    B: DenseAccess<FD, FS, D, T>,
+    D::FileClass: From<TensorType>,
+{
+    let bounds = coord
+        .into_iter()
+        .map(AxisBounds::At)
+        .chain(vec![AxisBounds::all(rows), AxisBounds::all(cols)])
+        .collect::<Vec<AxisBounds>>();
+
+    let matrix = tensor.slice(bounds.into())?;
+    let values = matrix.into_inner().value_stream(txn).await?;
+    values
+        .try_collect::<Vec<Number>>()
+        .map_ok(Array::from)
+        .await
+}
+
+/// Multiply the row-major `m` x `k` matrix `left` by the row-major `k` x `n` matrix `right`.
+fn matmul_block<T>(left: &Array, right: &Array, m: u64, k: u64, n: u64) -> Array
+where
+    T: af::HasAfEnum + af::FloatingPoint,
+    Array: From<ArrayExt<T>>,
+{
+    // ArrayFire is column-major, so a row-major matrix reads as its own transpose,
+    // and the transpose of (left x right) is (right^T x left^T)
+    let left = af::moddims(left.type_cast::<T>().af(), af::Dim4::new(&[k, m, 1, 1]));
+    let right = af::moddims(right.type_cast::<T>().af(), af::Dim4::new(&[n, k, 1, 1]));
+    let product = af::matmul(&right, &left, af::MatProp::NONE, af::MatProp::NONE);
+    Array::from(ArrayExt::from(af::flat(&product)))
 }
 
 impl<FD, FS, D, T> DenseTensor<FD, FS, D, T, BlockListFile<FD, FS, D, T>>
@@ -181,15 +556,33 @@ where
     type Combine = DenseTensor<FD, FS, D, T, BlockListCombine<FD, FS, D, T, B, O>>;
 
     fn and(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
-        self.combine(other, Array::and, Number::and, NumberType::Bool)
+        self.combine(
+            other,
+            Array::and,
+            Number::and,
+            NumberType::Bool,
+            NonFinite::Propagate,
+        )
     }
 
     fn or(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
-        self.combine(other, Array::or, Number::or, NumberType::Bool)
+        self.combine(
+            other,
+            Array::or,
+            Number::or,
+            NumberType::Bool,
+            NonFinite::Propagate,
+        )
     }
 
     fn xor(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
-        self.combine(other, Array::xor, Number::xor, NumberType::Bool)
+        self.combine(
+            other,
+            Array::xor,
+            Number::xor,
+            NumberType::Bool,
+            NonFinite::Propagate,
+        )
     }
 }
 
@@ -245,7 +638,7 @@ where
             Number::from(l == r)
         }
 
-        self.combine(other, Array::eq, eq, NumberType::Bool)
+        self.combine(other, Array::eq, eq, NumberType::Bool, NonFinite::Propagate)
     }
 
     fn gt(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Compare> {
@@ -253,7 +646,7 @@ where
             Number::from(l > r)
         }
 
-        self.combine(other, Array::gt, gt, NumberType::Bool)
+        self.combine(other, Array::gt, gt, NumberType::Bool, NonFinite::Propagate)
     }
 
     fn gte(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Dense> {
@@ -261,7 +654,13 @@ where
             Number::from(l >= r)
         }
 
-        self.combine(other, Array::gte, gte, NumberType::Bool)
+        self.combine(
+            other,
+            Array::gte,
+            gte,
+            NumberType::Bool,
+            NonFinite::Propagate,
+        )
     }
 
     fn lt(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Compare> {
@@ -269,7 +668,7 @@ where
             Number::from(l > r)
         }
 
-        self.combine(other, Array::lt, lt, NumberType::Bool)
+        self.combine(other, Array::lt, lt, NumberType::Bool, NonFinite::Propagate)
     }
 
     fn lte(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Dense> {
@@ -277,7 +676,13 @@ where
             Number::from(l > r)
         }
 
-        self.combine(other, Array::lte, lte, NumberType::Bool)
+        self.combine(
+            other,
+            Array::lte,
+            lte,
+            NumberType::Bool,
+            NonFinite::Propagate,
+        )
     }
 
     fn ne(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Compare> {
@@ -285,7 +690,7 @@ where
             Number::from(l > r)
         }
 
-        self.combine(other, Array::ne, ne, NumberType::Bool)
+        self.combine(other, Array::ne, ne, NumberType::Bool, NonFinite::Propagate)
     }
 }
 
@@ -344,6 +749,74 @@ where
     }
 }
 
+#[async_trait]
+impl<FD, FS, D, T, B, O> TensorDot<D, DenseTensor<FD, FS, D, T, O>> for DenseTensor<FD, FS, D, T, B>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    B: DenseAccess<FD, FS, D, T>,
+    O: DenseAccess<FD, FS, D, T>,
+    D::FileClass: From<TensorType>,
+{
+    type Txn = T;
+    type Dot = DenseTensor<FD, FS, D, T, BlockListFile<FD, FS, D, T>>;
+
+    async fn matmul(self, other: DenseTensor<FD, FS, D, T, O>, txn: T) -> TCResult<Self::Dot> {
+        let shape = matmul_shape(self.shape(), other.shape())?;
+        debug!(
+            "matmul {} with {} -> {:?}",
+            self.shape(),
+            other.shape(),
+            shape
+        );
+
+        let ndim = shape.len();
+        let (m, n) = (shape[ndim - 2], shape[ndim - 1]);
+        let k = self.shape()[self.ndim() - 1];
+        let dtype = Ord::max(self.dtype(), other.dtype());
+
+        // broadcast each operand over the batch axes only, then multiply one pair of matrices
+        // at a time, so that at most O(m * k + k * n + m * n) elements are held in memory
+        let batch = Shape::from(shape[..ndim - 2].to_vec());
+        let left = self.broadcast([&batch[..], &[m, k]].concat().into())?;
+        let right = other.broadcast([&batch[..], &[k, n]].concat().into())?;
+
+        let txn_id = *txn.id();
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        let products = futures::stream::iter(Bounds::all(&batch).affected())
+            .then(move |coord| {
+                let left = left.clone();
+                let right = right.clone();
+                let txn = txn.clone();
+
+                Box::pin(async move {
+                    let left = read_matrix(left, coord.clone(), m, k, txn.clone()).await?;
+                    let right = read_matrix(right, coord, k, n, txn).await?;
+
+                    let product = match dtype {
+                        NumberType::Complex(_) => matmul_block::<af::c64>(&left, &right, m, k, n),
+                        _ => matmul_block::<f64>(&left, &right, m, k, n),
+                    };
+
+                    TCResult::Ok(Array::cast_into(&product, dtype).to_vec())
+                })
+            })
+            .map_ok(|values| futures::stream::iter(values.into_iter().map(TCResult::Ok)))
+            .try_flatten();
+
+        let products: TCBoxTryStream<Number> = Box::pin(products);
+        BlockListFile::from_values(file, txn_id, shape.into(), dtype, products)
+            .map_ok(DenseTensor::from)
+            .await
+    }
+}
+
 #[async_trait]
 impl<FD, FS, D, T, B> TensorIO<D> for DenseTensor<FD, FS, D, T, B>
 where
@@ -394,6 +867,30 @@ where
         Err(TCError::not_implemented("DenseTensor::mask"))
     }
 
+    async fn set_where(
+        self,
+        txn: T,
+        mask: DenseTensor<FD, FS, D, T, O>,
+        value: Number,
+    ) -> TCResult<()> {
+        if self.shape() != mask.shape() {
+            return Err(TCError::unsupported(format!(
+                "cannot use a Tensor with shape {} as a mask for a Tensor with shape {}",
+                mask.shape(),
+                self.shape(),
+            )));
+        }
+
+        let txn_id = *txn.id();
+        let filled = mask.into_sparse().into_inner().filled(txn).await?;
+
+        filled
+            .map_ok(|(coord, _)| self.write_value_at(txn_id, coord, value))
+            .try_buffer_unordered(num_cpus::get())
+            .try_fold((), |_, _| future::ready(Ok(())))
+            .await
+    }
+
     async fn write(
         self,
         txn: T,
@@ -444,6 +941,29 @@ where
         }
     }
 
+    async fn set_where(self, txn: T, mask: Tensor<FD, FS, D, T>, value: Number) -> TCResult<()> {
+        let mask = if self.shape() == mask.shape() {
+            mask
+        } else {
+            mask.broadcast(self.shape().clone())?
+        };
+
+        match mask {
+            Tensor::Dense(dense) => self.set_where(txn, dense, value).await,
+            Tensor::Sparse(sparse) => {
+                // read only the filled elements of a sparse mask, rather than densifying it
+                let txn_id = *txn.id();
+                let filled = sparse.into_inner().filled(txn).await?;
+
+                filled
+                    .map_ok(|(coord, _)| self.write_value_at(txn_id, coord, value))
+                    .try_buffer_unordered(num_cpus::get())
+                    .try_fold((), |_, _| future::ready(Ok(())))
+                    .await
+            }
+        }
+    }
+
     async fn write(self, txn: T, bounds: Bounds, other: Tensor<FD, FS, D, T>) -> TCResult<()> {
         debug!("DenseTensor::write {} to {}", other, bounds);
 
@@ -474,37 +994,53 @@ where
 {
     type Combine = DenseTensor<FD, FS, D, T, BlockListCombine<FD, FS, D, T, B, O>>;
 
-    fn add(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
+    fn add_with(
+        self,
+        other: DenseTensor<FD, FS, D, T, O>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         fn add_array(l: &Array, r: &Array) -> Array {
             debug_assert_eq!(l.len(), r.len());
             l + r
         }
 
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, add_array, Add::add, dtype)
+        self.combine(other, add_array, Add::add, dtype, non_finite)
     }
 
-    fn div(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
+    fn div_with(
+        self,
+        other: DenseTensor<FD, FS, D, T, O>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         fn div_array(l: &Array, r: &Array) -> Array {
             debug_assert_eq!(l.len(), r.len());
             l / r
         }
 
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, div_array, Div::div, dtype)
+        self.combine(other, div_array, Div::div, dtype, non_finite)
     }
 
-    fn mul(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
+    fn mul_with(
+        self,
+        other: DenseTensor<FD, FS, D, T, O>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         fn mul_array(l: &Array, r: &Array) -> Array {
             debug_assert_eq!(l.len(), r.len());
             l * r
         }
 
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, mul_array, Mul::mul, dtype)
+        self.combine(other, mul_array, Mul::mul, dtype, non_finite)
     }
 
-    fn sub(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
+    fn sub_with(
+        self,
+        other: DenseTensor<FD, FS, D, T, O>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         debug!("subtract {} from {}", other, self);
 
         fn sub_array(l: &Array, r: &Array) -> Array {
@@ -513,7 +1049,23 @@ where
         }
 
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, sub_array, Sub::sub, dtype)
+        self.combine(other, sub_array, Sub::sub, dtype, non_finite)
+    }
+
+    fn maximum(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        let dtype = Ord::max(self.dtype(), other.dtype());
+        self.combine(other, max_array, max_value, dtype, NonFinite::default())
+    }
+
+    fn minimum(self, other: DenseTensor<FD, FS, D, T, O>) -> TCResult<Self::Combine> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        let dtype = Ord::max(self.dtype(), other.dtype());
+        self.combine(other, min_array, min_value, dtype, NonFinite::default())
     }
 }
 
@@ -528,31 +1080,69 @@ where
 {
     type Combine = Tensor<FD, FS, D, T>;
 
-    fn add(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn add_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
+        match other {
+            Tensor::Dense(dense) => self.add_with(dense, non_finite).map(Tensor::from),
+            Tensor::Sparse(sparse) => self
+                .add_with(sparse.into_dense(), non_finite)
+                .map(Tensor::from),
+        }
+    }
+
+    fn div_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
+        match other {
+            Tensor::Dense(dense) => self.div_with(dense, non_finite).map(Tensor::from),
+            Tensor::Sparse(sparse) => self
+                .div_with(sparse.into_dense(), non_finite)
+                .map(Tensor::from),
+        }
+    }
+
+    fn mul_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Dense(dense) => self.add(dense).map(Tensor::from),
-            Tensor::Sparse(sparse) => self.add(sparse.into_dense()).map(Tensor::from),
+            Tensor::Dense(dense) => self.mul_with(dense, non_finite).map(Tensor::from),
+            Tensor::Sparse(sparse) => sparse
+                .mul_with(self.into_sparse(), non_finite)
+                .map(Tensor::from),
         }
     }
 
-    fn div(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn sub_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Dense(dense) => self.div(dense).map(Tensor::from),
-            Tensor::Sparse(sparse) => self.div(sparse.into_dense()).map(Tensor::from),
+            Tensor::Dense(dense) => self.sub_with(dense, non_finite).map(Tensor::from),
+            Tensor::Sparse(sparse) => self
+                .sub_with(sparse.into_dense(), non_finite)
+                .map(Tensor::from),
         }
     }
 
-    fn mul(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn maximum(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Dense(dense) => self.mul(dense).map(Tensor::from),
-            Tensor::Sparse(sparse) => sparse.mul(self.into_sparse()).map(Tensor::from),
+            Tensor::Dense(dense) => self.maximum(dense).map(Tensor::from),
+            Tensor::Sparse(sparse) => self.maximum(sparse.into_dense()).map(Tensor::from),
         }
     }
 
-    fn sub(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn minimum(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Dense(dense) => self.sub(dense).map(Tensor::from),
-            Tensor::Sparse(sparse) => self.sub(sparse.into_dense()).map(Tensor::from),
+            Tensor::Dense(dense) => self.minimum(dense).map(Tensor::from),
+            Tensor::Sparse(sparse) => self.minimum(sparse.into_dense()).map(Tensor::from),
         }
     }
 }
@@ -594,7 +1184,20 @@ where
             let zero = self.dtype().zero();
             let mut product = self.dtype().one();
 
+            let concurrency = txn.reduce_concurrency();
             let blocks = self.blocks.clone().block_stream(txn).await?;
+            if self.size() > PER_BLOCK as u64 {
+                let product = reduce_blocks(
+                    blocks,
+                    product,
+                    |block| block.product(),
+                    Mul::mul,
+                    concurrency,
+                );
+
+                return product.await;
+            }
+
             let mut block_products = blocks.map_ok(|array| array.product());
 
             while let Some(block_product) = block_products.try_next().await? {
@@ -616,14 +1219,78 @@ where
     fn sum_all(&self, txn: T) -> TCBoxTryFuture<Number> {
         Box::pin(async move {
             let zero = self.dtype().zero();
+            let concurrency = txn.reduce_concurrency();
             let blocks = self.blocks.clone().block_stream(txn).await?;
 
+            if self.size() > PER_BLOCK as u64 {
+                let sum = reduce_blocks(blocks, zero, |block| block.sum(), Add::add, concurrency);
+
+                return sum.await;
+            }
+
             blocks
                 .map_ok(|array| array.sum())
                 .try_fold(zero, |sum, block_sum| future::ready(Ok(sum + block_sum)))
                 .await
         })
     }
+
+    fn max(self, axis: usize) -> TCResult<Self::Reduce> {
+        BlockListReduce::new(self.blocks, axis, DenseTensor::max_all).map(DenseTensor::from)
+    }
+
+    fn max_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move {
+            let blocks = self.blocks.clone().block_stream(txn).await?;
+            let max = blocks
+                .map_ok(|array| array.to_vec().into_iter().fold(None, max_option))
+                .try_fold(None, |max, block_max| {
+                    future::ready(Ok(block_max.into_iter().fold(max, max_option)))
+                })
+                .await?;
+
+            max.ok_or_else(|| TCError::unsupported("the maximum of an empty Tensor is undefined"))
+        })
+    }
+
+    fn mean(self, axis: usize) -> TCResult<Self::Reduce> {
+        let dtype = float_type(self.dtype());
+        BlockListReduce::with_dtype(self.blocks, axis, DenseTensor::mean_all, dtype)
+            .map(DenseTensor::from)
+    }
+
+    fn mean_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move {
+            let moments = self.moments(txn).await?;
+            Ok(statistic(moments.mean(self.size()), self.dtype()))
+        })
+    }
+
+    fn var(self, axis: usize) -> TCResult<Self::Reduce> {
+        let dtype = float_type(self.dtype());
+        BlockListReduce::with_dtype(self.blocks, axis, DenseTensor::var_all, dtype)
+            .map(DenseTensor::from)
+    }
+
+    fn var_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move {
+            let moments = self.moments(txn).await?;
+            Ok(statistic(moments.var(self.size()), self.dtype()))
+        })
+    }
+
+    fn std(self, axis: usize) -> TCResult<Self::Reduce> {
+        let dtype = float_type(self.dtype());
+        BlockListReduce::with_dtype(self.blocks, axis, DenseTensor::std_all, dtype)
+            .map(DenseTensor::from)
+    }
+
+    fn std_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move {
+            let moments = self.moments(txn).await?;
+            Ok(statistic(moments.std(self.size()), self.dtype()))
+        })
+    }
 }
 
 impl<FD, FS, D, T, B> TensorTransform for DenseTensor<FD, FS, D, T, B>
@@ -651,6 +1318,11 @@ where
         Ok(DenseTensor::from(blocks))
     }
 
+    fn cast_into_saturating(self, dtype: NumberType) -> TCResult<Self::Cast> {
+        let blocks = BlockListCast::saturating(self.blocks, dtype);
+        Ok(DenseTensor::from(blocks))
+    }
+
     fn expand_dims(self, axis: usize) -> TCResult<Self::Expand> {
         let blocks = BlockListExpand::new(self.blocks, axis)?;
         Ok(DenseTensor::from(blocks))
@@ -679,6 +1351,7 @@ where
 {
     type Txn = T;
     type Unary = DenseTensor<FD, FS, D, T, BlockListUnary<FD, FS, D, T, B>>;
+    type Dense = DenseTensor<FD, FS, D, T, BlockListUnary<FD, FS, D, T, B>>;
 
     fn abs(&self) -> TCResult<Self::Unary> {
         let blocks = BlockListUnary::new(
@@ -714,7 +1387,51 @@ where
         Ok(false)
     }
 
-    fn not(&self) -> TCResult<Self::Unary> {
+    fn exp(&self) -> TCResult<Self::Unary> {
+        let blocks = BlockListUnary::new(
+            self.blocks.clone(),
+            |block| float_op_block(block, af::exp),
+            |value| float_op(value, f64::exp),
+            float_type(self.dtype()),
+        );
+
+        Ok(DenseTensor::from(blocks))
+    }
+
+    fn ln(&self) -> TCResult<Self::Unary> {
+        let blocks = BlockListUnary::new(
+            self.blocks.clone(),
+            |block| float_op_block(block, af::log),
+            |value| float_op(value, f64::ln),
+            float_type(self.dtype()),
+        );
+
+        Ok(DenseTensor::from(blocks))
+    }
+
+    fn log2(&self) -> TCResult<Self::Unary> {
+        let blocks = BlockListUnary::new(
+            self.blocks.clone(),
+            |block| float_op_block(block, af::log2),
+            |value| float_op(value, f64::log2),
+            float_type(self.dtype()),
+        );
+
+        Ok(DenseTensor::from(blocks))
+    }
+
+    fn log10(&self) -> TCResult<Self::Unary> {
+        let blocks = BlockListUnary::new(
+            self.blocks.clone(),
+            |block| float_op_block(block, af::log10),
+            |value| float_op(value, f64::log10),
+            float_type(self.dtype()),
+        );
+
+        Ok(DenseTensor::from(blocks))
+    }
+
+    fn not(&self) -> TCResult<Self::Dense> {
         let blocks = BlockListUnary::new(
             self.blocks.clone(),
             Array::not,
@@ -744,6 +1461,24 @@ where
     }
 }
 
+#[async_trait]
+impl<FD, FS, D, T> Rollback for DenseTensor<FD, FS, D, T, BlockListFile<FD, FS, D, T>>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError> + Rollback,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.blocks.savepoint(txn_id, ordinal).await
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.blocks.rollback_to(txn_id, ordinal).await
+    }
+}
+
 #[async_trait]
 impl<'en, FD, FS, D, T, B> Hash<'en, D> for DenseTensor<FD, FS, D, T, B>
 where
@@ -1051,3 +1786,45 @@ fn encodable_c64<'en>(blocks: TCBoxTryStream<'en, Array>) -> impl Stream<Item =
             dest
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use futures::stream;
+
+    use tc_value::FloatType;
+
+    use super::*;
+
+    #[test]
+    fn test_parallel_sum_all() {
+        let dtype = NumberType::Float(FloatType::F64);
+        let num_blocks = 8;
+
+        let block = |i: usize| {
+            let values = (0..PER_BLOCK)
+                .map(|j| Number::from(((i * PER_BLOCK + j) % 7) as f64))
+                .collect::<Vec<Number>>();
+
+            Array::from(values)
+        };
+
+        let sequential = (0..num_blocks)
+            .map(|i| block(i).sum())
+            .fold(dtype.zero(), |sum, block_sum| sum + block_sum);
+
+        let blocks: TCBoxTryStream<Array> =
+            Box::pin(stream::iter((0..num_blocks).map(move |i| Ok(block(i)))));
+
+        let parallel = block_on(reduce_blocks(
+            blocks,
+            dtype.zero(),
+            |block| block.sum(),
+            Add::add,
+            4,
+        ))
+        .expect("parallel sum");
+
+        assert!(parallel == sequential);
+    }
+}
@@ -1,12 +1,16 @@
 /// A [`Tensor`], an n-dimensional array of [`Number`]s which supports basic math and logic
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::convert::TryFrom;
 use std::fmt;
 use std::marker::PhantomData;
+use std::ops::{Add, Mul, Range, Sub};
 
-use afarray::Array;
+use afarray::{Array, ArrayExt, ArrayInstance};
+use arrayfire as af;
 use async_trait::async_trait;
 use destream::{de, en};
-use futures::TryFutureExt;
+use futures::{future, StreamExt, TryFutureExt, TryStreamExt};
 use log::debug;
 use safecast::*;
 
@@ -14,16 +18,22 @@ use tc_btree::{BTreeType, Node};
 use tc_error::*;
 use tc_transact::fs::{Dir, File};
 use tc_transact::{IntoView, Transaction, TxnId};
-use tc_value::{Number, NumberType, Value, ValueType};
+use tc_value::{
+    Complex, ComplexType, Float, FloatType, Int, Number, NumberClass, NumberInstance, NumberType,
+    UInt, UIntType, Value, ValueType,
+};
 use tcgeneric::{
-    label, path_label, Class, Instance, NativeClass, PathLabel, PathSegment, TCBoxTryFuture,
-    TCPathBuf, Tuple,
+    label, path_label, Class, Id, Instance, Label, NativeClass, PathLabel, PathSegment,
+    TCBoxTryFuture, TCPathBuf, Tuple,
 };
 
 pub use bounds::{AxisBounds, Bounds, Shape};
 pub use dense::{BlockListFile, DenseAccess, DenseAccessor, DenseTensor};
 pub use einsum::einsum;
-pub use sparse::{SparseAccess, SparseAccessor, SparseTable, SparseTensor};
+pub use sparse::{
+    sorted_merge, sparse_concatenate, sparse_matmul, MergedRow, SparseAccess, SparseAccessor,
+    SparseTable, SparseTensor,
+};
 
 mod bounds;
 mod dense;
@@ -180,6 +190,23 @@ pub trait TensorCompare<O> {
     fn ne(self, other: O) -> TCResult<Self::Compare>;
 }
 
+/// Matrix products of [`Tensor`]s
+#[async_trait]
+pub trait TensorDot<D: Dir, O> {
+    /// The type of [`Transaction`] to expect
+    type Txn: Transaction<D>;
+
+    /// The result type of a matrix product
+    type Dot: TensorInstance;
+
+    /// Compute the matrix product of this [`Tensor`] with `other`, contracting the last axis
+    /// of this [`Tensor`] with the second-to-last axis of `other`.
+    ///
+    /// Any leading axes are treated as a batch of matrices and broadcast together, so the product
+    /// of a [`Tensor`] with shape `(b, m, k)` and one with shape `(k, n)` has shape `(b, m, n)`.
+    async fn matmul(self, other: O, txn: Self::Txn) -> TCResult<Self::Dot>;
+}
+
 /// [`Tensor`] I/O operations
 #[async_trait]
 pub trait TensorIO<D: Dir> {
@@ -190,6 +217,9 @@ pub trait TensorIO<D: Dir> {
     async fn read_value(self, txn: Self::Txn, coord: Coord) -> TCResult<Number>;
 
     /// Write a single value to the slice of this [`Tensor`] with the given [`Bounds`].
+    ///
+    /// Note that filling a sparse [`Tensor`] with a nonzero `value` writes an explicit entry
+    /// for every element of the slice, so a large slice will be stored densely.
     async fn write_value(&self, txn_id: TxnId, bounds: Bounds, value: Number) -> TCResult<()>;
 
     /// Overwrite a single element of this [`Tensor`].
@@ -205,26 +235,664 @@ pub trait TensorDualIO<D: Dir, O> {
     /// Zero out the elements of this [`Tensor`] where the corresponding element of `value` is nonzero.
     async fn mask(self, txn: Self::Txn, value: O) -> TCResult<()>;
 
+    /// Set the elements of this [`Tensor`] to `value` where the corresponding element of `mask` is nonzero.
+    async fn set_where(self, txn: Self::Txn, mask: O, value: Number) -> TCResult<()>;
+
     /// Overwrite the slice of this [`Tensor`] given by [`Bounds`] with the given `value`.
     async fn write(self, txn: Self::Txn, bounds: Bounds, value: O) -> TCResult<()>;
 }
 
+const PROPAGATE: Label = label("propagate");
+const ERROR: Label = label("error");
+const ZERO: Label = label("zero");
+
+/// The policy to apply when a [`TensorMath`] operation produces a non-finite value
+/// (`NaN` or infinity)
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum NonFinite {
+    /// Return the non-finite value as-is.
+    Propagate,
+
+    /// Return a `bad_request` error at the first non-finite value.
+    Error,
+
+    /// Replace the non-finite value with zero.
+    Zero,
+}
+
+impl Default for NonFinite {
+    fn default() -> Self {
+        Self::Propagate
+    }
+}
+
+impl NonFinite {
+    /// Apply this policy to a single `value` of the given `dtype`.
+    pub fn apply(self, value: Number, dtype: NumberType) -> TCResult<Number> {
+        if is_finite(&value) {
+            return Ok(value);
+        }
+
+        match self {
+            Self::Propagate => Ok(value),
+            Self::Error => Err(TCError::bad_request(
+                "tensor math operation produced a non-finite value",
+                value,
+            )),
+            Self::Zero => Ok(dtype.zero()),
+        }
+    }
+
+    /// Apply this policy to each element of the given `block` of the given `dtype`.
+    pub fn apply_block(self, block: Array, dtype: NumberType) -> TCResult<Array> {
+        match (self, dtype) {
+            (Self::Propagate, _) => Ok(block),
+            (_, NumberType::Complex(_)) | (_, NumberType::Float(_)) => {
+                let values = block.to_vec();
+                if values.iter().all(is_finite) {
+                    return Ok(block);
+                }
+
+                let values = values
+                    .into_iter()
+                    .map(|value| self.apply(value, dtype))
+                    .collect::<TCResult<Vec<Number>>>()?;
+
+                Ok(Array::cast_into(&Array::from(values), dtype))
+            }
+            _ => Ok(block),
+        }
+    }
+
+    fn as_id(&self) -> Id {
+        match self {
+            Self::Propagate => PROPAGATE.into(),
+            Self::Error => ERROR.into(),
+            Self::Zero => ZERO.into(),
+        }
+    }
+}
+
+impl TryCastFrom<Value> for NonFinite {
+    fn can_cast_from(value: &Value) -> bool {
+        Self::opt_cast_from(value.clone()).is_some()
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        if value.is_none() {
+            return Some(Self::default());
+        }
+
+        let id = Id::opt_cast_from(value)?;
+        if id == PROPAGATE {
+            Some(Self::Propagate)
+        } else if id == ERROR {
+            Some(Self::Error)
+        } else if id == ZERO {
+            Some(Self::Zero)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for NonFinite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_id(), f)
+    }
+}
+
+const L1: Label = label("l1");
+const L2: Label = label("l2");
+const MAX: Label = label("max");
+
+/// The norm by which to scale each lane of a `Tensor` in [`Tensor::normalize`]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum NormKind {
+    /// The sum of the absolute values of a lane.
+    L1,
+
+    /// The square root of the sum of the squares of a lane.
+    L2,
+
+    /// The greatest absolute value in a lane.
+    Max,
+}
+
+impl Default for NormKind {
+    fn default() -> Self {
+        Self::L2
+    }
+}
+
+impl NormKind {
+    /// Compute this norm of the given `lane`.
+    pub fn of(self, lane: &[f64]) -> f64 {
+        match self {
+            Self::L1 => lane.iter().map(|x| x.abs()).sum(),
+            Self::L2 => lane.iter().map(|x| x * x).sum::<f64>().sqrt(),
+            Self::Max => lane.iter().map(|x| x.abs()).fold(0., f64::max),
+        }
+    }
+
+    fn as_id(&self) -> Id {
+        match self {
+            Self::L1 => L1.into(),
+            Self::L2 => L2.into(),
+            Self::Max => MAX.into(),
+        }
+    }
+}
+
+impl TryCastFrom<Value> for NormKind {
+    fn can_cast_from(value: &Value) -> bool {
+        Self::opt_cast_from(value.clone()).is_some()
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        if value.is_none() {
+            return Some(Self::default());
+        }
+
+        let id = Id::opt_cast_from(value)?;
+        if id == L1 {
+            Some(Self::L1)
+        } else if id == L2 {
+            Some(Self::L2)
+        } else if id == MAX {
+            Some(Self::Max)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for NormKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_id(), f)
+    }
+}
+
+/// An element of one lane of a `Tensor`, ordered so that the greatest `Ranked` element
+/// is the first to evict from a bounded heap of the top-k elements.
+struct Ranked {
+    value: Number,
+    index: u64,
+    largest: bool,
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_value = cmp_nan_greatest(&self.value, &other.value);
+
+        let by_value = if self.largest {
+            by_value.reverse()
+        } else {
+            by_value
+        };
+
+        by_value.then(self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+/// Return the element of the given `lane` along `axis` at `index`,
+/// which counts back from the end of the `lane` if negative.
+fn take(lane: &[Number], axis: usize, index: Number) -> TCResult<Number> {
+    let dim = lane.len() as i64;
+    let index = i64::try_cast_from(Value::Number(index), |v| {
+        TCError::bad_request("invalid index for take_along_axis", v)
+    })?;
+
+    let i = if index < 0 { index + dim } else { index };
+    if i < 0 || i >= dim {
+        return Err(TCError::bad_request(
+            format!(
+                "index out of bounds for axis {} with dimension {}",
+                axis, dim
+            ),
+            index,
+        ));
+    }
+
+    Ok(lane[i as usize])
+}
+
+/// Select the `k` greatest (or, if `largest` is `false`, least) elements of the given `lane`,
+/// in order.
+fn top_k(lane: Vec<Number>, k: usize, largest: bool) -> Vec<Ranked> {
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for (index, value) in lane.into_iter().enumerate() {
+        heap.push(Ranked {
+            value,
+            index: index as u64,
+            largest,
+        });
+
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+}
+
+/// Compare two real numbers, ordering `NaN` after every other value (and equal to itself)
+/// so that a lane which contains `NaN` still has a total order, as in NumPy's `sort`.
+fn cmp_nan_greatest(l: &Number, r: &Number) -> Ordering {
+    let is_nan = |n: &Number| match n {
+        Number::Float(_) => f64::cast_from(*n).is_nan(),
+        _ => false,
+    };
+
+    match (is_nan(l), is_nan(r)) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => l
+            .partial_cmp(r)
+            .expect("real numbers which are not NaN are ordered"),
+    }
+}
+
+/// Return an error if the given `dtype` has no natural ordering, i.e. if it's complex.
+fn ordered(dtype: NumberType) -> TCResult<()> {
+    if let NumberType::Complex(_) = dtype {
+        Err(TCError::unsupported(format!(
+            "{} has no natural ordering",
+            dtype
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn max_value(l: Number, r: Number) -> Number {
+    if r > l {
+        r
+    } else {
+        l
+    }
+}
+
+fn max_option(max: Option<Number>, value: Number) -> Option<Number> {
+    match max {
+        Some(max) => Some(max_value(max, value)),
+        None => Some(value),
+    }
+}
+
+fn min_value(l: Number, r: Number) -> Number {
+    if r < l {
+        r
+    } else {
+        l
+    }
+}
+
+fn max_array(l: &Array, r: &Array) -> Array {
+    extremum_array(l, r, true)
+}
+
+fn min_array(l: &Array, r: &Array) -> Array {
+    extremum_array(l, r, false)
+}
+
+/// Return the element-wise maximum (or minimum, if `max` is `false`) of `l` and `r`,
+/// cast into their common type, using ArrayFire's `maxof` (or `minof`).
+fn extremum_array(l: &Array, r: &Array, max: bool) -> Array {
+    fn extremum<T>(l: &ArrayExt<T>, r: &ArrayExt<T>, max: bool) -> ArrayExt<T>
+    where
+        T: af::HasAfEnum + af::ImplicitPromote<T, Output = T>,
+    {
+        if max {
+            af::maxof(l.af(), r.af(), false).into()
+        } else {
+            af::minof(l.af(), r.af(), false).into()
+        }
+    }
+
+    let dtype = Ord::max(l.dtype(), r.dtype());
+    match (l.cast_into(dtype), r.cast_into(dtype)) {
+        (Array::Bool(l), Array::Bool(r)) => extremum(&l, &r, max).into(),
+        (Array::F32(l), Array::F32(r)) => extremum(&l, &r, max).into(),
+        (Array::F64(l), Array::F64(r)) => extremum(&l, &r, max).into(),
+        (Array::I16(l), Array::I16(r)) => extremum(&l, &r, max).into(),
+        (Array::I32(l), Array::I32(r)) => extremum(&l, &r, max).into(),
+        (Array::I64(l), Array::I64(r)) => extremum(&l, &r, max).into(),
+        (Array::U8(l), Array::U8(r)) => extremum(&l, &r, max).into(),
+        (Array::U16(l), Array::U16(r)) => extremum(&l, &r, max).into(),
+        (Array::U32(l), Array::U32(r)) => extremum(&l, &r, max).into(),
+        (Array::U64(l), Array::U64(r)) => extremum(&l, &r, max).into(),
+        _ => unreachable!("element-wise extremum of a complex array"),
+    }
+}
+
+fn is_finite(value: &Number) -> bool {
+    match value {
+        Number::Complex(Complex::C32(c)) => c.re.is_finite() && c.im.is_finite(),
+        Number::Complex(Complex::C64(c)) => c.re.is_finite() && c.im.is_finite(),
+        Number::Float(Float::F32(f)) => f.is_finite(),
+        Number::Float(Float::F64(f)) => f.is_finite(),
+        _ => true,
+    }
+}
+
+/// Return the real and imaginary parts of the given `value`.
+fn as_complex(value: Number) -> (f64, f64) {
+    match value {
+        Number::Complex(Complex::C32(c)) => (c.re as f64, c.im as f64),
+        Number::Complex(Complex::C64(c)) => (c.re, c.im),
+        real => (as_f64(real), 0.),
+    }
+}
+
+/// Compute the discrete Fourier transform (or its inverse) of the given complex `signal`,
+/// using the radix-2 Cooley-Tukey algorithm if its length is a power of two.
+fn dft(signal: Vec<(f64, f64)>, inverse: bool) -> Vec<(f64, f64)> {
+    let n = signal.len();
+    let sign = if inverse { 1. } else { -1. };
+
+    let mut spectrum = if n.is_power_of_two() {
+        let mut data = signal;
+
+        // reorder the signal by bit-reversed index
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+
+            j |= bit;
+            if i < j {
+                data.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let theta = sign * 2. * std::f64::consts::PI / len as f64;
+            for start in (0..n).step_by(len) {
+                for k in 0..(len / 2) {
+                    let (w_re, w_im) = ((theta * k as f64).cos(), (theta * k as f64).sin());
+                    let (u_re, u_im) = data[start + k];
+                    let (x_re, x_im) = data[start + k + len / 2];
+                    let (v_re, v_im) = (x_re * w_re - x_im * w_im, x_re * w_im + x_im * w_re);
+                    data[start + k] = (u_re + v_re, u_im + v_im);
+                    data[start + k + len / 2] = (u_re - v_re, u_im - v_im);
+                }
+            }
+
+            len <<= 1;
+        }
+
+        data
+    } else {
+        (0..n)
+            .map(|k| {
+                signal
+                    .iter()
+                    .enumerate()
+                    .fold((0., 0.), |(re, im), (t, (x_re, x_im))| {
+                        let theta =
+                            sign * 2. * std::f64::consts::PI * ((k * t) % n) as f64 / n as f64;
+                        let (w_re, w_im) = (theta.cos(), theta.sin());
+                        (
+                            re + x_re * w_re - x_im * w_im,
+                            im + x_re * w_im + x_im * w_re,
+                        )
+                    })
+            })
+            .collect()
+    };
+
+    if inverse && n > 0 {
+        for (re, im) in spectrum.iter_mut() {
+            *re /= n as f64;
+            *im /= n as f64;
+        }
+    }
+
+    spectrum
+}
+
+/// Replace a non-finite `value` with `nan`, `posinf`, or `neginf`, as appropriate.
+///
+/// The real and imaginary parts of a complex `value` are replaced independently.
+fn nan_to_num(value: Number, nan: f64, posinf: f64, neginf: f64) -> Number {
+    let replace = |f: f64| {
+        if f.is_nan() {
+            nan
+        } else if f == f64::INFINITY {
+            posinf
+        } else if f == f64::NEG_INFINITY {
+            neginf
+        } else {
+            f
+        }
+    };
+
+    match value {
+        Number::Complex(Complex::C32(mut c)) => {
+            c.re = replace(c.re as f64) as f32;
+            c.im = replace(c.im as f64) as f32;
+            Number::Complex(Complex::C32(c))
+        }
+        Number::Complex(Complex::C64(mut c)) => {
+            c.re = replace(c.re);
+            c.im = replace(c.im);
+            Number::Complex(Complex::C64(c))
+        }
+        Number::Float(Float::F32(f)) => Number::Float(Float::F32(replace(f as f64) as f32)),
+        Number::Float(Float::F64(f)) => Number::Float(Float::F64(replace(f))),
+        other => other,
+    }
+}
+
+/// Cast `value` into the given `dtype`, clamping it to the range of an integer `dtype`
+/// instead of wrapping it. A NaN is cast to zero.
+pub fn saturating_cast(value: Number, dtype: NumberType) -> Number {
+    let bits = dtype.size() as u32 * 8;
+    let (min, max) = match dtype {
+        NumberType::UInt(_) => (0i128, (1i128 << bits) - 1),
+        NumberType::Int(_) => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+        _ => return value.into_type(dtype),
+    };
+
+    let value = match value {
+        Number::UInt(u) => u64::cast_from(u) as i128,
+        Number::Int(i) => i64::cast_from(i) as i128,
+        Number::Float(_) => {
+            let f = f64::cast_from(value);
+            if f.is_nan() {
+                0
+            } else {
+                // a float-to-int `as` conversion saturates
+                f as i128
+            }
+        }
+        other => return other.into_type(dtype),
+    };
+
+    let value = value.max(min).min(max);
+    let value = if value < 0 {
+        Number::Int(Int::I64(value as i64))
+    } else {
+        Number::UInt(UInt::U64(value as u64))
+    };
+
+    value.into_type(dtype)
+}
+
+/// Cast each element of the given `block` into `dtype` as if by [`saturating_cast`].
+pub fn saturating_cast_block(block: Array, dtype: NumberType) -> Array {
+    match dtype {
+        NumberType::UInt(_) | NumberType::Int(_) => {
+            let values = block
+                .to_vec()
+                .into_iter()
+                .map(|value| saturating_cast(value, dtype))
+                .collect::<Vec<Number>>();
+
+            Array::cast_into(&Array::from(values), dtype)
+        }
+        _ => Array::cast_into(&block, dtype),
+    }
+}
+
+/// Add two [`Number`]s of the same type, or return `None` if an integer sum overflows.
+pub fn checked_add(left: Number, right: Number) -> Option<Number> {
+    checked_int(left, right, u64::checked_add, i64::checked_add, Add::add)
+}
+
+/// Multiply two [`Number`]s of the same type, or return `None` if an integer product overflows.
+pub fn checked_mul(left: Number, right: Number) -> Option<Number> {
+    checked_int(left, right, u64::checked_mul, i64::checked_mul, Mul::mul)
+}
+
+/// Subtract two [`Number`]s of the same type, or return `None` if an integer difference overflows.
+pub fn checked_sub(left: Number, right: Number) -> Option<Number> {
+    checked_int(left, right, u64::checked_sub, i64::checked_sub, Sub::sub)
+}
+
+/// Combine each element of `left` with the corresponding element of `right` using the given
+/// `checked` operation, or return a `bad_request` error if any result overflows `dtype`.
+pub fn checked_block(
+    left: Array,
+    right: Array,
+    checked: fn(Number, Number) -> Option<Number>,
+    dtype: NumberType,
+) -> TCResult<Array> {
+    let left = Array::cast_into(&left, dtype).to_vec();
+    let right = Array::cast_into(&right, dtype).to_vec();
+    let values = left
+        .into_iter()
+        .zip(right)
+        .map(|(l, r)| checked_value(l, r, checked, dtype))
+        .collect::<TCResult<Vec<Number>>>()?;
+
+    Ok(Array::cast_into(&Array::from(values), dtype))
+}
+
+/// Combine `left` with `right` using the given `checked` operation, or return a `bad_request`
+/// error if the result overflows `dtype`.
+pub fn checked_value(
+    left: Number,
+    right: Number,
+    checked: fn(Number, Number) -> Option<Number>,
+    dtype: NumberType,
+) -> TCResult<Number> {
+    let (left, right) = (left.into_type(dtype), right.into_type(dtype));
+    checked(left, right).ok_or_else(|| {
+        TCError::bad_request(
+            "tensor math operation overflowed",
+            format!("{} and {} ({})", left, right, dtype),
+        )
+    })
+}
+
+/// Combine two integer [`Number`]s at 64-bit width, then check that the result still fits in
+/// their original type. Non-integer [`Number`]s are combined with `op`, since they can't overflow.
+fn checked_int(
+    left: Number,
+    right: Number,
+    uint_op: fn(u64, u64) -> Option<u64>,
+    int_op: fn(i64, i64) -> Option<i64>,
+    op: fn(Number, Number) -> Number,
+) -> Option<Number> {
+    let dtype = left.class();
+    match (left, right) {
+        (Number::UInt(l), Number::UInt(r)) => {
+            let result = uint_op(u64::cast_from(l), u64::cast_from(r))?;
+            match Number::UInt(UInt::U64(result)).into_type(dtype) {
+                Number::UInt(narrow) if u64::cast_from(narrow) == result => {
+                    Some(Number::UInt(narrow))
+                }
+                _ => None,
+            }
+        }
+        (Number::Int(l), Number::Int(r)) => {
+            let result = int_op(i64::cast_from(l), i64::cast_from(r))?;
+            match Number::Int(Int::I64(result)).into_type(dtype) {
+                Number::Int(narrow) if i64::cast_from(narrow) == result => {
+                    Some(Number::Int(narrow))
+                }
+                _ => None,
+            }
+        }
+        (l, r) => Some(op(l, r)),
+    }
+}
+
 /// [`Tensor`] math operations
 pub trait TensorMath<D: Dir, O> {
     /// The result type of a math operation
     type Combine: TensorInstance;
 
     /// Add two tensors together.
-    fn add(self, other: O) -> TCResult<Self::Combine>;
+    fn add(self, other: O) -> TCResult<Self::Combine>
+    where
+        Self: Sized,
+    {
+        self.add_with(other, NonFinite::default())
+    }
+
+    /// Add two tensors together, handling non-finite results according to `non_finite`.
+    fn add_with(self, other: O, non_finite: NonFinite) -> TCResult<Self::Combine>;
 
     /// Divide `self` by `other`.
-    fn div(self, other: O) -> TCResult<Self::Combine>;
+    fn div(self, other: O) -> TCResult<Self::Combine>
+    where
+        Self: Sized,
+    {
+        self.div_with(other, NonFinite::default())
+    }
+
+    /// Divide `self` by `other`, handling non-finite results according to `non_finite`.
+    fn div_with(self, other: O, non_finite: NonFinite) -> TCResult<Self::Combine>;
 
     /// Multiply two tensors together.
-    fn mul(self, other: O) -> TCResult<Self::Combine>;
+    fn mul(self, other: O) -> TCResult<Self::Combine>
+    where
+        Self: Sized,
+    {
+        self.mul_with(other, NonFinite::default())
+    }
+
+    /// Multiply two tensors together, handling non-finite results according to `non_finite`.
+    fn mul_with(self, other: O, non_finite: NonFinite) -> TCResult<Self::Combine>;
 
     /// Subtract `other` from `self`.
-    fn sub(self, other: O) -> TCResult<Self::Combine>;
+    fn sub(self, other: O) -> TCResult<Self::Combine>
+    where
+        Self: Sized,
+    {
+        self.sub_with(other, NonFinite::default())
+    }
+
+    /// Subtract `other` from `self`, handling non-finite results according to `non_finite`.
+    fn sub_with(self, other: O, non_finite: NonFinite) -> TCResult<Self::Combine>;
+
+    /// Return the element-wise maximum of two tensors.
+    fn maximum(self, other: O) -> TCResult<Self::Combine>;
+
+    /// Return the element-wise minimum of two tensors.
+    fn minimum(self, other: O) -> TCResult<Self::Combine>;
 }
 
 /// [`Tensor`] reduction operations
@@ -246,6 +914,139 @@ pub trait TensorReduce<D: Dir> {
 
     /// Return the sum of all elements in this [`Tensor`].
     fn sum_all(&self, txn: Self::Txn) -> TCBoxTryFuture<Number>;
+
+    /// Return the maximum of this [`Tensor`] along the given `axis`.
+    fn max(self, axis: usize) -> TCResult<Self::Reduce>;
+
+    /// Return the maximum of all elements in this [`Tensor`].
+    fn max_all(&self, txn: Self::Txn) -> TCBoxTryFuture<Number>;
+
+    /// Return the mean of this [`Tensor`] along the given `axis`.
+    fn mean(self, axis: usize) -> TCResult<Self::Reduce>;
+
+    /// Return the mean of all elements in this [`Tensor`].
+    fn mean_all(&self, txn: Self::Txn) -> TCBoxTryFuture<Number>;
+
+    /// Return the population variance of this [`Tensor`] along the given `axis`.
+    fn var(self, axis: usize) -> TCResult<Self::Reduce>;
+
+    /// Return the population variance of all elements in this [`Tensor`].
+    fn var_all(&self, txn: Self::Txn) -> TCBoxTryFuture<Number>;
+
+    /// Return the population standard deviation of this [`Tensor`] along the given `axis`.
+    fn std(self, axis: usize) -> TCResult<Self::Reduce>;
+
+    /// Return the population standard deviation of all elements in this [`Tensor`].
+    fn std_all(&self, txn: Self::Txn) -> TCBoxTryFuture<Number>;
+}
+
+/// The running count, mean, and sum of squared deviations from the mean of a [`Tensor`],
+/// accumulated one block at a time so that the variance stays accurate for large values.
+#[derive(Clone, Copy, Default)]
+struct Moments {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn update(self, block: Array) -> Self {
+        let len = block.len();
+        if len == 0 {
+            return self;
+        }
+
+        let block = Array::cast_into(&block, NumberType::Float(FloatType::F64));
+        let mean = as_f64(block.sum()) / len as f64;
+        let deviation = &block - &Array::constant(Number::Float(Float::F64(mean)), len);
+        let m2 = as_f64((&deviation * &deviation).sum());
+
+        self.merge(Self {
+            count: len as u64,
+            mean,
+            m2,
+        })
+    }
+
+    /// Combine the moments of two disjoint sets of values (Chan et al.).
+    fn merge(self, other: Self) -> Self {
+        if self.count == 0 {
+            return other;
+        } else if other.count == 0 {
+            return self;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let mean = self.mean + delta * (other.count as f64 / count as f64);
+        let m2 = self.m2
+            + other.m2
+            + delta * delta * (self.count as f64 * other.count as f64 / count as f64);
+
+        Self { count, mean, m2 }
+    }
+
+    /// Treat any of the `count` elements not yet accumulated as zeros, e.g. the unfilled
+    /// elements of a sparse [`Tensor`].
+    fn with_zeros(self, count: u64) -> Self {
+        if count > self.count {
+            self.merge(Self {
+                count: count - self.count,
+                mean: 0.,
+                m2: 0.,
+            })
+        } else {
+            self
+        }
+    }
+
+    fn mean(&self, count: u64) -> f64 {
+        self.with_zeros(count).mean
+    }
+
+    fn var(&self, count: u64) -> f64 {
+        let moments = self.with_zeros(count);
+        moments.m2 / moments.count as f64
+    }
+
+    fn std(&self, count: u64) -> f64 {
+        self.var(count).sqrt()
+    }
+}
+
+/// Return the floating-point type of a statistic calculated over a [`Tensor`] of type `dtype`.
+fn float_type(dtype: NumberType) -> NumberType {
+    match dtype {
+        NumberType::Float(ft) => NumberType::Float(ft),
+        _ => NumberType::Float(FloatType::F64),
+    }
+}
+
+fn as_f64(value: Number) -> f64 {
+    match value.into_type(NumberType::Float(FloatType::F64)) {
+        Number::Float(Float::F32(f)) => f as f64,
+        Number::Float(Float::F64(f)) => f,
+        _ => unreachable!("cast to F64"),
+    }
+}
+
+fn statistic(value: f64, dtype: NumberType) -> Number {
+    Number::Float(Float::F64(value)).into_type(float_type(dtype))
+}
+
+/// Apply the floating-point function `f` to the given `value`.
+fn float_op(value: Number, f: fn(f64) -> f64) -> Number {
+    let dtype = value.class();
+    statistic(f(as_f64(value)), dtype)
+}
+
+/// Apply the ArrayFire floating-point operation `f` to the given `block`.
+fn float_op_block(block: &Array, f: fn(&af::Array<f64>) -> af::Array<f64>) -> Array {
+    let dtype = float_type(block.dtype());
+    match block.cast_into(NumberType::Float(FloatType::F64)) {
+        Array::F64(block) => Array::cast_into(&Array::from(ArrayExt::from(f(block.af()))), dtype),
+        _ => unreachable!("cast to F64"),
+    }
 }
 
 /// [`Tensor`] transforms
@@ -271,6 +1072,10 @@ pub trait TensorTransform {
     /// Cast this [`Tensor`] to the given `dtype`.
     fn cast_into(self, dtype: NumberType) -> TCResult<Self::Cast>;
 
+    /// Cast this [`Tensor`] to the given `dtype`, clamping any value out of the range of an
+    /// integer `dtype` to its minimum or maximum value instead of wrapping it.
+    fn cast_into_saturating(self, dtype: NumberType) -> TCResult<Self::Cast>;
+
     /// Insert a new dimension of size 1 at the given `axis`.
     fn expand_dims(self, axis: usize) -> TCResult<Self::Expand>;
 
@@ -291,6 +1096,10 @@ pub trait TensorUnary<D: Dir> {
     /// The return type of a unary operation
     type Unary: TensorInstance;
 
+    /// The return type of a unary operation whose result is dense, even if this [`Tensor`] is
+    /// sparse
+    type Dense: TensorInstance;
+
     /// Element-wise absolute value
     fn abs(&self) -> TCResult<Self::Unary>;
 
@@ -300,8 +1109,22 @@ pub trait TensorUnary<D: Dir> {
     /// Return `true` if any element in this [`Tensor`] is nonzero.
     async fn any(self, txn: Self::Txn) -> TCResult<bool>;
 
+    /// Element-wise exponential, `e^x`
+    fn exp(&self) -> TCResult<Self::Unary>;
+
+    /// Element-wise natural logarithm
+    fn ln(&self) -> TCResult<Self::Unary>;
+
+    /// Element-wise base-2 logarithm
+    fn log2(&self) -> TCResult<Self::Unary>;
+
+    /// Element-wise base-10 logarithm
+    fn log10(&self) -> TCResult<Self::Unary>;
+
     /// Element-wise logical not
-    fn not(&self) -> TCResult<Self::Unary>;
+    ///
+    /// Every zero element of this [`Tensor`] becomes `true`, so the result is always dense.
+    fn not(&self) -> TCResult<Self::Dense>;
 }
 
 /// The [`Class`] of [`Tensor`]
@@ -413,198 +1236,1338 @@ where
         }
     }
 
-    fn into_sparse(self) -> Self {
-        match self {
-            Self::Dense(dense) => Self::Sparse(dense.into_sparse().into_inner().accessor().into()),
-            Self::Sparse(sparse) => Self::Sparse(sparse),
+    fn into_sparse(self) -> Self {
+        match self {
+            Self::Dense(dense) => Self::Sparse(dense.into_sparse().into_inner().accessor().into()),
+            Self::Sparse(sparse) => Self::Sparse(sparse),
+        }
+    }
+}
+
+impl<FD, FS, D, T> TensorBoolean<Self> for Tensor<FD, FS, D, T>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    type Combine = Self;
+
+    fn and(self, other: Self) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(dense) => dense.and(other),
+            Self::Sparse(sparse) => sparse.and(other),
+        }
+    }
+
+    fn or(self, other: Self) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(dense) => dense.or(other),
+            Self::Sparse(sparse) => sparse.or(other),
+        }
+    }
+
+    fn xor(self, other: Self) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(dense) => dense.xor(other),
+            Self::Sparse(sparse) => sparse.xor(other),
+        }
+    }
+}
+
+impl<FD, FS, D, T> TensorCompare<Self> for Tensor<FD, FS, D, T>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    type Compare = Self;
+    type Dense = Self;
+
+    fn eq(self, other: Self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.eq(other),
+            Self::Sparse(sparse) => sparse.eq(other),
+        }
+    }
+
+    fn gt(self, other: Self) -> TCResult<Self> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        match self {
+            Self::Dense(dense) => dense.gt(other),
+            Self::Sparse(sparse) => sparse.gt(other),
+        }
+    }
+
+    fn gte(self, other: Self) -> TCResult<Self> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        match self {
+            Self::Dense(dense) => dense.gte(other),
+            Self::Sparse(sparse) => sparse.gte(other),
+        }
+    }
+
+    fn lt(self, other: Self) -> TCResult<Self> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        match self {
+            Self::Dense(dense) => dense.lt(other),
+            Self::Sparse(sparse) => sparse.lt(other),
+        }
+    }
+
+    fn lte(self, other: Self) -> TCResult<Self> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        match self {
+            Self::Dense(dense) => dense.lte(other),
+            Self::Sparse(sparse) => sparse.lte(other),
+        }
+    }
+
+    fn ne(self, other: Self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.ne(other),
+            Self::Sparse(sparse) => sparse.ne(other),
+        }
+    }
+}
+
+#[async_trait]
+impl<FD, FS, D, T> TensorDot<D, Self> for Tensor<FD, FS, D, T>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    type Txn = T;
+    type Dot = Self;
+
+    async fn matmul(self, other: Self, txn: T) -> TCResult<Self> {
+        match (self.into_dense(), other.into_dense()) {
+            (Self::Dense(left), Self::Dense(right)) => {
+                left.matmul(right, txn).map_ok(Self::from).await
+            }
+            _ => unreachable!("dense Tensor"),
+        }
+    }
+}
+
+#[async_trait]
+impl<FD, FS, D, T> TensorIO<D> for Tensor<FD, FS, D, T>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    type Txn = T;
+
+    async fn read_value(self, txn: Self::Txn, coord: Coord) -> TCResult<Number> {
+        match self {
+            Self::Dense(dense) => dense.read_value(txn, coord).await,
+            Self::Sparse(sparse) => sparse.read_value(txn, coord).await,
+        }
+    }
+
+    async fn write_value(&self, txn_id: TxnId, bounds: Bounds, value: Number) -> TCResult<()> {
+        debug!("Tensor::write_value {} {}", bounds, value);
+
+        match self {
+            Self::Dense(dense) => dense.write_value(txn_id, bounds, value).await,
+            Self::Sparse(sparse) => sparse.write_value(txn_id, bounds, value).await,
+        }
+    }
+
+    async fn write_value_at(&self, txn_id: TxnId, coord: Coord, value: Number) -> TCResult<()> {
+        match self {
+            Self::Dense(dense) => dense.write_value_at(txn_id, coord, value).await,
+            Self::Sparse(sparse) => sparse.write_value_at(txn_id, coord, value).await,
+        }
+    }
+}
+
+#[async_trait]
+impl<FD, FS, D, T> TensorDualIO<D, Self> for Tensor<FD, FS, D, T>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    type Txn = T;
+
+    async fn mask(self, txn: T, other: Self) -> TCResult<()> {
+        match self {
+            Self::Dense(this) => this.mask(txn, other).await,
+            Self::Sparse(this) => this.mask(txn, other).await,
+        }
+    }
+
+    async fn set_where(self, txn: T, mask: Self, value: Number) -> TCResult<()> {
+        match self {
+            Self::Dense(this) => this.set_where(txn, mask, value).await,
+            Self::Sparse(this) => this.set_where(txn, mask, value).await,
+        }
+    }
+
+    async fn write(self, txn: T, bounds: Bounds, value: Self) -> TCResult<()> {
+        debug!("Tensor::write {} to {}", value, bounds);
+
+        match self {
+            Self::Dense(this) => this.write(txn, bounds, value).await,
+            Self::Sparse(this) => this.write(txn, bounds, value).await,
+        }
+    }
+}
+
+impl<FD, FS, D, T> TensorMath<D, Self> for Tensor<FD, FS, D, T>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    type Combine = Self;
+
+    fn add_with(self, other: Self, non_finite: NonFinite) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(this) => this.add_with(other, non_finite),
+            Self::Sparse(this) => this.add_with(other, non_finite),
+        }
+    }
+
+    fn div_with(self, other: Self, non_finite: NonFinite) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(this) => this.div_with(other, non_finite),
+            Self::Sparse(this) => this.div_with(other, non_finite),
+        }
+    }
+
+    fn mul_with(self, other: Self, non_finite: NonFinite) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(this) => this.mul_with(other, non_finite),
+            Self::Sparse(this) => this.mul_with(other, non_finite),
+        }
+    }
+
+    fn sub_with(self, other: Self, non_finite: NonFinite) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(this) => this.sub_with(other, non_finite),
+            Self::Sparse(this) => this.sub_with(other, non_finite),
+        }
+    }
+
+    fn maximum(self, other: Self) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(this) => this.maximum(other),
+            Self::Sparse(this) => this.maximum(other),
+        }
+    }
+
+    fn minimum(self, other: Self) -> TCResult<Self::Combine> {
+        match self {
+            Self::Dense(this) => this.minimum(other),
+            Self::Sparse(this) => this.minimum(other),
+        }
+    }
+}
+
+impl<FD, FS, D, T> Tensor<FD, FS, D, T>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    D::FileClass: From<TensorType>,
+{
+    /// Compute the dot product of two 1-dimensional `Tensor`s of equal length.
+    pub async fn dot(self, other: Self, txn: T) -> TCResult<Number> {
+        if self.ndim() != 1 || self.shape() != other.shape() {
+            return Err(TCError::bad_request(
+                "dot product requires two 1-dimensional Tensors of equal length, not",
+                format!("{} and {}", self.shape(), other.shape()),
+            ));
+        }
+
+        match (self, other) {
+            (Self::Sparse(left), Self::Sparse(right)) => left.dot(right, txn).await,
+            (left, right) => {
+                let product = left.mul(right)?;
+                product.sum_all(txn).await
+            }
+        }
+    }
+
+    /// Compute the 2-dimensional cross-correlation of this `Tensor` with the given `kernel`.
+    ///
+    /// See [`DenseTensor::conv2d`].
+    pub async fn conv2d(
+        self,
+        kernel: Self,
+        stride: (u64, u64),
+        padding: (u64, u64),
+        txn: T,
+    ) -> TCResult<Self> {
+        match (self.into_dense(), kernel.into_dense()) {
+            (Self::Dense(input), Self::Dense(kernel)) => {
+                input
+                    .conv2d(kernel, stride, padding, txn)
+                    .map_ok(Self::from)
+                    .await
+            }
+            _ => unreachable!("dense Tensor"),
+        }
+    }
+
+    /// Compute the Kronecker product of this 2-dimensional `Tensor` with `other`.
+    ///
+    /// See [`DenseTensor::kron`].
+    pub async fn kron(self, other: Self, txn: T) -> TCResult<Self> {
+        match (self.into_dense(), other.into_dense()) {
+            (Self::Dense(left), Self::Dense(right)) => {
+                left.kron(right, txn).map_ok(Self::from).await
+            }
+            _ => unreachable!("dense Tensor"),
+        }
+    }
+
+    /// Return the number of nonzero elements in this `Tensor`.
+    pub async fn nnz(self, txn: T) -> TCResult<u64> {
+        match self {
+            Self::Dense(dense) => dense.into_sparse().nnz(txn).await,
+            Self::Sparse(sparse) => sparse.nnz(txn).await,
+        }
+    }
+
+    /// Return a 1-dimensional copy of this `Tensor` with the same elements in row-major order.
+    ///
+    /// A sparse `Tensor` remains sparse: each filled coordinate is mapped to its flat offset.
+    pub async fn flatten(self, txn: T) -> TCResult<Self>
+    where
+        D::FileClass: From<BTreeType>,
+    {
+        let shape = Shape::from(vec![self.size()]);
+        self.reshape(shape, txn).await
+    }
+
+    /// Remove the given `axis` of this `Tensor`, which must have size 1, or every axis of size 1
+    /// if no `axis` is specified.
+    ///
+    /// A sparse `Tensor` remains sparse: each filled coordinate is mapped to its squeezed
+    /// coordinate.
+    pub async fn squeeze(self, axis: Option<usize>, txn: T) -> TCResult<Self>
+    where
+        D::FileClass: From<BTreeType>,
+    {
+        let shape = self.shape();
+
+        let shape: Shape = if let Some(axis) = axis {
+            if axis >= shape.len() {
+                return Err(TCError::unsupported(format!(
+                    "Tensor with shape {} has no axis {}",
+                    shape, axis
+                )));
+            } else if shape[axis] != 1 {
+                return Err(TCError::unsupported(format!(
+                    "cannot squeeze axis {} of Tensor with shape {}",
+                    axis, shape
+                )));
+            }
+
+            let mut shape = shape.to_vec();
+            shape.remove(axis);
+            shape.into()
+        } else {
+            shape.iter().filter(|dim| **dim != 1).copied().collect()
+        };
+
+        if &shape == self.shape() {
+            Ok(self)
+        } else {
+            self.reshape(shape, txn).await
+        }
+    }
+
+    /// Cyclically shift the elements of this `Tensor` by `shift` along the given `axis`,
+    /// so that the element at coordinate `c` moves to `(c + shift) % shape[axis]`.
+    ///
+    /// A dense `Tensor` is copied as two slices, each with a single write.
+    /// A sparse `Tensor` remains sparse: only its filled coordinates are remapped.
+    pub async fn roll(self, shift: i64, axis: usize, txn: T) -> TCResult<Self>
+    where
+        D::FileClass: From<BTreeType>,
+    {
+        let shape = self.shape().clone();
+        if axis >= shape.len() {
+            return Err(TCError::unsupported(format!(
+                "Tensor with shape {} has no axis {}",
+                shape, axis
+            )));
+        }
+
+        let dim = shape[axis] as i64;
+        let shift = shift.rem_euclid(dim.max(1)) as u64;
+        if shift == 0 {
+            return Ok(self);
+        }
+
+        let txn_id = *txn.id();
+        let dtype = self.dtype();
+
+        let (rolled, source) = match self {
+            Self::Dense(dense) => {
+                let file = txn
+                    .context()
+                    .create_file_tmp(txn_id, TensorType::Dense)
+                    .await?;
+
+                let rolled =
+                    DenseTensor::constant(file, txn_id, shape.clone(), dtype.zero()).await?;
+                let rolled = Self::from(rolled);
+                let source = Self::from(dense);
+
+                let dim = dim as u64;
+                let bounds = |range: Range<u64>| {
+                    let mut bounds = Bounds::all(&shape);
+                    bounds[axis] = AxisBounds::In(range);
+                    bounds
+                };
+
+                // the last `shift` elements along `axis` wrap around to the front
+                let head = source.clone().slice(bounds(0..(dim - shift)))?;
+                let tail = source.slice(bounds((dim - shift)..dim))?;
+
+                rolled
+                    .clone()
+                    .write(txn.clone(), bounds(shift..dim), head)
+                    .await?;
+
+                rolled.clone().write(txn, bounds(0..shift), tail).await?;
+
+                return Ok(rolled);
+            }
+            Self::Sparse(sparse) => {
+                let dir = txn.context().create_dir_tmp(txn_id).await?;
+                let rolled = SparseTensor::create(&dir, Schema { shape, dtype }, txn_id).await?;
+                (Self::from(rolled), sparse.into_inner())
+            }
+        };
+
+        let dim = dim as u64;
+        let filled = source.filled(txn).await?;
+        filled
+            .map_ok(|(mut coord, value)| {
+                coord[axis] = (coord[axis] + shift) % dim;
+                rolled.write_value_at(txn_id, coord, value)
+            })
+            .try_buffer_unordered(num_cpus::get())
+            .try_fold((), |(), ()| future::ready(Ok(())))
+            .await?;
+
+        Ok(rolled)
+    }
+
+    /// Return a copy of this `Tensor` with the given `shape`, which must have the same size,
+    /// with the same elements in row-major order.
+    async fn reshape(self, shape: Shape, txn: T) -> TCResult<Self>
+    where
+        D::FileClass: From<BTreeType>,
+    {
+        if shape.size() != self.size() {
+            return Err(TCError::unsupported(format!(
+                "cannot reshape Tensor with shape {} into {}",
+                self.shape(),
+                shape
+            )));
+        }
+
+        let txn_id = *txn.id();
+        let dtype = self.dtype();
+
+        match self {
+            Self::Dense(dense) => {
+                let file = txn
+                    .context()
+                    .create_file_tmp(txn_id, TensorType::Dense)
+                    .await?;
+
+                let values = dense.into_inner().value_stream(txn).await?;
+                BlockListFile::from_values(file, txn_id, shape, dtype, values)
+                    .map_ok(DenseTensor::from)
+                    .map_ok(Self::from)
+                    .await
+            }
+            Self::Sparse(sparse) => {
+                let source_strides = coord_bounds(sparse.shape());
+                let strides = coord_bounds(&shape);
+
+                let dir = txn.context().create_dir_tmp(txn_id).await?;
+                let reshaped = SparseTensor::create(&dir, Schema { shape, dtype }, txn_id).await?;
+
+                let filled = sparse.into_inner().filled(txn).await?;
+                filled
+                    .map_ok(|(coord, value)| {
+                        let offset = coord
+                            .iter()
+                            .zip(&source_strides)
+                            .map(|(i, stride)| i * stride);
+                        let mut offset: u64 = offset.sum();
+
+                        let coord = strides
+                            .iter()
+                            .map(|stride| {
+                                let i = offset / stride;
+                                offset %= stride;
+                                i
+                            })
+                            .collect();
+
+                        reshaped.write_value_at(txn_id, coord, value)
+                    })
+                    .try_buffer_unordered(num_cpus::get())
+                    .try_fold((), |(), ()| future::ready(Ok(())))
+                    .await?;
+
+                Ok(Self::from(reshaped))
+            }
+        }
+    }
+
+    /// Write `value` to every coordinate of this `Tensor` where `mask` is nonzero, broadcasting
+    /// `mask` if necessary. Unlike [`TensorDualIO::mask`], which zeroes the masked elements,
+    /// this fills them with the given `value`. See [`TensorDualIO::set_where`].
+    pub async fn masked_fill(self, txn: T, mask: Self, value: Number) -> TCResult<()> {
+        TensorDualIO::set_where(self, txn, mask, value).await
+    }
+
+    /// Rescale this `Tensor` so that its L2 norm does not exceed `max_norm`.
+    ///
+    /// If the norm is already within `max_norm`, this `Tensor` is returned unchanged.
+    /// A sparse `Tensor` remains sparse, since scaling a zero value leaves it zero.
+    pub async fn clip_by_norm(self, max_norm: f64, txn: T) -> TCResult<Self> {
+        if !max_norm.is_finite() || max_norm < 0. {
+            return Err(TCError::bad_request(
+                "max norm must be a finite non-negative number, not",
+                max_norm,
+            ));
+        }
+
+        if let NumberType::Complex(_) = self.dtype() {
+            return Err(TCError::unsupported(
+                "clip_by_norm is not supported for complex Tensors",
+            ));
+        }
+
+        let squares = self.clone().mul(self.clone())?;
+        let norm = as_f64(squares.sum_all(txn.clone()).await?).sqrt();
+        if norm <= max_norm {
+            return Ok(self);
+        }
+
+        let txn_id = *txn.id();
+        let scale = statistic(max_norm / norm, self.dtype());
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        let scale = DenseTensor::constant(file, txn_id, vec![1; self.ndim()], scale).await?;
+        let scale = Self::from(scale).broadcast(self.shape().clone())?;
+        self.mul(scale)
+    }
+
+    /// Divide each lane of this `Tensor` along `axis` by its `norm`.
+    ///
+    /// A lane whose norm is zero is left unchanged, unless `strict` is `true`, in which case
+    /// a `bad_request` error is returned. An empty `Tensor` is returned as-is.
+    pub async fn normalize(
+        self,
+        axis: usize,
+        norm: NormKind,
+        strict: bool,
+        txn: T,
+    ) -> TCResult<Self> {
+        if axis >= self.ndim() {
+            return Err(TCError::bad_request(
+                format!("Tensor with shape {} has no axis", self.shape()),
+                axis,
+            ));
+        }
+
+        if let NumberType::Complex(_) = self.dtype() {
+            return Err(TCError::unsupported(
+                "normalize is not supported for complex Tensors",
+            ));
+        }
+
+        let txn_id = *txn.id();
+        let dtype = float_type(self.dtype());
+        let source = TensorTransform::cast_into(self, dtype)?;
+
+        if source.size() == 0 {
+            return Ok(source);
+        }
+
+        // reduce a vector as a single row, so that its norm is not a zero-dimensional Tensor
+        let (source, axis, is_vector) = if source.ndim() == 1 {
+            (source.expand_dims(0)?, 1, true)
+        } else {
+            (source, axis, false)
+        };
+
+        let shape = source.shape().clone();
+        let dim = shape[axis];
+
+        let mut outer = shape.to_vec();
+        outer.remove(axis);
+        let outer = Shape::from(outer);
+
+        let norms = match norm {
+            NormKind::L1 => source.abs()?.sum(axis)?,
+            NormKind::L2 => source.clone().mul(source.clone())?.sum(axis)?,
+            NormKind::Max => source.abs()?.max(axis)?,
+        };
+
+        let norms = match norms.into_dense() {
+            Self::Dense(dense) => dense.into_inner().value_stream(txn.clone()).await?,
+            Self::Sparse(_) => unreachable!("dense Tensor"),
+        };
+
+        // guard against a norm of zero, one lane at a time
+        let norms = futures::stream::iter(Bounds::all(&outer).affected())
+            .zip(norms)
+            .map(move |(lane_coord, norm_value)| {
+                let norm_value = match norm {
+                    NormKind::L2 => as_f64(norm_value?).sqrt(),
+                    NormKind::L1 | NormKind::Max => as_f64(norm_value?),
+                };
+
+                if norm_value != 0. {
+                    Ok(statistic(norm_value, dtype))
+                } else if strict {
+                    Err(TCError::bad_request(
+                        format!("cannot normalize by a {} norm of zero at", norm),
+                        Tuple::<u64>::from(lane_coord),
+                    ))
+                } else {
+                    Ok(statistic(1., dtype))
+                }
+            });
+
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        let norms = BlockListFile::from_values(file, txn_id, outer, dtype, Box::pin(norms)).await?;
+        let norms = Self::from(DenseTensor::from(norms))
+            .expand_dims(axis)?
+            .broadcast(shape.clone())?;
+
+        let normalized = source.div(norms)?;
+
+        if is_vector {
+            let bounds = vec![AxisBounds::At(0), AxisBounds::all(dim)];
+            normalized.slice(Bounds::from(bounds))
+        } else {
+            Ok(normalized)
+        }
+    }
+
+    /// Return `true` if every element of this `Tensor` is within `atol + rtol * |other|`
+    /// of the corresponding element of `other`.
+    ///
+    /// Integer and boolean `Tensor`s are always compared exactly.
+    pub async fn allclose(self, other: Self, rtol: f64, atol: f64, txn: T) -> TCResult<bool> {
+        if self.shape() != other.shape() {
+            return Ok(false);
+        }
+
+        let is_float = |dtype: NumberType| match dtype {
+            NumberType::Float(_) | NumberType::Complex(_) => true,
+            _ => false,
+        };
+
+        if !is_float(self.dtype()) && !is_float(other.dtype()) {
+            return self.eq(other)?.all(txn).await;
+        }
+
+        let txn_id = *txn.id();
+        let dtype = float_type(self.dtype());
+        let shape = self.shape().clone();
+
+        let mut constants = Vec::with_capacity(2);
+        for value in [rtol, atol].iter() {
+            let file = txn
+                .context()
+                .create_file_tmp(txn_id, TensorType::Dense)
+                .await?;
+
+            let constant =
+                DenseTensor::constant(file, txn_id, vec![1; shape.len()], statistic(*value, dtype))
+                    .await?;
+
+            constants.push(Self::from(constant).broadcast(shape.clone())?);
+        }
+
+        let atol = constants.pop().expect("atol");
+        let rtol = constants.pop().expect("rtol");
+
+        let tolerance = other.abs()?.mul(rtol)?.add(atol)?;
+        let difference = self.sub(other)?.abs()?;
+        difference.lte(tolerance)?.all(txn).await
+    }
+
+    /// Divide this `Tensor` along the given `axis` into consecutive slices of the given `sizes`,
+    /// which must sum to the dimension of `axis`.
+    ///
+    /// Each slice is a view of this `Tensor`, so a sparse `Tensor` is never densified.
+    pub fn split(self, axis: usize, sizes: Vec<u64>) -> TCResult<Vec<Self>> {
+        if axis >= self.ndim() {
+            return Err(TCError::bad_request(
+                format!("Tensor with shape {} has no axis", self.shape()),
+                axis,
+            ));
         }
-    }
-}
 
-impl<FD, FS, D, T> TensorBoolean<Self> for Tensor<FD, FS, D, T>
-where
-    FD: File<Array> + TryFrom<D::File, Error = TCError>,
-    FS: File<Node> + TryFrom<D::File, Error = TCError>,
-    D: Dir,
-    T: Transaction<D>,
-    D::FileClass: From<TensorType>,
-{
-    type Combine = Self;
+        let dim = self.shape()[axis];
+        if sizes.iter().any(|size| *size == 0) {
+            return Err(TCError::bad_request(
+                "cannot split a Tensor into an empty piece",
+                Tuple::<u64>::from(sizes),
+            ));
+        } else if sizes.iter().sum::<u64>() != dim {
+            return Err(TCError::bad_request(
+                format!(
+                    "cannot split axis {} of length {} into {} sections",
+                    axis,
+                    dim,
+                    sizes.len()
+                ),
+                Tuple::<u64>::from(sizes),
+            ));
+        }
 
-    fn and(self, other: Self) -> TCResult<Self::Combine> {
-        match self {
-            Self::Dense(dense) => dense.and(other),
-            Self::Sparse(sparse) => sparse.and(other),
+        let mut pieces = Vec::with_capacity(sizes.len());
+        let mut start = 0;
+        for size in sizes {
+            let mut bounds = Bounds::all(self.shape());
+            bounds[axis] = AxisBounds::In(start..(start + size));
+            pieces.push(self.clone().slice(bounds)?);
+            start += size;
         }
+
+        Ok(pieces)
     }
 
-    fn or(self, other: Self) -> TCResult<Self::Combine> {
-        match self {
-            Self::Dense(dense) => dense.or(other),
-            Self::Sparse(sparse) => sparse.or(other),
+    /// Divide this `Tensor` along the given `axis` into the given number of `chunks` of equal size.
+    ///
+    /// If the dimension of `axis` is not evenly divisible, the last chunk is smaller.
+    pub fn chunk(self, axis: usize, chunks: u64) -> TCResult<Vec<Self>> {
+        if axis >= self.ndim() {
+            return Err(TCError::bad_request(
+                format!("Tensor with shape {} has no axis", self.shape()),
+                axis,
+            ));
         }
-    }
 
-    fn xor(self, other: Self) -> TCResult<Self::Combine> {
-        match self {
-            Self::Dense(dense) => dense.xor(other),
-            Self::Sparse(sparse) => sparse.xor(other),
+        let dim = self.shape()[axis];
+        if chunks == 0 {
+            return Err(TCError::unsupported(format!(
+                "cannot split axis {} of length {} into {} sections",
+                axis, dim, chunks
+            )));
         }
+        let chunk_size = (dim + chunks - 1) / chunks;
+
+        let mut sizes = Vec::with_capacity(chunks as usize);
+        let mut remaining = dim;
+        while remaining > 0 {
+            let size = chunk_size.min(remaining);
+            sizes.push(size);
+            remaining -= size;
+        }
+
+        self.split(axis, sizes)
     }
 }
 
-impl<FD, FS, D, T> TensorCompare<Self> for Tensor<FD, FS, D, T>
+impl<FD, FS, D, T> Tensor<FD, FS, D, T>
 where
     FD: File<Array> + TryFrom<D::File, Error = TCError>,
     FS: File<Node> + TryFrom<D::File, Error = TCError>,
     D: Dir,
     T: Transaction<D>,
-    D::FileClass: From<TensorType>,
+    D::FileClass: From<BTreeType> + From<TensorType>,
 {
-    type Compare = Self;
-    type Dense = Self;
-
-    fn eq(self, other: Self) -> TCResult<Self> {
-        match self {
-            Self::Dense(dense) => dense.eq(other),
-            Self::Sparse(sparse) => sparse.eq(other),
+    /// One-hot encode this 1-dimensional `Tensor` of integer class indices.
+    ///
+    /// Returns a `SparseTensor` of shape `[len, depth]` with a single one in each row.
+    pub async fn one_hot(
+        self,
+        depth: u64,
+        txn: T,
+    ) -> TCResult<SparseTensor<FD, FS, D, T, SparseTable<FD, FS, D, T>>> {
+        if self.ndim() != 1 {
+            return Err(TCError::bad_request(
+                "one-hot encoding requires a 1-dimensional Tensor, not one with shape",
+                self.shape(),
+            ));
         }
-    }
 
-    fn gt(self, other: Self) -> TCResult<Self> {
-        match self {
-            Self::Dense(dense) => dense.gt(other),
-            Self::Sparse(sparse) => sparse.gt(other),
+        let dtype = self.dtype();
+        match dtype {
+            NumberType::Int(_) | NumberType::UInt(_) => {}
+            other => {
+                return Err(TCError::bad_request(
+                    "one-hot encoding requires an integer Tensor, not",
+                    other,
+                ))
+            }
         }
-    }
 
-    fn gte(self, other: Self) -> TCResult<Self> {
-        match self {
-            Self::Dense(dense) => dense.gte(other),
-            Self::Sparse(sparse) => sparse.gte(other),
+        let txn_id = *txn.id();
+        let schema = Schema {
+            shape: vec![self.shape()[0], depth].into(),
+            dtype,
+        };
+
+        let dir = txn.context().create_dir_tmp(txn_id).await?;
+        let encoded = SparseTensor::create(&dir, schema, txn_id).await?;
+
+        let indices = match self.into_dense() {
+            Self::Dense(dense) => dense.into_inner().value_stream(txn).await?,
+            Self::Sparse(_) => unreachable!("dense Tensor"),
+        };
+
+        let mut indices = indices.enumerate();
+        while let Some((i, index)) = indices.next().await {
+            let index = u64::try_cast_from(Value::Number(index?), |v| {
+                TCError::bad_request("invalid class index for one-hot encoding", v)
+            })?;
+
+            if index >= depth {
+                return Err(TCError::bad_request(
+                    format!("class index must be less than {}, not", depth),
+                    index,
+                ));
+            }
+
+            encoded
+                .write_value_at(txn_id, vec![i as u64, index], dtype.one())
+                .await?;
         }
+
+        Ok(encoded)
     }
 
-    fn lt(self, other: Self) -> TCResult<Self> {
-        match self {
-            Self::Dense(dense) => dense.lt(other),
-            Self::Sparse(sparse) => sparse.lt(other),
+    /// Select an element of this `Tensor` along `axis` for each element of `indices`, following
+    /// the semantics of NumPy's `take_along_axis`.
+    ///
+    /// `indices` must be an integer `Tensor` with as many dimensions as this `Tensor`, which is
+    /// broadcastable to this `Tensor`'s shape in every dimension except `axis`. A negative index
+    /// counts back from the end of `axis`. A sparse `Tensor` is densified.
+    pub async fn take_along_axis(self, axis: usize, indices: Self, txn: T) -> TCResult<Self> {
+        if axis >= self.ndim() {
+            return Err(TCError::bad_request(
+                format!("Tensor with shape {} has no axis", self.shape()),
+                axis,
+            ));
+        } else if indices.ndim() != self.ndim() {
+            return Err(TCError::bad_request(
+                format!(
+                    "take_along_axis requires indices with {} dimensions, not",
+                    self.ndim()
+                ),
+                indices.shape(),
+            ));
         }
-    }
 
-    fn lte(self, other: Self) -> TCResult<Self> {
-        match self {
-            Self::Dense(dense) => dense.lte(other),
-            Self::Sparse(sparse) => sparse.lte(other),
+        match indices.dtype() {
+            NumberType::Int(_) | NumberType::UInt(_) => {}
+            other => {
+                return Err(TCError::bad_request(
+                    "take_along_axis requires an integer index Tensor, not",
+                    other,
+                ))
+            }
         }
-    }
 
-    fn ne(self, other: Self) -> TCResult<Self> {
-        match self {
-            Self::Dense(dense) => dense.ne(other),
-            Self::Sparse(sparse) => sparse.ne(other),
+        let mut shape = Vec::with_capacity(self.ndim());
+        for (x, (dim, index_dim)) in self.shape().iter().zip(indices.shape().iter()).enumerate() {
+            if x == axis || dim == index_dim || *dim == 1 {
+                shape.push(*index_dim);
+            } else if *index_dim == 1 {
+                shape.push(*dim);
+            } else {
+                return Err(TCError::bad_request(
+                    format!(
+                        "cannot broadcast indices with shape {} into",
+                        indices.shape()
+                    ),
+                    self.shape(),
+                ));
+            }
         }
-    }
-}
 
-#[async_trait]
-impl<FD, FS, D, T> TensorIO<D> for Tensor<FD, FS, D, T>
-where
-    FD: File<Array> + TryFrom<D::File, Error = TCError>,
-    FS: File<Node> + TryFrom<D::File, Error = TCError>,
-    D: Dir,
-    T: Transaction<D>,
-    D::FileClass: From<TensorType>,
-{
-    type Txn = T;
+        let shape = Shape::from(shape);
+        let indices = if indices.shape() == &shape {
+            indices
+        } else {
+            indices.broadcast(shape.clone())?
+        };
+
+        let mut source_shape = shape.to_vec();
+        source_shape[axis] = self.shape()[axis];
+        let source_shape = Shape::from(source_shape);
+        let source = if self.shape() == &source_shape {
+            self
+        } else {
+            self.broadcast(source_shape)?
+        };
+
+        let dim = source.shape()[axis];
+        let dtype = source.dtype();
+        let txn_id = *txn.id();
+
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        if shape.size() == 0 {
+            return DenseTensor::constant(file, txn_id, shape, dtype.zero())
+                .map_ok(Self::from)
+                .await;
+        } else if dim == 0 {
+            return Err(TCError::bad_request("cannot take from an empty axis", axis));
+        }
 
-    async fn read_value(self, txn: Self::Txn, coord: Coord) -> TCResult<Number> {
-        match self {
-            Self::Dense(dense) => dense.read_value(txn, coord).await,
-            Self::Sparse(sparse) => sparse.read_value(txn, coord).await,
+        // move `axis` to the end, so that each lane is a contiguous run of the value stream
+        let ndim = shape.len();
+        let (source, indices) = if axis == ndim - 1 {
+            (source, indices)
+        } else {
+            let mut permutation: Vec<usize> = (0..ndim).filter(|x| *x != axis).collect();
+            permutation.push(axis);
+
+            let source = source.transpose(Some(permutation.clone()))?;
+            let indices = indices.transpose(Some(permutation))?;
+            (source, indices)
+        };
+
+        let index_dim = shape[axis];
+        let lane_shape = indices.shape().clone();
+
+        let lanes = |tensor: Self, len: u64, txn: T| async move {
+            let values = match tensor.into_dense() {
+                Self::Dense(dense) => dense.into_inner().value_stream(txn).await?,
+                Self::Sparse(_) => unreachable!("dense Tensor"),
+            };
+
+            let lanes = values
+                .chunks(len as usize)
+                .map(|lane| lane.into_iter().collect::<TCResult<Vec<Number>>>());
+
+            TCResult::Ok(lanes)
+        };
+
+        let source = lanes(source, dim, txn.clone()).await?;
+        let indices = lanes(indices, index_dim, txn).await?;
+
+        let taken = source
+            .zip(indices)
+            .map(move |(lane, indices)| {
+                let lane = lane?;
+                indices?
+                    .into_iter()
+                    .map(|index| take(&lane, axis, index))
+                    .collect::<TCResult<Vec<Number>>>()
+            })
+            .map_ok(|taken| futures::stream::iter(taken.into_iter().map(TCResult::Ok)))
+            .try_flatten();
+
+        let taken = BlockListFile::from_values(file, txn_id, lane_shape, dtype, taken)
+            .map_ok(DenseTensor::from)
+            .map_ok(Self::from)
+            .await?;
+
+        if axis == ndim - 1 {
+            Ok(taken)
+        } else {
+            // move the last axis back to `axis`
+            let mut permutation: Vec<usize> = (0..(ndim - 1)).collect();
+            permutation.insert(axis, ndim - 1);
+            taken.transpose(Some(permutation))
         }
     }
 
-    async fn write_value(&self, txn_id: TxnId, bounds: Bounds, value: Number) -> TCResult<()> {
-        debug!("Tensor::write_value {} {}", bounds, value);
+    /// Repeat each element of this `Tensor` `repeats` times consecutively along the given `axis`,
+    /// following the semantics of NumPy's `repeat`.
+    ///
+    /// The element at `c[axis]` is copied to `c[axis] * repeats .. (c[axis] + 1) * repeats`, so
+    /// the dimension of `axis` is multiplied by `repeats`. A sparse `Tensor` remains sparse.
+    pub async fn repeat_interleave(self, axis: usize, repeats: u64, txn: T) -> TCResult<Self> {
+        if axis >= self.ndim() {
+            return Err(TCError::bad_request(
+                format!("Tensor with shape {} has no axis", self.shape()),
+                axis,
+            ));
+        } else if repeats == 0 {
+            return Err(TCError::bad_request(
+                "repeat_interleave requires a positive number of repeats, not",
+                repeats,
+            ));
+        }
+
+        let dtype = self.dtype();
+        let mut shape = self.shape().to_vec();
+        shape[axis] *= repeats;
+        let schema = Schema {
+            shape: shape.into(),
+            dtype,
+        };
+
+        let txn_id = *txn.id();
 
         match self {
-            Self::Dense(dense) => dense.write_value(txn_id, bounds, value).await,
-            Self::Sparse(sparse) => sparse.write_value(txn_id, bounds, value).await,
+            Self::Dense(dense) => {
+                // in row-major order, each run of elements past `axis` is repeated as a whole
+                let inner = schema.shape[(axis + 1)..].iter().product::<u64>().max(1);
+
+                let file = txn
+                    .context()
+                    .create_file_tmp(txn_id, TensorType::Dense)
+                    .await?;
+
+                let values = dense.into_inner().value_stream(txn).await?;
+                let values = values
+                    .chunks(inner as usize)
+                    .map(|run| run.into_iter().collect::<TCResult<Vec<Number>>>())
+                    .map_ok(move |run| {
+                        let repeated = std::iter::repeat(run).take(repeats as usize).flatten();
+                        futures::stream::iter(repeated.map(TCResult::Ok))
+                    })
+                    .try_flatten();
+
+                BlockListFile::from_values(file, txn_id, schema.shape, dtype, values)
+                    .map_ok(DenseTensor::from)
+                    .map_ok(Self::from)
+                    .await
+            }
+            Self::Sparse(sparse) => {
+                let dir = txn.context().create_dir_tmp(txn_id).await?;
+                let output = SparseTensor::create(&dir, schema, txn_id).await?;
+
+                let filled = sparse.into_inner().filled(txn).await?;
+                filled
+                    .map_ok(|(coord, value)| {
+                        let writes = (0..repeats).map(move |i| {
+                            let mut coord = coord.clone();
+                            coord[axis] = (coord[axis] * repeats) + i;
+                            TCResult::Ok((coord, value))
+                        });
+
+                        futures::stream::iter(writes)
+                    })
+                    .try_flatten()
+                    .map_ok(|(coord, value)| output.write_value_at(txn_id, coord, value))
+                    .try_buffer_unordered(num_cpus::get())
+                    .try_fold((), |(), ()| future::ready(Ok(())))
+                    .await?;
+
+                Ok(Self::from(output))
+            }
         }
     }
 
-    async fn write_value_at(&self, txn_id: TxnId, coord: Coord, value: Number) -> TCResult<()> {
-        match self {
-            Self::Dense(dense) => dense.write_value_at(txn_id, coord, value).await,
-            Self::Sparse(sparse) => sparse.write_value_at(txn_id, coord, value).await,
+    /// Return the `k` greatest (or, if `largest` is `false`, least) elements of each lane of this
+    /// `Tensor` along `axis`, in order, together with a `U64` `Tensor` of their indices.
+    ///
+    /// Both results have this `Tensor`'s shape, except that the dimension of `axis` is `k`.
+    /// Ties are broken in favor of the lower index, and `NaN` is ordered after every other value.
+    /// A sparse `Tensor` is densified.
+    pub async fn topk(self, axis: usize, k: u64, largest: bool, txn: T) -> TCResult<(Self, Self)> {
+        if axis >= self.ndim() {
+            return Err(TCError::bad_request(
+                format!("Tensor with shape {} has no axis", self.shape()),
+                axis,
+            ));
         }
-    }
-}
 
-#[async_trait]
-impl<FD, FS, D, T> TensorDualIO<D, Self> for Tensor<FD, FS, D, T>
-where
-    FD: File<Array> + TryFrom<D::File, Error = TCError>,
-    FS: File<Node> + TryFrom<D::File, Error = TCError>,
-    D: Dir,
-    T: Transaction<D>,
-    D::FileClass: From<TensorType>,
-{
-    type Txn = T;
+        ordered(self.dtype())?;
+
+        let dim = self.shape()[axis];
+        if k == 0 || k > dim {
+            return Err(TCError::bad_request(
+                format!(
+                    "topk along an axis of dimension {} requires 0 < k <= {}, not",
+                    dim, dim
+                ),
+                k,
+            ));
+        }
 
-    async fn mask(self, txn: T, other: Self) -> TCResult<()> {
-        match self {
-            Self::Dense(this) => this.mask(txn, other).await,
-            Self::Sparse(this) => this.mask(txn, other).await,
+        let mut outer = self.shape().to_vec();
+        outer.remove(axis);
+        let outer = Shape::from(outer);
+
+        // move `axis` to the end, so that each lane is a contiguous run of the value stream
+        let source = if axis == self.ndim() - 1 {
+            self
+        } else {
+            let mut permutation: Vec<usize> = (0..self.ndim()).filter(|x| *x != axis).collect();
+            permutation.push(axis);
+            self.transpose(Some(permutation))?
+        };
+
+        let ndim = source.ndim();
+        let source = source.into_dense();
+
+        let txn_id = *txn.id();
+        let dtype = source.dtype();
+        let index_type = NumberType::UInt(UIntType::U64);
+
+        // the top k elements of each lane, still with `axis` at the end
+        let mut lane_shape = outer.to_vec();
+        lane_shape.push(k);
+        let lane_shape = Shape::from(lane_shape);
+
+        // read the source once for the values and again for the indices,
+        // so that neither output is held in memory
+        let lanes = |source: Self, txn: T| async move {
+            let values = match source {
+                Self::Dense(dense) => dense.into_inner().value_stream(txn).await?,
+                Self::Sparse(_) => unreachable!("dense Tensor"),
+            };
+
+            let lanes = values
+                .chunks(dim as usize)
+                .map(|lane| lane.into_iter().collect::<TCResult<Vec<Number>>>())
+                .map_ok(move |lane| top_k(lane, k as usize, largest));
+
+            TCResult::Ok(lanes)
+        };
+
+        let values = lanes(source.clone(), txn.clone())
+            .await?
+            .map_ok(|top| {
+                let values = top.into_iter().map(|ranked| ranked.value);
+                futures::stream::iter(values.map(TCResult::Ok))
+            })
+            .try_flatten();
+
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        let values =
+            BlockListFile::from_values(file, txn_id, lane_shape.clone(), dtype, values).await?;
+
+        let indices = lanes(source, txn.clone())
+            .await?
+            .map_ok(|top| {
+                let indices = top.into_iter().map(|ranked| Number::from(ranked.index));
+                futures::stream::iter(indices.map(TCResult::Ok))
+            })
+            .try_flatten();
+
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        let indices =
+            BlockListFile::from_values(file, txn_id, lane_shape, index_type, indices).await?;
+
+        let values = Self::from(DenseTensor::from(values));
+        let indices = Self::from(DenseTensor::from(indices));
+
+        if axis == ndim - 1 {
+            Ok((values, indices))
+        } else {
+            // move the last axis back to `axis`
+            let mut permutation: Vec<usize> = (0..(ndim - 1)).collect();
+            permutation.insert(axis, ndim - 1);
+
+            let values = values.transpose(Some(permutation.clone()))?;
+            let indices = indices.transpose(Some(permutation))?;
+            Ok((values, indices))
         }
     }
 
-    async fn write(self, txn: T, bounds: Bounds, value: Self) -> TCResult<()> {
-        debug!("Tensor::write {} to {}", value, bounds);
+    /// Compute the discrete Fourier transform of this 1-dimensional `Tensor`.
+    ///
+    /// The result is a dense `Tensor` of 64-bit complex numbers with the same shape.
+    /// A real-valued input is treated as a complex signal with no imaginary part.
+    pub async fn fft(self, txn: T) -> TCResult<Self> {
+        self.fourier(false, txn).await
+    }
 
-        match self {
-            Self::Dense(this) => this.write(txn, bounds, value).await,
-            Self::Sparse(this) => this.write(txn, bounds, value).await,
+    /// Compute the inverse discrete Fourier transform of this 1-dimensional `Tensor`.
+    ///
+    /// The result is a dense `Tensor` of 64-bit complex numbers with the same shape.
+    pub async fn ifft(self, txn: T) -> TCResult<Self> {
+        self.fourier(true, txn).await
+    }
+
+    async fn fourier(self, inverse: bool, txn: T) -> TCResult<Self> {
+        if self.ndim() != 1 {
+            return Err(TCError::bad_request(
+                "the Fourier transform requires a 1-dimensional Tensor, not one with shape",
+                self.shape(),
+            ));
         }
+
+        let shape = self.shape().clone();
+        let txn_id = *txn.id();
+
+        let signal = match self.into_dense() {
+            Self::Dense(dense) => dense.into_inner().value_stream(txn.clone()).await?,
+            Self::Sparse(_) => unreachable!("dense Tensor"),
+        };
+
+        let signal = signal.map_ok(as_complex).try_collect::<Vec<_>>().await?;
+        let spectrum = dft(signal, inverse)
+            .into_iter()
+            .map(|(re, im)| Complex::cast_from((Number::from(re), Number::from(im))))
+            .map(Number::Complex)
+            .map(TCResult::Ok);
+
+        let file = txn
+            .context()
+            .create_file_tmp(txn_id, TensorType::Dense)
+            .await?;
+
+        let dtype = NumberType::Complex(ComplexType::C64);
+        BlockListFile::from_values(file, txn_id, shape, dtype, futures::stream::iter(spectrum))
+            .map_ok(DenseTensor::from)
+            .map_ok(Self::from)
+            .await
     }
-}
 
-impl<FD, FS, D, T> TensorMath<D, Self> for Tensor<FD, FS, D, T>
-where
-    FD: File<Array> + TryFrom<D::File, Error = TCError>,
-    FS: File<Node> + TryFrom<D::File, Error = TCError>,
-    D: Dir,
-    T: Transaction<D>,
-    D::FileClass: From<TensorType>,
-{
-    type Combine = Self;
+    /// Replace each `NaN` in this `Tensor` with `nan`, each positive infinity with `posinf`, and
+    /// each negative infinity with `neginf`.
+    ///
+    /// A `Tensor` with an integer or boolean data type has no non-finite values, so it's returned
+    /// unchanged. A sparse `Tensor` remains sparse, since its implicit zeros are always finite.
+    pub async fn nan_to_num(
+        self,
+        nan: Number,
+        posinf: Number,
+        neginf: Number,
+        txn: T,
+    ) -> TCResult<Self> {
+        let dtype = self.dtype();
+        match dtype {
+            NumberType::Complex(_) | NumberType::Float(_) => {}
+            _ => return Ok(self),
+        }
+
+        let (nan, posinf, neginf) = (as_f64(nan), as_f64(posinf), as_f64(neginf));
+        let txn_id = *txn.id();
+        let schema = Schema {
+            shape: self.shape().clone(),
+            dtype,
+        };
 
-    fn add(self, other: Self) -> TCResult<Self::Combine> {
         match self {
-            Self::Dense(this) => this.add(other),
-            Self::Sparse(this) => this.add(other),
+            Self::Dense(dense) => {
+                let file = txn
+                    .context()
+                    .create_file_tmp(txn_id, TensorType::Dense)
+                    .await?;
+
+                let blocks = dense.into_inner().block_stream(txn).await?;
+                let blocks = blocks.map_ok(move |block| {
+                    let values = block
+                        .to_vec()
+                        .into_iter()
+                        .map(|value| nan_to_num(value, nan, posinf, neginf))
+                        .collect::<Vec<Number>>();
+
+                    Array::cast_into(&Array::from(values), dtype)
+                });
+
+                BlockListFile::from_blocks(file, txn_id, Some(schema.shape), dtype, blocks)
+                    .map_ok(DenseTensor::from)
+                    .map_ok(Self::from)
+                    .await
+            }
+            Self::Sparse(sparse) => {
+                let dir = txn.context().create_dir_tmp(txn_id).await?;
+                let output = SparseTensor::create(&dir, schema, txn_id).await?;
+
+                let filled = sparse.into_inner().filled(txn).await?;
+                filled
+                    .map_ok(|(coord, value)| {
+                        let value = nan_to_num(value, nan, posinf, neginf);
+                        output.write_value_at(txn_id, coord, value)
+                    })
+                    .try_buffer_unordered(num_cpus::get())
+                    .try_fold((), |(), ()| future::ready(Ok(())))
+                    .await?;
+
+                Ok(Self::from(output))
+            }
         }
     }
 
-    fn div(self, other: Self) -> TCResult<Self::Combine> {
-        match self {
-            Self::Dense(this) => this.div(other),
-            Self::Sparse(this) => this.div(other),
-        }
+    /// Add `other` to this `Tensor`, or return a `bad_request` error if an integer sum overflows.
+    ///
+    /// Both `Tensor`s must have the same shape. A sparse operand is combined as if it were dense.
+    pub fn add_checked(self, other: Self, non_finite: NonFinite) -> TCResult<Self> {
+        let (left, right) = self.into_dense_pair(other);
+        let sum = left.add_with(right, non_finite)?;
+        Ok(Self::from(sum.checked(checked_add)))
     }
 
-    fn mul(self, other: Self) -> TCResult<Self::Combine> {
-        match self {
-            Self::Dense(this) => this.mul(other),
-            Self::Sparse(this) => this.mul(other),
-        }
+    /// Multiply this `Tensor` by `other`, or return a `bad_request` error if an integer product
+    /// overflows.
+    ///
+    /// Both `Tensor`s must have the same shape. A sparse operand is combined as if it were dense.
+    pub fn mul_checked(self, other: Self, non_finite: NonFinite) -> TCResult<Self> {
+        let (left, right) = self.into_dense_pair(other);
+        let product = left.mul_with(right, non_finite)?;
+        Ok(Self::from(product.checked(checked_mul)))
     }
 
-    fn sub(self, other: Self) -> TCResult<Self::Combine> {
-        match self {
-            Self::Dense(this) => this.sub(other),
-            Self::Sparse(this) => this.sub(other),
+    /// Subtract `other` from this `Tensor`, or return a `bad_request` error if an integer
+    /// difference overflows.
+    ///
+    /// Both `Tensor`s must have the same shape. A sparse operand is combined as if it were dense.
+    pub fn sub_checked(self, other: Self, non_finite: NonFinite) -> TCResult<Self> {
+        let (left, right) = self.into_dense_pair(other);
+        let difference = left.sub_with(right, non_finite)?;
+        Ok(Self::from(difference.checked(checked_sub)))
+    }
+
+    fn into_dense_pair(
+        self,
+        other: Self,
+    ) -> (
+        DenseTensor<FD, FS, D, T, DenseAccessor<FD, FS, D, T>>,
+        DenseTensor<FD, FS, D, T, DenseAccessor<FD, FS, D, T>>,
+    ) {
+        match (self.into_dense(), other.into_dense()) {
+            (Self::Dense(left), Self::Dense(right)) => (left, right),
+            _ => unreachable!("dense Tensor"),
         }
     }
 }
@@ -647,6 +2610,62 @@ where
             Self::Sparse(sparse) => sparse.sum_all(txn),
         }
     }
+
+    fn max(self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.max(axis).map(Self::from),
+            Self::Sparse(sparse) => sparse.max(axis).map(Self::from),
+        }
+    }
+
+    fn max_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.max_all(txn),
+            Self::Sparse(sparse) => sparse.max_all(txn),
+        }
+    }
+
+    fn mean(self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.mean(axis).map(Self::from),
+            Self::Sparse(sparse) => sparse.mean(axis).map(Self::from),
+        }
+    }
+
+    fn mean_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.mean_all(txn),
+            Self::Sparse(sparse) => sparse.mean_all(txn),
+        }
+    }
+
+    fn var(self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.var(axis).map(Self::from),
+            Self::Sparse(sparse) => sparse.var(axis).map(Self::from),
+        }
+    }
+
+    fn var_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.var_all(txn),
+            Self::Sparse(sparse) => sparse.var_all(txn),
+        }
+    }
+
+    fn std(self, axis: usize) -> TCResult<Self::Reduce> {
+        match self {
+            Self::Dense(dense) => dense.std(axis).map(Self::from),
+            Self::Sparse(sparse) => sparse.std(axis).map(Self::from),
+        }
+    }
+
+    fn std_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        match self {
+            Self::Dense(dense) => dense.std_all(txn),
+            Self::Sparse(sparse) => sparse.std_all(txn),
+        }
+    }
 }
 
 impl<FD, FS, D, T> TensorTransform for Tensor<FD, FS, D, T>
@@ -685,6 +2704,17 @@ where
         }
     }
 
+    fn cast_into_saturating(self, dtype: NumberType) -> TCResult<Self> {
+        if dtype == self.dtype() {
+            return Ok(self);
+        }
+
+        match self {
+            Self::Dense(dense) => dense.cast_into_saturating(dtype).map(Self::from),
+            Self::Sparse(sparse) => sparse.cast_into_saturating(dtype).map(Self::from),
+        }
+    }
+
     fn expand_dims(self, axis: usize) -> TCResult<Self> {
         match self {
             Self::Dense(dense) => dense.expand_dims(axis).map(Self::from),
@@ -726,6 +2756,7 @@ where
 {
     type Txn = T;
     type Unary = Self;
+    type Dense = Self;
 
     fn abs(&self) -> TCResult<Self> {
         match self {
@@ -748,6 +2779,34 @@ where
         }
     }
 
+    fn exp(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.exp().map(Self::from),
+            Self::Sparse(sparse) => sparse.exp().map(Self::from),
+        }
+    }
+
+    fn ln(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.ln().map(Self::from),
+            Self::Sparse(sparse) => sparse.ln().map(Self::from),
+        }
+    }
+
+    fn log2(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.log2().map(Self::from),
+            Self::Sparse(sparse) => sparse.log2().map(Self::from),
+        }
+    }
+
+    fn log10(&self) -> TCResult<Self> {
+        match self {
+            Self::Dense(dense) => dense.log10().map(Self::from),
+            Self::Sparse(sparse) => sparse.log10().map(Self::from),
+        }
+    }
+
     fn not(&self) -> TCResult<Self> {
         match self {
             Self::Dense(dense) => dense.not().map(Self::from),
@@ -984,3 +3043,143 @@ fn coord_bounds(shape: &[u64]) -> Vec<u64> {
         .map(|axis| shape[axis + 1..].iter().product())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use tc_value::IntType;
+
+    use super::*;
+
+    #[test]
+    fn test_saturating_cast() {
+        let u8_type = NumberType::UInt(UIntType::U8);
+        assert!(saturating_cast(Number::from(300.), u8_type) == Number::UInt(UInt::U8(255)));
+        assert!(saturating_cast(Number::from(-3.), u8_type) == Number::UInt(UInt::U8(0)));
+        assert!(saturating_cast(Number::UInt(UInt::U64(7)), u8_type) == Number::UInt(UInt::U8(7)));
+
+        let i16_type = NumberType::Int(IntType::I16);
+        let big = Number::UInt(UInt::U64(u64::MAX));
+        assert!(saturating_cast(big, i16_type) == Number::Int(Int::I16(i16::MAX)));
+        let small = Number::Int(Int::I64(i64::MIN));
+        assert!(saturating_cast(small, i16_type) == Number::Int(Int::I16(i16::MIN)));
+        assert!(saturating_cast(Number::from(f64::NAN), i16_type) == Number::Int(Int::I16(0)));
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let max = Number::UInt(UInt::U64(u64::MAX));
+        let one = Number::UInt(UInt::U64(1));
+        assert!(checked_add(max, one).is_none());
+        assert!(checked_mul(max, one) == Some(max));
+
+        let small = Number::UInt(UInt::U8(200));
+        assert!(checked_add(small, small).is_none());
+
+        let negative = Number::Int(Int::I64(i64::MIN));
+        assert!(checked_mul(negative, Number::Int(Int::I64(-1))).is_none());
+
+        let zero = Number::UInt(UInt::U64(0));
+        assert!(checked_sub(zero, one).is_none());
+        assert!(checked_sub(negative, Number::Int(Int::I64(1))).is_none());
+        assert!(checked_sub(one, one) == Some(zero));
+    }
+
+    #[test]
+    fn test_dft() {
+        for n in [8usize, 12] {
+            let freq = 3;
+            let signal: Vec<(f64, f64)> = (0..n)
+                .map(|t| {
+                    let theta = 2. * std::f64::consts::PI * (freq * t) as f64 / n as f64;
+                    (theta.cos(), 0.)
+                })
+                .collect();
+
+            let spectrum = dft(signal.clone(), false);
+            let magnitude = |(re, im): (f64, f64)| (re * re + im * im).sqrt();
+            let dominant = (0..=(n / 2))
+                .max_by(|l, r| {
+                    magnitude(spectrum[*l])
+                        .partial_cmp(&magnitude(spectrum[*r]))
+                        .unwrap()
+                })
+                .unwrap();
+
+            assert_eq!(dominant, freq);
+            assert!((magnitude(spectrum[freq]) - n as f64 / 2.).abs() < 1e-9);
+
+            let round_trip = dft(spectrum, true);
+            for ((re, im), (expected_re, expected_im)) in round_trip.into_iter().zip(signal) {
+                assert!((re - expected_re).abs() < 1e-9);
+                assert!((im - expected_im).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_complex_arithmetic() {
+        let c = |re: f64, im: f64| {
+            Number::Complex(Complex::cast_from((Number::from(re), Number::from(im))))
+        };
+
+        assert!(c(1., 2.) + c(3., -1.) == c(4., 1.));
+        assert!(c(1., 2.) - c(3., -1.) == c(-2., 3.));
+        assert!(c(1., 2.) * c(3., -1.) == c(5., 5.));
+        assert!(c(5., 5.) / c(3., -1.) == c(1., 2.));
+        assert_eq!(as_f64(c(3., 4.).abs()), 5.);
+
+        assert!(ordered(c(0., 0.).class()).is_err());
+        assert!(ordered(NumberType::Float(FloatType::F64)).is_ok());
+    }
+
+    #[test]
+    fn test_top_k_nan() {
+        let lane: Vec<Number> = vec![1., f64::NAN, 3., 2.]
+            .into_iter()
+            .map(Number::from)
+            .collect();
+
+        let top = |largest| -> Vec<u64> {
+            top_k(lane.clone(), 2, largest)
+                .into_iter()
+                .map(|ranked| ranked.index)
+                .collect()
+        };
+
+        assert_eq!(top(true), vec![1, 2]);
+        assert_eq!(top(false), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_moments_large_offset() {
+        let block = |values: &[f64]| {
+            let values: Vec<Number> = values.iter().map(|x| Number::from(1e9 + x)).collect();
+            Array::from(values)
+        };
+
+        let moments = Moments::default()
+            .update(block(&[4., 7.]))
+            .update(block(&[13., 16.]));
+
+        assert_eq!(moments.mean(4), 1e9 + 10.);
+        assert_eq!(moments.var(4), 22.5);
+
+        let sparse = Moments::default().update(Array::from(vec![Number::from(2.)]));
+        assert_eq!(sparse.mean(4), 0.5);
+        assert_eq!(sparse.var(4), 0.75);
+    }
+
+    #[test]
+    fn test_norm_kind() {
+        let lane = [3., -4.];
+        assert_eq!(NormKind::L1.of(&lane), 7.);
+        assert_eq!(NormKind::L2.of(&lane), 5.);
+        assert_eq!(NormKind::Max.of(&lane), 4.);
+        assert_eq!(NormKind::L2.of(&[0., 0.]), 0.);
+
+        assert!(NormKind::opt_cast_from(Value::None) == Some(NormKind::L2));
+        assert!(
+            NormKind::opt_cast_from(Value::from(Id::from(label("max")))) == Some(NormKind::Max)
+        );
+    }
+}
@@ -18,13 +18,13 @@ use crate::dense::{DenseAccess, DenseAccessor, DenseTensor, PER_BLOCK};
 use crate::stream::{sorted_coords, sorted_values, Read, ReadValueAt};
 use crate::transform;
 use crate::{
-    coord_bounds, AxisBounds, Bounds, Coord, Phantom, Shape, TensorAccess, TensorType, TensorUnary,
-    ERR_NONBIJECTIVE_WRITE,
+    saturating_cast, AxisBounds, Bounds, Coord, NonFinite, Phantom, Shape, TensorAccess,
+    TensorType, TensorUnary, ERR_NONBIJECTIVE_WRITE,
 };
 
-use super::combine::{coord_to_offset, SparseCombine};
+use super::combine::sorted_merge;
 use super::table::{SparseTable, SparseTableSlice};
-use super::{SparseRow, SparseStream, SparseTensor};
+use super::{SparseStream, SparseTensor};
 
 /// Access methods for [`SparseTensor`] data
 #[async_trait]
@@ -40,7 +40,12 @@ pub trait SparseAccess<FD: File<Array>, FS: File<Node>, D: Dir, T: Transaction<D
     /// Return this accessor as a [`SparseAccessor`].
     fn accessor(self) -> SparseAccessor<FD, FS, D, T>;
 
-    /// Return this [`SparseTensor`]'s contents as an ordered stream of ([`Coord`], [`Number`]) pairs.
+    /// Return this [`SparseTensor`]'s contents as a stream of ([`Coord`], [`Number`]) pairs.
+    ///
+    /// The [`Coord`]s yielded must be strictly ascending in row-major order, i.e. unique and
+    /// sorted lexicographically, since operations like [`super::sorted_merge`] and `all` rely on
+    /// this order to align the filled values of a tensor with those of another, or with its
+    /// [`Bounds`].
     async fn filled<'a>(self, txn: T) -> TCResult<SparseStream<'a>>;
 
     /// Return an ordered stream of unique [`Coord`]s on the given axes with nonzero values.
@@ -48,6 +53,18 @@ pub trait SparseAccess<FD: File<Array>, FS: File<Node>, D: Dir, T: Transaction<D
     /// TODO: add `sort` parameter to make sorting results optional.
     async fn filled_at<'a>(self, txn: T, axes: Vec<usize>) -> TCResult<TCBoxTryStream<'a, Coords>>;
 
+    /// Return an ordered stream of the ([`Coord`], [`Number`]) pairs within the given [`Bounds`].
+    ///
+    /// Unlike `slice(bounds).filled(txn)`, the [`Coord`]s yielded are not rebased to the slice.
+    /// Accessors which read directly from a table should override this method to scan only the
+    /// range of the table which falls within `bounds`.
+    async fn filled_in<'a>(self, txn: T, bounds: Bounds) -> TCResult<SparseStream<'a>> {
+        let rebase = transform::Slice::new(self.shape().clone(), bounds.clone())?;
+        let filled = self.slice(bounds)?.filled(txn).await?;
+        let filled = filled.map_ok(move |(coord, value)| (rebase.invert_coord(&coord), value));
+        Ok(Box::pin(filled))
+    }
+
     /// Return the number of nonzero values in this [`SparseTensor`].
     async fn filled_count(self, txn: T) -> TCResult<u64>;
 
@@ -64,6 +81,18 @@ pub trait SparseAccess<FD: File<Array>, FS: File<Node>, D: Dir, T: Transaction<D
 
     /// Write the given `value` at the given `coord` of this [`SparseTensor`].
     async fn write_value(&self, txn_id: TxnId, coord: Coord, value: Number) -> TCResult<()>;
+
+    /// Write each of the given `values` to this [`SparseTensor`].
+    ///
+    /// By default each value is written individually; an accessor backed by a table
+    /// should group the writes to amortize the cost of each one.
+    async fn write_values(&self, txn_id: TxnId, values: Vec<(Coord, Number)>) -> TCResult<()> {
+        stream::iter(values)
+            .map(|(coord, value)| self.write_value(txn_id, coord, value))
+            .buffer_unordered(num_cpus::get())
+            .try_fold((), |(), ()| future::ready(Ok(())))
+            .await
+    }
 }
 
 /// A generic [`SparseAccess`] type
@@ -196,6 +225,21 @@ where
         }
     }
 
+    async fn filled_in<'a>(self, txn: T, bounds: Bounds) -> TCResult<SparseStream<'a>> {
+        match self {
+            Self::Broadcast(broadcast) => broadcast.filled_in(txn, bounds).await,
+            Self::Cast(cast) => cast.filled_in(txn, bounds).await,
+            Self::Combine(combine) => combine.filled_in(txn, bounds).await,
+            Self::Dense(dense) => dense.filled_in(txn, bounds).await,
+            Self::Expand(expand) => expand.filled_in(txn, bounds).await,
+            Self::Reduce(reduce) => reduce.filled_in(txn, bounds).await,
+            Self::Slice(slice) => slice.filled_in(txn, bounds).await,
+            Self::Table(table) => table.filled_in(txn, bounds).await,
+            Self::Transpose(transpose) => transpose.filled_in(txn, bounds).await,
+            Self::Unary(unary) => unary.filled_in(txn, bounds).await,
+        }
+    }
+
     async fn filled_count(self, txn: T) -> TCResult<u64> {
         match self {
             Self::Broadcast(broadcast) => broadcast.filled_count(txn).await,
@@ -276,6 +320,21 @@ where
             Self::Unary(unary) => unary.write_value(txn_id, coord, value).await,
         }
     }
+
+    async fn write_values(&self, txn_id: TxnId, values: Vec<(Coord, Number)>) -> TCResult<()> {
+        match self {
+            Self::Broadcast(broadcast) => broadcast.write_values(txn_id, values).await,
+            Self::Cast(cast) => cast.write_values(txn_id, values).await,
+            Self::Combine(combine) => combine.write_values(txn_id, values).await,
+            Self::Dense(dense) => dense.write_values(txn_id, values).await,
+            Self::Expand(expand) => expand.write_values(txn_id, values).await,
+            Self::Reduce(reduce) => reduce.write_values(txn_id, values).await,
+            Self::Slice(slice) => slice.write_values(txn_id, values).await,
+            Self::Table(table) => table.write_values(txn_id, values).await,
+            Self::Transpose(transpose) => transpose.write_values(txn_id, values).await,
+            Self::Unary(unary) => unary.write_values(txn_id, values).await,
+        }
+    }
 }
 
 impl<FD, FS, D, T> ReadValueAt<D> for SparseAccessor<FD, FS, D, T>
@@ -671,6 +730,7 @@ where
 pub struct SparseCast<FD, FS, D, T, A> {
     source: A,
     dtype: NumberType,
+    saturate: bool,
     phantom: Phantom<FD, FS, D, T>,
 }
 
@@ -679,6 +739,17 @@ impl<FD, FS, D, T, A> SparseCast<FD, FS, D, T, A> {
         Self {
             source,
             dtype,
+            saturate: false,
+            phantom: Phantom::default(),
+        }
+    }
+
+    /// Cast the given `source` to `dtype`, clamping out-of-range integers instead of wrapping them.
+    pub fn saturating(source: A, dtype: NumberType) -> Self {
+        Self {
+            source,
+            dtype,
+            saturate: true,
             phantom: Phantom::default(),
         }
     }
@@ -722,17 +793,20 @@ where
     type Transpose = SparseCast<FD, FS, D, T, A::Transpose>;
 
     fn accessor(self) -> SparseAccessor<FD, FS, D, T> {
-        SparseAccessor::Cast(Box::new(SparseCast::new(
-            self.source.accessor(),
-            self.dtype,
-        )))
+        SparseAccessor::Cast(Box::new(SparseCast {
+            source: self.source.accessor(),
+            dtype: self.dtype,
+            saturate: self.saturate,
+            phantom: self.phantom,
+        }))
     }
 
     async fn filled<'a>(self, txn: T) -> TCResult<SparseStream<'a>> {
         let dtype = self.dtype;
+        let saturate = self.saturate;
 
         let filled = self.source.filled(txn).await?;
-        let cast = filled.map_ok(move |(coord, value)| (coord, value.into_type(dtype)));
+        let cast = filled.map_ok(move |(coord, value)| (coord, cast_value(value, dtype, saturate)));
         Ok(Box::pin(cast))
     }
 
@@ -752,6 +826,7 @@ where
         Ok(SparseCast {
             source: self.source.slice(bounds)?,
             dtype: self.dtype,
+            saturate: self.saturate,
             phantom: self.phantom,
         })
     }
@@ -761,6 +836,7 @@ where
         Ok(SparseCast {
             source,
             dtype: self.dtype,
+            saturate: self.saturate,
             phantom: self.phantom,
         })
     }
@@ -782,21 +858,32 @@ where
 
     fn read_value_at<'a>(self, txn: T, coord: Coord) -> Read<'a> {
         let dtype = self.dtype;
+        let saturate = self.saturate;
         let read = self
             .source
             .read_value_at(txn, coord)
-            .map_ok(move |(coord, value)| (coord, value.into_type(dtype)));
+            .map_ok(move |(coord, value)| (coord, cast_value(value, dtype, saturate)));
 
         Box::pin(read)
     }
 }
 
+#[inline]
+fn cast_value(value: Number, dtype: NumberType, saturate: bool) -> Number {
+    if saturate {
+        saturating_cast(value, dtype)
+    } else {
+        value.into_type(dtype)
+    }
+}
+
 #[derive(Clone)]
 pub struct SparseCombinator<FD, FS, D, T, L, R> {
     left: L,
     right: R,
     combinator: fn(Number, Number) -> Number,
     dtype: NumberType,
+    non_finite: NonFinite,
     phantom: Phantom<FD, FS, D, T>,
 }
 
@@ -814,6 +901,7 @@ where
         right: R,
         combinator: fn(Number, Number) -> Number,
         dtype: NumberType,
+        non_finite: NonFinite,
     ) -> TCResult<Self> {
         if left.shape() != right.shape() {
             return Err(TCError::unsupported(
@@ -826,6 +914,7 @@ where
             right,
             combinator,
             dtype,
+            non_finite,
             phantom: Phantom::default(),
         })
     }
@@ -835,22 +924,20 @@ where
         let right = self.right.clone().filled(txn);
         let (left, right) = try_join!(left, right)?;
 
-        let coord_bounds = coord_bounds(self.shape());
         let combinator = self.combinator;
         let left_zero = self.left.dtype().zero();
         let right_zero = self.right.dtype().zero();
 
-        let offset = move |row: &SparseRow| coord_to_offset(&row.0, &coord_bounds);
-        let combined = SparseCombine::new(left, right, offset).map_ok(move |(l, r)| match (l, r) {
-            (Some((l_coord, l)), Some((r_coord, r))) => {
-                debug_assert_eq!(l_coord, r_coord);
-                (l_coord, combinator(l, r))
-            }
-            (Some((l_coord, l)), None) => (l_coord, combinator(l, right_zero)),
-            (None, Some((r_coord, r))) => (r_coord, combinator(left_zero, r)),
-            (None, None) => {
-                panic!("expected a coordinate and value from one sparse tensor stream")
-            }
+        let combined = sorted_merge(self.shape(), left, right).map_ok(move |(coord, l, r)| {
+            let l = l.unwrap_or(left_zero);
+            let r = r.unwrap_or(right_zero);
+            (coord, combinator(l, r))
+        });
+
+        let dtype = self.dtype;
+        let non_finite = self.non_finite;
+        let combined = combined.and_then(move |(coord, value)| {
+            future::ready(non_finite.apply(value, dtype).map(|value| (coord, value)))
         });
 
         Ok(Box::pin(combined))
@@ -902,6 +989,7 @@ where
             right: self.right.accessor(),
             combinator: self.combinator,
             dtype: self.dtype,
+            non_finite: self.non_finite,
             phantom: Phantom::default(),
         }))
     }
@@ -959,6 +1047,7 @@ where
             right,
             combinator: self.combinator,
             dtype: self.dtype,
+            non_finite: self.non_finite,
             phantom: self.phantom,
         })
     }
@@ -973,6 +1062,7 @@ where
             right,
             combinator: self.combinator,
             dtype: self.dtype,
+            non_finite: self.non_finite,
             phantom: self.phantom,
         })
     }
@@ -999,6 +1089,7 @@ where
             let right = self.right.read_value_at(txn, coord);
             let ((coord, left), (_, right)) = try_join!(left, right)?;
             let value = (self.combinator)(left, right);
+            let value = self.non_finite.apply(value, self.dtype)?;
             Ok((coord, value))
         })
     }
@@ -1188,6 +1279,7 @@ pub struct SparseReduce<FD, FS, D, T> {
     source: SparseAccessor<FD, FS, D, T>,
     rebase: transform::Reduce,
     reductor: Reductor<FD, FS, D, T>,
+    dtype: NumberType,
 }
 
 impl<FD, FS, D, T> SparseReduce<FD, FS, D, T>
@@ -1202,11 +1294,23 @@ where
         source: SparseAccessor<FD, FS, D, T>,
         axis: usize,
         reductor: Reductor<FD, FS, D, T>,
+    ) -> TCResult<Self> {
+        let dtype = source.dtype();
+        Self::with_dtype(source, axis, reductor, dtype)
+    }
+
+    /// Construct a new reduction whose output has the given `dtype`.
+    pub fn with_dtype(
+        source: SparseAccessor<FD, FS, D, T>,
+        axis: usize,
+        reductor: Reductor<FD, FS, D, T>,
+        dtype: NumberType,
     ) -> TCResult<Self> {
         transform::Reduce::new(source.shape().clone(), axis).map(|rebase| SparseReduce {
             source,
             rebase,
             reductor,
+            dtype,
         })
     }
 }
@@ -1220,7 +1324,7 @@ where
     D::FileClass: From<TensorType>,
 {
     fn dtype(&self) -> NumberType {
-        self.source.dtype()
+        self.dtype
     }
 
     fn ndim(&self) -> usize {
@@ -1311,7 +1415,7 @@ where
         let source_bounds = self.rebase.invert_bounds(bounds);
         debug!("source bounds are {}", source_bounds);
         let source = self.source.slice(source_bounds)?;
-        SparseReduce::new(source.into(), reduce_axis, self.reductor)
+        SparseReduce::with_dtype(source.into(), reduce_axis, self.reductor, self.dtype)
     }
 
     fn transpose(self, permutation: Option<Vec<usize>>) -> TCResult<Self::Transpose> {
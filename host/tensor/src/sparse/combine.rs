@@ -3,10 +3,19 @@ use std::mem;
 use std::pin::Pin;
 use std::task::{self, Poll};
 
-use futures::stream::{Fuse, Stream, StreamExt};
+use futures::future;
+use futures::stream::{Fuse, Stream, StreamExt, TryStreamExt};
 use pin_project::pin_project;
 
-use tc_error::TCResult;
+use tc_error::*;
+use tc_value::Number;
+
+use crate::{coord_bounds, Coord};
+
+use super::{SparseRow, SparseStream};
+
+/// A row of two merged [`SparseStream`]s: a [`Coord`] and the value of each stream, if filled.
+pub type MergedRow = (Coord, Option<Number>, Option<Number>);
 
 // Based on: https://github.com/rust-lang/futures-rs/blob/master/futures-util/src/stream/select.rs
 #[pin_project]
@@ -130,3 +139,103 @@ pub fn coord_to_offset(coord: &[u64], coord_bounds: &[u64]) -> u64 {
         .map(|(d, x)| d * x)
         .sum()
 }
+
+/// Merge two filled [`SparseStream`]s of a tensor with the given `shape` into a single stream.
+///
+/// Both streams must yield strictly ascending [`Coord`]s, as [`super::SparseAccess::filled`] does,
+/// and so will the merged stream. A [`Coord`] which is filled in both streams is yielded once.
+pub fn sorted_merge<'a>(
+    shape: &[u64],
+    left: SparseStream<'a>,
+    right: SparseStream<'a>,
+) -> impl Stream<Item = TCResult<MergedRow>> + Send + Unpin + 'a {
+    let coord_bounds = coord_bounds(shape);
+    let offset = move |row: &SparseRow| coord_to_offset(&row.0, &coord_bounds);
+
+    SparseCombine::new(left, right, offset).map_ok(|(l, r)| match (l, r) {
+        (Some((l_coord, l)), Some((r_coord, r))) => {
+            debug_assert_eq!(l_coord, r_coord);
+            (l_coord, Some(l), Some(r))
+        }
+        (Some((coord, l)), None) => (coord, Some(l), None),
+        (None, Some((coord, r))) => (coord, None, Some(r)),
+        (None, None) => panic!("expected a coordinate and value from one sparse tensor stream"),
+    })
+}
+
+/// Check that the given filled [`SparseStream`] yields strictly ascending [`Coord`]s,
+/// returning an internal error at the first [`Coord`] which is out of order.
+#[cfg(debug_assertions)]
+pub fn validate_order<'a>(filled: SparseStream<'a>) -> SparseStream<'a> {
+    let mut last: Option<Coord> = None;
+
+    let validated = filled.and_then(move |(coord, value)| {
+        let result = match &last {
+            Some(last) if last >= &coord => Err(TCError::internal(format!(
+                "sparse tensor coordinate {:?} is out of order after {:?}",
+                coord, last
+            ))),
+            _ => Ok(()),
+        };
+
+        last = Some(coord.clone());
+        future::ready(result.map(|()| (coord, value)))
+    });
+
+    Box::pin(validated)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use futures::stream;
+
+    use super::*;
+
+    fn filled<'a>(coords: &[[u64; 2]]) -> SparseStream<'a> {
+        let rows: Vec<TCResult<SparseRow>> = coords
+            .iter()
+            .map(|coord| Ok((coord.to_vec(), Number::from(1u64))))
+            .collect();
+
+        Box::pin(stream::iter(rows))
+    }
+
+    #[test]
+    fn test_sorted_merge() {
+        let left = filled(&[[0, 1], [1, 0], [2, 2]]);
+        let right = filled(&[[0, 0], [1, 0], [2, 1]]);
+
+        let merged: Vec<MergedRow> =
+            block_on(sorted_merge(&[3, 3], left, right).try_collect()).expect("merge");
+
+        let coords: Vec<Coord> = merged.iter().map(|(coord, _, _)| coord.to_vec()).collect();
+        assert_eq!(
+            coords,
+            vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![2, 1], vec![2, 2]]
+        );
+
+        let both: Vec<bool> = merged
+            .iter()
+            .map(|(_, l, r)| l.is_some() && r.is_some())
+            .collect();
+
+        assert_eq!(both, vec![false, false, true, false, false]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_validate_order() {
+        let sorted = validate_order(filled(&[[0, 1], [1, 0], [1, 2]]));
+        let sorted: TCResult<Vec<SparseRow>> = block_on(sorted.try_collect());
+        assert!(sorted.is_ok());
+
+        let unsorted = validate_order(filled(&[[0, 1], [1, 2], [1, 0]]));
+        let unsorted: TCResult<Vec<SparseRow>> = block_on(unsorted.try_collect());
+        assert!(unsorted.is_err());
+
+        let repeated = validate_order(filled(&[[0, 1], [0, 1]]));
+        let repeated: TCResult<Vec<SparseRow>> = block_on(repeated.try_collect());
+        assert!(repeated.is_err());
+    }
+}
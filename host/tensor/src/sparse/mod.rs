@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
 use std::marker::PhantomData;
@@ -13,14 +14,16 @@ use log::debug;
 
 use tc_btree::{BTreeType, Node};
 use tc_error::*;
+use tc_table::{Table, TableInstance};
 use tc_transact::fs::{CopyFrom, Dir, File, Hash, Persist, Restore};
-use tc_transact::{IntoView, Transact, Transaction, TxnId};
+use tc_transact::{IntoView, Rollback, Transact, Transaction, TxnId};
 use tc_value::{Number, NumberClass, NumberInstance, NumberType};
 use tcgeneric::{TCBoxTryFuture, TCBoxTryStream};
 
-use super::dense::{BlockListSparse, DenseTensor};
+use super::dense::{BlockListSparse, DenseAccess, DenseAccessor, DenseTensor};
 use super::{
-    Bounds, Coord, Phantom, Schema, Shape, Tensor, TensorAccess, TensorBoolean, TensorCompare,
+    float_type, max_value, min_value, ordered, statistic, AxisBounds, Bounds, Coord, Moments,
+    NonFinite, Phantom, Schema, Shape, Tensor, TensorAccess, TensorBoolean, TensorCompare,
     TensorDualIO, TensorIO, TensorInstance, TensorMath, TensorReduce, TensorTransform, TensorType,
     TensorUnary,
 };
@@ -29,6 +32,7 @@ use crate::dense::PER_BLOCK;
 
 use access::*;
 pub use access::{DenseToSparse, SparseAccess, SparseAccessor};
+pub use combine::{sorted_merge, MergedRow};
 pub use table::SparseTable;
 
 mod access;
@@ -41,6 +45,9 @@ pub type SparseStream<'a> = Pin<Box<dyn Stream<Item = TCResult<SparseRow>> + Sen
 const ERR_NOT_SPARSE: &str = "The result of the requested operation would not be sparse;\
 convert to a DenseTensor first.";
 
+// the number of filled values to buffer and write to the underlying table at once
+const WRITE_BATCH: usize = 4096;
+
 /// A `Tensor` stored as a `Table` of [`Coord`]s and [`Number`] values
 #[derive(Clone)]
 pub struct SparseTensor<FD, FS, D, T, A> {
@@ -66,11 +73,94 @@ where
     T: Transaction<D>,
     A: SparseAccess<FD, FS, D, T>,
 {
+    /// Return a stream of the filled ([`Coord`], [`Number`]) pairs of this `SparseTensor`
+    /// which fall within the given [`Bounds`].
+    ///
+    /// This is equivalent to `slice(bounds).filled(txn)` except that the [`Coord`]s are not
+    /// rebased, and a table-backed tensor will only scan the range of its index within `bounds`.
+    pub async fn filled_at<'a>(self, bounds: Bounds, txn: T) -> TCResult<SparseStream<'a>> {
+        self.shape().validate_bounds(&bounds)?;
+        self.accessor.filled_in(txn, bounds).await
+    }
+
+    /// Return the number of filled (i.e. nonzero) elements of this `SparseTensor`.
+    pub async fn nnz(self, txn: T) -> TCResult<u64> {
+        self.accessor.filled_count(txn).await
+    }
+
+    /// Compute the dot product of this 1-dimensional `SparseTensor` with `other`.
+    ///
+    /// Only the coordinates which are filled in both tensors are visited.
+    pub async fn dot<R: SparseAccess<FD, FS, D, T>>(
+        self,
+        other: SparseTensor<FD, FS, D, T, R>,
+        txn: T,
+    ) -> TCResult<Number> {
+        if self.ndim() != 1 || self.shape() != other.shape() {
+            return Err(TCError::unsupported(format!(
+                "dot product requires two 1-dimensional Tensors of equal length, not {} and {}",
+                self.shape(),
+                other.shape()
+            )));
+        }
+
+        let mut sum = Ord::max(self.dtype(), other.dtype()).zero();
+
+        let mut left = self.accessor.filled(txn.clone()).await?;
+        let mut right = other.accessor.filled(txn).await?;
+        let mut l = left.try_next().await?;
+        let mut r = right.try_next().await?;
+
+        loop {
+            let (i, x, j, y) = match (&l, &r) {
+                (Some((i, x)), Some((j, y))) => (i[0], *x, j[0], *y),
+                _ => break,
+            };
+
+            if i < j {
+                l = left.try_next().await?;
+            } else if i > j {
+                r = right.try_next().await?;
+            } else {
+                sum = sum + (x * y);
+                l = left.try_next().await?;
+                r = right.try_next().await?;
+            }
+        }
+
+        Ok(sum)
+    }
+
+    /// Accumulate the [`Moments`] of the filled values of this `SparseTensor`.
+    ///
+    /// Implicit zeros don't contribute to either sum, but they do count toward the `size` of
+    /// this tensor, which is what the caller should use as the denominator.
+    async fn moments(&self, txn: T) -> TCResult<Moments> {
+        let mut moments = Moments::default();
+        let mut filled = self.accessor.clone().filled(txn).await?;
+        let mut buffer = Vec::with_capacity(PER_BLOCK);
+        while let Some((_coord, value)) = filled.try_next().await? {
+            buffer.push(value);
+
+            if buffer.len() == PER_BLOCK {
+                moments = moments.update(Array::from(buffer.to_vec()));
+                buffer.clear()
+            }
+        }
+
+        if !buffer.is_empty() {
+            moments = moments.update(Array::from(buffer));
+        }
+
+        Ok(moments)
+    }
+
     fn combine<R: SparseAccess<FD, FS, D, T>>(
         self,
         other: SparseTensor<FD, FS, D, T, R>,
         combinator: fn(Number, Number) -> Number,
         dtype: NumberType,
+        non_finite: NonFinite,
     ) -> TCResult<SparseTensor<FD, FS, D, T, SparseCombinator<FD, FS, D, T, A, R>>> {
         if self.shape() != other.shape() {
             return Err(TCError::unsupported(format!(
@@ -80,7 +170,8 @@ where
             )));
         }
 
-        let accessor = SparseCombinator::new(self.accessor, other.accessor, combinator, dtype)?;
+        let accessor =
+            SparseCombinator::new(self.accessor, other.accessor, combinator, dtype, non_finite)?;
 
         Ok(SparseTensor {
             accessor,
@@ -104,8 +195,13 @@ where
             )));
         }
 
-        let accessor =
-            SparseCombinator::new(self.accessor, other.accessor, condensor, NumberType::Bool)?;
+        let accessor = SparseCombinator::new(
+            self.accessor,
+            other.accessor,
+            condensor,
+            NumberType::Bool,
+            NonFinite::Propagate,
+        )?;
 
         let dense = BlockListSparse::from(accessor);
         Ok(dense.into())
@@ -126,6 +222,296 @@ where
             .map_ok(Self::from)
             .await
     }
+
+    /// Return a read-only view of the backing [`Table`] of this `SparseTensor`, whose key is one
+    /// `u64` column per axis (named by its axis number) and whose only value column is the
+    /// element `value`.
+    ///
+    /// Only filled elements have a row, so the `Table` can be used to query the nonzero structure
+    /// of this `SparseTensor`. See [`SparseTensor::from_table`] for the inverse.
+    pub fn as_table(&self) -> TCResult<Table<FS, D, T>> {
+        self.accessor.index().read_only().map(Table::from)
+    }
+
+    /// Construct a new `SparseTensor` with the given `schema` from the rows of `table`,
+    /// which must have one coordinate column per axis followed by a value column, in the format
+    /// of [`SparseTensor::as_table`].
+    pub async fn from_table<Tbl: TableInstance<FS, D, T>>(
+        table: Tbl,
+        schema: Schema,
+        txn: T,
+    ) -> TCResult<Self>
+    where
+        D::FileClass: From<TensorType>,
+    {
+        let columns = table.key().len() + table.values().len();
+        if columns != schema.shape.len() + 1 {
+            return Err(TCError::bad_request(
+                format!(
+                    "a Tensor of shape {} requires one column per axis plus a value column, not",
+                    schema.shape
+                ),
+                columns,
+            ));
+        }
+
+        let txn_id = *txn.id();
+        let dir = txn.context().create_dir_tmp(txn_id).await?;
+        let tensor = SparseTable::create(&dir, schema, txn_id).await?;
+
+        table
+            .rows(txn_id)
+            .await?
+            .and_then(|row| future::ready(table::expect_row(row)))
+            .map_ok(|(coord, value)| tensor.write_value(txn_id, coord, value))
+            .try_buffer_unordered(num_cpus::get())
+            .try_fold((), |(), ()| future::ready(Ok(())))
+            .await?;
+
+        Ok(Self::from(tensor))
+    }
+
+    /// Construct the outer product of the 1-dimensional `SparseTensor`s `a` and `b`.
+    ///
+    /// Only the filled elements of `a` and `b` are visited, so the result has
+    /// `nnz(a) * nnz(b)` filled elements.
+    pub async fn outer<L, R>(
+        a: SparseTensor<FD, FS, D, T, L>,
+        b: SparseTensor<FD, FS, D, T, R>,
+        txn: T,
+    ) -> TCResult<Self>
+    where
+        L: SparseAccess<FD, FS, D, T>,
+        R: SparseAccess<FD, FS, D, T>,
+        D::FileClass: From<TensorType>,
+    {
+        if a.ndim() != 1 || b.ndim() != 1 {
+            return Err(TCError::unsupported(format!(
+                "outer product requires two 1-dimensional Tensors, not {} and {}",
+                a.shape(),
+                b.shape()
+            )));
+        }
+
+        let txn_id = *txn.id();
+        let schema = Schema {
+            shape: vec![a.shape()[0], b.shape()[0]].into(),
+            dtype: Ord::max(a.dtype(), b.dtype()),
+        };
+
+        let dir = txn.context().create_dir_tmp(txn_id).await?;
+        let outer = SparseTable::create(&dir, schema, txn_id).await?;
+
+        let mut left = a.accessor.filled(txn.clone()).await?;
+        while let Some((i, l)) = left.try_next().await? {
+            let right = b.accessor.clone().filled(txn.clone()).await?;
+            right
+                .map_ok(|(j, r)| outer.write_value(txn_id, vec![i[0], j[0]], l * r))
+                .try_buffer_unordered(num_cpus::get())
+                .try_fold((), |(), ()| future::ready(Ok(())))
+                .await?;
+        }
+
+        Ok(Self::from(outer))
+    }
+}
+
+/// Concatenate the given `tensors` along the given `axis`, without densifying them.
+///
+/// Each input's filled [`Coord`]s are offset along `axis` by the total dimension of the inputs
+/// before it, so the result has as many filled elements as all the inputs combined.
+pub async fn sparse_concatenate<FD, FS, D, T, A>(
+    tensors: Vec<SparseTensor<FD, FS, D, T, A>>,
+    axis: usize,
+    txn: T,
+) -> TCResult<SparseTensor<FD, FS, D, T, SparseTable<FD, FS, D, T>>>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    A: SparseAccess<FD, FS, D, T>,
+    D::FileClass: From<BTreeType> + From<TensorType>,
+{
+    let (first, rest) = tensors
+        .split_first()
+        .ok_or_else(|| TCError::unsupported("cannot concatenate an empty list of Tensors"))?;
+
+    if axis >= first.ndim() {
+        return Err(TCError::unsupported(format!(
+            "cannot concatenate Tensors with shape {} along axis {}",
+            first.shape(),
+            axis
+        )));
+    }
+
+    let mut shape = first.shape().clone();
+    for tensor in rest {
+        if tensor.dtype() != first.dtype() {
+            return Err(TCError::unsupported(format!(
+                "cannot concatenate a Tensor of type {} with one of type {}",
+                first.dtype(),
+                tensor.dtype()
+            )));
+        }
+
+        let off_axis = |shape: &Shape| {
+            let mut shape = shape.to_vec();
+            shape.remove(axis);
+            shape
+        };
+
+        if tensor.ndim() != first.ndim() || off_axis(tensor.shape()) != off_axis(first.shape()) {
+            return Err(TCError::unsupported(format!(
+                "cannot concatenate a Tensor with shape {} with one of shape {} along axis {}",
+                first.shape(),
+                tensor.shape(),
+                axis
+            )));
+        }
+
+        shape[axis] += tensor.shape()[axis];
+    }
+
+    let dtype = first.dtype();
+    let mut offset = 0;
+    let mut filled: Option<SparseStream> = None;
+    for tensor in tensors {
+        let dim = tensor.shape()[axis];
+        let offset_filled =
+            tensor
+                .accessor
+                .filled(txn.clone())
+                .await?
+                .map_ok(move |(mut coord, value)| {
+                    coord[axis] += offset;
+                    (coord, value)
+                });
+
+        let offset_filled: SparseStream = Box::pin(offset_filled);
+
+        filled = match filled {
+            None => Some(offset_filled),
+            Some(merged) => {
+                // the inputs' coordinates never coincide, so exactly one side of each row is filled
+                let merged = sorted_merge(&shape, merged, offset_filled).map_ok(|(coord, l, r)| {
+                    let value = l.or(r).expect("filled value");
+                    (coord, value)
+                });
+
+                Some(Box::pin(merged))
+            }
+        };
+
+        offset += dim;
+    }
+
+    let txn_id = *txn.id();
+    let schema = Schema { shape, dtype };
+
+    let dir = txn.context().create_dir_tmp(txn_id).await?;
+    let table = SparseTable::create(&dir, schema, txn_id).await?;
+
+    filled
+        .expect("filled")
+        .map_ok(|(coord, value)| table.write_value(txn_id, coord, value))
+        .try_buffer_unordered(num_cpus::get())
+        .try_fold((), |(), ()| future::ready(Ok(())))
+        .await?;
+
+    Ok(SparseTensor::from(table))
+}
+
+/// Multiply the 2-dimensional `SparseTensor`s `a` and `b` without densifying either of them,
+/// using Gustavson's algorithm.
+///
+/// Each row of the product is accumulated from the rows of `b` selected by the filled elements
+/// of the same row of `a`, so only filled elements are visited and only nonzero elements of the
+/// product are written.
+pub async fn sparse_matmul<FD, FS, D, T, L, R>(
+    a: SparseTensor<FD, FS, D, T, L>,
+    b: SparseTensor<FD, FS, D, T, R>,
+    txn: T,
+) -> TCResult<SparseTensor<FD, FS, D, T, SparseTable<FD, FS, D, T>>>
+where
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
+    D: Dir,
+    T: Transaction<D>,
+    L: SparseAccess<FD, FS, D, T>,
+    R: SparseAccess<FD, FS, D, T>,
+    D::FileClass: From<BTreeType> + From<TensorType>,
+{
+    if a.ndim() != 2 || b.ndim() != 2 {
+        return Err(TCError::unsupported(format!(
+            "matrix multiplication requires two 2-dimensional Tensors, not {} and {}",
+            a.shape(),
+            b.shape()
+        )));
+    }
+
+    if a.shape()[1] != b.shape()[0] {
+        return Err(TCError::unsupported(format!(
+            "cannot multiply a matrix with shape {} by one with shape {}",
+            a.shape(),
+            b.shape()
+        )));
+    }
+
+    let txn_id = *txn.id();
+    let dtype = Ord::max(a.dtype(), b.dtype());
+    let zero = dtype.zero();
+    let n = b.shape()[1];
+
+    let schema = Schema {
+        shape: vec![a.shape()[0], n].into(),
+        dtype,
+    };
+
+    let dir = txn.context().create_dir_tmp(txn_id).await?;
+    let product = SparseTable::create(&dir, schema, txn_id).await?;
+
+    // the filled elements of `a` are visited in row-major order, so each row is finished
+    // as soon as the first element of the next row is read
+    let mut row = None;
+    let mut accumulator = BTreeMap::<u64, Number>::new();
+
+    let mut left = a.accessor.filled(txn.clone()).await?;
+    while let Some((coord, x)) = left.try_next().await? {
+        let (i, k) = (coord[0], coord[1]);
+
+        if row != Some(i) {
+            if let Some(row) = row {
+                let values = matmul_row(row, &mut accumulator, zero);
+                product.write_values(txn_id, values).await?;
+            }
+
+            row = Some(i);
+        }
+
+        let bounds = Bounds::from(vec![AxisBounds::At(k), AxisBounds::all(n)]);
+        let mut right = b.clone().filled_at(bounds, txn.clone()).await?;
+        while let Some((coord, y)) = right.try_next().await? {
+            let sum = accumulator.entry(coord[1]).or_insert(zero);
+            *sum = *sum + (x * y);
+        }
+    }
+
+    if let Some(row) = row {
+        let values = matmul_row(row, &mut accumulator, zero);
+        product.write_values(txn_id, values).await?;
+    }
+
+    Ok(SparseTensor::from(product))
+}
+
+// drain the accumulated row `i` of a matrix product, skipping any sum which cancelled out to zero
+fn matmul_row(i: u64, accumulator: &mut BTreeMap<u64, Number>, zero: Number) -> Vec<SparseRow> {
+    let row = std::mem::take(accumulator);
+    row.into_iter()
+        .filter(|(_, value)| value != &zero)
+        .map(|(j, value)| (vec![i, j], value))
+        .collect()
 }
 
 impl<FD, FS, D, T, A> TensorAccess for SparseTensor<FD, FS, D, T, A>
@@ -180,15 +566,15 @@ where
     type Combine = SparseTensor<FD, FS, D, T, SparseCombinator<FD, FS, D, T, L, R>>;
 
     fn and(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
-        self.combine(other, Number::and, NumberType::Bool)
+        self.combine(other, Number::and, NumberType::Bool, NonFinite::Propagate)
     }
 
     fn or(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
-        self.combine(other, Number::or, NumberType::Bool)
+        self.combine(other, Number::or, NumberType::Bool, NonFinite::Propagate)
     }
 
     fn xor(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
-        self.combine(other, Number::xor, NumberType::Bool)
+        self.combine(other, Number::xor, NumberType::Bool, NonFinite::Propagate)
     }
 }
 
@@ -253,7 +639,7 @@ where
             (l > r).into()
         }
 
-        self.combine(other, gt, NumberType::Bool)
+        self.combine(other, gt, NumberType::Bool, NonFinite::Propagate)
     }
 
     fn gte(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Dense> {
@@ -269,7 +655,7 @@ where
             (l < r).into()
         }
 
-        self.combine(other, lt, NumberType::Bool)
+        self.combine(other, lt, NumberType::Bool, NonFinite::Propagate)
     }
 
     fn lte(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Dense> {
@@ -285,7 +671,7 @@ where
             (l != r).into()
         }
 
-        self.combine(other, ne, NumberType::Bool)
+        self.combine(other, ne, NumberType::Bool, NonFinite::Propagate)
     }
 }
 
@@ -379,6 +765,30 @@ where
             .await
     }
 
+    async fn set_where(
+        self,
+        txn: T,
+        mask: SparseTensor<FD, FS, D, T, R>,
+        value: Number,
+    ) -> TCResult<()> {
+        if self.shape() != mask.shape() {
+            return Err(TCError::unsupported(format!(
+                "cannot use a Tensor with shape {} as a mask for a Tensor with shape {}",
+                mask.shape(),
+                self.shape(),
+            )));
+        }
+
+        let txn_id = *txn.id();
+        let filled = mask.accessor.filled(txn).await?;
+
+        filled
+            .map_ok(|(coord, _)| self.write_value_at(txn_id, coord, value))
+            .try_buffer_unordered(num_cpus::get())
+            .try_fold((), |_, _| future::ready(Ok(())))
+            .await
+    }
+
     async fn write(
         self,
         txn: T,
@@ -396,11 +806,15 @@ where
 
         let txn_id = *txn.id();
         let filled = other.accessor.filled(txn).await?;
-        filled
-            .map_ok(|(coord, value)| slice.write_value_at(txn_id, coord, value))
-            .try_buffer_unordered(num_cpus::get())
-            .try_fold((), |_, _| future::ready(Ok(())))
-            .await
+        let mut batches = filled
+            .chunks(WRITE_BATCH)
+            .map(|batch| batch.into_iter().collect::<TCResult<Vec<_>>>());
+
+        while let Some(batch) = batches.next().await {
+            slice.accessor.write_values(txn_id, batch?).await?;
+        }
+
+        Ok(())
     }
 }
 
@@ -429,6 +843,19 @@ where
         }
     }
 
+    async fn set_where(self, txn: T, mask: Tensor<FD, FS, D, T>, value: Number) -> TCResult<()> {
+        let mask = if self.shape() == mask.shape() {
+            mask
+        } else {
+            mask.broadcast(self.shape().clone())?
+        };
+
+        match mask {
+            Tensor::Dense(mask) => self.set_where(txn, mask.into_sparse(), value).await,
+            Tensor::Sparse(mask) => self.set_where(txn, mask, value).await,
+        }
+    }
+
     async fn write(
         self,
         txn: Self::Txn,
@@ -498,26 +925,60 @@ where
 {
     type Combine = SparseTensor<FD, FS, D, T, SparseCombinator<FD, FS, D, T, L, R>>;
 
-    fn add(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
+    fn add_with(
+        self,
+        other: SparseTensor<FD, FS, D, T, R>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, Number::add, dtype)
+        self.combine(other, Number::add, dtype, non_finite)
     }
 
-    fn div(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
+    fn div_with(
+        self,
+        other: SparseTensor<FD, FS, D, T, R>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         // TODO: implement left_combine
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, Number::div, dtype)
+        self.combine(other, Number::div, dtype, non_finite)
     }
 
-    fn mul(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
+    fn mul_with(
+        self,
+        other: SparseTensor<FD, FS, D, T, R>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         // TODO: implement left_combine
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, Number::mul, dtype)
+        self.combine(other, Number::mul, dtype, non_finite)
+    }
+
+    fn sub_with(
+        self,
+        other: SparseTensor<FD, FS, D, T, R>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
+        let dtype = Ord::max(self.dtype(), other.dtype());
+        self.combine(other, Number::sub, dtype, non_finite)
     }
 
-    fn sub(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
+    fn maximum(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        // the maximum of two implicit zeros is zero, so the result is still sparse
+        let dtype = Ord::max(self.dtype(), other.dtype());
+        self.combine(other, max_value, dtype, NonFinite::default())
+    }
+
+    fn minimum(self, other: SparseTensor<FD, FS, D, T, R>) -> TCResult<Self::Combine> {
+        ordered(self.dtype())?;
+        ordered(other.dtype())?;
+
+        // the minimum of two implicit zeros is zero, so the result is still sparse
         let dtype = Ord::max(self.dtype(), other.dtype());
-        self.combine(other, Number::sub, dtype)
+        self.combine(other, min_value, dtype, NonFinite::default())
     }
 }
 
@@ -532,31 +993,71 @@ where
 {
     type Combine = Tensor<FD, FS, D, T>;
 
-    fn add(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn add_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Sparse(sparse) => self.add(sparse).map(Tensor::from),
-            Tensor::Dense(dense) => self.into_dense().add(dense).map(Tensor::from),
+            Tensor::Sparse(sparse) => self.add_with(sparse, non_finite).map(Tensor::from),
+            Tensor::Dense(dense) => self
+                .into_dense()
+                .add_with(dense, non_finite)
+                .map(Tensor::from),
         }
     }
 
-    fn div(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn div_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Sparse(sparse) => self.div(sparse).map(Tensor::from),
-            Tensor::Dense(dense) => self.div(dense.into_sparse()).map(Tensor::from),
+            Tensor::Sparse(sparse) => self.div_with(sparse, non_finite).map(Tensor::from),
+            Tensor::Dense(dense) => self
+                .div_with(dense.into_sparse(), non_finite)
+                .map(Tensor::from),
         }
     }
 
-    fn mul(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn mul_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Sparse(sparse) => self.mul(sparse).map(Tensor::from),
-            Tensor::Dense(dense) => self.mul(dense.into_sparse()).map(Tensor::from),
+            Tensor::Sparse(sparse) => self.mul_with(sparse, non_finite).map(Tensor::from),
+            Tensor::Dense(dense) => self
+                .mul_with(dense.into_sparse(), non_finite)
+                .map(Tensor::from),
         }
     }
 
-    fn sub(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+    fn sub_with(
+        self,
+        other: Tensor<FD, FS, D, T>,
+        non_finite: NonFinite,
+    ) -> TCResult<Self::Combine> {
         match other {
-            Tensor::Sparse(sparse) => self.sub(sparse).map(Tensor::from),
-            Tensor::Dense(dense) => self.into_dense().sub(dense).map(Tensor::from),
+            Tensor::Sparse(sparse) => self.sub_with(sparse, non_finite).map(Tensor::from),
+            Tensor::Dense(dense) => self
+                .into_dense()
+                .sub_with(dense, non_finite)
+                .map(Tensor::from),
+        }
+    }
+
+    fn maximum(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+        match other {
+            Tensor::Sparse(sparse) => self.maximum(sparse).map(Tensor::from),
+            Tensor::Dense(dense) => self.into_dense().maximum(dense).map(Tensor::from),
+        }
+    }
+
+    fn minimum(self, other: Tensor<FD, FS, D, T>) -> TCResult<Self::Combine> {
+        match other {
+            Tensor::Sparse(sparse) => self.minimum(sparse).map(Tensor::from),
+            Tensor::Dense(dense) => self.into_dense().minimum(dense).map(Tensor::from),
         }
     }
 }
@@ -620,6 +1121,77 @@ where
             Ok(sum)
         })
     }
+
+    fn max(self, axis: usize) -> TCResult<Self::Reduce> {
+        let accessor = SparseReduce::new(
+            self.accessor.accessor(),
+            axis,
+            SparseTensor::<FD, FS, D, T, SparseAccessor<FD, FS, D, T>>::max_all,
+        )?;
+
+        Ok(SparseTensor::from(accessor))
+    }
+
+    fn max_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move { self.clone().into_dense().max_all(txn).await })
+    }
+
+    fn mean(self, axis: usize) -> TCResult<Self::Reduce> {
+        let dtype = float_type(self.dtype());
+        let accessor = SparseReduce::with_dtype(
+            self.accessor.accessor(),
+            axis,
+            SparseTensor::<FD, FS, D, T, SparseAccessor<FD, FS, D, T>>::mean_all,
+            dtype,
+        )?;
+
+        Ok(SparseTensor::from(accessor))
+    }
+
+    fn mean_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move {
+            let moments = self.moments(txn).await?;
+            Ok(statistic(moments.mean(self.size()), self.dtype()))
+        })
+    }
+
+    fn var(self, axis: usize) -> TCResult<Self::Reduce> {
+        let dtype = float_type(self.dtype());
+        let accessor = SparseReduce::with_dtype(
+            self.accessor.accessor(),
+            axis,
+            SparseTensor::<FD, FS, D, T, SparseAccessor<FD, FS, D, T>>::var_all,
+            dtype,
+        )?;
+
+        Ok(SparseTensor::from(accessor))
+    }
+
+    fn var_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move {
+            let moments = self.moments(txn).await?;
+            Ok(statistic(moments.var(self.size()), self.dtype()))
+        })
+    }
+
+    fn std(self, axis: usize) -> TCResult<Self::Reduce> {
+        let dtype = float_type(self.dtype());
+        let accessor = SparseReduce::with_dtype(
+            self.accessor.accessor(),
+            axis,
+            SparseTensor::<FD, FS, D, T, SparseAccessor<FD, FS, D, T>>::std_all,
+            dtype,
+        )?;
+
+        Ok(SparseTensor::from(accessor))
+    }
+
+    fn std_all(&self, txn: T) -> TCBoxTryFuture<Number> {
+        Box::pin(async move {
+            let moments = self.moments(txn).await?;
+            Ok(statistic(moments.std(self.size()), self.dtype()))
+        })
+    }
 }
 
 impl<FD, FS, D, T, A> TensorTransform for SparseTensor<FD, FS, D, T, A>
@@ -647,6 +1219,11 @@ where
         Ok(accessor.into())
     }
 
+    fn cast_into_saturating(self, dtype: NumberType) -> TCResult<Self::Cast> {
+        let accessor = SparseCast::saturating(self.accessor, dtype);
+        Ok(accessor.into())
+    }
+
     fn expand_dims(self, axis: usize) -> TCResult<Self::Expand> {
         let accessor = SparseExpand::new(self.accessor, axis)?;
         Ok(accessor.into())
@@ -666,14 +1243,16 @@ where
 #[async_trait]
 impl<FD, FS, D, T, A> TensorUnary<D> for SparseTensor<FD, FS, D, T, A>
 where
-    FD: File<Array>,
-    FS: File<Node>,
+    FD: File<Array> + TryFrom<D::File, Error = TCError>,
+    FS: File<Node> + TryFrom<D::File, Error = TCError>,
     D: Dir,
     T: Transaction<D>,
     A: SparseAccess<FD, FS, D, T>,
+    D::FileClass: From<TensorType>,
 {
     type Txn = T;
     type Unary = SparseTensor<FD, FS, D, T, SparseUnary<FD, FS, D, T>>;
+    type Dense = DenseTensor<FD, FS, D, T, DenseAccessor<FD, FS, D, T>>;
 
     fn abs(&self) -> TCResult<Self::Unary> {
         let source = self.accessor.clone().accessor();
@@ -684,6 +1263,7 @@ where
     }
 
     async fn all(self, txn: Self::Txn) -> TCResult<bool> {
+        let size = self.size();
         let affected = stream::iter(Bounds::all(self.shape()).affected());
         let filled = self.accessor.filled(txn).await?;
 
@@ -692,13 +1272,17 @@ where
             .zip(affected)
             .map(|(r, expected)| r.map(|actual| (actual, expected)));
 
+        let mut count = 0u64;
         while let Some((actual, expected)) = coords.try_next().await? {
             if actual != expected {
                 return Ok(false);
             }
+
+            count += 1;
         }
 
-        Ok(true)
+        // the zip above stops at the end of the filled coordinates, which may be a prefix
+        Ok(count == size)
     }
 
     async fn any(self, txn: Self::Txn) -> TCResult<bool> {
@@ -706,9 +1290,28 @@ where
         Ok(filled.next().await.is_some())
     }
 
-    fn not(&self) -> TCResult<Self::Unary> {
+    fn exp(&self) -> TCResult<Self::Unary> {
+        Err(TCError::unsupported(ERR_NOT_SPARSE))
+    }
+
+    fn ln(&self) -> TCResult<Self::Unary> {
         Err(TCError::unsupported(ERR_NOT_SPARSE))
     }
+
+    fn log2(&self) -> TCResult<Self::Unary> {
+        Err(TCError::unsupported(ERR_NOT_SPARSE))
+    }
+
+    fn log10(&self) -> TCResult<Self::Unary> {
+        Err(TCError::unsupported(ERR_NOT_SPARSE))
+    }
+
+    fn not(&self) -> TCResult<Self::Dense> {
+        // every zero element becomes true, so read this tensor lazily as a dense one
+        let dense = DenseTensor::from(BlockListSparse::from(self.accessor.clone()));
+        let not = dense.not()?;
+        Ok(DenseTensor::from(not.into_inner().accessor()))
+    }
 }
 
 #[async_trait]
@@ -803,6 +1406,21 @@ where
     }
 }
 
+#[async_trait]
+impl<FD, FS, D, T> Rollback for SparseTensor<FD, FS, D, T, SparseTable<FD, FS, D, T>>
+where
+    Self: Send + Sync,
+    SparseTable<FD, FS, D, T>: Rollback + Send + Sync,
+{
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.accessor.savepoint(txn_id, ordinal).await
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.accessor.rollback_to(txn_id, ordinal).await
+    }
+}
+
 impl<FD, FS, D, T, A> From<A> for SparseTensor<FD, FS, D, T, A> {
     fn from(accessor: A) -> Self {
         Self {
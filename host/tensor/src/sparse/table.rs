@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::{TryFrom, TryInto};
 use std::marker::PhantomData;
 
@@ -13,7 +13,7 @@ use tc_btree::{BTreeType, Node};
 use tc_error::*;
 use tc_table::{Column, ColumnBound, Merged, TableIndex, TableInstance, TableSchema};
 use tc_transact::fs::{CopyFrom, Dir, File, Persist, Restore};
-use tc_transact::{Transact, Transaction, TxnId};
+use tc_transact::{Rollback, Transact, Transaction, TxnId};
 use tc_value::{Bound, Number, NumberClass, NumberInstance, NumberType, UInt, Value, ValueType};
 use tcgeneric::{label, Id, Label, TCBoxTryStream, Tuple};
 
@@ -23,6 +23,8 @@ use crate::transform;
 use crate::{AxisBounds, Bounds, Coord, Schema, Shape, TensorAccess, TensorType};
 
 use super::access::SparseTranspose;
+#[cfg(debug_assertions)]
+use super::combine::validate_order;
 use super::{SparseAccess, SparseAccessor, SparseStream, SparseTensor};
 
 const VALUE: Label = label("value");
@@ -56,6 +58,11 @@ where
         })
     }
 
+    /// Borrow the [`TableIndex`] which stores the filled elements of this `SparseTable`.
+    pub(super) fn index(&self) -> &TableIndex<FS, D, T> {
+        &self.table
+    }
+
     fn table_schema(schema: &Schema) -> TableSchema {
         let ndim = schema.shape.len();
         let u64_type = NumberType::uint64();
@@ -108,6 +115,10 @@ where
         let rows = self.table.rows(*txn.id()).await?;
         let filled = rows.and_then(|row| future::ready(expect_row(row)));
         let filled: SparseStream = Box::pin(filled);
+
+        #[cfg(debug_assertions)]
+        let filled = validate_order(filled);
+
         Ok(filled)
     }
 
@@ -134,6 +145,25 @@ where
         }
     }
 
+    async fn filled_in<'a>(self, txn: T, bounds: Bounds) -> TCResult<SparseStream<'a>> {
+        debug!("SparseTable::filled_in {}", bounds);
+        self.shape().validate_bounds(&bounds)?;
+
+        let table_bounds = table_bounds(self.shape(), &bounds)?;
+        if table_bounds.is_empty() {
+            return self.filled(txn).await;
+        }
+
+        let rows = self.table.slice(table_bounds)?.rows(*txn.id()).await?;
+        let filled = rows.and_then(|row| future::ready(expect_row(row)));
+        let filled: SparseStream = Box::pin(filled);
+
+        #[cfg(debug_assertions)]
+        let filled = validate_order(filled);
+
+        Ok(filled)
+    }
+
     async fn filled_count(self, txn: T) -> TCResult<u64> {
         self.table.count(*txn.id()).await
     }
@@ -161,7 +191,38 @@ where
 
     async fn write_value(&self, txn_id: TxnId, coord: Coord, value: Number) -> TCResult<()> {
         self.shape().validate_coord(&coord)?;
-        upsert_value(&self.table, txn_id, coord, value).await
+        upsert_value(&self.table, txn_id, coord, value, self.dtype()).await
+    }
+
+    async fn write_values(&self, txn_id: TxnId, values: Vec<(Coord, Number)>) -> TCResult<()> {
+        for (coord, _) in &values {
+            self.shape().validate_coord(coord)?;
+        }
+
+        let (deletes, upserts) = group_writes(values, self.dtype());
+
+        stream::iter(deletes)
+            .map(|coord| {
+                let key = (0..coord.len())
+                    .map(Id::from)
+                    .zip(coord.into_iter().map(u64_into_value))
+                    .collect();
+
+                self.table.delete_row(txn_id, key)
+            })
+            .buffer_unordered(num_cpus::get())
+            .try_fold((), |(), ()| future::ready(Ok(())))
+            .await?;
+
+        let rows = upserts
+            .into_iter()
+            .map(|(coord, value)| {
+                let key = coord.into_iter().map(u64_into_value).collect();
+                (key, vec![Value::Number(value)])
+            })
+            .collect();
+
+        self.table.upsert_all(txn_id, rows).await
     }
 }
 
@@ -202,6 +263,24 @@ where
     }
 }
 
+#[async_trait]
+impl<FD, FS, D, T> Rollback for SparseTable<FD, FS, D, T>
+where
+    FD: File<Array>,
+    FS: File<Node>,
+    D: Dir,
+    T: Transaction<D>,
+    TableIndex<FS, D, T>: Rollback,
+{
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.table.savepoint(txn_id, ordinal).await
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        self.table.rollback_to(txn_id, ordinal).await
+    }
+}
+
 #[async_trait]
 impl<FD, FS, D, T, A> CopyFrom<D, SparseTensor<FD, FS, D, T, A>> for SparseTable<FD, FS, D, T>
 where
@@ -387,6 +466,18 @@ where
         Ok(Box::pin(filled_at))
     }
 
+    async fn filled_in<'a>(self, txn: T, bounds: Bounds) -> TCResult<SparseStream<'a>> {
+        self.shape().validate_bounds(&bounds)?;
+
+        let rebase = self.rebase;
+        let source_bounds = rebase.invert_bounds(bounds);
+        let filled = self.source.filled_in(txn, source_bounds).await?;
+        let filled = filled.map_ok(move |(coord, value)| (rebase.map_coord(coord), value));
+
+        let filled: SparseStream<'a> = Box::pin(filled);
+        Ok(filled)
+    }
+
     async fn filled_count(self, txn: T) -> TCResult<u64> {
         self.table.count(*txn.id()).await
     }
@@ -411,6 +502,18 @@ where
         let source_coord = self.rebase.invert_coord(&coord);
         self.source.write_value(txn_id, source_coord, value).await
     }
+
+    async fn write_values(&self, txn_id: TxnId, values: Vec<(Coord, Number)>) -> TCResult<()> {
+        let values = values
+            .into_iter()
+            .map(|(coord, value)| {
+                self.shape().validate_coord(&coord)?;
+                Ok((self.rebase.invert_coord(&coord), value))
+            })
+            .collect::<TCResult<Vec<_>>>()?;
+
+        self.source.write_values(txn_id, values).await
+    }
 }
 
 impl<FD, FS, D, T> ReadValueAt<D> for SparseTableSlice<FD, FS, D, T>
@@ -527,14 +630,19 @@ async fn read_value_at<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInsta
     coord: Coord,
     dtype: NumberType,
 ) -> TCResult<(Coord, Number)> {
-    let selector: HashMap<Id, ColumnBound> = coord
-        .iter()
-        .enumerate()
-        .map(|(axis, at)| (axis.into(), u64_into_value(*at).into()))
-        .collect();
-
-    let slice = table.slice(selector.into())?.select(vec![VALUE.into()])?;
-    let mut slice = slice.rows(*txn.id()).await?;
+    let mut slice = if coord.is_empty() {
+        // a zero-dimensional tensor has at most one row, with no coordinate columns
+        table.select(vec![VALUE.into()])?.rows(*txn.id()).await?
+    } else {
+        let selector: HashMap<Id, ColumnBound> = coord
+            .iter()
+            .enumerate()
+            .map(|(axis, at)| (axis.into(), u64_into_value(*at).into()))
+            .collect();
+
+        let slice = table.slice(selector.into())?.select(vec![VALUE.into()])?;
+        slice.rows(*txn.id()).await?
+    };
 
     let value = match slice.try_next().await? {
         Some(mut number) => number.pop().unwrap().try_into()?,
@@ -544,15 +652,19 @@ async fn read_value_at<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInsta
     Ok((coord, value))
 }
 
+/// Write `value` at `coord`, deleting the entry instead if `value` casts to zero in `dtype`,
+/// so that a sparse table never stores an explicit zero.
 async fn upsert_value<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInstance<F, D, Txn>>(
     table: &T,
     txn_id: TxnId,
     coord: Coord,
     value: Number,
+    dtype: NumberType,
 ) -> TCResult<()> {
     let coord = coord.into_iter().map(Number::from).map(Value::Number);
+    let value = value.into_type(dtype);
 
-    if value == value.class().zero() {
+    if value == dtype.zero() {
         let key = (0..coord.len()).map(Id::from).zip(coord).collect();
         table.delete_row(txn_id, key).await
     } else {
@@ -561,6 +673,31 @@ async fn upsert_value<F: File<Node>, D: Dir, Txn: Transaction<D>, T: TableInstan
     }
 }
 
+/// Group a batch of writes into the coordinates to delete, because their new value is zero,
+/// and the values to upsert, both in coordinate order. Only the last write to a coordinate is kept.
+fn group_writes(
+    values: Vec<(Coord, Number)>,
+    dtype: NumberType,
+) -> (Vec<Coord>, Vec<(Coord, Number)>) {
+    let zero = dtype.zero();
+    let writes: BTreeMap<Coord, Number> = values
+        .into_iter()
+        .map(|(coord, value)| (coord, value.into_type(dtype)))
+        .collect();
+
+    let mut deletes = Vec::new();
+    let mut upserts = Vec::with_capacity(writes.len());
+    for (coord, value) in writes {
+        if value == zero {
+            deletes.push(coord);
+        } else {
+            upserts.push((coord, value));
+        }
+    }
+
+    (deletes, upserts)
+}
+
 #[inline]
 fn u64_into_value(u: u64) -> Value {
     Value::Number(Number::UInt(UInt::U64(u)))
@@ -572,7 +709,7 @@ fn expect_coord(coord: Vec<Value>) -> TCResult<Coord> {
 }
 
 #[inline]
-fn expect_row(mut row: Vec<Value>) -> TCResult<(Coord, Number)> {
+pub(super) fn expect_row(mut row: Vec<Value>) -> TCResult<(Coord, Number)> {
     if let Some(value) = row.pop() {
         let value = value.try_into()?;
         expect_coord(row).map(|coord| (coord, value))
@@ -589,3 +726,54 @@ fn expect_u64(value: Value) -> TCResult<u64> {
         Err(TCError::bad_request("expected u64 but found", value))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tc_value::{FloatType, UIntType};
+
+    use super::*;
+
+    #[test]
+    fn test_group_writes() {
+        let dtype = NumberType::UInt(UIntType::U64);
+        let values = vec![
+            (vec![1, 0], Number::from(2.)),
+            (vec![0, 1], Number::from(1.)),
+            (vec![1, 0], Number::from(0.)),
+            (vec![0, 0], Number::from(3.)),
+            (vec![0, 1], Number::from(4.)),
+        ];
+
+        let (deletes, upserts) = group_writes(values, dtype);
+
+        // five writes to three coordinates are grouped into one delete and two upserts
+        assert_eq!(deletes, vec![vec![1, 0]]);
+        assert_eq!(
+            upserts
+                .iter()
+                .map(|(coord, _)| coord.to_vec())
+                .collect::<Vec<_>>(),
+            vec![vec![0, 0], vec![0, 1]]
+        );
+
+        assert!(upserts[0].1 == Number::from(3.).into_type(dtype));
+        assert!(upserts[1].1 == Number::from(4.).into_type(dtype));
+
+        // a value which rounds to zero is eliminated
+        let (deletes, upserts) = group_writes(
+            vec![(vec![0], Number::from(0.25))],
+            NumberType::UInt(UIntType::U8),
+        );
+
+        assert_eq!(deletes.len(), 1);
+        assert!(upserts.is_empty());
+
+        let (deletes, upserts) = group_writes(
+            vec![(vec![0], Number::from(0.25))],
+            NumberType::Float(FloatType::F32),
+        );
+
+        assert!(deletes.is_empty());
+        assert_eq!(upserts.len(), 1);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::collections::HashSet;
 use std::convert::TryFrom;
+use std::fmt;
 use std::io;
 use std::ops::{Deref, DerefMut};
 
@@ -12,7 +13,7 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use destream::{de, en};
 use futures::{future, TryFutureExt, TryStreamExt};
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
 use tokio::io::{AsyncReadExt, AsyncWrite};
 use tokio_util::io::StreamReader;
 
@@ -25,6 +26,50 @@ use super::{Transaction, TxnId};
 /// An alias for [`Id`] used for code clarity.
 pub type BlockId = PathSegment;
 
+/// The hash algorithm used to compute the hash of a [`BlockData`].
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum HashAlgo {
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgo {
+    /// Return the `HashAlgo` with the given name, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sha256" => Some(Self::Sha256),
+            "sha512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// The name of this `HashAlgo`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+        }
+    }
+}
+
+impl Default for HashAlgo {
+    fn default() -> Self {
+        Self::Sha256
+    }
+}
+
+impl fmt::Debug for HashAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// The contents of a [`Block`].
 #[async_trait]
 pub trait BlockData: de::FromStream<Context = ()> + Clone + Send + Sync + 'static {
@@ -36,10 +81,17 @@ pub trait BlockData: de::FromStream<Context = ()> + Clone + Send + Sync + 'stati
     where
         Self: en::ToStream<'en>,
     {
-        let mut hasher = Sha256::default();
-        hash_chunks(&mut hasher, self).await?;
-        let digest = hasher.finalize();
-        Ok(Bytes::from(digest.to_vec()))
+        self.hash_with(HashAlgo::default()).await
+    }
+
+    async fn hash_with<'en>(&'en self, algo: HashAlgo) -> TCResult<Bytes>
+    where
+        Self: en::ToStream<'en>,
+    {
+        match algo {
+            HashAlgo::Sha256 => digest::<Sha256, _>(self).await,
+            HashAlgo::Sha512 => digest::<Sha512, _>(self).await,
+        }
     }
 
     async fn load<S: AsyncReadExt + Send + Unpin>(source: S) -> TCResult<Self> {
@@ -275,11 +327,40 @@ pub trait Persist<D: Dir>: Sized {
     type Store: Store;
     type Txn: Transaction<D>;
 
+    /// The current version of the persisted format of this state.
+    const SCHEMA_VERSION: u32 = 1;
+
     /// Return the schema of this persistent state.
     fn schema(&self) -> &Self::Schema;
 
     /// Load a saved state from persistent storage.
     async fn load(txn: &Self::Txn, schema: Self::Schema, store: Self::Store) -> TCResult<Self>;
+
+    /// Migrate a state loaded from persistent storage which was written with an older
+    /// `from_version` of its persisted format to [`Self::SCHEMA_VERSION`].
+    ///
+    /// The default implementation does nothing.
+    async fn migrate(&self, _from_version: u32, _txn: &Self::Txn) -> TCResult<()>
+    where
+        Self: Sync,
+    {
+        Ok(())
+    }
+}
+
+/// Return the version to migrate from, if a state persisted with the `stored` schema version
+/// must be migrated to the `current` schema version, or an error if `stored` is newer.
+pub fn migrate_from(stored: u32, current: u32) -> TCResult<Option<u32>> {
+    if stored < current {
+        Ok(Some(stored))
+    } else if stored == current {
+        Ok(None)
+    } else {
+        Err(TCError::unsupported(format!(
+            "cannot load state with schema version {} (the latest supported version is {})",
+            stored, current
+        )))
+    }
 }
 
 /// Defines how to copy a base state from another instance, possibly a view.
@@ -324,8 +405,17 @@ pub trait Hash<'en, D: Dir> {
     async fn hashable(&'en self, txn: &'en Self::Txn) -> TCResult<TCBoxTryStream<'en, Self::Item>>;
 }
 
-async fn hash_chunks<'en, T: en::IntoStream<'en> + 'en>(
-    hasher: &mut Sha256,
+async fn digest<'en, H: Digest + Default + Send, T: en::IntoStream<'en> + 'en>(
+    data: T,
+) -> TCResult<Bytes> {
+    let mut hasher = H::default();
+    hash_chunks(&mut hasher, data).await?;
+    let digest = hasher.finalize();
+    Ok(Bytes::from(digest.to_vec()))
+}
+
+async fn hash_chunks<'en, H: Digest + Send, T: en::IntoStream<'en> + 'en>(
+    hasher: &mut H,
     data: T,
 ) -> TCResult<()> {
     let mut data = tbon::en::encode(data).map_err(TCError::internal)?;
@@ -335,3 +425,15 @@ async fn hash_chunks<'en, T: en::IntoStream<'en> + 'en>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_from() {
+        assert_eq!(migrate_from(1, 2).unwrap(), Some(1));
+        assert_eq!(migrate_from(2, 2).unwrap(), None);
+        assert!(migrate_from(3, 2).is_err());
+    }
+}
@@ -54,6 +54,11 @@ impl TxnId {
         NetworkTime::from_nanos(self.timestamp)
     }
 
+    /// Return the nonce which orders this `TxnId` among those with the same timestamp.
+    pub fn nonce(&self) -> u16 {
+        self.nonce
+    }
+
     /// Convert this `TxnId` into an [`Id`].
     pub fn to_id(&self) -> Id {
         self.to_string().parse().unwrap()
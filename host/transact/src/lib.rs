@@ -33,6 +33,18 @@ pub trait Transact {
     async fn finalize(&self, txn_id: &TxnId);
 }
 
+/// Savepoint callbacks, to undo some but not all of the writes of a transaction
+#[async_trait]
+pub trait Rollback {
+    /// Mark the savepoint `ordinal` in this transaction, so that the writes made after it can be
+    /// undone by [`Self::rollback_to`].
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()>;
+
+    /// Undo the writes made in this transaction since the savepoint `ordinal`,
+    /// and discard that savepoint and any later ones.
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()>;
+}
+
 /// Common transaction context properties.
 #[async_trait]
 pub trait Transaction<D: fs::Dir>: Clone + Sized + Send + Sync + 'static {
@@ -50,4 +62,8 @@ pub trait Transaction<D: fs::Dir>: Clone + Sized + Send + Sync + 'static {
 
     /// Return a transaction subcontext with its own unique [`fs::Dir`].
     async fn subcontext_tmp(&self) -> TCResult<Self>;
+
+    /// Return the maximum number of blocks of a collection which a single reduction in this
+    /// transaction may process concurrently.
+    fn reduce_concurrency(&self) -> usize;
 }
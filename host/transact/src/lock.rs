@@ -1,11 +1,13 @@
 //! A [`TxnLock`] featuring transaction-specific versioning
 
 use std::cell::UnsafeCell;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use futures::future::Future;
@@ -14,7 +16,86 @@ use log::debug;
 
 use tc_error::*;
 
-use super::{Transact, TxnId};
+use super::{Rollback, Transact, TxnId};
+
+// the maximum number of resources whose conflicts are counted individually
+const MAX_CONTENDED: usize = 1_000;
+
+tokio::task_local! {
+    // the stats to update, and the path of the resource requested, in the current task
+    static SCOPE: (Arc<LockStats>, String);
+}
+
+/// Counters of the conflicts and wait times of the [`TxnLock`]s acquired on behalf of requests,
+/// broken out by the path of the requested resource.
+///
+/// A transaction manager should own its `LockStats` and run each request inside [`Self::scope`].
+#[derive(Default)]
+pub struct LockStats {
+    conflicts: AtomicU64,
+    wait_micros: AtomicU64,
+    contended: Mutex<HashMap<String, u64>>,
+}
+
+impl LockStats {
+    /// Run the given `request` for the resource at `path`, updating these `LockStats` with any
+    /// lock conflicts or wait time it incurs.
+    pub async fn scope<F: Future>(self: Arc<Self>, path: String, request: F) -> F::Output {
+        SCOPE.scope((self, path), request).await
+    }
+
+    /// Return the total number of lock acquisitions which failed with a conflict.
+    pub fn conflicts(&self) -> u64 {
+        self.conflicts.load(Ordering::Relaxed)
+    }
+
+    /// Return the total time spent waiting to acquire a lock.
+    pub fn wait_time(&self) -> Duration {
+        Duration::from_micros(self.wait_micros.load(Ordering::Relaxed))
+    }
+
+    /// Return the path and number of conflicts of each resource which has had a conflict,
+    /// in order of path.
+    ///
+    /// Only the first [`MAX_CONTENDED`] resources to conflict are listed individually,
+    /// but every conflict counts toward [`Self::conflicts`].
+    pub fn contended(&self) -> Vec<(String, u64)> {
+        let contended = self.contended.lock().expect("lock stats");
+        let mut contended: Vec<(String, u64)> = contended
+            .iter()
+            .map(|(path, count)| (path.clone(), *count))
+            .collect();
+
+        contended.sort();
+        contended
+    }
+
+    fn record_conflict(&self, path: &str) {
+        self.conflicts.fetch_add(1, Ordering::Relaxed);
+
+        let mut contended = self.contended.lock().expect("lock stats");
+        if let Some(count) = contended.get_mut(path) {
+            *count += 1;
+        } else if contended.len() < MAX_CONTENDED {
+            contended.insert(path.to_string(), 1);
+        }
+    }
+
+    fn record_wait(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.wait_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+}
+
+// count a conflict toward the `LockStats` of the current request, if any
+fn record_conflict() {
+    let _ = SCOPE.try_with(|(stats, path)| stats.record_conflict(path));
+}
+
+// count lock wait time toward the `LockStats` of the current request, if any
+fn record_wait(elapsed: Duration) {
+    let _ = SCOPE.try_with(|(stats, _)| stats.record_wait(elapsed));
+}
 
 /// An immutable read guard for a transactional state.
 pub struct TxnLockReadGuard<T> {
@@ -28,6 +109,7 @@ impl<T> TxnLockReadGuard<T> {
         TxnLockWriteFuture {
             txn_id: self.txn_id,
             lock: self.lock.clone(),
+            waiting: None,
         }
     }
 }
@@ -146,6 +228,7 @@ struct LockState<T> {
 
     canon: UnsafeCell<T>,
     at: BTreeMap<TxnId, UnsafeCell<T>>,
+    saved: BTreeMap<TxnId, BTreeMap<usize, T>>,
 }
 
 struct Inner<T> {
@@ -177,6 +260,7 @@ impl<T: Clone> TxnLock<T> {
 
             canon: UnsafeCell::new(value),
             at: BTreeMap::new(),
+            saved: BTreeMap::new(),
         };
 
         let inner = Inner {
@@ -207,6 +291,7 @@ impl<T: Clone> TxnLock<T> {
                 txn_id
             );
 
+            record_conflict();
             Err(TCError::conflict())
         } else if let Some(ref past_write) = state.reserved {
             // If a writer can mutate the locked value at the requested time, wait it out.
@@ -238,6 +323,7 @@ impl<T: Clone> TxnLock<T> {
         TxnLockReadFuture {
             txn_id,
             lock: self.clone(),
+            waiting: None,
         }
     }
 
@@ -252,13 +338,17 @@ impl<T: Clone> TxnLock<T> {
         if let Some(latest_read) = state.readers.keys().max() {
             // If there's already a reader in the future, there's no point in waiting.
             if latest_read > txn_id {
+                record_conflict();
                 return Err(TCError::conflict());
             }
         }
 
         match &state.reserved {
             // If there's already a writer in the future, there's no point in waiting.
-            Some(current_txn) if current_txn > txn_id => Err(TCError::conflict()),
+            Some(current_txn) if current_txn > txn_id => {
+                record_conflict();
+                Err(TCError::conflict())
+            }
             // If there's a writer in the past, wait for it to complete.
             Some(current_txn) if current_txn < txn_id => {
                 debug!(
@@ -305,8 +395,22 @@ impl<T: Clone> TxnLock<T> {
         TxnLockWriteFuture {
             txn_id,
             lock: self.clone(),
+            waiting: None,
         }
     }
+
+    /// Return the [`TxnId`] of each pending version of this state which is not currently locked.
+    pub fn idle_versions(&self) -> Vec<TxnId> {
+        let state = self.inner.state.lock().expect("TxnLock versions");
+
+        state
+            .at
+            .keys()
+            .filter(|txn_id| state.reserved.as_ref() != Some(*txn_id))
+            .filter(|txn_id| state.readers.get(*txn_id).copied().unwrap_or(0) == 0)
+            .copied()
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -345,6 +449,36 @@ impl<T: Clone + Send> Transact for TxnLock<T> {
         }
 
         state.at.remove(txn_id);
+        state.saved.remove(txn_id);
+    }
+}
+
+#[async_trait]
+impl<T: Clone + Send> Rollback for TxnLock<T> {
+    async fn savepoint(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        let value = T::clone(&*self.read(txn_id).await?);
+
+        let mut state = self.inner.state.lock().expect("TxnLock savepoint state");
+        let saved = state.saved.entry(*txn_id).or_insert_with(BTreeMap::new);
+        saved.entry(ordinal).or_insert(value);
+        Ok(())
+    }
+
+    async fn rollback_to(&self, txn_id: &TxnId, ordinal: usize) -> TCResult<()> {
+        let saved = {
+            let mut state = self.inner.state.lock().expect("TxnLock rollback state");
+            state
+                .saved
+                .get_mut(txn_id)
+                .and_then(|saved| saved.split_off(&ordinal).remove(&ordinal))
+        };
+
+        if let Some(value) = saved {
+            debug!("TxnLock {} rollback at {}", &self.inner.name, txn_id);
+            *self.write(*txn_id).await? = value;
+        }
+
+        Ok(())
     }
 }
 
@@ -352,16 +486,27 @@ impl<T: Clone + Send> Transact for TxnLock<T> {
 pub struct TxnLockReadFuture<'a, T> {
     txn_id: &'a TxnId,
     lock: TxnLock<T>,
+    waiting: Option<Instant>,
 }
 
 impl<'a, T: Clone> Future for TxnLockReadFuture<'a, T> {
     type Output = TCResult<TxnLockReadGuard<T>>;
 
-    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
-        match self.lock.try_read(&self.txn_id) {
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        let result = self.lock.try_read(&self.txn_id);
+
+        if let Some(waiting) = self.waiting {
+            if !matches!(result, Ok(None)) {
+                record_wait(waiting.elapsed());
+            }
+        }
+
+        match result {
             Ok(Some(guard)) => Poll::Ready(Ok(guard)),
             Err(cause) => Poll::Ready(Err(cause)),
             Ok(None) => {
+                self.waiting.get_or_insert_with(Instant::now);
+
                 self.lock
                     .inner
                     .state
@@ -380,16 +525,27 @@ impl<'a, T: Clone> Future for TxnLockReadFuture<'a, T> {
 pub struct TxnLockWriteFuture<T> {
     txn_id: TxnId,
     lock: TxnLock<T>,
+    waiting: Option<Instant>,
 }
 
 impl<T: Clone> Future for TxnLockWriteFuture<T> {
     type Output = TCResult<TxnLockWriteGuard<T>>;
 
-    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
-        match self.lock.try_write(&self.txn_id) {
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        let result = self.lock.try_write(&self.txn_id);
+
+        if let Some(waiting) = self.waiting {
+            if !matches!(result, Ok(None)) {
+                record_wait(waiting.elapsed());
+            }
+        }
+
+        match result {
             Ok(Some(guard)) => Poll::Ready(Ok(guard)),
             Err(cause) => Poll::Ready(Err(cause)),
             Ok(None) => {
+                self.waiting.get_or_insert_with(Instant::now);
+
                 self.lock
                     .inner
                     .state
@@ -403,3 +559,55 @@ impl<T: Clone> Future for TxnLockWriteFuture<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use tcgeneric::NetworkTime;
+
+    use super::*;
+
+    #[test]
+    fn test_conflict_stats() {
+        let stats = Arc::new(LockStats::default());
+        let lock = TxnLock::new("test conflict stats", 0u64);
+        let earlier = TxnId::new(NetworkTime::from_nanos(1));
+        let later = TxnId::new(NetworkTime::from_nanos(2));
+
+        let guard = lock.try_read(&later).unwrap().expect("read lock");
+
+        // a conflict outside of a request scope is not counted
+        assert!(lock.try_write(&earlier).is_err());
+        assert_eq!(stats.conflicts(), 0);
+
+        let request = async { lock.try_write(&earlier).map(|_| ()) };
+        let result = block_on(stats.clone().scope("/app/example".to_string(), request));
+        assert!(result.is_err());
+
+        std::mem::drop(guard);
+
+        assert_eq!(stats.conflicts(), 1);
+        assert_eq!(stats.contended(), vec![("/app/example".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let lock = TxnLock::new("test rollback", 0u64);
+        let txn_id = TxnId::new(NetworkTime::from_nanos(1));
+
+        block_on(async {
+            *lock.write(txn_id).await.unwrap() = 1;
+            lock.savepoint(&txn_id, 0).await.unwrap();
+            *lock.write(txn_id).await.unwrap() = 2;
+            lock.savepoint(&txn_id, 1).await.unwrap();
+            *lock.write(txn_id).await.unwrap() = 3;
+
+            lock.rollback_to(&txn_id, 0).await.unwrap();
+            assert_eq!(*lock.read(&txn_id).await.unwrap(), 1);
+
+            // the later savepoint was discarded along with the one rolled back to
+            lock.rollback_to(&txn_id, 1).await.unwrap();
+            assert_eq!(*lock.read(&txn_id).await.unwrap(), 1);
+        });
+    }
+}
@@ -50,6 +50,20 @@ impl ValueType {
         }
     }
 
+    /// The value of this type which an explicit [`Value::None`] resets a field to, if any.
+    ///
+    /// Returns `None` if this type has no default and does not allow [`Value::None`].
+    pub fn default_value(&self) -> Option<Value> {
+        match self {
+            Self::Bytes => Some(Value::Bytes(Bytes::new())),
+            Self::Number(nt) => Some(Value::Number(nt.zero())),
+            Self::String => Some(Value::String(String::new())),
+            Self::Tuple => Some(Value::Tuple(Tuple::default())),
+            Self::None | Self::Value => Some(Value::None),
+            Self::Id | Self::Link => None,
+        }
+    }
+
     pub fn try_cast<V>(&self, value: V) -> TCResult<Value>
     where
         Value: From<V>,
@@ -935,6 +949,51 @@ impl<T1: TryCastFrom<Value>, T2: TryCastFrom<Value>, T3: TryCastFrom<Value>> Try
     }
 }
 
+impl<T1, T2, T3, T4> TryCastFrom<Value> for (T1, T2, T3, T4)
+where
+    T1: TryCastFrom<Value>,
+    T2: TryCastFrom<Value>,
+    T3: TryCastFrom<Value>,
+    T4: TryCastFrom<Value>,
+{
+    fn can_cast_from(value: &Value) -> bool {
+        match value {
+            Value::Tuple(tuple) => Self::can_cast_from(tuple),
+            _ => false,
+        }
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        match value {
+            Value::Tuple(tuple) => Self::opt_cast_from(tuple),
+            _ => None,
+        }
+    }
+}
+
+impl<T1, T2, T3, T4, T5> TryCastFrom<Value> for (T1, T2, T3, T4, T5)
+where
+    T1: TryCastFrom<Value>,
+    T2: TryCastFrom<Value>,
+    T3: TryCastFrom<Value>,
+    T4: TryCastFrom<Value>,
+    T5: TryCastFrom<Value>,
+{
+    fn can_cast_from(value: &Value) -> bool {
+        match value {
+            Value::Tuple(tuple) => Self::can_cast_from(tuple),
+            _ => false,
+        }
+    }
+
+    fn opt_cast_from(value: Value) -> Option<Self> {
+        match value {
+            Value::Tuple(tuple) => Self::opt_cast_from(tuple),
+            _ => None,
+        }
+    }
+}
+
 impl<T: Clone + TryCastFrom<Value>> TryCastFrom<Value> for Map<T> {
     fn can_cast_from(value: &Value) -> bool {
         Vec::<(Id, T)>::can_cast_from(value)
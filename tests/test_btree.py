@@ -72,6 +72,43 @@ class BTreeTests(unittest.TestCase):
         result = self.host.post(ENDPOINT, cxt)
         self.assertEqual(result, expected(keys[29:32]))
 
+    def testOrder(self):
+        keys = [(i, num2words(i)) for i in range(100)]
+        random.shuffle(keys)
+
+        def query(schema):
+            cxt = tc.Context()
+            cxt.tree = tc.BTree(schema)
+            cxt.inserts = [cxt.tree.insert(key) for key in keys]
+            cxt.result = tc.After(cxt.inserts, cxt.tree[25:75])
+            return self.host.post(ENDPOINT, cxt)
+
+        small = tc.schema.BTree(*SCHEMA.columns, order=2)
+        self.assertEqual(query(small), query(SCHEMA))
+
+        cxt = tc.Context()
+        cxt.tree = tc.BTree(tc.schema.BTree(*SCHEMA.columns, order=1))
+        cxt.result = cxt.tree.count()
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testCompress(self):
+        keys = [(i, "/app/users/" + num2words(i)) for i in range(100)]
+        random.shuffle(keys)
+
+        def query(schema):
+            cxt = tc.Context()
+            cxt.tree = tc.BTree(schema)
+            cxt.inserts = [cxt.tree.insert(key) for key in keys]
+            cxt.result = tc.After(cxt.inserts, cxt.tree[25:75])
+            return self.host.post(ENDPOINT, cxt)
+
+        compressed = tc.schema.BTree(*SCHEMA.columns, compress=True)
+        self.assertEqual(query(compressed), query(SCHEMA))
+
+        small = tc.schema.BTree(*SCHEMA.columns, order=2, compress=True)
+        self.assertEqual(query(small), query(SCHEMA))
+
     def testDeleteAll(self):
         keys = [(i, num2words(i)) for i in range(100)]
 
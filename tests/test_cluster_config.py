@@ -0,0 +1,52 @@
+import tinychain as tc
+import unittest
+
+from testutils import PORT, start_host
+
+
+CLUSTER = "/app/test/config"
+
+
+class Settings(tc.Cluster, metaclass=tc.Meta):
+    __uri__ = tc.URI(f"http://127.0.0.1:{PORT}" + CLUSTER)
+
+    def _configure(self):
+        self.rev = tc.chain.Sync(tc.UInt(0))
+
+
+class ClusterConfigTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host(
+            "test_cluster_config",
+            [Settings],
+            quorum="all",
+            replica_retries=2,
+            replica_retry_delay=50,
+            replica_concurrency=4,
+            replica_queue_size=8)
+
+    def testGetConfig(self):
+        expected = {
+            "quorum": "all",
+            "retries": 2,
+            "retry_delay": 50,
+            "replicas": 1,
+            "concurrency": 4,
+            "queue_size": 8,
+            "balance_reads": False,
+        }
+
+        self.assertEqual(self.host.get(CLUSTER + "/config"), expected)
+
+    def testConfigIsReadOnly(self):
+        with self.assertRaises(tc.error.MethodNotAllowed):
+            self.host.put(CLUSTER + "/config", value="majority")
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
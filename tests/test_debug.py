@@ -0,0 +1,46 @@
+import tinychain as tc
+import unittest
+
+from testutils import PORT, start_host
+
+
+CLUSTER = "/app/test/debug"
+DEBUG = "/debug"
+SCHEMA = tc.schema.Table(
+    [tc.Column("name", tc.String, 100)],
+    [tc.Column("views", tc.UInt)])
+
+
+class Stats(tc.Cluster, metaclass=tc.Meta):
+    __uri__ = tc.URI(f"http://127.0.0.1:{PORT}" + CLUSTER)
+
+    def _configure(self):
+        self.table = tc.chain.Sync(tc.Table(SCHEMA))
+
+
+class DebugTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_debug", [Stats])
+
+    def testDescribeTable(self):
+        self.host.put(CLUSTER + "/table", ["one"], [1])
+        self.host.put(CLUSTER + "/table", ["two"], [2])
+
+        description = self.host.get(DEBUG, CLUSTER + "/table")
+
+        self.assertIn("name: type String", description)
+        self.assertIn("views: type", description)
+        self.assertIn("rows: 2", description)
+
+    def testDescribeUnknownPath(self):
+        with self.assertRaises(tc.error.NotFound):
+            self.host.get(DEBUG, CLUSTER + "/nonexistent")
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,77 @@
+import tinychain as tc
+import unittest
+
+from testutils import start_host
+
+
+ENDPOINT = "/transact/hypothetical"
+MAX_OP_STEPS = 5
+MAX_OP_STATES = 9
+
+
+@tc.post_op
+def add_three(txn, x: tc.Number) -> tc.Number:
+    txn.a = x + 1
+    txn.b = txn.a + 1
+    return txn.b + 1
+
+
+class OpLimitTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_op_limits", max_op_steps=MAX_OP_STEPS)
+
+    def testWithinStepLimit(self):
+        cxt = tc.Context()
+        cxt.x = tc.Number(1)
+        cxt.y = cxt.x + 1
+        cxt.result = cxt.y * 2
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 4)
+
+    def testExceedStepLimit(self):
+        cxt = tc.Context()
+        cxt.x0 = tc.Number(0)
+        for i in range(1, MAX_OP_STEPS + 2):
+            setattr(cxt, f"x{i}", getattr(cxt, f"x{i - 1}") + 1)
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+class OpStateLimitTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_op_state_limits", max_op_states=MAX_OP_STATES)
+
+    def testWithinStateLimit(self):
+        cxt = tc.Context()
+        cxt.add_three = add_three
+        cxt.result = cxt.add_three(x=1)
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 4)
+
+    def testNestedOpsExceedStateLimit(self):
+        # neither op exceeds the limit on its own, but together they do
+        cxt = tc.Context()
+        cxt.add_three = add_three
+        cxt.w = tc.Number(1)
+        cxt.x = cxt.w + 1
+        cxt.y = cxt.x + 1
+        cxt.z = cxt.y + 1
+        cxt.result = cxt.add_three(x=cxt.z)
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,99 @@
+import re
+import time
+import tinychain as tc
+import unittest
+import urllib.request
+
+from testutils import PORT, start_host
+
+
+CLUSTER = "/app/test/metrics"
+TXN_STATS = "/cluster/txn_stats"
+
+
+class Counter(tc.Cluster, metaclass=tc.Meta):
+    __uri__ = tc.URI(f"http://127.0.0.1:{PORT}" + CLUSTER)
+
+    def _configure(self):
+        self.count = tc.chain.Sync(tc.Number(0))
+
+
+class MetricsTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_metrics", [Counter])
+
+    def testCounters(self):
+        before = self.host.metrics()
+
+        self.assertEqual(self.host.get(CLUSTER + "/count"), 0)
+        self.assertEqual(self.host.get(CLUSTER + "/count"), 0)
+
+        with self.assertRaises(tc.error.NotFound):
+            self.host.get(CLUSTER + "/nonexistent")
+
+        after = self.host.metrics()
+
+        # the first GET /metrics request is counted too
+        self.assertEqual(requests(after, "GET"), requests(before, "GET") + 4)
+        self.assertEqual(errors(after, "not_found"), errors(before, "not_found") + 1)
+
+        self.assertIn('tc_request_duration_seconds_bucket{method="GET",le="+Inf"}', after)
+
+    def testContentType(self):
+        with urllib.request.urlopen(str(self.host.link("/metrics"))) as response:
+            self.assertEqual(response.headers["Content-Type"], "text/plain; version=0.0.4")
+
+    def testTxnStats(self):
+        self.host.put(CLUSTER + "/count", value=1)
+        self.assertEqual(self.host.get(CLUSTER + "/count"), 1)
+
+        stats = self.host.get(TXN_STATS)
+        self.assertEqual(set(stats.keys()), {"conflicts", "contended", "lock_wait"})
+        self.assertEqual(stats["conflicts"], sum(count for _name, count in stats["contended"]))
+        self.assertGreaterEqual(stats["lock_wait"], 0)
+
+        with self.assertRaises(tc.error.NotFound):
+            self.host.get(TXN_STATS, "key")
+
+    def testConflict(self):
+        before = self.host.get(TXN_STATS)
+
+        earlier = tc.host.new_txn_id()
+        time.sleep(0.01)
+        later = tc.host.new_txn_id()
+
+        # a read at a later transaction prevents a write at an earlier one
+        self.host.get(CLUSTER + "/count", txn_id=later)
+        with self.assertRaises(tc.error.Conflict):
+            self.host.put(CLUSTER + "/count", value=2, txn_id=earlier)
+
+        self.host.abort(earlier)
+        self.host.abort(later)
+
+        after = self.host.get(TXN_STATS)
+        self.assertGreater(after["conflicts"], before["conflicts"])
+
+        contended = dict((path, count) for path, count in after["contended"])
+        self.assertGreater(contended[CLUSTER + "/count"], 0)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+def requests(metrics, method):
+    return metric(metrics, "tc_requests_total", "method", method)
+
+
+def errors(metrics, error_type):
+    return metric(metrics, "tc_errors_total", "type", error_type)
+
+
+def metric(metrics, name, label, value):
+    match = re.search(f'^{name}{{{label}="{value}"}} ([0-9]+)$', metrics, re.MULTILINE)
+    return int(match.group(1))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -54,6 +54,23 @@ class InteractionTests(unittest.TestCase):
         self.assertEqual(left.get("/app/balance/left/weight"), 5)
         self.assertEqual(right.get("/app/balance/right/weight"), 15)
 
+        left.stop()
+        right.stop()
+
+    def testNestedRequests(self):
+        # with a single slot per host, the request from right back to left would deadlock
+        # if it had to wait for the slot which the original request to left is holding
+        left = start_host("test_multi_host_nested_left", [Left], high_priority_concurrency=1)
+        right = start_host("test_multi_host_nested_right", [Right], high_priority_concurrency=1)
+
+        left.post("/app/balance/left/weigh", {"weight": 7})
+
+        self.assertEqual(left.get("/app/balance/left/weight"), 7)
+        self.assertEqual(right.get("/app/balance/right/weight"), 13)
+
+        left.stop()
+        right.stop()
+
 
 if __name__ == "__main__":
     unittest.main()
@@ -0,0 +1,220 @@
+import socket
+import tinychain as tc
+import unittest
+
+from testutils import start_host
+
+
+ENDPOINT = "/transact/hypothetical"
+CLASS = "/state/object/class"
+GET_OP = str(tc.uri(tc.op.Get))
+
+
+class OpTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_op")
+
+    def testPartial(self):
+        @tc.post_op
+        def subtract(txn, a: tc.Number, b: tc.Number) -> tc.Number:
+            return a - b
+
+        cxt = tc.Context()
+        cxt.subtract = subtract
+        cxt.ten_minus = cxt.subtract.partial(a=10)
+        cxt.result = cxt.ten_minus(b=3)
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 7)
+
+    def testPartialUnknownParam(self):
+        @tc.post_op
+        def subtract(txn, a: tc.Number, b: tc.Number) -> tc.Number:
+            return a - b
+
+        cxt = tc.Context()
+        cxt.subtract = subtract
+        cxt.result = cxt.subtract.partial(c=10)
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testThen(self):
+        @tc.get_op
+        def square(txn, key: tc.Number) -> tc.Number:
+            return key * key
+
+        @tc.post_op
+        def double(txn, x: tc.Number) -> tc.Number:
+            return x * 2
+
+        cxt = tc.Context()
+        cxt.square = square
+        cxt.double = double
+        cxt.square_then_double = cxt.square.then(cxt.double)
+        cxt.result = cxt.square_then_double(3)
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 18)
+
+    def testThenTooManyParams(self):
+        @tc.get_op
+        def square(txn, key: tc.Number) -> tc.Number:
+            return key * key
+
+        @tc.post_op
+        def subtract(txn, a: tc.Number, b: tc.Number) -> tc.Number:
+            return a - b
+
+        cxt = tc.Context()
+        cxt.square = square
+        cxt.subtract = subtract
+        cxt.result = cxt.square.then(cxt.subtract)
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testIf(self):
+        schema = tc.schema.Table([tc.Column("name", tc.String, 8)], [tc.Column("views", tc.UInt)])
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(schema)
+        cxt.write = tc.If(True, cxt.table.insert(("then",), (1,)), cxt.table.insert(("else",), (2,)))
+        cxt.result = tc.After(cxt.write, [
+            cxt.table.contains(("then",)),
+            cxt.table.contains(("else",)),
+        ])
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), [True, False])
+
+    def testTimeout(self):
+        @tc.timeout(60)
+        @tc.post_op
+        def subtract(txn, a: tc.Number, b: tc.Number) -> tc.Number:
+            return a - b
+
+        cxt = tc.Context()
+        cxt.subtract = subtract
+        cxt.result = cxt.subtract(a=10, b=3)
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 7)
+
+    def testTimeoutExceeded(self):
+        # a host which accepts connections but never responds, so a GET to it never finishes
+        blocking = socket.socket()
+        blocking.bind(("127.0.0.1", 0))
+        blocking.listen()
+        self.addCleanup(blocking.close)
+
+        port = blocking.getsockname()[1]
+
+        @tc.timeout(0.5)
+        @tc.post_op
+        def blocked(txn) -> tc.Value:
+            return tc.ref.Get(tc.URI(f"http://127.0.0.1:{port}/blocked"))
+
+        cxt = tc.Context()
+        cxt.blocked = blocked
+        cxt.result = cxt.blocked()
+
+        with self.assertRaises(tc.error.Timeout):
+            self.host.post(ENDPOINT, cxt)
+
+    def testInvalidTimeout(self):
+        @tc.timeout(-1)
+        @tc.post_op
+        def subtract(txn, a: tc.Number, b: tc.Number) -> tc.Number:
+            return a - b
+
+        cxt = tc.Context()
+        cxt.subtract = subtract
+        cxt.result = cxt.subtract(a=10, b=3)
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testSignature(self):
+        @tc.signature(input={"x": tc.Number}, output=tc.Number)
+        @tc.post_op
+        def double(txn, x: tc.Number) -> tc.Number:
+            return x * 2
+
+        cxt = tc.Context()
+        cxt.double = double
+        cxt.result = cxt.double(x=2)
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 4)
+
+    def testSignatureInvalidInput(self):
+        @tc.signature(input={"x": tc.Number}, output=tc.Number)
+        @tc.post_op
+        def double(txn, x: tc.Number) -> tc.Number:
+            return x * 2
+
+        cxt = tc.Context()
+        cxt.double = double
+        cxt.result = cxt.double(x="two")
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testSignatureInvalidOutput(self):
+        @tc.signature(input={"x": tc.Number}, output=tc.String)
+        @tc.post_op
+        def double(txn, x: tc.Number) -> tc.Number:
+            return x * 2
+
+        cxt = tc.Context()
+        cxt.double = double
+        cxt.result = cxt.double(x=2)
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testContextMetadata(self):
+        @tc.post_op
+        def tenant(txn) -> tc.String:
+            return tc.Map(tc.URI("meta"))["tenant"]
+
+        cxt = tc.Context()
+        cxt.tenant = tenant
+        cxt.result = cxt.tenant()
+
+        actual = self.host.post(ENDPOINT, cxt, metadata={"tenant": "acme"})
+        self.assertEqual(actual, "acme")
+
+    def testContextMetadataReserved(self):
+        @tc.post_op
+        def tenant(txn, meta: tc.Map) -> tc.String:
+            return meta["tenant"]
+
+        cxt = tc.Context()
+        cxt.tenant = tenant
+        cxt.result = cxt.tenant(meta={"tenant": "spoofed"})
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt, metadata={"tenant": "acme"})
+
+    def testDefineClass(self):
+        # a POST to the path of a class itself constructs a new class which extends it
+        proto = {"greet": {GET_OP: ["name", [["greeting", "hello"]]]}}
+        actual = self.host.post(CLASS, proto)
+        self.assertIn("greet", str(actual))
+
+    def testDefineClassWithMalformedOp(self):
+        proto = {"greet": {GET_OP: ["name", [["greeting", "hello"], ["greeting", "goodbye"]]]}}
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(CLASS, proto)
+
+        proto = {"greet": {GET_OP: ["name", [["name", "hello"]]]}}
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(CLASS, proto)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
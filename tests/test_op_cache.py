@@ -0,0 +1,97 @@
+import tinychain as tc
+import unittest
+
+from testutils import PORT, start_host
+
+
+ENDPOINT = "/transact/hypothetical"
+SCOPE = "/read"
+
+
+class Secret(tc.Cluster):
+    __uri__ = tc.URI("/app/secret")
+
+    def _configure(self):
+        self.value = tc.chain.Sync(tc.Int(1))
+
+    @tc.get_method
+    def current(self) -> tc.Int:
+        return self.value
+
+    @tc.get_method
+    def protected(self) -> tc.Int:
+        return tc.After(self.authorize(SCOPE), self.value)
+
+    @tc.put_method
+    def update(self, _txn, value: tc.Int):
+        return self.value.set(value)
+
+
+@tc.get_op
+def read_current(txn, key: tc.Value) -> tc.Int:
+    return tc.use(Secret).current()
+
+
+@tc.get_op
+def read_protected(txn, key: tc.Value) -> tc.Int:
+    return tc.use(Secret).protected()
+
+
+class Reader(tc.Cluster):
+    __uri__ = tc.URI("/app/reader")
+
+    @tc.post_method
+    def read(self, _txn):
+        @tc.post_op
+        def call(txn):
+            txn.read_protected = read_protected
+            return txn.read_protected(None)
+
+        return self.grant(SCOPE, call, {})
+
+
+class OpCacheTests(unittest.TestCase):
+    def setUp(self):
+        self.host = start_host("test_op_cache", [Secret, Reader], op_cache_size=16, op_cache_ttl=60)
+
+    def testPure(self):
+        @tc.get_op
+        def square(txn, key: tc.Number) -> tc.Number:
+            return key * key
+
+        cxt = tc.Context()
+        cxt.square = square
+        cxt.result = cxt.square(3)
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 9)
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 9)
+
+    def testMutableState(self):
+        cxt = tc.Context()
+        cxt.read_current = read_current
+        cxt.result = cxt.read_current(None)
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 1)
+
+        self.host.put("/app/secret/update", None, 2)
+        self.assertEqual(self.host.post(ENDPOINT, cxt), 2)
+
+    def testCallerPermissions(self):
+        link = f"http://127.0.0.1:{PORT}" + tc.uri(Reader)
+        self.host.put("/app/secret/install", link, [SCOPE[1:]])
+
+        self.assertEqual(self.host.post("/app/reader/read"), 1)
+
+        cxt = tc.Context()
+        cxt.read_protected = read_protected
+        cxt.result = cxt.read_protected(None)
+
+        with self.assertRaises(tc.error.Unauthorized):
+            self.host.post(ENDPOINT, cxt)
+
+    def tearDown(self):
+        self.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
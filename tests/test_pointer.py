@@ -0,0 +1,53 @@
+import tinychain as tc
+import unittest
+
+from testutils import PORT, start_host
+
+
+LINK = f"http://127.0.0.1:{PORT}/app/profile"
+
+
+class Profile(tc.Map, metaclass=tc.Meta):
+    __uri__ = tc.URI(LINK) + "/Profile"
+
+
+class ProfileService(tc.Cluster):
+    __uri__ = tc.URI(LINK)
+
+    def _configure(self):
+        self.Profile = Profile
+        self.visits = tc.chain.Sync(tc.Number(0))
+
+    @tc.get_method
+    def profile(self) -> Profile:
+        return Profile({"name": "Alice", "address": {"city": "Paris", "zip": [75, 1]}})
+
+
+class PointerTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_pointer", [ProfileService])
+
+    def testGetNestedField(self):
+        self.assertEqual(self.host.get("/app/profile/profile", pointer="/name"), "Alice")
+        self.assertEqual(self.host.get("/app/profile/profile", pointer="/address/city"), "Paris")
+        self.assertEqual(self.host.get("/app/profile/profile", pointer="/address/zip/0"), 75)
+
+    def testMissingSegment(self):
+        with self.assertRaises(tc.error.NotFound):
+            self.host.get("/app/profile/profile", pointer="/address/street")
+
+        with self.assertRaises(tc.error.NotFound):
+            self.host.get("/app/profile/profile", pointer="/address/zip/2")
+
+    def testPutNestedMember(self):
+        self.host.put("/app/profile", value=3, pointer="/visits")
+        self.assertEqual(self.host.get("/app/profile/visits"), 3)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,57 @@
+import tinychain as tc
+import unittest
+
+from testutils import PORT, start_host
+
+
+CLUSTER = "/app/test/read_only"
+
+
+class Counter(tc.Cluster, metaclass=tc.Meta):
+    __uri__ = tc.URI(f"http://127.0.0.1:{PORT}" + CLUSTER)
+
+    def _configure(self):
+        self.count = tc.chain.Sync(tc.Number(0))
+
+
+class ReadOnlyTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_read_only", [Counter])
+
+    def testRead(self):
+        self.assertEqual(self.host.get(CLUSTER + "/count", read_only=True), 0)
+
+    def testWrite(self):
+        with self.assertRaises(tc.error.MethodNotAllowed):
+            self.host.put(CLUSTER + "/count", None, 1, read_only=True)
+
+        with self.assertRaises(tc.error.MethodNotAllowed):
+            self.host.delete(CLUSTER + "/count", read_only=True)
+
+        self.assertEqual(self.host.get(CLUSTER + "/count"), 0)
+
+        self.host.put(CLUSTER + "/count", None, 2)
+        self.assertEqual(self.host.get(CLUSTER + "/count", read_only=True), 2)
+
+    def testPostWrite(self):
+        schema = tc.schema.Table(
+            [tc.Column("name", tc.String, 512)],
+            [tc.Column("expires_at", tc.U64)]).set_ttl(3600)
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(schema)
+        cxt.purged = cxt.table.purge_expired()
+
+        with self.assertRaises(tc.error.MethodNotAllowed):
+            self.host.post("/transact/hypothetical", cxt, read_only=True)
+
+        self.assertEqual(self.host.post("/transact/hypothetical", cxt), 0)
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
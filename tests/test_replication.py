@@ -1,3 +1,5 @@
+import json
+import requests
 import tinychain as tc
 import unittest
 
@@ -56,6 +58,14 @@ class ChainTests(PersistenceTest, unittest.TestCase):
         actual = hosts[-1].get(cluster_path + "/rev")
         self.assertEqual(actual, 2)
 
+        # the restarted host should report to each replica that it has caught up
+        replica = tc.uri(hosts[-1]) + cluster_path
+        for host in hosts:
+            status = host.get(cluster_path + "/replicas", replica)
+            current, target = status["progress"]
+            self.assertEqual(current, target)
+            self.assertEqual(status["missed"], 0)
+
         # test a distributed write after recovering
         hosts[0].put(cluster_path + "/bump")
 
@@ -64,5 +74,256 @@ class ChainTests(PersistenceTest, unittest.TestCase):
             self.assertEqual(actual, 3)
 
 
+class QueueTests(ChainTests):
+    FLAGS = {"replica_queue_size": 8}
+    NAME = "replica_queue"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+
+        hosts[-1].put(cluster_path + "/bump")
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 1)
+
+        # these writes should be queued for the offline host
+        hosts[-1].stop()
+        hosts[-2].put(cluster_path + "/bump")
+        hosts[0].put(cluster_path + "/bump")
+        for host in hosts[:-1]:
+            self.assertEqual(host.get(cluster_path + "/rev"), 3)
+
+        # the offline host should not have been dropped from the replica set
+        replicas = {}
+        for link in hosts[0].get(cluster_path + "/replicas"):
+            replicas.update(link)
+
+        self.assertIn(str(tc.uri(hosts[-1]) + cluster_path), replicas)
+
+        # the restarted host should replay the writes it missed
+        hosts[-1].start()
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 3)
+
+        hosts[-1].put(cluster_path + "/bump")
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 4)
+
+
+class LaggingReplicaTests(ChainTests):
+    FLAGS = {"replica_queue_size": 8}
+    NAME = "replica_lag"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+        replica = tc.uri(hosts[-1]) + cluster_path
+
+        # each replica should have a report of the progress of the last host to join
+        for host in hosts:
+            status = host.get(cluster_path + "/replicas", replica)
+            current, target = status["progress"]
+            self.assertEqual(current, target)
+            self.assertEqual(status["missed"], 0)
+
+        # the writes queued for an offline host should show how far it lags behind
+        hosts[-1].stop()
+        hosts[0].put(cluster_path + "/bump")
+        hosts[0].put(cluster_path + "/bump")
+
+        status = hosts[0].get(cluster_path + "/replicas", replica)
+        self.assertEqual(status["missed"], 2)
+
+        # and should be cleared once it catches up
+        hosts[-1].start()
+        self.assertEqual(hosts[-1].get(cluster_path + "/rev"), 2)
+
+        status = hosts[0].get(cluster_path + "/replicas", replica)
+        self.assertEqual(status["missed"], 0)
+
+
+class QueuePersistenceTests(ChainTests):
+    FLAGS = {"replica_queue_size": 8}
+    NAME = "replica_queue_persistence"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+
+        hosts[-1].put(cluster_path + "/bump")
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 1)
+
+        # this write should be queued for the offline host
+        hosts[-1].stop()
+        hosts[0].put(cluster_path + "/bump")
+        for host in hosts[:-1]:
+            self.assertEqual(host.get(cluster_path + "/rev"), 2)
+
+        # the queue should survive a restart of the host which holds it
+        hosts[0].stop()
+        hosts[0].start()
+
+        hosts[-1].start()
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 2)
+
+
+class BalancedReadTests(ChainTests):
+    FLAGS = {"replica_queue_size": 8, "balance_reads": True}
+    NAME = "balanced_read"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+        expected = set(str(tc.uri(host) + cluster_path) for host in hosts)
+
+        # control paths are always served by the replica which receives the request
+        for host in hosts:
+            actual = {}
+            for link in host.get(cluster_path + "/replicas"):
+                actual.update(link)
+
+            self.assertEqual(expected, set(actual.keys()))
+
+        hosts[-1].put(cluster_path + "/bump")
+
+        # this write should be queued for the offline host
+        hosts[-1].stop()
+        hosts[0].put(cluster_path + "/bump")
+
+        # a replica which is missing writes should never serve a balanced read
+        hosts[-1].start()
+        for _ in range(len(hosts)):
+            for host in hosts:
+                self.assertEqual(host.get(cluster_path + "/rev"), 2)
+
+
+class QuorumTests(ChainTests):
+    NAME = "replica_quorum"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+
+        # a write which a majority of replicas fails to receive is not committed anywhere
+        hosts[-1].stop()
+        hosts[-2].stop()
+        self.assertRaises(
+            tc.error.TinychainError,
+            lambda: hosts[0].put(cluster_path + "/bump"))
+
+        for host in hosts[:-2]:
+            self.assertEqual(host.get(cluster_path + "/rev"), 0)
+
+        # but a write which only a minority of replicas fails to receive still commits
+        hosts[-2].start()
+        hosts[0].put(cluster_path + "/bump")
+        for host in hosts[:-1]:
+            self.assertEqual(host.get(cluster_path + "/rev"), 1)
+
+        # and the replica which missed it catches up when it rejoins
+        hosts[-1].start()
+        self.assertEqual(hosts[-1].get(cluster_path + "/rev"), 1)
+
+        hosts[-1].put(cluster_path + "/bump")
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 2)
+
+
+class ReplicaFailoverTests(ChainTests):
+    FLAGS = {"replica_queue_size": 8, "balance_reads": True}
+    NAME = "replica_failover"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+        replica = tc.uri(hosts[-1]) + cluster_path
+
+        hosts[0].put(cluster_path + "/bump")
+
+        # a balanced read which lands on a stopped replica is served by another one instead
+        hosts[-1].stop()
+        for _ in range(len(hosts)):
+            for host in hosts[:-1]:
+                self.assertEqual(host.get(cluster_path + "/rev"), 1)
+
+        # a replica cut off from a majority of the replica set refuses a write
+        hosts[-2].stop()
+        hosts[-3].stop()
+        self.assertRaises(
+            tc.error.TinychainError,
+            lambda: hosts[0].put(cluster_path + "/bump"))
+
+        self.assertEqual(hosts[0].get(cluster_path + "/rev"), 1)
+
+        # and the stopped replicas catch up and report their progress once they rejoin
+        for host in hosts[1:]:
+            host.start()
+
+        hosts[0].put(cluster_path + "/bump")
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 2)
+
+            status = host.get(cluster_path + "/replicas", replica)
+            current, target = status["progress"]
+            self.assertEqual(current, target)
+
+
+class EpochTests(ChainTests):
+    NAME = "replica_epoch"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+
+        # every membership change bumps the epoch, and each replica should agree on it
+        epochs = set(host.get(cluster_path + "/replicas/epoch") for host in hosts)
+        self.assertEqual(len(epochs), 1)
+        epoch = epochs.pop()
+        self.assertGreater(epoch, 0)
+
+        # the epoch should survive a restart
+        hosts[0].stop()
+        hosts[0].start()
+        self.assertEqual(hosts[0].get(cluster_path + "/replicas/epoch"), epoch)
+
+        # a write replicated from the other side of a partition, at an older epoch, is rejected
+        url = hosts[1].link(cluster_path + "/rev")
+        params = {"key": json.dumps(None), "epoch": epoch - 1}
+        response = requests.put(url, params=params, data=json.dumps(5))
+        self.assertEqual(response.status_code, 409)
+
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 0)
+
+        # only another replica can move the epoch of the replica set
+        url = hosts[1].link(cluster_path + "/replicas/epoch")
+        response = requests.put(url, params={"key": json.dumps(None)}, data=json.dumps(epoch + 10))
+        self.assertEqual(response.status_code, 401)
+        self.assertEqual(hosts[1].get(cluster_path + "/replicas/epoch"), epoch)
+
+        # a write at the current epoch is replicated as usual
+        hosts[-1].put(cluster_path + "/bump")
+        for host in hosts:
+            self.assertEqual(host.get(cluster_path + "/rev"), 1)
+
+
+class EpochRestartTests(ChainTests):
+    NAME = "replica_epoch_restart"
+
+    def execute(self, hosts):
+        cluster_path = "/app/test/replication"
+
+        hosts[-1].put(cluster_path + "/bump")
+
+        # a host which restarts and rejoins should leave every replica at the same epoch
+        for host in (hosts[-1], hosts[0]):
+            host.stop()
+            host.start()
+
+            epochs = [host.get(cluster_path + "/replicas/epoch") for host in hosts]
+            self.assertEqual(len(set(epochs)), 1, epochs)
+
+        # so that a write from any replica is still accepted by every other replica
+        for i, host in enumerate(hosts):
+            host.put(cluster_path + "/bump")
+            for replica in hosts:
+                self.assertEqual(replica.get(cluster_path + "/rev"), i + 2)
+
+
 if __name__ == "__main__":
     unittest.main()
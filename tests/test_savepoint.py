@@ -0,0 +1,74 @@
+import tinychain as tc
+import unittest
+
+from testutils import PORT, PersistenceTest, start_host
+
+
+CLUSTER = "/app/test/savepoint"
+SAVEPOINT = tc.URI("/transact/savepoint")
+SCHEMA = tc.schema.BTree(tc.Column("number", tc.Int), tc.Column("word", tc.String, 100))
+
+
+def numbers(chain_type):
+    class Numbers(tc.Cluster, metaclass=tc.Meta):
+        __uri__ = tc.URI(f"http://127.0.0.1:{PORT}" + CLUSTER)
+
+        def _configure(self):
+            self.tree = chain_type(tc.BTree(SCHEMA))
+
+        @tc.post_method
+        def insert_and_roll_back(self, txn):
+            txn.a = self.tree.insert((1, "one"))
+            txn.savepoint = tc.After(txn.a, tc.ref.Post(SAVEPOINT, {}))
+            txn.b = tc.After(txn.savepoint, self.tree.insert((2, "two")))
+            txn.rollback = tc.After(txn.b, tc.ref.Delete(SAVEPOINT, txn.savepoint))
+            return tc.After(txn.rollback, self.tree.count())
+
+    return Numbers
+
+
+class SavepointTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_savepoint", [numbers(tc.chain.Sync)])
+
+    def testCreateWithGet(self):
+        # creating a savepoint is a write, so it requires a POST
+        self.assertRaises(tc.error.MethodNotAllowed, lambda: self.host.get("/transact/savepoint"))
+
+    def testRollbackToSavepoint(self):
+        count = self.host.post(CLUSTER + "/insert_and_roll_back")
+        self.assertEqual(count, 1)
+
+        self.assertEqual(self.host.get(CLUSTER + "/tree", (1,)), expected([[1, "one"]]))
+        self.assertEqual(self.host.get(CLUSTER + "/tree", (2,)), expected([]))
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+class ReplicatedSavepointTests(PersistenceTest, unittest.TestCase):
+    NAME = "savepoint"
+    NUM_HOSTS = 2
+
+    def cluster(self, chain_type):
+        return numbers(chain_type)
+
+    def execute(self, hosts):
+        # a write which was replicated to another host can't be rolled back on this host alone
+        with self.assertRaises(tc.error.BadRequest) as context:
+            hosts[0].post(CLUSTER + "/insert_and_roll_back")
+
+        self.assertIn("replicated", str(context.exception))
+
+        for host in hosts:
+            self.assertEqual(host.get(CLUSTER + "/tree", (1,)), expected([]))
+
+
+def expected(rows):
+    return {str(tc.uri(tc.BTree)): [tc.to_json(SCHEMA), rows]}
+
+
+if __name__ == "__main__":
+    unittest.main()
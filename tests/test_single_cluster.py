@@ -11,6 +11,13 @@ class BlockChainTest(tc.Cluster):
         self.rev = tc.chain.Block(tc.Number(0))
 
 
+class Sha512BlockChainTest(tc.Cluster):
+    __uri__ = tc.URI("/app/example/block_sha512")
+
+    def _configure(self):
+        self.rev = tc.chain.Block(tc.Number(0), hash_algo="sha512")
+
+
 class SyncChainTest(tc.Cluster):
     __uri__ = tc.URI("/app/example/sync")
 
@@ -20,7 +27,7 @@ class SyncChainTest(tc.Cluster):
 
 class ClusterTests(unittest.TestCase):
     def setUp(self):
-        self.host = start_host("test_update", [BlockChainTest, SyncChainTest])
+        self.host = start_host("test_update", [BlockChainTest, Sha512BlockChainTest, SyncChainTest])
 
     def _test(self, endpoint):
         def expect(n):
@@ -44,6 +51,9 @@ class ClusterTests(unittest.TestCase):
     def testBlockChain(self):
         self._test("/app/example/block/rev")
 
+    def testBlockChainSha512(self):
+        self._test("/app/example/block_sha512/rev")
+
     def testSyncChain(self):
         self._test("/app/example/sync/rev")
 
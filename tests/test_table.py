@@ -1,4 +1,5 @@
 import random
+import re
 import tinychain as tc
 import unittest
 
@@ -24,6 +25,143 @@ class TableTests(unittest.TestCase):
         count = self.host.post(ENDPOINT, cxt)
         self.assertEqual(count, 1)
 
+    def testAggregate(self):
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert((num2words(i),), (i,)) for i in range(1, 4)]
+        cxt.result = tc.After(cxt.inserts, [
+            cxt.table.aggregate("views", "sum"),
+            cxt.table.aggregate("views", "avg"),
+        ])
+
+        total, avg = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(total, 6)
+        self.assertEqual(avg, 2.)
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert((num2words(i),), (i,)) for i in range(1, 4)]
+        cxt.result = tc.After(cxt.inserts, cxt.table.aggregate("name", "sum"))
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, cxt))
+
+    def testDescribe(self):
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert((num2words(i),), (i,)) for i in range(1, 5)]
+        cxt.result = tc.After(cxt.inserts, cxt.table.describe())
+
+        summary = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(summary["views"], {"count": 4, "min": 1, "max": 4, "mean": 2.5})
+        self.assertEqual(summary["name"]["count"], 4)
+        self.assertTrue(3 <= summary["name"]["distinct"] <= 5)
+
+    def testArchive(self):
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert((num2words(i),), (i,)) for i in range(1, 5)]
+        cxt.archive = tc.After(cxt.inserts, cxt.table.export_archive())
+        cxt.copy = tc.Table.import_archive(cxt.archive)
+        cxt.result = cxt.table.equals(cxt.copy)
+
+        self.assertTrue(self.host.post(ENDPOINT, cxt))
+
+    def testEquals(self):
+        keys = [(num2words(i),) for i in range(3)]
+        values = [(i,) for i in range(3)]
+
+        cxt = tc.Context()
+        cxt.a = tc.Table(SCHEMA)
+        cxt.b = tc.Table(SCHEMA)
+        cxt.c = tc.Table(SCHEMA)
+        cxt.inserts = [
+            [cxt.a.insert(k, v), cxt.b.insert(k, v), cxt.c.insert(k, (v[0] + 1,))]
+            for k, v in zip(keys, values)
+        ]
+        cxt.result = tc.After(cxt.inserts, [cxt.a.equals(cxt.b), cxt.a.equals(cxt.c)])
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [True, False])
+
+    def testStreamingImport(self):
+        num_rows = 10000
+        schema = tc.schema.Table([tc.Column("id", tc.U64)], [tc.Column("views", tc.U64)])
+        rows = ([i, i * 2] for i in range(num_rows))
+
+        actual = self.host.post_stream(
+            str(tc.uri(tc.Table)) + "/copy_from", {"schema": schema}, "source", rows)
+
+        self.assertEqual(actual, expected(schema, [[i, i * 2] for i in range(num_rows)]))
+
+    def testImportOnError(self):
+        schema = tc.schema.Table([tc.Column("id", tc.U64)], [tc.Column("views", tc.U64)])
+        rows = [[0, 0], [1, 2], [2, "many"], [3, 6]]
+        path = str(tc.uri(tc.Table)) + "/copy_from"
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.post_stream(path, {"schema": schema}, "source", iter(rows)))
+
+        actual = self.host.post_stream(
+            path, {"schema": schema, "on_error": "skip"}, "source", iter(rows))
+
+        self.assertEqual(actual["collection"], expected(schema, [[0, 0], [1, 2], [3, 6]]))
+        self.assertEqual(actual["applied"], 3)
+        self.assertEqual(actual["skipped"], 1)
+        self.assertEqual(len(actual["errors"]), 1)
+        self.assertEqual(actual["errors"][0][0], 2)
+
+    def testDigest(self):
+        keys = [(num2words(i),) for i in range(3)]
+        values = [(i,) for i in range(3)]
+
+        cxt = tc.Context()
+        cxt.a = tc.Table(SCHEMA)
+        cxt.b = tc.Table(SCHEMA)
+        cxt.c = tc.Table(SCHEMA)
+        cxt.inserts = [[cxt.a.insert(k, v), cxt.b.insert(k, v), cxt.c.insert(k, v)] for k, v in zip(keys, values)]
+        cxt.extra = tc.After(cxt.inserts, cxt.c.insert(("three",), (3,)))
+        cxt.result = tc.After(cxt.extra, [cxt.a.digest(), cxt.b.digest(), cxt.c.digest()])
+
+        a, b, c = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(a, b)
+        self.assertNotEqual(a, c)
+
+    def testContains(self):
+        count = 5
+        values = [(v,) for v in range(count)]
+        keys = [(num2words(i),) for i in range(count)]
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert(k, v) for k, v in zip(keys, values)]
+        cxt.result = tc.After(cxt.inserts, [
+            cxt.table.contains(("two",)),
+            cxt.table.where({"name": "two"}).count(),
+            cxt.table.contains(("ten",)),
+            cxt.table.where({"name": "ten"}).count(),
+        ])
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [True, 1, False, 0])
+
+    def testProbe(self):
+        count = 5
+        values = [(v,) for v in range(count)]
+        keys = [(num2words(i),) for i in range(count)]
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert(k, v) for k, v in zip(keys, values)]
+        cxt.result = tc.After(cxt.inserts, [
+            cxt.table.probe({"name": "two"}),
+            cxt.table.probe({"name": "ten"}),
+            cxt.table.probe({"views": slice(2, 4)}),
+            cxt.table.probe({"views": slice(10, 20)}),
+        ])
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [True, False, True, False])
+
     def testDelete(self):
         count = 2
         values = [(v,) for v in range(count)]
@@ -180,6 +318,69 @@ class TableTests(unittest.TestCase):
         result = self.host.post(ENDPOINT, cxt)
         self.assertEqual(result, expected(SCHEMA, list([[num2words(i), i] for i in range(10, 20)])))
 
+    def testSelectCoveringIndex(self):
+        count = 50
+        values = [(v,) for v in range(count)]
+        keys = [(num2words(i),) for i in range(count)]
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert(k, v) for k, v in zip(keys, values)]
+        cxt.result = tc.After(cxt.inserts, cxt.table.where({"views": slice(10, 20)}).select("name", "views"))
+
+        schema = tc.schema.Table([tc.Column("name", tc.String, 512)], [tc.Column("views", tc.UInt)])
+        result = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(result, expected(schema, list([[num2words(i), i] for i in range(10, 20)])))
+
+    def testSliceMatches(self):
+        count = 50
+        values = [(v,) for v in range(count)]
+        keys = [(num2words(i),) for i in range(count)]
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert(k, v) for k, v in zip(keys, values)]
+        cxt.result = tc.After(cxt.inserts, cxt.table.where({"name": tc.bound.Matches("^t.+y$")}))
+
+        result = self.host.post(ENDPOINT, cxt)
+        matches = sorted([num2words(i), i] for i in range(count) if re.match("^t.+y$", num2words(i)))
+        self.assertEqual(result, expected(SCHEMA, matches))
+
+    def testSliceOneOf(self):
+        count = 50
+        values = [(v,) for v in range(count)]
+        keys = [(num2words(i),) for i in range(count)]
+        selected = [3, 17, 42]
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert(k, v) for k, v in zip(keys, values)]
+        cxt.result = tc.After(cxt.inserts, cxt.table.where({
+            "name": tc.bound.OneOf(*[num2words(i) for i in selected])}))
+
+        result = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(result, expected(SCHEMA, sorted([num2words(i), i] for i in selected)))
+
+    def testPurgeExpired(self):
+        schema = tc.schema.Table(
+            [tc.Column("name", tc.String, 512)],
+            [tc.Column("expires_at", tc.U64)]).set_ttl(3600)
+
+        future = 2 ** 62
+
+        cxt = tc.Context()
+        cxt.table = tc.Table(schema)
+        cxt.inserts = [
+            cxt.table.insert(("expired",), (1,)),
+            cxt.table.insert(("also expired",), (2,)),
+            cxt.table.insert(("default",), (None,)),
+            cxt.table.insert(("explicit",), (future,)),
+        ]
+        cxt.purged = tc.After(cxt.inserts, cxt.table.purge_expired())
+        cxt.result = tc.After(cxt.purged, tc.Tuple([cxt.purged, cxt.table.count()]))
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), [2, 2])
+
     @classmethod
     def tearDownClass(cls):
         cls.host.stop()
@@ -273,7 +474,7 @@ class ChainTests(PersistenceTest, unittest.TestCase):
             self.assertEqual(actual, row2)
 
         hosts[2].stop()
-        self.assertIsNone(hosts[1].delete("/test/table/table", ["one"]))
+        self.assertEqual(hosts[1].delete("/test/table/table", ["one"]), 1)
         hosts[2].start()
 
         check_replicas()
@@ -282,7 +483,7 @@ class ChainTests(PersistenceTest, unittest.TestCase):
             actual = hosts[i].get("/test/table/table")
             self.assertEqual(actual, expected(SCHEMA, [["two", 2]]), f"host {i}")
 
-        self.assertIsNone(hosts[0].delete("/test/table/table"))
+        self.assertEqual(hosts[0].delete("/test/table/table"), 1)
         for i in range(len(hosts)):
             count = hosts[i].get("/test/table/table/count")
             self.assertEqual(0, count, f"host {i}")
@@ -317,6 +518,543 @@ class ErrorTest(unittest.TestCase):
         self.host.stop()
 
 
+class PatchTests(unittest.TestCase):
+    def setUp(self):
+        schema = tc.schema.Table(
+            [tc.Column("name", tc.String, 100)],
+            [tc.Column("views", tc.UInt), tc.Column("likes", tc.UInt)])
+
+        tagged = tc.schema.Table([tc.Column("name", tc.String, 100)], [tc.Column("tag", tc.Id, 32)])
+
+        class Persistent(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI(f"/test/table")
+
+            def _configure(self):
+                self.table = tc.chain.Block(tc.Table(schema))
+                self.tagged = tc.chain.Block(tc.Table(tagged))
+
+        self.host = start_host("table_patch", [Persistent])
+
+    def testPatch(self):
+        self.host.put("/test/table/table", ["one"], [1, 2])
+        self.assertIsNone(self.host.patch("/test/table/table", ["one"], {"views": 3}))
+
+        actual = self.host.get("/test/table/table", ["one"])
+        self.assertEqual(actual, {"name": "one", "views": 3, "likes": 2})
+
+    def testPatchNull(self):
+        self.host.put("/test/table/table", ["one"], [1, 2])
+        self.assertIsNone(self.host.patch("/test/table/table", ["one"], {"views": None}))
+
+        actual = self.host.get("/test/table/table", ["one"])
+        self.assertEqual(actual, {"name": "one", "views": 0, "likes": 2})
+
+    def testPatchNullWithoutDefault(self):
+        self.host.put("/test/table/tagged", ["one"], ["first"])
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.patch("/test/table/tagged", ["one"], {"tag": None}))
+
+        actual = self.host.get("/test/table/tagged", ["one"])
+        self.assertEqual(actual, {"name": "one", "tag": "first"})
+
+    def testPutPartialRow(self):
+        self.host.put("/test/table/table", ["one"], [1, 2])
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.put("/test/table/table", ["one"], {"views": 3}))
+
+        actual = self.host.get("/test/table/table", ["one"])
+        self.assertEqual(actual, {"name": "one", "views": 1, "likes": 2})
+
+    def testPatchMissing(self):
+        self.assertRaises(
+            tc.error.NotFound,
+            lambda: self.host.patch("/test/table/table", ["two"], {"views": 3}))
+
+    def tearDown(self):
+        self.host.stop()
+
+
+class ChangeTests(unittest.TestCase):
+    def setUp(self):
+        schema = tc.schema.Table(
+            [tc.Column("name", tc.String, 512)], [tc.Column("views", tc.UInt)]
+        ).create_index("views", ["views"]).track_changes()
+
+        class Persistent(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI(f"/test/table")
+
+            def _configure(self):
+                self.table = tc.chain.Block(tc.Table(schema))
+                self.untracked = tc.chain.Block(tc.Table(SCHEMA))
+
+            @tc.get_method
+            def copy_count(self, cxt) -> tc.UInt:
+                cxt.copy = tc.chain.Block.import_archive(self.table.export_archive())
+                return cxt.copy._get("count", rtype=tc.UInt)
+
+        self.host = start_host("table_changes", [Persistent])
+
+    def testChanges(self):
+        self.host.put("/test/table/table", ["one"], [1])
+        self.host.put("/test/table/table", ["two"], [2])
+        self.host.put("/test/table/table", ["one"], [3])
+        self.host.delete("/test/table/table", ["two"])
+
+        actual = self.host.get("/test/table/table/changes")
+        self.assertEqual(actual, [
+            ["insert", {"name": "one", "views": 1}],
+            ["insert", {"name": "two", "views": 2}],
+            ["update", {"name": "one", "views": 1}, {"name": "one", "views": 3}],
+            ["delete", {"name": "two", "views": 2}],
+        ])
+
+    def testArchive(self):
+        self.host.put("/test/table/table", ["one"], [1])
+        self.host.put("/test/table/table", ["two"], [2])
+
+        self.assertEqual(self.host.get("/test/table/copy_count"), 2)
+
+    def testUpdateAll(self):
+        self.host.put("/test/table/table", ["one"], [3])
+        self.host.put("/test/table/table", None, {"views": 0})
+
+        actual = self.host.get("/test/table/table/changes")
+        self.assertEqual(actual, [
+            ["insert", {"name": "one", "views": 3}],
+            ["update", {"name": "one", "views": 3}, {"name": "one", "views": 0}],
+        ])
+
+    def testSince(self):
+        txn_id = tc.host.new_txn_id()
+        self.host.put("/test/table/table", ["one"], [1], txn_id=txn_id)
+        self.host.commit(txn_id)
+
+        self.host.put("/test/table/table", ["two"], [2])
+
+        actual = self.host.get("/test/table/table/changes", txn_id)
+        self.assertEqual(actual, [["insert", {"name": "two", "views": 2}]])
+
+    def testCompact(self):
+        txn_id = tc.host.new_txn_id()
+        self.host.put("/test/table/table", ["one"], [1], txn_id=txn_id)
+        self.host.commit(txn_id)
+
+        self.host.put("/test/table/table", ["two"], [2])
+        self.host.delete("/test/table/table/changes", txn_id)
+
+        actual = self.host.get("/test/table/table/changes")
+        self.assertEqual(actual, [["insert", {"name": "two", "views": 2}]])
+        self.assertEqual(self.host.get("/test/table/table/count"), 2)
+
+    def testUntracked(self):
+        self.host.put("/test/table/untracked", ["one"], [1])
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.get("/test/table/untracked/changes"))
+
+    def tearDown(self):
+        self.host.stop()
+
+
+class SyncChangeTests(unittest.TestCase):
+    def setUp(self):
+        schema = tc.schema.Table([tc.Column("name", tc.String, 512)], [tc.Column("views", tc.UInt)]).track_changes()
+
+        class Persistent(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI(f"/test/table")
+
+            def _configure(self):
+                self.table = tc.chain.Sync(tc.Table(schema))
+
+        self.host = start_host("table_sync_changes", [Persistent])
+
+    def testChanges(self):
+        self.host.put("/test/table/table", ["one"], [1])
+        self.host.delete("/test/table/table", ["one"])
+
+        actual = self.host.get("/test/table/table/changes")
+        self.assertEqual(actual, [
+            ["insert", {"name": "one", "views": 1}],
+            ["delete", {"name": "one", "views": 1}],
+        ])
+
+    def tearDown(self):
+        self.host.stop()
+
+
+class DeleteCountTests(unittest.TestCase):
+    def setUp(self):
+        schema = tc.schema.Table(
+            [tc.Column("user_id", tc.U32), tc.Column("item_id", tc.U32)],
+            [tc.Column("quantity", tc.U32)])
+
+        class Persistent(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI(f"/test/table")
+
+            def _configure(self):
+                self.table = tc.chain.Block(tc.Table(schema))
+
+        self.host = start_host("table_delete_count", [Persistent])
+
+    def testDeleteRow(self):
+        self.host.put("/test/table/table", [1, 0], [1])
+        self.host.put("/test/table/table", [1, 1], [1])
+
+        self.assertEqual(self.host.delete("/test/table/table", [1, 0]), 1)
+        self.assertEqual(self.host.get("/test/table/table/count"), 1)
+
+        # a DELETE request must specify the whole primary key
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.delete("/test/table/table", [1])
+
+    def testDeleteSlice(self):
+        cxt = tc.Context()
+        cxt.table = tc.Table(SCHEMA)
+        cxt.inserts = [cxt.table.insert((num2words(i),), (i,)) for i in range(10)]
+        cxt.deleted = tc.After(cxt.inserts, cxt.table.where({"views": slice(3, 6)})._delete(""))
+        cxt.result = tc.After(cxt.deleted, [cxt.deleted, cxt.table.count()])
+
+        deleted, count = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(deleted, 3)
+        self.assertEqual(count, 7)
+
+    def tearDown(self):
+        self.host.stop()
+
+
+class ForeignKeyTests(unittest.TestCase):
+    def setUp(self):
+        class Store(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI("/test/store")
+
+            def _configure(self):
+                users = tc.schema.Table([tc.Column("user_id", tc.U32)], [tc.Column("name", tc.String, 100)])
+
+                orders = tc.schema.Table(
+                    [tc.Column("order_id", tc.U32)],
+                    [tc.Column("user_id", tc.U32)]
+                ).create_foreign_key("user_id", "/test/store/users", "user_id")
+
+                reviews = tc.schema.Table(
+                    [tc.Column("review_id", tc.U32)],
+                    [tc.Column("order_id", tc.U32)]
+                ).create_foreign_key("order_id", "/test/store/orders", "order_id", "cascade")
+
+                self.users = tc.chain.Block(tc.Table(users))
+                self.orders = tc.chain.Block(tc.Table(orders))
+                self.reviews = tc.chain.Block(tc.Table(reviews))
+
+        self.host = start_host("table_foreign_key", [Store])
+
+    def testInsert(self):
+        self.host.put("/test/store/users", [1], ["one"])
+        self.assertIsNone(self.host.put("/test/store/orders", [1], [1]))
+        self.assertEqual(self.host.get("/test/store/orders/count"), 1)
+
+    def testInsertViolation(self):
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.put("/test/store/orders", [1], [2]))
+
+        self.assertEqual(self.host.get("/test/store/orders/count"), 0)
+
+    def testDeleteRestrict(self):
+        self.host.put("/test/store/users", [1], ["one"])
+        self.host.put("/test/store/orders", [1], [1])
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.delete("/test/store/users", [1]))
+
+        self.assertTrue(self.host.get("/test/store/users/contains", [1]))
+
+    def testDeleteUnreferenced(self):
+        self.host.put("/test/store/users", [1], ["one"])
+        self.host.put("/test/store/users", [2], ["two"])
+        self.host.put("/test/store/orders", [1], [1])
+
+        self.host.delete("/test/store/users", [2])
+        self.assertFalse(self.host.get("/test/store/users/contains", [2]))
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.delete("/test/store/users", [1]))
+
+        self.assertTrue(self.host.get("/test/store/users/contains", [1]))
+
+    def testDeleteCascade(self):
+        self.host.put("/test/store/users", [1], ["one"])
+        self.host.put("/test/store/orders", [1], [1])
+        self.host.put("/test/store/reviews", [1], [1])
+
+        self.host.delete("/test/store/orders", [1])
+        self.assertEqual(self.host.get("/test/store/reviews/count"), 0)
+
+    def testDeleteAllRestrict(self):
+        self.host.put("/test/store/users", [1], ["one"])
+        self.host.put("/test/store/users", [2], ["two"])
+        self.host.put("/test/store/orders", [1], [2])
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.delete("/test/store/users"))
+
+        self.assertEqual(self.host.get("/test/store/users/count"), 2)
+
+    def testDeleteAllCascade(self):
+        self.host.put("/test/store/users", [1], ["one"])
+
+        for order_id in range(3):
+            self.host.put("/test/store/orders", [order_id], [1])
+            self.host.put("/test/store/reviews", [order_id], [order_id])
+
+        self.host.put("/test/store/reviews", [3], [0])
+
+        self.host.delete("/test/store/orders")
+        self.assertEqual(self.host.get("/test/store/orders/count"), 0)
+        self.assertEqual(self.host.get("/test/store/reviews/count"), 0)
+
+    def testCopyViolation(self):
+        self.host.put("/test/store/users", [1], ["one"])
+
+        schema = tc.schema.Table(
+            [tc.Column("order_id", tc.U32)],
+            [tc.Column("user_id", tc.U32)]
+        ).create_foreign_key("user_id", "/test/store/users", "user_id")
+
+        rows = [[1, 1], [2, 2]]
+        path = str(tc.uri(tc.Table)) + "/copy_from"
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.post_stream(path, {"schema": schema}, "source", iter(rows)))
+
+        actual = self.host.post_stream(
+            path, {"schema": schema, "on_error": "skip"}, "source", iter(rows))
+
+        self.assertEqual(actual["applied"], 1)
+        self.assertEqual(actual["skipped"], 1)
+
+    def tearDown(self):
+        self.host.stop()
+
+
+class ForeignKeyValidationTests(unittest.TestCase):
+    def testCrossCluster(self):
+        class Users(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI("/test/users")
+
+            def _configure(self):
+                self.users = tc.chain.Block(tc.Table(tc.schema.Table([tc.Column("user_id", tc.U32)])))
+
+        class Store(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI("/test/store")
+
+            def _configure(self):
+                orders = tc.schema.Table(
+                    [tc.Column("order_id", tc.U32)],
+                    [tc.Column("user_id", tc.U32)]
+                ).create_foreign_key("user_id", "/test/users/users", "user_id")
+
+                self.orders = tc.chain.Block(tc.Table(orders))
+
+        self.assertRaises(RuntimeError, lambda: start_host("table_foreign_key_cluster", [Users, Store]))
+
+    def testNotPrimaryKey(self):
+        class Store(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI("/test/store")
+
+            def _configure(self):
+                users = tc.schema.Table([tc.Column("user_id", tc.U32)], [tc.Column("name", tc.String, 100)])
+
+                orders = tc.schema.Table(
+                    [tc.Column("order_id", tc.U32)],
+                    [tc.Column("name", tc.String, 100)]
+                ).create_foreign_key("name", "/test/store/users", "name")
+
+                self.users = tc.chain.Block(tc.Table(users))
+                self.orders = tc.chain.Block(tc.Table(orders))
+
+        self.assertRaises(RuntimeError, lambda: start_host("table_foreign_key_column", [Store]))
+
+
+class Audited(tc.Cluster, metaclass=tc.Meta):
+    __uri__ = tc.URI("/test/audited")
+
+    def _configure(self):
+        schema = tc.schema.Table([tc.Column("name", tc.String, 100)], [tc.Column("views", tc.UInt)])
+
+        self.table = tc.chain.Block(tc.Table(schema))
+        self.audit = tc.chain.Block(tc.Table(schema))
+
+    @tc.post_method
+    def delete_first(self, txn):
+        return self.table.limit(1)._delete("")
+
+
+class TriggerTests(unittest.TestCase):
+    def setUp(self):
+        self.host = start_host("table_trigger", [Audited], cache_size="1M")
+
+    def testAfterInsert(self):
+        @tc.post_op
+        def audit(txn, name: tc.String, views: tc.UInt):
+            return tc.use(Audited).audit.upsert([name], [views])
+
+        self.host.put("/test/audited/table/trigger", "after_insert", audit)
+        self.host.put("/test/audited/table", ["one"], [1])
+
+        self.assertEqual(self.host.get("/test/audited/audit", ["one"]), {"name": "one", "views": 1})
+
+    def testBeforeInsert(self):
+        @tc.post_op
+        def check(txn, views: tc.UInt):
+            return tc.If(views > 10, tc.error.BadRequest("too many views"), None)
+
+        self.host.put("/test/audited/table/trigger", "before_insert", check)
+        self.host.put("/test/audited/table", ["one"], [1])
+
+        self.assertRaises(
+            tc.error.BadRequest,
+            lambda: self.host.put("/test/audited/table", ["two"], [100]))
+
+        self.assertEqual(self.host.get("/test/audited/table/count"), 1)
+
+    def testAfterDelete(self):
+        @tc.post_op
+        def audit(txn, name: tc.String, views: tc.UInt):
+            return tc.use(Audited).audit.upsert([name], [views])
+
+        self.host.put("/test/audited/table", ["one"], [1])
+        self.host.put("/test/audited/table", ["two"], [2])
+        self.host.put("/test/audited/table/trigger", "after_delete", audit)
+
+        self.assertEqual(self.host.delete("/test/audited/table"), 2)
+        self.assertEqual(self.host.get("/test/audited/table/count"), 0)
+        self.assertEqual(self.host.get("/test/audited/audit/count"), 2)
+
+    def testAfterDeleteLimit(self):
+        @tc.post_op
+        def audit(txn, name: tc.String, views: tc.UInt):
+            return tc.use(Audited).audit.upsert([name], [views])
+
+        self.host.put("/test/audited/table", ["one"], [1])
+        self.host.put("/test/audited/table", ["two"], [2])
+        self.host.put("/test/audited/table/trigger", "after_delete", audit)
+
+        self.host.post("/test/audited/delete_first")
+        self.assertEqual(self.host.get("/test/audited/table/count"), 1)
+        self.assertEqual(self.host.get("/test/audited/audit/count"), 1)
+
+    def testPersisted(self):
+        @tc.post_op
+        def audit(txn, name: tc.String, views: tc.UInt):
+            return tc.use(Audited).audit.upsert([name], [views])
+
+        self.host.put("/test/audited/table/trigger", "after_insert", audit)
+
+        self.host.stop()
+        self.host.start(wait_time=3)
+
+        self.host.put("/test/audited/table", ["one"], [1])
+        self.assertEqual(self.host.get("/test/audited/audit", ["one"]), {"name": "one", "views": 1})
+
+    def tearDown(self):
+        self.host.stop()
+
+
+class People(tc.Cluster, metaclass=tc.Meta):
+    __uri__ = tc.URI("/test/people")
+
+    def _configure(self):
+        schema = tc.schema.Table(
+            [tc.Column("id", tc.U64)], [tc.Column("first", tc.String, 100), tc.Column("last", tc.String, 100)]
+        ).create_index("last", ["last"]).create_computed_column("full_name", tc.String, "/test/people/full_name")
+
+        self.people = tc.chain.Block(tc.Table(schema))
+
+    @tc.post_method
+    def full_name(self, txn, first: tc.String, last: tc.String) -> tc.String:
+        return tc.String("{} {}").format([first, last])
+
+    @tc.get_method
+    def by_last_name(self) -> tc.Table:
+        return self.people.order_by(["last"])
+
+    @tc.get_method
+    def full_names(self) -> tc.Table:
+        return self.people.select("id", "full_name")
+
+    @tc.get_method
+    def lovelace(self) -> tc.Table:
+        return self.people.where({"last": "Lovelace"})
+
+
+class ComputedColumnTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("table_computed_column", [People])
+        cls.host.put("/test/people/people", [1], ["Grace", "Hopper"])
+        cls.host.put("/test/people/people", [2], ["Ada", "Lovelace"])
+
+    def testRead(self):
+        self.assertEqual(rows(self.host.get("/test/people/people")), [
+            [1, "Grace", "Hopper", "Grace Hopper"],
+            [2, "Ada", "Lovelace", "Ada Lovelace"],
+        ])
+
+    def testOrder(self):
+        self.assertEqual(rows(self.host.get("/test/people/by_last_name")), [
+            [1, "Grace", "Hopper", "Grace Hopper"],
+            [2, "Ada", "Lovelace", "Ada Lovelace"],
+        ])
+
+    def testSelect(self):
+        self.assertEqual(rows(self.host.get("/test/people/full_names")), [[1, "Grace Hopper"], [2, "Ada Lovelace"]])
+
+    def testSlice(self):
+        self.assertEqual(rows(self.host.get("/test/people/lovelace")), [[2, "Ada", "Lovelace", "Ada Lovelace"]])
+
+    def testNotStored(self):
+        self.assertEqual(self.host.get("/test/people/people", [2]), {"id": 2, "first": "Ada", "last": "Lovelace"})
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+class ComputedColumnValidationTests(unittest.TestCase):
+    def testIndexed(self):
+        class Indexed(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI("/test/indexed")
+
+            def _configure(self):
+                schema = tc.schema.Table([tc.Column("id", tc.U64)], [tc.Column("name", tc.String, 100)])
+                schema.create_computed_column("upper", tc.String, "/test/indexed/upper")
+                schema.create_index("upper", ["upper"])
+                self.table = tc.chain.Block(tc.Table(schema))
+
+        self.assertRaises(RuntimeError, lambda: start_host("table_computed_column_indexed", [Indexed]))
+
+    def testMissingOp(self):
+        class Missing(tc.Cluster, metaclass=tc.Meta):
+            __uri__ = tc.URI("/test/missing")
+
+            def _configure(self):
+                schema = tc.schema.Table([tc.Column("id", tc.U64)], [tc.Column("name", tc.String, 100)])
+                schema.create_computed_column("upper", tc.String, "/test/missing/upper")
+                self.table = tc.chain.Block(tc.Table(schema))
+
+        self.assertRaises(RuntimeError, lambda: start_host("table_computed_column_missing", [Missing]))
+
+
+def rows(table):
+    return table[str(tc.uri(tc.Table))][1]
+
+
 def expected(schema, rows):
     return {str(tc.uri(tc.Table)): [tc.to_json(schema), rows]}
 
@@ -1,5 +1,6 @@
 import itertools
 import numpy as np
+import re
 import tinychain as tc
 import unittest
 
@@ -26,6 +27,32 @@ class DenseTests(unittest.TestCase):
         actual = self.host.post(ENDPOINT, cxt)
         self.assertEqual(expected, actual)
 
+    def testZeroDimensional(self):
+        cxt = tc.Context()
+        cxt.scalar = tc.tensor.Dense.constant([], 2)
+        cxt.result = tc.After(cxt.scalar.write(5), cxt.scalar)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I64, [], [5]))
+
+        for reduce in ["sum", "product"]:
+            cxt = tc.Context()
+            cxt.scalar = tc.tensor.Dense.constant([], 2)
+            cxt.result = tc.After(cxt.scalar.write(5), getattr(cxt.scalar, reduce)())
+
+            actual = self.host.post(ENDPOINT, cxt)
+            self.assertEqual(actual, 5)
+
+    def testArchive(self):
+        shape = [2, 3]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.arange(shape, 1, 7)
+        cxt.result = tc.tensor.Dense.import_archive(cxt.tensor.export_archive())
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I64, shape, range(1, 7)))
+
     def testSlice(self):
         shape = [2, 5]
 
@@ -51,6 +78,101 @@ class DenseTests(unittest.TestCase):
 
         self.assertEqual(actual, expected)
 
+    def testSetWhere(self):
+        shape = [3, 3]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.zeros(shape, tc.I32)
+        cxt.mask = tc.tensor.Sparse.zeros(shape, tc.Bool)
+        cxt.result = tc.After(
+            [cxt.mask[0].write(True), cxt.mask[1, 1].write(True)],
+            tc.After(cxt.tensor.set_where(cxt.mask, 7), cxt.tensor))
+
+        actual = self.host.post(ENDPOINT, cxt)
+
+        expected = np.zeros(shape, np.int32)
+        expected[0] = 7
+        expected[1, 1] = 7
+        expected = expect_dense(tc.I32, shape, expected.flatten())
+
+        self.assertEqual(actual, expected)
+
+    def testCastSaturating(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.load([4], tc.I32, [-5, 7, 255, 300])
+        cxt.result = cxt.tensor.cast(tc.U8, saturate=True)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.U8, [4], [0, 7, 255, 255]))
+
+    def testWriteScalar(self):
+        shape = [3, 4]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.zeros(shape, tc.I32)
+        cxt.result = tc.After(cxt.tensor[1:3, 1:3].write(5), cxt.tensor)
+
+        actual = self.host.post(ENDPOINT, cxt)
+
+        expected = np.zeros(shape, np.int32)
+        expected[1:3, 1:3] = 5
+        expected = expect_dense(tc.I32, shape, expected.flatten())
+
+        self.assertEqual(actual, expected)
+
+    def testMaskedFill(self):
+        shape = [2, 3]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.arange(shape, 0, 6)
+        cxt.mask = tc.tensor.Dense.load([3], tc.Bool, [True, False, True])
+        cxt.result = tc.After(cxt.tensor.masked_fill(cxt.mask, -1), cxt.tensor)
+
+        actual = self.host.post(ENDPOINT, cxt)
+
+        expected = np.arange(0, 6).reshape(shape)
+        expected[:, [0, 2]] = -1
+        expected = expect_dense(tc.I64, shape, expected.flatten())
+
+        self.assertEqual(actual, expected)
+
+    def testTakeAlongAxis(self):
+        shape = [3, 4]
+        data = np.array([[3, 9, 1, 4], [7, 2, 8, 0], [5, 6, 4, 11]])
+        argmax = np.array([[1], [2], [3]])
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.load(shape, tc.I64, data.flatten().tolist())
+        cxt.indices = tc.tensor.Dense.load([3, 1], tc.U64, argmax.flatten().tolist())
+        cxt.result = cxt.tensor.take_along_axis(cxt.indices, axis=1)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        expected = expect_dense(tc.I64, [3, 1], [9, 8, 11])
+        self.assertEqual(actual, expected)
+
+    def testRepeatInterleave(self):
+        data = np.array([[1, 2, 3]])
+
+        for axis in range(2):
+            cxt = tc.Context()
+            cxt.tensor = tc.tensor.Dense.load([1, 3], tc.I32, data.flatten().tolist())
+            cxt.result = cxt.tensor.repeat_interleave(2, axis)
+
+            actual = self.host.post(ENDPOINT, cxt)
+            expected = np.repeat(data, 2, axis)
+            self.assertEqual(actual, expect_dense(tc.I32, list(expected.shape), expected.flatten()))
+
+    def testTopK(self):
+        data = [[3, 1, 4, 1], [5, 9, 2, 6], [5, 3, 5, 8]]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.load([3, 4], tc.I32, [n for row in data for n in row])
+        cxt.result = cxt.tensor.topk(2, axis=1)
+
+        values, indices = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(values, expect_dense(tc.I32, [3, 2], [4, 3, 9, 6, 8, 5]))
+        self.assertEqual(indices, expect_dense(tc.U64, [3, 2], [2, 0, 1, 3, 3, 0]))
+
     def testAdd(self):
         cxt = tc.Context()
         cxt.left = tc.tensor.Dense.arange([5, 2, 2], 1., 21.)
@@ -77,6 +199,52 @@ class DenseTests(unittest.TestCase):
         expected = expect_dense(tc.F64, shape, np.arange(1, 4))
         self.assertEqual(actual, expected)
 
+    def testDivByZero(self):
+        def divide(non_finite):
+            cxt = tc.Context()
+            cxt.left = tc.tensor.Dense.load([3], tc.F64, [1., 2., 3.])
+            cxt.right = tc.tensor.Dense.load([3], tc.F64, [1., 0., 1.])
+            cxt.result = cxt.left.div(cxt.right, non_finite)
+            return cxt
+
+        cxt = divide("propagate")
+        cxt.big = tc.tensor.Dense.constant([3], 1000.)
+        cxt.any_inf = cxt.result.gt(cxt.big).any()
+        self.assertTrue(self.host.post(ENDPOINT, cxt))
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, divide("error")))
+
+        actual = self.host.post(ENDPOINT, divide("zero"))
+        self.assertEqual(actual, expect_dense(tc.F64, [3], [1., 0., 3.]))
+
+    def testCheckedAdd(self):
+        big = 2 ** 63
+
+        def add(checked):
+            cxt = tc.Context()
+            cxt.left = tc.tensor.Dense.load([2], tc.U64, [big, 1])
+            cxt.right = tc.tensor.Dense.load([2], tc.U64, [big, 2])
+            cxt.result = cxt.left.add(cxt.right, checked=checked)
+            return cxt
+
+        actual = self.host.post(ENDPOINT, add(False))
+        self.assertEqual(actual, expect_dense(tc.U64, [2], [0, 3]))
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, add(True)))
+
+    def testCheckedSub(self):
+        def sub(checked):
+            cxt = tc.Context()
+            cxt.left = tc.tensor.Dense.load([2], tc.U64, [3, 1])
+            cxt.right = tc.tensor.Dense.load([2], tc.U64, [2, 2])
+            cxt.result = cxt.left.sub(cxt.right, checked=checked)
+            return cxt
+
+        actual = self.host.post(ENDPOINT, sub(False))
+        self.assertEqual(actual, expect_dense(tc.U64, [2], [1, 2 ** 64 - 1]))
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, sub(True)))
+
     def testMul(self):
         shape = [5, 2, 1]
 
@@ -93,6 +261,16 @@ class DenseTests(unittest.TestCase):
         expected = expect_dense(tc.I64, list(expected.shape), expected.flatten())
         self.assertEqual(actual, expected)
 
+    def testMaximumMinimum(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.load([2, 2], tc.I32, [-3, 2, 0, -1])
+        cxt.b = tc.tensor.Dense.load([2, 2], tc.I32, [1, -2, -4, -1])
+        cxt.result = [cxt.a.maximum(cxt.b), cxt.a.minimum(cxt.b)]
+
+        maximum, minimum = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(maximum, expect_dense(tc.I32, [2, 2], [1, 2, 0, -1]))
+        self.assertEqual(minimum, expect_dense(tc.I32, [2, 2], [-3, -2, -4, -1]))
+
     def testSub(self):
         shape = [1, 3]
 
@@ -169,6 +347,279 @@ class DenseTests(unittest.TestCase):
         actual = self.host.post(ENDPOINT, cxt)
         self.assertEqual(actual, sum(range(10)))
 
+    def testSumAllComplex(self):
+        cxt = tc.Context()
+        cxt.complex = tc.tensor.Dense.constant([2, 3], [1., 2.])
+        cxt.result = cxt.complex.sum()
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, {str(tc.uri(tc.C64)): [6., 12.]})
+
+    def testCompareComplex(self):
+        cxt = tc.Context()
+        cxt.complex = tc.tensor.Dense.constant([2, 3], [1., 2.])
+        cxt.result = cxt.complex > cxt.complex
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testFFT(self):
+        n = 16
+        freq = 3
+        signal = np.cos(2 * np.pi * freq * np.arange(n) / n)
+
+        cxt = tc.Context()
+        cxt.signal = tc.tensor.Dense.load([n], tc.F64, signal.tolist())
+        cxt.spectrum = cxt.signal.fft()
+        cxt.result = tc.Tuple([cxt.spectrum.abs(), cxt.spectrum.ifft().abs()])
+
+        magnitude, round_trip = self.host.post(ENDPOINT, cxt)
+        magnitude = np.array(magnitude[str(tc.uri(tc.tensor.Dense))][1])
+        round_trip = np.array(round_trip[str(tc.uri(tc.tensor.Dense))][1])
+
+        self.assertEqual(np.argmax(magnitude[:n // 2 + 1]), freq)
+        self.assertTrue(np.allclose(round_trip, np.abs(signal)))
+
+    def testFFTRequires1D(self):
+        cxt = tc.Context()
+        cxt.matrix = tc.tensor.Dense.ones([2, 2])
+        cxt.result = cxt.matrix.fft()
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testSumAllLarge(self):
+        # spans multiple blocks, so the blocks are reduced in parallel
+        shape = [1024, 1024]
+
+        cxt = tc.Context()
+        cxt.big = tc.tensor.Dense.arange(shape, 0, 1024 * 1024)
+        cxt.result = cxt.big.sum()
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, sum(range(1024 * 1024)))
+
+    def testMeanVarStd(self):
+        shape = [2, 4]
+        data = [1, 3, 5, 7, 2, 2, 2, 2]
+
+        for name, expected in [("mean", [4., 2.]), ("var", [5., 0.])]:
+            cxt = tc.Context()
+            cxt.big = tc.tensor.Dense.load(shape, tc.I32, data)
+            cxt.result = getattr(cxt.big, name)(1)
+
+            actual = self.host.post(ENDPOINT, cxt)
+            self.assertEqual(actual, expect_dense(tc.F64, [2], expected))
+
+        for name, expected in [("mean", 3.), ("var", 3.5), ("std", 3.5 ** 0.5)]:
+            cxt = tc.Context()
+            cxt.big = tc.tensor.Dense.load(shape, tc.I32, data)
+            cxt.result = getattr(cxt.big, name)()
+
+            actual = self.host.post(ENDPOINT, cxt)
+            self.assertAlmostEqual(actual, expected)
+
+    def testClipByNorm(self):
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Dense.load([2], tc.F64, [3., 4.])
+        cxt.clipped = cxt.x.clip_by_norm(2.5)
+        cxt.result = [cxt.clipped, cxt.clipped.dot(cxt.clipped)]
+
+        clipped, norm_squared = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(clipped, expect_dense(tc.F64, [2], [1.5, 2.]))
+        self.assertAlmostEqual(norm_squared, 2.5 ** 2)
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Dense.load([2], tc.F64, [3., 4.])
+        cxt.result = cxt.x.clip_by_norm(10)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.F64, [2], [3., 4.]))
+
+    def testNormalize(self):
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Dense.load([2, 2], tc.F64, [3., 4., 1., 1.])
+        cxt.normalized = cxt.x.normalize(axis=1)
+        cxt.result = (cxt.normalized * cxt.normalized).sum(1)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        for norm_squared in actual[str(tc.uri(tc.tensor.Dense))][1]:
+            self.assertAlmostEqual(norm_squared, 1.)
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Dense.load([2, 2], tc.F64, [3., -4., 0., 0.])
+        cxt.result = cxt.x.normalize(axis=1, norm="max")
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.F64, [2, 2], [0.75, -1., 0., 0.]))
+
+        cxt.result = cxt.x.normalize(axis=1, norm="l1", strict=True)
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, cxt))
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Dense.load([2], tc.F64, [3., 4.])
+        cxt.result = cxt.x.normalize(axis=0)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.F64, [2], [0.6, 0.8]))
+
+    def testConv2d(self):
+        cxt = tc.Context()
+        cxt.input = tc.tensor.Dense.arange([4, 4], 0, 16)
+        cxt.kernel = tc.tensor.Dense.load([3, 3], tc.I32, [1, 0, -1, 1, 0, -1, 1, 0, -1])
+        cxt.result = cxt.input.conv2d(cxt.kernel)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.F64, [2, 2], [-6., -6., -6., -6.]))
+
+        cxt.result = cxt.input.conv2d(cxt.kernel, stride=(2, 2), padding=(1, 1))
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.F64, [2, 2], [-6., -4., -27., -6.]))
+
+    def testKron(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.load([2, 2], tc.I32, [1, 2, 3, 4])
+        cxt.b = tc.tensor.Dense.load([2, 2], tc.I32, [0, 5, 6, 7])
+        cxt.result = cxt.a.kron(cxt.b)
+
+        expected = [
+            0, 5, 0, 10,
+            6, 7, 12, 14,
+            0, 15, 0, 20,
+            18, 21, 24, 28,
+        ]
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I32, [4, 4], expected))
+
+    def testKronEmpty(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.zeros([0, 2], tc.I32)
+        cxt.b = tc.tensor.Dense.load([2, 2], tc.I32, [0, 5, 6, 7])
+        cxt.result = cxt.a.kron(cxt.b)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I32, [0, 4], []))
+
+    def testMatmul(self):
+        a = np.arange(12).reshape([2, 2, 3])
+        b = np.arange(12).reshape([3, 4])
+
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.load([2, 2, 3], tc.I32, a.flatten().tolist())
+        cxt.b = tc.tensor.Dense.load([3, 4], tc.I32, b.flatten().tolist())
+        cxt.result = cxt.a.matmul(cxt.b)
+
+        expected = np.matmul(a, b)
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I32, [2, 2, 4], expected.flatten().tolist()))
+
+    def testMatmulEncoded(self):
+        # a product large enough to be encoded as a stream of several blocks
+        a = np.arange(128 * 64).reshape([128, 64]) % 7
+        b = np.arange(64 * 128).reshape([64, 128]) % 5
+
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.load([128, 64], tc.I64, a.flatten().tolist())
+        cxt.b = tc.tensor.Dense.load([64, 128], tc.I64, b.flatten().tolist())
+        cxt.result = cxt.a.matmul(cxt.b)
+
+        expected = np.matmul(a, b)
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I64, [128, 128], expected.flatten().tolist()))
+
+    def testMatmulChained(self):
+        a = np.arange(12).reshape([2, 2, 3])
+        b = np.arange(12).reshape([3, 4])
+
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.load([2, 2, 3], tc.I32, a.flatten().tolist())
+        cxt.b = tc.tensor.Dense.load([3, 4], tc.I32, b.flatten().tolist())
+        cxt.c = tc.tensor.Dense.ones([2, 2, 4], tc.I32)
+        cxt.product = cxt.a.matmul(cxt.b)
+        cxt.result = cxt.c.add(cxt.product)
+
+        expected = np.matmul(a, b) + 1
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I32, [2, 2, 4], expected.flatten().tolist()))
+
+    def testMatmulShapeMismatch(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.ones([2, 3])
+        cxt.b = tc.tensor.Dense.ones([2, 3])
+        cxt.result = cxt.a.matmul(cxt.b)
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, cxt))
+
+    def testDot(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.load([4], tc.I32, [1, 2, 3, 4])
+        cxt.b = tc.tensor.Dense.load([4], tc.I32, [2, 0, -1, 5])
+        cxt.result = cxt.a.dot(cxt.b)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, 19)
+
+    def testExpAndLog(self):
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Dense.load([3], tc.F64, [0., 1., 2.])
+        cxt.result = [cxt.x.exp(), cxt.x.exp().ln()]
+
+        exp, ln = self.host.post(ENDPOINT, cxt)
+        self.assertTrue(np.allclose(exp[str(tc.uri(tc.tensor.Dense))][1], np.exp([0., 1., 2.])))
+        self.assertTrue(np.allclose(ln[str(tc.uri(tc.tensor.Dense))][1], [0., 1., 2.]))
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Dense.load([3], tc.I32, [1, 10, 100])
+        cxt.result = cxt.x.log10()
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.F64, [3], [0., 1., 2.]))
+
+    def testNanToNum(self):
+        cxt = tc.Context()
+        cxt.left = tc.tensor.Dense.load([4], tc.F64, [0., 1., -1., 2.])
+        cxt.right = tc.tensor.Dense.load([4], tc.F64, [0., 0., 0., 1.])
+        cxt.quotient = cxt.left.div(cxt.right)
+        cxt.result = cxt.quotient.nan_to_num(nan=-1., posinf=100., neginf=-100.)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.F64, [4], [-1., 100., -100., 2.]))
+
+    def testFlatten(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.arange([2, 3], 0, 6)
+        cxt.result = cxt.tensor.flatten()
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I64, [6], list(range(6))))
+
+    def testSqueeze(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.arange([1, 3, 1], 0, 3)
+        cxt.result = cxt.tensor.squeeze()
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I64, [3], list(range(3))))
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.arange([1, 3, 1], 0, 3)
+        cxt.result = cxt.tensor.squeeze(0)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.I64, [3, 1], list(range(3))))
+
+    def testRoll(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Dense.arange([1, 4], 0, 4)
+        cxt.result = [cxt.tensor.roll(1, 1), cxt.tensor.roll(-1, 1)]
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [
+            expect_dense(tc.I64, [1, 4], [3, 0, 1, 2]),
+            expect_dense(tc.I64, [1, 4], [1, 2, 3, 0]),
+        ])
+
     def testSliceAndTransposeAndSliceAndSlice(self):
         self.maxDiff = None
         shape = [2, 3, 4, 5]
@@ -212,6 +663,136 @@ class SparseTests(unittest.TestCase):
         expected = expect_sparse(tc.I32, shape, [[coord, value]])
         self.assertEqual(actual, expected)
 
+    def testArchive(self):
+        shape = [2, 5]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros(shape, tc.I32)
+        cxt.archive = tc.After(cxt.tensor[1, 3].write(4), cxt.tensor.export_archive())
+        cxt.result = tc.tensor.Sparse.import_archive(cxt.archive)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.I32, shape, [[[1, 3], 4]]))
+
+    def testLogicalNot(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros([2, 3], tc.Bool)
+        cxt.result = tc.After(cxt.tensor[0, 1].write(True), cxt.tensor.logical_not())
+
+        expected = [True, False, True, True, True, True]
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_dense(tc.Bool, [2, 3], expected))
+
+    def testWriteZero(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros([2, 5], tc.I32)
+        cxt.write_one = cxt.tensor[0, 0].write(1)
+        cxt.nnz_before = tc.After(cxt.write_one, cxt.tensor.nnz())
+        cxt.write_two = tc.After(cxt.nnz_before, cxt.tensor[1, 1].write(2))
+        cxt.nnz_filled = tc.After(cxt.write_two, cxt.tensor.nnz())
+        cxt.write_zero = tc.After(cxt.nnz_filled, cxt.tensor[1, 1].write(0))
+        cxt.result = tc.After(cxt.write_zero, [
+            cxt.nnz_before, cxt.nnz_filled, cxt.tensor.nnz(), cxt.tensor.any()])
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [1, 2, 1, True])
+
+    def testFilledSorted(self):
+        coords = [[1, 2], [0, 3], [1, 0], [0, 1]]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros([2, 4], tc.I32)
+        cxt.writes = [cxt.tensor[coord].write(1) for coord in coords]
+        cxt.result = tc.After(cxt.writes, cxt.tensor)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.I32, [2, 4], [[coord, 1] for coord in sorted(coords)]))
+
+    def testAllWithGap(self):
+        def all_filled(coords):
+            cxt = tc.Context()
+            cxt.tensor = tc.tensor.Sparse.zeros([2, 2], tc.I32)
+            cxt.writes = [cxt.tensor[coord].write(1) for coord in coords]
+            cxt.result = tc.After(cxt.writes, cxt.tensor.all())
+            return self.host.post(ENDPOINT, cxt)
+
+        self.assertTrue(all_filled([[0, 0], [0, 1], [1, 0], [1, 1]]))
+        self.assertFalse(all_filled([[0, 0], [0, 1], [1, 0]]))
+        self.assertFalse(all_filled([[0, 0], [1, 0], [1, 1]]))
+
+    def testOneHot(self):
+        cxt = tc.Context()
+        cxt.labels = tc.tensor.Dense.load([3], tc.I32, [0, 2, 1])
+        cxt.result = cxt.labels.one_hot(3)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.I32, [3, 3], [[[0, 0], 1], [[1, 2], 1], [[2, 1], 1]]))
+
+    def testFlatten(self):
+        coords = [[0, 1], [1, 0], [1, 2]]
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.load([2, 3], tc.I32, [[coord, 1] for coord in coords])
+        cxt.result = cxt.tensor.flatten()
+
+        actual = self.host.post(ENDPOINT, cxt)
+        expected = [[[row * 3 + col], 1] for row, col in coords]
+        self.assertEqual(actual, expect_sparse(tc.I32, [6], expected))
+
+    def testSqueeze(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.load([1, 3, 1], tc.I32, [[[0, 2, 0], 1]])
+        cxt.all = cxt.tensor.squeeze()
+        cxt.first = cxt.tensor.squeeze(0)
+        cxt.result = [cxt.all, cxt.first]
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [
+            expect_sparse(tc.I32, [3], [[[2], 1]]),
+            expect_sparse(tc.I32, [3, 1], [[[2, 0], 1]]),
+        ])
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros([1, 3, 1])
+        cxt.result = cxt.tensor.squeeze(1)
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, cxt))
+
+    def testRoll(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.load([1, 4], tc.I32, [[[0, 0], 1], [[0, 3], 2]])
+        cxt.result = [cxt.tensor.roll(1, 1), cxt.tensor.roll(-1, 1)]
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [
+            expect_sparse(tc.I32, [1, 4], [[[0, 0], 2], [[0, 1], 1]]),
+            expect_sparse(tc.I32, [1, 4], [[[0, 2], 2], [[0, 3], 1]]),
+        ])
+
+    def testExpUnsupported(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros([2, 3])
+        cxt.result = cxt.tensor.exp()
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testZeroDimensional(self):
+        cxt = tc.Context()
+        cxt.scalar = tc.tensor.Sparse.zeros([], tc.I32)
+        cxt.result = tc.After(cxt.scalar.write(3), cxt.scalar)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.I32, [], [[[], 3]]))
+
+        for reduce in ["sum", "product"]:
+            cxt = tc.Context()
+            cxt.scalar = tc.tensor.Sparse.zeros([], tc.I32)
+            cxt.result = tc.After(cxt.scalar.write(3), getattr(cxt.scalar, reduce)())
+
+            actual = self.host.post(ENDPOINT, cxt)
+            self.assertEqual(actual, 3)
+
     def testWriteAndSlice(self):
         shape = [2, 5]
 
@@ -223,6 +804,64 @@ class SparseTests(unittest.TestCase):
         expected = expect_sparse(tc.F32, shape, [[[0, 2], 1], [[0, 3], 1], [[1, 2], 1], [[1, 3], 1]])
         self.assertEqual(actual, expected)
 
+    def testCastSaturating(self):
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros([2, 2], tc.F32)
+        cxt.write = [cxt.tensor[0, 0].write(1000), cxt.tensor[1, 1].write(3)]
+        cxt.result = tc.After(cxt.write, cxt.tensor.cast(tc.U8, saturate=True))
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.U8, [2, 2], [[[0, 0], 255], [[1, 1], 3]]))
+
+    def testWriteDenseRegion(self):
+        shape = [100, 100]
+        region = (np.arange(60 * 100) % 7).reshape([60, 100])
+
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros(shape, tc.I32)
+        cxt.region = tc.tensor.Dense.load(region.shape, tc.I32, region.flatten().tolist())
+        cxt.fill = [cxt.tensor[0, 0].write(1), cxt.tensor[99, 99].write(2)]
+        cxt.write = tc.After(cxt.fill, cxt.tensor[20:80].write(cxt.region))
+        cxt.result = tc.After(cxt.write, [cxt.tensor, cxt.tensor.nnz()])
+
+        expected = np.zeros(shape, dtype=np.int32)
+        expected[0, 0] = 1
+        expected[99, 99] = 2
+        expected[20:80] = region
+
+        actual, nnz = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.I32, shape, expected))
+        self.assertEqual(nnz, np.count_nonzero(expected))
+
+    def testWriteCount(self):
+        shape = [4, 50]
+        region = (np.arange(2 * 50) % 7 + 1).reshape([2, 50])
+
+        # write the region all at once
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros(shape, tc.I32)
+        cxt.region = tc.tensor.Dense.load(region.shape, tc.I32, region.flatten().tolist())
+        cxt.result = tc.After(cxt.tensor[1:3].write(cxt.region), cxt.tensor.nnz())
+
+        before = block_writes(self.host)
+        self.assertEqual(self.host.post(ENDPOINT, cxt), region.size)
+        grouped = block_writes(self.host) - before
+
+        # write the same region one entry at a time
+        cxt = tc.Context()
+        cxt.tensor = tc.tensor.Sparse.zeros(shape, tc.I32)
+        cxt.writes = [
+            cxt.tensor[1 + x, y].write(int(region[x, y]))
+            for x, y in itertools.product(range(2), range(50))]
+
+        cxt.result = tc.After(cxt.writes, cxt.tensor.nnz())
+
+        before = block_writes(self.host)
+        self.assertEqual(self.host.post(ENDPOINT, cxt), region.size)
+        per_entry = block_writes(self.host) - before
+
+        self.assertLess(grouped * 2, per_entry)
+
     def testAdd(self):
         shape = [5, 2, 3]
 
@@ -289,6 +928,16 @@ class SparseTests(unittest.TestCase):
         expected = expect_sparse(tc.F32, shape, expected)
         self.assertEqual(actual, expected)
 
+    def testMaximum(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Sparse.load([2, 3], tc.I32, [[[0, 0], -2], [[1, 1], 3]])
+        cxt.b = tc.tensor.Sparse.load([2, 3], tc.I32, [[[0, 0], -1], [[1, 2], -5]])
+        cxt.result = cxt.a.maximum(cxt.b)
+
+        # the maximum of a negative value and an implicit zero is zero, so it's not filled
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.I32, [2, 3], [[[0, 0], -1], [[1, 1], 3]]))
+
     def testSub(self):
         shape = [3, 5, 2]
 
@@ -339,6 +988,26 @@ class SparseTests(unittest.TestCase):
         expected = expected.prod(axis)
         self.assertEqual(actual, expect_sparse(tc.I32, [2, 4, 5], expected))
 
+    def testMeanAndVar(self):
+        shape = [2, 4]
+
+        # the implicit zeros must count toward the denominator
+        for name, expected in [("mean", 2.), ("var", 4.)]:
+            cxt = tc.Context()
+            cxt.big = tc.tensor.Sparse.zeros(shape, tc.I32)
+            cxt.result = tc.After(cxt.big[0, 0:2].write(4), getattr(cxt.big, name)(1))
+
+            actual = self.host.post(ENDPOINT, cxt)
+            self.assertEqual(actual, expect_sparse(tc.F64, [2], [[[0], expected]]))
+
+        for name, expected in [("mean", 1.), ("var", 3.)]:
+            cxt = tc.Context()
+            cxt.big = tc.tensor.Sparse.zeros(shape, tc.I32)
+            cxt.result = tc.After(cxt.big[0, 0:2].write(4), getattr(cxt.big, name)())
+
+            actual = self.host.post(ENDPOINT, cxt)
+            self.assertAlmostEqual(actual, expected)
+
     def testSliceAndBroadcast(self):
         self.maxDiff = None
         data = [
@@ -363,6 +1032,139 @@ class SparseTests(unittest.TestCase):
 
         self.assertEqual(actual, expect_sparse(tc.F32, expected.shape, expected))
 
+    def testClipByNorm(self):
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Sparse.load([6], tc.F64, [[[1], 3.], [[4], 4.]])
+        cxt.result = cxt.x.clip_by_norm(2.5)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.F64, [6], [[[1], 1.5], [[4], 2.]]))
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Sparse.load([6], tc.F64, [[[1], 3.], [[4], 4.]])
+        cxt.result = cxt.x.clip_by_norm(5)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, expect_sparse(tc.F64, [6], [[[1], 3.], [[4], 4.]]))
+
+    def testDot(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Sparse.load([5], tc.I32, [[[0], 2], [[2], 3], [[4], 1]])
+        cxt.b = tc.tensor.Sparse.load([5], tc.I32, [[[1], 7], [[2], 4], [[4], -2]])
+        cxt.result = cxt.a.dot(cxt.b)
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, 10)
+
+    def testConcatenate(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Sparse.load([2, 2], tc.I32, [[[0, 1], 1], [[1, 0], 2]])
+        cxt.b = tc.tensor.Sparse.load([2, 2], tc.I32, [[[1, 1], 3]])
+        cxt.concatenated = tc.tensor.Sparse.concatenate([cxt.a, cxt.b], 0)
+        cxt.result = [cxt.concatenated, cxt.concatenated.nnz(), cxt.a.nnz(), cxt.b.nnz()]
+
+        actual, nnz, a_nnz, b_nnz = self.host.post(ENDPOINT, cxt)
+
+        expected = [[[0, 1], 1], [[1, 0], 2], [[3, 1], 3]]
+        self.assertEqual(actual, expect_sparse(tc.I32, [4, 2], expected))
+        self.assertEqual(nnz, a_nnz + b_nnz)
+
+    def testMatmul(self):
+        a = np.zeros([4, 3], dtype=np.int32)
+        a[0, 1] = 2
+        a[2, 0] = -1
+        a[2, 2] = 4
+        a[3, 1] = 1
+
+        b = np.zeros([3, 5], dtype=np.int32)
+        b[0, 4] = 4
+        b[1, 0] = 5
+        b[1, 3] = -2
+        b[2, 4] = 1
+
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Sparse.load([4, 3], tc.I32, nparray_to_sparse(a, tc.I32))
+        cxt.b = tc.tensor.Sparse.load([3, 5], tc.I32, nparray_to_sparse(b, tc.I32))
+        cxt.product = tc.tensor.Sparse.matmul(cxt.a, cxt.b)
+        cxt.result = [cxt.product, cxt.product.nnz()]
+
+        actual, nnz = self.host.post(ENDPOINT, cxt)
+
+        # row 2 of the product cancels out to zero in column 4, which must not be filled
+        expected = np.matmul(a, b)
+        self.assertEqual(actual, expect_sparse(tc.I32, [4, 5], expected))
+        self.assertEqual(nnz, np.count_nonzero(expected))
+
+    def testOuter(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Sparse.load([4], tc.I32, [[[1], 2], [[3], 3]])
+        cxt.b = tc.tensor.Sparse.load([3], tc.I32, [[[0], 5], [[2], -1]])
+        cxt.result = tc.tensor.Sparse.outer(cxt.a, cxt.b)
+
+        actual = self.host.post(ENDPOINT, cxt)
+
+        expected = [
+            [[1, 0], 10],
+            [[1, 2], -2],
+            [[3, 0], 15],
+            [[3, 2], -3],
+        ]
+
+        self.assertEqual(actual, expect_sparse(tc.I32, [4, 3], expected))
+
+    def testSplit(self):
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Sparse.load([4, 2], tc.I32, [[[0, 1], 1], [[1, 0], 2], [[2, 1], 3], [[3, 0], 4]])
+        cxt.result = cxt.x.split([2, 2])
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [
+            expect_sparse(tc.I32, [2, 2], [[[0, 1], 1], [[1, 0], 2]]),
+            expect_sparse(tc.I32, [2, 2], [[[0, 1], 3], [[1, 0], 4]]),
+        ])
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Sparse.zeros([4, 2], tc.I32)
+        cxt.result = cxt.x.split([2, 3])
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
+    def testRepeatInterleave(self):
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Sparse.load([1, 3], tc.I32, [[[0, 0], 1], [[0, 2], 3]])
+        cxt.result = [cxt.x.repeat_interleave(2, 1), cxt.x.repeat_interleave(2, 1).nnz()]
+
+        actual, nnz = self.host.post(ENDPOINT, cxt)
+        expected = [[[0, 0], 1], [[0, 1], 1], [[0, 4], 3], [[0, 5], 3]]
+        self.assertEqual(actual, expect_sparse(tc.I32, [1, 6], expected))
+        self.assertEqual(nnz, 4)
+
+    def testTableRoundTrip(self):
+        data = [[[0, 1], 1], [[1, 0], 2], [[2, 1], 3], [[3, 0], 4]]
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Sparse.load([4, 2], tc.I32, data)
+        cxt.table = cxt.x.as_table()
+        cxt.filtered = cxt.table.where({"0": slice(1, 3)})
+        cxt.result = [
+            cxt.table.count(),
+            tc.tensor.Sparse.from_table(cxt.table, [4, 2], tc.I32),
+            tc.tensor.Sparse.from_table(cxt.filtered, [4, 2], tc.I32),
+        ]
+
+        count, round_trip, filtered = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(count, len(data))
+        self.assertEqual(round_trip, expect_sparse(tc.I32, [4, 2], data))
+        self.assertEqual(filtered, expect_sparse(tc.I32, [4, 2], data[1:3]))
+
+        cxt = tc.Context()
+        cxt.x = tc.tensor.Sparse.load([4, 2], tc.I32, data)
+        cxt.result = tc.tensor.Sparse.from_table(cxt.x.as_table(), [4, 2, 1], tc.I32)
+
+        with self.assertRaises(tc.error.BadRequest):
+            self.host.post(ENDPOINT, cxt)
+
     @classmethod
     def tearDownClass(cls):
         cls.host.stop()
@@ -373,6 +1175,20 @@ class TensorTests(unittest.TestCase):
     def setUpClass(cls):
         cls.host = start_host("test_tensor", cache_size="1G")
 
+    def testEquals(self):
+        cxt = tc.Context()
+        cxt.a = tc.tensor.Dense.load([2, 2], tc.F64, [1., 2., 3., 4.])
+        cxt.b = tc.tensor.Dense.load([2, 2], tc.F64, [1., 2., 3., 4.])
+        cxt.c = tc.tensor.Dense.load([2, 2], tc.F64, [1., 2., 3., 4.1])
+        cxt.d = tc.tensor.Dense.load([2, 2], tc.I32, [1, 0, 0, 4])
+        cxt.e = tc.tensor.Sparse.zeros([2, 2], tc.I32)
+        cxt.result = tc.After(
+            [cxt.e[0, 0].write(1), cxt.e[1, 1].write(4)],
+            [cxt.a.equals(cxt.b), cxt.a.equals(cxt.c), cxt.d.equals(cxt.e), cxt.a.equals(cxt.d)])
+
+        actual = self.host.post(ENDPOINT, cxt)
+        self.assertEqual(actual, [True, False, True, False])
+
     def testAdd(self):
         cxt = tc.Context()
         cxt.dense = tc.tensor.Dense.arange([3, 5, 2], 0, 30)
@@ -494,6 +1310,12 @@ class ChainTests(PersistenceTest, unittest.TestCase):
             self.assertEqual(actual, eq)
 
 
+def block_writes(host):
+    metrics = host.metrics()
+    match = re.search("^tc_block_writes_total ([0-9]+)$", metrics, re.MULTILINE)
+    return int(match.group(1))
+
+
 def expect_dense(dtype, shape, flat):
     return {
         str(tc.uri(tc.tensor.Dense)): [
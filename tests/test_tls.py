@@ -0,0 +1,40 @@
+import os
+import requests
+import subprocess
+import tempfile
+import unittest
+import urllib3
+
+from testutils import PORT, start_host
+
+
+class TLSTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.tmp = tempfile.TemporaryDirectory()
+        cls.cert = os.path.join(cls.tmp.name, "cert.pem")
+        cls.key = os.path.join(cls.tmp.name, "key.pem")
+
+        subprocess.run([
+            "openssl", "req", "-x509", "-newkey", "rsa:2048", "-nodes", "-days", "1",
+            "-subj", "/CN=127.0.0.1", "-keyout", cls.key, "-out", cls.cert,
+        ], check=True)
+
+        cls.host = start_host("test_tls", tls_cert=cls.cert, tls_key=cls.key)
+
+    def testRequest(self):
+        # the certificate is self-signed, so it can't be verified
+        urllib3.disable_warnings(urllib3.exceptions.InsecureRequestWarning)
+
+        response = requests.get(f"https://127.0.0.1:{PORT}/metrics", verify=False)
+        self.assertEqual(response.status_code, 200)
+        self.assertIn("tc_requests_total", response.json())
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+        cls.tmp.cleanup()
+
+
+if __name__ == "__main__":
+    unittest.main()
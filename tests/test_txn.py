@@ -0,0 +1,55 @@
+import tinychain as tc
+import unittest
+
+from testutils import start_host
+
+
+ENDPOINT = "/app/example/held"
+
+
+class HeldTxnTest(tc.Cluster):
+    __uri__ = tc.URI(ENDPOINT)
+
+    def _configure(self):
+        self.first = tc.chain.Sync(tc.Number(0))
+        self.second = tc.chain.Sync(tc.Number(0))
+
+
+class HeldTxnTests(unittest.TestCase):
+    def setUp(self):
+        self.host = start_host("test_held_txn", [HeldTxnTest])
+
+    def testCommit(self):
+        txn_id = tc.host.new_txn_id()
+        self.host.put(ENDPOINT + "/first", None, 2, txn_id=txn_id)
+        self.host.put(ENDPOINT + "/second", None, 3, txn_id=txn_id)
+
+        self.host.commit(txn_id)
+
+        self.host.stop()
+        self.host.start()
+
+        self.assertEqual(self.host.get(ENDPOINT + "/first"), 2)
+        self.assertEqual(self.host.get(ENDPOINT + "/second"), 3)
+
+    def testAbort(self):
+        txn_id = tc.host.new_txn_id()
+        self.host.put(ENDPOINT + "/first", None, 2, txn_id=txn_id)
+        self.host.put(ENDPOINT + "/second", None, 3, txn_id=txn_id)
+        self.host.abort(txn_id)
+
+        self.assertEqual(self.host.get(ENDPOINT + "/first"), 0)
+        self.assertEqual(self.host.get(ENDPOINT + "/second"), 0)
+
+        self.assertRaises(tc.error.NotFound, lambda: self.host.commit(txn_id))
+
+    def testCommitUnknown(self):
+        txn_id = tc.host.new_txn_id()
+        self.assertRaises(tc.error.NotFound, lambda: self.host.commit(txn_id))
+
+    def tearDown(self):
+        self.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
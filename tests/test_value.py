@@ -0,0 +1,69 @@
+import tinychain as tc
+import unittest
+
+from testutils import start_host
+
+
+ENDPOINT = "/transact/hypothetical"
+
+
+class StringTests(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.host = start_host("test_value")
+
+    def testConcat(self):
+        cxt = tc.Context()
+        cxt.greeting = tc.String("hello")
+        cxt.result = cxt.greeting.concat(", world")
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), "hello, world")
+
+    def testSplit(self):
+        cxt = tc.Context()
+        cxt.path = tc.String("a/b/c")
+        cxt.result = cxt.path.split("/")
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), ["a", "b", "c"])
+
+    def testSplitEmptySeparator(self):
+        cxt = tc.Context()
+        cxt.path = tc.String("a/b/c")
+        cxt.result = cxt.path.split("")
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, cxt))
+
+    def testErrorMessage(self):
+        cxt = tc.Context()
+        cxt.path = tc.String("a/b/c")
+        cxt.result = cxt.path.split("")
+
+        with self.assertRaises(tc.error.BadRequest) as context:
+            self.host.post(ENDPOINT, cxt)
+
+        error = context.exception
+        self.assertEqual(error.code, "/error/bad_request")
+        self.assertEqual(error.status, 400)
+        self.assertTrue(error.message.endswith("expected a non-empty String separator, not: "))
+
+    def testFormat(self):
+        cxt = tc.Context()
+        cxt.template = tc.String("{}-{}")
+        cxt.result = cxt.template.format(["a", "b"])
+
+        self.assertEqual(self.host.post(ENDPOINT, cxt), "a-b")
+
+    def testFormatWrongArgs(self):
+        cxt = tc.Context()
+        cxt.template = tc.String("{}-{}")
+        cxt.result = cxt.template.format(["a"])
+
+        self.assertRaises(tc.error.BadRequest, lambda: self.host.post(ENDPOINT, cxt))
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.host.stop()
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -8,7 +8,7 @@ TC_PATH = "host/target/debug/tinychain"
 PORT = 8702
 
 
-def start_host(name, clusters=[], overwrite=True, host_uri=None, cache_size="5K", wait_time=1):
+def start_host(name, clusters=[], overwrite=True, host_uri=None, cache_size="5K", wait_time=1, max_op_steps=None, **flags):
     port = PORT
     if host_uri is not None and host_uri.port():
         port = host_uri.port()
@@ -35,7 +35,9 @@ def start_host(name, clusters=[], overwrite=True, host_uri=None, cache_size="5K"
         port=port,
         log_level="debug",
         cache_size=cache_size,
-        force_create=True)
+        max_op_steps=max_op_steps,
+        force_create=True,
+        **flags)
 
     print(f"start host on port {port}")
     host.start(wait_time=wait_time)
@@ -44,6 +46,7 @@ def start_host(name, clusters=[], overwrite=True, host_uri=None, cache_size="5K"
 
 class PersistenceTest(object):
     CACHE_SIZE = "5K"
+    FLAGS = {}
     NUM_HOSTS = 4
     NAME = "persistence"
 
@@ -68,7 +71,8 @@ class PersistenceTest(object):
         for i in range(self.NUM_HOSTS):
             port = PORT + i
             host_uri = f"http://127.0.0.1:{port}" + tc.uri(cluster).path()
-            host = start_host(f"test_{name}_{i}", [cluster], host_uri=tc.URI(host_uri), cache_size=self.CACHE_SIZE)
+            host = start_host(
+                f"test_{name}_{i}", [cluster], host_uri=tc.URI(host_uri), cache_size=self.CACHE_SIZE, **self.FLAGS)
             hosts.append(host)
             printlines(5)
 